@@ -23,15 +23,24 @@ pub struct FileManager {
     file_map: file_map::FileMap,
     id_to_path: HashMap<FileId, VirtualPath>,
     path_to_id: HashMap<VirtualPath, FileId>,
+    /// Sources read instead of the files at their paths, such as the unsaved documents of an
+    /// editor, keyed by the canonical path of each file
+    overlays: HashMap<PathBuf, String>,
 }
 
 impl FileManager {
+    /// Creates a file manager which reads the given sources instead of the files at their
+    /// paths. Each path should be canonical, unless the file does not exist on disk.
+    pub fn with_overlays(overlays: HashMap<PathBuf, String>) -> Self {
+        FileManager { overlays, ..Default::default() }
+    }
+
     // XXX: Maybe use a AsRef<Path> here, for API ergonomics
     pub fn add_file(&mut self, path_to_file: &Path) -> Option<FileId> {
         // Handle both relative file paths and std/lib virtual paths.
         let base = Path::new(".").canonicalize().expect("Base path canonicalize failed");
         let res = path_to_file.canonicalize().unwrap_or_else(|_| path_to_file.to_path_buf());
-        let resolved_path = res.strip_prefix(base).unwrap_or(&res);
+        let resolved_path = res.strip_prefix(&base).unwrap_or(&res);
 
         // Check that the resolved path already exists in the file map, if it is, we return it.
         let path_to_file = virtualize_path(resolved_path);
@@ -39,8 +48,13 @@ impl FileManager {
             return Some(*file_id);
         }
 
-        // Otherwise we add the file
-        let source = file_reader::read_file_to_string(resolved_path).ok()?;
+        // Otherwise we add the file, preferring its overlay. Paths which could not be
+        // canonicalized are looked up relative to the base.
+        let overlay = self.overlays.get(&res).or_else(|| self.overlays.get(&base.join(&res)));
+        let source = match overlay {
+            Some(source) => source.clone(),
+            None => file_reader::read_file_to_string(resolved_path).ok()?,
+        };
         let file_id = self.file_map.add_file(resolved_path.to_path_buf().into(), source);
         self.register_path(file_id, path_to_file);
         Some(file_id)
@@ -74,8 +88,13 @@ impl FileManager {
 
         // First we attempt to look at `base/anchor/mod_name.nr` (child of the anchor)
         candidate_files.push(anchor_path.join(format!("{mod_name}.{FILE_EXTENSION}")));
+        // or at `base/anchor/mod_name/mod.nr` (child directory of the anchor)
+        candidate_files.push(anchor_path.join(mod_name).join(format!("mod.{FILE_EXTENSION}")));
         // If not found, we attempt to look at `base/mod_name.nr` (sibling of the anchor)
         candidate_files.push(anchor_dir.join(format!("{mod_name}.{FILE_EXTENSION}")));
+        // or at `base/mod_name/mod.nr` (sibling directory of the anchor). This is also where
+        // the submodules of an anchor which is itself a `mod.nr` file are found.
+        candidate_files.push(anchor_dir.join(mod_name).join(format!("mod.{FILE_EXTENSION}")));
 
         for candidate in candidate_files.iter() {
             if let Some(file_id) = self.add_file(candidate) {
@@ -170,6 +189,55 @@ mod tests {
         fm.resolve_path(sub_dir_file_id, "foo").unwrap();
     }
 
+    #[test]
+    fn path_resolve_directory_module() {
+        let mut fm = FileManager::default();
+
+        // Create a lib.nr file at the root along with a sub directory
+        // holding the module's mod.nr file and one of its submodules.
+        // we now have:
+        // - dir/lib.nr
+        // - dir/sub_dir/mod.nr
+        // - dir/sub_dir/foo.nr
+        let dir = tempdir().unwrap();
+        let file_path = dummy_file_path(&dir, "lib.nr");
+        let sub_dir = TempDir::new_in(&dir).unwrap();
+        let sub_dir_name = sub_dir.path().file_name().unwrap().to_str().unwrap();
+        let _mod_file_path = dummy_file_path(&sub_dir, "mod.nr");
+        let _foo_file_path = dummy_file_path(&sub_dir, "foo.nr");
+
+        let file_id = fm.add_file(&file_path).unwrap();
+
+        // The sub directory's mod.nr file is the module itself
+        let sub_dir_file_id = fm.resolve_path(file_id, sub_dir_name).unwrap();
+        let sub_dir_path = fm.path(sub_dir_file_id).to_path_buf();
+        assert!(sub_dir_path.ends_with("mod"));
+
+        // and its submodules are its siblings within the sub directory
+        let foo_file_id = fm.resolve_path(sub_dir_file_id, "foo").unwrap();
+        assert_eq!(fm.path(foo_file_id).parent(), sub_dir_path.parent());
+    }
+
+    #[test]
+    fn overlays_are_read_instead_of_files() {
+        let dir = tempdir().unwrap();
+        let file_path = dummy_file_path(&dir, "lib.nr");
+        let unsaved_path = dir.path().canonicalize().unwrap().join("unsaved.nr");
+
+        let overlays = HashMap::from([
+            (file_path.canonicalize().unwrap(), "fn foo() {}".to_owned()),
+            (unsaved_path.clone(), "fn bar() {}".to_owned()),
+        ]);
+        let mut fm = FileManager::with_overlays(overlays);
+
+        let file_id = fm.add_file(&file_path).unwrap();
+        assert_eq!(fm.fetch_file(file_id).source(), "fn foo() {}");
+
+        // Files which are not yet saved to disk can still be resolved as modules
+        let unsaved_id = fm.resolve_path(file_id, "unsaved").unwrap();
+        assert_eq!(fm.fetch_file(unsaved_id).source(), "fn bar() {}");
+    }
+
     /// Tests that two identical files that have different paths are treated as the same file
     /// e.g. if we start in the dir ./src and have a file ../../foo.nr
     /// that should be treated as the same file as ../ starting in ./
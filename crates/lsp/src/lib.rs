@@ -1,54 +1,269 @@
 use std::{
+    collections::{HashMap, HashSet},
     future::Future,
     ops::{self, ControlFlow},
+    path::{Path, PathBuf},
     pin::Pin,
     task::{self, Poll},
+    time::SystemTime,
 };
 
 use async_lsp::{
-    router::Router, AnyEvent, AnyNotification, AnyRequest, ClientSocket, Error, LanguageClient,
-    LspService, ResponseError,
+    router::Router, AnyEvent, AnyNotification, AnyRequest, ClientSocket, Error, ErrorCode,
+    LanguageClient, LspService, ResponseError,
 };
 use codespan_reporting::files;
+use fm::{FileId, FileManager};
 use lsp_types::{
-    notification, request, CodeLens, CodeLensOptions, CodeLensParams, Command, Diagnostic,
+    notification, request, CodeLens, CodeLensOptions, CodeLensParams, Command, CompletionItem,
+    CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse, Diagnostic,
     DiagnosticSeverity, DidChangeConfigurationParams, DidChangeTextDocumentParams,
     DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
-    InitializeParams, InitializeResult, InitializedParams, Position, PublishDiagnosticsParams,
-    Range, ServerCapabilities, TextDocumentSyncOptions,
+    DocumentSymbolParams, DocumentSymbolResponse, GotoDefinitionParams, GotoDefinitionResponse,
+    Hover, HoverContents, HoverParams, HoverProviderCapability, InitializeParams, InitializeResult,
+    InitializedParams, InlayHint, InlayHintKind, InlayHintLabel, InlayHintParams, MarkupContent,
+    MarkupKind, NumberOrString, OneOf, Position, PublishDiagnosticsParams, Range, ReferenceParams,
+    RenameParams, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+    SemanticTokensFullOptions, SemanticTokensLegend, SemanticTokensOptions, SemanticTokensParams,
+    SemanticTokensResult, ServerCapabilities, TextDocumentPositionParams, TextDocumentSyncKind,
+    TextDocumentSyncOptions, TextEdit, Url, WorkspaceEdit,
+};
+use noirc_driver::{check_crate, create_local_crate, DebugInfo};
+use noirc_errors::{CustomDiagnostic, DiagnosticKind, FileDiagnostic, Location};
+use noirc_frontend::{
+    graph::{CrateGraph, CrateType, LOCAL_CRATE},
+    hir::{
+        completion::{attribute_completions, Completion, CompletionKind},
+        highlight::{Highlight, HighlightKind},
+        inlay_hints, Context,
+    },
+    node_interner::FuncId,
 };
-use noirc_driver::{check_crate, create_local_crate};
-use noirc_errors::{DiagnosticKind, FileDiagnostic};
-use noirc_frontend::{graph::CrateType, hir::Context};
 use serde_json::Value as JsonValue;
 use tower::Service;
 
+mod symbols;
+
 const TEST_COMMAND: &str = "nargo.test";
-const TEST_CODELENS_TITLE: &str = "▶\u{fe0e} Run Test";
+const TEST_CODELENS_TITLE: &str = "▶\u{fe0e} Run test";
+
+/// The types of semantic tokens, in the order of the legend the `token_type` of a token indexes
+const SEMANTIC_TOKEN_TYPES: [SemanticTokenType; 10] = [
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::STRUCT,
+    SemanticTokenType::ENUM,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::INTERFACE,
+    SemanticTokenType::TYPE_PARAMETER,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::METHOD,
+];
+
+/// The modifiers of semantic tokens, in the order of the bits of a token's modifier bitset
+const SEMANTIC_TOKEN_MODIFIERS: [SemanticTokenModifier; 5] = [
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::READONLY,
+    SemanticTokenModifier::new("mutable"),
+    SemanticTokenModifier::new("unconstrained"),
+    SemanticTokenModifier::new("oracle"),
+];
+
+/// The share of the opcodes of a build a function must generate for its opcode count to be
+/// shown above it. The count is always shown above `main`.
+const HEAVY_FUNCTION_SHARE: f64 = 0.1;
+
+/// Builds the context a file is checked in, with the crate the file belongs to as the local
+/// crate. Resolving the file's package lets requests see into the package's dependencies.
+///
+/// The sources of the documents open in the editor are given by their paths, and are read
+/// instead of the files on disk, which may not have been saved since they were edited. The
+/// context is returned along with the paths of the manifests read to build it, so that a cached
+/// context is built again once one of them is modified.
+pub type ContextResolver =
+    Box<dyn FnMut(&Path, &HashMap<PathBuf, String>) -> (Context, Vec<PathBuf>)>;
+
+/// Finds the last build of the package containing the given file, returning the number of
+/// opcodes of its circuit along with the location each opcode was generated from.
+pub type BuildResolver = fn(&Path) -> Option<(usize, DebugInfo)>;
+
+/// Builds a context with the given file as the root of the local crate, for files which are not
+/// part of a package.
+pub fn local_crate_context(file_path: &Path, open_documents: &HashMap<PathBuf, String>) -> Context {
+    let file_manager = FileManager::with_overlays(open_documents.clone());
+    let mut context = Context::new(file_manager, CrateGraph::default());
+    create_local_crate(&mut context, file_path, CrateType::Binary);
+    context
+}
+
+/// Resolves the context of each file with [local_crate_context], for files which are not part
+/// of a package.
+pub fn local_crate_resolver() -> ContextResolver {
+    Box::new(|file_path: &Path, open_documents: &HashMap<PathBuf, String>| {
+        (local_crate_context(file_path, open_documents), Vec::new())
+    })
+}
+
+/// Finds no build for any file, for files which are not part of a package.
+pub fn no_build(_file_path: &Path) -> Option<(usize, DebugInfo)> {
+    None
+}
 
 // State for the LSP gets implemented on this struct and is internal to the implementation
 struct LspState {
     client: ClientSocket,
+    resolve_context: ContextResolver,
+    resolve_build: BuildResolver,
+    /// The packages checked when saving one of their files, keyed by the root file of each
+    checked_packages: HashMap<PathBuf, CheckedPackage>,
+    /// The text of each document open in the editor, keyed by its path. The whole text is sent
+    /// on each change, so it is kept up to date without applying edits.
+    open_documents: HashMap<PathBuf, String>,
+    /// The checked contexts requests about a document are answered from, keyed by the path of
+    /// the document, which are reused until a file they were built from changes
+    checked_contexts: HashMap<PathBuf, CheckedContext>,
+}
+
+/// The files of a checked package, along with those its diagnostics were published for.
+#[derive(Default)]
+struct CheckedPackage {
+    /// Every file of the package and of its dependencies, as saving any of them may change
+    /// the diagnostics of the package
+    files: HashSet<Url>,
+    /// The files with diagnostics, which are cleared once a later check finds none in them
+    published: HashSet<Url>,
+}
+
+/// A checked context along with the version of each file it was built from.
+struct CheckedContext {
+    context: Context,
+    /// The id of the document the context was checked for
+    file_id: FileId,
+    /// The index of each source file of the context, along with its canonical path and version
+    sources: Vec<(usize, PathBuf, FileVersion)>,
+    /// The manifests read to build the context, along with the time each was last modified
+    manifests: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+/// The version of a source file a context was built from
+#[derive(Debug, PartialEq, Eq)]
+enum FileVersion {
+    /// The file was open in the editor, so its source is that of the document
+    Open,
+    /// The file was read from disk, when it was last modified at the given time
+    Saved(Option<SystemTime>),
+}
+
+impl CheckedContext {
+    fn new(
+        context: Context,
+        file_id: FileId,
+        manifests: Vec<PathBuf>,
+        open_documents: &HashMap<PathBuf, String>,
+    ) -> Self {
+        let files = context.file_manager.as_simple_files();
+        let sources = (0..)
+            .map_while(|index| Some((index, files.get(index).ok()?.name().to_string())))
+            .map(|(index, name)| {
+                // The files of the standard library are embedded rather than read from disk, so
+                // they are never modified
+                let path = PathBuf::from(name);
+                let path = path.canonicalize().unwrap_or(path);
+                let version = if open_documents.contains_key(&path) {
+                    FileVersion::Open
+                } else {
+                    FileVersion::Saved(modified_time(&path))
+                };
+                (index, path, version)
+            })
+            .collect();
+        let manifests = manifests
+            .into_iter()
+            .map(|manifest| {
+                let modified = modified_time(&manifest);
+                (manifest, modified)
+            })
+            .collect();
+        Self { context, file_id, sources, manifests }
+    }
+
+    /// Returns true unless a source file or manifest of the context changed since it was built,
+    /// whether it was edited in the editor or modified on disk.
+    fn is_current(&self, open_documents: &HashMap<PathBuf, String>) -> bool {
+        let files = self.context.file_manager.as_simple_files();
+        let sources_current =
+            self.sources.iter().all(|(index, path, version)| match open_documents.get(path) {
+                Some(text) => files.get(*index).map_or(false, |file| file.source() == text),
+                None => *version == FileVersion::Saved(modified_time(path)),
+            });
+        sources_current
+            && self
+                .manifests
+                .iter()
+                .all(|(manifest, modified)| modified_time(manifest) == *modified)
+    }
+}
+
+/// Returns the time the file at the given path was last modified, if it exists
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    path.metadata().and_then(|metadata| metadata.modified()).ok()
 }
 
 impl LspState {
-    fn new(client: &ClientSocket) -> Self {
-        Self { client: client.clone() }
+    fn new(
+        client: &ClientSocket,
+        resolve_context: ContextResolver,
+        resolve_build: BuildResolver,
+    ) -> Self {
+        Self {
+            client: client.clone(),
+            resolve_context,
+            resolve_build,
+            checked_packages: HashMap::new(),
+            open_documents: HashMap::new(),
+            checked_contexts: HashMap::new(),
+        }
+    }
+
+    /// Builds the context the file at the given path is checked in, reading the documents open
+    /// in the editor instead of the files on disk, along with the manifests read to build it.
+    fn resolve_context(&mut self, file_path: &Path) -> (Context, Vec<PathBuf>) {
+        (self.resolve_context)(file_path, &self.open_documents)
     }
 }
 
+/// Returns the path of a document, canonicalized to match the paths of the file manager. A
+/// document which has not yet been saved to disk keeps the path of its url.
+fn document_path(uri: &Url) -> Option<PathBuf> {
+    let path = uri.to_file_path().ok()?;
+    Some(path.canonicalize().unwrap_or(path))
+}
+
 pub struct NargoLspService {
     router: Router<LspState>,
 }
 
 impl NargoLspService {
-    pub fn new(client: &ClientSocket) -> Self {
-        let state = LspState::new(client);
+    pub fn new(
+        client: &ClientSocket,
+        resolve_context: ContextResolver,
+        resolve_build: BuildResolver,
+    ) -> Self {
+        let state = LspState::new(client, resolve_context, resolve_build);
         let mut router = Router::new(state);
         router
             .request::<request::Initialize, _>(on_initialize)
             .request::<request::Shutdown, _>(on_shutdown)
             .request::<request::CodeLensRequest, _>(on_code_lens_request)
+            .request::<request::GotoDefinition, _>(on_goto_definition_request)
+            .request::<request::HoverRequest, _>(on_hover_request)
+            .request::<request::Completion, _>(on_completion_request)
+            .request::<request::References, _>(on_references_request)
+            .request::<request::Rename, _>(on_rename_request)
+            .request::<request::DocumentSymbolRequest, _>(on_document_symbol_request)
+            .request::<request::SemanticTokensFullRequest, _>(on_semantic_tokens_request)
+            .request::<request::InlayHintRequest, _>(on_inlay_hint_request)
             .notification::<notification::Initialized>(on_initialized)
             .notification::<notification::DidChangeConfiguration>(on_did_change_configuration)
             .notification::<notification::DidOpenTextDocument>(on_did_open_text_document)
@@ -103,15 +318,41 @@ fn on_initialize(
     _params: InitializeParams,
 ) -> impl Future<Output = Result<InitializeResult, ResponseError>> {
     async {
-        let text_document_sync =
-            TextDocumentSyncOptions { save: Some(true.into()), ..Default::default() };
+        let text_document_sync = TextDocumentSyncOptions {
+            open_close: Some(true),
+            change: Some(TextDocumentSyncKind::FULL),
+            save: Some(true.into()),
+            ..Default::default()
+        };
 
         let code_lens = CodeLensOptions { resolve_provider: Some(false) };
 
+        let trigger_characters = vec![".".to_string(), ":".to_string(), "[".to_string()];
+        let completion = CompletionOptions {
+            trigger_characters: Some(trigger_characters),
+            ..Default::default()
+        };
+
+        let legend = SemanticTokensLegend {
+            token_types: SEMANTIC_TOKEN_TYPES.to_vec(),
+            token_modifiers: SEMANTIC_TOKEN_MODIFIERS.to_vec(),
+        };
+        let semantic_tokens = SemanticTokensOptions {
+            legend,
+            full: Some(SemanticTokensFullOptions::Bool(true)),
+            ..Default::default()
+        };
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(text_document_sync.into()),
                 code_lens_provider: Some(code_lens),
+                definition_provider: Some(OneOf::Left(true)),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                completion_provider: Some(completion),
+                references_provider: Some(OneOf::Left(true)),
+                rename_provider: Some(OneOf::Left(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
                 // Add capabilities before this spread when adding support for one
                 ..Default::default()
             },
@@ -128,57 +369,498 @@ fn on_shutdown(
 }
 
 fn on_code_lens_request(
-    _state: &mut LspState,
+    state: &mut LspState,
     params: CodeLensParams,
 ) -> impl Future<Output = Result<Option<Vec<CodeLens>>, ResponseError>> {
-    let file_path = &params.text_document.uri.to_file_path().unwrap();
-
-    let mut context = Context::default();
-
-    let crate_id = create_local_crate(&mut context, file_path, CrateType::Binary);
+    let lenses = code_lenses(state, &params.text_document.uri).unwrap_or_default();
 
-    // We ignore the warnings and errors produced by compilation for producing codelenses
-    // because we can still get the test functions even if compilation fails
-    let _ = check_crate(&mut context, false, false);
+    async move {
+        if lenses.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(lenses))
+        }
+    }
+}
 
-    let fm = &context.file_manager;
-    let files = fm.as_simple_files();
-    let tests = context.get_all_test_functions_in_crate_matching(&crate_id, "");
+/// Shows a lens running each test function of the document, and a lens showing the number of
+/// opcodes generated from `main` and from the costliest functions in the last build.
+fn code_lenses(state: &mut LspState, uri: &Url) -> Option<Vec<CodeLens>> {
+    let resolve_build = state.resolve_build;
+    let (context, file_id) = check_document(state, uri)?;
+    let files = context.file_manager.as_simple_files();
+    let lens_range = |location: Location| {
+        byte_span_to_range(files, file_id.as_usize(), location.span.into()).unwrap_or_default()
+    };
 
     let mut lenses: Vec<CodeLens> = vec![];
-    for func_id in tests {
+    for func_id in context.get_all_test_functions_in_crate_matching(&LOCAL_CRATE, "") {
         let location = context.function_meta(&func_id).name.location;
-        let file_id = location.file;
-        // TODO(#1681): This file_id never be 0 because the "path" where it maps is the directory, not a file
-        if file_id.as_usize() != 0 {
+        if location.file != file_id {
             continue;
         }
 
         let func_name = context.function_name(&func_id);
-
-        let range =
-            byte_span_to_range(files, file_id.as_usize(), location.span.into()).unwrap_or_default();
-
         let command = Command {
             title: TEST_CODELENS_TITLE.into(),
             command: TEST_COMMAND.into(),
-            arguments: Some(vec![func_name.into()]),
+            arguments: Some(vec![func_name.into(), "--exact".into()]),
         };
+        lenses.push(CodeLens { range: lens_range(location), command: command.into(), data: None });
+    }
 
-        let lens = CodeLens { range, command: command.into(), data: None };
+    let file_path = uri.to_file_path().ok()?;
+    if let Some((num_opcodes, debug_info)) = resolve_build(&file_path) {
+        for (func_id, count) in opcode_counts(context, file_id, num_opcodes, &debug_info) {
+            let location = context.function_meta(&func_id).name.location;
+            let plural = if count == 1 { "" } else { "s" };
+            let command = Command {
+                title: format!("{count} ACIR opcode{plural}"),
+                command: String::new(),
+                arguments: None,
+            };
+            lenses.push(CodeLens {
+                range: lens_range(location),
+                command: command.into(),
+                data: None,
+            });
+        }
+    }
+    Some(lenses)
+}
+
+/// Returns the number of opcodes of a build generated from `main` and from each function of
+/// the file generating a large share of them.
+///
+/// The opcodes of functions are inlined into `main`, so the count shown for `main` is that of
+/// the whole circuit, while the count of any other function is of the opcodes generated from
+/// code written within its body.
+fn opcode_counts(
+    context: &Context,
+    file_id: FileId,
+    num_opcodes: usize,
+    debug_info: &DebugInfo,
+) -> Vec<(FuncId, usize)> {
+    let file_name = match context.file_manager.as_simple_files().get(file_id.as_usize()) {
+        Ok(file) => file.name().to_string(),
+        Err(_) => return Vec::new(),
+    };
+    let debug_files: Vec<usize> = (0..debug_info.files.len())
+        .filter(|index| same_file(&debug_info.files[*index], &file_name))
+        .collect();
+    let main = context.get_main_function(&LOCAL_CRATE);
 
-        lenses.push(lens);
+    let mut counts = Vec::new();
+    for (func_id, location) in context.def_interner.functions_in_file(file_id) {
+        if Some(func_id) == main {
+            counts.push((func_id, num_opcodes));
+            continue;
+        }
+
+        let span = location.span;
+        let count = debug_info
+            .locations
+            .values()
+            .filter(|opcode| debug_files.contains(&opcode.file))
+            .filter(|opcode| span.start() <= opcode.start && opcode.end <= span.end())
+            .count();
+        if count > 0 && count as f64 >= num_opcodes as f64 * HEAVY_FUNCTION_SHARE {
+            counts.push((func_id, count));
+        }
     }
+    counts
+}
 
-    async move {
-        if lenses.is_empty() {
-            Ok(None)
+/// Returns true if both paths name the same file, which may be written differently in a build
+/// compiled from another directory.
+fn same_file(lhs: &str, rhs: &str) -> bool {
+    let canonicalize = |path: &str| PathBuf::from(path).canonicalize().ok();
+    lhs == rhs
+        || matches!((canonicalize(lhs), canonicalize(rhs)), (Some(lhs), Some(rhs)) if lhs == rhs)
+}
+
+fn on_goto_definition_request(
+    state: &mut LspState,
+    params: GotoDefinitionParams,
+) -> impl Future<Output = Result<Option<GotoDefinitionResponse>, ResponseError>> {
+    let definition = find_definition(state, &params.text_document_position_params);
+    async move { Ok(definition.map(GotoDefinitionResponse::Scalar)) }
+}
+
+/// Finds the definition of the name at the given position. Definitions within dependencies
+/// are found in their source, such as the cached source of git dependencies.
+fn find_definition(
+    state: &mut LspState,
+    params: &TextDocumentPositionParams,
+) -> Option<lsp_types::Location> {
+    let (context, file_id, offset) = check_document_position(state, params)?;
+    let definition = context.def_interner.find_definition(file_id, offset)?;
+    to_lsp_location(context, definition)
+}
+
+fn on_references_request(
+    state: &mut LspState,
+    params: ReferenceParams,
+) -> impl Future<Output = Result<Option<Vec<lsp_types::Location>>, ResponseError>> {
+    let references = find_references(state, &params);
+    async move { Ok(references) }
+}
+
+/// Finds every use of the definition named at the given position across the package and its
+/// dependencies, including uses within test functions.
+fn find_references(
+    state: &mut LspState,
+    params: &ReferenceParams,
+) -> Option<Vec<lsp_types::Location>> {
+    let (context, file_id, offset) =
+        check_document_position(state, &params.text_document_position)?;
+    let include_declaration = params.context.include_declaration;
+    let references = context.def_interner.find_references(file_id, offset, include_declaration);
+    Some(references.into_iter().filter_map(|location| to_lsp_location(context, location)).collect())
+}
+
+fn on_rename_request(
+    state: &mut LspState,
+    params: RenameParams,
+) -> impl Future<Output = Result<Option<WorkspaceEdit>, ResponseError>> {
+    let edit = rename(state, &params);
+    async move { edit }
+}
+
+/// Renames the definition named at the given position along with each of its uses, failing
+/// if the new name would conflict with another name in scope.
+fn rename(
+    state: &mut LspState,
+    params: &RenameParams,
+) -> Result<Option<WorkspaceEdit>, ResponseError> {
+    let (context, file_id, offset) =
+        match check_document_position(state, &params.text_document_position) {
+            Some(checked) => checked,
+            None => return Ok(None),
+        };
+    let edits = context
+        .rename(file_id, offset, &params.new_name)
+        .map_err(|error| ResponseError::new(ErrorCode::REQUEST_FAILED, error.to_string()))?;
+
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for edit in edits {
+        let location = to_lsp_location(context, edit.location).ok_or_else(|| {
+            ResponseError::new(ErrorCode::REQUEST_FAILED, "A renamed name is not within a file")
+        })?;
+        changes
+            .entry(location.uri)
+            .or_default()
+            .push(TextEdit { range: location.range, new_text: edit.new_text });
+    }
+    Ok(Some(WorkspaceEdit { changes: Some(changes), ..Default::default() }))
+}
+
+fn on_semantic_tokens_request(
+    state: &mut LspState,
+    params: SemanticTokensParams,
+) -> impl Future<Output = Result<Option<SemanticTokensResult>, ResponseError>> {
+    let tokens = semantic_tokens(state, &params.text_document.uri);
+    async move { Ok(tokens.map(|data| SemanticTokens { result_id: None, data }.into())) }
+}
+
+/// Highlights the names of the document by what they refer to, with each token encoded
+/// relative to the one before it.
+fn semantic_tokens(state: &mut LspState, uri: &Url) -> Option<Vec<SemanticToken>> {
+    let (context, file_id) = check_document(state, uri)?;
+    let files = context.file_manager.as_simple_files();
+
+    let mut tokens = Vec::new();
+    let mut previous = Position::default();
+    for highlight in context.highlights(file_id) {
+        let range = match byte_span_to_range(files, file_id.as_usize(), highlight.span.into()) {
+            // Tokens may not span several lines
+            Some(range) if range.start.line == range.end.line => range,
+            _ => continue,
+        };
+
+        let delta_line = range.start.line - previous.line;
+        let delta_start = if delta_line == 0 {
+            range.start.character - previous.character
         } else {
-            Ok(Some(lenses))
+            range.start.character
+        };
+        previous = range.start;
+
+        tokens.push(SemanticToken {
+            delta_line,
+            delta_start,
+            length: range.end.character - range.start.character,
+            token_type: semantic_token_type(highlight.kind),
+            token_modifiers_bitset: semantic_token_modifiers(&highlight),
+        });
+    }
+    Some(tokens)
+}
+
+fn semantic_token_type(kind: HighlightKind) -> u32 {
+    let token_type = match kind {
+        HighlightKind::Module => SemanticTokenType::NAMESPACE,
+        HighlightKind::Struct => SemanticTokenType::STRUCT,
+        HighlightKind::Enum => SemanticTokenType::ENUM,
+        HighlightKind::TypeAlias => SemanticTokenType::TYPE,
+        HighlightKind::Trait => SemanticTokenType::INTERFACE,
+        HighlightKind::Generic => SemanticTokenType::TYPE_PARAMETER,
+        HighlightKind::Variable | HighlightKind::Global => SemanticTokenType::VARIABLE,
+        HighlightKind::Field => SemanticTokenType::PROPERTY,
+        HighlightKind::Function => SemanticTokenType::FUNCTION,
+        HighlightKind::Method => SemanticTokenType::METHOD,
+    };
+    let index = SEMANTIC_TOKEN_TYPES.iter().position(|legend_type| *legend_type == token_type);
+    index.expect("Every token type should be in the legend") as u32
+}
+
+fn semantic_token_modifiers(highlight: &Highlight) -> u32 {
+    let modifiers = [
+        highlight.is_declaration,
+        highlight.kind == HighlightKind::Global,
+        highlight.is_mutable,
+        highlight.is_unconstrained,
+        highlight.is_oracle,
+    ];
+    // Each modifier is set in the bit of its index in the legend
+    modifiers.iter().enumerate().filter(|(_, is_set)| **is_set).map(|(bit, _)| 1 << bit).sum()
+}
+
+fn on_inlay_hint_request(
+    state: &mut LspState,
+    params: InlayHintParams,
+) -> impl Future<Output = Result<Option<Vec<InlayHint>>, ResponseError>> {
+    let hints = inlay_hints(state, &params.text_document.uri, params.range);
+    async move { Ok(hints) }
+}
+
+/// Shows the inferred type of each variable declared without a type annotation, and the number
+/// of iterations of each loop of a constrained function whose bounds are known at compile-time.
+fn inlay_hints(state: &mut LspState, uri: &Url, range: Range) -> Option<Vec<InlayHint>> {
+    let (context, file_id) = check_document(state, uri)?;
+    let files = context.file_manager.as_simple_files();
+
+    let mut hints = Vec::new();
+    for hint in context.inlay_hints(file_id) {
+        let offset = hint.offset as usize;
+        let position = match byte_span_to_range(files, file_id.as_usize(), offset..offset) {
+            Some(hint_range) => hint_range.start,
+            None => continue,
+        };
+        if position < range.start || range.end < position {
+            continue;
+        }
+
+        let (kind, padding_left) = match hint.kind {
+            inlay_hints::InlayHintKind::Type => (InlayHintKind::TYPE, false),
+            inlay_hints::InlayHintKind::TripCount => (InlayHintKind::PARAMETER, true),
+        };
+        hints.push(InlayHint {
+            position,
+            label: InlayHintLabel::String(hint.label),
+            kind: Some(kind),
+            text_edits: None,
+            tooltip: None,
+            padding_left: Some(padding_left),
+            padding_right: None,
+            data: None,
+        });
+    }
+    Some(hints)
+}
+
+fn on_document_symbol_request(
+    state: &mut LspState,
+    params: DocumentSymbolParams,
+) -> impl Future<Output = Result<Option<DocumentSymbolResponse>, ResponseError>> {
+    // The outline only depends on the file itself, so the file is parsed without checking it
+    let source = document_path(&params.text_document.uri).and_then(|file_path| {
+        match state.open_documents.get(&file_path) {
+            Some(source) => Some(source.clone()),
+            None => std::fs::read_to_string(file_path).ok(),
+        }
+    });
+    let symbols = source.map(|source| symbols::document_symbols(&source));
+    async move { Ok(symbols.map(DocumentSymbolResponse::Nested)) }
+}
+
+/// Converts a location in the source of a checked context to one within a file on disk.
+fn to_lsp_location(context: &Context, location: Location) -> Option<lsp_types::Location> {
+    let files = context.file_manager.as_simple_files();
+    let uri = file_url(context, location.file.as_usize())?;
+    let range = byte_span_to_range(files, location.file.as_usize(), location.span.into())?;
+    Some(lsp_types::Location { uri, range })
+}
+
+/// Returns the url of a file of a checked context.
+fn file_url(context: &Context, file_index: usize) -> Option<Url> {
+    // The standard library is embedded in the compiler, so it has no file to point to
+    let file = context.file_manager.as_simple_files().get(file_index).ok()?;
+    let path = PathBuf::from(file.name().to_string()).canonicalize().ok()?;
+    Url::from_file_path(path).ok()
+}
+
+fn on_hover_request(
+    state: &mut LspState,
+    params: HoverParams,
+) -> impl Future<Output = Result<Option<Hover>, ResponseError>> {
+    let hover = hover(state, &params.text_document_position_params);
+    async move { Ok(hover) }
+}
+
+/// Shows the type of the expression at the given position, as inferred by the type checker,
+/// along with the numeric generics of the function it refers to and the doc comments of the
+/// item it refers to.
+fn hover(state: &mut LspState, params: &TextDocumentPositionParams) -> Option<Hover> {
+    let (context, file_id, offset) = check_document_position(state, params)?;
+    let info = context.def_interner.find_expression_info(file_id, offset)?;
+
+    let mut signature = info.typ.to_string();
+    for (name, value) in &info.numeric_generics {
+        signature.push_str(&format!("\n{name} = {value}"));
+    }
+    let mut value = format!("```noir\n{signature}\n```");
+    if !info.doc_comments.is_empty() {
+        let doc_comments: Vec<_> = info.doc_comments.iter().map(|line| line.trim()).collect();
+        value.push_str(&format!("\n---\n{}", doc_comments.join("\n")));
+    }
+
+    let files = context.file_manager.as_simple_files();
+    let range = byte_span_to_range(files, file_id.as_usize(), info.location.span.into());
+    let contents = HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value });
+    Some(Hover { contents, range })
+}
+
+fn on_completion_request(
+    state: &mut LspState,
+    params: CompletionParams,
+) -> impl Future<Output = Result<Option<CompletionResponse>, ResponseError>> {
+    let completions = complete(state, &params.text_document_position);
+    async move { Ok(completions.map(CompletionResponse::Array)) }
+}
+
+/// What is being written at the cursor, as found from the line before it
+#[derive(Debug, PartialEq, Eq)]
+enum CompletionTrigger<'a> {
+    /// An item within the module at the given path of a `use` statement
+    Path(Vec<&'a str>),
+    /// A field of the expression ending at the given byte offset of the line, where the `.`
+    /// accessing the field is written
+    Field(usize),
+    /// The name of an attribute, after `#[`
+    Attribute,
+    /// Any other name
+    Name,
+}
+
+fn completion_trigger(line: &str) -> CompletionTrigger<'_> {
+    let statement = line.trim_start();
+    if statement.starts_with("#[") && !statement.contains(']') {
+        return CompletionTrigger::Attribute;
+    }
+
+    // The part of the name being written before the cursor is matched by the client
+    let name_start = line
+        .char_indices()
+        .rfind(|(_, c)| !(c.is_ascii_alphanumeric() || *c == '_'))
+        .map_or(0, |(index, c)| index + c.len_utf8());
+    let before_name = &line[..name_start];
+
+    let path = statement.strip_prefix("pub ").unwrap_or(statement).strip_prefix("use ");
+    if let Some(path) = path {
+        let path = &path[..path.len() - (line.len() - name_start)];
+        let segments = path.trim().split("::").filter(|segment| !segment.is_empty()).collect();
+        CompletionTrigger::Path(segments)
+    } else if before_name.ends_with('.') {
+        CompletionTrigger::Field(name_start - 1)
+    } else {
+        CompletionTrigger::Name
+    }
+}
+
+/// Completes the name at the given position from the items the def collector found in each
+/// crate: the items of a module within a `use` path, the fields of a struct after a `.`, the
+/// names of attributes, and otherwise the functions in scope.
+fn complete(
+    state: &mut LspState,
+    params: &TextDocumentPositionParams,
+) -> Option<Vec<CompletionItem>> {
+    let (context, file_id, offset) = check_document_position(state, params)?;
+    let files = context.file_manager.as_simple_files();
+    let source = files.get(file_id.as_usize()).ok()?.source();
+    let before_cursor = source.get(..offset as usize)?;
+    let line = before_cursor.rsplit('\n').next().unwrap_or_default();
+    let line_start = (offset as usize - line.len()) as u32;
+
+    let completions = match completion_trigger(line) {
+        CompletionTrigger::Path(segments) => context.path_completions(file_id, &segments),
+        CompletionTrigger::Field(dot) => {
+            context.field_completions(file_id, line_start + dot as u32)
         }
+        CompletionTrigger::Attribute => attribute_completions(),
+        CompletionTrigger::Name => context.function_completions(file_id),
+    };
+    Some(completions.into_iter().map(completion_item).collect())
+}
+
+fn completion_item(completion: Completion) -> CompletionItem {
+    let kind = match completion.kind {
+        CompletionKind::Module => CompletionItemKind::MODULE,
+        CompletionKind::Function => CompletionItemKind::FUNCTION,
+        CompletionKind::Struct | CompletionKind::TypeAlias => CompletionItemKind::STRUCT,
+        CompletionKind::Enum => CompletionItemKind::ENUM,
+        CompletionKind::Trait => CompletionItemKind::INTERFACE,
+        CompletionKind::Global => CompletionItemKind::CONSTANT,
+        CompletionKind::Field => CompletionItemKind::FIELD,
+        CompletionKind::Attribute => CompletionItemKind::KEYWORD,
+    };
+    CompletionItem {
+        label: completion.label,
+        kind: Some(kind),
+        detail: completion.detail,
+        ..Default::default()
     }
 }
 
+/// Checks the package of the document at the given position so that the resolver and type
+/// checker record what each name refers to and the type of each expression, returning the
+/// checked context along with the file id and byte offset of the position.
+fn check_document_position<'a>(
+    state: &'a mut LspState,
+    params: &TextDocumentPositionParams,
+) -> Option<(&'a Context, FileId, u32)> {
+    let (context, file_id) = check_document(state, &params.text_document.uri)?;
+    let files = context.file_manager.as_simple_files();
+    let offset = position_to_byte_offset(files, file_id.as_usize(), params.position)?;
+    Some((context, file_id, offset))
+}
+
+/// Checks the package of the given document, returning the checked context along with the
+/// file id of the document. The package is only checked again once one of its files or
+/// manifests changed since the last request about the document.
+fn check_document<'a>(state: &'a mut LspState, uri: &Url) -> Option<(&'a Context, FileId)> {
+    let file_path = document_path(uri)?;
+    let is_current = state
+        .checked_contexts
+        .get(&file_path)
+        .map_or(false, |checked| checked.is_current(&state.open_documents));
+
+    if !is_current {
+        let (mut context, manifests) = state.resolve_context(&file_path);
+
+        // Names are resolved and expressions typed even if the crate has errors, so they are
+        // ignored
+        let _ = check_crate(&mut context, false, false);
+
+        let file_id = context.file_manager.add_file(&file_path)?;
+        let checked = CheckedContext::new(context, file_id, manifests, &state.open_documents);
+        state.checked_contexts.insert(file_path.clone(), checked);
+    }
+
+    let checked = &state.checked_contexts[&file_path];
+    Some((&checked.context, checked.file_id))
+}
+
 fn on_initialized(
     _state: &mut LspState,
     _params: InitializedParams,
@@ -194,23 +876,35 @@ fn on_did_change_configuration(
 }
 
 fn on_did_open_text_document(
-    _state: &mut LspState,
-    _params: DidOpenTextDocumentParams,
+    state: &mut LspState,
+    params: DidOpenTextDocumentParams,
 ) -> ControlFlow<Result<(), async_lsp::Error>> {
+    if let Some(file_path) = document_path(&params.text_document.uri) {
+        state.open_documents.insert(file_path, params.text_document.text);
+    }
     ControlFlow::Continue(())
 }
 
 fn on_did_change_text_document(
-    _state: &mut LspState,
-    _params: DidChangeTextDocumentParams,
+    state: &mut LspState,
+    params: DidChangeTextDocumentParams,
 ) -> ControlFlow<Result<(), async_lsp::Error>> {
+    // Documents are synced in full, so the last change holds the whole text of the document
+    let text = params.content_changes.into_iter().last().map(|change| change.text);
+    if let (Some(file_path), Some(text)) = (document_path(&params.text_document.uri), text) {
+        state.open_documents.insert(file_path, text);
+    }
     ControlFlow::Continue(())
 }
 
 fn on_did_close_text_document(
-    _state: &mut LspState,
-    _params: DidCloseTextDocumentParams,
+    state: &mut LspState,
+    params: DidCloseTextDocumentParams,
 ) -> ControlFlow<Result<(), async_lsp::Error>> {
+    if let Some(file_path) = document_path(&params.text_document.uri) {
+        state.open_documents.remove(&file_path);
+        state.checked_contexts.remove(&file_path);
+    }
     ControlFlow::Continue(())
 }
 
@@ -218,64 +912,138 @@ fn on_did_save_text_document(
     state: &mut LspState,
     params: DidSaveTextDocumentParams,
 ) -> ControlFlow<Result<(), async_lsp::Error>> {
-    let file_path = &params.text_document.uri.to_file_path().unwrap();
+    let uri = params.text_document.uri;
+    let file_path = match uri.to_file_path() {
+        Ok(file_path) => file_path,
+        Err(_) => return ControlFlow::Continue(()),
+    };
 
-    let mut context = Context::default();
+    // Besides the package of the saved file, every package checked before which includes the
+    // file is affected by the change, such as the packages depending on it
+    let (context, _) = state.resolve_context(&file_path);
+    let mut contexts = vec![context];
+    let affected =
+        state.checked_packages.iter().filter(|(_, package)| package.files.contains(&uri));
+    let affected: Vec<PathBuf> = affected.map(|(root, _)| root.clone()).collect();
+    for root in affected {
+        if package_root(&contexts[0]).as_ref() != Some(&root) {
+            contexts.push(state.resolve_context(&root).0);
+        }
+    }
 
-    create_local_crate(&mut context, file_path, CrateType::Binary);
+    for context in contexts {
+        publish_package_diagnostics(state, context);
+    }
+    ControlFlow::Continue(())
+}
 
-    let mut diagnostics = Vec::new();
+/// Checks a package, publishing the diagnostics of each of its files and clearing those of the
+/// files which no longer have any.
+fn publish_package_diagnostics(state: &mut LspState, mut context: Context) {
+    let root = match package_root(&context) {
+        Some(root) => root,
+        None => return,
+    };
 
     let file_diagnostics = match check_crate(&mut context, false, false) {
         Ok(warnings) => warnings,
         Err(errors_and_warnings) => errors_and_warnings,
     };
 
-    if !file_diagnostics.is_empty() {
-        let fm = &context.file_manager;
-        let files = fm.as_simple_files();
+    let files = context.file_manager.as_simple_files();
+    let urls: HashMap<_, _> = (0..)
+        .take_while(|index| files.get(*index).is_ok())
+        .filter_map(|index| Some((index, file_url(&context, index)?)))
+        .collect();
 
-        for FileDiagnostic { file_id, diagnostic } in file_diagnostics {
-            // TODO(#1681): This file_id never be 0 because the "path" where it maps is the directory, not a file
-            if file_id.as_usize() != 0 {
-                continue;
-            }
+    let mut diagnostics: HashMap<Url, Vec<Diagnostic>> = HashMap::new();
+    for FileDiagnostic { file_id, diagnostic } in file_diagnostics {
+        if let Some(url) = urls.get(&file_id.as_usize()) {
+            let diagnostic = to_lsp_diagnostic(&context, file_id, diagnostic);
+            diagnostics.entry(url.clone()).or_default().push(diagnostic);
+        }
+    }
 
-            let mut range = Range::default();
+    let previous = state.checked_packages.remove(&root).unwrap_or_default();
+    let cleared = previous.published.into_iter().filter(|url| !diagnostics.contains_key(url));
+    let cleared: Vec<_> = cleared.map(|url| (url, Vec::new())).collect();
 
-            // TODO: Should this be the first item in secondaries? Should we bail when we find a range?
-            for sec in diagnostic.secondaries {
-                // Not using `unwrap_or_default` here because we don't want to overwrite a valid range with a default range
-                if let Some(r) = byte_span_to_range(files, file_id.as_usize(), sec.span.into()) {
-                    range = r
-                }
-            }
-            let severity = match diagnostic.kind {
-                DiagnosticKind::Error => Some(DiagnosticSeverity::ERROR),
-                DiagnosticKind::Warning => Some(DiagnosticSeverity::WARNING),
-            };
-            diagnostics.push(Diagnostic {
-                range,
-                severity,
-                message: diagnostic.message,
-                ..Diagnostic::default()
-            })
-        }
+    let published = diagnostics.keys().cloned().collect();
+    for (uri, diagnostics) in diagnostics.into_iter().chain(cleared) {
+        let params = PublishDiagnosticsParams { uri, version: None, diagnostics };
+        let _ = state.client.publish_diagnostics(params);
     }
 
-    let _ = state.client.publish_diagnostics(PublishDiagnosticsParams {
-        uri: params.text_document.uri,
-        version: None,
-        diagnostics,
-    });
+    let files = urls.into_values().collect();
+    state.checked_packages.insert(root, CheckedPackage { files, published });
+}
 
-    ControlFlow::Continue(())
+fn to_lsp_diagnostic(
+    context: &Context,
+    file_id: FileId,
+    diagnostic: CustomDiagnostic,
+) -> Diagnostic {
+    let files = context.file_manager.as_simple_files();
+    let mut range = Range::default();
+
+    // TODO: Should this be the first item in secondaries? Should we bail when we find a range?
+    for sec in diagnostic.secondaries {
+        // Labels pointing into other files can't be shown on this file's ranges
+        if matches!(sec.file, Some(sec_file) if sec_file != file_id) {
+            continue;
+        }
+        // Not using `unwrap_or_default` here because we don't want to overwrite a valid range with a default range
+        if let Some(r) = byte_span_to_range(files, file_id.as_usize(), sec.span.into()) {
+            range = r
+        }
+    }
+    let severity = match diagnostic.kind {
+        DiagnosticKind::Error | DiagnosticKind::Bug => Some(DiagnosticSeverity::ERROR),
+        DiagnosticKind::Warning => Some(DiagnosticSeverity::WARNING),
+    };
+    Diagnostic {
+        range,
+        severity,
+        code: diagnostic.code.map(|code| NumberOrString::String(code.to_string())),
+        message: diagnostic.message,
+        ..Diagnostic::default()
+    }
+}
+
+/// Returns the path of the root file of the local crate of a context, which identifies the
+/// package being checked.
+fn package_root(context: &Context) -> Option<PathBuf> {
+    let root_file_id = context.crate_graph[LOCAL_CRATE].root_file_id;
+    let file = context.file_manager.as_simple_files().get(root_file_id.as_usize()).ok()?;
+    PathBuf::from(file.name().to_string()).canonicalize().ok()
 }
 
 fn on_exit(_state: &mut LspState, _params: ()) -> ControlFlow<Result<(), async_lsp::Error>> {
     ControlFlow::Continue(())
 }
 
+/// Converts a position to a byte offset in a file. The character of a position counts the
+/// UTF-16 code units before it within its line, so each character of the line is measured in
+/// both encodings. Characters past the end of the line point at its end.
+fn position_to_byte_offset<'a, F: files::Files<'a> + ?Sized>(
+    files: &'a F,
+    file_id: F::FileId,
+    position: Position,
+) -> Option<u32> {
+    let line_range = files.line_range(file_id, position.line as usize).ok()?;
+    let source = files.source(file_id).ok()?;
+    let line = source.as_ref().get(line_range.clone())?.trim_end_matches(['\n', '\r']);
+
+    let mut column = 0;
+    for (index, c) in line.char_indices() {
+        if column >= position.character as usize {
+            return Some((line_range.start + index) as u32);
+        }
+        column += c.len_utf16();
+    }
+    Some((line_range.start + line.len()) as u32)
+}
+
 fn byte_span_to_range<'a, F: files::Files<'a> + ?Sized>(
     files: &'a F,
     file_id: F::FileId,
@@ -303,7 +1071,14 @@ fn byte_span_to_range<'a, F: files::Files<'a> + ?Sized>(
 
 #[cfg(test)]
 mod lsp_tests {
-    use lsp_types::TextDocumentSyncCapability;
+    use lsp_types::{
+        TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+        TextDocumentSyncCapability, VersionedTextDocumentIdentifier,
+    };
+    use noirc_driver::SourceLocation;
+    use noirc_frontend::hir::rename::RenameError;
+    use std::{cell::Cell, rc::Rc};
+    use tempdir::TempDir;
     use tokio::test;
 
     use super::*;
@@ -312,19 +1087,312 @@ mod lsp_tests {
     async fn test_on_initialize() {
         // Not available in published release yet
         let client = ClientSocket::new_closed();
-        let mut state = LspState::new(&client);
+        let mut state = LspState::new(&client, local_crate_resolver(), no_build);
         let params = InitializeParams::default();
         let response = on_initialize(&mut state, params).await.unwrap();
         assert!(matches!(
             response.capabilities,
             ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Options(
-                    TextDocumentSyncOptions { save: Some(_), .. }
+                    TextDocumentSyncOptions {
+                        open_close: Some(true),
+                        change: Some(TextDocumentSyncKind::FULL),
+                        save: Some(_),
+                        ..
+                    }
                 )),
                 code_lens_provider: Some(CodeLensOptions { resolve_provider: Some(false) }),
+                definition_provider: Some(OneOf::Left(true)),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                completion_provider: Some(CompletionOptions { trigger_characters: Some(_), .. }),
+                references_provider: Some(OneOf::Left(true)),
+                rename_provider: Some(OneOf::Left(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                semantic_tokens_provider: Some(_),
+                inlay_hint_provider: Some(OneOf::Left(true)),
                 ..
             }
         ));
         assert!(response.server_info.is_none());
     }
+
+    #[test]
+    async fn test_completion_trigger() {
+        assert_eq!(
+            completion_trigger("use dep::std::ha"),
+            CompletionTrigger::Path(vec!["dep", "std"])
+        );
+        assert_eq!(
+            completion_trigger("    pub use crate::"),
+            CompletionTrigger::Path(vec!["crate"])
+        );
+        assert_eq!(completion_trigger("use "), CompletionTrigger::Path(vec![]));
+        assert_eq!(completion_trigger("    let x = foo.ba"), CompletionTrigger::Field(15));
+        assert_eq!(completion_trigger("#[inl"), CompletionTrigger::Attribute);
+        assert_eq!(completion_trigger("    let x = sq"), CompletionTrigger::Name);
+        assert_eq!(completion_trigger("#[test] fn fo"), CompletionTrigger::Name);
+        assert_eq!(completion_trigger("    let x = «sq"), CompletionTrigger::Name);
+    }
+
+    #[test]
+    async fn test_completion_of_unsaved_text() {
+        let root_dir = TempDir::new("unsaved").unwrap().into_path();
+        let root_file = root_dir.join("main.nr");
+        std::fs::write(&root_file, "fn main() {}").unwrap();
+        let uri = Url::from_file_path(&root_file).unwrap();
+
+        let client = ClientSocket::new_closed();
+        let mut state = LspState::new(&client, local_crate_resolver(), no_build);
+        let text_document = TextDocumentItem {
+            uri: uri.clone(),
+            language_id: "noir".to_owned(),
+            version: 0,
+            text: "fn main() {}".to_owned(),
+        };
+        let _ = on_did_open_text_document(&mut state, DidOpenTextDocumentParams { text_document });
+
+        // Neither the struct nor the field access have been saved to disk
+        let text = "struct Foo { bar: Field }\nfn main(foo: Foo) -> pub Field {\n    let s = \"«»\"; let x = foo.bar;\n    x\n}";
+        let change = TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: text.to_owned(),
+        };
+        let _ = on_did_change_text_document(
+            &mut state,
+            DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier { uri: uri.clone(), version: 1 },
+                content_changes: vec![change],
+            },
+        );
+
+        // The guillemets are a single UTF-16 code unit each but two bytes each, so the position
+        // after the `.` is two bytes further into the line than its character
+        let params = TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            position: Position { line: 2, character: 30 },
+        };
+        let completions = complete(&mut state, &params).unwrap();
+        let labels: Vec<_> = completions.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, vec!["bar"]);
+
+        // Once closed, the document is read from disk again
+        let text_document = TextDocumentIdentifier { uri };
+        let _ =
+            on_did_close_text_document(&mut state, DidCloseTextDocumentParams { text_document });
+        assert!(state.open_documents.is_empty());
+    }
+
+    #[test]
+    async fn test_checked_contexts_are_reused_until_their_files_change() {
+        let dir = TempDir::new("cached").unwrap().into_path().canonicalize().unwrap();
+        let (root_file, manifest) = (dir.join("main.nr"), dir.join("Nargo.toml"));
+        std::fs::write(&root_file, "fn main() {}").unwrap();
+        std::fs::write(&manifest, "").unwrap();
+        let uri = Url::from_file_path(&root_file).unwrap();
+
+        let resolved = Rc::new(Cell::new(0));
+        let resolve_count = resolved.clone();
+        let resolver: ContextResolver =
+            Box::new(move |file_path: &Path, open_documents: &HashMap<PathBuf, String>| {
+                resolve_count.set(resolve_count.get() + 1);
+                (local_crate_context(file_path, open_documents), vec![manifest.clone()])
+            });
+        let client = ClientSocket::new_closed();
+        let mut state = LspState::new(&client, resolver, no_build);
+        let check = |state: &mut LspState| {
+            assert!(check_document(state, &uri).is_some());
+            resolved.get()
+        };
+
+        assert_eq!(check(&mut state), 1);
+        assert_eq!(check(&mut state), 1);
+
+        // Opening the document with unsaved text checks it again, as does editing it
+        let text_document = TextDocumentItem {
+            uri: uri.clone(),
+            language_id: "noir".to_owned(),
+            version: 0,
+            text: "fn main() { }".to_owned(),
+        };
+        let _ = on_did_open_text_document(&mut state, DidOpenTextDocumentParams { text_document });
+        assert_eq!(check(&mut state), 2);
+        assert_eq!(check(&mut state), 2);
+
+        let change = TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "fn main() {  }".to_owned(),
+        };
+        let _ = on_did_change_text_document(
+            &mut state,
+            DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier { uri: uri.clone(), version: 1 },
+                content_changes: vec![change],
+            },
+        );
+        assert_eq!(check(&mut state), 3);
+
+        // Once closed, the document is read from disk again
+        let text_document = TextDocumentIdentifier { uri: uri.clone() };
+        let _ =
+            on_did_close_text_document(&mut state, DidCloseTextDocumentParams { text_document });
+        assert_eq!(check(&mut state), 4);
+        assert_eq!(check(&mut state), 4);
+
+        // A file modified on disk since the context was checked is read again
+        let checked = state.checked_contexts.get_mut(&root_file).unwrap();
+        let (_, _, version) =
+            checked.sources.iter_mut().find(|(_, path, _)| *path == root_file).unwrap();
+        *version = FileVersion::Saved(Some(SystemTime::UNIX_EPOCH));
+        assert_eq!(check(&mut state), 5);
+
+        // As is the package of a modified manifest
+        let checked = state.checked_contexts.get_mut(&root_file).unwrap();
+        checked.manifests[0].1 = Some(SystemTime::UNIX_EPOCH);
+        assert_eq!(check(&mut state), 6);
+        assert_eq!(check(&mut state), 6);
+    }
+
+    #[test]
+    async fn test_position_to_byte_offset_counts_utf16_code_units() {
+        let mut files = files::SimpleFiles::new();
+        let file_id = files.add("main.nr", "fn main() {}\nlet 𝔽 = «x»;\n");
+        let offset = |line, character| {
+            position_to_byte_offset(&files, file_id, Position { line, character })
+        };
+
+        assert_eq!(offset(0, 3), Some(3));
+        // `𝔽` is two UTF-16 code units and four bytes, while `«` is one code unit and two bytes
+        assert_eq!(offset(1, 4), Some(17));
+        assert_eq!(offset(1, 6), Some(21));
+        assert_eq!(offset(1, 10), Some(26));
+        // Positions past the end of a line point at its end, before the line break
+        assert_eq!(offset(1, 13), Some(30));
+        assert_eq!(offset(1, 20), Some(30));
+        assert_eq!(offset(2, 0), Some(31));
+        assert_eq!(offset(3, 0), None);
+    }
+
+    #[test]
+    async fn test_opcode_counts_of_main_and_heavy_functions() {
+        let source = "fn heavy() {}\nfn light() {}\nfn main() { heavy(); light(); }";
+        let root_dir = TempDir::new("opcodes").unwrap().into_path();
+        let root_file = root_dir.join("main.nr");
+        std::fs::write(&root_file, source).unwrap();
+
+        let mut context = local_crate_context(&root_file, &HashMap::new());
+        let _ = check_crate(&mut context, false, false);
+        let file_id = context.file_manager.add_file(&root_file).unwrap();
+
+        // Opcodes are attributed to the functions whose bodies contain their locations
+        let opcode_at = |code: &str| {
+            let start = source.find(code).unwrap() as u32;
+            SourceLocation { file: 0, start, end: start + 1, line: 1, column: 1 }
+        };
+        let mut debug_info = DebugInfo::default();
+        debug_info.files.push(root_file.to_string_lossy().into_owned());
+        debug_info.locations.extend((0..3).map(|index| (index, opcode_at("heavy() {}"))));
+        debug_info.locations.insert(3, opcode_at("light() {}"));
+
+        // Only functions generating at least a tenth of the opcodes are counted
+        let counts = opcode_counts(&context, file_id, 20, &debug_info);
+        let mut counts: Vec<_> = counts
+            .into_iter()
+            .map(|(func_id, count)| (context.function_name(&func_id), count))
+            .collect();
+        counts.sort();
+        assert_eq!(counts, vec![("heavy", 3), ("main", 20)]);
+    }
+
+    /// Checks `source` as the root of a crate and renames what is named at the first occurrence
+    /// of `target`, returning the replaced text and the new text of each edit in
+    /// source order.
+    fn rename_source(
+        source: &str,
+        target: &str,
+        new_name: &str,
+    ) -> Result<Vec<(String, String)>, RenameError> {
+        let root_dir = TempDir::new("rename").unwrap().into_path();
+        let root_file = root_dir.join("main.nr");
+        std::fs::write(&root_file, source).unwrap();
+
+        let mut context = local_crate_context(&root_file, &HashMap::new());
+        let _ = check_crate(&mut context, false, false);
+        let file_id = context.file_manager.add_file(&root_file).unwrap();
+
+        let offset = source.find(target).unwrap() as u32;
+        let mut edits = context.rename(file_id, offset, new_name)?;
+        edits.sort_by_key(|edit| edit.location.span.start());
+        Ok(edits
+            .into_iter()
+            .map(|edit| {
+                let span = edit.location.span;
+                (source[span.start() as usize..span.end() as usize].to_owned(), edit.new_text)
+            })
+            .collect())
+    }
+
+    #[test]
+    async fn test_rename_function() {
+        let source = "fn foo() -> Field { 1 }\nfn main() -> pub Field { foo() + foo() }";
+        let edits = rename_source(source, "foo", "bar").unwrap();
+        assert_eq!(edits, vec![("foo".to_owned(), "bar".to_owned()); 3]);
+    }
+
+    #[test]
+    async fn test_rename_conflict() {
+        let source =
+            "fn foo() -> Field { 1 }\nfn bar() -> Field { 2 }\nfn main() -> pub Field { foo() }";
+        let result = rename_source(source, "foo", "bar");
+        assert!(matches!(result, Err(RenameError::Conflict { name, .. }) if name == "bar"));
+    }
+
+    #[test]
+    async fn test_rename_struct_shorthand() {
+        let source = "struct Foo { x: Field }\nfn main() -> pub Field { let x = 1; let foo = Foo { x }; foo.x }";
+        let rename = |target, new_name: &str| rename_source(source, target, new_name).unwrap();
+        let renamed = |edits: &[(&str, &str)]| {
+            edits.iter().map(|(old, new)| (old.to_string(), new.to_string())).collect::<Vec<_>>()
+        };
+
+        // Renaming the variable keeps the field of the shorthand
+        assert_eq!(rename("x = 1", "y"), renamed(&[("x", "y"), ("x", "x: y")]));
+        // Renaming the field keeps the variable of the shorthand
+        assert_eq!(rename("x: Field", "y"), renamed(&[("x", "y"), ("x", "y: x"), ("x", "y")]));
+    }
+
+    #[test]
+    async fn test_saving_a_file_checks_the_packages_including_it() {
+        let dir = TempDir::new("packages").unwrap().into_path().canonicalize().unwrap();
+        let (module, dependent) = (dir.join("module.nr"), dir.join("dependent.nr"));
+        std::fs::write(&module, "fn main() {}").unwrap();
+        std::fs::write(&dependent, "mod module;\nfn main() { module::main(); }").unwrap();
+        let module_url = Url::from_file_path(&module).unwrap();
+        let dependent_url = Url::from_file_path(&dependent).unwrap();
+
+        let client = ClientSocket::new_closed();
+        let mut state = LspState::new(&client, local_crate_resolver(), no_build);
+        let save = |state: &mut LspState, uri: &Url| {
+            let text_document = TextDocumentIdentifier { uri: uri.clone() };
+            let params = DidSaveTextDocumentParams { text_document, text: None };
+            let _ = on_did_save_text_document(state, params);
+        };
+
+        // Checking the dependent package records that it includes the module
+        save(&mut state, &dependent_url);
+        assert!(state.checked_packages[&dependent].files.contains(&module_url));
+        assert!(state.checked_packages[&dependent].published.is_empty());
+
+        // Saving the module checks the dependent package again, whose call no longer type checks
+        std::fs::write(&module, "fn main(x: Field) { assert(x == x); }").unwrap();
+        save(&mut state, &module_url);
+        assert!(state.checked_packages[&module].published.is_empty());
+        assert_eq!(state.checked_packages[&dependent].published, HashSet::from([dependent_url]));
+
+        // Once fixed, the diagnostics of the dependent package are cleared
+        std::fs::write(&module, "fn main() {}").unwrap();
+        save(&mut state, &module_url);
+        assert!(state.checked_packages[&dependent].published.is_empty());
+    }
 }
@@ -0,0 +1,221 @@
+//! The outline of a file: a tree of the modules, impls, functions and other items it declares.
+//!
+//! Symbols are found by parsing the file on its own, so an outline is available even if the
+//! package the file belongs to does not compile.
+use codespan_reporting::files::SimpleFile;
+use lsp_types::{DocumentSymbol, SymbolKind};
+use noirc_errors::Span;
+use noirc_frontend::{parse_program, NoirFunction, ParsedModule};
+
+use crate::byte_span_to_range;
+
+/// Returns the symbols declared in the given source, nested within the module or impl
+/// declaring them.
+pub(crate) fn document_symbols(source: &str) -> Vec<DocumentSymbol> {
+    let (mut module, _) = parse_program(source);
+    // Items which are only compiled with some feature enabled are still shown
+    module.enable_features(|_| true);
+
+    let file = SimpleFile::new("", source);
+    vec_to_lsp(&file, module_symbols(module))
+}
+
+/// A symbol before its spans are converted into the ranges of the LSP
+struct Symbol {
+    name: String,
+    detail: Option<String>,
+    kind: SymbolKind,
+    /// The span of the whole item
+    span: Span,
+    /// The span of the name of the item, within `span`
+    name_span: Span,
+    children: Vec<Symbol>,
+}
+
+impl Symbol {
+    fn new(name: impl ToString, kind: SymbolKind, span: Span, name_span: Span) -> Symbol {
+        let span = span.merge(name_span);
+        Symbol { name: name.to_string(), detail: None, kind, span, name_span, children: Vec::new() }
+    }
+
+    fn with_children(mut self, children: Vec<Symbol>) -> Symbol {
+        self.span = children.iter().fold(self.span, |span, child| span.merge(child.span));
+        self.children = children;
+        self
+    }
+}
+
+fn module_symbols(module: ParsedModule) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+
+    for (_, declaration) in module.module_decls {
+        let name = declaration.item;
+        symbols.push(Symbol::new(&name, SymbolKind::MODULE, name.span(), name.span()));
+    }
+
+    for submodule in module.submodules {
+        let name = submodule.name;
+        let children = module_symbols(submodule.contents);
+        let mut symbol = Symbol::new(&name, SymbolKind::MODULE, name.span(), name.span());
+        if submodule.is_contract {
+            symbol.detail = Some("contract".to_owned());
+        }
+        symbols.push(symbol.with_children(children));
+    }
+
+    for (_, global) in module.globals {
+        let pattern = &global.item.pattern;
+        let span = pattern.span().merge(global.item.expression.span);
+        let mut symbol = Symbol::new(pattern, SymbolKind::CONSTANT, span, pattern.span());
+        symbol.detail = Some(global.item.r#type.to_string());
+        symbols.push(symbol);
+    }
+
+    for typ in module.types {
+        let fields = typ.fields.iter().map(|(name, typ)| {
+            let mut field = Symbol::new(name, SymbolKind::FIELD, name.span(), name.span());
+            field.detail = Some(typ.to_string());
+            field
+        });
+        let symbol = Symbol::new(&typ.name, SymbolKind::STRUCT, typ.span, typ.name.span());
+        symbols.push(symbol.with_children(fields.collect()));
+    }
+
+    for typ in module.enums {
+        let variants = typ
+            .variants
+            .iter()
+            .map(|(name, _)| Symbol::new(name, SymbolKind::ENUM_MEMBER, name.span(), name.span()));
+        let symbol = Symbol::new(&typ.name, SymbolKind::ENUM, typ.span, typ.name.span());
+        symbols.push(symbol.with_children(variants.collect()));
+    }
+
+    for alias in module.type_aliases {
+        let mut symbol =
+            Symbol::new(&alias.name, SymbolKind::TYPE_PARAMETER, alias.span, alias.name.span());
+        symbol.detail = Some(alias.typ.to_string());
+        symbols.push(symbol);
+    }
+
+    for noir_trait in module.traits {
+        let methods = noir_trait.methods.iter().map(|method| {
+            Symbol::new(&method.name, SymbolKind::METHOD, method.name.span(), method.name.span())
+        });
+        let name_span = noir_trait.name.span();
+        let symbol =
+            Symbol::new(&noir_trait.name, SymbolKind::INTERFACE, noir_trait.span, name_span);
+        symbols.push(symbol.with_children(methods.collect()));
+    }
+
+    for noir_impl in module.impls {
+        let name = format!("impl {}", noir_impl.object_type);
+        let symbol =
+            Symbol::new(name, SymbolKind::OBJECT, noir_impl.type_span, noir_impl.type_span);
+        symbols.push(symbol.with_children(function_symbols(noir_impl.methods)));
+    }
+
+    for trait_impl in module.trait_impls {
+        let name = format!("impl {} for {}", trait_impl.trait_name, trait_impl.object_type);
+        let span = trait_impl.type_span;
+        let symbol = Symbol::new(name, SymbolKind::OBJECT, span, span);
+        symbols.push(symbol.with_children(function_symbols(trait_impl.methods)));
+    }
+
+    symbols.extend(function_symbols(module.functions));
+
+    // Show the items in the order they are written in
+    symbols.sort_by_key(|symbol| symbol.span.start());
+    symbols
+}
+
+fn function_symbols(functions: Vec<NoirFunction>) -> Vec<Symbol> {
+    functions
+        .into_iter()
+        .map(|function| {
+            let def = function.def;
+            let name_span = def.name.span();
+            let mut symbol = Symbol::new(&def.name, SymbolKind::FUNCTION, def.full_span, name_span);
+            // Tests are told apart from other functions by their `#[test]` attribute
+            symbol.detail = def.attribute.map(|attribute| attribute.to_string());
+            symbol
+        })
+        .collect()
+}
+
+fn vec_to_lsp(file: &SimpleFile<&str, &str>, symbols: Vec<Symbol>) -> Vec<DocumentSymbol> {
+    symbols.into_iter().filter_map(|symbol| to_lsp(file, symbol)).collect()
+}
+
+fn to_lsp(file: &SimpleFile<&str, &str>, symbol: Symbol) -> Option<DocumentSymbol> {
+    let range = byte_span_to_range(file, (), symbol.span.into())?;
+    let selection_range = byte_span_to_range(file, (), symbol.name_span.into())?;
+    let children = vec_to_lsp(file, symbol.children);
+
+    // `deprecated` is itself deprecated in favor of `tags`, yet must still be given
+    #[allow(deprecated)]
+    Some(DocumentSymbol {
+        name: symbol.name,
+        detail: symbol.detail,
+        kind: symbol.kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range,
+        children: (!children.is_empty()).then_some(children),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{DocumentSymbol, SymbolKind};
+
+    use super::document_symbols;
+
+    fn outline(symbols: &[DocumentSymbol]) -> Vec<(String, SymbolKind, Vec<String>)> {
+        symbols
+            .iter()
+            .map(|symbol| {
+                let children = symbol.children.iter().flatten();
+                let children = children.map(|child| child.name.clone()).collect();
+                (symbol.name.clone(), symbol.kind, children)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn symbols_are_nested_within_their_module() {
+        let src = "
+            global N: Field = 3;
+
+            mod foo {
+                struct Bar { x: Field }
+
+                impl Bar {
+                    fn get(self) -> Field { self.x }
+                }
+            }
+
+            fn main() {}
+
+            #[test]
+            fn test_main() {}
+        ";
+        let symbols = document_symbols(src);
+
+        let expected = vec![
+            ("N".to_owned(), SymbolKind::CONSTANT, vec![]),
+            ("foo".to_owned(), SymbolKind::MODULE, vec!["Bar".to_owned(), "impl Bar".to_owned()]),
+            ("main".to_owned(), SymbolKind::FUNCTION, vec![]),
+            ("test_main".to_owned(), SymbolKind::FUNCTION, vec![]),
+        ];
+        assert_eq!(outline(&symbols), expected);
+
+        let foo = symbols[1].children.as_ref().unwrap();
+        assert_eq!(outline(foo)[1].2, vec!["get".to_owned()]);
+        assert_eq!(symbols[3].detail.as_deref(), Some("#[test]"));
+
+        // The range of a symbol covers those of its children
+        assert!(symbols[1].range.start <= foo[1].range.start);
+        assert!(symbols[1].range.end >= foo[1].range.end);
+    }
+}
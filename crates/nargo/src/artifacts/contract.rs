@@ -1,6 +1,6 @@
 use acvm::acir::circuit::Circuit;
 use noirc_abi::Abi;
-use noirc_driver::ContractFunctionType;
+use noirc_driver::{ContractFunctionType, DebugInfo};
 use serde::{Deserialize, Serialize};
 
 /// `PreprocessedContract` represents a Noir contract which has been preprocessed by a particular backend proving system.
@@ -14,8 +14,23 @@ pub struct PreprocessedContract {
     pub name: String,
     /// The identifier of the proving backend which this contract has been compiled for.
     pub backend: String,
+    /// The version of the schema in which the ABIs of the contract's functions are written, `0`
+    /// for artifacts written before ABIs were versioned.
+    #[serde(default)]
+    pub abi_version: u32,
     /// Each of the contract's functions are compiled into a separate program stored in this `Vec`.
     pub functions: Vec<PreprocessedContractFunction>,
+    /// The hash of the contents of the artifact, written by `nargo compile` so that builds of a
+    /// contract may be checked against each other.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+impl PreprocessedContract {
+    /// Returns the hash of the contents of the artifact, not including its `hash` field.
+    pub fn content_hash(&self) -> String {
+        super::content_hash(self)
+    }
 }
 
 /// Each function in the contract will be compiled as a separate noir program.
@@ -38,4 +53,9 @@ pub struct PreprocessedContractFunction {
 
     pub proving_key: Option<Vec<u8>>,
     pub verification_key: Option<Vec<u8>>,
+
+    /// The location in the program of each opcode of the bytecode, left out of artifacts
+    /// compiled with `--skip-debug-info`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug_info: Option<DebugInfo>,
 }
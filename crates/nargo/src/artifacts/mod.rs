@@ -6,10 +6,25 @@
 
 use acvm::acir::circuit::Circuit;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
 
 pub mod contract;
 pub mod program;
 
+/// The field of an artifact holding its content hash, which is left out of the hash itself.
+const HASH_FIELD: &str = "hash";
+
+/// Returns the SHA-256 hash of the json serialization of an artifact as hex text, leaving out
+/// the hash embedded in the artifact.
+fn content_hash<T: Serialize>(artifact: &T) -> String {
+    let mut artifact = serde_json::to_value(artifact).expect("artifacts are serializable as json");
+    if let Some(fields) = artifact.as_object_mut() {
+        fields.remove(HASH_FIELD);
+    }
+    let artifact_bytes = serde_json::to_vec(&artifact).unwrap();
+    format!("{:x}", Sha256::digest(artifact_bytes))
+}
+
 // TODO: move these down into ACVM.
 fn serialize_circuit<S>(circuit: &Circuit, s: S) -> Result<S::Ok, S::Error>
 where
@@ -1,5 +1,6 @@
 use acvm::acir::circuit::Circuit;
 use noirc_abi::Abi;
+use noirc_driver::DebugInfo;
 use serde::{Deserialize, Serialize};
 
 /// `PreprocessedProgram` represents a Noir program which has been preprocessed by a particular backend proving system.
@@ -10,6 +11,10 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PreprocessedProgram {
     pub backend: String,
+    /// The version of the schema in which the ABI is written, `0` for artifacts written before
+    /// ABIs were versioned.
+    #[serde(default)]
+    pub abi_version: u32,
     pub abi: Abi,
 
     #[serde(
@@ -20,4 +25,21 @@ pub struct PreprocessedProgram {
 
     pub proving_key: Option<Vec<u8>>,
     pub verification_key: Option<Vec<u8>>,
+
+    /// The location in the program of each opcode of the bytecode, left out of artifacts
+    /// compiled with `--skip-debug-info`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug_info: Option<DebugInfo>,
+
+    /// The hash of the contents of the artifact, written by `nargo compile` so that builds of a
+    /// program may be checked against each other.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+impl PreprocessedProgram {
+    /// Returns the hash of the contents of the artifact, not including its `hash` field.
+    pub fn content_hash(&self) -> String {
+        super::content_hash(self)
+    }
 }
@@ -1,4 +1,5 @@
 use acvm::pwg::OpcodeResolutionError;
+use noirc_abi::errors::AbiError;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -10,4 +11,16 @@ pub enum NargoError {
     /// ACIR circuit solving error
     #[error(transparent)]
     SolvingError(#[from] OpcodeResolutionError),
+
+    /// A constraint with a message failed while solving the circuit
+    #[error("Assertion failed: {message}")]
+    AssertionFailed { message: String, source: OpcodeResolutionError },
+
+    /// A foreign call which nargo cannot resolve, or which was given inputs it cannot use
+    #[error("Failed to resolve foreign call `{function}`: {reason}")]
+    ForeignCallError { function: String, reason: String },
+
+    /// Error while decoding the values passed to the `print` foreign call
+    #[error("Failed to print value: {0}")]
+    PrintError(#[from] AbiError),
 }
@@ -23,4 +23,8 @@ pub enum InvalidPackageError {
     /// Package has both a `main.nr` (for binaries) and `lib.nr` (for libraries)
     #[error("package cannot contain both a `lib.nr` and a `main.nr`")]
     ContainsMultipleCrates,
+
+    /// A feature enables another feature which the package does not declare.
+    #[error("feature `{enabled_by}` enables `{feature}`, which is not declared in [features]")]
+    UnknownFeature { feature: String, enabled_by: String },
 }
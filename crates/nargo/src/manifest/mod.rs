@@ -1,5 +1,7 @@
+use noirc_driver::OverflowMode;
+use noirc_errors::LintLevels;
 use serde::Deserialize;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 mod errors;
 pub use self::errors::InvalidPackageError;
@@ -8,6 +10,19 @@ pub use self::errors::InvalidPackageError;
 pub struct PackageManifest {
     pub package: PackageMetadata,
     pub dependencies: BTreeMap<String, Dependency>,
+    /// Maps each feature of the package to the other features it enables. The `default`
+    /// feature is enabled unless a dependent package opts out of it.
+    #[serde(default)]
+    pub features: BTreeMap<String, Vec<String>>,
+    /// Maps lint names to whether their warnings are allowed, warned about or denied
+    #[serde(default)]
+    pub lints: LintLevels,
+    /// The backend the package is compiled and proven with, unless another is given on the
+    /// command line
+    pub backend: Option<BackendConfig>,
+    /// The remote proving service the package is proven on, unless another is given on the
+    /// command line or in the environment
+    pub remote_prover: Option<RemoteProverConfig>,
 }
 
 impl PackageManifest {
@@ -20,8 +35,39 @@ impl PackageManifest {
 
     pub fn from_toml_str(toml_as_string: &str) -> Result<Self, InvalidPackageError> {
         let manifest = toml::from_str::<PackageManifest>(toml_as_string)?;
+
+        for (feature, enabled_features) in &manifest.features {
+            if let Some(unknown) = enabled_features.iter().find(|f| !manifest.has_feature(f)) {
+                return Err(InvalidPackageError::UnknownFeature {
+                    feature: unknown.clone(),
+                    enabled_by: feature.clone(),
+                });
+            }
+        }
+
         Ok(manifest)
     }
+
+    /// Returns whether the package declares the given feature in its `[features]` table.
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.contains_key(feature)
+    }
+
+    /// Returns the given features along with every feature they transitively enable.
+    pub fn expand_features<'a>(
+        &self,
+        features: impl IntoIterator<Item = &'a String>,
+    ) -> BTreeSet<String> {
+        let mut enabled = BTreeSet::new();
+        let mut queue: Vec<&String> = features.into_iter().collect();
+
+        while let Some(feature) = queue.pop() {
+            if enabled.insert(feature.clone()) {
+                queue.extend(self.features.get(feature).into_iter().flatten());
+            }
+        }
+        enabled
+    }
 }
 
 #[allow(dead_code)]
@@ -37,6 +83,22 @@ pub struct PackageMetadata {
     compiler_version: Option<String>,
     backend: Option<String>,
     license: Option<String>,
+    /// The behaviour of the package's integer arithmetic on overflow
+    #[serde(default)]
+    pub overflow: OverflowMode,
+}
+
+/// The `[backend]` section of a package manifest
+#[derive(Debug, Deserialize, Clone)]
+pub struct BackendConfig {
+    pub name: String,
+}
+
+/// The `[remote_prover]` section of a package manifest. The token authenticating nargo to the
+/// service is only read from the environment, so that it is not committed along with the package.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RemoteProverConfig {
+    pub url: String,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -44,8 +106,42 @@ pub struct PackageMetadata {
 /// Enum representing the different types of ways to
 /// supply a source for the dependency
 pub enum Dependency {
-    Github { git: String, tag: String },
-    Path { path: String },
+    Github {
+        git: String,
+        tag: String,
+        #[serde(default)]
+        features: Vec<String>,
+        #[serde(default = "enabled", rename = "default-features")]
+        default_features: bool,
+    },
+    Path {
+        path: String,
+        #[serde(default)]
+        features: Vec<String>,
+        #[serde(default = "enabled", rename = "default-features")]
+        default_features: bool,
+    },
+}
+
+impl Dependency {
+    /// The features of the dependency which the package enables
+    pub fn features(&self) -> &[String] {
+        match self {
+            Dependency::Github { features, .. } | Dependency::Path { features, .. } => features,
+        }
+    }
+
+    /// Whether the package enables the `default` feature of the dependency
+    pub fn default_features(&self) -> bool {
+        match self {
+            Dependency::Github { default_features, .. }
+            | Dependency::Path { default_features, .. } => *default_features,
+        }
+    }
+}
+
+fn enabled() -> bool {
+    true
 }
 
 #[test]
@@ -64,3 +160,104 @@ fn parse_standard_toml() {
 
     assert!(PackageManifest::from_toml_str(src).is_ok());
 }
+
+#[test]
+fn parse_features() {
+    let src = r#"
+        [package]
+        authors = [""]
+        compiler_version = "0.1"
+
+        [features]
+        default = ["pedersen"]
+        pedersen = []
+        poseidon = []
+        all = ["pedersen", "poseidon"]
+
+        [dependencies]
+        hashes = { path = "../hashes", features = ["poseidon"], default-features = false }
+        other = { path = "../other" }
+    "#;
+
+    let manifest = PackageManifest::from_toml_str(src).unwrap();
+    assert!(!manifest.dependencies["hashes"].default_features());
+    assert_eq!(manifest.dependencies["hashes"].features(), &["poseidon".to_string()]);
+    assert!(manifest.dependencies["other"].default_features());
+
+    let enabled = manifest.expand_features(&["all".to_string(), "default".to_string()]);
+    let expected = ["all", "default", "pedersen", "poseidon"].map(String::from);
+    assert_eq!(enabled, BTreeSet::from(expected));
+
+    let unknown = src.replace("pedersen = []", "");
+    assert!(matches!(
+        PackageManifest::from_toml_str(&unknown),
+        Err(InvalidPackageError::UnknownFeature { .. })
+    ));
+}
+
+#[test]
+fn parse_lints() {
+    use noirc_errors::{Lint, LintLevel};
+
+    let src = r#"
+        [package]
+        authors = [""]
+        compiler_version = "0.1"
+
+        [lints]
+        unused = "deny"
+        deprecated = "allow"
+
+        [dependencies]
+    "#;
+
+    let manifest = PackageManifest::from_toml_str(src).unwrap();
+    assert_eq!(manifest.lints.level(Lint::Unused), LintLevel::Deny);
+    assert_eq!(manifest.lints.level(Lint::Deprecated), LintLevel::Allow);
+    assert_eq!(manifest.lints.level(Lint::UnreachableCode), LintLevel::Warn);
+
+    let unknown = src.replace("deprecated", "unknown_lint");
+    assert!(PackageManifest::from_toml_str(&unknown).is_err());
+}
+
+#[test]
+fn parse_overflow_mode() {
+    let src = r#"
+        [package]
+        authors = [""]
+        compiler_version = "0.1"
+        overflow = "saturating"
+
+        [dependencies]
+    "#;
+
+    let manifest = PackageManifest::from_toml_str(src).unwrap();
+    assert_eq!(manifest.package.overflow, OverflowMode::Saturating);
+
+    let default = src.replace("overflow = \"saturating\"", "");
+    let manifest = PackageManifest::from_toml_str(&default).unwrap();
+    assert_eq!(manifest.package.overflow, OverflowMode::Wrapping);
+
+    let unknown = src.replace("saturating", "clamping");
+    assert!(PackageManifest::from_toml_str(&unknown).is_err());
+}
+
+#[test]
+fn parse_remote_prover() {
+    let src = r#"
+        [package]
+        authors = [""]
+        compiler_version = "0.1"
+
+        [remote_prover]
+        url = "https://prover.example.com"
+
+        [dependencies]
+    "#;
+
+    let manifest = PackageManifest::from_toml_str(src).unwrap();
+    assert_eq!(manifest.remote_prover.unwrap().url, "https://prover.example.com");
+
+    let missing_url = src.replace("url = \"https://prover.example.com\"", "");
+    assert!(PackageManifest::from_toml_str(&missing_url).is_err());
+}
@@ -1,16 +1,56 @@
-use acvm::acir::brillig_vm::ForeignCallResult;
-use acvm::pwg::{ACVMStatus, ForeignCallWaitInfo, ACVM};
-use acvm::BlackBoxFunctionSolver;
+use acvm::acir::brillig_vm::{ForeignCallOutput, ForeignCallResult, Value};
+use acvm::acir::BlackBoxFunc;
+use acvm::pwg::{ACVMStatus, ForeignCallWaitInfo, OpcodeResolutionError, ACVM};
 use acvm::{acir::circuit::Circuit, acir::native_types::WitnessMap};
+use acvm::{BlackBoxFunctionSolver, FieldElement};
+use noirc_abi::{errors::AbiError, printable::PrintableType};
+use noirc_driver::BLACK_BOX_FOREIGN_CALL_PREFIX;
 
 use crate::NargoError;
 
+/// The outcome of executing a circuit of which only some inputs may be given
+#[derive(Debug)]
+pub enum PartialExecution {
+    /// Every witness of the circuit has been solved
+    Solved(WitnessMap),
+    /// Execution stopped at an opcode which depends on inputs which have not been given. The
+    /// witnesses solved until then are kept so that execution may be resumed with more inputs.
+    Incomplete(WitnessMap),
+}
+
 pub fn execute_circuit<B: BlackBoxFunctionSolver + Default>(
-    _backend: &B,
+    backend: &B,
     circuit: Circuit,
     initial_witness: WitnessMap,
 ) -> Result<WitnessMap, NargoError> {
+    match solve_circuit(backend, circuit, initial_witness, false)? {
+        PartialExecution::Solved(solved_witness) => Ok(solved_witness),
+        PartialExecution::Incomplete(_) => {
+            unreachable!("Execution should only stop early when inputs may be missing")
+        }
+    }
+}
+
+/// Executes a circuit like [execute_circuit], except that inputs missing from the initial
+/// witness do not cause execution to fail. Opcodes are solved until one depends on a missing
+/// input, so that execution may be resumed once more inputs are known.
+pub fn execute_circuit_partially<B: BlackBoxFunctionSolver + Default>(
+    backend: &B,
+    circuit: Circuit,
+    initial_witness: WitnessMap,
+) -> Result<PartialExecution, NargoError> {
+    solve_circuit(backend, circuit, initial_witness, true)
+}
+
+fn solve_circuit<B: BlackBoxFunctionSolver + Default>(
+    backend: &B,
+    circuit: Circuit,
+    initial_witness: WitnessMap,
+    partial: bool,
+) -> Result<PartialExecution, NargoError> {
     let mut acvm = ACVM::new(B::default(), circuit.opcodes, initial_witness);
+    // The message of a failing assertion is reported just before the assertion itself fails
+    let mut assert_message = None;
 
     loop {
         let solver_status = acvm.solve();
@@ -20,10 +60,25 @@ pub fn execute_circuit<B: BlackBoxFunctionSolver + Default>(
             ACVMStatus::InProgress => {
                 unreachable!("Execution should not stop while in `InProgress` state.")
             }
-            ACVMStatus::Failure(error) => return Err(error.into()),
+            // Any opcode which cannot be solved may be waiting on inputs which are yet to be
+            // given, whether its unknowns are read directly or through an expression
+            ACVMStatus::Failure(OpcodeResolutionError::OpcodeNotSolvable(_)) if partial => {
+                return Ok(PartialExecution::Incomplete(acvm.witness_map().clone()));
+            }
+            ACVMStatus::Failure(error) => {
+                return Err(match assert_message {
+                    Some(message) => NargoError::AssertionFailed { message, source: error },
+                    None => error.into(),
+                })
+            }
             ACVMStatus::RequiresForeignCall => {
                 while let Some(foreign_call) = acvm.get_pending_foreign_call() {
-                    let foreign_call_result = execute_foreign_call(foreign_call);
+                    let foreign_call_result = if foreign_call.function == "assert_message" {
+                        assert_message = Some(display_printed_values(&foreign_call.inputs)?);
+                        ForeignCallResult { values: vec![] }
+                    } else {
+                        execute_foreign_call(backend, foreign_call)?
+                    };
                     acvm.resolve_pending_foreign_call(foreign_call_result);
                 }
             }
@@ -31,17 +86,35 @@ pub fn execute_circuit<B: BlackBoxFunctionSolver + Default>(
     }
 
     let solved_witness = acvm.finalize();
-    Ok(solved_witness)
+    Ok(PartialExecution::Solved(solved_witness))
 }
 
-fn execute_foreign_call(foreign_call: &ForeignCallWaitInfo) -> ForeignCallResult {
+fn execute_foreign_call(
+    backend: &impl BlackBoxFunctionSolver,
+    foreign_call: &ForeignCallWaitInfo,
+) -> Result<ForeignCallResult, NargoError> {
+    // Unconstrained functions call the black box functions the Brillig VM has no opcodes for
+    // as foreign calls named after them under a reserved prefix, so that oracles sharing the
+    // name of a black box function are not resolved by the backend.
+    if let Some(black_box_func) = foreign_call
+        .function
+        .strip_prefix(BLACK_BOX_FOREIGN_CALL_PREFIX)
+        .and_then(BlackBoxFunc::lookup)
+    {
+        return execute_black_box_foreign_call(backend, black_box_func, foreign_call);
+    }
+
     // TODO(#1615): Nargo only supports "oracle_print_**_impl" functions  that print a singular value or an array and nothing else
     // This should be expanded in a general logging refactor
     match foreign_call.function.as_str() {
+        "print" => {
+            println!("{}", display_printed_values(&foreign_call.inputs)?);
+            Ok(ForeignCallResult { values: vec![] })
+        }
         "oracle_print_impl" => {
             let values = &foreign_call.inputs[0];
             println!("{:?}", values[0].to_field().to_hex());
-            values[0].into()
+            Ok(values[0].into())
         }
         "oracle_print_array_impl" => {
             let mut outputs_hex = Vec::new();
@@ -55,8 +128,190 @@ fn execute_foreign_call(foreign_call: &ForeignCallWaitInfo) -> ForeignCallResult
             let output_witnesses_string = "[".to_owned() + &comma_separated_elements + "]";
             println!("{output_witnesses_string}");
 
-            foreign_call.inputs[0][0].into()
+            Ok(foreign_call.inputs[0][0].into())
+        }
+        _ => Err(NargoError::ForeignCallError {
+            function: foreign_call.function.clone(),
+            reason: "nargo does not resolve this foreign call".to_owned(),
+        }),
+    }
+}
+
+/// The final input to the `print` and `assert_message` foreign calls is the JSON encoded type
+/// of the values before it.
+fn display_printed_values(inputs: &[Vec<Value>]) -> Result<String, AbiError> {
+    let to_fields = |values: &[Vec<Value>]| -> Vec<FieldElement> {
+        values.iter().flatten().map(|value| value.to_field()).collect()
+    };
+
+    let (type_info, values) =
+        inputs.split_last().ok_or_else(|| AbiError::Generic("Nothing was printed".into()))?;
+    let printable_type = PrintableType::from_encoded(&to_fields(std::slice::from_ref(type_info)))?;
+    printable_type.display(&to_fields(values))
+}
+
+fn execute_black_box_foreign_call(
+    backend: &impl BlackBoxFunctionSolver,
+    black_box_func: BlackBoxFunc,
+    foreign_call: &ForeignCallWaitInfo,
+) -> Result<ForeignCallResult, NargoError> {
+    let invalid_inputs = |reason: String| NargoError::ForeignCallError {
+        function: foreign_call.function.clone(),
+        reason,
+    };
+    let fields = |values: &[Value]| -> Vec<FieldElement> {
+        values.iter().map(|value| value.to_field()).collect()
+    };
+    // Integer inputs are range checked rather than truncated
+    let integer = |value: &Value, bit_size: u32| -> Result<u128, NargoError> {
+        let field = value.to_field();
+        if field.num_bits() <= bit_size {
+            Ok(field.to_u128())
+        } else {
+            Err(invalid_inputs(format!("{} does not fit in {bit_size} bits", field.to_hex())))
         }
-        _ => panic!("unexpected foreign call type"),
+    };
+    let bytes = |values: &[Value]| -> Result<Vec<u8>, NargoError> {
+        values.iter().map(|value| integer(value, 8).map(|byte| byte as u8)).collect()
+    };
+    let point = |(x, y): (FieldElement, FieldElement)| ForeignCallResult {
+        values: vec![ForeignCallOutput::Array(vec![x.into(), y.into()])],
+    };
+
+    match (black_box_func, foreign_call.inputs.as_slice()) {
+        (BlackBoxFunc::Pedersen, [message, domain_separator]) if domain_separator.len() == 1 => {
+            let domain_separator = integer(&domain_separator[0], 32)? as u32;
+            Ok(backend.pedersen(&fields(message), domain_separator).map(point)?)
+        }
+        (BlackBoxFunc::SchnorrVerify, [public_key_x, public_key_y, signature, message])
+            if public_key_x.len() == 1 && public_key_y.len() == 1 =>
+        {
+            let verified = backend.schnorr_verify(
+                &public_key_x[0].to_field(),
+                &public_key_y[0].to_field(),
+                &bytes(signature)?,
+                &bytes(message)?,
+            )?;
+            Ok(Value::from(FieldElement::from(u128::from(verified))).into())
+        }
+        (BlackBoxFunc::FixedBaseScalarMul, [input]) if input.len() == 1 => {
+            Ok(backend.fixed_base_scalar_mul(&input[0].to_field()).map(point)?)
+        }
+        _ => {
+            Err(invalid_inputs(format!("unexpected inputs to black box function {black_box_func}")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use acvm::{
+        acir::{
+            brillig_vm::Value,
+            circuit::{opcodes::Opcode, Circuit, PublicInputs},
+            native_types::{Expression, Witness, WitnessMap},
+        },
+        pwg::{ForeignCallWaitInfo, OpcodeResolutionError},
+        BlackBoxFunctionSolver, FieldElement,
+    };
+
+    use super::{execute_circuit_partially, execute_foreign_call, PartialExecution};
+    use crate::NargoError;
+
+    #[derive(Default)]
+    struct MockBackend;
+
+    impl BlackBoxFunctionSolver for MockBackend {
+        fn schnorr_verify(
+            &self,
+            _public_key_x: &FieldElement,
+            _public_key_y: &FieldElement,
+            _signature: &[u8],
+            _message: &[u8],
+        ) -> Result<bool, OpcodeResolutionError> {
+            panic!("Path not trodden by this test")
+        }
+        fn pedersen(
+            &self,
+            _inputs: &[FieldElement],
+            _domain_separator: u32,
+        ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
+            panic!("Path not trodden by this test")
+        }
+        fn fixed_base_scalar_mul(
+            &self,
+            _input: &FieldElement,
+        ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
+            panic!("Path not trodden by this test")
+        }
+    }
+
+    #[test]
+    fn partial_execution_resumes_once_inputs_are_given() {
+        // w3 = w1 + w2, where w1 is given by the first party and w2 by the second
+        let sum = &(&Expression::from(Witness(1)) + &Expression::from(Witness(2)))
+            - &Expression::from(Witness(3));
+        let circuit = Circuit {
+            current_witness_index: 3,
+            opcodes: vec![Opcode::Arithmetic(sum)],
+            public_parameters: PublicInputs(BTreeSet::from([Witness(1), Witness(2)])),
+            return_values: PublicInputs(BTreeSet::from([Witness(3)])),
+        };
+
+        // The sum has two unknowns until w2 is given, so it cannot be solved yet
+        let mut initial_witness = WitnessMap::new();
+        initial_witness.insert(Witness(1), FieldElement::from(2_i128));
+        let partial_witness =
+            match execute_circuit_partially(&MockBackend, circuit.clone(), initial_witness) {
+                Ok(PartialExecution::Incomplete(partial_witness)) => partial_witness,
+                result => panic!("Expected an incomplete execution, got {result:?}"),
+            };
+        assert_eq!(partial_witness.get(&Witness(3)), None);
+
+        let mut resumed_witness = partial_witness;
+        resumed_witness.insert(Witness(2), FieldElement::from(3_i128));
+        let solved_witness = match execute_circuit_partially(&MockBackend, circuit, resumed_witness)
+        {
+            Ok(PartialExecution::Solved(solved_witness)) => solved_witness,
+            result => panic!("Expected a solved execution, got {result:?}"),
+        };
+        assert_eq!(solved_witness.get(&Witness(3)), Some(&FieldElement::from(5_i128)));
+    }
+
+    #[test]
+    fn oracles_named_after_black_box_functions_are_not_resolved_by_the_backend() {
+        // The mock backend panics if asked to compute a pedersen hash
+        let message = vec![Value::from(FieldElement::from(1_i128))];
+        let domain_separator = vec![Value::from(FieldElement::from(0_i128))];
+        let oracle_call = ForeignCallWaitInfo {
+            function: "pedersen".to_owned(),
+            inputs: vec![message.clone(), domain_separator],
+        };
+        assert!(matches!(
+            execute_foreign_call(&MockBackend, &oracle_call),
+            Err(NargoError::ForeignCallError { function, .. }) if function == "pedersen"
+        ));
+
+        // Inputs which do not fit the black box function are reported rather than truncated
+        let out_of_range = vec![Value::from(FieldElement::from(1_i128 << 32))];
+        let black_box_call = ForeignCallWaitInfo {
+            function: "black_box::pedersen".to_owned(),
+            inputs: vec![message.clone(), out_of_range],
+        };
+        assert!(matches!(
+            execute_foreign_call(&MockBackend, &black_box_call),
+            Err(NargoError::ForeignCallError { .. })
+        ));
+
+        let missing_domain_separator = ForeignCallWaitInfo {
+            function: "black_box::pedersen".to_owned(),
+            inputs: vec![message],
+        };
+        assert!(matches!(
+            execute_foreign_call(&MockBackend, &missing_domain_separator),
+            Err(NargoError::ForeignCallError { .. })
+        ));
     }
 }
@@ -1,11 +1,13 @@
 pub use self::codegen_verifier::codegen_verifier;
-pub use self::execute::execute_circuit;
+pub use self::execute::{execute_circuit, execute_circuit_partially, PartialExecution};
 pub use self::preprocess::{preprocess_contract_function, preprocess_program};
 pub use self::prove::prove_execution;
+pub use self::recursion::{recursion_artifacts, RecursionArtifacts};
 pub use self::verify::verify_proof;
 
 mod codegen_verifier;
 mod execute;
 mod preprocess;
 mod prove;
+mod recursion;
 mod verify;
@@ -1,4 +1,5 @@
 use acvm::ProofSystemCompiler;
+use noirc_abi::compatibility::ABI_VERSION;
 use noirc_driver::{CompiledProgram, ContractFunction};
 
 use crate::artifacts::{contract::PreprocessedContractFunction, program::PreprocessedProgram};
@@ -26,10 +27,13 @@ pub fn preprocess_program<B: ProofSystemCompiler>(
 
     Ok(PreprocessedProgram {
         backend: String::from(BACKEND_IDENTIFIER),
+        abi_version: ABI_VERSION,
         abi: compiled_program.abi,
         bytecode: optimized_bytecode,
         proving_key,
         verification_key,
+        debug_info: Some(compiled_program.debug_info),
+        hash: None,
     })
 }
 
@@ -58,5 +62,6 @@ pub fn preprocess_contract_function<B: ProofSystemCompiler>(
         bytecode: optimized_bytecode,
         proving_key,
         verification_key,
+        debug_info: Some(func.debug_info),
     })
 }
@@ -1,13 +1,21 @@
 use acvm::acir::{circuit::Circuit, native_types::WitnessMap};
 use acvm::ProofSystemCompiler;
 
+/// Generates a proof of the execution of a circuit. Proofs generated with `is_recursive` set are
+/// meant to be verified within another circuit.
 pub fn prove_execution<B: ProofSystemCompiler>(
     backend: &B,
     common_reference_string: &[u8],
     circuit: &Circuit,
     solved_witness: WitnessMap,
     proving_key: &[u8],
+    is_recursive: bool,
 ) -> Result<Vec<u8>, B::Error> {
-    // TODO(#1569): update from not just accepting `false` once we get nargo to interop with dynamic backend
-    backend.prove_with_pk(common_reference_string, circuit, solved_witness, proving_key, false)
+    backend.prove_with_pk(
+        common_reference_string,
+        circuit,
+        solved_witness,
+        proving_key,
+        is_recursive,
+    )
 }
@@ -0,0 +1,26 @@
+use acvm::{acir::native_types::WitnessMap, FieldElement, ProofSystemCompiler};
+
+/// The arguments with which `std::verify_proof` verifies a proof within another circuit.
+pub struct RecursionArtifacts {
+    pub verification_key: Vec<FieldElement>,
+    pub proof: Vec<FieldElement>,
+    pub public_inputs: Vec<FieldElement>,
+    pub key_hash: FieldElement,
+}
+
+/// Encodes a proof generated for recursion, along with its public inputs and the verification
+/// key of its circuit, as the field elements which `std::verify_proof` takes.
+pub fn recursion_artifacts<B: ProofSystemCompiler>(
+    backend: &B,
+    common_reference_string: &[u8],
+    proof: &[u8],
+    public_inputs: WitnessMap,
+    verification_key: &[u8],
+) -> Result<RecursionArtifacts, B::Error> {
+    let public_input_values = public_inputs.clone().into_iter().map(|(_, value)| value).collect();
+    let proof = backend.proof_as_fields(proof, public_inputs)?;
+    let (verification_key, key_hash) =
+        backend.vk_as_fields(common_reference_string, verification_key)?;
+
+    Ok(RecursionArtifacts { verification_key, proof, public_inputs: public_input_values, key_hash })
+}
@@ -1,6 +1,7 @@
 use acvm::acir::{circuit::Circuit, native_types::WitnessMap};
 use acvm::ProofSystemCompiler;
 
+/// Verifies a proof, where `is_recursive` must be set for proofs generated for recursion.
 pub fn verify_proof<B: ProofSystemCompiler>(
     backend: &B,
     common_reference_string: &[u8],
@@ -8,14 +9,14 @@ pub fn verify_proof<B: ProofSystemCompiler>(
     proof: &[u8],
     public_inputs: WitnessMap,
     verification_key: &[u8],
+    is_recursive: bool,
 ) -> Result<bool, B::Error> {
-    // TODO(#1569): update from not just accepting `false` once we get nargo to interop with dynamic backend
     backend.verify_with_vk(
         common_reference_string,
         proof,
         public_inputs,
         circuit,
         verification_key,
-        false,
+        is_recursive,
     )
 }
@@ -20,6 +20,8 @@ fn main() {
 
     check_rustc_version();
 
+    set_backend_version();
+
     // Only use build_data if the environment variable isn't set
     // The environment variable is always set when working via Nix
     if std::env::var(GIT_COMMIT).is_err() {
@@ -36,6 +38,27 @@ fn main() {
     generate_tests(&mut test_file, true);
 }
 
+/// Exposes the version of the backend nargo is built with as `BACKEND_VERSION`, taking it from
+/// the workspace's lockfile.
+fn set_backend_version() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let lockfile_path = manifest_dir.join("..").join("..").join("Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lockfile_path.display());
+
+    // Builds outside of the workspace, such as those of the published crate, have no lockfile
+    let version = fs::read_to_string(&lockfile_path)
+        .ok()
+        .and_then(|lockfile| toml::from_str::<toml::Value>(&lockfile).ok())
+        .and_then(|lockfile| {
+            lockfile.get("package")?.as_array()?.iter().find_map(|package| {
+                let is_backend = package.get("name")?.as_str()? == "acvm-backend-barretenberg";
+                is_backend.then(|| package.get("version")?.as_str().map(str::to_owned))?
+            })
+        })
+        .unwrap_or_else(|| "unknown".to_owned());
+    println!("cargo:rustc-env=BACKEND_VERSION={version}");
+}
+
 fn load_conf(conf_path: &Path) -> BTreeMap<String, Vec<String>> {
     let config_str = std::fs::read_to_string(conf_path).unwrap();
 
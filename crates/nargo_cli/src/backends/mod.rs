@@ -0,0 +1,111 @@
+use std::{env, path::PathBuf};
+
+pub(crate) use acvm_backend_barretenberg::Barretenberg as ConcreteBackend;
+
+mod plugin;
+mod remote;
+pub(crate) use plugin::{discover_plugins, BackendPlugin, BackendPluginError};
+pub(crate) use remote::{RemoteProver, RemoteProverError};
+
+#[cfg(not(any(feature = "plonk_bn254", feature = "plonk_bn254_wasm", feature = "flat_witness")))]
+compile_error!("please specify a backend to compile with");
+
+#[cfg(all(feature = "plonk_bn254", feature = "plonk_bn254_wasm"))]
+compile_error!(
+    "feature \"plonk_bn254\"  and feature \"plonk_bn254_wasm\" cannot be enabled at the same time"
+);
+
+/// The name of the backend nargo is built with, used unless another backend is selected.
+pub(crate) const DEFAULT_BACKEND: &str = "acvm-backend-barretenberg";
+
+/// The version of the backend nargo is built with, recorded in the artifacts it generates.
+pub(crate) const DEFAULT_BACKEND_VERSION: &str = env!("BACKEND_VERSION");
+
+/// The backends which nargo is built with. These may be selected with `--backend` along with the
+/// backend plugins installed in the backends directory.
+pub(crate) const BACKENDS: &[&str] = &[DEFAULT_BACKEND];
+
+/// The file in the backends directory holding the name of the backend selected with
+/// `nargo backend use`.
+const SELECTED_BACKEND_FILE: &str = "selected";
+
+/// Returns the directory in which backends keep their files, such as their common reference
+/// strings.
+pub(crate) fn backends_dir() -> PathBuf {
+    match env::var("NARGO_BACKEND_CACHE_DIR") {
+        Ok(cache_dir) => PathBuf::from(cache_dir),
+        Err(_) => dirs::home_dir().unwrap().join(".nargo").join("backends"),
+    }
+}
+
+pub(crate) fn backend_dir(backend_name: &str) -> PathBuf {
+    backends_dir().join(backend_name)
+}
+
+/// Returns the name of the backend a command runs with, which is the selected plugin if any and
+/// otherwise the backend nargo is built with.
+pub(crate) fn resolved_backend_name(backend_plugin: Option<&BackendPlugin>) -> &str {
+    backend_plugin.map_or(DEFAULT_BACKEND, |plugin| plugin.name())
+}
+
+/// Returns the backend selected with `nargo backend use`, if any.
+pub(crate) fn read_selected_backend() -> Option<String> {
+    let selected = std::fs::read_to_string(backends_dir().join(SELECTED_BACKEND_FILE)).ok()?;
+    Some(selected.trim().to_owned())
+}
+
+pub(crate) fn write_selected_backend(backend_name: &str) {
+    let backends_dir = backends_dir();
+    std::fs::create_dir_all(&backends_dir).expect("could not create the `backends` directory");
+    std::fs::write(backends_dir.join(SELECTED_BACKEND_FILE), backend_name)
+        .expect("could not write the selected backend");
+}
+
+/// Returns the name of the backend to run a command with. The backend given on the command line
+/// takes precedence over the backend of the package, followed by the backend selected with
+/// `nargo backend use` and lastly the default backend.
+pub(crate) fn resolve_backend_name(flag: Option<&str>, package_backend: Option<&str>) -> String {
+    choose_backend_name(flag, package_backend, read_selected_backend)
+}
+
+fn choose_backend_name(
+    flag: Option<&str>,
+    package_backend: Option<&str>,
+    selected_backend: impl FnOnce() -> Option<String>,
+) -> String {
+    match flag.or(package_backend) {
+        Some(backend_name) => backend_name.to_owned(),
+        None => selected_backend().unwrap_or_else(|| DEFAULT_BACKEND.to_owned()),
+    }
+}
+
+/// Returns whether nargo is built with a backend or it is installed as a plugin.
+pub(crate) fn is_known_backend(backend_name: &str) -> bool {
+    BACKENDS.contains(&backend_name)
+        || discover_plugins(&backends_dir()).iter().any(|plugin| plugin == backend_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{choose_backend_name, DEFAULT_BACKEND};
+
+    #[test]
+    fn backend_name_precedence() {
+        let selected = || Some("selected".to_owned());
+        let none = || None;
+
+        assert_eq!(choose_backend_name(Some("flag"), Some("package"), selected), "flag");
+        assert_eq!(choose_backend_name(None, Some("package"), selected), "package");
+        assert_eq!(choose_backend_name(None, None, selected), "selected");
+        assert_eq!(choose_backend_name(None, None, none), DEFAULT_BACKEND);
+    }
+
+    #[test]
+    fn selected_backend_is_only_read_without_a_flag_or_package_backend() {
+        let unreachable =
+            || -> Option<String> { panic!("the selected backend should not be read") };
+
+        assert_eq!(choose_backend_name(Some("flag"), None, unreachable), "flag");
+        assert_eq!(choose_backend_name(None, Some("package"), unreachable), "package");
+    }
+}
@@ -0,0 +1,427 @@
+//! Backends which are not built into nargo, but installed as executables in the backends
+//! directory so that they may be released independently of nargo.
+//!
+//! A plugin is a directory of the backends directory holding a `backend.toml` file, which names
+//! the executable of the backend, its version and the version of the plugin interface it speaks:
+//!
+//! ```toml
+//! interface_version = 1
+//! version = "0.1.0"
+//! executable = "bin/backend"
+//! ```
+//!
+//! Nargo runs the executable with a command (`gates`, `prove`, `verify` or `contract`) as its
+//! only argument, writing a json request to its stdin and reading a json response from its
+//! stdout. Bytes are written as hex text, and witness maps as objects mapping witness indices to
+//! hex encoded field elements. A backend signals failure by exiting with a non-zero status,
+//! writing the reason to its stderr.
+//!
+//! Plugins are always executables. Backends built as dynamic libraries are not supported, as
+//! loading them requires unsafe code, which nargo forbids. A manifest naming a `library` instead
+//! of an executable is rejected, and such a backend must be wrapped in an executable speaking
+//! the interface to be installed.
+
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use acvm::acir::{circuit::Circuit, native_types::WitnessMap};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+
+use super::backends_dir;
+
+/// The version of the interface spoken between nargo and backend plugins. This must be increased
+/// whenever the requests or responses of the interface change.
+pub(crate) const PLUGIN_INTERFACE_VERSION: u32 = 1;
+
+const PLUGIN_MANIFEST_FILE: &str = "backend.toml";
+
+#[derive(Debug, Error)]
+pub(crate) enum BackendPluginError {
+    #[error("Error: could not read the plugin manifest {} ({1})", .0.display())]
+    InvalidManifest(PathBuf, String),
+    #[error("Error: backend `{name}` speaks version {version} of the plugin interface, but nargo speaks version {}", PLUGIN_INTERFACE_VERSION)]
+    UnsupportedInterfaceVersion { name: String, version: u32 },
+    #[error("Error: backend `{name}` is a dynamic library, but plugins must be executables")]
+    LibraryPlugin { name: String },
+    #[error("Error: could not run backend `{name}` ({reason})")]
+    CouldNotRun { name: String, reason: String },
+    #[error("Error: backend `{name}` failed to {command}:\n{stderr}")]
+    CommandFailed { name: String, command: &'static str, stderr: String },
+    #[error("Error: backend `{name}` responded to {command} with an invalid response ({reason})")]
+    InvalidResponse { name: String, command: &'static str, reason: String },
+}
+
+#[derive(Deserialize)]
+struct PluginManifest {
+    interface_version: u32,
+    /// The version of the backend, recorded in the artifacts it generates
+    version: String,
+    /// The path of the executable, relative to the directory of the plugin
+    executable: Option<PathBuf>,
+    /// The path of a dynamic library, which is only read to explain that plugins can't be
+    /// libraries
+    library: Option<PathBuf>,
+}
+
+/// A backend installed as an executable in the backends directory.
+#[derive(Debug, Clone)]
+pub(crate) struct BackendPlugin {
+    name: String,
+    version: String,
+    executable: PathBuf,
+}
+
+/// Returns the names of the backend plugins installed in the backends directory.
+pub(crate) fn discover_plugins(backends_dir: &Path) -> Vec<String> {
+    let entries = match std::fs::read_dir(backends_dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut plugins: Vec<_> = entries
+        .flatten()
+        .filter(|entry| entry.path().join(PLUGIN_MANIFEST_FILE).is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    plugins.sort();
+    plugins
+}
+
+impl BackendPlugin {
+    /// Loads the backend plugin of the given name, if one is installed.
+    pub(crate) fn find(name: &str) -> Result<Option<BackendPlugin>, BackendPluginError> {
+        Self::find_in(&backends_dir(), name)
+    }
+
+    /// Loads the backend plugin of the given name from `backends_dir`, if one is installed.
+    fn find_in(
+        backends_dir: &Path,
+        name: &str,
+    ) -> Result<Option<BackendPlugin>, BackendPluginError> {
+        let plugin_dir = backends_dir.join(name);
+        let manifest_path = plugin_dir.join(PLUGIN_MANIFEST_FILE);
+        if !manifest_path.is_file() {
+            return Ok(None);
+        }
+
+        let manifest = std::fs::read_to_string(&manifest_path).map_err(|err| {
+            BackendPluginError::InvalidManifest(manifest_path.clone(), err.to_string())
+        })?;
+        let manifest: PluginManifest = toml::from_str(&manifest).map_err(|err| {
+            BackendPluginError::InvalidManifest(manifest_path.clone(), err.to_string())
+        })?;
+        if manifest.interface_version != PLUGIN_INTERFACE_VERSION {
+            return Err(BackendPluginError::UnsupportedInterfaceVersion {
+                name: name.to_owned(),
+                version: manifest.interface_version,
+            });
+        }
+
+        let executable = match (manifest.executable, manifest.library) {
+            (Some(executable), _) => plugin_dir.join(executable),
+            (None, Some(_)) => {
+                return Err(BackendPluginError::LibraryPlugin { name: name.to_owned() })
+            }
+            (None, None) => {
+                let reason = "missing field `executable`".to_owned();
+                return Err(BackendPluginError::InvalidManifest(manifest_path, reason));
+            }
+        };
+        Ok(Some(BackendPlugin { name: name.to_owned(), version: manifest.version, executable }))
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Returns the size of the circuit in the backend's own constraint system.
+    pub(crate) fn gates(&self, circuit: &Circuit) -> Result<u32, BackendPluginError> {
+        #[derive(Deserialize)]
+        struct GatesResponse {
+            circuit_size: u32,
+        }
+        let request = CircuitRequest { circuit: encode_circuit(circuit) };
+        let response: GatesResponse = self.call("gates", &request)?;
+        Ok(response.circuit_size)
+    }
+
+    pub(crate) fn prove(
+        &self,
+        circuit: &Circuit,
+        witness: &WitnessMap,
+    ) -> Result<Vec<u8>, BackendPluginError> {
+        #[derive(Serialize)]
+        struct ProveRequest {
+            circuit: String,
+            witness: BTreeMap<u32, String>,
+        }
+        #[derive(Deserialize)]
+        struct ProveResponse {
+            proof: String,
+        }
+        let request =
+            ProveRequest { circuit: encode_circuit(circuit), witness: encode_witness_map(witness) };
+        let response: ProveResponse = self.call("prove", &request)?;
+        self.decode_hex("prove", &response.proof)
+    }
+
+    pub(crate) fn verify(
+        &self,
+        circuit: &Circuit,
+        proof: &[u8],
+        public_inputs: &WitnessMap,
+    ) -> Result<bool, BackendPluginError> {
+        #[derive(Serialize)]
+        struct VerifyRequest {
+            circuit: String,
+            proof: String,
+            public_inputs: BTreeMap<u32, String>,
+        }
+        #[derive(Deserialize)]
+        struct VerifyResponse {
+            valid: bool,
+        }
+        let request = VerifyRequest {
+            circuit: encode_circuit(circuit),
+            proof: hex::encode(proof),
+            public_inputs: encode_witness_map(public_inputs),
+        };
+        let response: VerifyResponse = self.call("verify", &request)?;
+        Ok(response.valid)
+    }
+
+    /// Returns the source of a smart contract verifying proofs of the circuit.
+    pub(crate) fn contract(&self, circuit: &Circuit) -> Result<String, BackendPluginError> {
+        #[derive(Deserialize)]
+        struct ContractResponse {
+            contract: String,
+        }
+        let request = CircuitRequest { circuit: encode_circuit(circuit) };
+        let response: ContractResponse = self.call("contract", &request)?;
+        Ok(response.contract)
+    }
+
+    fn call<Request: Serialize, Response: DeserializeOwned>(
+        &self,
+        command: &'static str,
+        request: &Request,
+    ) -> Result<Response, BackendPluginError> {
+        let could_not_run = |err: std::io::Error| BackendPluginError::CouldNotRun {
+            name: self.name.clone(),
+            reason: err.to_string(),
+        };
+        let mut child = Command::new(&self.executable)
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(could_not_run)?;
+
+        // The request is written from another thread while the response is read, as a backend
+        // may write its response before it has read the whole request. Writing the request
+        // first would then block forever once the pipes of both are full.
+        let request = serde_json::to_vec(request).unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        // The stdin of the backend is closed once the request is written
+        let writer = std::thread::spawn(move || stdin.write_all(&request));
+        let output = child.wait_with_output().map_err(could_not_run)?;
+        // A backend may exit without reading the whole request, in which case writing it fails.
+        // Whether the request was understood is left to the status and response of the backend.
+        let _ = writer.join().expect("writing the request should not panic");
+
+        if !output.status.success() {
+            return Err(BackendPluginError::CommandFailed {
+                name: self.name.clone(),
+                command,
+                stderr: String::from_utf8_lossy(&output.stderr).trim_end().to_owned(),
+            });
+        }
+        serde_json::from_slice(&output.stdout).map_err(|err| BackendPluginError::InvalidResponse {
+            name: self.name.clone(),
+            command,
+            reason: err.to_string(),
+        })
+    }
+
+    fn decode_hex(&self, command: &'static str, data: &str) -> Result<Vec<u8>, BackendPluginError> {
+        hex::decode(data).map_err(|err| BackendPluginError::InvalidResponse {
+            name: self.name.clone(),
+            command,
+            reason: err.to_string(),
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct CircuitRequest {
+    circuit: String,
+}
+
+pub(super) fn encode_circuit(circuit: &Circuit) -> String {
+    let mut circuit_bytes = Vec::new();
+    circuit.write(&mut circuit_bytes).unwrap();
+    hex::encode(circuit_bytes)
+}
+
+pub(super) fn encode_witness_map(witness_map: &WitnessMap) -> BTreeMap<u32, String> {
+    let witness_map = witness_map.clone().into_iter();
+    witness_map.map(|(witness, value)| (witness.witness_index(), value.to_hex())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, path::Path};
+
+    use acvm::acir::circuit::{Circuit, PublicInputs};
+    use tempdir::TempDir;
+
+    use super::{
+        discover_plugins, BackendPlugin, BackendPluginError, PLUGIN_INTERFACE_VERSION,
+        PLUGIN_MANIFEST_FILE,
+    };
+
+    fn empty_circuit() -> Circuit {
+        Circuit {
+            current_witness_index: 0,
+            opcodes: Vec::new(),
+            public_parameters: PublicInputs(BTreeSet::new()),
+            return_values: PublicInputs(BTreeSet::new()),
+        }
+    }
+
+    fn write_manifest(plugin_dir: &Path, manifest: &str) {
+        std::fs::create_dir_all(plugin_dir).unwrap();
+        std::fs::write(plugin_dir.join(PLUGIN_MANIFEST_FILE), manifest).unwrap();
+    }
+
+    #[test]
+    fn plugins_are_the_backends_with_a_manifest() {
+        let backends_dir = TempDir::new("backends_dir").unwrap().into_path();
+        for backend in ["plugin", "other_plugin", "built_in"] {
+            std::fs::create_dir(backends_dir.join(backend)).unwrap();
+        }
+        for plugin in ["plugin", "other_plugin"] {
+            let manifest = "interface_version = 1\nversion = \"0.1.0\"\nexecutable = \"backend\"\n";
+            write_manifest(&backends_dir.join(plugin), manifest);
+        }
+
+        assert_eq!(discover_plugins(&backends_dir), vec!["other_plugin", "plugin"]);
+    }
+
+    #[test]
+    fn plugins_of_another_interface_version_are_rejected() {
+        let backends_dir = TempDir::new("backends_dir").unwrap().into_path();
+        let interface_version = PLUGIN_INTERFACE_VERSION + 1;
+        let manifest = format!(
+            "interface_version = {interface_version}\nversion = \"0.1.0\"\nexecutable = \"backend\"\n"
+        );
+        write_manifest(&backends_dir.join("plugin"), &manifest);
+
+        let result = BackendPlugin::find_in(&backends_dir, "plugin");
+        assert!(matches!(
+            result,
+            Err(BackendPluginError::UnsupportedInterfaceVersion { version, .. })
+                if version == interface_version
+        ));
+    }
+
+    #[test]
+    fn plugins_with_an_invalid_manifest_are_rejected() {
+        let backends_dir = TempDir::new("backends_dir").unwrap().into_path();
+        // The manifest is missing the executable of the plugin
+        write_manifest(
+            &backends_dir.join("plugin"),
+            "interface_version = 1\nversion = \"0.1.0\"\n",
+        );
+
+        let result = BackendPlugin::find_in(&backends_dir, "plugin");
+        assert!(matches!(result, Err(BackendPluginError::InvalidManifest(..))));
+        assert!(matches!(BackendPlugin::find_in(&backends_dir, "missing"), Ok(None)));
+    }
+
+    #[test]
+    fn plugins_built_as_dynamic_libraries_are_rejected() {
+        let backends_dir = TempDir::new("backends_dir").unwrap().into_path();
+        write_manifest(
+            &backends_dir.join("plugin"),
+            "interface_version = 1\nversion = \"0.1.0\"\nlibrary = \"libbackend.so\"\n",
+        );
+
+        let result = BackendPlugin::find_in(&backends_dir, "plugin");
+        assert!(
+            matches!(result, Err(BackendPluginError::LibraryPlugin { name }) if name == "plugin")
+        );
+    }
+
+    /// Installs a plugin running the given shell script in a new backends directory.
+    #[cfg(unix)]
+    fn script_plugin(script: &str) -> BackendPlugin {
+        use std::os::unix::fs::PermissionsExt;
+
+        let backends_dir = TempDir::new("backends_dir").unwrap().into_path();
+        let plugin_dir = backends_dir.join("plugin");
+        write_manifest(
+            &plugin_dir,
+            "interface_version = 1\nversion = \"0.1.0\"\nexecutable = \"backend.sh\"\n",
+        );
+        let executable = plugin_dir.join("backend.sh");
+        std::fs::write(&executable, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&executable, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        BackendPlugin::find_in(&backends_dir, "plugin").unwrap().unwrap()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn plugins_are_sent_requests_and_read_responses_as_json() {
+        // The backend answers with the number of bytes of the command and request it was given
+        let plugin = script_plugin(
+            r#"request=$(cat)
+echo "{\"circuit_size\": $(printf '%s%s' "$1" "$request" | wc -c)}""#,
+        );
+
+        let circuit = empty_circuit();
+        let request = serde_json::json!({ "circuit": super::encode_circuit(&circuit) });
+        let expected_size = "gates".len() + request.to_string().len();
+        assert_eq!(plugin.gates(&circuit).unwrap() as usize, expected_size);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn large_responses_are_read_while_the_request_is_written() {
+        // The backend writes its whole response before it reads the request, both of which are
+        // larger than the buffer of a pipe
+        let plugin = script_plugin(
+            r#"printf '{"contract": "'
+head -c 1000000 /dev/zero | tr '\0' 'a'
+printf '"}'
+cat > /dev/null"#,
+        );
+
+        let request = super::CircuitRequest { circuit: "0".repeat(1_000_000) };
+        let response: serde_json::Value = plugin.call("contract", &request).unwrap();
+        assert_eq!(response["contract"].as_str().unwrap().len(), 1_000_000);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_plugins_report_their_stderr() {
+        let plugin = script_plugin("cat > /dev/null\necho 'no proof for you' >&2\nexit 1");
+
+        let result = plugin.contract(&empty_circuit());
+        assert!(matches!(
+            result,
+            Err(BackendPluginError::CommandFailed { command: "contract", stderr, .. })
+                if stderr == "no proof for you"
+        ));
+    }
+}
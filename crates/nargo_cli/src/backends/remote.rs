@@ -0,0 +1,292 @@
+//! Proving on a remote service, so that the common reference string and proving key of large
+//! circuits need not be held locally.
+//!
+//! Nargo submits a proof request as json to `POST <url>/proofs`, which responds with the `id` of
+//! the request. The proof is then polled for at `GET <url>/proofs/<id>`, which responds with a
+//! `status` of `pending`, `failed` along with an `error`, or `done` along with the `proof`.
+//! Circuits and proofs are written as hex text, and witness maps as objects mapping witness
+//! indices to hex encoded field elements. When a token is given, every request carries it in an
+//! `Authorization: Bearer <token>` header.
+//!
+//! Only this json over http interface is supported, there is no gRPC client. Requests are only
+//! sent if nargo is built with the `remote_prover` feature.
+
+use std::{
+    collections::BTreeMap,
+    thread,
+    time::{Duration, Instant},
+};
+
+use acvm::acir::{circuit::Circuit, native_types::WitnessMap};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+
+use super::plugin::{encode_circuit, encode_witness_map};
+
+/// The environment variable holding the url of the remote prover, used unless one is given on
+/// the command line.
+const REMOTE_PROVER_URL_ENV: &str = "NARGO_REMOTE_PROVER_URL";
+
+/// The environment variable holding the token authenticating nargo to the remote prover.
+const REMOTE_PROVER_TOKEN_ENV: &str = "NARGO_REMOTE_PROVER_TOKEN";
+
+/// The time waited between polls for a proof
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Error)]
+pub(crate) enum RemoteProverError {
+    #[cfg(feature = "remote_prover")]
+    #[error("Error: could not reach the remote prover at {url} ({source})")]
+    Request { url: String, source: reqwest::Error },
+    #[error("Error: nargo is built without the `remote_prover` feature, so cannot prove remotely")]
+    Unsupported,
+    #[error("Error: the remote prover failed to prove the program: {0}")]
+    ProvingFailed(String),
+    #[error("Error: the remote prover responded with an invalid proof ({0})")]
+    InvalidProof(hex::FromHexError),
+    #[error("Error: the remote prover did not generate the proof within {} seconds", .0.as_secs())]
+    TimedOut(Duration),
+}
+
+#[derive(Serialize)]
+struct ProofRequest<'a> {
+    backend: &'a str,
+    circuit: String,
+    witness: BTreeMap<u32, String>,
+}
+
+#[derive(Deserialize)]
+struct SubmittedProofRequest {
+    id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum ProofStatus {
+    Pending,
+    Failed { error: String },
+    Done { proof: String },
+}
+
+/// A remote service which generates proofs on behalf of nargo.
+#[derive(Debug, Clone)]
+pub(crate) struct RemoteProver {
+    url: String,
+    #[cfg_attr(not(feature = "remote_prover"), allow(dead_code))]
+    token: Option<String>,
+    /// The time after which nargo stops waiting for a proof
+    timeout: Duration,
+    poll_interval: Duration,
+}
+
+impl RemoteProver {
+    pub(crate) fn new(url: &str, token: Option<String>, timeout: Duration) -> Self {
+        RemoteProver {
+            url: url.trim_end_matches('/').to_owned(),
+            token,
+            timeout,
+            poll_interval: POLL_INTERVAL,
+        }
+    }
+
+    /// Returns the remote prover a package is proven on, if any. The url given on the command
+    /// line takes precedence over the url in the environment, followed by the url in the
+    /// package's manifest. The token is read from the environment.
+    pub(crate) fn resolve(
+        flag: Option<&str>,
+        package_url: Option<&str>,
+        timeout: Duration,
+    ) -> Option<Self> {
+        let env_url = std::env::var(REMOTE_PROVER_URL_ENV).ok();
+        let url = flag.or(env_url.as_deref()).or(package_url)?;
+        let token = std::env::var(REMOTE_PROVER_TOKEN_ENV).ok();
+        Some(RemoteProver::new(url, token, timeout))
+    }
+
+    /// Submits a circuit and its solved witness to the remote prover, waiting for the proof to
+    /// be generated by the given backend.
+    pub(crate) fn prove(
+        &self,
+        backend: &str,
+        circuit: &Circuit,
+        witness: &WitnessMap,
+    ) -> Result<Vec<u8>, RemoteProverError> {
+        let request = ProofRequest {
+            backend,
+            circuit: encode_circuit(circuit),
+            witness: encode_witness_map(witness),
+        };
+
+        let proofs_url = format!("{}/proofs", self.url);
+        let submitted: SubmittedProofRequest = self.request_json(&proofs_url, Some(&request))?;
+
+        let proof_url = format!("{proofs_url}/{}", submitted.id);
+        println!("Proof request submitted to {proof_url}, waiting for the proof");
+        let submitted_at = Instant::now();
+        loop {
+            match self.request_json(&proof_url, None)? {
+                ProofStatus::Pending if submitted_at.elapsed() >= self.timeout => {
+                    return Err(RemoteProverError::TimedOut(self.timeout))
+                }
+                ProofStatus::Pending => thread::sleep(self.poll_interval),
+                ProofStatus::Failed { error } => {
+                    return Err(RemoteProverError::ProvingFailed(error))
+                }
+                ProofStatus::Done { proof } => {
+                    return hex::decode(proof).map_err(RemoteProverError::InvalidProof)
+                }
+            }
+        }
+    }
+
+    /// Posts the given proof request to `url`, or gets `url` if there is none, reading the json
+    /// response.
+    #[cfg(feature = "remote_prover")]
+    fn request_json<Response: DeserializeOwned>(
+        &self,
+        url: &str,
+        proof_request: Option<&ProofRequest>,
+    ) -> Result<Response, RemoteProverError> {
+        let client = reqwest::blocking::Client::new();
+        let mut request = match proof_request {
+            Some(proof_request) => client.post(url).json(proof_request),
+            None => client.get(url),
+        };
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        request
+            .send()
+            .and_then(|response| response.error_for_status()?.json())
+            .map_err(|source| RemoteProverError::Request { url: url.to_owned(), source })
+    }
+
+    #[cfg(not(feature = "remote_prover"))]
+    fn request_json<Response: DeserializeOwned>(
+        &self,
+        _url: &str,
+        _proof_request: Option<&ProofRequest>,
+    ) -> Result<Response, RemoteProverError> {
+        Err(RemoteProverError::Unsupported)
+    }
+}
+
+#[cfg(all(test, feature = "remote_prover"))]
+mod tests {
+    use std::{
+        collections::BTreeSet,
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::mpsc,
+        thread,
+        time::Duration,
+    };
+
+    use acvm::acir::{
+        circuit::{Circuit, PublicInputs},
+        native_types::WitnessMap,
+    };
+
+    use super::{RemoteProver, RemoteProverError};
+
+    /// Serves the given json responses in order, one per connection, sending the request line
+    /// and authorization header of each request received.
+    fn serve(responses: Vec<&'static str>) -> (String, mpsc::Receiver<(String, Option<String>)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for response in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let (mut content_length, mut authorization) = (0, None);
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    let header = header.trim_end();
+                    if header.is_empty() {
+                        break;
+                    }
+                    let (name, value) = header.split_once(": ").unwrap();
+                    match name.to_lowercase().as_str() {
+                        "content-length" => content_length = value.parse().unwrap(),
+                        "authorization" => authorization = Some(value.to_owned()),
+                        _ => (),
+                    }
+                }
+                reader.read_exact(&mut vec![0; content_length]).unwrap();
+                sender.send((request_line.trim_end().to_owned(), authorization)).unwrap();
+
+                let mut stream = reader.into_inner();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                    response.len()
+                )
+                .unwrap();
+            }
+        });
+
+        (url, receiver)
+    }
+
+    fn empty_circuit() -> Circuit {
+        Circuit {
+            current_witness_index: 0,
+            opcodes: Vec::new(),
+            public_parameters: PublicInputs(BTreeSet::new()),
+            return_values: PublicInputs(BTreeSet::new()),
+        }
+    }
+
+    #[test]
+    fn proofs_are_polled_for_until_done() {
+        let (url, requests) = serve(vec![
+            r#"{"id": "7"}"#,
+            r#"{"status": "pending"}"#,
+            r#"{"status": "done", "proof": "0102"}"#,
+        ]);
+        let mut prover =
+            RemoteProver::new(&url, Some("secret".to_owned()), Duration::from_secs(60));
+        prover.poll_interval = Duration::from_millis(1);
+
+        let proof = prover.prove("backend", &empty_circuit(), &WitnessMap::new()).unwrap();
+        assert_eq!(proof, vec![1, 2]);
+
+        let requests: Vec<_> = requests.iter().collect();
+        let bearer = Some("Bearer secret".to_owned());
+        assert_eq!(
+            requests,
+            vec![
+                ("POST /proofs HTTP/1.1".to_owned(), bearer.clone()),
+                ("GET /proofs/7 HTTP/1.1".to_owned(), bearer.clone()),
+                ("GET /proofs/7 HTTP/1.1".to_owned(), bearer),
+            ]
+        );
+    }
+
+    #[test]
+    fn polling_stops_after_the_timeout() {
+        let (url, _requests) = serve(vec![r#"{"id": "7"}"#, r#"{"status": "pending"}"#]);
+        let prover = RemoteProver::new(&url, None, Duration::ZERO);
+
+        let result = prover.prove("backend", &empty_circuit(), &WitnessMap::new());
+        assert!(matches!(result, Err(RemoteProverError::TimedOut(_))));
+    }
+
+    #[test]
+    fn failed_proofs_are_reported() {
+        let (url, _requests) =
+            serve(vec![r#"{"id": "7"}"#, r#"{"status": "failed", "error": "out of memory"}"#]);
+        let prover = RemoteProver::new(&url, None, Duration::from_secs(60));
+
+        let result = prover.prove("backend", &empty_circuit(), &WitnessMap::new());
+        assert!(
+            matches!(result, Err(RemoteProverError::ProvingFailed(error)) if error == "out of memory")
+        );
+    }
+}
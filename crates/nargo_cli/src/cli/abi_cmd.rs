@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use acvm::Backend;
+use clap::{Args, Subcommand};
+use noirc_abi::{compatibility::ABI_VERSION, Abi};
+use noirc_driver::CompileOptions;
+use serde::Deserialize;
+
+use super::{compile_cmd::compile_circuit, NargoConfig};
+use crate::errors::{CliError, FilesystemError};
+
+/// Inspects the ABI of the program
+#[derive(Debug, Clone, Args)]
+pub(crate) struct AbiCommand {
+    #[command(subcommand)]
+    command: AbiSubcommand,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum AbiSubcommand {
+    Check(CheckAbiCommand),
+}
+
+/// Checks that the ABI of the program is compatible with the ABI of a previously compiled
+/// artifact, reporting the changes which break its callers
+#[derive(Debug, Clone, Args)]
+struct CheckAbiCommand {
+    /// The build artifact of the previous version of the program
+    #[arg(long)]
+    against: PathBuf,
+
+    #[clap(flatten)]
+    compile_options: CompileOptions,
+}
+
+/// The parts of a program's build artifact needed to compare ABIs, which allows the ABIs of
+/// artifacts to be read regardless of how their bytecode was written.
+#[derive(Deserialize)]
+struct AbiArtifact {
+    #[serde(default)]
+    abi_version: u32,
+    abi: Abi,
+}
+
+pub(crate) fn run<B: Backend>(
+    backend: &B,
+    args: AbiCommand,
+    config: NargoConfig,
+) -> Result<(), CliError<B>> {
+    match args.command {
+        AbiSubcommand::Check(args) => check_abi(backend, args, config),
+    }
+}
+
+fn check_abi<B: Backend>(
+    backend: &B,
+    args: CheckAbiCommand,
+    config: NargoConfig,
+) -> Result<(), CliError<B>> {
+    let artifact = std::fs::read(&args.against)
+        .map_err(|_| FilesystemError::PathNotValid(args.against.clone()))?;
+    let artifact: AbiArtifact = serde_json::from_slice(&artifact).map_err(|error| {
+        CliError::Generic(format!("Could not read the ABI of {}: {error}", args.against.display()))
+    })?;
+    if artifact.abi_version > ABI_VERSION {
+        return Err(CliError::UnsupportedAbiVersion(args.against, artifact.abi_version));
+    }
+
+    let program = compile_circuit(backend, config.program_dir.as_ref(), &args.compile_options)?;
+
+    let changes = program.abi.breaking_changes(&artifact.abi);
+    if !changes.is_empty() {
+        return Err(CliError::IncompatibleAbi(args.against, changes));
+    }
+    println!("The ABI is compatible with {}", args.against.display());
+    Ok(())
+}
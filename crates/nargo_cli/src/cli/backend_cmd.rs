@@ -0,0 +1,122 @@
+use std::path::{Path, PathBuf};
+
+use acvm::Backend;
+use clap::{Args, Subcommand};
+
+use super::NargoConfig;
+use crate::{
+    backends::{
+        backend_dir, backends_dir, discover_plugins, is_known_backend, read_selected_backend,
+        write_selected_backend, BackendPlugin, BACKENDS, DEFAULT_BACKEND,
+    },
+    errors::CliError,
+};
+
+/// Manages the backends which programs are proven with
+///
+/// Besides the backends nargo is built with, backends may be installed as plugins, which are
+/// executables speaking a versioned interface with nargo. Backends built as dynamic libraries
+/// are not supported, and must be wrapped in an executable to be installed.
+#[derive(Debug, Clone, Args)]
+pub(crate) struct BackendCommand {
+    #[command(subcommand)]
+    command: BackendSubcommand,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum BackendSubcommand {
+    List(ListBackendsCommand),
+    Install(InstallBackendCommand),
+    Use(UseBackendCommand),
+}
+
+/// Lists the backends nargo is built with and the backend plugins installed, marking the backend
+/// which is used by default
+#[derive(Debug, Clone, Args)]
+struct ListBackendsCommand;
+
+/// Installs a backend plugin from a directory. Only plugins are installed, as the backends nargo
+/// is built with are part of nargo and always available
+#[derive(Debug, Clone, Args)]
+struct InstallBackendCommand {
+    /// The name of the backend
+    backend: String,
+
+    /// The directory holding the plugin's `backend.toml`, which names the executable of the
+    /// plugin. Dynamic libraries are not supported
+    #[arg(long)]
+    path: PathBuf,
+}
+
+/// Selects the backend used by packages which do not declare a backend in their Nargo.toml
+#[derive(Debug, Clone, Args)]
+struct UseBackendCommand {
+    /// The name of the backend
+    backend: String,
+}
+
+pub(crate) fn run<B: Backend>(
+    _backend: &B,
+    args: BackendCommand,
+    _config: NargoConfig,
+) -> Result<(), CliError<B>> {
+    match args.command {
+        BackendSubcommand::List(_) => {
+            let selected = read_selected_backend().unwrap_or_else(|| DEFAULT_BACKEND.to_owned());
+            for backend_name in BACKENDS {
+                let marker = if *backend_name == selected { "*" } else { " " };
+                println!("{marker} {backend_name} (built in)");
+            }
+            for plugin_name in discover_plugins(&backends_dir()) {
+                let marker = if plugin_name == selected { "*" } else { " " };
+                println!("{marker} {plugin_name} (plugin)");
+            }
+            Ok(())
+        }
+        BackendSubcommand::Install(InstallBackendCommand { backend, path }) => {
+            if BACKENDS.contains(&backend.as_str()) {
+                return Err(CliError::Generic(format!(
+                    "Error: nargo is built with backend `{backend}`, which needs no installation and cannot be replaced by a plugin"
+                )));
+            }
+            let plugin_dir = backend_dir(&backend);
+            // Plugins which cannot be copied or loaded are not left installed
+            if let Err(err) = copy_dir(&path, &plugin_dir) {
+                std::fs::remove_dir_all(&plugin_dir).ok();
+                return Err(CliError::Generic(format!(
+                    "Error: could not install backend `{backend}` from {} ({err})",
+                    path.display()
+                )));
+            }
+            if let Err(err) = BackendPlugin::find(&backend) {
+                std::fs::remove_dir_all(&plugin_dir).ok();
+                return Err(err.into());
+            }
+            println!("Backend {backend} installed from {}", path.display());
+            Ok(())
+        }
+        BackendSubcommand::Use(args) => {
+            if !is_known_backend(&args.backend) {
+                return Err(CliError::UnknownBackend(args.backend));
+            }
+            write_selected_backend(&args.backend);
+            println!("Backend {} is now used by default", args.backend);
+            Ok(())
+        }
+    }
+}
+
+/// Copies the contents of the directory `source` into `destination`, creating it if needed.
+fn copy_dir(source: &Path, destination: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(destination)?;
+    for entry in std::fs::read_dir(source)? {
+        let path = entry?.path();
+        let destination = destination.join(path.file_name().expect("entries have a name"));
+        if path.is_dir() {
+            copy_dir(&path, &destination)?;
+        } else {
+            std::fs::copy(&path, &destination)?;
+        }
+    }
+    Ok(())
+}
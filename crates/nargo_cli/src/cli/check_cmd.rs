@@ -1,11 +1,12 @@
 use crate::{errors::CliError, resolver::resolve_root_manifest};
 use acvm::Backend;
 use clap::Args;
-use iter_extended::btree_map;
+use iter_extended::vecmap;
 use noirc_abi::{AbiParameter, AbiType, MAIN_RETURN_NAME};
-use noirc_driver::{check_crate, compute_function_signature, CompileOptions};
+use noirc_driver::{check_crate, compute_function_signature, set_overflow_mode, CompileOptions};
 use noirc_errors::reporter::ReportedErrors;
-use noirc_frontend::hir::Context;
+use noirc_frontend::{graph::LOCAL_CRATE, hir::Context};
+use std::fmt::Write;
 use std::path::{Path, PathBuf};
 
 use super::fs::write_to_file;
@@ -37,7 +38,7 @@ fn check_from_path<B: Backend>(
     compile_options: &CompileOptions,
 ) -> Result<(), CliError<B>> {
     let mut context = resolve_root_manifest(program_dir)?;
-    check_crate_and_report_errors(&mut context, compile_options.deny_warnings, compile_options.experimental_ssa)?;
+    check_crate_and_report_errors(&mut context, compile_options)?;
 
     // XXX: We can have a --overwrite flag to determine if you want to overwrite the Prover/Verifier.toml files
     if let Some((parameters, return_type)) = compute_function_signature(&context) {
@@ -66,39 +67,59 @@ fn check_from_path<B: Backend>(
     Ok(())
 }
 
-/// Generates the contents of a toml file with fields for each of the passed parameters.
+/// Generates the contents of a toml file with fields for each of the passed parameters, in
+/// the shape of their types. Each value is a placeholder followed by a comment naming its type.
 fn create_input_toml_template(
     parameters: Vec<AbiParameter>,
     return_type: Option<AbiType>,
 ) -> String {
-    /// Returns a default placeholder `toml::Value` for `typ` which
-    /// complies with the structure of the specified `AbiType`.
-    fn default_value(typ: AbiType) -> toml::Value {
-        match typ {
-            AbiType::Array { length, typ } => {
-                let default_value_vec = std::iter::repeat(default_value(*typ))
-                    .take(length.try_into().unwrap())
-                    .collect();
-                toml::Value::Array(default_value_vec)
-            }
-            AbiType::Struct { fields } => {
-                let default_value_map = toml::map::Map::from_iter(
-                    fields.into_iter().map(|(name, typ)| (name, default_value(typ))),
-                );
-                toml::Value::Table(default_value_map)
-            }
-            _ => toml::Value::String("".to_owned()),
-        }
+    let mut fields = vecmap(parameters, |AbiParameter { name, typ, .. }| (name, typ));
+    if let Some(typ) = return_type {
+        fields.push((MAIN_RETURN_NAME.to_owned(), typ));
     }
 
-    let mut map =
-        btree_map(parameters, |AbiParameter { name, typ, .. }| (name, default_value(typ)));
+    let mut template = String::new();
+    write_toml_table(&mut template, None, &fields);
+    template
+}
 
-    if let Some(typ) = return_type {
-        map.insert(MAIN_RETURN_NAME.to_owned(), default_value(typ));
+/// Writes the fields of a table, where structs are written as nested tables.
+fn write_toml_table(template: &mut String, table_name: Option<&str>, fields: &[(String, AbiType)]) {
+    // Every key following the header of a table belongs to it, so values come before tables.
+    for (name, typ) in fields.iter().filter(|(_, typ)| !matches!(typ, AbiType::Struct { .. })) {
+        writeln!(template, "{name} = {} # {typ}", default_value(typ)).unwrap();
+    }
+
+    for (name, typ) in fields {
+        if let AbiType::Struct { fields } = typ {
+            let name = match table_name {
+                Some(table_name) => format!("{table_name}.{name}"),
+                None => name.clone(),
+            };
+            if !template.is_empty() {
+                template.push('\n');
+            }
+            writeln!(template, "[{name}]").unwrap();
+            write_toml_table(template, Some(&name), fields);
+        }
     }
+}
 
-    toml::to_string(&map).unwrap()
+/// Returns a placeholder toml value which complies with the structure of `typ`.
+fn default_value(typ: &AbiType) -> String {
+    match typ {
+        AbiType::Array { length, typ } => {
+            let elements = vec![default_value(typ); *length as usize];
+            format!("[{}]", elements.join(", "))
+        }
+        AbiType::Tuple { fields } => format!("[{}]", vecmap(fields, default_value).join(", ")),
+        AbiType::Struct { fields } if fields.is_empty() => "{}".to_owned(),
+        AbiType::Struct { fields } => {
+            let fields = vecmap(fields, |(name, typ)| format!("{name} = {}", default_value(typ)));
+            format!("{{ {} }}", fields.join(", "))
+        }
+        _ => "\"\"".to_owned(),
+    }
 }
 
 #[cfg(test)]
@@ -140,18 +161,56 @@ mod tests {
 
         let toml_str = create_input_toml_template(parameters, None);
 
-        let expected_toml_str = r#"a = ""
-b = ""
-c = ["", ""]
-e = ""
+        let expected_toml_str = r#"a = "" # Field
+b = "" # u32
+c = ["", ""] # [Field; 2]
+e = "" # bool
 
 [d]
-d1 = ""
-d2 = ["", "", ""]
+d1 = "" # Field
+d2 = ["", "", ""] # [Field; 3]
 "#;
         assert_eq!(toml_str, expected_toml_str);
     }
 
+    #[test]
+    fn nested_structs_are_written_as_nested_tables() {
+        let account = AbiType::Struct {
+            fields: vec![
+                (String::from("owner"), AbiType::Field),
+                (String::from("balance"), AbiType::Integer { sign: Sign::Unsigned, width: 8 }),
+            ],
+        };
+        let parameters = vec![AbiParameter {
+            name: String::from("bank"),
+            typ: AbiType::Struct {
+                fields: vec![
+                    (String::from("vault"), account.clone()),
+                    (
+                        String::from("accounts"),
+                        AbiType::Array { length: 2, typ: Box::new(account) },
+                    ),
+                ],
+            },
+            visibility: AbiVisibility::Public,
+        }];
+        let return_type = AbiType::Tuple { fields: vec![AbiType::Boolean, AbiType::Field] };
+
+        let toml_str = create_input_toml_template(parameters, Some(return_type));
+
+        let expected_toml_str = r#"return = ["", ""] # (bool, Field)
+
+[bank]
+accounts = [{ owner = "", balance = "" }, { owner = "", balance = "" }] # [{ owner: Field, balance: u8 }; 2]
+
+[bank.vault]
+owner = "" # Field
+balance = "" # u8
+"#;
+        assert_eq!(toml_str, expected_toml_str);
+        assert!(toml::from_str::<toml::Value>(&toml_str).is_ok());
+    }
+
     #[test]
     fn pass() {
         let pass_dir =
@@ -213,9 +272,13 @@ d2 = ["", "", ""]
 /// and errors found.
 pub(crate) fn check_crate_and_report_errors(
     context: &mut Context,
-    deny_warnings: bool,
-    enable_slices: bool,
+    compile_options: &CompileOptions,
 ) -> Result<(), ReportedErrors> {
-    let result = check_crate(context, deny_warnings, enable_slices).map(|warnings| ((), warnings));
-    super::compile_cmd::report_errors(result, context, deny_warnings)
+    if let Some(overflow) = compile_options.overflow {
+        set_overflow_mode(context, LOCAL_CRATE, overflow);
+    }
+    let result =
+        check_crate(context, compile_options.deny_warnings, compile_options.experimental_ssa)
+            .map(|warnings| ((), warnings));
+    super::compile_cmd::report_errors(result, context, compile_options)
 }
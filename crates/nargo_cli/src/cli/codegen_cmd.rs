@@ -0,0 +1,43 @@
+use acvm::Backend;
+use clap::{Args, ValueEnum};
+use noirc_abi::bindings::generate_rust_bindings;
+use noirc_driver::CompileOptions;
+
+use super::fs::{create_named_dir, write_to_file};
+use super::NargoConfig;
+use crate::{cli::compile_cmd::compile_circuit, constants::CODEGEN_DIR, errors::CliError};
+
+/// Generates bindings for the ABI of the program
+#[derive(Debug, Clone, Args)]
+pub(crate) struct CodegenCommand {
+    /// The language of the bindings
+    #[arg(long, value_enum)]
+    lang: Language,
+
+    #[clap(flatten)]
+    compile_options: CompileOptions,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+enum Language {
+    Rust,
+}
+
+pub(crate) fn run<B: Backend>(
+    backend: &B,
+    args: CodegenCommand,
+    config: NargoConfig,
+) -> Result<(), CliError<B>> {
+    let program = compile_circuit(backend, config.program_dir.as_ref(), &args.compile_options)?;
+
+    let (bindings, bindings_file) = match args.lang {
+        Language::Rust => (generate_rust_bindings(&program.abi), "abi.rs"),
+    };
+
+    let codegen_dir = config.program_dir.join(CODEGEN_DIR);
+    create_named_dir(&codegen_dir, "codegen");
+
+    let path = write_to_file(bindings.as_bytes(), &codegen_dir.join(bindings_file));
+    println!("Bindings successfully created and located at {path}");
+    Ok(())
+}
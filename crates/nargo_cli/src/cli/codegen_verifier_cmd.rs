@@ -1,4 +1,5 @@
 use super::fs::{
+    artifact::{write_artifact, ArtifactFormat, ArtifactMetadata},
     common_reference_string::{
         read_cached_common_reference_string, update_common_reference_string,
         write_cached_common_reference_string,
@@ -9,13 +10,16 @@ use super::fs::{
 };
 use super::NargoConfig;
 use crate::{
-    cli::compile_cmd::compile_circuit, constants::CONTRACT_DIR, constants::TARGET_DIR,
+    backends::{resolved_backend_name, DEFAULT_BACKEND_VERSION},
+    cli::compile_cmd::compile_circuit,
+    constants::{CONTRACT_DIR, TARGET_DIR, VERIFICATION_KEY_EXT},
     errors::CliError,
 };
 use acvm::Backend;
 use clap::Args;
 use nargo::ops::{codegen_verifier, preprocess_program};
 use noirc_driver::CompileOptions;
+use std::path::{Path, PathBuf};
 
 /// Generates a Solidity verifier smart contract for the program
 #[derive(Debug, Clone, Args)]
@@ -23,6 +27,11 @@ pub(crate) struct CodegenVerifierCommand {
     /// The name of the circuit build files (ACIR, proving and verification keys)
     circuit_name: Option<String>,
 
+    /// The format in which to write the verification key alongside the contract. The key is not
+    /// written unless a format is given
+    #[arg(long, value_enum)]
+    artifact_format: Option<ArtifactFormat>,
+
     #[clap(flatten)]
     compile_options: CompileOptions,
 }
@@ -37,7 +46,27 @@ pub(crate) fn run<B: Backend>(
         .circuit_name
         .map(|circuit_name| config.program_dir.join(TARGET_DIR).join(circuit_name));
 
-    let common_reference_string = read_cached_common_reference_string();
+    if let Some(backend_plugin) = &config.backend_plugin {
+        if args.artifact_format.is_some() {
+            return Err(CliError::Generic(format!(
+                "Error: backend `{}` does not expose its verification key",
+                backend_plugin.name()
+            )));
+        }
+        let bytecode = match circuit_build_path {
+            Some(circuit_build_path) => read_program_from_file(circuit_build_path)?.bytecode,
+            None => {
+                compile_circuit(backend, config.program_dir.as_ref(), &args.compile_options)?
+                    .circuit
+            }
+        };
+        let smart_contract_string = backend_plugin.contract(&bytecode)?;
+        save_contract_to_dir(&smart_contract_string, &config.program_dir);
+        return Ok(());
+    }
+
+    let backend_name = resolved_backend_name(config.backend_plugin.as_ref());
+    let common_reference_string = read_cached_common_reference_string(backend_name);
 
     let (common_reference_string, preprocessed_program) = match circuit_build_path {
         Some(circuit_build_path) => {
@@ -62,6 +91,11 @@ pub(crate) fn run<B: Backend>(
         }
     };
 
+    let metadata = ArtifactMetadata::new(
+        &preprocessed_program.backend,
+        DEFAULT_BACKEND_VERSION,
+        &preprocessed_program.bytecode,
+    );
     let verification_key = preprocessed_program
         .verification_key
         .expect("Verification key should exist as `true` is passed to `preprocess_program`");
@@ -69,13 +103,24 @@ pub(crate) fn run<B: Backend>(
         codegen_verifier(backend, &common_reference_string, &verification_key)
             .map_err(CliError::SmartContractError)?;
 
-    write_cached_common_reference_string(&common_reference_string);
+    write_cached_common_reference_string(backend_name, &common_reference_string);
+
+    let contract_path = save_contract_to_dir(&smart_contract_string, &config.program_dir);
 
-    let contract_dir = config.program_dir.join(CONTRACT_DIR);
+    if let Some(artifact_format) = args.artifact_format {
+        let key_path = contract_path.with_extension(VERIFICATION_KEY_EXT);
+        write_artifact(&verification_key, &key_path, artifact_format, &metadata);
+        println!("Verification key written to {}", key_path.display());
+    }
+    Ok(())
+}
+
+fn save_contract_to_dir(smart_contract_string: &str, program_dir: &Path) -> PathBuf {
+    let contract_dir = program_dir.join(CONTRACT_DIR);
     create_named_dir(&contract_dir, "contract");
     let contract_path = contract_dir.join("plonk_vk").with_extension("sol");
 
     let path = write_to_file(smart_contract_string.as_bytes(), &contract_path);
     println!("Contract successfully created and located at {path}");
-    Ok(())
+    contract_path
 }
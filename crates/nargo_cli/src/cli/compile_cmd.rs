@@ -1,10 +1,12 @@
 use acvm::Backend;
 use iter_extended::try_vecmap;
 use nargo::artifacts::contract::PreprocessedContract;
+use noirc_abi::compatibility::ABI_VERSION;
 use noirc_driver::{
-    compile_contracts, compile_main, CompileOptions, CompiledProgram, ErrorsAndWarnings, Warnings,
+    compile_contracts, compile_main, CompileOptions, CompiledProgram, ErrorsAndWarnings,
+    MessageFormat, Warnings,
 };
-use noirc_errors::reporter::ReportedErrors;
+use noirc_errors::reporter::{self, location_to_string, ReportedErrors};
 use noirc_frontend::hir::Context;
 use std::path::Path;
 
@@ -12,14 +14,21 @@ use clap::Args;
 
 use nargo::ops::{preprocess_contract_function, preprocess_program};
 
-use crate::{constants::TARGET_DIR, errors::CliError, resolver::resolve_root_manifest};
+use crate::{
+    backends::resolved_backend_name,
+    constants::{BRILLIG_DIR, SSA_DIR, TARGET_DIR},
+    errors::CliError,
+    resolver::resolve_root_manifest,
+};
 
 use super::fs::{
+    brillig::save_brillig_disassembly_to_dir,
     common_reference_string::{
         read_cached_common_reference_string, update_common_reference_string,
         write_cached_common_reference_string,
     },
     program::{save_contract_to_file, save_program_to_file},
+    ssa::save_ssa_passes_to_dir,
 };
 use super::NargoConfig;
 
@@ -36,10 +45,19 @@ pub(crate) struct CompileCommand {
     #[arg(long)]
     include_keys: bool,
 
+    /// Leave out the source location of each opcode from the build artifacts.
+    #[arg(long)]
+    skip_debug_info: bool,
+
     /// Compile each contract function used within the program
     #[arg(short, long)]
     contracts: bool,
 
+    /// Fail unless the hash of the compiled program matches the given hash, such as the hash
+    /// embedded in the artifact of a deployed circuit. The artifact is not written on failure
+    #[arg(long, value_name = "HASH", conflicts_with = "contracts")]
+    check_hash: Option<String>,
+
     #[clap(flatten)]
     compile_options: CompileOptions,
 }
@@ -51,7 +69,8 @@ pub(crate) fn run<B: Backend>(
 ) -> Result<(), CliError<B>> {
     let circuit_dir = config.program_dir.join(TARGET_DIR);
 
-    let mut common_reference_string = read_cached_common_reference_string();
+    let backend_name = resolved_backend_name(config.backend_plugin.as_ref());
+    let mut common_reference_string = read_cached_common_reference_string(backend_name);
 
     // If contracts is set we're compiling every function in a 'contract' rather than just 'main'.
     if args.contracts {
@@ -63,7 +82,7 @@ pub(crate) fn run<B: Backend>(
             &|op| backend.supports_opcode(op),
             &args.compile_options,
         );
-        let contracts = report_errors(result, &context, args.compile_options.deny_warnings)?;
+        let contracts = report_errors(result, &context, &args.compile_options)?;
 
         // TODO(#1389): I wonder if it is incorrect for nargo-core to know anything about contracts.
         // As can be seen here, It seems like a leaky abstraction where ContractFunctions (essentially CompiledPrograms)
@@ -72,6 +91,23 @@ pub(crate) fn run<B: Backend>(
         let preprocessed_contracts: Result<Vec<PreprocessedContract>, CliError<B>> =
             try_vecmap(contracts, |contract| {
                 let preprocessed_contract_functions = try_vecmap(contract.functions, |func| {
+                    if args.compile_options.show_brillig {
+                        save_brillig_disassembly_to_dir(
+                            &func.brillig_debug_info.disassembly,
+                            |location| location_to_string(&context.file_manager, location),
+                            circuit_dir.join(BRILLIG_DIR).join(&contract.name).join(&func.name),
+                        );
+                    }
+                    let options = &args.compile_options;
+                    if options.show_ssa || options.show_ssa_cfg {
+                        save_ssa_passes_to_dir(
+                            &func.ssa_passes,
+                            options.show_ssa,
+                            options.show_ssa_cfg,
+                            circuit_dir.join(SSA_DIR).join(&contract.name).join(&func.name),
+                        );
+                    }
+
                     common_reference_string = update_common_reference_string(
                         backend,
                         &common_reference_string,
@@ -79,20 +115,31 @@ pub(crate) fn run<B: Backend>(
                     )
                     .map_err(CliError::CommonReferenceStringError)?;
 
-                    preprocess_contract_function(
+                    let mut preprocessed_function = preprocess_contract_function(
                         backend,
                         args.include_keys,
                         &common_reference_string,
                         func,
                     )
-                    .map_err(CliError::ProofSystemCompilerError)
+                    .map_err(CliError::ProofSystemCompilerError)?;
+                    if args.skip_debug_info {
+                        preprocessed_function.debug_info = None;
+                    }
+                    if let Some(debug_info) = &mut preprocessed_function.debug_info {
+                        debug_info.relativize_files(&config.program_dir);
+                    }
+                    Ok(preprocessed_function)
                 })?;
 
-                Ok(PreprocessedContract {
+                let mut preprocessed_contract = PreprocessedContract {
                     name: contract.name,
                     backend: String::from(BACKEND_IDENTIFIER),
+                    abi_version: ABI_VERSION,
                     functions: preprocessed_contract_functions,
-                })
+                    hash: None,
+                };
+                preprocessed_contract.hash = Some(preprocessed_contract.content_hash());
+                Ok(preprocessed_contract)
             });
         for contract in preprocessed_contracts? {
             save_contract_to_file(
@@ -107,13 +154,27 @@ pub(crate) fn run<B: Backend>(
             update_common_reference_string(backend, &common_reference_string, &program.circuit)
                 .map_err(CliError::CommonReferenceStringError)?;
 
-        let preprocessed_program =
+        let mut preprocessed_program =
             preprocess_program(backend, args.include_keys, &common_reference_string, program)
                 .map_err(CliError::ProofSystemCompilerError)?;
+        if args.skip_debug_info {
+            preprocessed_program.debug_info = None;
+        }
+        if let Some(debug_info) = &mut preprocessed_program.debug_info {
+            debug_info.relativize_files(&config.program_dir);
+        }
+
+        let hash = preprocessed_program.content_hash();
+        if let Some(expected_hash) = args.check_hash {
+            if !hash.eq_ignore_ascii_case(expected_hash.trim_start_matches("0x")) {
+                return Err(CliError::MismatchedHash { expected: expected_hash, found: hash });
+            }
+        }
+        preprocessed_program.hash = Some(hash);
         save_program_to_file(&preprocessed_program, &args.circuit_name, circuit_dir);
     }
 
-    write_cached_common_reference_string(&common_reference_string);
+    write_cached_common_reference_string(backend_name, &common_reference_string);
 
     Ok(())
 }
@@ -130,20 +191,44 @@ pub(crate) fn compile_circuit<B: Backend>(
         &|op| backend.supports_opcode(op),
         compile_options,
     );
-    report_errors(result, &context, compile_options.deny_warnings).map_err(Into::into)
+    let program = report_errors(result, &context, compile_options)?;
+
+    if compile_options.show_brillig {
+        save_brillig_disassembly_to_dir(
+            &program.brillig_debug_info.disassembly,
+            |location| location_to_string(&context.file_manager, location),
+            program_dir.join(TARGET_DIR).join(BRILLIG_DIR),
+        );
+    }
+    if compile_options.show_ssa || compile_options.show_ssa_cfg {
+        save_ssa_passes_to_dir(
+            &program.ssa_passes,
+            compile_options.show_ssa,
+            compile_options.show_ssa_cfg,
+            program_dir.join(TARGET_DIR).join(SSA_DIR),
+        );
+    }
+
+    Ok(program)
 }
 
 /// Helper function for reporting any errors in a Result<(T, Warnings), ErrorsAndWarnings>
 /// structure that is commonly used as a return result in this file.
+/// Diagnostics are reported in the format given by `compile_options.message_format`.
 pub(crate) fn report_errors<T>(
     result: Result<(T, Warnings), ErrorsAndWarnings>,
     context: &Context,
-    deny_warnings: bool,
+    compile_options: &CompileOptions,
 ) -> Result<T, ReportedErrors> {
-    let (t, warnings) = result.map_err(|errors| {
-        noirc_errors::reporter::report_all(&context.file_manager, &errors, deny_warnings)
-    })?;
+    let report_all = match compile_options.message_format {
+        MessageFormat::Human => reporter::report_all,
+        MessageFormat::Json => reporter::report_all_json,
+    };
+    let deny_warnings = compile_options.deny_warnings;
+
+    let (t, warnings) =
+        result.map_err(|errors| report_all(&context.file_manager, &errors, deny_warnings))?;
 
-    noirc_errors::reporter::report_all(&context.file_manager, &warnings, deny_warnings);
+    report_all(&context.file_manager, &warnings, deny_warnings);
     Ok(t)
 }
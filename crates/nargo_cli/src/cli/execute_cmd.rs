@@ -3,11 +3,19 @@ use std::path::Path;
 use acvm::acir::{circuit::Circuit, native_types::WitnessMap};
 use acvm::Backend;
 use clap::Args;
-use noirc_abi::input_parser::{Format, InputValue};
+use nargo::ops::PartialExecution;
+use noirc_abi::errors::AbiError;
+use noirc_abi::input_parser::InputValue;
 use noirc_abi::{Abi, InputMap};
 use noirc_driver::{CompileOptions, CompiledProgram};
 
-use super::fs::{inputs::read_inputs_from_file, witness::save_witness_to_dir};
+use super::fs::{
+    inputs::{
+        parse_input_override, read_inputs_with_overrides, read_partial_inputs_with_overrides,
+        resolve_input_format, InputFormat,
+    },
+    witness::{read_partial_witness_from_dir, save_partial_witness_to_dir, save_witness_to_dir},
+};
 use super::NargoConfig;
 use crate::{
     cli::compile_cmd::compile_circuit,
@@ -21,10 +29,34 @@ pub(crate) struct ExecuteCommand {
     /// Write the execution witness to named file
     witness_name: Option<String>,
 
-    /// The name of the toml file which contains the inputs for the prover
+    /// The name of the toml or json file which contains the inputs for the prover
     #[clap(long, short, default_value = PROVER_INPUT_FILE)]
     prover_name: String,
 
+    /// The format of the inputs for the prover. The toml file is read if it exists, and the
+    /// json file otherwise
+    #[arg(long, value_enum)]
+    input_format: Option<InputFormat>,
+
+    /// Sets the value of a parameter, written as a toml value such as `5` or `[1, 2]`, in place
+    /// of its value in the prover's input file
+    #[arg(long = "arg", value_name = "NAME=VALUE", value_parser = parse_input_override)]
+    input_overrides: Vec<(String, String)>,
+
+    /// Solve as much of the witness as the inputs given allow, saving the partially solved
+    /// witness under the witness name so that execution may be resumed once the remaining inputs
+    /// are known. Parameters may be left out of the prover's inputs.
+    ///
+    /// The saved witness holds the values of every input given so far in plaintext, including
+    /// private ones, so sharing it with another party reveals those inputs to them
+    #[arg(long, requires = "witness_name")]
+    partial: bool,
+
+    /// Resume execution from a partially solved witness saved with `--partial`, adding the
+    /// inputs given to those it holds
+    #[arg(long, value_name = "WITNESS")]
+    resume: Option<String>,
+
     #[clap(flatten)]
     compile_options: CompileOptions,
 }
@@ -34,16 +66,43 @@ pub(crate) fn run<B: Backend>(
     args: ExecuteCommand,
     config: NargoConfig,
 ) -> Result<(), CliError<B>> {
-    let (return_value, solved_witness) =
-        execute_with_path(backend, &config.program_dir, args.prover_name, &args.compile_options)?;
+    let witness_dir = config.program_dir.join(TARGET_DIR);
+    let resumed_witness = match &args.resume {
+        Some(witness_name) => Some(read_partial_witness_from_dir(witness_name, &witness_dir)?),
+        None => None,
+    };
+
+    let (return_value, execution) = execute_with_path(
+        backend,
+        &config.program_dir,
+        args.prover_name,
+        args.input_format,
+        &args.input_overrides,
+        resumed_witness,
+        args.partial,
+        &args.compile_options,
+    )?;
+
+    let solved_witness = match execution {
+        PartialExecution::Solved(solved_witness) => solved_witness,
+        PartialExecution::Incomplete(partial_witness) => {
+            println!("Circuit witness partially solved, as it depends on inputs not yet given");
+            let witness_name = args.witness_name.expect("A witness name is required by --partial");
+            let witness_path =
+                save_partial_witness_to_dir(partial_witness, &witness_name, witness_dir)?;
+            println!(
+                "Partial witness saved to {}, resume with `nargo execute --resume {witness_name}`",
+                witness_path.display()
+            );
+            return Ok(());
+        }
+    };
 
     println!("Circuit witness successfully solved");
     if let Some(return_value) = return_value {
         println!("Circuit output: {return_value:?}");
     }
     if let Some(witness_name) = args.witness_name {
-        let witness_dir = config.program_dir.join(TARGET_DIR);
-
         let witness_path = save_witness_to_dir(solved_witness, &witness_name, witness_dir)?;
 
         println!("Witness saved to {}", witness_path.display());
@@ -51,24 +110,74 @@ pub(crate) fn run<B: Backend>(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn execute_with_path<B: Backend>(
     backend: &B,
     program_dir: &Path,
     prover_name: String,
+    input_format: Option<InputFormat>,
+    input_overrides: &[(String, String)],
+    resumed_witness: Option<WitnessMap>,
+    partial: bool,
     compile_options: &CompileOptions,
-) -> Result<(Option<InputValue>, WitnessMap), CliError<B>> {
-    let CompiledProgram { abi, circuit } = compile_circuit(backend, program_dir, compile_options)?;
-
-    // Parse the initial witness values from Prover.toml
-    let (inputs_map, _) =
-        read_inputs_from_file(program_dir, prover_name.as_str(), Format::Toml, &abi)?;
-
-    let solved_witness = execute_program(backend, circuit, &abi, &inputs_map)?;
-
-    let public_abi = abi.public_abi();
-    let (_, return_value) = public_abi.decode(&solved_witness)?;
+) -> Result<(Option<InputValue>, PartialExecution), CliError<B>> {
+    let CompiledProgram { abi, circuit, .. } =
+        compile_circuit(backend, program_dir, compile_options)?;
+
+    // Parse the initial witness values from Prover.toml or Prover.json
+    let format = resolve_input_format(program_dir, &prover_name, input_format);
+
+    let execution = if partial || resumed_witness.is_some() {
+        // Only some of the inputs are given when execution is split between several runs
+        let inputs_map = read_partial_inputs_with_overrides(
+            program_dir,
+            prover_name.as_str(),
+            format,
+            &abi,
+            input_overrides,
+        )?;
+        let initial_witness = merge_witnesses(
+            resumed_witness.unwrap_or_else(WitnessMap::new),
+            abi.encode_partial(&inputs_map)?,
+        )?;
+
+        if partial {
+            nargo::ops::execute_circuit_partially(backend, circuit, initial_witness)?
+        } else {
+            let solved_witness = nargo::ops::execute_circuit(backend, circuit, initial_witness)?;
+            PartialExecution::Solved(solved_witness)
+        }
+    } else {
+        let inputs_map = read_inputs_with_overrides(
+            program_dir,
+            prover_name.as_str(),
+            format,
+            &abi,
+            input_overrides,
+        )?;
+        PartialExecution::Solved(execute_program(backend, circuit, &abi, &inputs_map)?)
+    };
+
+    let return_value = match &execution {
+        PartialExecution::Solved(solved_witness) => abi.public_abi().decode(solved_witness)?.1,
+        PartialExecution::Incomplete(_) => None,
+    };
+
+    Ok((return_value, execution))
+}
 
-    Ok((return_value, solved_witness))
+/// Adds the witnesses of newly given inputs to a partially solved witness, which must agree on
+/// the value of any witness they share.
+fn merge_witnesses(mut witness: WitnessMap, inputs: WitnessMap) -> Result<WitnessMap, AbiError> {
+    for (index, value) in inputs {
+        match witness.insert(index, value) {
+            Some(existing_value) if existing_value != value => {
+                return Err(AbiError::InconsistentWitnessAssignment(index));
+            }
+            _ => (),
+        }
+    }
+    Ok(witness)
 }
 
 pub(crate) fn execute_program<B: Backend>(
@@ -83,3 +192,42 @@ pub(crate) fn execute_program<B: Backend>(
 
     Ok(solved_witness)
 }
+
+#[cfg(test)]
+mod tests {
+    use acvm::{
+        acir::native_types::{Witness, WitnessMap},
+        FieldElement,
+    };
+    use noirc_abi::errors::AbiError;
+
+    use super::merge_witnesses;
+
+    fn witness_map(values: &[(u32, i128)]) -> WitnessMap {
+        let mut witness = WitnessMap::new();
+        for (index, value) in values {
+            witness.insert(Witness(*index), FieldElement::from(*value));
+        }
+        witness
+    }
+
+    #[test]
+    fn merge_witnesses_adds_new_inputs() {
+        let partial_witness = witness_map(&[(1, 2), (3, 5)]);
+        let inputs = witness_map(&[(2, 3), (3, 5)]);
+
+        let merged = merge_witnesses(partial_witness, inputs).unwrap();
+        assert_eq!(merged, witness_map(&[(1, 2), (2, 3), (3, 5)]));
+    }
+
+    #[test]
+    fn merge_witnesses_rejects_conflicting_inputs() {
+        let partial_witness = witness_map(&[(1, 2)]);
+        let inputs = witness_map(&[(1, 3)]);
+
+        assert!(matches!(
+            merge_witnesses(partial_witness, inputs),
+            Err(AbiError::InconsistentWitnessAssignment(Witness(1)))
+        ));
+    }
+}
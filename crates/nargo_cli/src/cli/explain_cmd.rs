@@ -0,0 +1,25 @@
+use acvm::Backend;
+use clap::Args;
+use noirc_errors::registry;
+
+use crate::errors::CliError;
+
+use super::NargoConfig;
+
+/// Print the extended explanation of a compiler error code
+#[derive(Debug, Clone, Args)]
+pub(crate) struct ExplainCommand {
+    /// The error code to explain, e.g. E0001
+    code: String,
+}
+
+pub(crate) fn run<B: Backend>(
+    _backend: &B,
+    args: ExplainCommand,
+    _config: NargoConfig,
+) -> Result<(), CliError<B>> {
+    let code = registry::lookup(&args.code).ok_or(CliError::UnknownErrorCode(args.code))?;
+
+    println!("{}", code.explanation());
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use std::{io::Write, path::Path};
+
+use acvm::acir::circuit::Circuit;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::errors::FilesystemError;
+
+use super::write_to_file;
+
+/// The formats in which proofs and keys may be written
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ArtifactFormat {
+    /// The raw bytes of the artifact
+    Binary,
+    /// The bytes of the artifact as hex text
+    Hex,
+    /// A json object holding the artifact as hex text, along with the circuit and the backend
+    /// and backend version it was generated for
+    Json,
+}
+
+/// The circuit and backend which a proof or key was generated for.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ArtifactMetadata {
+    backend: String,
+    backend_version: String,
+    circuit_hash: String,
+}
+
+impl ArtifactMetadata {
+    pub(crate) fn new(backend: &str, backend_version: &str, circuit: &Circuit) -> Self {
+        let mut circuit_bytes = Vec::new();
+        circuit.write(&mut circuit_bytes).unwrap();
+        ArtifactMetadata {
+            backend: backend.to_owned(),
+            backend_version: backend_version.to_owned(),
+            circuit_hash: hash_bytes(&circuit_bytes),
+        }
+    }
+}
+
+/// A proof or key written in the [`ArtifactFormat::Json`] format
+#[derive(Serialize, Deserialize)]
+struct ArtifactEnvelope {
+    #[serde(flatten)]
+    metadata: ArtifactMetadata,
+    data: String,
+}
+
+/// Returns the SHA-256 hash of some bytes as hex text. Unlike the hashers of the standard
+/// library, the hash is the same across platforms and releases of nargo, so it may be used to
+/// identify artifacts such as circuits.
+pub(super) fn hash_bytes(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn encode_artifact(bytes: &[u8], format: ArtifactFormat, metadata: &ArtifactMetadata) -> Vec<u8> {
+    match format {
+        ArtifactFormat::Binary => bytes.to_vec(),
+        ArtifactFormat::Hex => hex::encode(bytes).into_bytes(),
+        ArtifactFormat::Json => {
+            let envelope =
+                ArtifactEnvelope { metadata: metadata.clone(), data: hex::encode(bytes) };
+            serde_json::to_vec_pretty(&envelope).unwrap()
+        }
+    }
+}
+
+pub(crate) fn write_artifact(
+    bytes: &[u8],
+    path: &Path,
+    format: ArtifactFormat,
+    metadata: &ArtifactMetadata,
+) {
+    write_to_file(&encode_artifact(bytes, format, metadata), path);
+}
+
+/// Prints an artifact to stdout, following artifacts written as text with a newline.
+pub(crate) fn print_artifact(bytes: &[u8], format: ArtifactFormat, metadata: &ArtifactMetadata) {
+    let mut stdout = std::io::stdout();
+    stdout.write_all(&encode_artifact(bytes, format, metadata)).unwrap();
+    if format != ArtifactFormat::Binary {
+        stdout.write_all(b"\n").unwrap();
+    }
+}
+
+/// Reads an artifact written in the given format. Artifacts written as json are rejected unless
+/// they were generated for the circuit and backend described by `metadata`.
+pub(crate) fn read_artifact(
+    path: &Path,
+    format: ArtifactFormat,
+    metadata: &ArtifactMetadata,
+) -> Result<Vec<u8>, FilesystemError> {
+    let contents =
+        std::fs::read(path).map_err(|_| FilesystemError::PathNotValid(path.to_path_buf()))?;
+
+    match format {
+        ArtifactFormat::Binary => Ok(contents),
+        ArtifactFormat::Hex => hex::decode(contents).map_err(FilesystemError::HexArtifactNotValid),
+        ArtifactFormat::Json => {
+            let envelope: ArtifactEnvelope = serde_json::from_slice(&contents)
+                .map_err(|err| FilesystemError::JsonArtifactNotValid(path.to_path_buf(), err))?;
+            if &envelope.metadata != metadata {
+                return Err(FilesystemError::MismatchedArtifact {
+                    path: path.to_path_buf(),
+                    backend: envelope.metadata.backend,
+                    backend_version: envelope.metadata.backend_version,
+                    circuit_hash: envelope.metadata.circuit_hash,
+                    expected_backend: metadata.backend.clone(),
+                    expected_backend_version: metadata.backend_version.clone(),
+                    expected_circuit_hash: metadata.circuit_hash.clone(),
+                });
+            }
+            hex::decode(envelope.data).map_err(FilesystemError::HexArtifactNotValid)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::{hash_bytes, read_artifact, write_artifact, ArtifactFormat, ArtifactMetadata};
+    use crate::errors::FilesystemError;
+
+    fn metadata(circuit_hash: &str) -> ArtifactMetadata {
+        ArtifactMetadata {
+            backend: "backend".to_owned(),
+            backend_version: "0.1.0".to_owned(),
+            circuit_hash: circuit_hash.to_owned(),
+        }
+    }
+
+    #[test]
+    fn hashes_are_sha256() {
+        assert_eq!(
+            hash_bytes(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn artifacts_are_read_back_in_every_format() {
+        let artifact_dir = TempDir::new("artifact_dir").unwrap().into_path();
+        let bytes = [0, 1, 2, 254, 255];
+
+        for format in [ArtifactFormat::Binary, ArtifactFormat::Hex, ArtifactFormat::Json] {
+            let path = artifact_dir.join(format!("{format:?}"));
+            write_artifact(&bytes, &path, format, &metadata("0123"));
+            assert_eq!(read_artifact(&path, format, &metadata("0123")).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn json_artifacts_of_another_circuit_are_rejected() {
+        let artifact_dir = TempDir::new("artifact_dir").unwrap().into_path();
+        let path = artifact_dir.join("proof");
+        write_artifact(&[1, 2, 3], &path, ArtifactFormat::Json, &metadata("0123"));
+
+        let result = read_artifact(&path, ArtifactFormat::Json, &metadata("4567"));
+        assert!(matches!(
+            result,
+            Err(FilesystemError::MismatchedArtifact { circuit_hash, .. }) if circuit_hash == "0123"
+        ));
+    }
+
+    #[test]
+    fn json_artifacts_of_another_backend_version_are_rejected() {
+        let artifact_dir = TempDir::new("artifact_dir").unwrap().into_path();
+        let path = artifact_dir.join("proof");
+        write_artifact(&[1, 2, 3], &path, ArtifactFormat::Json, &metadata("0123"));
+
+        let newer_backend =
+            ArtifactMetadata { backend_version: "0.2.0".to_owned(), ..metadata("0123") };
+        let result = read_artifact(&path, ArtifactFormat::Json, &newer_backend);
+        assert!(matches!(
+            result,
+            Err(FilesystemError::MismatchedArtifact { backend_version, .. }) if backend_version == "0.1.0"
+        ));
+    }
+}
@@ -0,0 +1,33 @@
+use std::path::{Path, PathBuf};
+
+use noirc_driver::BrilligDisassembly;
+use noirc_errors::Location;
+
+use super::{create_named_dir, write_to_file};
+use crate::constants::{BRILLIG_EXT, DOT_EXT};
+
+/// Writes the disassembly and control flow graph of each unconstrained function
+/// into their own files in `brillig_dir`.
+///
+/// The disassembly is annotated with the source locations formatted by `format_location`.
+pub(crate) fn save_brillig_disassembly_to_dir<P: AsRef<Path>>(
+    brillig_disassembly: &BrilligDisassembly,
+    format_location: impl Fn(Location) -> String,
+    brillig_dir: P,
+) -> PathBuf {
+    create_named_dir(brillig_dir.as_ref(), "brillig");
+
+    for (function_name, disassembly) in brillig_disassembly {
+        let function_path = brillig_dir.as_ref().join(function_name);
+        write_to_file(
+            disassembly.annotated_instructions(&format_location).as_bytes(),
+            &function_path.with_extension(BRILLIG_EXT),
+        );
+        write_to_file(
+            disassembly.control_flow_graph.as_bytes(),
+            &function_path.with_extension(DOT_EXT),
+        );
+    }
+
+    brillig_dir.as_ref().to_path_buf()
+}
@@ -1,23 +1,20 @@
-use std::{env, path::PathBuf};
+use std::path::PathBuf;
 
 use acvm::{acir::circuit::Circuit, CommonReferenceString};
 
+use crate::backends::backend_dir;
+
 use super::{create_named_dir, write_to_file};
 
-// TODO(#1388): pull this from backend.
-const BACKEND_IDENTIFIER: &str = "acvm-backend-barretenberg";
 const TRANSCRIPT_NAME: &str = "common-reference-string.bin";
 
-fn common_reference_string_location() -> PathBuf {
-    let cache_dir = match env::var("NARGO_BACKEND_CACHE_DIR") {
-        Ok(cache_dir) => PathBuf::from(cache_dir),
-        Err(_) => dirs::home_dir().unwrap().join(".nargo").join("backends"),
-    };
-    cache_dir.join(BACKEND_IDENTIFIER).join(TRANSCRIPT_NAME)
+/// Returns the path of the common reference string cached for a backend, within its directory.
+fn common_reference_string_location(backend_name: &str) -> PathBuf {
+    backend_dir(backend_name).join(TRANSCRIPT_NAME)
 }
 
-pub(crate) fn read_cached_common_reference_string() -> Vec<u8> {
-    let crs_path = common_reference_string_location();
+pub(crate) fn read_cached_common_reference_string(backend_name: &str) -> Vec<u8> {
+    let crs_path = common_reference_string_location(backend_name);
 
     // TODO(#1390): Implement checksum
     match std::fs::read(crs_path) {
@@ -46,8 +43,11 @@ pub(crate) fn update_common_reference_string<B: CommonReferenceString>(
     runtime.block_on(fut)
 }
 
-pub(crate) fn write_cached_common_reference_string(common_reference_string: &[u8]) {
-    let crs_path = common_reference_string_location();
+pub(crate) fn write_cached_common_reference_string(
+    backend_name: &str,
+    common_reference_string: &[u8],
+) {
+    let crs_path = common_reference_string_location(backend_name);
 
     create_named_dir(crs_path.parent().unwrap(), "crs");
 
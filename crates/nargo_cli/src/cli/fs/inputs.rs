@@ -1,5 +1,6 @@
+use clap::ValueEnum;
 use noirc_abi::{
-    input_parser::{Format, InputValue},
+    input_parser::{parse_argument, Format, InputValue},
     Abi, InputMap, MAIN_RETURN_NAME,
 };
 use std::{collections::BTreeMap, path::Path};
@@ -8,6 +9,42 @@ use crate::errors::FilesystemError;
 
 use super::write_to_file;
 
+/// The formats in which the inputs of a program may be written
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum InputFormat {
+    Toml,
+    Json,
+}
+
+impl From<InputFormat> for Format {
+    fn from(format: InputFormat) -> Format {
+        match format {
+            InputFormat::Toml => Format::Toml,
+            InputFormat::Json => Format::Json,
+        }
+    }
+}
+
+/// Returns the format of the named input file in the given directory. Unless a format is given,
+/// the TOML file is read if it exists and the JSON file otherwise.
+pub(crate) fn resolve_input_format<P: AsRef<Path>>(
+    path: P,
+    file_name: &str,
+    format: Option<InputFormat>,
+) -> Format {
+    if let Some(format) = format {
+        return format.into();
+    }
+    let file_path = path.as_ref().join(file_name);
+    let json_exists = file_path.with_extension(Format::Json.ext()).exists();
+    let toml_exists = file_path.with_extension(Format::Toml.ext()).exists();
+    if json_exists && !toml_exists {
+        Format::Json
+    } else {
+        Format::Toml
+    }
+}
+
 /// Returns the circuit's parameters and its return value, if one exists.
 /// # Examples
 ///
@@ -27,7 +64,8 @@ pub(crate) fn read_inputs_from_file<P: AsRef<Path>>(
 
     let file_path = path.as_ref().join(file_name).with_extension(format.ext());
     if !file_path.exists() {
-        return Err(FilesystemError::MissingTomlFile(file_name.to_owned(), file_path));
+        let file_name = format!("{file_name}.{}", format.ext());
+        return Err(FilesystemError::MissingInputFile(file_name, file_path));
     }
 
     let input_string = std::fs::read_to_string(file_path).unwrap();
@@ -37,6 +75,62 @@ pub(crate) fn read_inputs_from_file<P: AsRef<Path>>(
     Ok((input_map, return_value))
 }
 
+/// Parses the value of a parameter given on the command line as `name=value`.
+pub(crate) fn parse_input_override(arg: &str) -> Result<(String, String), String> {
+    let (name, value) =
+        arg.split_once('=').ok_or_else(|| format!("expected `NAME=VALUE`, found `{arg}`"))?;
+    Ok((name.trim().to_owned(), value.trim().to_owned()))
+}
+
+/// Reads the circuit's parameters like [read_inputs_from_file], replacing the values of those
+/// given on the command line. The file is not needed if every parameter is given this way.
+pub(crate) fn read_inputs_with_overrides<P: AsRef<Path>>(
+    path: P,
+    file_name: &str,
+    format: Format,
+    abi: &Abi,
+    overrides: &[(String, String)],
+) -> Result<InputMap, FilesystemError> {
+    let file_path = path.as_ref().join(file_name).with_extension(format.ext());
+    let all_overridden =
+        abi.parameters.iter().all(|param| overrides.iter().any(|(name, _)| name == &param.name));
+
+    let mut input_map = if all_overridden && !file_path.exists() {
+        InputMap::new()
+    } else {
+        read_inputs_from_file(path, file_name, format, abi)?.0
+    };
+    for (name, value) in overrides {
+        input_map.insert(name.clone(), parse_argument(name, value, abi)?);
+    }
+    Ok(input_map)
+}
+
+/// Reads the parameters given for a partial execution of the circuit like
+/// [read_inputs_with_overrides], permitting parameters to be missing from both the file and the
+/// command line. The file is not needed at all when inputs are only given on the command line.
+pub(crate) fn read_partial_inputs_with_overrides<P: AsRef<Path>>(
+    path: P,
+    file_name: &str,
+    format: Format,
+    abi: &Abi,
+    overrides: &[(String, String)],
+) -> Result<InputMap, FilesystemError> {
+    let file_path = path.as_ref().join(file_name).with_extension(format.ext());
+
+    let mut input_map = if file_path.exists() {
+        let input_string = std::fs::read_to_string(file_path).unwrap();
+        format.parse_partial(&input_string, abi)?
+    } else {
+        InputMap::new()
+    };
+    input_map.remove(MAIN_RETURN_NAME);
+    for (name, value) in overrides {
+        input_map.insert(name.clone(), parse_argument(name, value, abi)?);
+    }
+    Ok(input_map)
+}
+
 pub(crate) fn write_inputs_to_file<P: AsRef<Path>>(
     input_map: &InputMap,
     return_value: &Option<InputValue>,
@@ -76,8 +170,12 @@ mod tests {
     };
     use tempdir::TempDir;
 
-    use super::{read_inputs_from_file, write_inputs_to_file};
-    use crate::constants::VERIFIER_INPUT_FILE;
+    use super::{
+        parse_input_override, read_inputs_from_file, read_inputs_with_overrides,
+        read_partial_inputs_with_overrides, resolve_input_format, write_inputs_to_file,
+        InputFormat,
+    };
+    use crate::constants::{PROVER_INPUT_FILE, VERIFIER_INPUT_FILE};
 
     #[test]
     fn write_and_read_recovers_inputs_and_return_value() {
@@ -127,4 +225,142 @@ mod tests {
         assert_eq!(loaded_inputs, input_map);
         assert_eq!(loaded_return_value, return_value);
     }
+
+    #[test]
+    fn json_inputs_are_read_when_there_are_no_toml_inputs() {
+        let input_dir = TempDir::new("input_dir").unwrap().into_path();
+        let abi = Abi {
+            parameters: vec![AbiParameter {
+                name: "foo".into(),
+                typ: AbiType::Array { length: 2, typ: Box::new(AbiType::Field) },
+                visibility: AbiVisibility::Private,
+            }],
+            return_type: None,
+            param_witnesses: BTreeMap::new(),
+            return_witnesses: Vec::new(),
+        };
+        std::fs::write(input_dir.join("Prover.json"), r#"{ "foo": ["1", "0x02"] }"#).unwrap();
+
+        let format = resolve_input_format(&input_dir, PROVER_INPUT_FILE, None);
+        assert!(matches!(format, Format::Json));
+        let (inputs, _) =
+            read_inputs_from_file(&input_dir, PROVER_INPUT_FILE, format, &abi).unwrap();
+        let expected = InputValue::Vec(vec![
+            InputValue::Field(FieldElement::one()),
+            InputValue::Field(2_u128.into()),
+        ]);
+        assert_eq!(inputs, BTreeMap::from([("foo".to_owned(), expected)]));
+
+        // TOML inputs are preferred unless a format is asked for
+        std::fs::write(input_dir.join("Prover.toml"), "foo = [1, 2]").unwrap();
+        let format = resolve_input_format(&input_dir, PROVER_INPUT_FILE, None);
+        assert!(matches!(format, Format::Toml));
+        let format = resolve_input_format(&input_dir, PROVER_INPUT_FILE, Some(InputFormat::Json));
+        assert!(matches!(format, Format::Json));
+    }
+
+    #[test]
+    fn command_line_values_override_the_input_file() {
+        let input_dir = TempDir::new("input_dir").unwrap().into_path();
+        let abi = Abi {
+            parameters: vec![
+                AbiParameter {
+                    name: "x".into(),
+                    typ: AbiType::Field,
+                    visibility: AbiVisibility::Private,
+                },
+                AbiParameter {
+                    name: "pub_key".into(),
+                    typ: AbiType::Array { length: 2, typ: Box::new(AbiType::Field) },
+                    visibility: AbiVisibility::Public,
+                },
+            ],
+            return_type: None,
+            param_witnesses: BTreeMap::new(),
+            return_witnesses: Vec::new(),
+        };
+        let overrides = vec![
+            parse_input_override("x=5").unwrap(),
+            parse_input_override("pub_key=[1, 2]").unwrap(),
+        ];
+        let expected = BTreeMap::from([
+            ("x".to_owned(), InputValue::Field(5_u128.into())),
+            (
+                "pub_key".to_owned(),
+                InputValue::Vec(vec![
+                    InputValue::Field(FieldElement::one()),
+                    InputValue::Field(2_u128.into()),
+                ]),
+            ),
+        ]);
+
+        // No input file is needed when every parameter is given
+        let inputs = read_inputs_with_overrides(
+            &input_dir,
+            PROVER_INPUT_FILE,
+            Format::Toml,
+            &abi,
+            &overrides,
+        )
+        .unwrap();
+        assert_eq!(inputs, expected);
+
+        std::fs::write(input_dir.join("Prover.toml"), "x = 1\npub_key = [1, 2]").unwrap();
+        let inputs = read_inputs_with_overrides(
+            &input_dir,
+            PROVER_INPUT_FILE,
+            Format::Toml,
+            &abi,
+            &overrides[..1],
+        )
+        .unwrap();
+        assert_eq!(inputs, expected);
+
+        assert!(parse_input_override("x").is_err());
+    }
+
+    #[test]
+    fn partial_inputs_may_omit_parameters() {
+        let input_dir = TempDir::new("input_dir").unwrap().into_path();
+        let abi = Abi {
+            parameters: vec![
+                AbiParameter {
+                    name: "x".into(),
+                    typ: AbiType::Field,
+                    visibility: AbiVisibility::Private,
+                },
+                AbiParameter {
+                    name: "y".into(),
+                    typ: AbiType::Field,
+                    visibility: AbiVisibility::Private,
+                },
+            ],
+            return_type: None,
+            param_witnesses: BTreeMap::new(),
+            return_witnesses: Vec::new(),
+        };
+        let read = |overrides: &[(String, String)]| {
+            read_partial_inputs_with_overrides(
+                &input_dir,
+                PROVER_INPUT_FILE,
+                Format::Toml,
+                &abi,
+                overrides,
+            )
+            .unwrap()
+        };
+
+        // No input file is needed when no parameters are given
+        assert!(read(&[]).is_empty());
+
+        std::fs::write(input_dir.join("Prover.toml"), "x = 1").unwrap();
+        assert_eq!(
+            read(&[]),
+            BTreeMap::from([("x".to_owned(), InputValue::Field(FieldElement::one()))])
+        );
+        assert!(read_inputs_from_file(&input_dir, PROVER_INPUT_FILE, Format::Toml, &abi).is_err());
+
+        let overrides = vec![parse_input_override("y=2").unwrap()];
+        assert_eq!(read(&overrides).len(), 2);
+    }
 }
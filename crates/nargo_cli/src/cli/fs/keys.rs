@@ -0,0 +1,293 @@
+use std::path::{Path, PathBuf};
+
+use acvm::{acir::circuit::Circuit, ProofSystemCompiler};
+use nargo::{artifacts::program::PreprocessedProgram, ops::preprocess_program};
+use noirc_driver::CompiledProgram;
+use serde::{Deserialize, Serialize};
+
+use crate::backends::DEFAULT_BACKEND_VERSION;
+
+use super::{artifact::hash_bytes, create_named_dir, write_to_file};
+
+/// The proving and verification keys of a circuit as hex text, along with the hash of the
+/// circuit and the backend they were generated for so that keys are never used for a circuit
+/// other than their own. Keys generated by another version of the backend or from another
+/// common reference string are not reused either, as they may differ.
+#[derive(Serialize, Deserialize)]
+struct CachedKeys {
+    backend: String,
+    backend_version: String,
+    common_reference_string_hash: String,
+    circuit_hash: String,
+    proving_key: String,
+    verification_key: String,
+}
+
+/// Preprocesses a program like [`preprocess_program`], reusing the keys cached in `keys_dir`
+/// for its circuit rather than generating them anew. Generated keys are added to the cache, and
+/// setting `force` has the keys generated even if they are cached.
+pub(crate) fn preprocess_program_with_cached_keys<B: ProofSystemCompiler>(
+    backend: &B,
+    common_reference_string: &[u8],
+    compiled_program: CompiledProgram,
+    keys_dir: &Path,
+    force: bool,
+) -> Result<PreprocessedProgram, B::Error> {
+    let mut program =
+        preprocess_program(backend, false, common_reference_string, compiled_program)?;
+
+    // Keys are always generated by the backend nargo is built with, rather than a plugin
+    let circuit = serialize_circuit(&program.bytecode);
+    let (proving_key, verification_key) = read_or_generate_keys(
+        keys_dir,
+        (&program.backend, DEFAULT_BACKEND_VERSION),
+        common_reference_string,
+        &circuit,
+        force,
+        || backend.preprocess(common_reference_string, &program.bytecode),
+    )?;
+
+    program.proving_key = Some(proving_key);
+    program.verification_key = Some(verification_key);
+    Ok(program)
+}
+
+/// Returns the proving and verification keys cached in `keys_dir` for a circuit, or generates
+/// them with `generate_keys` and adds them to the cache if they are not cached or `force` is set.
+///
+/// The `backend` generating the keys is given by its name and version, and the keys are
+/// generated from the given common reference string.
+fn read_or_generate_keys<E>(
+    keys_dir: &Path,
+    backend: (&str, &str),
+    common_reference_string: &[u8],
+    circuit: &[u8],
+    force: bool,
+    generate_keys: impl FnOnce() -> Result<(Vec<u8>, Vec<u8>), E>,
+) -> Result<(Vec<u8>, Vec<u8>), E> {
+    let (backend_name, backend_version) = backend;
+    let common_reference_string_hash = hash_bytes(common_reference_string);
+    let circuit_hash = hash_bytes(circuit);
+    let keys_path = cached_keys_path(keys_dir, &circuit_hash);
+
+    if !force {
+        let cached_keys = read_cached_keys(&keys_path).filter(|keys| {
+            keys.backend == backend_name
+                && keys.backend_version == backend_version
+                && keys.common_reference_string_hash == common_reference_string_hash
+                && keys.circuit_hash == circuit_hash
+        });
+        if let Some(keys) = cached_keys.and_then(|keys| decode_keys(&keys)) {
+            return Ok(keys);
+        }
+    }
+
+    let (proving_key, verification_key) = generate_keys()?;
+    let keys = CachedKeys {
+        backend: backend_name.to_owned(),
+        backend_version: backend_version.to_owned(),
+        common_reference_string_hash,
+        circuit_hash,
+        proving_key: hex::encode(&proving_key),
+        verification_key: hex::encode(&verification_key),
+    };
+    create_named_dir(keys_dir, "keys");
+    write_to_file(&serde_json::to_vec(&keys).unwrap(), &keys_path);
+    Ok((proving_key, verification_key))
+}
+
+fn serialize_circuit(circuit: &Circuit) -> Vec<u8> {
+    let mut circuit_bytes = Vec::new();
+    circuit.write(&mut circuit_bytes).unwrap();
+    circuit_bytes
+}
+
+/// Returns the path of the keys of a circuit, named after the hash of the circuit.
+fn cached_keys_path(keys_dir: &Path, circuit_hash: &str) -> PathBuf {
+    keys_dir.join(circuit_hash).with_extension("json")
+}
+
+/// Reads the keys cached at `keys_path`. Keys which cannot be read, such as those cached by
+/// releases of nargo which did not record how they were generated, are generated anew.
+fn read_cached_keys(keys_path: &Path) -> Option<CachedKeys> {
+    let keys = std::fs::read(keys_path).ok()?;
+    serde_json::from_slice(&keys).ok()
+}
+
+fn decode_keys(keys: &CachedKeys) -> Option<(Vec<u8>, Vec<u8>)> {
+    Some((hex::decode(&keys.proving_key).ok()?, hex::decode(&keys.verification_key).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, convert::Infallible};
+
+    use tempdir::TempDir;
+
+    use super::read_or_generate_keys;
+
+    const CRS: [u8; 4] = [0, 1, 2, 3];
+
+    /// Generates keys derived from `circuit`, counting the times keys are generated.
+    fn generate_keys<'a>(
+        circuit: &'a [u8],
+        generated: &'a Cell<u32>,
+    ) -> impl FnOnce() -> Result<(Vec<u8>, Vec<u8>), Infallible> + 'a {
+        move || {
+            generated.set(generated.get() + 1);
+            Ok((circuit.to_vec(), circuit.iter().rev().copied().collect()))
+        }
+    }
+
+    #[test]
+    fn cached_keys_are_reused() {
+        let keys_dir = TempDir::new("keys_dir").unwrap().into_path();
+        let generated = Cell::new(0);
+        let circuit = [1, 2, 3];
+
+        let keys = read_or_generate_keys(
+            &keys_dir,
+            ("backend", "0.1.0"),
+            &CRS,
+            &circuit,
+            false,
+            generate_keys(&circuit, &generated),
+        )
+        .unwrap();
+        let cached_keys = read_or_generate_keys(
+            &keys_dir,
+            ("backend", "0.1.0"),
+            &CRS,
+            &circuit,
+            false,
+            generate_keys(&circuit, &generated),
+        )
+        .unwrap();
+
+        assert_eq!(generated.get(), 1);
+        assert_eq!(keys, cached_keys);
+        assert_eq!(cached_keys, (vec![1, 2, 3], vec![3, 2, 1]));
+    }
+
+    #[test]
+    fn forced_keys_are_generated_anew() {
+        let keys_dir = TempDir::new("keys_dir").unwrap().into_path();
+        let generated = Cell::new(0);
+        let circuit = [1, 2, 3];
+
+        read_or_generate_keys(
+            &keys_dir,
+            ("backend", "0.1.0"),
+            &CRS,
+            &circuit,
+            false,
+            generate_keys(&circuit, &generated),
+        )
+        .unwrap();
+        read_or_generate_keys(
+            &keys_dir,
+            ("backend", "0.1.0"),
+            &CRS,
+            &circuit,
+            true,
+            generate_keys(&circuit, &generated),
+        )
+        .unwrap();
+
+        assert_eq!(generated.get(), 2);
+    }
+
+    #[test]
+    fn keys_are_generated_for_changed_circuits() {
+        let keys_dir = TempDir::new("keys_dir").unwrap().into_path();
+        let generated = Cell::new(0);
+        let (circuit, changed_circuit) = ([1, 2, 3], [1, 2, 4]);
+
+        read_or_generate_keys(
+            &keys_dir,
+            ("backend", "0.1.0"),
+            &CRS,
+            &circuit,
+            false,
+            generate_keys(&circuit, &generated),
+        )
+        .unwrap();
+        let keys = read_or_generate_keys(
+            &keys_dir,
+            ("backend", "0.1.0"),
+            &CRS,
+            &changed_circuit,
+            false,
+            generate_keys(&changed_circuit, &generated),
+        )
+        .unwrap();
+
+        assert_eq!(generated.get(), 2);
+        assert_eq!(keys, (vec![1, 2, 4], vec![4, 2, 1]));
+
+        // Keys generated by another backend are not reused either
+        read_or_generate_keys(
+            &keys_dir,
+            ("other", "0.1.0"),
+            &CRS,
+            &circuit,
+            false,
+            generate_keys(&circuit, &generated),
+        )
+        .unwrap();
+        assert_eq!(generated.get(), 3);
+    }
+
+    #[test]
+    fn keys_are_generated_for_other_backend_versions_and_crs() {
+        let keys_dir = TempDir::new("keys_dir").unwrap().into_path();
+        let generated = Cell::new(0);
+        let circuit = [1, 2, 3];
+
+        read_or_generate_keys(
+            &keys_dir,
+            ("backend", "0.1.0"),
+            &CRS,
+            &circuit,
+            false,
+            generate_keys(&circuit, &generated),
+        )
+        .unwrap();
+
+        // Bumping the version of the backend generates the keys anew
+        read_or_generate_keys(
+            &keys_dir,
+            ("backend", "0.2.0"),
+            &CRS,
+            &circuit,
+            false,
+            generate_keys(&circuit, &generated),
+        )
+        .unwrap();
+        assert_eq!(generated.get(), 2);
+
+        // As does a change to the common reference string
+        read_or_generate_keys(
+            &keys_dir,
+            ("backend", "0.2.0"),
+            &[3, 2, 1, 0],
+            &circuit,
+            false,
+            generate_keys(&circuit, &generated),
+        )
+        .unwrap();
+        assert_eq!(generated.get(), 3);
+
+        // While the keys generated for the latest version and string are reused
+        read_or_generate_keys(
+            &keys_dir,
+            ("backend", "0.2.0"),
+            &[3, 2, 1, 0],
+            &circuit,
+            false,
+            generate_keys(&circuit, &generated),
+        )
+        .unwrap();
+        assert_eq!(generated.get(), 3);
+    }
+}
@@ -1,18 +1,58 @@
 use std::path::{Path, PathBuf};
 
+use acvm::FieldElement;
+use nargo::ops::RecursionArtifacts;
+use serde::Serialize;
+
 use crate::{constants::PROOF_EXT, errors::FilesystemError};
 
-use super::{create_named_dir, write_to_file};
+use super::{
+    artifact::{write_artifact, ArtifactFormat, ArtifactMetadata},
+    create_named_dir, write_to_file,
+};
 
 pub(crate) fn save_proof_to_dir<P: AsRef<Path>>(
     proof: &[u8],
     proof_name: &str,
     proof_dir: P,
+    format: ArtifactFormat,
+    metadata: &ArtifactMetadata,
 ) -> Result<PathBuf, FilesystemError> {
     create_named_dir(proof_dir.as_ref(), "proof");
     let proof_path = proof_dir.as_ref().join(proof_name).with_extension(PROOF_EXT);
 
-    write_to_file(hex::encode(proof).as_bytes(), &proof_path);
+    write_artifact(proof, &proof_path, format, metadata);
 
     Ok(proof_path)
 }
+
+/// The arguments of `std::verify_proof` for a proof, written as hex encoded field elements.
+#[derive(Serialize)]
+struct RecursionInputs {
+    verification_key: Vec<String>,
+    proof: Vec<String>,
+    public_inputs: Vec<String>,
+    key_hash: String,
+}
+
+pub(crate) fn save_recursion_artifacts_to_dir<P: AsRef<Path>>(
+    recursion_artifacts: &RecursionArtifacts,
+    recursion_name: &str,
+    proof_dir: P,
+) -> Result<PathBuf, FilesystemError> {
+    let to_hex = |field: &FieldElement| format!("0x{}", field.to_hex());
+    let recursion_inputs = RecursionInputs {
+        verification_key: recursion_artifacts.verification_key.iter().map(to_hex).collect(),
+        proof: recursion_artifacts.proof.iter().map(to_hex).collect(),
+        public_inputs: recursion_artifacts.public_inputs.iter().map(to_hex).collect(),
+        key_hash: to_hex(&recursion_artifacts.key_hash),
+    };
+
+    create_named_dir(proof_dir.as_ref(), "proof");
+    let recursion_path = proof_dir.as_ref().join(recursion_name).with_extension("toml");
+
+    let recursion_toml = toml::to_string(&recursion_inputs).unwrap();
+    write_to_file(recursion_toml.as_bytes(), &recursion_path);
+
+    Ok(recursion_path)
+}
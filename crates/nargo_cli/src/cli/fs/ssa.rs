@@ -0,0 +1,36 @@
+use std::path::{Path, PathBuf};
+
+use noirc_driver::SsaPasses;
+
+use super::{create_named_dir, write_to_file};
+use crate::constants::{DOT_EXT, SSA_EXT};
+
+/// Writes the SSA after each pass into its own file in `ssa_dir`, named after the
+/// position and name of the pass so that the files are listed in the order the passes ran.
+///
+/// If `save_control_flow_graphs` is set, the control flow graph of each function after
+/// each pass is also written into a directory named after the pass.
+pub(crate) fn save_ssa_passes_to_dir<P: AsRef<Path>>(
+    ssa_passes: &SsaPasses,
+    save_ssa: bool,
+    save_control_flow_graphs: bool,
+    ssa_dir: P,
+) -> PathBuf {
+    create_named_dir(ssa_dir.as_ref(), "ssa");
+
+    for (index, pass) in ssa_passes.iter().enumerate() {
+        let pass_path = ssa_dir.as_ref().join(format!("{index:02}_{}", pass.name));
+        if save_ssa {
+            write_to_file(pass.ssa.as_bytes(), &pass_path.with_extension(SSA_EXT));
+        }
+        if save_control_flow_graphs {
+            create_named_dir(&pass_path, "ssa");
+            for (function_name, control_flow_graph) in &pass.control_flow_graphs {
+                let function_path = pass_path.join(function_name).with_extension(DOT_EXT);
+                write_to_file(control_flow_graph.as_bytes(), &function_path);
+            }
+        }
+    }
+
+    ssa_dir.as_ref().to_path_buf()
+}
@@ -10,14 +10,39 @@ pub(crate) fn save_witness_to_dir<P: AsRef<Path>>(
     witness_name: &str,
     witness_dir: P,
 ) -> Result<PathBuf, FilesystemError> {
-    create_named_dir(witness_dir.as_ref(), "witness");
+    let buf: Vec<u8> = serialize_witness_map(witnesses)?;
+    Ok(write_witness_to_dir(&buf, witness_name, witness_dir))
+}
+
+/// Saves a partially solved witness. Partial witnesses are always written along with their
+/// witness indices, as execution is resumed from them.
+pub(crate) fn save_partial_witness_to_dir<P: AsRef<Path>>(
+    witnesses: WitnessMap,
+    witness_name: &str,
+    witness_dir: P,
+) -> Result<PathBuf, FilesystemError> {
+    let buf: Vec<u8> = witnesses.try_into()?;
+    Ok(write_witness_to_dir(&buf, witness_name, witness_dir))
+}
+
+/// Reads a partially solved witness saved by [save_partial_witness_to_dir].
+pub(crate) fn read_partial_witness_from_dir<P: AsRef<Path>>(
+    witness_name: &str,
+    witness_dir: P,
+) -> Result<WitnessMap, FilesystemError> {
     let witness_path = witness_dir.as_ref().join(witness_name).with_extension(WITNESS_EXT);
+    let buf =
+        std::fs::read(&witness_path).map_err(|_| FilesystemError::PathNotValid(witness_path))?;
+    Ok(WitnessMap::try_from(buf.as_slice())?)
+}
 
-    let buf: Vec<u8> = serialize_witness_map(witnesses)?;
+fn write_witness_to_dir<P: AsRef<Path>>(buf: &[u8], witness_name: &str, witness_dir: P) -> PathBuf {
+    create_named_dir(witness_dir.as_ref(), "witness");
+    let witness_path = witness_dir.as_ref().join(witness_name).with_extension(WITNESS_EXT);
 
-    write_to_file(buf.as_slice(), &witness_path);
+    write_to_file(buf, &witness_path);
 
-    Ok(witness_path)
+    witness_path
 }
 
 #[cfg(not(feature = "flat_witness"))]
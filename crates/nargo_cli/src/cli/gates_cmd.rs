@@ -3,6 +3,7 @@ use clap::Args;
 use noirc_driver::CompileOptions;
 use std::path::Path;
 
+use crate::backends::BackendPlugin;
 use crate::cli::compile_cmd::compile_circuit;
 use crate::errors::CliError;
 
@@ -20,11 +21,17 @@ pub(crate) fn run<B: Backend>(
     args: GatesCommand,
     config: NargoConfig,
 ) -> Result<(), CliError<B>> {
-    count_gates_with_path(backend, config.program_dir, &args.compile_options)
+    count_gates_with_path(
+        backend,
+        config.backend_plugin.as_ref(),
+        config.program_dir,
+        &args.compile_options,
+    )
 }
 
 fn count_gates_with_path<B: Backend, P: AsRef<Path>>(
     backend: &B,
+    backend_plugin: Option<&BackendPlugin>,
     program_dir: P,
     compile_options: &CompileOptions,
 ) -> Result<(), CliError<B>> {
@@ -37,9 +44,12 @@ fn count_gates_with_path<B: Backend, P: AsRef<Path>>(
         num_opcodes
     );
 
-    let exact_circuit_size = backend
-        .get_exact_circuit_size(&compiled_program.circuit)
-        .map_err(CliError::ProofSystemCompilerError)?;
+    let exact_circuit_size = match backend_plugin {
+        Some(backend_plugin) => backend_plugin.gates(&compiled_program.circuit)?,
+        None => backend
+            .get_exact_circuit_size(&compiled_program.circuit)
+            .map_err(CliError::ProofSystemCompilerError)?,
+    };
     println!("Backend circuit size: {exact_circuit_size}");
 
     Ok(())
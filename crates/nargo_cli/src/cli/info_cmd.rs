@@ -0,0 +1,243 @@
+use acvm::{
+    acir::{brillig_vm::Opcode as BrilligOpcode, circuit::Opcode},
+    Backend,
+};
+use clap::Args;
+use noirc_driver::{
+    disassemble_brillig, BrilligFunctionStatistics, CompileOptions, CompiledProgram, OpcodeProfile,
+};
+use std::path::{Path, PathBuf};
+
+use crate::cli::compile_cmd::compile_circuit;
+use crate::errors::CliError;
+
+use super::{fs::program::read_program_from_file, NargoConfig};
+
+/// Reports the size of the circuit and statistics about the Brillig bytecode of unconstrained functions
+#[derive(Debug, Clone, Args)]
+pub(crate) struct InfoCommand {
+    /// Compare the Brillig bytecode of the program against the one in a previously built artifact
+    #[arg(long, value_name = "OLD_ARTIFACT")]
+    brillig_diff: Option<PathBuf>,
+
+    /// Report the number of ACIR opcodes generated from each file and line of the program,
+    /// counted before the backend optimizes the circuit. Requires `--experimental-ssa`
+    #[arg(long)]
+    profile_gates: bool,
+
+    #[clap(flatten)]
+    compile_options: CompileOptions,
+}
+
+pub(crate) fn run<B: Backend>(
+    backend: &B,
+    args: InfoCommand,
+    config: NargoConfig,
+) -> Result<(), CliError<B>> {
+    let compiled_program = compile_circuit(backend, &config.program_dir, &args.compile_options)?;
+    print_info(&compiled_program);
+
+    if args.profile_gates {
+        print_opcode_profile(&compiled_program.opcode_profile);
+    }
+
+    if let Some(old_artifact_path) = args.brillig_diff {
+        print_brillig_diff(&compiled_program, &old_artifact_path)?;
+    }
+    Ok(())
+}
+
+fn print_info(compiled_program: &CompiledProgram) {
+    println!("Total ACIR opcodes generated: {}", compiled_program.circuit.opcodes.len());
+
+    let deduplication = &compiled_program.deduplication;
+    println!(
+        "Redundant ACIR opcodes removed: {} ({} before deduplication, {} after)",
+        deduplication.removed_opcodes(),
+        deduplication.opcodes_before,
+        deduplication.opcodes_after
+    );
+
+    let brillig_statistics = &compiled_program.brillig_debug_info.statistics;
+    if brillig_statistics.is_empty() {
+        return;
+    }
+
+    println!("Brillig bytecode of unconstrained functions:");
+    for (function_name, statistics) in brillig_statistics {
+        println!("  {function_name}:");
+        print_brillig_function_statistics(statistics);
+    }
+}
+
+/// Prints the number of opcodes generated from each file and from each line, most costly first.
+fn print_opcode_profile(profile: &OpcodeProfile) {
+    let total = profile.total();
+    let share = |count: usize| 100.0 * count as f64 / total.max(1) as f64;
+
+    let mut files: Vec<_> = profile.files().into_iter().collect();
+    files.sort_by(|(_, lhs), (_, rhs)| rhs.cmp(lhs));
+    println!("ACIR opcodes by file:");
+    for (file, count) in files {
+        println!("  {file}: {count} ({:.1}%)", share(count));
+    }
+
+    let mut lines: Vec<_> = profile.lines.iter().collect();
+    lines.sort_by(|(_, lhs), (_, rhs)| rhs.cmp(lhs));
+    println!("ACIR opcodes by line:");
+    for ((file, line), count) in lines {
+        println!("  {file}:{line}: {count} ({:.1}%)", share(*count));
+    }
+
+    if profile.unattributed > 0 {
+        let count = profile.unattributed;
+        println!("ACIR opcodes not generated from a single line: {count} ({:.1}%)", share(count));
+    }
+}
+
+fn print_brillig_function_statistics(statistics: &BrilligFunctionStatistics) {
+    // Memory usage is only a lower bound if some allocations are dynamically sized or repeated.
+    let bound = if statistics.is_heap_size_unbounded { "at least " } else { "" };
+
+    println!("    Opcodes: {}", statistics.num_opcodes);
+    println!("    Registers used: {}", statistics.num_registers);
+    println!("    Heap allocations: {}", statistics.num_heap_allocations);
+    println!("    Foreign calls: {}", statistics.num_foreign_calls);
+    match statistics.max_call_depth {
+        Some(depth) => println!("    Maximum call depth: {depth}"),
+        None => println!("    Maximum call depth: unbounded (recursive)"),
+    }
+    println!("    Estimated memory: {bound}{} slots", statistics.estimated_memory());
+}
+
+/// Prints the differences between the Brillig bytecode of the program and of an older build.
+///
+/// The bytecode of each call to an unconstrained function in the circuit is compared
+/// with the bytecode of the call at the same position in the old circuit.
+fn print_brillig_diff<B: Backend>(
+    compiled_program: &CompiledProgram,
+    old_artifact_path: &Path,
+) -> Result<(), CliError<B>> {
+    let old_program = read_program_from_file(old_artifact_path)?;
+    let old_bytecodes = brillig_bytecodes(&old_program.bytecode.opcodes);
+    let new_bytecodes = brillig_bytecodes(&compiled_program.circuit.opcodes);
+
+    println!("Brillig bytecode changes since {}:", old_artifact_path.display());
+    for index in 0..old_bytecodes.len().max(new_bytecodes.len()) {
+        let old_text = old_bytecodes.get(index).map(|bytecode| disassemble_brillig(bytecode));
+        let new_text = new_bytecodes.get(index).map(|bytecode| disassemble_brillig(bytecode));
+        let old_lines: Vec<_> = old_text.iter().flat_map(|text| text.lines()).collect();
+        let new_lines: Vec<_> = new_text.iter().flat_map(|text| text.lines()).collect();
+
+        let header = format!("  Brillig call {index}");
+        match (old_text.is_some(), new_text.is_some()) {
+            (true, false) => println!("{header}: removed"),
+            (false, true) => println!("{header}: added"),
+            _ if old_lines == new_lines => {
+                println!("{header}: unchanged");
+                continue;
+            }
+            _ => println!("{header}: changed"),
+        }
+
+        for line in diff_lines(&old_lines, &new_lines) {
+            match line {
+                LineDiff::Removed(line) => println!("  -{line}"),
+                LineDiff::Added(line) => println!("  +{line}"),
+                LineDiff::Unchanged(_) => (),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Collects the bytecode of each call to an unconstrained function in a circuit, in order.
+fn brillig_bytecodes(opcodes: &[Opcode]) -> Vec<&[BrilligOpcode]> {
+    opcodes
+        .iter()
+        .filter_map(|opcode| match opcode {
+            Opcode::Brillig(brillig) => Some(brillig.bytecode.as_slice()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// A line of the difference between two texts.
+#[derive(Debug, PartialEq, Eq)]
+enum LineDiff<'a> {
+    Unchanged(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Computes the lines to remove from and add to `old` to turn it into `new`,
+/// keeping the longest common subsequence of lines unchanged.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<LineDiff<'a>> {
+    // Changes are usually small, so the common prefix and suffix are set aside
+    // to keep the table of common subsequences small.
+    let prefix_len = old.iter().zip(new).take_while(|(old, new)| old == new).count();
+    let suffix_len = old[prefix_len..]
+        .iter()
+        .rev()
+        .zip(new[prefix_len..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_middle = &old[prefix_len..old.len() - suffix_len];
+    let new_middle = &new[prefix_len..new.len() - suffix_len];
+
+    // common_len[i][j] is the length of the longest common subsequence
+    // of old_middle[i..] and new_middle[j..]
+    let mut common_len = vec![vec![0; new_middle.len() + 1]; old_middle.len() + 1];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            common_len[i][j] = if old_middle[i] == new_middle[j] {
+                common_len[i + 1][j + 1] + 1
+            } else {
+                common_len[i + 1][j].max(common_len[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff: Vec<_> = old[..prefix_len].iter().map(|line| LineDiff::Unchanged(line)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            diff.push(LineDiff::Unchanged(old_middle[i]));
+            i += 1;
+            j += 1;
+        } else if i < old_middle.len()
+            && (j == new_middle.len() || common_len[i + 1][j] >= common_len[i][j + 1])
+        {
+            diff.push(LineDiff::Removed(old_middle[i]));
+            i += 1;
+        } else {
+            diff.push(LineDiff::Added(new_middle[j]));
+            j += 1;
+        }
+    }
+
+    diff.extend(old[old.len() - suffix_len..].iter().map(|line| LineDiff::Unchanged(line)));
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_lines, LineDiff};
+
+    #[test]
+    fn diff_keeps_common_lines() {
+        let old = ["CONST R1 = 1", "MOV R2, R1", "STOP"];
+        let new = ["CONST R1 = 2", "MOV R2, R1", "MOV R3, R1", "STOP"];
+
+        assert_eq!(
+            diff_lines(&old, &new),
+            vec![
+                LineDiff::Removed("CONST R1 = 1"),
+                LineDiff::Added("CONST R1 = 2"),
+                LineDiff::Unchanged("MOV R2, R1"),
+                LineDiff::Added("MOV R3, R1"),
+                LineDiff::Unchanged("STOP"),
+            ]
+        );
+    }
+}
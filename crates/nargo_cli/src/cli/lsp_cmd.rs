@@ -4,13 +4,23 @@ use async_lsp::{
     panic::CatchUnwindLayer, server::LifecycleLayer, tracing::TracingLayer,
 };
 use clap::Args;
-use noir_lsp::NargoLspService;
-use noirc_driver::CompileOptions;
+use fm::FileManager;
+use nargo::artifacts::program::PreprocessedProgram;
+use noir_lsp::{local_crate_context, ContextResolver, NargoLspService};
+use noirc_driver::{CompileOptions, DebugInfo};
+use noirc_frontend::hir::Context;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use tokio::io::BufReader;
 use tower::ServiceBuilder;
 
 use super::NargoConfig;
-use crate::errors::CliError;
+use crate::{
+    constants::TARGET_DIR,
+    errors::CliError,
+    find_package_manifest, find_package_root,
+    resolver::{resolve_root_manifest_cached, DependencyCache},
+};
 
 #[derive(Debug, Clone, Args)]
 pub(crate) struct LspCommand {
@@ -30,7 +40,7 @@ pub(crate) fn run<B: Backend>(
 
     runtime.block_on(async {
         let (server, _) = async_lsp::Frontend::new_server(|client| {
-            let router = NargoLspService::new(&client);
+            let router = NargoLspService::new(&client, package_resolver(), resolve_last_build);
 
             ServiceBuilder::new()
                 .layer(TracingLayer::default())
@@ -56,3 +66,79 @@ pub(crate) fn run<B: Backend>(
         server.run(stdin, stdout).await.map_err(CliError::LspError)
     })
 }
+
+/// Resolves the context of each file with [resolve_package_context], reusing the dependencies
+/// resolved for the files before it for as long as the language server runs.
+fn package_resolver() -> ContextResolver {
+    let mut dependencies = DependencyCache::default();
+    Box::new(move |file_path: &Path, open_documents: &HashMap<PathBuf, String>| {
+        resolve_package_context(file_path, open_documents, &mut dependencies)
+    })
+}
+
+/// Resolves the package containing the given file along with its dependencies, so that the
+/// language server can follow names into them. Files outside of a package, or in a package
+/// whose dependencies can't be resolved, are checked on their own. The documents open in the
+/// editor are read instead of the files on disk.
+///
+/// The context is returned along with the manifests it was resolved from, so that it is
+/// resolved again once a manifest is modified, such as to fix an unresolved dependency.
+fn resolve_package_context(
+    file_path: &Path,
+    open_documents: &HashMap<PathBuf, String>,
+    dependencies: &mut DependencyCache,
+) -> (Context, Vec<PathBuf>) {
+    let manifest = file_path.parent().and_then(|dir| find_package_manifest(dir).ok());
+    let context = manifest.as_ref().and_then(|manifest| {
+        let package_root = manifest.parent()?;
+        let file_manager = FileManager::with_overlays(open_documents.clone());
+        resolve_root_manifest_cached(package_root, file_manager, dependencies).ok()
+    });
+
+    match context {
+        Some(context) => {
+            let manifests = crate_manifests(&context);
+            (context, manifests)
+        }
+        None => (local_crate_context(file_path, open_documents), manifest.into_iter().collect()),
+    }
+}
+
+/// Returns the manifest of the package of each crate of a context.
+fn crate_manifests(context: &Context) -> Vec<PathBuf> {
+    let files = context.file_manager.as_simple_files();
+    context
+        .crate_graph
+        .iter_keys()
+        .filter_map(|crate_id| {
+            let root_file_id = context.crate_graph[crate_id].root_file_id;
+            let root_file = files.get(root_file_id.as_usize()).ok()?;
+            // The standard library is embedded in the compiler, so it has no manifest on disk
+            let root_file = PathBuf::from(root_file.name().to_string()).canonicalize().ok()?;
+            find_package_manifest(root_file.parent()?).ok()
+        })
+        .collect()
+}
+
+/// Reads the most recently compiled program artifact of the package containing the given file,
+/// so that the language server can show the number of opcodes generated from its functions.
+/// Artifacts compiled with `--skip-debug-info` are passed over, as are those of contracts.
+fn resolve_last_build(file_path: &Path) -> Option<(usize, DebugInfo)> {
+    let package_root = find_package_root(file_path.parent()?).ok()?;
+    let artifacts = std::fs::read_dir(package_root.join(TARGET_DIR)).ok()?;
+
+    let mut builds: Vec<_> = artifacts
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let modified = path.metadata().and_then(|metadata| metadata.modified()).ok()?;
+            (path.extension()? == "json").then_some((modified, path))
+        })
+        .collect();
+    builds.sort_by(|(lhs, _), (rhs, _)| rhs.cmp(lhs));
+
+    builds.into_iter().find_map(|(_, path)| {
+        let contents = std::fs::read(path).ok()?;
+        let program: PreprocessedProgram = serde_json::from_slice(&contents).ok()?;
+        Some((program.bytecode.opcodes.len(), program.debug_info?))
+    })
+}
@@ -5,15 +5,25 @@ use std::path::{Path, PathBuf};
 
 use color_eyre::eyre;
 
-use crate::find_package_root;
+use crate::{
+    backends::{is_known_backend, resolve_backend_name, BackendPlugin, ConcreteBackend},
+    constants::PKG_FILE,
+    errors::CliError,
+    find_package_root, manifest,
+};
 
 mod fs;
 
+mod abi_cmd;
+mod backend_cmd;
 mod check_cmd;
+mod codegen_cmd;
 mod codegen_verifier_cmd;
 mod compile_cmd;
 mod execute_cmd;
+mod explain_cmd;
 mod gates_cmd;
+mod info_cmd;
 mod lsp_cmd;
 mod new_cmd;
 mod prove_cmd;
@@ -27,6 +37,10 @@ const CARGO_PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
 static VERSION_STRING: &str =
     formatcp!("{} (git version hash: {}, is dirty: {})", CARGO_PKG_VERSION, GIT_HASH, IS_DIRTY);
 
+/// The exit code of nargo when compilation was aborted by an internal compiler error,
+/// distinguishing bugs in the compiler from errors in the program being compiled
+pub const INTERNAL_COMPILER_ERROR_EXIT_CODE: i32 = 3;
+
 #[derive(Parser, Debug)]
 #[command(name="nargo", author, version=VERSION_STRING, about, long_about = None)]
 struct NargoCli {
@@ -42,20 +56,40 @@ struct NargoCli {
 pub(crate) struct NargoConfig {
     #[arg(short, long, hide=true, default_value_os_t = std::env::current_dir().unwrap())]
     program_dir: PathBuf,
+
+    /// The backend to run the command with, in place of the backend of the package. This is
+    /// either a backend nargo is built with or an installed plugin. Plugins are only used to
+    /// prove, verify, count gates and generate verifiers, other commands use the backend nargo
+    /// is built with
+    #[arg(long, global = true)]
+    backend: Option<String>,
+
+    /// The plugin of the selected backend, unless it is built into nargo
+    #[arg(skip)]
+    backend_plugin: Option<BackendPlugin>,
+
+    /// The url of the remote prover in the package's manifest, if any
+    #[arg(skip)]
+    package_remote_prover: Option<String>,
 }
 
 #[non_exhaustive]
 #[derive(Subcommand, Clone, Debug)]
 enum NargoCommand {
+    Abi(abi_cmd::AbiCommand),
+    Backend(backend_cmd::BackendCommand),
     Check(check_cmd::CheckCommand),
+    Codegen(codegen_cmd::CodegenCommand),
     CodegenVerifier(codegen_verifier_cmd::CodegenVerifierCommand),
     Compile(compile_cmd::CompileCommand),
     New(new_cmd::NewCommand),
     Execute(execute_cmd::ExecuteCommand),
+    Explain(explain_cmd::ExplainCommand),
     Prove(prove_cmd::ProveCommand),
     Verify(verify_cmd::VerifyCommand),
     Test(test_cmd::TestCommand),
     Gates(gates_cmd::GatesCommand),
+    Info(info_cmd::InfoCommand),
     Lsp(lsp_cmd::LspCommand),
 }
 
@@ -63,24 +97,55 @@ pub fn start_cli() -> eyre::Result<()> {
     let NargoCli { command, mut config } = NargoCli::parse();
 
     // Search through parent directories to find package root if necessary.
-    if !matches!(command, NargoCommand::New(_) | NargoCommand::Lsp(_)) {
+    if !matches!(
+        command,
+        NargoCommand::New(_)
+            | NargoCommand::Explain(_)
+            | NargoCommand::Lsp(_)
+            | NargoCommand::Backend(_)
+    ) {
         config.program_dir = find_package_root(&config.program_dir)?;
+
+        let manifest = manifest::parse(config.program_dir.join(PKG_FILE))?;
+        let package_backend = manifest.backend.as_ref().map(|backend| backend.name.as_str());
+        let backend_name = resolve_backend_name(config.backend.as_deref(), package_backend);
+        config.backend_plugin = BackendPlugin::find(&backend_name)?;
+        config.package_remote_prover =
+            manifest.remote_prover.map(|remote_prover| remote_prover.url);
+        if config.backend_plugin.is_none() && !is_known_backend(&backend_name) {
+            let error: CliError<ConcreteBackend> = CliError::UnknownBackend(backend_name);
+            return Err(error.into());
+        }
     }
 
-    let backend = crate::backends::ConcreteBackend::default();
+    let backend = ConcreteBackend::default();
 
-    match command {
+    let result = match command {
         NargoCommand::New(args) => new_cmd::run(&backend, args, config),
+        NargoCommand::Abi(args) => abi_cmd::run(&backend, args, config),
+        NargoCommand::Backend(args) => backend_cmd::run(&backend, args, config),
         NargoCommand::Check(args) => check_cmd::run(&backend, args, config),
         NargoCommand::Compile(args) => compile_cmd::run(&backend, args, config),
         NargoCommand::Execute(args) => execute_cmd::run(&backend, args, config),
+        NargoCommand::Explain(args) => explain_cmd::run(&backend, args, config),
         NargoCommand::Prove(args) => prove_cmd::run(&backend, args, config),
         NargoCommand::Verify(args) => verify_cmd::run(&backend, args, config),
         NargoCommand::Test(args) => test_cmd::run(&backend, args, config),
         NargoCommand::Gates(args) => gates_cmd::run(&backend, args, config),
+        NargoCommand::Info(args) => info_cmd::run(&backend, args, config),
+        NargoCommand::Codegen(args) => codegen_cmd::run(&backend, args, config),
         NargoCommand::CodegenVerifier(args) => codegen_verifier_cmd::run(&backend, args, config),
         NargoCommand::Lsp(args) => lsp_cmd::run(&backend, args, config),
-    }?;
+    };
+
+    // Internal compiler errors have already been reported along with how to file a bug
+    if let Err(CliError::ReportedErrors(errors)) = &result {
+        if errors.bug_count > 0 {
+            eprintln!("Aborting due to an internal compiler error");
+            std::process::exit(INTERNAL_COMPILER_ERROR_EXIT_CODE);
+        }
+    }
+    result?;
 
     Ok(())
 }
@@ -93,10 +158,14 @@ pub fn prove_and_verify(program_dir: &Path, experimental_ssa: bool) -> bool {
 
     let compile_options = CompileOptions {
         show_ssa: false,
+        show_ssa_cfg: false,
         print_acir: false,
         deny_warnings: false,
         show_output: false,
         experimental_ssa,
+        show_brillig: false,
+        print_brillig: false,
+        ..Default::default()
     };
 
     let program =
@@ -128,11 +197,11 @@ mod tests {
     /// Compiles a file and returns true if compilation was successful
     ///
     /// This is used for tests.
-    fn file_compiles<P: AsRef<Path>>(root_file: P) -> bool {
+    fn file_compiles<P: AsRef<Path>>(root_file: P, deny_warnings: bool) -> bool {
         let mut context = Context::default();
         create_local_crate(&mut context, &root_file, CrateType::Binary);
 
-        let result = check_crate(&mut context, false, false);
+        let result = check_crate(&mut context, deny_warnings, false);
         let success = result.is_ok();
 
         let errors = match result {
@@ -144,6 +213,15 @@ mod tests {
         success
     }
 
+    /// Returns the message of each warning reported when compiling a file
+    fn file_warnings<P: AsRef<Path>>(root_file: P) -> Vec<String> {
+        let mut context = Context::default();
+        create_local_crate(&mut context, &root_file, CrateType::Binary);
+
+        let warnings = check_crate(&mut context, false, false).expect("Compile should succeed");
+        warnings.into_iter().map(|warning| warning.diagnostic.message).collect()
+    }
+
     #[test]
     fn compilation_pass() {
         let pass_dir =
@@ -152,7 +230,7 @@ mod tests {
         let paths = std::fs::read_dir(pass_dir).unwrap();
         for path in paths.flatten() {
             let path = path.path();
-            assert!(file_compiles(&path), "path: {}", path.display());
+            assert!(file_compiles(&path, false), "path: {}", path.display());
         }
     }
 
@@ -164,7 +242,43 @@ mod tests {
         let paths = std::fs::read_dir(fail_dir).unwrap();
         for path in paths.flatten() {
             let path = path.path();
-            assert!(!file_compiles(&path), "path: {}", path.display());
+            assert!(!file_compiles(&path, false), "path: {}", path.display());
         }
     }
+
+    #[test]
+    fn deprecation_warnings_are_denied() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join(format!("{TEST_DATA_DIR}/pass/deprecated.nr"));
+
+        assert!(file_compiles(&path, false));
+        assert!(!file_compiles(&path, true));
+    }
+
+    #[test]
+    fn unread_fields_of_public_structs_are_not_reported() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join(format!("{TEST_DATA_DIR}/pass/public_struct_fields.nr"));
+
+        assert!(file_compiles(&path, true));
+    }
+
+    #[test]
+    fn unread_fields_of_structs_returned_from_entry_points_are_not_reported() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join(format!("{TEST_DATA_DIR}/pass/returned_struct_fields.nr"));
+
+        let warnings = file_warnings(&path);
+        assert_eq!(warnings, vec!["field `value` of struct `Scratch` is never read".to_owned()]);
+    }
+
+    #[test]
+    fn unread_fields_are_reported() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join(format!("{TEST_DATA_DIR}/pass/dead_code.nr"));
+
+        let warnings = file_warnings(&path);
+        assert!(warnings.contains(&"field `y` of struct `Point` is never read".to_owned()));
+        assert!(!warnings.iter().any(|warning| warning.contains("`_tag`")));
+    }
 }
@@ -1,9 +1,10 @@
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use acvm::Backend;
 use clap::Args;
 use nargo::artifacts::program::PreprocessedProgram;
-use nargo::ops::{preprocess_program, prove_execution, verify_proof};
+use nargo::ops::{preprocess_program, prove_execution, recursion_artifacts, verify_proof};
 use noirc_abi::input_parser::Format;
 use noirc_driver::CompileOptions;
 
@@ -11,18 +12,27 @@ use super::NargoConfig;
 use super::{
     compile_cmd::compile_circuit,
     fs::{
+        artifact::{print_artifact, ArtifactFormat, ArtifactMetadata},
         common_reference_string::{
             read_cached_common_reference_string, update_common_reference_string,
             write_cached_common_reference_string,
         },
-        inputs::{read_inputs_from_file, write_inputs_to_file},
+        inputs::{
+            parse_input_override, read_inputs_with_overrides, resolve_input_format,
+            write_inputs_to_file, InputFormat,
+        },
+        keys::preprocess_program_with_cached_keys,
         program::read_program_from_file,
-        proof::save_proof_to_dir,
+        proof::{save_proof_to_dir, save_recursion_artifacts_to_dir},
     },
 };
 use crate::{
+    backends::{resolved_backend_name, BackendPlugin, RemoteProver, DEFAULT_BACKEND_VERSION},
     cli::execute_cmd::execute_program,
-    constants::{PROOFS_DIR, PROVER_INPUT_FILE, TARGET_DIR, VERIFIER_INPUT_FILE},
+    constants::{
+        KEYS_DIR, PROOFS_DIR, PROVER_INPUT_FILE, RECURSION_INPUT_FILE, TARGET_DIR,
+        VERIFIER_INPUT_FILE,
+    },
     errors::CliError,
 };
 
@@ -35,10 +45,20 @@ pub(crate) struct ProveCommand {
     /// The name of the circuit build files (ACIR, proving and verification keys)
     circuit_name: Option<String>,
 
-    /// The name of the toml file which contains the inputs for the prover
+    /// The name of the toml or json file which contains the inputs for the prover
     #[clap(long, short, default_value = PROVER_INPUT_FILE)]
     prover_name: String,
 
+    /// The format of the inputs for the prover. The toml file is read if it exists, and the
+    /// json file otherwise
+    #[arg(long, value_enum)]
+    input_format: Option<InputFormat>,
+
+    /// Sets the value of a parameter, written as a toml value such as `5` or `[1, 2]`, in place
+    /// of its value in the prover's input file
+    #[arg(long = "arg", value_name = "NAME=VALUE", value_parser = parse_input_override)]
+    input_overrides: Vec<(String, String)>,
+
     /// The name of the toml file which contains the inputs for the verifier
     #[clap(long, short, default_value = VERIFIER_INPUT_FILE)]
     verifier_name: String,
@@ -47,6 +67,32 @@ pub(crate) struct ProveCommand {
     #[arg(long)]
     verify: bool,
 
+    /// Generate the proving and verification keys even if they are cached
+    #[arg(long)]
+    force: bool,
+
+    /// Generate a proof which may be verified within another circuit, writing the arguments of
+    /// `std::verify_proof` for it to the proofs directory
+    #[arg(long)]
+    recursive: bool,
+
+    /// The format in which to write the proof. The proving and verification keys are kept in
+    /// nargo's own key cache rather than written as artifacts, so this applies to proofs only
+    #[arg(long, value_enum, default_value_t = ArtifactFormat::Hex)]
+    artifact_format: ArtifactFormat,
+
+    /// Generate the proof on the remote proving service at the given url rather than locally,
+    /// so that the common reference string and proving key need not be held locally. The url
+    /// may also be given by the `NARGO_REMOTE_PROVER_URL` environment variable or the
+    /// `[remote_prover]` section of Nargo.toml, and a bearer token authenticating nargo to the
+    /// service by the `NARGO_REMOTE_PROVER_TOKEN` environment variable
+    #[arg(long, value_name = "URL", conflicts_with_all = ["verify", "recursive"])]
+    remote_prover: Option<String>,
+
+    /// The number of seconds to wait for the remote prover to generate the proof
+    #[arg(long, value_name = "SECONDS", default_value_t = 600)]
+    remote_timeout: u64,
+
     #[clap(flatten)]
     compile_options: CompileOptions,
 }
@@ -62,15 +108,34 @@ pub(crate) fn run<B: Backend>(
         .circuit_name
         .map(|circuit_name| config.program_dir.join(TARGET_DIR).join(circuit_name));
 
+    // Proofs which are verified or made recursive are generated locally, as their keys are
+    // needed, so the remote prover configured for the package is only used for other proofs
+    let remote_prover = if args.verify || args.recursive {
+        None
+    } else {
+        RemoteProver::resolve(
+            args.remote_prover.as_deref(),
+            config.package_remote_prover.as_deref(),
+            Duration::from_secs(args.remote_timeout),
+        )
+    };
+
     prove_with_path(
         backend,
+        config.backend_plugin.as_ref(),
+        remote_prover.as_ref(),
         args.proof_name,
         args.prover_name,
+        args.input_format,
+        &args.input_overrides,
         args.verifier_name,
         config.program_dir,
         proof_dir,
         circuit_build_path,
         args.verify,
+        args.force,
+        args.recursive,
+        args.artifact_format,
         &args.compile_options,
     )?;
 
@@ -80,18 +145,49 @@ pub(crate) fn run<B: Backend>(
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn prove_with_path<B: Backend, P: AsRef<Path>>(
     backend: &B,
+    backend_plugin: Option<&BackendPlugin>,
+    remote_prover: Option<&RemoteProver>,
     proof_name: Option<String>,
     prover_name: String,
+    input_format: Option<InputFormat>,
+    input_overrides: &[(String, String)],
     verifier_name: String,
     program_dir: P,
     proof_dir: P,
     circuit_build_path: Option<PathBuf>,
     check_proof: bool,
+    force_preprocessing: bool,
+    is_recursive: bool,
+    artifact_format: ArtifactFormat,
     compile_options: &CompileOptions,
 ) -> Result<Option<PathBuf>, CliError<B>> {
-    let common_reference_string = read_cached_common_reference_string();
+    match backend_plugin {
+        Some(backend_plugin) if is_recursive => {
+            return Err(CliError::Generic(format!(
+                "Error: backend `{}` does not support recursive proofs",
+                backend_plugin.name()
+            )));
+        }
+        _ => (),
+    }
+
+    let crs_backend_name = resolved_backend_name(backend_plugin);
+    let common_reference_string = read_cached_common_reference_string(crs_backend_name);
 
     let (common_reference_string, preprocessed_program) = match circuit_build_path {
+        // Backend plugins and remote provers generate their own keys, so only the circuit is
+        // needed
+        _ if backend_plugin.is_some() || remote_prover.is_some() => {
+            let program = match circuit_build_path {
+                Some(circuit_build_path) => read_program_from_file(circuit_build_path)?,
+                None => {
+                    let program = compile_circuit(backend, program_dir.as_ref(), compile_options)?;
+                    preprocess_program(backend, false, &common_reference_string, program)
+                        .map_err(CliError::ProofSystemCompilerError)?
+                }
+            };
+            (common_reference_string, program)
+        }
         Some(circuit_build_path) => {
             let program = read_program_from_file(circuit_build_path)?;
             let common_reference_string = update_common_reference_string(
@@ -107,24 +203,47 @@ pub(crate) fn prove_with_path<B: Backend, P: AsRef<Path>>(
             let common_reference_string =
                 update_common_reference_string(backend, &common_reference_string, &program.circuit)
                     .map_err(CliError::CommonReferenceStringError)?;
-            let program = preprocess_program(backend, true, &common_reference_string, program)
-                .map_err(CliError::ProofSystemCompilerError)?;
+            let keys_dir = program_dir.as_ref().join(TARGET_DIR).join(KEYS_DIR);
+            let program = preprocess_program_with_cached_keys(
+                backend,
+                &common_reference_string,
+                program,
+                &keys_dir,
+                force_preprocessing,
+            )
+            .map_err(CliError::ProofSystemCompilerError)?;
             (common_reference_string, program)
         }
     };
 
-    write_cached_common_reference_string(&common_reference_string);
+    write_cached_common_reference_string(crs_backend_name, &common_reference_string);
 
-    let PreprocessedProgram { abi, bytecode, proving_key, verification_key, .. } =
-        preprocessed_program;
+    let PreprocessedProgram {
+        backend: backend_name,
+        abi,
+        bytecode,
+        proving_key,
+        verification_key,
+        ..
+    } = preprocessed_program;
+    let backend_name = backend_plugin.map_or(backend_name, |plugin| plugin.name().to_owned());
+    let backend_version = backend_plugin.map_or(DEFAULT_BACKEND_VERSION, |plugin| plugin.version());
+    let metadata = ArtifactMetadata::new(&backend_name, backend_version, &bytecode);
 
-    // Parse the initial witness values from Prover.toml
-    let (inputs_map, _) =
-        read_inputs_from_file(&program_dir, prover_name.as_str(), Format::Toml, &abi)?;
+    // Parse the initial witness values from Prover.toml or Prover.json
+    let format = resolve_input_format(&program_dir, &prover_name, input_format);
+    let inputs_map = read_inputs_with_overrides(
+        &program_dir,
+        prover_name.as_str(),
+        format,
+        &abi,
+        input_overrides,
+    )?;
 
     let solved_witness = execute_program(backend, bytecode.clone(), &abi, &inputs_map)?;
 
-    // Write public inputs into Verifier.toml
+    // Write public inputs and the return value into Verifier.toml, so that the proof may be
+    // verified straight away
     let public_abi = abi.public_abi();
     let (public_inputs, return_value) = public_abi.decode(&solved_witness)?;
 
@@ -137,36 +256,75 @@ pub(crate) fn prove_with_path<B: Backend, P: AsRef<Path>>(
         Format::Toml,
     )?;
 
-    let proving_key =
-        proving_key.expect("Proving key should exist as `true` is passed to `preprocess_program`");
+    let proof = match (remote_prover, backend_plugin) {
+        (Some(remote_prover), _) => {
+            remote_prover.prove(&backend_name, &bytecode, &solved_witness)?
+        }
+        (None, Some(backend_plugin)) => backend_plugin.prove(&bytecode, &solved_witness)?,
+        (None, None) => {
+            let proving_key = proving_key
+                .expect("Proving key should exist as the program is preprocessed with its keys");
+            prove_execution(
+                backend,
+                &common_reference_string,
+                &bytecode,
+                solved_witness,
+                &proving_key,
+                is_recursive,
+            )
+            .map_err(CliError::ProofSystemCompilerError)?
+        }
+    };
 
-    let proof =
-        prove_execution(backend, &common_reference_string, &bytecode, solved_witness, &proving_key)
-            .map_err(CliError::ProofSystemCompilerError)?;
+    let public_inputs = public_abi.encode(&public_inputs, return_value)?;
+    let verification_key = || {
+        verification_key
+            .as_ref()
+            .expect("Verification key should exist as the program is preprocessed with its keys")
+    };
 
     if check_proof {
-        let public_inputs = public_abi.encode(&public_inputs, return_value)?;
-        let verification_key = verification_key
-            .expect("Verification key should exist as `true` is passed to `preprocess_program`");
-        let valid_proof = verify_proof(
+        let valid_proof = match backend_plugin {
+            Some(backend_plugin) => backend_plugin.verify(&bytecode, &proof, &public_inputs)?,
+            None => verify_proof(
+                backend,
+                &common_reference_string,
+                &bytecode,
+                &proof,
+                public_inputs.clone(),
+                verification_key(),
+                is_recursive,
+            )
+            .map_err(CliError::ProofSystemCompilerError)?,
+        };
+
+        if !valid_proof {
+            return Err(CliError::InvalidProof("".into()));
+        }
+    }
+
+    if is_recursive {
+        let recursion_artifacts = recursion_artifacts(
             backend,
             &common_reference_string,
-            &bytecode,
             &proof,
             public_inputs,
-            &verification_key,
+            verification_key(),
         )
         .map_err(CliError::ProofSystemCompilerError)?;
-
-        if !valid_proof {
-            return Err(CliError::InvalidProof("".into()));
-        }
+        let recursion_name = proof_name.as_deref().unwrap_or(RECURSION_INPUT_FILE);
+        let recursion_path =
+            save_recursion_artifacts_to_dir(&recursion_artifacts, recursion_name, &proof_dir)?;
+        println!(
+            "Arguments for verifying the proof recursively written to {}",
+            recursion_path.display()
+        );
     }
 
     let proof_path = if let Some(proof_name) = proof_name {
-        Some(save_proof_to_dir(&proof, &proof_name, proof_dir)?)
+        Some(save_proof_to_dir(&proof, &proof_name, proof_dir, artifact_format, &metadata)?)
     } else {
-        println!("{}", hex::encode(&proof));
+        print_artifact(&proof, artifact_format, &metadata);
         None
     };
 
@@ -20,6 +20,10 @@ pub(crate) struct TestCommand {
     /// If given, only tests with names containing this string will be run
     test_name: Option<String>,
 
+    /// Only run the test whose name is exactly the given test name
+    #[arg(long, requires = "test_name")]
+    exact: bool,
+
     #[clap(flatten)]
     compile_options: CompileOptions,
 }
@@ -31,19 +35,24 @@ pub(crate) fn run<B: Backend>(
 ) -> Result<(), CliError<B>> {
     let test_name: String = args.test_name.unwrap_or_else(|| "".to_owned());
 
-    run_tests(backend, &config.program_dir, &test_name, &args.compile_options)
+    run_tests(backend, &config.program_dir, &test_name, args.exact, &args.compile_options)
 }
 
 fn run_tests<B: Backend>(
     backend: &B,
     program_dir: &Path,
     test_name: &str,
+    exact: bool,
     compile_options: &CompileOptions,
 ) -> Result<(), CliError<B>> {
     let mut context = resolve_root_manifest(program_dir)?;
-    check_crate_and_report_errors(&mut context, compile_options.deny_warnings, compile_options.experimental_ssa)?;
+    check_crate_and_report_errors(&mut context, compile_options)?;
 
-    let test_functions = context.get_all_test_functions_in_crate_matching(&LOCAL_CRATE, test_name);
+    let mut test_functions =
+        context.get_all_test_functions_in_crate_matching(&LOCAL_CRATE, test_name);
+    if exact {
+        test_functions.retain(|function| context.function_name(function) == test_name);
+    }
     println!("Running {} test functions...", test_functions.len());
     let mut failing = 0;
 
@@ -100,6 +109,7 @@ fn run_test<B: Backend>(
             writer.set_color(ColorSpec::new().set_fg(Some(Color::Red))).ok();
             writeln!(writer, "failed").ok();
             writer.reset().ok();
+            writeln!(writer, "{error}").ok();
             Err(error.into())
         }
     }
@@ -1,32 +1,38 @@
 use super::compile_cmd::compile_circuit;
 use super::fs::{
+    artifact::{read_artifact, ArtifactFormat, ArtifactMetadata},
     common_reference_string::{
         read_cached_common_reference_string, update_common_reference_string,
         write_cached_common_reference_string,
     },
     inputs::read_inputs_from_file,
-    load_hex_data,
+    keys::preprocess_program_with_cached_keys,
     program::read_program_from_file,
 };
 use super::NargoConfig;
 use crate::{
-    constants::{PROOFS_DIR, PROOF_EXT, TARGET_DIR, VERIFIER_INPUT_FILE},
+    backends::{resolved_backend_name, BackendPlugin, DEFAULT_BACKEND_VERSION},
+    constants::{KEYS_DIR, PROOFS_DIR, PROOF_EXT, TARGET_DIR, VERIFIER_INPUT_FILE},
     errors::CliError,
 };
 
 use acvm::Backend;
-use clap::Args;
+use clap::{Args, ValueEnum};
+use iter_extended::vecmap;
 use nargo::artifacts::program::PreprocessedProgram;
 use nargo::ops::{preprocess_program, verify_proof};
 use noirc_abi::input_parser::Format;
 use noirc_driver::CompileOptions;
+use serde::Serialize;
 use std::path::{Path, PathBuf};
+use std::thread;
 
 /// Given a proof and a program, verify whether the proof is valid
 #[derive(Debug, Clone, Args)]
 pub(crate) struct VerifyCommand {
     /// The proof to verify
-    proof: String,
+    #[arg(required_unless_present = "batch")]
+    proof: Option<String>,
 
     /// The name of the circuit build files (ACIR, proving and verification keys)
     circuit_name: Option<String>,
@@ -35,43 +41,301 @@ pub(crate) struct VerifyCommand {
     #[clap(long, short, default_value = VERIFIER_INPUT_FILE)]
     verifier_name: String,
 
+    /// Generate the proving and verification keys even if they are cached
+    #[arg(long)]
+    force: bool,
+
+    /// Verify a proof generated with `nargo prove --recursive`
+    #[arg(long)]
+    recursive: bool,
+
+    /// The format in which the proof is written. The verification key is read from nargo's own
+    /// key cache rather than from an artifact, so this applies to proofs only
+    #[arg(long, value_enum, default_value_t = ArtifactFormat::Hex)]
+    artifact_format: ArtifactFormat,
+
+    /// Verify every proof in a directory, each against the public inputs in the toml file of
+    /// the same name, in parallel
+    #[arg(long, value_name = "DIR", conflicts_with = "proof")]
+    batch: Option<PathBuf>,
+
+    /// The format in which the results of a batch verification are reported
+    #[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+    report_format: ReportFormat,
+
     #[clap(flatten)]
     compile_options: CompileOptions,
 }
 
-pub(crate) fn run<B: Backend>(
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+enum ReportFormat {
+    Table,
+    Json,
+}
+
+/// The outcome of verifying one of the proofs of a batch
+#[derive(Debug, Serialize)]
+struct BatchResult {
+    proof: String,
+    valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl BatchResult {
+    fn new(proof_path: &Path, valid: bool, error: Option<String>) -> Self {
+        let proof = proof_path.file_name().unwrap().to_string_lossy().into_owned();
+        BatchResult { proof, valid, error }
+    }
+}
+
+pub(crate) fn run<B: Backend + Default>(
     backend: &B,
     args: VerifyCommand,
     config: NargoConfig,
 ) -> Result<(), CliError<B>> {
-    let proof_path =
-        config.program_dir.join(PROOFS_DIR).join(&args.proof).with_extension(PROOF_EXT);
-
     let circuit_build_path = args
         .circuit_name
         .map(|circuit_name| config.program_dir.join(TARGET_DIR).join(circuit_name));
 
+    if let Some(batch_dir) = args.batch {
+        return verify_batch(
+            backend,
+            config.backend_plugin.as_ref(),
+            &config.program_dir,
+            &batch_dir,
+            circuit_build_path.as_ref(),
+            args.force,
+            args.recursive,
+            args.artifact_format,
+            args.report_format,
+            &args.compile_options,
+        );
+    }
+
+    let proof = args.proof.expect("Proof should be given unless verifying a batch");
+    let proof_path = config.program_dir.join(PROOFS_DIR).join(proof).with_extension(PROOF_EXT);
+
     verify_with_path(
         backend,
+        config.backend_plugin.as_ref(),
         &config.program_dir,
         proof_path,
         circuit_build_path.as_ref(),
         args.verifier_name,
+        args.force,
+        args.recursive,
+        args.artifact_format,
         &args.compile_options,
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn verify_with_path<B: Backend, P: AsRef<Path>>(
     backend: &B,
+    backend_plugin: Option<&BackendPlugin>,
     program_dir: P,
     proof_path: PathBuf,
     circuit_build_path: Option<P>,
     verifier_name: String,
+    force_preprocessing: bool,
+    is_recursive: bool,
+    artifact_format: ArtifactFormat,
+    compile_options: &CompileOptions,
+) -> Result<(), CliError<B>> {
+    let (common_reference_string, preprocessed_program) = load_program(
+        backend,
+        backend_plugin,
+        program_dir.as_ref(),
+        circuit_build_path.as_ref().map(AsRef::as_ref),
+        force_preprocessing,
+        is_recursive,
+        compile_options,
+    )?;
+    let metadata = artifact_metadata(backend_plugin, &preprocessed_program);
+
+    let valid_proof = verify_proof_file(
+        backend,
+        backend_plugin,
+        &common_reference_string,
+        &preprocessed_program,
+        &metadata,
+        &proof_path,
+        program_dir.as_ref(),
+        &verifier_name,
+        is_recursive,
+        artifact_format,
+    )?;
+
+    if valid_proof {
+        Ok(())
+    } else {
+        Err(CliError::InvalidProof(proof_path))
+    }
+}
+
+/// Verifies each proof in `batch_dir` against the public inputs in the toml file of the same
+/// name. The program is compiled and preprocessed once, after which the proofs are verified in
+/// parallel, each thread with its own instance of the backend.
+#[allow(clippy::too_many_arguments)]
+fn verify_batch<B: Backend + Default, P: AsRef<Path>>(
+    backend: &B,
+    backend_plugin: Option<&BackendPlugin>,
+    program_dir: P,
+    batch_dir: &Path,
+    circuit_build_path: Option<P>,
+    force_preprocessing: bool,
+    is_recursive: bool,
+    artifact_format: ArtifactFormat,
+    report_format: ReportFormat,
     compile_options: &CompileOptions,
 ) -> Result<(), CliError<B>> {
-    let common_reference_string = read_cached_common_reference_string();
+    let proof_paths = find_proofs(batch_dir).map_err(|err| {
+        CliError::Generic(format!("Error: could not read {}: {err}", batch_dir.display()))
+    })?;
+    if proof_paths.is_empty() {
+        return Err(CliError::Generic(format!(
+            "Error: no .{PROOF_EXT} files found in {}",
+            batch_dir.display()
+        )));
+    }
+
+    let (common_reference_string, preprocessed_program) = load_program(
+        backend,
+        backend_plugin,
+        program_dir.as_ref(),
+        circuit_build_path.as_ref().map(AsRef::as_ref),
+        force_preprocessing,
+        is_recursive,
+        compile_options,
+    )?;
+    let metadata = artifact_metadata(backend_plugin, &preprocessed_program);
+
+    let workers = thread::available_parallelism().map_or(1, usize::from);
+    let chunk_size = (proof_paths.len() + workers - 1) / workers;
+    let results: Vec<BatchResult> = thread::scope(|scope| {
+        let handles: Vec<_> = proof_paths
+            .chunks(chunk_size)
+            .map(|proof_paths| {
+                let (common_reference_string, preprocessed_program, metadata) =
+                    (&common_reference_string, &preprocessed_program, &metadata);
+                let handle = scope.spawn(move || {
+                    let backend = B::default();
+                    let verify = |proof_path: &PathBuf| {
+                        let proof_name = proof_path.file_stem().unwrap().to_string_lossy();
+                        verify_proof_file(
+                            &backend,
+                            backend_plugin,
+                            common_reference_string,
+                            preprocessed_program,
+                            metadata,
+                            proof_path,
+                            batch_dir,
+                            &proof_name,
+                            is_recursive,
+                            artifact_format,
+                        )
+                    };
+                    proof_paths
+                        .iter()
+                        .map(|proof_path| match verify(proof_path) {
+                            Ok(valid) => BatchResult::new(proof_path, valid, None),
+                            Err(err) => BatchResult::new(proof_path, false, Some(err.to_string())),
+                        })
+                        .collect::<Vec<_>>()
+                });
+                (proof_paths, handle)
+            })
+            .collect();
+
+        // A thread which panicked fails each of the proofs it was verifying, rather than the
+        // whole batch
+        handles
+            .into_iter()
+            .flat_map(|(proof_paths, handle)| {
+                handle.join().unwrap_or_else(|_| {
+                    let error = "Error: verification panicked".to_owned();
+                    vecmap(proof_paths, |path| BatchResult::new(path, false, Some(error.clone())))
+                })
+            })
+            .collect()
+    });
+
+    match report_format {
+        ReportFormat::Table => print!("{}", format_report_table(&results)),
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&results).unwrap()),
+    }
+
+    let failed = results.iter().filter(|result| !result.valid).count();
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(CliError::InvalidProofs { dir: batch_dir.to_path_buf(), failed, total: results.len() })
+    }
+}
+
+/// Returns the proofs in the given directory, ordered by name.
+fn find_proofs(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut proof_paths: Vec<_> = std::fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().map_or(false, |ext| ext == PROOF_EXT))
+        .collect();
+    proof_paths.sort();
+    Ok(proof_paths)
+}
+
+fn format_report_table(results: &[BatchResult]) -> String {
+    let width = results.iter().map(|result| result.proof.len()).max().unwrap_or(0).max(5);
+    let mut table = format!("{:width$}  RESULT\n", "PROOF");
+    for result in results {
+        let outcome = match &result.error {
+            Some(error) => error.as_str(),
+            None if result.valid => "valid",
+            None => "invalid",
+        };
+        table.push_str(&format!("{:width$}  {outcome}\n", result.proof));
+    }
+    table
+}
+
+/// Compiles the program, or reads it from its build artifact, preprocessing it with the keys
+/// needed to verify its proofs.
+fn load_program<B: Backend>(
+    backend: &B,
+    backend_plugin: Option<&BackendPlugin>,
+    program_dir: &Path,
+    circuit_build_path: Option<&Path>,
+    force_preprocessing: bool,
+    is_recursive: bool,
+    compile_options: &CompileOptions,
+) -> Result<(Vec<u8>, PreprocessedProgram), CliError<B>> {
+    match backend_plugin {
+        Some(backend_plugin) if is_recursive => {
+            return Err(CliError::Generic(format!(
+                "Error: backend `{}` does not support recursive proofs",
+                backend_plugin.name()
+            )));
+        }
+        _ => (),
+    }
+
+    let backend_name = resolved_backend_name(backend_plugin);
+    let common_reference_string = read_cached_common_reference_string(backend_name);
 
     let (common_reference_string, preprocessed_program) = match circuit_build_path {
+        // Backend plugins generate their own keys, so only the circuit is needed
+        _ if backend_plugin.is_some() => {
+            let program = match circuit_build_path {
+                Some(circuit_build_path) => read_program_from_file(circuit_build_path)?,
+                None => {
+                    let program = compile_circuit(backend, program_dir, compile_options)?;
+                    preprocess_program(backend, false, &common_reference_string, program)
+                        .map_err(CliError::ProofSystemCompilerError)?
+                }
+            };
+            (common_reference_string, program)
+        }
         Some(circuit_build_path) => {
             let program = read_program_from_file(circuit_build_path)?;
             let common_reference_string = update_common_reference_string(
@@ -83,43 +347,116 @@ fn verify_with_path<B: Backend, P: AsRef<Path>>(
             (common_reference_string, program)
         }
         None => {
-            let program = compile_circuit(backend, program_dir.as_ref(), compile_options)?;
+            let program = compile_circuit(backend, program_dir, compile_options)?;
             let common_reference_string =
                 update_common_reference_string(backend, &common_reference_string, &program.circuit)
                     .map_err(CliError::CommonReferenceStringError)?;
-            let program = preprocess_program(backend, true, &common_reference_string, program)
-                .map_err(CliError::ProofSystemCompilerError)?;
+            let keys_dir = program_dir.join(TARGET_DIR).join(KEYS_DIR);
+            let program = preprocess_program_with_cached_keys(
+                backend,
+                &common_reference_string,
+                program,
+                &keys_dir,
+                force_preprocessing,
+            )
+            .map_err(CliError::ProofSystemCompilerError)?;
             (common_reference_string, program)
         }
     };
 
-    write_cached_common_reference_string(&common_reference_string);
+    write_cached_common_reference_string(backend_name, &common_reference_string);
 
-    let PreprocessedProgram { abi, bytecode, verification_key, .. } = preprocessed_program;
+    Ok((common_reference_string, preprocessed_program))
+}
+
+fn artifact_metadata(
+    backend_plugin: Option<&BackendPlugin>,
+    program: &PreprocessedProgram,
+) -> ArtifactMetadata {
+    let backend_name = backend_plugin.map_or(program.backend.as_str(), |plugin| plugin.name());
+    let backend_version = backend_plugin.map_or(DEFAULT_BACKEND_VERSION, |plugin| plugin.version());
+    ArtifactMetadata::new(backend_name, backend_version, &program.bytecode)
+}
 
-    // Load public inputs (if any) from `verifier_name`.
-    let public_abi = abi.public_abi();
+/// Verifies the proof at `proof_path` against the public inputs in the toml file
+/// `inputs_name` of `inputs_dir`.
+#[allow(clippy::too_many_arguments)]
+fn verify_proof_file<B: Backend>(
+    backend: &B,
+    backend_plugin: Option<&BackendPlugin>,
+    common_reference_string: &[u8],
+    program: &PreprocessedProgram,
+    metadata: &ArtifactMetadata,
+    proof_path: &Path,
+    inputs_dir: &Path,
+    inputs_name: &str,
+    is_recursive: bool,
+    artifact_format: ArtifactFormat,
+) -> Result<bool, CliError<B>> {
+    // Load public inputs (if any) from `inputs_name`.
+    let public_abi = program.abi.clone().public_abi();
     let (public_inputs_map, return_value) =
-        read_inputs_from_file(program_dir, verifier_name.as_str(), Format::Toml, &public_abi)?;
+        read_inputs_from_file(inputs_dir, inputs_name, Format::Toml, &public_abi)?;
 
     let public_inputs = public_abi.encode(&public_inputs_map, return_value)?;
-    let proof = load_hex_data(&proof_path)?;
+    let proof = read_artifact(proof_path, artifact_format, metadata)?;
 
-    let verification_key = verification_key
-        .expect("Verification key should exist as `true` is passed to `preprocess_program`");
-    let valid_proof = verify_proof(
-        backend,
-        &common_reference_string,
-        &bytecode,
-        &proof,
-        public_inputs,
-        &verification_key,
-    )
-    .map_err(CliError::ProofSystemCompilerError)?;
+    let valid_proof = match backend_plugin {
+        Some(backend_plugin) => backend_plugin.verify(&program.bytecode, &proof, &public_inputs)?,
+        None => {
+            let verification_key = program.verification_key.as_ref().expect(
+                "Verification key should exist as the program is preprocessed with its keys",
+            );
+            verify_proof(
+                backend,
+                common_reference_string,
+                &program.bytecode,
+                &proof,
+                public_inputs,
+                verification_key,
+                is_recursive,
+            )
+            .map_err(CliError::ProofSystemCompilerError)?
+        }
+    };
+    Ok(valid_proof)
+}
 
-    if valid_proof {
-        Ok(())
-    } else {
-        Err(CliError::InvalidProof(proof_path))
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::{find_proofs, format_report_table, BatchResult};
+
+    #[test]
+    fn batch_proofs_are_the_proof_files_ordered_by_name() {
+        let batch_dir = TempDir::new("batch_dir").unwrap().into_path();
+        for file in ["b.proof", "a.proof", "a.toml", "notes.txt"] {
+            std::fs::write(batch_dir.join(file), "").unwrap();
+        }
+        std::fs::create_dir(batch_dir.join("c.proof")).unwrap();
+
+        let proofs = find_proofs(&batch_dir).unwrap();
+        assert_eq!(proofs, vec![batch_dir.join("a.proof"), batch_dir.join("b.proof")]);
+
+        assert!(find_proofs(&batch_dir.join("missing")).is_err());
+    }
+
+    #[test]
+    fn report_table_aligns_outcomes() {
+        let results = vec![
+            BatchResult { proof: "a.proof".to_owned(), valid: true, error: None },
+            BatchResult { proof: "longer.proof".to_owned(), valid: false, error: None },
+            BatchResult {
+                proof: "c.proof".to_owned(),
+                valid: false,
+                error: Some("Error: broken".to_owned()),
+            },
+        ];
+        let expected = "PROOF         RESULT\n\
+                        a.proof       valid\n\
+                        longer.proof  invalid\n\
+                        c.proof       Error: broken\n";
+        assert_eq!(format_report_table(&results), expected);
     }
 }
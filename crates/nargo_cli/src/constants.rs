@@ -1,23 +1,42 @@
 // Directories
+/// The directory for the disassembled Brillig bytecode of unconstrained functions
+pub(crate) const BRILLIG_DIR: &str = "brillig";
+/// The directory for the bindings generated by `nargo codegen`
+pub(crate) const CODEGEN_DIR: &str = "codegen";
 /// The directory for the `nargo contract` command output
 pub(crate) const CONTRACT_DIR: &str = "contract";
+/// The directory under the target directory for cached proving and verification keys
+pub(crate) const KEYS_DIR: &str = "keys";
 /// The directory to store serialized circuit proofs.
 pub(crate) const PROOFS_DIR: &str = "proofs";
 /// The directory to store Noir source files
 pub(crate) const SRC_DIR: &str = "src";
+/// The directory for the SSA of the program after each optimization pass
+pub(crate) const SSA_DIR: &str = "ssa";
 /// The directory to store circuits' serialized ACIR representations.
 pub(crate) const TARGET_DIR: &str = "target";
 
 // Files
 /// The file from which Nargo pulls prover inputs
 pub(crate) const PROVER_INPUT_FILE: &str = "Prover";
+/// The file to which `nargo prove --recursive` writes the arguments for verifying a proof
+/// recursively, unless the proof is named
+pub(crate) const RECURSION_INPUT_FILE: &str = "Recursion";
 /// The file from which Nargo pulls verifier inputs
 pub(crate) const VERIFIER_INPUT_FILE: &str = "Verifier";
 /// The package definition file for a Noir project.
 pub(crate) const PKG_FILE: &str = "Nargo.toml";
 
 // Extensions
+/// The extension for files containing disassembled Brillig bytecode.
+pub(crate) const BRILLIG_EXT: &str = "brillig";
+/// The extension for files containing Graphviz control flow graphs.
+pub(crate) const DOT_EXT: &str = "dot";
 /// The extension for files containing circuit proofs.
 pub(crate) const PROOF_EXT: &str = "proof";
+/// The extension for files containing the SSA of a program.
+pub(crate) const SSA_EXT: &str = "ssa";
+/// The extension for files containing verification keys.
+pub(crate) const VERIFICATION_KEY_EXT: &str = "vk";
 /// The extension for files containing proof witnesses.
 pub(crate) const WITNESS_EXT: &str = "tr";
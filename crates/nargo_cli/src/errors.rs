@@ -4,12 +4,16 @@ use acvm::{
 };
 use hex::FromHexError;
 use nargo::NargoError;
+use noirc_abi::compatibility::{BreakingChange, ABI_VERSION};
 use noirc_abi::errors::{AbiError, InputParserError};
 use noirc_errors::reporter::ReportedErrors;
 use std::path::PathBuf;
 use thiserror::Error;
 
-use crate::resolver::DependencyResolutionError;
+use crate::{
+    backends::{BackendPluginError, RemoteProverError, BACKENDS},
+    resolver::DependencyResolutionError,
+};
 
 #[derive(Debug, Error)]
 pub(crate) enum FilesystemError {
@@ -17,10 +21,22 @@ pub(crate) enum FilesystemError {
     PathNotValid(PathBuf),
     #[error("Error: could not parse hex build artifact (proof, proving and/or verification keys, ACIR checksum) ({0})")]
     HexArtifactNotValid(FromHexError),
+    #[error("Error: could not parse json build artifact {} ({1})", .0.display())]
+    JsonArtifactNotValid(PathBuf, serde_json::Error),
+    #[error("Error: {} was generated by backend {backend} {backend_version} for circuit {circuit_hash}, but the program is compiled by backend {expected_backend} {expected_backend_version} to circuit {expected_circuit_hash}", .path.display())]
+    MismatchedArtifact {
+        path: PathBuf,
+        backend: String,
+        backend_version: String,
+        circuit_hash: String,
+        expected_backend: String,
+        expected_backend_version: String,
+        expected_circuit_hash: String,
+    },
     #[error(
-        " Error: cannot find {0}.toml file.\n Expected location: {1:?} \n Please generate this file at the expected location."
+        " Error: cannot find {0} file.\n Expected location: {1:?} \n Please generate this file at the expected location."
     )]
-    MissingTomlFile(String, PathBuf),
+    MissingInputFile(String, PathBuf),
 
     /// Input parsing error
     #[error(transparent)]
@@ -38,9 +54,35 @@ pub(crate) enum CliError<B: Backend> {
     #[error("Error: destination {} already exists", .0.display())]
     DestinationAlreadyExists(PathBuf),
 
+    #[error("Error: {0} is not a known error code")]
+    UnknownErrorCode(String),
+
     #[error("Failed to verify proof {}", .0.display())]
     InvalidProof(PathBuf),
 
+    #[error("Failed to verify {failed} of the {total} proofs in {}", .dir.display())]
+    InvalidProofs { dir: PathBuf, failed: usize, total: usize },
+
+    #[error("Error: {} was written with ABI version {}, but only versions up to {} are supported", .0.display(), .1, ABI_VERSION)]
+    UnsupportedAbiVersion(PathBuf, u32),
+
+    #[error("The ABI has {} breaking change{} from {}:{}", .1.len(), if .1.len() == 1 { "" } else { "s" }, .0.display(), display_changes(.1))]
+    IncompatibleAbi(PathBuf, Vec<BreakingChange>),
+
+    #[error(
+        "The compiled program has hash {found}, which does not match the expected hash {expected}"
+    )]
+    MismatchedHash { expected: String, found: String },
+
+    #[error("Error: backend `{0}` is not available, as nargo is built with the backends: {} and no plugin of that name is installed", BACKENDS.join(", "))]
+    UnknownBackend(String),
+
+    #[error(transparent)]
+    BackendPluginError(#[from] BackendPluginError),
+
+    #[error(transparent)]
+    RemoteProverError(#[from] RemoteProverError),
+
     #[error(transparent)]
     ResolutionError(#[from] DependencyResolutionError),
 
@@ -77,6 +119,10 @@ pub(crate) enum CliError<B: Backend> {
     CommonReferenceStringError(<B as CommonReferenceString>::Error), // Unfortunately, Rust won't let us `impl From` over an Associated Type on a generic
 }
 
+fn display_changes(changes: &[BreakingChange]) -> String {
+    changes.iter().map(|change| format!("\n  - {change}")).collect()
+}
+
 impl<B: Backend> From<ReportedErrors> for CliError<B> {
     fn from(errors: ReportedErrors) -> Self {
         Self::ReportedErrors(errors)
@@ -1,12 +1,16 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
+use fm::FileManager;
 use nargo::manifest::{Dependency, PackageManifest};
-use noirc_driver::{add_dep, create_local_crate, create_non_local_crate};
+use noirc_driver::{
+    add_dep, create_local_crate, create_non_local_crate, enable_features, set_overflow_mode,
+};
 use noirc_frontend::{
-    graph::{CrateId, CrateType},
+    graph::{CrateGraph, CrateId, CrateType},
     hir::Context,
 };
 use thiserror::Error;
@@ -41,6 +45,10 @@ pub(crate) enum DependencyResolutionError {
     /// Dependency is not a valid crate
     #[error(transparent)]
     MalformedDependency(#[from] InvalidPackageError),
+
+    /// Attempted to enable a feature which the dependency does not declare.
+    #[error("dependency {dep_pkg_name} does not have a feature named `{feature}`")]
+    UnknownFeature { dep_pkg_name: String, feature: String },
 }
 
 #[derive(Debug, Clone)]
@@ -53,6 +61,44 @@ struct CachedDep {
     remote: bool,
 }
 
+/// The dependencies resolved so far, keyed by the directory of each, so that checking a
+/// package again does not read the manifests of its dependencies again. A dependency is
+/// resolved again once its manifest is modified.
+#[derive(Debug, Default)]
+pub(crate) struct DependencyCache {
+    dependencies: HashMap<PathBuf, (SystemTime, CachedDep)>,
+}
+
+impl DependencyCache {
+    fn retrieve_meta(
+        &mut self,
+        dir_path: &Path,
+        remote: bool,
+    ) -> Result<CachedDep, DependencyResolutionError> {
+        let manifest_path = super::find_package_manifest(dir_path)?;
+        let modified = manifest_path.metadata().and_then(|metadata| metadata.modified()).ok();
+
+        if let Some((cached_at, dep)) = self.dependencies.get(dir_path) {
+            if Some(*cached_at) == modified && dep.entry_path.exists() {
+                return Ok(dep.clone());
+            }
+        }
+
+        let (entry_path, crate_type) = super::lib_or_bin(dir_path)?;
+        let manifest = super::manifest::parse(manifest_path)?;
+        let dep = CachedDep { entry_path, crate_type, manifest, remote };
+        if let Some(modified) = modified {
+            self.dependencies.insert(dir_path.to_path_buf(), (modified, dep.clone()));
+        }
+        Ok(dep)
+    }
+}
+
+/// The features of each crate requested by the packages depending on it, along with the
+/// manifest declaring them. A crate is only compiled once however many packages depend on it,
+/// so it is compiled with every feature requested by any of them.
+type FeatureRequests = HashMap<CrateId, (PackageManifest, BTreeSet<String>)>;
+
 /// Resolves a toml file by either downloading the necessary git repo
 /// or it uses the repo on the cache.
 /// Downloading will be recursive, so if a package contains packages
@@ -65,20 +111,58 @@ struct CachedDep {
 pub(crate) fn resolve_root_manifest(
     dir_path: &std::path::Path,
 ) -> Result<Context, DependencyResolutionError> {
-    let mut context = Context::default();
+    resolve_root_manifest_cached(dir_path, FileManager::default(), &mut DependencyCache::default())
+}
+
+/// Resolves a toml file like [resolve_root_manifest], reusing the dependencies resolved when
+/// resolving other packages with the same cache. Sources are read through the given file
+/// manager, such as one holding the unsaved documents of an editor.
+pub(crate) fn resolve_root_manifest_cached(
+    dir_path: &Path,
+    file_manager: FileManager,
+    cache: &mut DependencyCache,
+) -> Result<Context, DependencyResolutionError> {
+    let mut context = Context::new(file_manager, CrateGraph::default());
     let (entry_path, crate_type) = super::lib_or_bin(dir_path)?;
 
     let manifest_path = super::find_package_manifest(dir_path)?;
     let manifest = super::manifest::parse(&manifest_path)?;
 
     let crate_id = create_local_crate(&mut context, entry_path, crate_type);
+    set_overflow_mode(&mut context, crate_id, manifest.package.overflow);
+    context.lint_levels = manifest.lints.clone();
+
+    let mut feature_requests = FeatureRequests::new();
+    request_features(&mut feature_requests, crate_id, &manifest, Vec::new(), true);
 
     let pkg_root = manifest_path.parent().expect("Every manifest path has a parent.");
-    resolve_manifest(&mut context, crate_id, manifest, pkg_root)?;
+    resolve_manifest(&mut context, crate_id, manifest, pkg_root, &mut feature_requests, cache)?;
+
+    for (crate_id, (manifest, requested)) in feature_requests {
+        enable_features(&mut context, crate_id, manifest.expand_features(&requested));
+    }
 
     Ok(context)
 }
 
+/// Records the features of a crate requested by a package depending on it, including its
+/// `default` feature unless the package opted out of it.
+fn request_features(
+    feature_requests: &mut FeatureRequests,
+    crate_id: CrateId,
+    manifest: &PackageManifest,
+    mut features: Vec<String>,
+    default_features: bool,
+) {
+    if default_features && manifest.has_feature("default") {
+        features.push("default".to_owned());
+    }
+
+    let (_, requested) =
+        feature_requests.entry(crate_id).or_insert_with(|| (manifest.clone(), BTreeSet::new()));
+    requested.extend(features);
+}
+
 // Resolves a config file by recursively resolving the dependencies in the config
 // Need to solve the case of a project trying to use itself as a dep
 //
@@ -90,12 +174,14 @@ fn resolve_manifest(
     parent_crate: CrateId,
     manifest: PackageManifest,
     pkg_root: &Path,
+    feature_requests: &mut FeatureRequests,
+    cache: &mut DependencyCache,
 ) -> Result<(), DependencyResolutionError> {
     let mut cached_packages: HashMap<PathBuf, (CrateId, CachedDep)> = HashMap::new();
 
     // First download and add these top level dependencies crates to the Driver
     for (dep_pkg_name, pkg_src) in manifest.dependencies.iter() {
-        let (dir_path, dep_meta) = cache_dep(pkg_src, pkg_root)?;
+        let (dir_path, dep_meta) = cache_dep(pkg_src, pkg_root, cache)?;
 
         let (entry_path, crate_type) = (&dep_meta.entry_path, &dep_meta.crate_type);
 
@@ -106,8 +192,24 @@ fn resolve_manifest(
         }
 
         let crate_id = create_non_local_crate(context, entry_path, *crate_type);
+        set_overflow_mode(context, crate_id, dep_meta.manifest.package.overflow);
         add_dep(context, parent_crate, crate_id, dep_pkg_name);
 
+        let features = pkg_src.features();
+        if let Some(feature) = features.iter().find(|f| !dep_meta.manifest.has_feature(f)) {
+            return Err(DependencyResolutionError::UnknownFeature {
+                dep_pkg_name: dep_pkg_name.to_string(),
+                feature: feature.clone(),
+            });
+        }
+        request_features(
+            feature_requests,
+            crate_id,
+            &dep_meta.manifest,
+            features.to_vec(),
+            pkg_src.default_features(),
+        );
+
         cached_packages.insert(dir_path, (crate_id, dep_meta));
     }
 
@@ -117,7 +219,14 @@ fn resolve_manifest(
             return Err(DependencyResolutionError::RemoteDepWithLocalDep { dependency_path });
         }
         // TODO: Why did it create a new resolver?
-        resolve_manifest(context, crate_id, dep_meta.manifest, &dependency_path)?;
+        resolve_manifest(
+            context,
+            crate_id,
+            dep_meta.manifest,
+            &dependency_path,
+            feature_requests,
+            cache,
+        )?;
     }
     Ok(())
 }
@@ -131,27 +240,71 @@ fn resolve_manifest(
 fn cache_dep(
     dep: &Dependency,
     pkg_root: &Path,
+    cache: &mut DependencyCache,
 ) -> Result<(PathBuf, CachedDep), DependencyResolutionError> {
-    fn retrieve_meta(
-        dir_path: &Path,
-        remote: bool,
-    ) -> Result<CachedDep, DependencyResolutionError> {
-        let (entry_path, crate_type) = super::lib_or_bin(dir_path)?;
-        let manifest_path = super::find_package_manifest(dir_path)?;
-        let manifest = super::manifest::parse(manifest_path)?;
-        Ok(CachedDep { entry_path, crate_type, manifest, remote })
-    }
-
     match dep {
-        Dependency::Github { git, tag } => {
+        Dependency::Github { git, tag, .. } => {
             let dir_path = clone_git_repo(git, tag).map_err(DependencyResolutionError::GitError)?;
-            let meta = retrieve_meta(&dir_path, true)?;
+            let meta = cache.retrieve_meta(&dir_path, true)?;
             Ok((dir_path, meta))
         }
-        Dependency::Path { path } => {
+        Dependency::Path { path, .. } => {
             let dir_path = pkg_root.join(path);
-            let meta = retrieve_meta(&dir_path, false)?;
+            let meta = cache.retrieve_meta(&dir_path, false)?;
             Ok((dir_path, meta))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use nargo::manifest::PackageManifest;
+    use tempdir::TempDir;
+
+    use super::DependencyCache;
+
+    const MANIFEST: &str = "[package]\nauthors = [\"\"]\n\n[dependencies]\n";
+
+    #[test]
+    fn dependencies_are_resolved_again_once_modified() {
+        let package_dir = TempDir::new("dependency").unwrap().into_path();
+        std::fs::create_dir(package_dir.join("src")).unwrap();
+        std::fs::write(package_dir.join("src").join("lib.nr"), "fn foo() {}").unwrap();
+        std::fs::write(package_dir.join("Nargo.toml"), MANIFEST).unwrap();
+
+        let mut cache = DependencyCache::default();
+        let dep = cache.retrieve_meta(&package_dir, false).unwrap();
+        assert!(dep.manifest.features.is_empty());
+
+        // An unmodified dependency is taken from the cache, so a manifest marked as cached is
+        // returned instead of the one on disk
+        let mut marked = dep.clone();
+        marked.manifest =
+            PackageManifest::from_toml_str(&format!("{MANIFEST}\n[features]\ncached = []\n"))
+                .unwrap();
+        let (_, cached) = cache.dependencies.get_mut(&package_dir).unwrap();
+        *cached = marked;
+        let dep = cache.retrieve_meta(&package_dir, false).unwrap();
+        assert!(dep.manifest.features.contains_key("cached"));
+
+        // A dependency whose manifest was modified since it was cached is read again
+        let (cached_at, _) = cache.dependencies.get_mut(&package_dir).unwrap();
+        *cached_at = SystemTime::UNIX_EPOCH;
+        let dep = cache.retrieve_meta(&package_dir, false).unwrap();
+        assert!(dep.manifest.features.is_empty());
+
+        // As is a dependency whose entry file was moved
+        let (_, cached) = cache.dependencies.get_mut(&package_dir).unwrap();
+        cached.manifest.features.insert("cached".to_owned(), Vec::new());
+        std::fs::rename(
+            package_dir.join("src").join("lib.nr"),
+            package_dir.join("src").join("main.nr"),
+        )
+        .unwrap();
+        let dep = cache.retrieve_meta(&package_dir, false).unwrap();
+        assert!(dep.manifest.features.is_empty());
+        assert_eq!(dep.entry_path, package_dir.join("src").join("main.nr"));
+    }
+}
@@ -0,0 +1,23 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::path::Path;
+use std::process::Command;
+
+use assert_fs::prelude::PathCopy;
+
+// A test_data program whose `assert_eq` of two structs implementing `Eq` fails
+const TEST_PROGRAM: &str = "tests/test_data_ssa_refactor/assert_eq_struct_fail";
+
+#[test]
+fn failing_assert_eq_reports_both_structs_after_the_message() {
+    let program_dir = assert_fs::TempDir::new().unwrap();
+    program_dir
+        .copy_from(Path::new(env!("CARGO_MANIFEST_DIR")).join(TEST_PROGRAM), &["**"])
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&program_dir).args(["execute", "--experimental-ssa"]);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "points should be equal: { x: 1, y: 2 } != { x: 2, y: 1 }",
+    ));
+}
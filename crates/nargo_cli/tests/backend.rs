@@ -0,0 +1,62 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::process::Command;
+
+use assert_fs::prelude::{FileWriteStr, PathChild};
+
+#[test]
+fn built_in_backends_are_listed_without_installation() {
+    let backends_dir = assert_fs::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.env("NARGO_BACKEND_CACHE_DIR", backends_dir.path()).args(["backend", "list"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("* acvm-backend-barretenberg (built in)"));
+}
+
+#[test]
+fn built_in_backends_cannot_be_installed() {
+    let backends_dir = assert_fs::TempDir::new().unwrap();
+    let plugin_dir = assert_fs::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.env("NARGO_BACKEND_CACHE_DIR", backends_dir.path())
+        .args(["backend", "install", "acvm-backend-barretenberg", "--path"])
+        .arg(plugin_dir.path());
+    cmd.assert().failure().stderr(predicate::str::contains("needs no installation"));
+
+    // Only plugins are installed, so their directory must be given
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.env("NARGO_BACKEND_CACHE_DIR", backends_dir.path()).args([
+        "backend",
+        "install",
+        "acvm-backend-barretenberg",
+    ]);
+    cmd.assert().failure().stderr(predicate::str::contains("--path"));
+}
+
+#[test]
+fn backends_built_as_dynamic_libraries_cannot_be_installed() {
+    let backends_dir = assert_fs::TempDir::new().unwrap();
+    let plugin_dir = assert_fs::TempDir::new().unwrap();
+    plugin_dir
+        .child("backend.toml")
+        .write_str("interface_version = 1\nversion = \"0.1.0\"\nlibrary = \"libbackend.so\"\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.args(["backend", "--help"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Backends built as dynamic libraries are not supported"));
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.env("NARGO_BACKEND_CACHE_DIR", backends_dir.path())
+        .args(["backend", "install", "library", "--path"])
+        .arg(plugin_dir.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("is a dynamic library, but plugins must be executables"));
+    assert!(!backends_dir.child("library").exists());
+}
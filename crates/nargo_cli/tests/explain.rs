@@ -0,0 +1,22 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::process::Command;
+
+#[test]
+fn explains_error_code() {
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.arg("explain").arg("E0007");
+    cmd.assert().success().stdout(predicate::str::contains("logical-and operator `&&`"));
+
+    // The `E` prefix and leading zeroes are optional
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.arg("explain").arg("7");
+    cmd.assert().success().stdout(predicate::str::contains("logical-and operator `&&`"));
+}
+
+#[test]
+fn rejects_unknown_error_code() {
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.arg("explain").arg("E9999");
+    cmd.assert().failure().stderr(predicate::str::contains("E9999 is not a known error code"));
+}
@@ -0,0 +1,76 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::process::Command;
+
+use assert_fs::prelude::{FileWriteStr, PathChild};
+
+#[test]
+fn lint_levels_from_manifest() {
+    let test_dir = assert_fs::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&test_dir).arg("new").arg("lints");
+    cmd.assert().success();
+
+    let project_dir = test_dir.child("lints");
+    project_dir
+        .child("src")
+        .child("main.nr")
+        .write_str("fn main(x : Field) {\n    let y = x;\n}\n")
+        .unwrap();
+
+    let manifest = std::fs::read_to_string(project_dir.child("Nargo.toml").path()).unwrap();
+    let write_lints = |lints: &str| {
+        project_dir
+            .child("Nargo.toml")
+            .write_str(&format!("{manifest}\n[lints]\n{lints}\n"))
+            .unwrap();
+    };
+
+    // Denied lints fail compilation
+    write_lints(r#"unused = "deny""#);
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).arg("check");
+    cmd.assert().failure().stderr(predicate::str::contains("the `unused` lint is denied"));
+
+    // Allowed lints aren't reported, even when denying all other warnings
+    write_lints(r#"unused = "allow""#);
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).args(["check", "--deny-warnings"]);
+    cmd.assert().success().stderr(predicate::str::contains("unused variable").not());
+}
+
+#[test]
+fn deprecated_struct_types_are_reported_except_in_impls() {
+    let test_dir = assert_fs::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&test_dir).arg("new").arg("deprecated");
+    cmd.assert().success();
+
+    let project_dir = test_dir.child("deprecated");
+    project_dir
+        .child("src")
+        .child("main.nr")
+        .write_str(
+            "#[deprecated(\"use Point instead\")]
+struct Coordinates { x: Field, y: Field }
+
+impl Coordinates {
+    fn sum(self) -> Field { self.x + self.y }
+}
+
+fn sum(coordinates: Coordinates) -> Field { coordinates.sum() }
+
+fn main(x : Field) { assert(x != 0); }
+",
+        )
+        .unwrap();
+
+    // Only the parameter of `sum` uses the struct, as implementing its methods does not
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).arg("check");
+    cmd.assert().success().stderr(predicate::function(|stderr: &str| {
+        stderr.matches("use of deprecated struct `Coordinates`").count() == 1
+    }));
+}
@@ -0,0 +1,35 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::process::Command;
+
+use assert_fs::prelude::{FileWriteStr, PathChild};
+
+#[test]
+fn json_message_format() {
+    let test_dir = assert_fs::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&test_dir).arg("new").arg("json_diagnostics");
+    cmd.assert().success();
+
+    let project_dir = test_dir.child("json_diagnostics");
+    project_dir
+        .child("src")
+        .child("main.nr")
+        .write_str("fn main(x : Field) {\n    let y = x;\n}\n")
+        .unwrap();
+
+    // Each diagnostic is written to stdout as a single line of JSON
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).arg("check").arg("--message-format").arg("json");
+    cmd.assert().success().stdout(
+        predicate::str::contains(r#""severity":"warning","code":"E0036""#)
+            .and(predicate::str::contains(r#""start":29,"end":30,"line":2,"column":9"#))
+            .and(predicate::str::contains(r#""replacement":"_y""#)),
+    );
+
+    // Warnings are reported as errors when they are denied
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).args(["check", "--message-format", "json", "--deny-warnings"]);
+    cmd.assert().failure().stdout(predicate::str::contains(r#""severity":"error","code":"E0036""#));
+}
@@ -0,0 +1,53 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::process::Command;
+
+use assert_fs::prelude::{FileWriteStr, PathChild};
+
+#[test]
+fn explains_conflicting_numeric_generic() {
+    let test_dir = assert_fs::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&test_dir).arg("new").arg("numeric_generics");
+    cmd.assert().success();
+
+    let project_dir = test_dir.child("numeric_generics");
+    project_dir
+        .child("src")
+        .child("main.nr")
+        .write_str(include_str!("compile_tests_data/fail/numeric_generic_conflict.nr"))
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).args(["check", "--message-format", "json"]);
+    cmd.assert().failure().stdout(
+        predicate::str::contains("Expected type [Field; 2], found type [Field; 3]")
+            .and(predicate::str::contains("`N` was inferred to be `2` from this argument"))
+            .and(predicate::str::contains("`foo` declares this parameter as `[Field; N]`")),
+    );
+}
+
+#[test]
+fn explains_numeric_generics_up_the_call_stack() {
+    let test_dir = assert_fs::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&test_dir).arg("new").arg("numeric_generics");
+    cmd.assert().success();
+
+    let project_dir = test_dir.child("numeric_generics");
+    project_dir
+        .child("src")
+        .child("main.nr")
+        .write_str(include_str!("compile_tests_data/fail/numeric_generic_call_chain.nr"))
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).args(["check", "--message-format", "json"]);
+    cmd.assert().failure().stdout(
+        predicate::str::contains("`M` was inferred to be `K`, a generic of `inner`")
+            .and(predicate::str::contains("`inner` is called by `outer` with `K` = `N`"))
+            .and(predicate::str::contains("`outer` is called by `main` with `N` = `2`")),
+    );
+}
@@ -0,0 +1,98 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::path::Path;
+use std::process::Command;
+
+use assert_fs::prelude::{FileWriteStr, PathChild};
+
+// Merging the stores of both branches of an `if` used to follow the order of a hash map
+const PROGRAM: &str = "fn main(x: Field, y: pub Field) {
+    let mut a = 0;
+    let mut b = 0;
+    let mut c = 0;
+    if x == y {
+        a = x;
+        b = y;
+        c = 3;
+    } else {
+        a = y;
+        c = x;
+    }
+    assert(a + b + c != 1);
+}
+";
+
+// Both SSA pipelines must produce reproducible builds
+const PIPELINES: [&[&str]; 2] = [&[], &["--experimental-ssa"]];
+
+fn new_project(parent: &assert_fs::TempDir, name: &str) -> assert_fs::fixture::ChildPath {
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(parent).arg("new").arg(name);
+    cmd.assert().success();
+
+    let project_dir = parent.child(name);
+    project_dir.child("src").child("main.nr").write_str(PROGRAM).unwrap();
+    project_dir
+}
+
+fn compile(
+    project_dir: &Path,
+    pipeline: &[&str],
+    extra_args: &[&str],
+) -> assert_cmd::assert::Assert {
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(project_dir).args(["compile", "main"]).args(pipeline).args(extra_args);
+    cmd.assert()
+}
+
+fn artifact_hash(project_dir: &Path) -> String {
+    let artifact = std::fs::read_to_string(project_dir.join("target").join("main.json")).unwrap();
+    let artifact: serde_json::Value = serde_json::from_str(&artifact).unwrap();
+    artifact["hash"].as_str().expect("artifacts embed their hash").to_owned()
+}
+
+#[test]
+fn builds_are_reproducible() {
+    let first_dir = assert_fs::TempDir::new().unwrap();
+    let second_dir = assert_fs::TempDir::new().unwrap();
+
+    // The same program compiled in separate processes and directories has the same hash
+    let first_project = new_project(&first_dir, "first");
+    let second_project = new_project(&second_dir, "second");
+    for pipeline in PIPELINES {
+        compile(first_project.path(), pipeline, &[]).success();
+        compile(second_project.path(), pipeline, &[]).success();
+        compile(first_project.path(), pipeline, &[]).success();
+
+        let hash = artifact_hash(first_project.path());
+        assert_eq!(hash, artifact_hash(second_project.path()), "pipeline: {pipeline:?}");
+
+        let first_artifact = first_project.path().join("target").join("main.json");
+        let second_artifact = second_project.path().join("target").join("main.json");
+        assert_eq!(
+            std::fs::read(first_artifact).unwrap(),
+            std::fs::read(second_artifact).unwrap(),
+            "pipeline: {pipeline:?}"
+        );
+
+        // `--check-hash` accepts the hash of a previous build, with or without its `0x` prefix
+        compile(second_project.path(), pipeline, &["--check-hash", &hash]).success();
+        compile(second_project.path(), pipeline, &["--check-hash", &format!("0x{hash}")]).success();
+    }
+}
+
+#[test]
+fn check_hash_rejects_mismatched_builds() {
+    let test_dir = assert_fs::TempDir::new().unwrap();
+    let project = new_project(&test_dir, "mismatch");
+
+    let wrong_hash = "0".repeat(64);
+    for pipeline in PIPELINES {
+        compile(project.path(), pipeline, &["--check-hash", &wrong_hash])
+            .failure()
+            .stderr(predicate::str::contains("does not match the expected hash"));
+
+        // The artifact is not written when the hash does not match
+        assert!(!project.path().join("target").join("main.json").exists());
+    }
+}
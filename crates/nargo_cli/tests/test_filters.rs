@@ -0,0 +1,49 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::process::Command;
+
+use assert_fs::prelude::{FileWriteStr, PathChild};
+
+#[test]
+fn exact_test_names_do_not_match_longer_names() {
+    let test_dir = assert_fs::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&test_dir).arg("new").arg("filters");
+    cmd.assert().success();
+
+    let project_dir = test_dir.child("filters");
+    project_dir
+        .child("src")
+        .child("main.nr")
+        .write_str(
+            "fn main() {}
+
+#[test]
+fn test_add() {
+    assert(1 + 1 == 2);
+}
+
+#[test]
+fn test_add_twice() {
+    assert(1 + 1 + 1 == 2);
+}
+",
+        )
+        .unwrap();
+
+    // Without `--exact`, every test containing the name is run
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).args(["test", "test_add"]);
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("Running 2 test functions"))
+        .stderr(predicate::str::contains("1 test failed"));
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).args(["test", "test_add", "--exact"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Running 1 test functions"))
+        .stderr(predicate::str::contains("Testing test_add_twice").not());
+}
@@ -0,0 +1,71 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::process::Command;
+
+use assert_fs::prelude::{FileWriteStr, PathChild};
+
+#[test]
+fn points_at_trait_declared_in_another_file() {
+    let test_dir = assert_fs::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&test_dir).arg("new").arg("trait_impls");
+    cmd.assert().success();
+
+    let project_dir = test_dir.child("trait_impls");
+    project_dir
+        .child("src")
+        .child("hasher.nr")
+        .write_str("trait Hasher {\n    fn hash(self) -> Field;\n}\n")
+        .unwrap();
+    project_dir
+        .child("src")
+        .child("main.nr")
+        .write_str("mod hasher;\nuse hasher::Hasher;\n\nimpl Hasher for Field {}\n\nfn main() {}\n")
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).args(["check", "--message-format", "json"]);
+    cmd.assert().failure().stdout(
+        predicate::str::contains("Method hash of trait Hasher is not implemented")
+            .and(predicate::str::contains("hash is declared by the trait here"))
+            .and(predicate::str::contains("hasher.nr")),
+    );
+}
+
+#[test]
+fn generic_impls_overlap_the_impls_for_their_instances() {
+    let test_dir = assert_fs::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&test_dir).arg("new").arg("trait_impls");
+    cmd.assert().success();
+
+    let project_dir = test_dir.child("trait_impls");
+    let check_impls = |impls: [&str; 2]| {
+        let program = format!(
+            "trait Describe {{\n    fn describe(self) -> Field;\n}}\n\n\
+             struct Foo<T> {{ x: T }}\n\n{}\n\n{}\n\nfn main() {{}}\n",
+            impls[0], impls[1]
+        );
+        project_dir.child("src").child("main.nr").write_str(&program).unwrap();
+
+        let mut cmd = Command::cargo_bin("nargo").unwrap();
+        cmd.current_dir(&project_dir).arg("check");
+        cmd.assert()
+    };
+    let concrete_impl = |typ: &str| {
+        format!("impl Describe for Foo<{typ}> {{\n    fn describe(self) -> Field {{ 1 }}\n}}")
+    };
+    let generic_impl = "impl<T> Describe for Foo<T> {\n    fn describe(self) -> Field { 2 }\n}";
+
+    // The overlap is found whichever impl is declared first
+    check_impls([&concrete_impl("u8"), generic_impl])
+        .failure()
+        .stderr(predicate::str::contains("Conflicting impls of trait Describe for type Foo<T>"));
+    check_impls([generic_impl, &concrete_impl("u8")])
+        .failure()
+        .stderr(predicate::str::contains("Conflicting impls of trait Describe for type Foo<u8>"));
+
+    check_impls([&concrete_impl("u8"), &concrete_impl("bool")]).success();
+}
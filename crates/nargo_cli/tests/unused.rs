@@ -0,0 +1,38 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::process::Command;
+
+use assert_fs::prelude::{FileWriteStr, PathChild};
+
+#[test]
+fn unused_imports_and_functions_are_reported() {
+    let test_dir = assert_fs::TempDir::new().unwrap();
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&test_dir).arg("new").arg("unused");
+    cmd.assert().success();
+
+    let project_dir = test_dir.child("unused");
+    project_dir
+        .child("src")
+        .child("main.nr")
+        .write_str(include_str!("compile_tests_data/pass/unused.nr"))
+        .unwrap();
+
+    // Only the items which are not marked `#[allow(unused)]` are reported
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).arg("check");
+    cmd.assert().success().stderr(
+        predicate::str::contains("unused import std")
+            .and(predicate::str::contains("function never_called is never called"))
+            .and(predicate::str::contains("unused import hash").not())
+            .and(predicate::str::contains("function helper is never called").not()),
+    );
+
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(&project_dir).args(["check", "--deny-warnings"]);
+    cmd.assert().failure().stderr(
+        predicate::str::contains("unused import std")
+            .and(predicate::str::contains("function never_called is never called")),
+    );
+}
@@ -0,0 +1,61 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::path::Path;
+use std::process::Command;
+
+use assert_fs::prelude::{FileWriteStr, PathChild, PathCopy};
+
+// A test_data program with inputs in its Prover.toml
+const TEST_PROGRAM: &str = "tests/test_data/1_mul";
+
+fn nargo(program_dir: &Path, args: &[&str]) -> assert_cmd::assert::Assert {
+    let mut cmd = Command::cargo_bin("nargo").unwrap();
+    cmd.current_dir(program_dir).args(args);
+    cmd.assert()
+}
+
+#[test]
+fn verify_batch_of_proofs() {
+    let program_dir = assert_fs::TempDir::new().unwrap();
+    program_dir
+        .copy_from(Path::new(env!("CARGO_MANIFEST_DIR")).join(TEST_PROGRAM), &["**"])
+        .unwrap();
+    let proofs_dir = program_dir.child("proofs");
+
+    // Each proof is verified against the public inputs in the toml file of the same name
+    for proof_name in ["a", "b"] {
+        nargo(&program_dir, &["prove", proof_name]).success();
+        std::fs::copy(
+            program_dir.child("Verifier.toml"),
+            proofs_dir.child(format!("{proof_name}.toml")),
+        )
+        .unwrap();
+    }
+
+    nargo(&program_dir, &["verify", "--batch", "proofs"])
+        .success()
+        .stdout(predicate::str::contains("a.proof  valid"))
+        .stdout(predicate::str::contains("b.proof  valid"));
+
+    // A proof which cannot be read is reported along with the rest of the batch
+    proofs_dir.child("c.proof").write_str("not a proof").unwrap();
+    std::fs::copy(program_dir.child("Verifier.toml"), proofs_dir.child("c.toml")).unwrap();
+
+    nargo(&program_dir, &["verify", "--batch", "proofs", "--report-format", "json"])
+        .failure()
+        .stdout(predicate::str::contains(r#""proof": "a.proof""#))
+        .stdout(predicate::str::contains(r#""proof": "c.proof""#))
+        .stdout(predicate::str::contains(r#""error""#));
+}
+
+#[test]
+fn verify_batch_of_a_missing_directory() {
+    let program_dir = assert_fs::TempDir::new().unwrap();
+    program_dir
+        .copy_from(Path::new(env!("CARGO_MANIFEST_DIR")).join(TEST_PROGRAM), &["**"])
+        .unwrap();
+
+    nargo(&program_dir, &["verify", "--batch", "missing"])
+        .failure()
+        .stderr(predicate::str::contains("could not read missing"));
+}
@@ -0,0 +1,212 @@
+//! Conversions between the values of an ABI and Rust types, used by the bindings which
+//! `nargo codegen --lang rust` generates for a program so that hosts need not build the
+//! [`InputMap`] of the program by hand.
+use std::collections::BTreeMap;
+
+pub use acvm::FieldElement;
+
+use crate::{errors::AbiError, input_parser::InputValue, InputMap};
+
+mod rust;
+
+pub use rust::generate_rust_bindings;
+
+/// A Rust type which the values of some ABI type convert to and from.
+pub trait AbiValue: Sized {
+    fn into_input_value(self) -> InputValue;
+
+    /// Returns `None` if the value does not fit into the Rust type.
+    fn from_input_value(value: InputValue) -> Option<Self>;
+}
+
+/// Removes the value of the named parameter from an [`InputMap`], converting it into a Rust type.
+pub fn take_input<T: AbiValue>(input_map: &mut InputMap, name: &str) -> Result<T, AbiError> {
+    let value = input_map.remove(name).ok_or_else(|| AbiError::MissingParam(name.to_owned()))?;
+    T::from_input_value(value).ok_or_else(|| AbiError::UnexpectedValue(name.to_owned()))
+}
+
+impl AbiValue for FieldElement {
+    fn into_input_value(self) -> InputValue {
+        InputValue::Field(self)
+    }
+
+    fn from_input_value(value: InputValue) -> Option<Self> {
+        match value {
+            InputValue::Field(field) => Some(field),
+            _ => None,
+        }
+    }
+}
+
+impl AbiValue for bool {
+    fn into_input_value(self) -> InputValue {
+        InputValue::Field(self.into())
+    }
+
+    fn from_input_value(value: InputValue) -> Option<Self> {
+        let field = FieldElement::from_input_value(value)?;
+        if field.is_one() {
+            Some(true)
+        } else {
+            field.is_zero().then_some(false)
+        }
+    }
+}
+
+macro_rules! impl_abi_value_for_unsigned {
+    ($($typ:ty),*) => {$(
+        impl AbiValue for $typ {
+            fn into_input_value(self) -> InputValue {
+                InputValue::Field(FieldElement::from(self as u128))
+            }
+
+            fn from_input_value(value: InputValue) -> Option<Self> {
+                let field = FieldElement::from_input_value(value)?;
+                (field.num_bits() <= <$typ>::BITS).then(|| field.to_u128() as $typ)
+            }
+        }
+    )*};
+}
+
+impl_abi_value_for_unsigned!(u8, u16, u32, u64, u128);
+
+macro_rules! impl_abi_value_for_signed {
+    ($($typ:ty),*) => {$(
+        impl AbiValue for $typ {
+            fn into_input_value(self) -> InputValue {
+                // Negative values are given by their field negation, like in input files
+                InputValue::Field(FieldElement::from(self as i128))
+            }
+
+            fn from_input_value(value: InputValue) -> Option<Self> {
+                let field = FieldElement::from_input_value(value)?;
+                if field.num_bits() <= <$typ>::BITS {
+                    // Values decoded from a witness are in two's complement
+                    return Some(field.to_u128() as $typ);
+                }
+                let magnitude = FieldElement::zero() - field;
+                let min_magnitude = 1_u128 << (<$typ>::BITS - 1);
+                (magnitude.num_bits() <= 128 && magnitude.to_u128() <= min_magnitude)
+                    .then(|| (magnitude.to_u128() as i128).wrapping_neg() as $typ)
+            }
+        }
+    )*};
+}
+
+impl_abi_value_for_signed!(i8, i16, i32, i64, i128);
+
+impl AbiValue for String {
+    fn into_input_value(self) -> InputValue {
+        InputValue::String(self)
+    }
+
+    fn from_input_value(value: InputValue) -> Option<Self> {
+        match value {
+            InputValue::String(string) => Some(string),
+            _ => None,
+        }
+    }
+}
+
+impl<T: AbiValue> AbiValue for Vec<T> {
+    fn into_input_value(self) -> InputValue {
+        InputValue::Vec(self.into_iter().map(T::into_input_value).collect())
+    }
+
+    fn from_input_value(value: InputValue) -> Option<Self> {
+        match value {
+            InputValue::Vec(elements) => elements.into_iter().map(T::from_input_value).collect(),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_abi_value_for_tuple {
+    ($(($($name:ident),+)),*) => {$(
+        impl<$($name: AbiValue),+> AbiValue for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn into_input_value(self) -> InputValue {
+                let ($($name,)+) = self;
+                InputValue::Vec(vec![$($name.into_input_value()),+])
+            }
+
+            fn from_input_value(value: InputValue) -> Option<Self> {
+                let mut elements = tuple_elements(value)?;
+                let tuple = ($($name::from_input_value(elements.next()?)?,)+);
+                elements.next().is_none().then_some(tuple)
+            }
+        }
+    )*};
+}
+
+impl_abi_value_for_tuple!(
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G),
+    (A, B, C, D, E, F, G, H),
+    (A, B, C, D, E, F, G, H, I),
+    (A, B, C, D, E, F, G, H, I, J),
+    (A, B, C, D, E, F, G, H, I, J, K),
+    (A, B, C, D, E, F, G, H, I, J, K, L)
+);
+
+/// Returns the elements of the value of an ABI tuple, for tuples and the tuple structs of
+/// generated bindings.
+pub fn tuple_elements(value: InputValue) -> Option<std::vec::IntoIter<InputValue>> {
+    match value {
+        InputValue::Vec(elements) => Some(elements.into_iter()),
+        _ => None,
+    }
+}
+
+/// Converts the fields of a struct into the value of an ABI struct, for the structs of generated
+/// bindings.
+pub fn struct_into_input_value(fields: Vec<(&str, InputValue)>) -> InputValue {
+    let fields = fields.into_iter().map(|(name, value)| (name.to_owned(), value));
+    InputValue::Struct(BTreeMap::from_iter(fields))
+}
+
+/// Returns the fields of the value of an ABI struct, for the structs of generated bindings.
+pub fn struct_fields(value: InputValue) -> Option<BTreeMap<String, InputValue>> {
+    match value {
+        InputValue::Struct(fields) => Some(fields),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::FieldElement;
+
+    use super::AbiValue;
+    use crate::input_parser::InputValue;
+
+    #[test]
+    fn signed_integers_round_trip() {
+        for value in [0_i8, 5, -1, i8::MIN, i8::MAX] {
+            let input_value = value.into_input_value();
+            assert_eq!(i8::from_input_value(input_value), Some(value));
+        }
+
+        // Values decoded from a witness are in two's complement
+        let decoded = InputValue::Field(FieldElement::from(255_u128));
+        assert_eq!(i8::from_input_value(decoded), Some(-1));
+
+        let too_small = (-129_i16).into_input_value();
+        assert_eq!(i8::from_input_value(too_small), None);
+    }
+
+    #[test]
+    fn tuples_and_vectors_round_trip() {
+        let value = vec![(1_u8, true), (2, false)];
+        let input_value = value.clone().into_input_value();
+        assert_eq!(Vec::<(u8, bool)>::from_input_value(input_value), Some(value));
+
+        let too_large = 256_u16.into_input_value();
+        assert_eq!(u8::from_input_value(too_large), None);
+    }
+}
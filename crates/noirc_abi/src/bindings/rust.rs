@@ -0,0 +1,324 @@
+use std::fmt::Write;
+
+use iter_extended::vecmap;
+
+use crate::{Abi, AbiType, Sign, MAIN_RETURN_NAME};
+
+/// The name of the struct holding the parameters of the program's `main` function
+const INPUTS_STRUCT: &str = "Inputs";
+
+/// The most elements of a tuple which the standard library derives traits for. Wider tuples are
+/// declared as tuple structs instead.
+const MAX_TUPLE_LEN: usize = 12;
+
+const HEADER: &str = "\
+//! Bindings of the ABI of a Noir program, generated by `nargo codegen --lang rust`.
+#![allow(dead_code, unused_imports)]
+use noirc_abi::bindings::{
+    struct_fields, struct_into_input_value, take_input, tuple_elements, AbiValue, FieldElement,
+};
+use noirc_abi::{errors::AbiError, input_parser::InputValue, InputMap};
+use serde::{Deserialize, Serialize};
+";
+
+/// Generates the source of Rust bindings for an ABI: a struct holding the parameters of the
+/// program's `main` function, which converts to and from an `InputMap`, along with a struct for
+/// each struct type and each tuple type too wide for Rust's tuples found in the ABI. The bindings
+/// depend on the `noirc_abi` and `serde` crates.
+pub fn generate_rust_bindings(abi: &Abi) -> String {
+    let mut generator = Generator::default();
+    let parameters = vecmap(&abi.parameters, |param| {
+        (param.name.clone(), generator.rust_type(&param.typ, &param.name))
+    });
+    let return_type =
+        abi.return_type.as_ref().map(|typ| generator.rust_type(typ, MAIN_RETURN_NAME));
+
+    let mut source = HEADER.to_owned();
+    for (name, fields) in &generator.structs {
+        match fields {
+            Fields::Named(fields) => write_struct(&mut source, name, fields),
+            Fields::Unnamed(fields) => write_tuple_struct(&mut source, name, fields),
+        }
+    }
+    write_inputs_struct(&mut source, &parameters);
+
+    // Structs returned by `main` are already named after the return value
+    if let Some(return_type) = return_type.filter(|typ| typ != "Return") {
+        source.push_str("\n/// The return value of the program's `main` function\n");
+        writeln!(source, "pub type Return = {return_type};").unwrap();
+    }
+    source
+}
+
+#[derive(Default)]
+struct Generator {
+    /// The structs to declare, along with their fields
+    structs: Vec<(String, Fields)>,
+}
+
+/// The fields of a declared struct
+#[derive(PartialEq)]
+enum Fields {
+    /// The names and Rust types of the fields of a struct
+    Named(Vec<(String, String)>),
+    /// The Rust types of the elements of a tuple wider than [MAX_TUPLE_LEN]
+    Unnamed(Vec<String>),
+}
+
+impl Generator {
+    /// Returns the Rust type of an ABI type, declaring structs named after the `path` of the
+    /// value the type is found at.
+    fn rust_type(&mut self, typ: &AbiType, path: &str) -> String {
+        match typ {
+            AbiType::Field => "FieldElement".to_owned(),
+            AbiType::Boolean => "bool".to_owned(),
+            AbiType::Integer { sign, width } => {
+                let prefix = match sign {
+                    Sign::Unsigned => "u",
+                    Sign::Signed => "i",
+                };
+                // Integers wider than the largest Rust integer are left as field elements
+                match [8, 16, 32, 64, 128].into_iter().find(|bits| width <= bits) {
+                    Some(bits) => format!("{prefix}{bits}"),
+                    None => "FieldElement".to_owned(),
+                }
+            }
+            AbiType::String { .. } => "String".to_owned(),
+            AbiType::Array { typ, .. } => format!("Vec<{}>", self.rust_type(typ, path)),
+            AbiType::Tuple { fields } => {
+                let fields = fields.iter().enumerate();
+                let fields =
+                    vecmap(fields, |(index, typ)| self.rust_type(typ, &format!("{path}_{index}")));
+                match fields.as_slice() {
+                    [field] => format!("({field},)"),
+                    _ if fields.len() > MAX_TUPLE_LEN => {
+                        self.declare_struct(pascal_case(path), Fields::Unnamed(fields))
+                    }
+                    _ => format!("({})", fields.join(", ")),
+                }
+            }
+            AbiType::Struct { fields } => {
+                let fields = vecmap(fields, |(name, typ)| {
+                    (name.clone(), self.rust_type(typ, &format!("{path}_{name}")))
+                });
+                self.declare_struct(pascal_case(path), Fields::Named(fields))
+            }
+        }
+    }
+
+    /// Declares a struct unless one with the same name and fields was declared already,
+    /// returning its name. Structs of different fields are told apart by a numeric suffix.
+    fn declare_struct(&mut self, name: String, fields: Fields) -> String {
+        let mut unique_name = name.clone();
+        for suffix in 2.. {
+            if unique_name != INPUTS_STRUCT {
+                match self.structs.iter().find(|(declared, _)| declared == &unique_name) {
+                    Some((_, declared_fields)) if declared_fields == &fields => break,
+                    Some(_) => (),
+                    None => {
+                        self.structs.push((unique_name.clone(), fields));
+                        break;
+                    }
+                }
+            }
+            unique_name = format!("{name}{suffix}");
+        }
+        unique_name
+    }
+}
+
+fn write_struct(source: &mut String, name: &str, fields: &[(String, String)]) {
+    source.push('\n');
+    write_struct_declaration(source, name, fields);
+
+    writeln!(source, "\nimpl AbiValue for {name} {{").unwrap();
+    source.push_str("    fn into_input_value(self) -> InputValue {\n");
+    source.push_str("        struct_into_input_value(vec![\n");
+    for (field, _) in fields {
+        let ident = rust_ident(field);
+        writeln!(source, "            (\"{field}\", self.{ident}.into_input_value()),").unwrap();
+    }
+    source.push_str("        ])\n    }\n\n");
+
+    source.push_str("    fn from_input_value(value: InputValue) -> Option<Self> {\n");
+    if fields.is_empty() {
+        source.push_str("        struct_fields(value)?;\n");
+    } else {
+        source.push_str("        let mut fields = struct_fields(value)?;\n");
+    }
+    writeln!(source, "        Some({name} {{").unwrap();
+    for (field, _) in fields {
+        let ident = rust_ident(field);
+        writeln!(
+            source,
+            "            {ident}: AbiValue::from_input_value(fields.remove(\"{field}\")?)?,"
+        )
+        .unwrap();
+    }
+    source.push_str("        })\n    }\n}\n");
+}
+
+fn write_tuple_struct(source: &mut String, name: &str, fields: &[String]) {
+    source.push_str("\n#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n");
+    let fields_declaration = vecmap(fields, |typ| format!("pub {typ}"));
+    writeln!(source, "pub struct {name}({});", fields_declaration.join(", ")).unwrap();
+
+    writeln!(source, "\nimpl AbiValue for {name} {{").unwrap();
+    source.push_str("    fn into_input_value(self) -> InputValue {\n");
+    source.push_str("        InputValue::Vec(vec![\n");
+    for index in 0..fields.len() {
+        writeln!(source, "            self.{index}.into_input_value(),").unwrap();
+    }
+    source.push_str("        ])\n    }\n\n");
+
+    source.push_str("    fn from_input_value(value: InputValue) -> Option<Self> {\n");
+    source.push_str("        let mut elements = tuple_elements(value)?;\n");
+    writeln!(source, "        let tuple = {name}(").unwrap();
+    for _ in fields {
+        source.push_str("            AbiValue::from_input_value(elements.next()?)?,\n");
+    }
+    source.push_str("        );\n");
+    source.push_str("        elements.next().is_none().then_some(tuple)\n    }\n}\n");
+}
+
+fn write_inputs_struct(source: &mut String, parameters: &[(String, String)]) {
+    source.push_str("\n/// The parameters of the program's `main` function\n");
+    write_struct_declaration(source, INPUTS_STRUCT, parameters);
+
+    writeln!(source, "\nimpl From<{INPUTS_STRUCT}> for InputMap {{").unwrap();
+    writeln!(source, "    fn from(inputs: {INPUTS_STRUCT}) -> InputMap {{").unwrap();
+    source.push_str("        InputMap::from([\n");
+    for (name, _) in parameters {
+        let ident = rust_ident(name);
+        writeln!(source, "            (\"{name}\".to_owned(), inputs.{ident}.into_input_value()),")
+            .unwrap();
+    }
+    source.push_str("        ])\n    }\n}\n");
+
+    writeln!(source, "\nimpl TryFrom<InputMap> for {INPUTS_STRUCT} {{").unwrap();
+    source.push_str("    type Error = AbiError;\n\n");
+    let input_map = if parameters.is_empty() { "_input_map" } else { "mut input_map" };
+    writeln!(
+        source,
+        "    fn try_from({input_map}: InputMap) -> Result<{INPUTS_STRUCT}, AbiError> {{"
+    )
+    .unwrap();
+    writeln!(source, "        Ok({INPUTS_STRUCT} {{").unwrap();
+    for (name, _) in parameters {
+        let ident = rust_ident(name);
+        writeln!(source, "            {ident}: take_input(&mut input_map, \"{name}\")?,").unwrap();
+    }
+    source.push_str("        })\n    }\n}\n");
+}
+
+fn write_struct_declaration(source: &mut String, name: &str, fields: &[(String, String)]) {
+    source.push_str("#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n");
+    writeln!(source, "pub struct {name} {{").unwrap();
+    for (field, typ) in fields {
+        writeln!(source, "    pub {}: {typ},", rust_ident(field)).unwrap();
+    }
+    source.push_str("}\n");
+}
+
+/// Converts a `snake_case` path into the `PascalCase` name of a struct.
+fn pascal_case(path: &str) -> String {
+    let words = path.split('_').filter(|word| !word.is_empty());
+    let words = words.map(|word| {
+        let mut chars = word.chars();
+        let first = chars.next().map(|first| first.to_ascii_uppercase());
+        first.into_iter().chain(chars).collect::<String>()
+    });
+    words.collect()
+}
+
+/// Returns the Rust identifier of a name, escaping the names which are keywords in Rust.
+fn rust_ident(name: &str) -> String {
+    const RUST_KEYWORDS: &[&str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do",
+        "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let",
+        "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+        "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+        "virtual", "where", "while", "yield",
+    ];
+    if RUST_KEYWORDS.contains(&name) {
+        format!("r#{name}")
+    } else {
+        name.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::generate_rust_bindings;
+    use crate::{Abi, AbiParameter, AbiType, AbiVisibility, Sign};
+
+    #[test]
+    fn structs_are_named_after_their_parameter() {
+        let account = AbiType::Struct {
+            fields: vec![
+                ("owner".into(), AbiType::Field),
+                ("balance".into(), AbiType::Integer { sign: Sign::Signed, width: 32 }),
+            ],
+        };
+        let abi = Abi {
+            parameters: vec![
+                AbiParameter {
+                    name: "accounts".into(),
+                    typ: AbiType::Array { length: 2, typ: Box::new(account.clone()) },
+                    visibility: AbiVisibility::Private,
+                },
+                AbiParameter {
+                    name: "type".into(),
+                    typ: AbiType::Tuple { fields: vec![account, AbiType::Boolean] },
+                    visibility: AbiVisibility::Public,
+                },
+            ],
+            return_type: Some(AbiType::Integer { sign: Sign::Unsigned, width: 3 }),
+            param_witnesses: BTreeMap::new(),
+            return_witnesses: Vec::new(),
+        };
+        let bindings = generate_rust_bindings(&abi);
+
+        assert!(bindings.contains(
+            "pub struct Accounts {\n    pub owner: FieldElement,\n    pub balance: i32,\n}"
+        ));
+        assert!(bindings.contains("pub struct Type0 {"));
+        assert!(bindings.contains(
+            "pub struct Inputs {\n    pub accounts: Vec<Accounts>,\n    pub r#type: (Type0, bool),\n}"
+        ));
+        assert!(bindings.contains("            r#type: take_input(&mut input_map, \"type\")?,"));
+        assert!(bindings.contains("pub type Return = u8;"));
+    }
+
+    #[test]
+    fn wide_tuples_are_declared_as_tuple_structs() {
+        let tuple = |length| AbiType::Tuple { fields: vec![AbiType::Field; length] };
+        let abi = Abi {
+            parameters: vec![
+                AbiParameter {
+                    name: "narrow".into(),
+                    typ: tuple(12),
+                    visibility: AbiVisibility::Private,
+                },
+                AbiParameter {
+                    name: "wide".into(),
+                    typ: tuple(13),
+                    visibility: AbiVisibility::Private,
+                },
+            ],
+            return_type: None,
+            param_witnesses: BTreeMap::new(),
+            return_witnesses: Vec::new(),
+        };
+        let bindings = generate_rust_bindings(&abi);
+
+        let fields = ["FieldElement"; 12].join(", ");
+        assert!(bindings.contains(&format!("    pub narrow: ({fields}),\n")));
+        let fields = ["pub FieldElement"; 13].join(", ");
+        assert!(bindings.contains(&format!("pub struct Wide({fields});")));
+        assert!(bindings.contains("            self.12.into_input_value(),\n"));
+        assert!(bindings.contains("    pub wide: Wide,\n"));
+    }
+}
@@ -0,0 +1,188 @@
+use thiserror::Error;
+
+use crate::{Abi, AbiType, AbiVisibility};
+
+/// The version of the schema in which ABIs are written to compiled artifacts.
+///
+/// This must be increased whenever the serialized form of [`Abi`] changes, so that tools reading
+/// artifacts can tell which schema they were written in. Artifacts written before ABIs were
+/// versioned have version `0`.
+pub const ABI_VERSION: u32 = 1;
+
+/// A change to an ABI which breaks callers of the previous version of the program, such as
+/// verifier contracts which encode the program's public inputs.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BreakingChange {
+    #[error("Parameter `{0}` was removed")]
+    RemovedParameter(String),
+    #[error("Parameter `{0}` was added")]
+    AddedParameter(String),
+    #[error("Parameter `{old}` was renamed to `{new}`")]
+    RenamedParameter { old: String, new: String },
+    #[error("Parameter `{name}` moved from position {old} to position {new}")]
+    MovedParameter { name: String, old: usize, new: usize },
+    #[error("Parameter `{name}` changed type from {old} to {new}")]
+    ParameterTypeChanged { name: String, old: AbiType, new: AbiType },
+    #[error("Parameter `{name}` changed visibility from {old} to {new}")]
+    VisibilityChanged { name: String, old: AbiVisibility, new: AbiVisibility },
+    #[error("The return type changed from {} to {}", display_return_type(.old), display_return_type(.new))]
+    ReturnTypeChanged { old: Option<AbiType>, new: Option<AbiType> },
+}
+
+fn display_return_type(return_type: &Option<AbiType>) -> String {
+    match return_type {
+        Some(typ) => typ.to_string(),
+        None => "()".to_owned(),
+    }
+}
+
+impl Abi {
+    /// Returns the changes from a previous version of the ABI which break its callers.
+    ///
+    /// Parameters are matched by name. A parameter whose name is found nowhere in the other ABI
+    /// is taken to be renamed if the parameter at the same position is new as well.
+    pub fn breaking_changes(&self, previous: &Abi) -> Vec<BreakingChange> {
+        let mut changes = Vec::new();
+        let mut renamed = Vec::new();
+
+        for (old_position, old_param) in previous.parameters.iter().enumerate() {
+            let new_position =
+                self.parameters.iter().position(|param| param.name == old_param.name);
+            let new_position = match new_position {
+                Some(new_position) => {
+                    if new_position != old_position {
+                        changes.push(BreakingChange::MovedParameter {
+                            name: old_param.name.clone(),
+                            old: old_position,
+                            new: new_position,
+                        });
+                    }
+                    new_position
+                }
+                None => match self.parameters.get(old_position) {
+                    Some(new_param) if !previous.has_parameter(&new_param.name) => {
+                        changes.push(BreakingChange::RenamedParameter {
+                            old: old_param.name.clone(),
+                            new: new_param.name.clone(),
+                        });
+                        renamed.push(old_position);
+                        old_position
+                    }
+                    _ => {
+                        changes.push(BreakingChange::RemovedParameter(old_param.name.clone()));
+                        continue;
+                    }
+                },
+            };
+
+            let new_param = &self.parameters[new_position];
+            if new_param.typ != old_param.typ {
+                changes.push(BreakingChange::ParameterTypeChanged {
+                    name: new_param.name.clone(),
+                    old: old_param.typ.clone(),
+                    new: new_param.typ.clone(),
+                });
+            }
+            if new_param.visibility != old_param.visibility {
+                changes.push(BreakingChange::VisibilityChanged {
+                    name: new_param.name.clone(),
+                    old: old_param.visibility,
+                    new: new_param.visibility,
+                });
+            }
+        }
+
+        for (position, new_param) in self.parameters.iter().enumerate() {
+            if !previous.has_parameter(&new_param.name) && !renamed.contains(&position) {
+                changes.push(BreakingChange::AddedParameter(new_param.name.clone()));
+            }
+        }
+
+        if self.return_type != previous.return_type {
+            changes.push(BreakingChange::ReturnTypeChanged {
+                old: previous.return_type.clone(),
+                new: self.return_type.clone(),
+            });
+        }
+        changes
+    }
+
+    fn has_parameter(&self, name: &str) -> bool {
+        self.parameters.iter().any(|param| param.name == name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::BreakingChange;
+    use crate::{Abi, AbiParameter, AbiType, AbiVisibility, Sign};
+
+    fn abi(parameters: &[(&str, AbiType, AbiVisibility)], return_type: Option<AbiType>) -> Abi {
+        let parameters = parameters.iter().cloned();
+        let parameters = parameters.map(|(name, typ, visibility)| AbiParameter {
+            name: name.to_owned(),
+            typ,
+            visibility,
+        });
+        Abi {
+            parameters: parameters.collect(),
+            param_witnesses: BTreeMap::new(),
+            return_type,
+            return_witnesses: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn unchanged_abis_are_compatible() {
+        let params = [("x", AbiType::Field, AbiVisibility::Private)];
+        let previous = abi(&params, Some(AbiType::Boolean));
+        let current = abi(&params, Some(AbiType::Boolean));
+
+        assert_eq!(current.breaking_changes(&previous), Vec::new());
+    }
+
+    #[test]
+    fn breaking_changes_are_reported() {
+        let u8 = AbiType::Integer { sign: Sign::Unsigned, width: 8 };
+        let previous = abi(
+            &[
+                ("x", AbiType::Field, AbiVisibility::Private),
+                ("y", AbiType::Field, AbiVisibility::Public),
+                ("z", AbiType::Field, AbiVisibility::Public),
+            ],
+            None,
+        );
+        let current = abi(
+            &[
+                ("renamed", AbiType::Field, AbiVisibility::Private),
+                ("z", u8.clone(), AbiVisibility::Public),
+                ("y", AbiType::Field, AbiVisibility::Private),
+                ("w", AbiType::Boolean, AbiVisibility::Private),
+            ],
+            Some(AbiType::Field),
+        );
+
+        assert_eq!(
+            current.breaking_changes(&previous),
+            vec![
+                BreakingChange::RenamedParameter { old: "x".into(), new: "renamed".into() },
+                BreakingChange::MovedParameter { name: "y".into(), old: 1, new: 2 },
+                BreakingChange::VisibilityChanged {
+                    name: "y".into(),
+                    old: AbiVisibility::Public,
+                    new: AbiVisibility::Private,
+                },
+                BreakingChange::MovedParameter { name: "z".into(), old: 2, new: 1 },
+                BreakingChange::ParameterTypeChanged {
+                    name: "z".into(),
+                    old: AbiType::Field,
+                    new: u8,
+                },
+                BreakingChange::AddedParameter("w".into()),
+                BreakingChange::ReturnTypeChanged { old: None, new: Some(AbiType::Field) },
+            ]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{input_parser::InputValue, AbiParameter, AbiType};
+use crate::{input_parser::InputValue, AbiParameter, AbiType, Sign};
 use acvm::acir::native_types::Witness;
 use thiserror::Error;
 
@@ -10,12 +10,51 @@ pub enum InputParserError {
     ParseStr(String),
     #[error("Could not parse hex value {0}")]
     ParseHexStr(String),
+    #[error("{0} does not fit into a field element")]
+    ExceedsFieldModulus(String),
     #[error("duplicate variable name {0}")]
     DuplicateVariableName(String),
     #[error("cannot parse value into {0:?}")]
     AbiTypeMismatch(AbiType),
     #[error("Expected argument `{0}`, but none was found")]
     MissingArgument(String),
+    #[error("The program has no parameter named `{0}`")]
+    UnexpectedArgument(String),
+    #[error("Expected `{path}` to be of type {expected}")]
+    TypeMismatch { path: String, expected: AbiType },
+    #[error("Expected `{path}` to have {expected} elements, but found {found}")]
+    LengthMismatch { path: String, expected: usize, found: usize },
+    #[error("Could not parse `{path}`: {reason}")]
+    InvalidValue { path: String, reason: String },
+    #[error("`{path}` is {value}, which is out of the range of {expected} ({})", display_range(.expected))]
+    OutOfRange { path: String, value: String, expected: AbiType },
+    #[error("Unexpected field `{0}`, which the struct does not declare")]
+    UnexpectedField(String),
+    #[error("{} problems were found in the inputs:{}", .0.len(), display_errors(.0))]
+    InvalidInputs(Vec<InputParserError>),
+}
+
+/// Describes the numbers which values of a field, integer or boolean type may be given.
+fn display_range(typ: &AbiType) -> String {
+    match typ {
+        AbiType::Integer { sign: Sign::Unsigned, width } if *width <= 128 => {
+            format!("0 to {}", u128::MAX >> (128 - width))
+        }
+        AbiType::Integer { sign: Sign::Unsigned, width } => format!("0 to 2^{width} - 1"),
+        AbiType::Integer { sign: Sign::Signed, width } if (1..=128).contains(width) => {
+            let max = i128::MAX >> (128 - width);
+            format!("{} to {max}", -max - 1)
+        }
+        AbiType::Integer { sign: Sign::Signed, width } => {
+            format!("-2^{} to 2^{} - 1", width.saturating_sub(1), width.saturating_sub(1))
+        }
+        AbiType::Boolean => "0 or 1".to_owned(),
+        _ => "any field element".to_owned(),
+    }
+}
+
+fn display_errors(errors: &[InputParserError]) -> String {
+    errors.iter().map(|error| format!("\n  - {error}")).collect()
 }
 
 impl From<toml::ser::Error> for InputParserError {
@@ -56,4 +95,6 @@ pub enum AbiError {
     ReturnTypeMismatch { return_type: AbiType, value: InputValue },
     #[error("No return value is expected but received {0:?}")]
     UnexpectedReturnValue(InputValue),
+    #[error("The value of `{0}` does not fit into the type of its binding")]
+    UnexpectedValue(String),
 }
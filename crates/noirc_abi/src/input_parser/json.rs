@@ -1,6 +1,5 @@
-use super::{parse_str_to_field, InputValue};
-use crate::{errors::InputParserError, Abi, AbiType, MAIN_RETURN_NAME};
-use acvm::FieldElement;
+use super::{check_length, collect_errors, parse_number_at_path, to_integer, InputValue};
+use crate::{errors::InputParserError, Abi, AbiParameter, AbiType, MAIN_RETURN_NAME};
 use iter_extended::{try_btree_map, try_vecmap, vecmap};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -8,31 +7,45 @@ use std::collections::BTreeMap;
 pub(crate) fn parse_json(
     input_string: &str,
     abi: &Abi,
+    partial: bool,
 ) -> Result<BTreeMap<String, InputValue>, InputParserError> {
     // Parse input.json into a BTreeMap.
-    let data: BTreeMap<String, JsonTypes> = serde_json::from_str(input_string)?;
-
-    // Convert arguments to field elements.
-    let mut parsed_inputs = try_btree_map(abi.to_btree_map(), |(arg_name, abi_type)| {
-        // Check that json contains a value for each argument in the ABI.
-        let value = data
-            .get(&arg_name)
-            .ok_or_else(|| InputParserError::MissingArgument(arg_name.clone()))?;
-
-        InputValue::try_from_json(value.clone(), &abi_type, &arg_name)
-            .map(|input_value| (arg_name, input_value))
-    })?;
+    let mut data: BTreeMap<String, JsonTypes> = serde_json::from_str(input_string)?;
+
+    // Convert arguments to field elements, recording every problem found along the way.
+    let mut errors = Vec::new();
+    let mut parsed_inputs = BTreeMap::new();
+    for AbiParameter { name, typ, .. } in &abi.parameters {
+        // Check that json contains a value for each argument in the ABI, unless only some of
+        // the arguments are being given.
+        let value = match data.remove(name) {
+            Some(value) => value,
+            None if partial => continue,
+            None => {
+                errors.push(InputParserError::MissingArgument(name.clone()));
+                continue;
+            }
+        };
+        if let Some(input_value) = InputValue::from_json(value, typ, name, &mut errors) {
+            parsed_inputs.insert(name.clone(), input_value);
+        }
+    }
 
     // If the json file also includes a return value then we parse it as well.
     // This isn't required as the prover calculates the return value itself.
-    if let (Some(return_type), Some(json_return_value)) =
-        (&abi.return_type, data.get(MAIN_RETURN_NAME))
-    {
-        let return_value =
-            InputValue::try_from_json(json_return_value.clone(), return_type, MAIN_RETURN_NAME)?;
-        parsed_inputs.insert(MAIN_RETURN_NAME.to_owned(), return_value);
+    let json_return_value = data.remove(MAIN_RETURN_NAME);
+    if let (Some(return_type), Some(json_return_value)) = (&abi.return_type, json_return_value) {
+        if let Some(return_value) =
+            InputValue::from_json(json_return_value, return_type, MAIN_RETURN_NAME, &mut errors)
+        {
+            parsed_inputs.insert(MAIN_RETURN_NAME.to_owned(), return_value);
+        }
     }
 
+    // Values left over are not arguments of the ABI
+    errors.extend(data.into_keys().map(InputParserError::UnexpectedArgument));
+
+    collect_errors(errors)?;
     Ok(parsed_inputs)
 }
 
@@ -69,41 +82,56 @@ enum JsonTypes {
     // however we restrict the allowable size. Values which do not fit in a u64 should be passed
     // as a string.
     Integer(u64),
+    // A negative integer, that can fit in 64 bits.
+    NegativeInteger(i64),
     // Simple boolean flag
     Bool(bool),
-    // Array of regular integers
-    ArrayNum(Vec<u64>),
-    // Array of hexadecimal integers
-    ArrayString(Vec<String>),
-    // Array of booleans
-    ArrayBool(Vec<bool>),
+    // Array of JsonTypes, holding the elements of an array or the fields of a tuple
+    Array(Vec<JsonTypes>),
     // Struct of JsonTypes
     Table(BTreeMap<String, JsonTypes>),
 }
 
 impl JsonTypes {
+    fn from_integer(integer: i128) -> JsonTypes {
+        if let Ok(integer) = u64::try_from(integer) {
+            JsonTypes::Integer(integer)
+        } else if let Ok(integer) = i64::try_from(integer) {
+            JsonTypes::NegativeInteger(integer)
+        } else {
+            JsonTypes::String(integer.to_string())
+        }
+    }
+
     fn try_from_input_value(
         value: &InputValue,
         abi_type: &AbiType,
     ) -> Result<JsonTypes, InputParserError> {
         let json_value = match (value, abi_type) {
-            (InputValue::Field(f), AbiType::Field | AbiType::Integer { .. }) => {
+            (InputValue::Field(f), AbiType::Field) => {
                 let f_str = format!("0x{}", f.to_hex());
                 JsonTypes::String(f_str)
             }
+            // Integers are written as the numbers they represent, in a string if JSON cannot
+            // hold them as integers
+            (InputValue::Field(f), AbiType::Integer { sign, width }) => {
+                match to_integer(*f, *sign, *width) {
+                    Some(integer) => JsonTypes::from_integer(integer),
+                    None => JsonTypes::String(format!("0x{}", f.to_hex())),
+                }
+            }
             (InputValue::Field(f), AbiType::Boolean) => JsonTypes::Bool(f.is_one()),
 
-            (InputValue::Vec(v), AbiType::Array { typ, .. }) => match typ.as_ref() {
-                AbiType::Field | AbiType::Integer { .. } => {
-                    let array = v.iter().map(|i| format!("0x{}", i.to_hex())).collect();
-                    JsonTypes::ArrayString(array)
-                }
-                AbiType::Boolean => {
-                    let array = v.iter().map(|i| i.is_one()).collect();
-                    JsonTypes::ArrayBool(array)
-                }
-                _ => return Err(InputParserError::AbiTypeMismatch(abi_type.clone())),
-            },
+            (InputValue::Vec(v), AbiType::Array { typ, .. }) => {
+                let array = try_vecmap(v, |element| JsonTypes::try_from_input_value(element, typ))?;
+                JsonTypes::Array(array)
+            }
+            (InputValue::Vec(v), AbiType::Tuple { fields }) => {
+                let array = try_vecmap(v.iter().zip(fields), |(element, typ)| {
+                    JsonTypes::try_from_input_value(element, typ)
+                })?;
+                JsonTypes::Array(array)
+            }
 
             (InputValue::String(s), AbiType::String { .. }) => JsonTypes::String(s.to_string()),
 
@@ -122,75 +150,95 @@ impl JsonTypes {
 }
 
 impl InputValue {
-    fn try_from_json(
+    /// Converts the value at `path` into the given type, where `path` names the value starting
+    /// from the argument it is part of, such as `accounts[3].balance`. Every problem found in
+    /// the value is recorded in `errors`, in which case `None` is returned.
+    fn from_json(
         value: JsonTypes,
         param_type: &AbiType,
-        arg_name: &str,
-    ) -> Result<InputValue, InputParserError> {
+        path: &str,
+        errors: &mut Vec<InputParserError>,
+    ) -> Option<InputValue> {
         let input_value = match (value, param_type) {
             (JsonTypes::String(string), AbiType::String { .. }) => InputValue::String(string),
             (
                 JsonTypes::String(string),
                 AbiType::Field | AbiType::Integer { .. } | AbiType::Boolean,
-            ) => InputValue::Field(parse_str_to_field(&string)?),
+            ) => return parse_number_at_path(&string, param_type, path, errors),
 
             (
                 JsonTypes::Integer(integer),
                 AbiType::Field | AbiType::Integer { .. } | AbiType::Boolean,
-            ) => {
-                let new_value = FieldElement::from(i128::from(integer));
-
-                InputValue::Field(new_value)
-            }
+            ) => return parse_number_at_path(&integer.to_string(), param_type, path, errors),
+            (
+                JsonTypes::NegativeInteger(integer),
+                AbiType::Field | AbiType::Integer { .. } | AbiType::Boolean,
+            ) => return parse_number_at_path(&integer.to_string(), param_type, path, errors),
 
             (JsonTypes::Bool(boolean), AbiType::Boolean) => InputValue::Field(boolean.into()),
 
-            (JsonTypes::ArrayNum(arr_num), AbiType::Array { typ, .. })
-                if matches!(
-                    typ.as_ref(),
-                    AbiType::Field | AbiType::Integer { .. } | AbiType::Boolean
-                ) =>
-            {
-                let array_elements =
-                    vecmap(arr_num, |elem_num| FieldElement::from(i128::from(elem_num)));
+            (JsonTypes::Array(array), AbiType::Array { length, typ }) => {
+                let length_matches = check_length(path, *length as usize, array.len(), errors);
+                let array_elements = vecmap(array.into_iter().enumerate(), |(index, element)| {
+                    let element_path = format!("{path}[{index}]");
+                    InputValue::from_json(element, typ, &element_path, errors)
+                });
+                let array_elements: Option<Vec<_>> = array_elements.into_iter().collect();
 
-                InputValue::Vec(array_elements)
+                InputValue::Vec(array_elements.filter(|_| length_matches)?)
             }
-            (JsonTypes::ArrayString(arr_str), AbiType::Array { typ, .. })
-                if matches!(
-                    typ.as_ref(),
-                    AbiType::Field | AbiType::Integer { .. } | AbiType::Boolean
-                ) =>
-            {
-                let array_elements = try_vecmap(arr_str, |elem_str| parse_str_to_field(&elem_str))?;
-
-                InputValue::Vec(array_elements)
-            }
-            (JsonTypes::ArrayBool(arr_bool), AbiType::Array { typ, .. })
-                if matches!(typ.as_ref(), AbiType::Boolean) =>
-            {
-                let array_elements = vecmap(arr_bool, FieldElement::from);
 
-                InputValue::Vec(array_elements)
+            (JsonTypes::Array(array), AbiType::Tuple { fields }) => {
+                let length_matches = check_length(path, fields.len(), array.len(), errors);
+                let tuple_elements =
+                    vecmap(array.into_iter().zip(fields).enumerate(), |(index, (element, typ))| {
+                        InputValue::from_json(element, typ, &format!("{path}.{index}"), errors)
+                    });
+                let tuple_elements: Option<Vec<_>> = tuple_elements.into_iter().collect();
+
+                InputValue::Vec(tuple_elements.filter(|_| length_matches)?)
             }
 
-            (JsonTypes::Table(table), AbiType::Struct { fields }) => {
-                let native_table = try_btree_map(fields, |(field_name, abi_type)| {
+            (JsonTypes::Table(mut table), AbiType::Struct { fields }) => {
+                let mut native_table = BTreeMap::new();
+                let mut is_complete = true;
+                for (field_name, abi_type) in fields {
                     // Check that json contains a value for each field of the struct.
-                    let field_id = format!("{arg_name}.{field_name}");
-                    let value = table
-                        .get(field_name)
-                        .ok_or_else(|| InputParserError::MissingArgument(field_id.clone()))?;
-                    InputValue::try_from_json(value.clone(), abi_type, &field_id)
-                        .map(|input_value| (field_name.to_string(), input_value))
-                })?;
+                    let field_id = format!("{path}.{field_name}");
+                    let value = table.remove(field_name);
+                    let input_value = match value {
+                        Some(value) => InputValue::from_json(value, abi_type, &field_id, errors),
+                        None => {
+                            errors.push(InputParserError::MissingArgument(field_id));
+                            None
+                        }
+                    };
+                    match input_value {
+                        Some(input_value) => {
+                            native_table.insert(field_name.to_string(), input_value);
+                        }
+                        None => is_complete = false,
+                    }
+                }
 
-                InputValue::Struct(native_table)
+                // Values left over are not fields of the struct
+                for field_name in table.into_keys() {
+                    errors.push(InputParserError::UnexpectedField(format!("{path}.{field_name}")));
+                    is_complete = false;
+                }
+
+                is_complete.then_some(InputValue::Struct(native_table))?
             }
 
-            (_, _) => return Err(InputParserError::AbiTypeMismatch(param_type.clone())),
+            (_, _) => {
+                errors.push(InputParserError::TypeMismatch {
+                    path: path.to_owned(),
+                    expected: param_type.clone(),
+                });
+                return None;
+            }
         };
 
-        Ok(input_value)
+        Some(input_value)
     }
 }
@@ -7,24 +7,58 @@ use acvm::FieldElement;
 use serde::Serialize;
 
 use crate::errors::InputParserError;
-use crate::{Abi, AbiType};
+use crate::{Abi, AbiType, Sign};
 /// This is what all formats eventually transform into
 /// For example, a toml file will parse into TomlTypes
 /// and those TomlTypes will be mapped to Value
 #[derive(Debug, Clone, Serialize, PartialEq)]
 pub enum InputValue {
     Field(FieldElement),
-    Vec(Vec<FieldElement>),
+    Vec(Vec<InputValue>),
     String(String),
     Struct(BTreeMap<String, InputValue>),
 }
 
+/// Converts a signed integer input of `width` bits into its two's complement representation.
+///
+/// Inputs which already fit into `width` bits are returned unchanged while negative inputs,
+/// which are represented by their field negation, are offset by 2^width.
+/// Returns `None` if the input is outside of the signed integer's range.
+pub(crate) fn to_twos_complement(value: FieldElement, width: u32) -> Option<FieldElement> {
+    if value.num_bits() <= width {
+        return Some(value);
+    }
+    let magnitude = FieldElement::zero() - value;
+    let modulus = FieldElement::from(2_i128).pow(&FieldElement::from(width as i128));
+    let min_magnitude = FieldElement::from(2_i128).pow(&FieldElement::from(width as i128 - 1));
+    (magnitude <= min_magnitude).then(|| modulus - magnitude)
+}
+
+/// Returns the number which an integer input of the given type represents, reading signed
+/// integers both in two's complement and by their field negation.
+/// Returns `None` if the number does not fit into an `i128`.
+pub(crate) fn to_integer(value: FieldElement, sign: Sign, width: u32) -> Option<i128> {
+    match sign {
+        Sign::Unsigned => (value.num_bits() < 128).then(|| value.to_u128() as i128),
+        Sign::Signed if width == 0 || width > 128 => None,
+        Sign::Signed => {
+            // Sign extends the two's complement representation from `width` to 128 bits
+            let shift = 128 - width;
+            let value = to_twos_complement(value, width)?.to_u128();
+            Some(((value << shift) as i128) >> shift)
+        }
+    }
+}
+
 impl InputValue {
     /// Checks whether the ABI type matches the InputValue type
     /// and also their arity
     pub fn matches_abi(&self, abi_param: &AbiType) -> bool {
         match (self, abi_param) {
             (InputValue::Field(_), AbiType::Field) => true,
+            (InputValue::Field(field_element), AbiType::Integer { sign: Sign::Signed, width }) => {
+                to_twos_complement(*field_element, *width).is_some()
+            }
             (InputValue::Field(field_element), AbiType::Integer { width, .. }) => {
                 field_element.num_bits() <= *width
             }
@@ -32,14 +66,17 @@ impl InputValue {
                 field_element.is_one() || field_element.is_zero()
             }
 
-            (InputValue::Vec(field_elements), AbiType::Array { length, typ, .. }) => {
-                if field_elements.len() != *length as usize {
+            (InputValue::Vec(elements), AbiType::Array { length, typ, .. }) => {
+                if elements.len() != *length as usize {
                     return false;
                 }
                 // Check that all of the array's elements' values match the ABI as well.
-                field_elements
-                    .iter()
-                    .all(|field_element| Self::Field(*field_element).matches_abi(typ))
+                elements.iter().all(|element| element.matches_abi(typ))
+            }
+
+            (InputValue::Vec(elements), AbiType::Tuple { fields }) => {
+                elements.len() == fields.len()
+                    && elements.iter().zip(fields).all(|(element, typ)| element.matches_abi(typ))
             }
 
             (InputValue::String(string), AbiType::String { length }) => {
@@ -99,8 +136,21 @@ impl Format {
         abi: &Abi,
     ) -> Result<BTreeMap<String, InputValue>, InputParserError> {
         match self {
-            Format::Json => json::parse_json(input_string, abi),
-            Format::Toml => toml::parse_toml(input_string, abi),
+            Format::Json => json::parse_json(input_string, abi, false),
+            Format::Toml => toml::parse_toml(input_string, abi, false),
+        }
+    }
+
+    /// Parses the arguments given in the input string like [Format::parse], permitting
+    /// arguments of the ABI to be missing so that the inputs of a program may be given in parts.
+    pub fn parse_partial(
+        &self,
+        input_string: &str,
+        abi: &Abi,
+    ) -> Result<BTreeMap<String, InputValue>, InputParserError> {
+        match self {
+            Format::Json => json::parse_json(input_string, abi, true),
+            Format::Toml => toml::parse_toml(input_string, abi, true),
         }
     }
 
@@ -116,6 +166,14 @@ impl Format {
     }
 }
 
+/// Parses the value of a single parameter of the ABI, written as a toml value such as `5` or
+/// `[1, 2]`, against the type of the parameter.
+pub fn parse_argument(name: &str, value: &str, abi: &Abi) -> Result<InputValue, InputParserError> {
+    let param = abi.parameters.iter().find(|param| param.name == name);
+    let param = param.ok_or_else(|| InputParserError::UnexpectedArgument(name.to_owned()))?;
+    toml::parse_toml_value(value, &param.typ, name)
+}
+
 #[cfg(test)]
 mod serialization_tests {
     use std::collections::BTreeMap;
@@ -124,10 +182,11 @@ mod serialization_tests {
     use strum::IntoEnumIterator;
 
     use crate::{
-        input_parser::InputValue, Abi, AbiParameter, AbiType, AbiVisibility, Sign, MAIN_RETURN_NAME,
+        errors::InputParserError, input_parser::InputValue, Abi, AbiParameter, AbiType,
+        AbiVisibility, Sign, MAIN_RETURN_NAME,
     };
 
-    use super::Format;
+    use super::{parse_argument, to_twos_complement, Format};
 
     #[test]
     fn serialization_round_trip() {
@@ -164,7 +223,13 @@ mod serialization_tests {
                 "bar".into(),
                 InputValue::Struct(BTreeMap::from([
                     ("field1".into(), InputValue::Field(255u128.into())),
-                    ("field2".into(), InputValue::Vec(vec![true.into(), false.into()])),
+                    (
+                        "field2".into(),
+                        InputValue::Vec(vec![
+                            InputValue::Field(true.into()),
+                            InputValue::Field(false.into()),
+                        ]),
+                    ),
                 ])),
             ),
             (MAIN_RETURN_NAME.into(), InputValue::String("hello".to_owned())),
@@ -178,26 +243,435 @@ mod serialization_tests {
             assert_eq!(input_map, reconstructed_input_map);
         }
     }
+
+    #[test]
+    fn integers_are_written_as_the_numbers_they_represent() {
+        let abi = Abi {
+            parameters: vec![AbiParameter {
+                name: "delta".into(),
+                typ: AbiType::Integer { sign: Sign::Signed, width: 8 },
+                visibility: AbiVisibility::Public,
+            }],
+            return_type: Some(AbiType::Integer { sign: Sign::Unsigned, width: 128 }),
+            param_witnesses: BTreeMap::new(),
+            return_witnesses: Vec::new(),
+        };
+        // Signed integers decoded from a witness are in two's complement
+        let input_map: BTreeMap<String, InputValue> = BTreeMap::from([
+            ("delta".into(), InputValue::Field(255_u128.into())),
+            (MAIN_RETURN_NAME.into(), InputValue::Field((1_u128 << 100).into())),
+        ]);
+
+        let toml = Format::Toml.serialize(&input_map, &abi).unwrap();
+        assert!(toml.contains("delta = -1"));
+        assert!(toml.contains(&format!("return = \"{}\"", 1_u128 << 100)));
+
+        let json = Format::Json.serialize(&input_map, &abi).unwrap();
+        assert!(json.contains("\"delta\":-1"));
+
+        for (format, serialized_inputs) in [(Format::Toml, toml), (Format::Json, json)] {
+            let reconstructed_input_map = format.parse(&serialized_inputs, &abi).unwrap();
+
+            let delta = match reconstructed_input_map["delta"] {
+                InputValue::Field(delta) => delta,
+                _ => panic!("Expected `delta` to be a field element"),
+            };
+            assert_eq!(to_twos_complement(delta, 8), Some(255_u128.into()));
+            assert_eq!(reconstructed_input_map[MAIN_RETURN_NAME], input_map[MAIN_RETURN_NAME]);
+        }
+    }
+
+    #[test]
+    fn numbers_are_parsed_alike_in_every_format() {
+        let abi = Abi {
+            parameters: vec![
+                AbiParameter {
+                    name: "delta".into(),
+                    typ: AbiType::Integer { sign: Sign::Signed, width: 8 },
+                    visibility: AbiVisibility::Private,
+                },
+                AbiParameter {
+                    name: "hash".into(),
+                    typ: AbiType::Field,
+                    visibility: AbiVisibility::Private,
+                },
+            ],
+            return_type: None,
+            param_witnesses: BTreeMap::new(),
+            return_witnesses: Vec::new(),
+        };
+        let large = u128::MAX.to_string() + "0";
+        let expected_hash = FieldElement::from(u128::MAX) * FieldElement::from(10_u128);
+
+        let inputs = [
+            (Format::Toml, format!("delta = -1\nhash = \"{large}\"")),
+            (Format::Json, format!("{{\"delta\": -1, \"hash\": \"{large}\"}}")),
+            (Format::Toml, format!("delta = \"-0x1\"\nhash = \"{large}\"")),
+            (Format::Json, format!("{{\"delta\": \"-1\", \"hash\": \"{large}\"}}")),
+        ];
+        for (format, input) in inputs {
+            let input_map = format.parse(&input, &abi).unwrap();
+            assert_eq!(
+                input_map["delta"],
+                InputValue::Field(FieldElement::zero() - FieldElement::one())
+            );
+            assert_eq!(input_map["hash"], InputValue::Field(expected_hash));
+        }
+
+        let inputs = [
+            (Format::Toml, "delta = -129\nhash = 0"),
+            (Format::Json, "{\"delta\": -129, \"hash\": 0}"),
+        ];
+        for (format, input) in inputs {
+            assert_eq!(
+                format.parse(input, &abi).unwrap_err().to_string(),
+                "`delta` is -129, which is out of the range of i8 (-128 to 127)"
+            );
+        }
+    }
+
+    fn accounts_abi() -> Abi {
+        let account = AbiType::Struct {
+            fields: vec![
+                ("owner".into(), AbiType::Field),
+                ("balance".into(), AbiType::Integer { sign: Sign::Unsigned, width: 8 }),
+            ],
+        };
+        Abi {
+            parameters: vec![AbiParameter {
+                name: "accounts".into(),
+                typ: AbiType::Array {
+                    length: 2,
+                    typ: Box::new(AbiType::Tuple {
+                        fields: vec![
+                            account,
+                            AbiType::Array { length: 2, typ: Box::new(AbiType::Boolean) },
+                        ],
+                    }),
+                },
+                visibility: AbiVisibility::Private,
+            }],
+            return_type: None,
+            param_witnesses: BTreeMap::new(),
+            return_witnesses: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn nested_arrays_of_structs_and_tuples_round_trip() {
+        let abi = accounts_abi();
+        let account = |owner: u128, balance: u128| {
+            InputValue::Struct(BTreeMap::from([
+                ("owner".into(), InputValue::Field(owner.into())),
+                ("balance".into(), InputValue::Field(balance.into())),
+            ]))
+        };
+        let flags = |flag: bool| {
+            InputValue::Vec(vec![InputValue::Field(flag.into()), InputValue::Field((!flag).into())])
+        };
+        let input_map: BTreeMap<String, InputValue> = BTreeMap::from([(
+            "accounts".into(),
+            InputValue::Vec(vec![
+                InputValue::Vec(vec![account(1, 2), flags(true)]),
+                InputValue::Vec(vec![account(3, 4), flags(false)]),
+            ]),
+        )]);
+
+        for format in Format::iter() {
+            let serialized_inputs = format.serialize(&input_map, &abi).unwrap();
+
+            let reconstructed_input_map = format.parse(&serialized_inputs, &abi).unwrap();
+
+            assert_eq!(input_map, reconstructed_input_map);
+        }
+    }
+
+    #[test]
+    fn arguments_are_parsed_against_their_parameter_type() {
+        let abi = accounts_abi();
+
+        let accounts = parse_argument(
+            "accounts",
+            "[[{ owner = 1, balance = 2 }, [true, false]], [{ owner = \"0x03\", balance = 4 }, [false, true]]]",
+            &abi,
+        )
+        .unwrap();
+        assert!(accounts.matches_abi(&abi.parameters[0].typ));
+
+        assert!(matches!(
+            parse_argument("accounts", "0x01", &abi).unwrap_err(),
+            InputParserError::TypeMismatch { path, .. } if path == "accounts"
+        ));
+        assert!(matches!(
+            parse_argument("foo", "1", &abi).unwrap_err(),
+            InputParserError::UnexpectedArgument(name) if name == "foo"
+        ));
+    }
+
+    #[test]
+    fn errors_name_the_full_path_of_the_value() {
+        let abi = accounts_abi();
+        let parse = |balance: &str| {
+            let toml = format!(
+                "accounts = [[{{ owner = 1, balance = 2 }}, [true, false]], [{{ owner = 3{balance} }}, [true, false]]]"
+            );
+            Format::Toml.parse(&toml, &abi).unwrap_err()
+        };
+
+        assert!(matches!(
+            parse(""),
+            InputParserError::MissingArgument(path) if path == "accounts[1].0.balance"
+        ));
+        assert!(matches!(
+            parse(", balance = 256"),
+            InputParserError::OutOfRange { path, .. } if path == "accounts[1].0.balance"
+        ));
+        assert!(matches!(
+            parse(", balance = \"0xzz\""),
+            InputParserError::InvalidValue { path, .. } if path == "accounts[1].0.balance"
+        ));
+
+        let toml = "accounts = [[{ owner = 1, balance = 2 }, [true, false]]]";
+        assert!(matches!(
+            Format::Toml.parse(toml, &abi).unwrap_err(),
+            InputParserError::LengthMismatch { path, expected: 2, found: 1 } if path == "accounts"
+        ));
+    }
+
+    #[test]
+    fn every_problem_in_the_inputs_is_reported() {
+        let abi = accounts_abi();
+        let toml = "
+            accounts = [[{ owner = 1, balance = 2, nonce = 3 }, [true]], [{ balance = 256 }, [true, false]]]
+            extra = 4
+        ";
+
+        let errors = match Format::Toml.parse(toml, &abi).unwrap_err() {
+            InputParserError::InvalidInputs(errors) => errors,
+            error => panic!("Expected every problem to be reported, found {error}"),
+        };
+        let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            errors,
+            vec![
+                "Unexpected field `accounts[0].0.nonce`, which the struct does not declare",
+                "Expected `accounts[0].1` to have 2 elements, but found 1",
+                "Expected argument `accounts[1].0.owner`, but none was found",
+                "`accounts[1].0.balance` is 256, which is out of the range of u8 (0 to 255)",
+                "The program has no parameter named `extra`",
+            ]
+        );
+    }
+
+    #[test]
+    fn partial_inputs_may_omit_arguments() {
+        let mut abi = accounts_abi();
+        abi.parameters.push(AbiParameter {
+            name: "fee".into(),
+            typ: AbiType::Field,
+            visibility: AbiVisibility::Private,
+        });
+
+        for format in Format::iter() {
+            let input = match format {
+                Format::Toml => "fee = 1",
+                Format::Json => "{ \"fee\": 1 }",
+            };
+            let input_map = format.parse_partial(input, &abi).unwrap();
+            assert_eq!(
+                input_map,
+                BTreeMap::from([("fee".into(), InputValue::Field(FieldElement::one()))])
+            );
+            assert!(matches!(
+                format.parse(input, &abi).unwrap_err(),
+                InputParserError::MissingArgument(name) if name == "accounts"
+            ));
+        }
+
+        // The arguments which are given must still be complete
+        let toml = "accounts = [[{ owner = 1 }, [true, false]], [{ owner = 3, balance = 4 }, [false, true]]]";
+        assert!(matches!(
+            Format::Toml.parse_partial(toml, &abi).unwrap_err(),
+            InputParserError::MissingArgument(path) if path == "accounts[0].0.balance"
+        ));
+    }
 }
 
+/// Parses a number written in decimal or, with the `0x` prefix, in hexadecimal. Negative numbers
+/// are given by the field negation of their magnitude, and numbers which do not fit into the
+/// field are rejected rather than reduced.
 fn parse_str_to_field(value: &str) -> Result<FieldElement, InputParserError> {
-    if value.starts_with("0x") {
-        FieldElement::from_hex(value).ok_or_else(|| InputParserError::ParseHexStr(value.to_owned()))
-    } else {
-        value
-            .parse::<i128>()
-            .map_err(|err_msg| InputParserError::ParseStr(err_msg.to_string()))
-            .map(FieldElement::from)
+    let (is_negative, magnitude) = match value.strip_prefix('-') {
+        Some(magnitude) => (true, magnitude),
+        None => (false, value),
+    };
+    let mut bytes = match magnitude.strip_prefix("0x") {
+        Some(digits) => parse_digits(digits, 16)
+            .ok_or_else(|| InputParserError::ParseHexStr(value.to_owned()))?,
+        None if magnitude.is_empty() => {
+            return Err(InputParserError::ParseStr("cannot parse integer from empty string".into()))
+        }
+        None => parse_digits(magnitude, 10)
+            .ok_or_else(|| InputParserError::ParseStr("invalid digit found in string".into()))?,
+    };
+
+    let modulus = FieldElement::modulus().to_bytes_le();
+    let exceeds_modulus = bytes
+        .len()
+        .cmp(&modulus.len())
+        .then_with(|| bytes.iter().rev().cmp(modulus.iter().rev()))
+        .is_ge();
+    if exceeds_modulus {
+        return Err(InputParserError::ExceedsFieldModulus(value.to_owned()));
+    }
+
+    bytes.reverse();
+    let field = FieldElement::from_be_bytes_reduce(&bytes);
+    Ok(if is_negative { FieldElement::zero() - field } else { field })
+}
+
+/// Parses digits in the given radix into little-endian bytes without trailing zeroes.
+/// Returns `None` if there are no digits or a digit is invalid for the radix.
+fn parse_digits(digits: &str, radix: u32) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for ch in digits.chars() {
+        let mut carry = ch.to_digit(radix)?;
+        for byte in bytes.iter_mut() {
+            let value = u32::from(*byte) * radix + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        if carry != 0 {
+            bytes.push(carry as u8);
+        }
+    }
+    (!digits.is_empty()).then_some(bytes)
+}
+
+/// Parses the number written at `path` as a value of a field, integer or boolean type. Numbers
+/// which cannot be parsed or which are out of the range of the type are recorded in `errors`.
+fn parse_number_at_path(
+    value: &str,
+    typ: &AbiType,
+    path: &str,
+    errors: &mut Vec<InputParserError>,
+) -> Option<InputValue> {
+    let input_value = match parse_str_to_field(value) {
+        Ok(field) => InputValue::Field(field),
+        Err(error) => {
+            let reason = error.to_string();
+            errors.push(InputParserError::InvalidValue { path: path.to_owned(), reason });
+            return None;
+        }
+    };
+    if !input_value.matches_abi(typ) {
+        errors.push(InputParserError::OutOfRange {
+            path: path.to_owned(),
+            value: value.to_owned(),
+            expected: typ.clone(),
+        });
+        return None;
+    }
+    Some(input_value)
+}
+
+/// Checks that the array or tuple at `path` has as many elements as its type expects,
+/// recording an error in `errors` if it does not.
+fn check_length(
+    path: &str,
+    expected: usize,
+    found: usize,
+    errors: &mut Vec<InputParserError>,
+) -> bool {
+    if expected != found {
+        errors.push(InputParserError::LengthMismatch { path: path.to_owned(), expected, found });
+    }
+    expected == found
+}
+
+/// Returns the problems found in some inputs as a single error, if any were found.
+fn collect_errors(mut errors: Vec<InputParserError>) -> Result<(), InputParserError> {
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.remove(0)),
+        _ => Err(InputParserError::InvalidInputs(errors)),
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::parse_str_to_field;
+    use acvm::FieldElement;
+
+    use super::{parse_str_to_field, to_integer, to_twos_complement};
+    use crate::Sign;
 
     #[test]
     fn parse_empty_str_fails() {
         // Check that this fails appropriately rather than being treated as 0, etc.
         assert!(parse_str_to_field("").is_err());
     }
+
+    #[test]
+    fn parse_u128_max() {
+        let value = parse_str_to_field(&u128::MAX.to_string()).unwrap();
+        assert_eq!(value, FieldElement::from(u128::MAX));
+    }
+
+    #[test]
+    fn numbers_larger_than_the_field_modulus_are_rejected() {
+        let modulus = FieldElement::modulus();
+        let below_modulus = (&modulus - 1_u32).to_string();
+        assert_eq!(
+            parse_str_to_field(&below_modulus).unwrap(),
+            FieldElement::zero() - FieldElement::one()
+        );
+        assert!(parse_str_to_field(&modulus.to_string()).is_err());
+        assert!(parse_str_to_field(&format!("0x{}", modulus.to_str_radix(16))).is_err());
+    }
+
+    #[test]
+    fn negative_and_hex_numbers_are_parsed() {
+        assert_eq!(
+            parse_str_to_field("-0x10").unwrap(),
+            FieldElement::zero() - FieldElement::from(16_u128)
+        );
+        assert_eq!(parse_str_to_field("0xff").unwrap(), 255_u128.into());
+        assert!(parse_str_to_field("-").is_err());
+        assert!(parse_str_to_field("0x").is_err());
+        assert!(parse_str_to_field("12a").is_err());
+    }
+
+    #[test]
+    fn negative_signed_inputs_use_twos_complement() {
+        let to_twos_complement = |value| to_twos_complement(parse_str_to_field(value).unwrap(), 8);
+
+        assert_eq!(to_twos_complement("5"), Some(FieldElement::from(5_i128)));
+        assert_eq!(to_twos_complement("-1"), Some(FieldElement::from(255_i128)));
+        assert_eq!(to_twos_complement("-128"), Some(FieldElement::from(128_i128)));
+        assert_eq!(to_twos_complement("-129"), None);
+        assert_eq!(to_twos_complement("256"), None);
+    }
+
+    #[test]
+    fn signed_integers_are_read_at_the_bounds_of_their_width() {
+        for width in [1, 8, 64, 127, 128] {
+            let min = i128::MIN >> (128 - width);
+            let max = i128::MAX >> (128 - width);
+            for value in [min, max, 0] {
+                let field = FieldElement::from(value);
+                assert_eq!(to_integer(field, Sign::Signed, width), Some(value), "i{width}");
+            }
+            // Negative integers may also be given in two's complement
+            let twos_complement = FieldElement::from(1_u128 << (width - 1));
+            assert_eq!(to_integer(twos_complement, Sign::Signed, width), Some(min), "i{width}");
+        }
+    }
+
+    #[test]
+    fn unsigned_integers_are_read_up_to_the_bounds_of_an_i128() {
+        let max = FieldElement::from(i128::MAX);
+        assert_eq!(to_integer(max, Sign::Unsigned, 128), Some(i128::MAX));
+        let too_large = FieldElement::from(u128::MAX);
+        assert_eq!(to_integer(too_large, Sign::Unsigned, 128), None);
+    }
 }
@@ -18,8 +18,11 @@ use serde::{Deserialize, Serialize};
 //
 // This ABI has nothing to do with ACVM or ACIR. Although they implicitly have a relationship
 
+pub mod bindings;
+pub mod compatibility;
 pub mod errors;
 pub mod input_parser;
+pub mod printable;
 mod serialization;
 
 /// A map from the fields in an TOML/JSON file which correspond to some ABI to their values
@@ -64,6 +67,9 @@ pub enum AbiType {
     String {
         length: u64,
     },
+    Tuple {
+        fields: Vec<AbiType>,
+    },
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -123,6 +129,7 @@ impl AbiType {
             AbiType::Array { length, typ: _ } => *length as usize,
             AbiType::Struct { fields, .. } => fields.len(),
             AbiType::String { length } => *length as usize,
+            AbiType::Tuple { fields } => fields.len(),
         }
     }
 
@@ -135,6 +142,30 @@ impl AbiType {
                 fields.iter().fold(0, |acc, (_, field_type)| acc + field_type.field_count())
             }
             AbiType::String { length } => *length as u32,
+            AbiType::Tuple { fields } => {
+                fields.iter().fold(0, |acc, field_type| acc + field_type.field_count())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for AbiType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AbiType::Field => write!(f, "Field"),
+            AbiType::Array { length, typ } => write!(f, "[{typ}; {length}]"),
+            AbiType::Integer { sign: Sign::Unsigned, width } => write!(f, "u{width}"),
+            AbiType::Integer { sign: Sign::Signed, width } => write!(f, "i{width}"),
+            AbiType::Boolean => write!(f, "bool"),
+            AbiType::Struct { fields } => {
+                let fields = vecmap(fields, |(name, typ)| format!("{name}: {typ}"));
+                write!(f, "{{ {} }}", fields.join(", "))
+            }
+            AbiType::String { length } => write!(f, "str<{length}>"),
+            AbiType::Tuple { fields } => {
+                let fields = vecmap(fields, ToString::to_string);
+                write!(f, "({})", fields.join(", "))
+            }
         }
     }
 }
@@ -221,9 +252,60 @@ impl Abi {
         input_map: &InputMap,
         return_value: Option<InputValue>,
     ) -> Result<WitnessMap, AbiError> {
+        let mut witness_map = self.encode_parameters(input_map, false)?;
+
+        // When encoding public inputs to be passed to the verifier, the user can must provide a return value
+        // to be inserted into the witness map. This is not needed when generating a witness when proving the circuit.
+        match (&self.return_type, return_value) {
+            (Some(return_type), Some(return_value)) => {
+                if !return_value.matches_abi(return_type) {
+                    return Err(AbiError::ReturnTypeMismatch {
+                        return_type: return_type.clone(),
+                        value: return_value,
+                    });
+                }
+                let encoded_return_fields = Self::encode_value(return_value, return_type)?;
+
+                // We need to be more careful when writing the return value's witness values.
+                // This is as it may share witness indices with other public inputs so we must check that when
+                // this occurs the witness values are consistent with each other.
+                self.return_witnesses.iter().zip(encoded_return_fields.iter()).try_for_each(
+                    |(&witness, &field_element)| match witness_map.insert(witness, field_element) {
+                        Some(existing_value) if existing_value != field_element => {
+                            Err(AbiError::InconsistentWitnessAssignment(witness))
+                        }
+                        _ => Ok(()),
+                    },
+                )?;
+            }
+            (None, Some(return_value)) => {
+                return Err(AbiError::UnexpectedReturnValue(return_value))
+            }
+            // We allow not passing a return value despite the circuit defining one
+            // in order to generate the initial partial witness.
+            (_, None) => {}
+        }
+
+        Ok(witness_map.into())
+    }
+
+    /// Encode the inputs given for some of the parameters of the ABI into a `WitnessMap`, so
+    /// that the inputs of a program may be provided in parts. The witnesses of the parameters
+    /// which are not given are left unassigned.
+    pub fn encode_partial(&self, input_map: &InputMap) -> Result<WitnessMap, AbiError> {
+        Ok(self.encode_parameters(input_map, true)?.into())
+    }
+
+    /// Encodes the parameters in `input_map` into their witnesses. Every parameter must be given
+    /// unless encoding `partial` inputs.
+    fn encode_parameters(
+        &self,
+        input_map: &InputMap,
+        partial: bool,
+    ) -> Result<BTreeMap<Witness, FieldElement>, AbiError> {
         // Check that no extra witness values have been provided.
         let param_names = self.parameter_names();
-        if param_names.len() < input_map.len() {
+        if input_map.keys().any(|param| !param_names.contains(&param)) {
             let unexpected_params: Vec<String> =
                 input_map.keys().filter(|param| !param_names.contains(param)).cloned().collect();
             return Err(AbiError::UnexpectedParams(unexpected_params));
@@ -233,6 +315,7 @@ impl Abi {
         let encoded_input_map: BTreeMap<String, Vec<FieldElement>> = self
             .to_btree_map()
             .into_iter()
+            .filter(|(param_name, _)| !partial || input_map.contains_key(param_name))
             .map(|(param_name, expected_type)| {
                 let value = input_map
                     .get(&param_name)
@@ -254,7 +337,7 @@ impl Abi {
             .collect::<Result<_, _>>()?;
 
         // Write input field elements into witness indices specified in `self.param_witnesses`.
-        let mut witness_map: BTreeMap<Witness, FieldElement> = encoded_input_map
+        let witness_map = encoded_input_map
             .iter()
             .flat_map(|(param_name, encoded_param_fields)| {
                 let param_witness_indices = &self.param_witnesses[param_name];
@@ -264,48 +347,31 @@ impl Abi {
                     .map(|(&witness, &field_element)| (witness, field_element))
             })
             .collect();
-
-        // When encoding public inputs to be passed to the verifier, the user can must provide a return value
-        // to be inserted into the witness map. This is not needed when generating a witness when proving the circuit.
-        match (&self.return_type, return_value) {
-            (Some(return_type), Some(return_value)) => {
-                if !return_value.matches_abi(return_type) {
-                    return Err(AbiError::ReturnTypeMismatch {
-                        return_type: return_type.clone(),
-                        value: return_value,
-                    });
-                }
-                let encoded_return_fields = Self::encode_value(return_value, return_type)?;
-
-                // We need to be more careful when writing the return value's witness values.
-                // This is as it may share witness indices with other public inputs so we must check that when
-                // this occurs the witness values are consistent with each other.
-                self.return_witnesses.iter().zip(encoded_return_fields.iter()).try_for_each(
-                    |(&witness, &field_element)| match witness_map.insert(witness, field_element) {
-                        Some(existing_value) if existing_value != field_element => {
-                            Err(AbiError::InconsistentWitnessAssignment(witness))
-                        }
-                        _ => Ok(()),
-                    },
-                )?;
-            }
-            (None, Some(return_value)) => {
-                return Err(AbiError::UnexpectedReturnValue(return_value))
-            }
-            // We allow not passing a return value despite the circuit defining one
-            // in order to generate the initial partial witness.
-            (_, None) => {}
-        }
-
-        Ok(witness_map.into())
+        Ok(witness_map)
     }
 
     fn encode_value(value: InputValue, abi_type: &AbiType) -> Result<Vec<FieldElement>, AbiError> {
         let mut encoded_value = Vec::new();
         match (value, abi_type) {
+            (InputValue::Field(elem), AbiType::Integer { sign: Sign::Signed, width }) => {
+                let elem = input_parser::to_twos_complement(elem, *width)
+                    .expect("value should have already been checked to match abi type");
+                encoded_value.push(elem);
+            }
+
             (InputValue::Field(elem), _) => encoded_value.push(elem),
 
-            (InputValue::Vec(vec_elem), _) => encoded_value.extend(vec_elem),
+            (InputValue::Vec(vec_elem), AbiType::Array { typ, .. }) => {
+                for elem in vec_elem {
+                    encoded_value.extend(Self::encode_value(elem, typ)?);
+                }
+            }
+
+            (InputValue::Vec(vec_elem), AbiType::Tuple { fields }) => {
+                for (elem, typ) in vec_elem.into_iter().zip(fields) {
+                    encoded_value.extend(Self::encode_value(elem, typ)?);
+                }
+            }
 
             (InputValue::String(string), _) => {
                 let str_as_fields =
@@ -384,11 +450,10 @@ impl Abi {
 
                 InputValue::Field(field_element)
             }
-            AbiType::Array { length, .. } => {
-                let field_elements: Vec<FieldElement> =
-                    field_iterator.take(*length as usize).collect();
+            AbiType::Array { length, typ } => {
+                let elements = try_vecmap(0..*length, |_| Self::decode_value(field_iterator, typ))?;
 
-                InputValue::Vec(field_elements)
+                InputValue::Vec(elements)
             }
             AbiType::String { length } => {
                 let field_elements: Vec<FieldElement> =
@@ -407,6 +472,11 @@ impl Abi {
 
                 InputValue::Struct(struct_map)
             }
+            AbiType::Tuple { fields } => {
+                let elements = try_vecmap(fields, |typ| Self::decode_value(field_iterator, typ))?;
+
+                InputValue::Vec(elements)
+            }
         };
 
         Ok(value)
@@ -459,7 +529,13 @@ mod test {
 
         // Note we omit return value from inputs
         let inputs: InputMap = BTreeMap::from([
-            ("thing1".to_string(), InputValue::Vec(vec![FieldElement::one(), FieldElement::one()])),
+            (
+                "thing1".to_string(),
+                InputValue::Vec(vec![
+                    InputValue::Field(FieldElement::one()),
+                    InputValue::Field(FieldElement::one()),
+                ]),
+            ),
             ("thing2".to_string(), InputValue::Field(FieldElement::zero())),
         ]);
 
@@ -473,4 +549,36 @@ mod test {
         // We also decode the return value (we can do this immediately as we know it shares a witness with an input).
         assert_eq!(return_value.unwrap(), reconstructed_inputs["thing2"]);
     }
+
+    #[test]
+    fn partial_encoding_leaves_missing_parameters_unassigned() {
+        let abi = Abi {
+            parameters: vec![
+                AbiParameter {
+                    name: "thing1".to_string(),
+                    typ: AbiType::Field,
+                    visibility: AbiVisibility::Private,
+                },
+                AbiParameter {
+                    name: "thing2".to_string(),
+                    typ: AbiType::Field,
+                    visibility: AbiVisibility::Private,
+                },
+            ],
+            param_witnesses: BTreeMap::from([
+                ("thing1".to_string(), vec![Witness(1)]),
+                ("thing2".to_string(), vec![Witness(2)]),
+            ]),
+            return_type: None,
+            return_witnesses: Vec::new(),
+        };
+
+        let inputs: InputMap =
+            BTreeMap::from([("thing2".to_string(), InputValue::Field(FieldElement::one()))]);
+
+        let witness_map = abi.encode_partial(&inputs).unwrap();
+        assert_eq!(witness_map.get(&Witness(1)), None);
+        assert_eq!(witness_map.get(&Witness(2)), Some(&FieldElement::one()));
+        assert!(abi.encode(&inputs, None).is_err());
+    }
 }
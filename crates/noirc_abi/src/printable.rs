@@ -0,0 +1,248 @@
+//! Displaying the values passed to the `print` foreign call.
+//!
+//! Along with the values to be printed, the compiler passes a JSON encoded [PrintableType] to
+//! the `print` foreign call as its final input. This describes how the values should be decoded,
+//! so that structs print with the names of their fields and format strings can be interpolated.
+use acvm::FieldElement;
+use serde::{Deserialize, Serialize};
+
+use crate::{decode_string_value, errors::AbiError, AbiType, Sign};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PrintableType {
+    /// A single value of the given type
+    Value {
+        #[serde(rename = "type")]
+        typ: AbiType,
+    },
+    /// A format string of the given length, followed by the values of each variable it refers to
+    FormatString { length: u64, fields: Vec<AbiType> },
+    /// A value whose type has no ABI representation, such as a function, which is printed
+    /// as its raw field elements
+    Unknown,
+}
+
+impl PrintableType {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("printable types should always be serializable")
+    }
+
+    /// Decodes a printable type from the string of bytes it was encoded into by the compiler
+    pub fn from_encoded(bytes: &[FieldElement]) -> Result<Self, AbiError> {
+        serde_json::from_str(&decode_string_value(bytes))
+            .map_err(|error| AbiError::Generic(error.to_string()))
+    }
+
+    /// Decodes the given field elements as a value of this type and formats them for display
+    pub fn display(&self, fields: &[FieldElement]) -> Result<String, AbiError> {
+        let mut fields = fields.iter().copied();
+        let output = match self {
+            PrintableType::Value { typ } => display_value(&mut fields, typ)?,
+            PrintableType::FormatString { length, fields: field_types } => {
+                let template = take_fields(&mut fields, *length as usize)?;
+                let values = field_types
+                    .iter()
+                    .map(|typ| display_value(&mut fields, typ))
+                    .collect::<Result<Vec<_>, _>>()?;
+                interpolate(&decode_string_value(&template), values)
+            }
+            PrintableType::Unknown => {
+                let values: Vec<_> = fields.by_ref().map(format_field).collect();
+                match values.as_slice() {
+                    [value] => value.clone(),
+                    values => format!("[{}]", values.join(", ")),
+                }
+            }
+        };
+
+        match fields.next() {
+            None => Ok(output),
+            Some(_) => Err(AbiError::Generic(
+                "Received more values to print than expected by their type".into(),
+            )),
+        }
+    }
+}
+
+fn display_value(
+    fields: &mut impl Iterator<Item = FieldElement>,
+    typ: &AbiType,
+) -> Result<String, AbiError> {
+    let output = match typ {
+        AbiType::Field => format_field(take_field(fields)?),
+        AbiType::Integer { sign: Sign::Unsigned, .. } => take_field(fields)?.to_u128().to_string(),
+        AbiType::Integer { sign: Sign::Signed, width } => {
+            let value = take_field(fields)?.to_u128();
+            // Negative values are represented in two's complement
+            if *width > 0 && *width <= 128 && value >> (width - 1) == 1 {
+                let magnitude = (u128::MAX >> (128 - width)) - value + 1;
+                format!("-{magnitude}")
+            } else {
+                value.to_string()
+            }
+        }
+        AbiType::Boolean => (!take_field(fields)?.is_zero()).to_string(),
+        AbiType::String { length } => decode_string_value(&take_fields(fields, *length as usize)?),
+        AbiType::Array { length, typ } => {
+            // Arrays of structs are stored as a struct of arrays, so the values of each
+            // element must first be gathered from every field's array.
+            let length = *length as usize;
+            let values = take_fields(fields, length * typ.field_count() as usize)?;
+            let elements = (0..length)
+                .map(|index| {
+                    let mut element = values.iter().skip(index).step_by(length).copied();
+                    display_value(&mut element, typ)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            format!("[{}]", elements.join(", "))
+        }
+        AbiType::Struct { fields: field_types } => {
+            let values = field_types
+                .iter()
+                .map(|(name, typ)| Ok(format!("{name}: {}", display_value(fields, typ)?)))
+                .collect::<Result<Vec<_>, AbiError>>()?;
+            format!("{{ {} }}", values.join(", "))
+        }
+    };
+    Ok(output)
+}
+
+/// Replaces each `{variable}` in the template with the next of the given values
+fn interpolate(template: &str, values: Vec<String>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut offset = 0;
+
+    for ((start, name), value) in format_string_variables(template).into_iter().zip(values) {
+        output.push_str(&template[offset..start]);
+        output.push_str(&value);
+        offset = start + name.len() + 2;
+    }
+
+    output.push_str(&template[offset..]);
+    output
+}
+
+/// Returns each variable interpolated into the given format string as `{variable}`, along with
+/// the offset of its opening brace. Braces around anything other than a variable name are
+/// left in the string as they are.
+pub fn format_string_variables(template: &str) -> Vec<(usize, &str)> {
+    let mut variables = Vec::new();
+    let mut offset = 0;
+
+    while let Some(start) = template[offset..].find('{').map(|index| offset + index) {
+        let end = match template[start..].find('}') {
+            Some(index) => start + index,
+            None => break,
+        };
+
+        let name = &template[start + 1..end];
+        if is_variable_name(name) {
+            variables.push((start, name));
+        }
+        offset = end + 1;
+    }
+
+    variables
+}
+
+fn is_variable_name(text: &str) -> bool {
+    let mut chars = text.chars();
+    matches!(chars.next(), Some(first) if first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+fn take_field(fields: &mut impl Iterator<Item = FieldElement>) -> Result<FieldElement, AbiError> {
+    fields.next().ok_or_else(|| AbiError::Generic("Received too few values to print".into()))
+}
+
+fn take_fields(
+    fields: &mut impl Iterator<Item = FieldElement>,
+    count: usize,
+) -> Result<Vec<FieldElement>, AbiError> {
+    (0..count).map(|_| take_field(fields)).collect()
+}
+
+/// Formats a field element as hex, without any leading zeroes
+fn format_field(field: FieldElement) -> String {
+    let trimmed = field.to_hex().trim_start_matches('0').to_owned();
+    if trimmed.is_empty() {
+        "0x00".to_owned()
+    } else if trimmed.len() % 2 == 1 {
+        format!("0x0{trimmed}")
+    } else {
+        format!("0x{trimmed}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use acvm::FieldElement;
+
+    use super::PrintableType;
+    use crate::{AbiType, Sign};
+
+    fn encode_str(string: &str) -> Vec<FieldElement> {
+        string.bytes().map(|byte| FieldElement::from(byte as u128)).collect()
+    }
+
+    #[test]
+    fn displays_structs_with_field_names() {
+        let typ = AbiType::Struct {
+            fields: vec![
+                ("x".into(), AbiType::Integer { sign: Sign::Unsigned, width: 8 }),
+                ("flags".into(), AbiType::Array { length: 2, typ: Box::new(AbiType::Boolean) }),
+            ],
+        };
+        let printable = PrintableType::Value { typ };
+        let values = [3_u128, 1, 0].map(FieldElement::from);
+
+        assert_eq!(printable.display(&values).unwrap(), "{ x: 3, flags: [true, false] }");
+    }
+
+    #[test]
+    fn displays_arrays_of_structs() {
+        let point = AbiType::Struct {
+            fields: vec![("x".into(), AbiType::Field), ("y".into(), AbiType::Boolean)],
+        };
+        let printable =
+            PrintableType::Value { typ: AbiType::Array { length: 2, typ: Box::new(point) } };
+        let values = [1_u128, 2, 1, 0].map(FieldElement::from);
+
+        assert_eq!(
+            printable.display(&values).unwrap(),
+            "[{ x: 0x01, y: true }, { x: 0x02, y: false }]"
+        );
+    }
+
+    #[test]
+    fn displays_negative_integers() {
+        let printable =
+            PrintableType::Value { typ: AbiType::Integer { sign: Sign::Signed, width: 8 } };
+        let values = [FieldElement::from(254_u128)];
+
+        assert_eq!(printable.display(&values).unwrap(), "-2");
+    }
+
+    #[test]
+    fn interpolates_format_strings() {
+        let template = "x = {x}, hash = {h}, {not a variable}";
+        let printable = PrintableType::FormatString {
+            length: template.len() as u64,
+            fields: vec![AbiType::Integer { sign: Sign::Unsigned, width: 32 }, AbiType::Field],
+        };
+        let mut values = encode_str(template);
+        values.push(FieldElement::from(5_u128));
+        values.push(FieldElement::from(255_u128));
+
+        assert_eq!(printable.display(&values).unwrap(), "x = 5, hash = 0xff, {not a variable}");
+    }
+
+    #[test]
+    fn round_trips_through_encoding() {
+        let printable = PrintableType::FormatString { length: 3, fields: vec![AbiType::Field] };
+        let encoded = encode_str(&printable.to_json());
+
+        assert_eq!(PrintableType::from_encoded(&encoded).unwrap(), printable);
+    }
+}
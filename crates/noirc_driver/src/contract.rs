@@ -1,6 +1,8 @@
 use crate::program::{deserialize_circuit, serialize_circuit};
+use crate::DebugInfo;
 use acvm::acir::circuit::Circuit;
 use noirc_abi::Abi;
+use noirc_evaluator::{brillig::BrilligDebugInfo, ssa_refactor::SsaPasses};
 use serde::{Deserialize, Serialize};
 
 /// Describes the types of smart contract functions that are allowed.
@@ -45,6 +47,14 @@ pub struct ContractFunction {
 
     #[serde(serialize_with = "serialize_circuit", deserialize_with = "deserialize_circuit")]
     pub bytecode: Circuit,
+
+    #[serde(skip)]
+    pub brillig_debug_info: BrilligDebugInfo,
+
+    #[serde(skip)]
+    pub ssa_passes: SsaPasses,
+
+    pub debug_info: DebugInfo,
 }
 
 impl ContractFunctionType {
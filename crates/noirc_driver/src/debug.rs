@@ -0,0 +1,135 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use acvm::acir::circuit::Opcode;
+use fm::{FileId, FileManager};
+use noirc_errors::{reporter::location_to_line_and_column, Location};
+use noirc_evaluator::ssa_refactor::OpcodeLocations;
+use serde::{Deserialize, Serialize};
+
+/// The number of original opcodes searched for an arithmetic opcode of the compiled circuit
+/// before assuming that the backend generated it.
+const MAX_LOOKAHEAD: usize = 16;
+
+/// The location in the Noir source of each opcode of a compiled circuit, so that tools such as
+/// debuggers and profilers can point back to the code an opcode was generated from without
+/// recompiling the program.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DebugInfo {
+    /// The names of the source files referred to by the locations.
+    pub files: Vec<String>,
+    /// The location of each opcode, keyed by its index in the circuit. Opcodes which are not
+    /// generated from a single location of the program are left out.
+    pub locations: BTreeMap<usize, SourceLocation>,
+}
+
+/// A location in one of the source files of a `DebugInfo`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    /// The index of the file in `DebugInfo::files`.
+    pub file: usize,
+    /// The byte offsets of the start and end of the code within the file.
+    pub start: u32,
+    pub end: u32,
+    /// The line and column numbers of the start of the code, both starting at one.
+    pub line: usize,
+    pub column: usize,
+}
+
+impl DebugInfo {
+    pub(crate) fn new(files: &FileManager, opcode_locations: &[Option<Location>]) -> Self {
+        let mut debug_info = DebugInfo::default();
+        let mut file_indices: HashMap<FileId, usize> = HashMap::new();
+
+        for (opcode_index, location) in opcode_locations.iter().enumerate() {
+            let location = match location {
+                Some(location) => *location,
+                None => continue,
+            };
+            let (name, line, column) = match location_to_line_and_column(files, location) {
+                Some(position) => position,
+                None => continue,
+            };
+            let file = *file_indices.entry(location.file).or_insert_with(|| {
+                debug_info.files.push(name);
+                debug_info.files.len() - 1
+            });
+            let (start, end) = (location.span.start(), location.span.end());
+            debug_info
+                .locations
+                .insert(opcode_index, SourceLocation { file, start, end, line, column });
+        }
+        debug_info
+    }
+
+    /// Names the source files relative to `root`, the directory of the package being compiled, so
+    /// that the debug info is the same whichever directory the program is compiled from. Files
+    /// outside of `root`, such as those of the standard library, keep their names.
+    pub fn relativize_files(&mut self, root: &Path) {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let current_dir = std::env::current_dir().unwrap_or_default();
+        for file in &mut self.files {
+            // File names are relative to the current directory unless they are outside of it
+            let path = current_dir.join(&*file);
+            if let Ok(relative_path) = path.strip_prefix(&root) {
+                *file = relative_path.to_string_lossy().into_owned();
+            }
+        }
+    }
+
+    /// Returns the name of the file and the location the opcode at the given index was
+    /// generated from, if known.
+    pub fn opcode_location(&self, opcode_index: usize) -> Option<(&str, &SourceLocation)> {
+        let location = self.locations.get(&opcode_index)?;
+        Some((self.files[location.file].as_str(), location))
+    }
+}
+
+/// Returns the locations of the opcodes of `compiled`, the circuit the backend compiled from
+/// the `original` opcodes whose locations are `opcode_locations`.
+///
+/// The backend only rewrites arithmetic opcodes and the black box functions it does not support,
+/// keeping the order of the opcodes. Each compiled opcode is therefore matched with the next
+/// identical original opcode, while the opcodes generated by the backend are attributed to the
+/// original opcode being rewritten.
+pub(crate) fn transfer_opcode_locations(
+    original: &[Opcode],
+    opcode_locations: &[Option<Location>],
+    compiled: &[Opcode],
+) -> OpcodeLocations {
+    if original.len() != opcode_locations.len() {
+        return vec![None; compiled.len()];
+    }
+
+    // The index of the first non-arithmetic opcode at or after each index
+    let mut next_non_arithmetic = vec![original.len(); original.len() + 1];
+    for index in (0..original.len()).rev() {
+        next_non_arithmetic[index] = match original[index] {
+            Opcode::Arithmetic(_) => next_non_arithmetic[index + 1],
+            _ => index,
+        };
+    }
+
+    let mut next = 0;
+    compiled
+        .iter()
+        .map(|opcode| {
+            let matching_index = if matches!(opcode, Opcode::Arithmetic(_)) {
+                let end = next_non_arithmetic[next].min(next + MAX_LOOKAHEAD);
+                (next..end).find(|index| &original[*index] == opcode)
+            } else {
+                let index = next_non_arithmetic[next];
+                (index < original.len() && &original[index] == opcode).then_some(index)
+            };
+            match matching_index {
+                Some(index) => {
+                    next = index + 1;
+                    opcode_locations[index]
+                }
+                None => opcode_locations.get(next).copied().flatten(),
+            }
+        })
+        .collect()
+}
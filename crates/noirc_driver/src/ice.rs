@@ -0,0 +1,138 @@
+//! A panic within the compiler is a bug in the compiler rather than in the program being compiled.
+//! Each pass of the pipeline is run through [`catch_internal_errors`] so that such a panic is
+//! reported as an internal compiler error diagnostic, instead of unwinding through the caller
+//! and printing a backtrace.
+
+use std::cell::{Cell, RefCell};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+use fm::FileId;
+use noirc_errors::{CustomDiagnostic, FileDiagnostic};
+
+const ISSUES_URL: &str = "https://github.com/noir-lang/noir/issues";
+
+const BUG_REPORT_URL: &str =
+    "https://github.com/noir-lang/noir/issues/new?labels=bug&template=bug_report.yml";
+
+static INSTALL_PANIC_HOOK: Once = Once::new();
+
+thread_local! {
+    /// Whether this thread is running a pass of the compiler through [`catch_internal_errors`]
+    static INSIDE_COMPILER: Cell<bool> = Cell::new(false);
+
+    /// The source location within the compiler of the last panic caught on this thread
+    static PANIC_LOCATION: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Installs a panic hook which only records where a panic within a pass of the compiler
+/// occurred, rather than printing it along with its backtrace, as it is reported as an internal
+/// compiler error instead. Panics outside of the compiler, including those on other threads,
+/// are passed on to the hook which was installed before.
+///
+/// The hook is installed once for the whole process, as replacing it around each pass would
+/// race with other threads doing the same.
+fn install_panic_hook() {
+    INSTALL_PANIC_HOOK.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if INSIDE_COMPILER.with(Cell::get) {
+                let location = info.location().map(|location| location.to_string());
+                PANIC_LOCATION.with(|panic_location| *panic_location.borrow_mut() = location);
+            } else {
+                previous_hook(info);
+            }
+        }));
+    });
+}
+
+/// Runs `pass`, turning a panic within it into an internal compiler error which names the pass.
+pub(crate) fn catch_internal_errors<T>(
+    pass_name: &str,
+    pass: impl FnOnce() -> T,
+) -> Result<T, FileDiagnostic> {
+    install_panic_hook();
+
+    // Passes may be nested, so the flag is restored rather than cleared once the pass is done
+    let was_inside_compiler = INSIDE_COMPILER.with(|inside| inside.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(pass));
+    INSIDE_COMPILER.with(|inside| inside.set(was_inside_compiler));
+
+    result.map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_owned());
+        let location = PANIC_LOCATION.with(|panic_location| panic_location.borrow_mut().take());
+        internal_compiler_error(pass_name, &message, location)
+    })
+}
+
+fn internal_compiler_error(
+    pass_name: &str,
+    message: &str,
+    location: Option<String>,
+) -> FileDiagnostic {
+    let mut diagnostic =
+        CustomDiagnostic::bug_from_message(&format!("internal compiler error: {message}"));
+
+    let location = location.map(|location| format!(" at {location}")).unwrap_or_default();
+    diagnostic.add_note(format!("the compiler panicked during {pass_name}{location}"));
+    diagnostic.add_note(format!("compiler version: {}", env!("CARGO_PKG_VERSION")));
+    diagnostic.add_note(format!(
+        "this is a bug in the compiler. We may have already fixed it in a newer version, so try searching for similar issues at {ISSUES_URL}.\nIf there isn't an open issue for this bug, consider opening one including this message at {BUG_REPORT_URL}"
+    ));
+
+    diagnostic.in_file(FileId::dummy())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::{catch_internal_errors, INSIDE_COMPILER};
+
+    #[test]
+    fn reports_panics_as_internal_errors() {
+        let result = catch_internal_errors("testing", || -> u32 { panic!("oops") });
+        let diagnostic = result.unwrap_err().diagnostic;
+        assert!(diagnostic.is_bug());
+        assert_eq!(diagnostic.message, "internal compiler error: oops");
+
+        let formatted = format!("{diagnostic}");
+        assert!(formatted.contains("the compiler panicked during testing at "));
+        assert!(formatted.contains("compiler version: "));
+    }
+
+    #[test]
+    fn returns_the_result_of_passes_which_do_not_panic() {
+        assert_eq!(catch_internal_errors("testing", || 1).unwrap(), 1);
+    }
+
+    #[test]
+    fn panics_after_a_pass_are_not_treated_as_internal_errors() {
+        catch_internal_errors("testing", || 1).unwrap();
+        assert!(!INSIDE_COMPILER.with(Cell::get));
+
+        catch_internal_errors("testing", || -> u32 { panic!("oops") }).unwrap_err();
+        assert!(!INSIDE_COMPILER.with(Cell::get));
+    }
+
+    #[test]
+    fn passes_on_many_threads_report_their_own_panics() {
+        let threads: Vec<_> = (0..8)
+            .map(|index| {
+                std::thread::spawn(move || {
+                    let pass = || -> u32 { panic!("oops {index}") };
+                    catch_internal_errors("testing", pass).unwrap_err().diagnostic.message
+                })
+            })
+            .collect();
+
+        for (index, thread) in threads.into_iter().enumerate() {
+            let message = thread.join().unwrap();
+            assert_eq!(message, format!("internal compiler error: oops {index}"));
+        }
+    }
+}
@@ -6,32 +6,58 @@
 use acvm::acir::circuit::Opcode;
 use acvm::compiler::CircuitSimplifier;
 use acvm::Language;
-use clap::Args;
+use clap::{Args, ValueEnum};
+use debug::transfer_opcode_locations;
 use fm::FileId;
+use ice::catch_internal_errors;
 use noirc_abi::FunctionSignature;
-use noirc_errors::{CustomDiagnostic, FileDiagnostic};
-use noirc_evaluator::{create_circuit, ssa_refactor::experimental_create_circuit};
+use noirc_errors::{reporter::location_to_string, CustomDiagnostic, FileDiagnostic};
+use noirc_evaluator::{
+    create_circuit, deduplicate_opcodes, normalize_opcodes,
+    ssa_refactor::{experimental_create_circuit, OpcodeLocations, SsaPasses},
+    unsupported_black_box_fallbacks,
+};
 use noirc_frontend::graph::{CrateId, CrateName, CrateType, LOCAL_CRATE};
 use noirc_frontend::hir::def_map::{Contract, CrateDefMap};
 use noirc_frontend::hir::Context;
-use noirc_frontend::monomorphization::monomorphize;
+use noirc_frontend::monomorphization::monomorphize_with_fallbacks;
 use noirc_frontend::node_interner::FuncId;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 mod contract;
+mod debug;
+mod ice;
+mod profile;
 mod program;
 
 pub use contract::{CompiledContract, ContractFunction, ContractFunctionType};
+pub use debug::{DebugInfo, SourceLocation};
+pub use noirc_evaluator::brillig::{
+    disassemble_with_labels as disassemble_brillig, BrilligDebugInfo, BrilligDisassembly,
+    BrilligFunctionDisassembly, BrilligFunctionStatistics, BrilligStatistics,
+    BLACK_BOX_FOREIGN_CALL_PREFIX,
+};
+pub use noirc_evaluator::ssa_refactor::{SsaPass, SsaPasses};
+pub use noirc_evaluator::{disassemble_acir, DeduplicationStatistics};
+pub use noirc_frontend::OverflowMode;
+pub use profile::OpcodeProfile;
 pub use program::CompiledProgram;
 
 #[derive(Args, Clone, Debug, Serialize, Deserialize)]
 pub struct CompileOptions {
-    /// Emit debug information for the intermediate SSA IR
+    /// Emit debug information for the intermediate SSA IR.
+    /// With the experimental SSA pass, the SSA after each optimization pass is written to `target/ssa`
     #[arg(short, long)]
     pub show_ssa: bool,
 
-    /// Display the ACIR for compiled circuit
+    /// Write the control flow graph of each function after each SSA optimization pass
+    /// to `target/ssa` as Graphviz dot files, showing the dominator tree with dashed edges
+    #[arg(long)]
+    pub show_ssa_cfg: bool,
+
+    /// Display the ACIR of the compiled circuit in a human-readable format
     #[arg(long)]
     pub print_acir: bool,
 
@@ -46,20 +72,85 @@ pub struct CompileOptions {
     /// Compile and optimize using the new experimental SSA pass
     #[arg(long)]
     pub experimental_ssa: bool,
+
+    /// Write the Brillig bytecode and control flow graph of unconstrained functions to `target/brillig`
+    #[arg(long)]
+    pub show_brillig: bool,
+
+    /// Display the Brillig bytecode of unconstrained functions.
+    /// This is also enabled by setting the `NOIR_BRILLIG_TRACE=1` environment variable
+    #[arg(long)]
+    pub print_brillig: bool,
+
+    /// Inline calls between unconstrained functions to functions with at most this many
+    /// SSA instructions and no calls of their own. Set to 0 to disable this inlining.
+    /// Functions marked `#[inline(always)]` or `#[inline(never)]` ignore this threshold
+    #[arg(long, default_value_t = DEFAULT_BRILLIG_INLINE_THRESHOLD)]
+    pub inline_brillig_threshold: usize,
+
+    /// Maximum number of iterations of a loop of a constrained function, which is
+    /// unrolled once per iteration
+    #[arg(long, default_value_t = DEFAULT_MAX_UNROLL)]
+    pub max_unroll: usize,
+
+    /// The format in which warnings and errors are reported. `json` writes each diagnostic
+    /// to stdout as a JSON object on its own line
+    #[arg(long, value_enum, default_value_t = MessageFormat::Human)]
+    pub message_format: MessageFormat,
+
+    /// The behaviour of integer arithmetic on overflow: `checked`, `wrapping` or
+    /// `saturating`. Overrides the `overflow` mode in the Nargo.toml of the compiled package
+    #[arg(long)]
+    pub overflow: Option<OverflowMode>,
+}
+
+/// The formats in which diagnostics may be reported
+#[derive(ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MessageFormat {
+    #[default]
+    Human,
+    Json,
 }
 
 impl Default for CompileOptions {
     fn default() -> Self {
         Self {
             show_ssa: false,
+            show_ssa_cfg: false,
             print_acir: false,
             deny_warnings: false,
             show_output: true,
             experimental_ssa: false,
+            show_brillig: false,
+            print_brillig: false,
+            inline_brillig_threshold: DEFAULT_BRILLIG_INLINE_THRESHOLD,
+            max_unroll: DEFAULT_MAX_UNROLL,
+            message_format: MessageFormat::Human,
+            overflow: None,
         }
     }
 }
 
+/// Default maximum number of instructions of the unconstrained functions inlined
+/// into their unconstrained callers.
+const DEFAULT_BRILLIG_INLINE_THRESHOLD: usize = 10;
+
+/// Default maximum number of iterations of the loops of constrained functions.
+const DEFAULT_MAX_UNROLL: usize = 100_000;
+
+/// Environment variable which displays the Brillig bytecode of unconstrained functions
+/// when set to `1`, in the same way as `--print-brillig`.
+pub const BRILLIG_TRACE_ENV_VAR: &str = "NOIR_BRILLIG_TRACE";
+
+impl CompileOptions {
+    /// True if the Brillig bytecode should be displayed, either because `print_brillig`
+    /// is set or because it was requested through `BRILLIG_TRACE_ENV_VAR`.
+    fn should_print_brillig(&self) -> bool {
+        self.print_brillig
+            || std::env::var(BRILLIG_TRACE_ENV_VAR).map_or(false, |value| value == "1")
+    }
+}
+
 /// Helper type used to signify where only warnings are expected in file diagnostics
 pub type Warnings = Vec<FileDiagnostic>;
 
@@ -133,6 +224,21 @@ pub fn add_dep(context: &mut Context, this_crate: CrateId, depends_on: CrateId,
         .expect("cyclic dependency triggered");
 }
 
+/// Enables the given features of a crate, so that its items marked with
+/// `#[cfg(feature = "..")]` for any of them are compiled.
+pub fn enable_features(
+    context: &mut Context,
+    crate_id: CrateId,
+    features: impl IntoIterator<Item = String>,
+) {
+    context.crate_graph.enable_features(crate_id, features);
+}
+
+/// Sets the behaviour of the integer arithmetic of a crate on overflow.
+pub fn set_overflow_mode(context: &mut Context, crate_id: CrateId, overflow: OverflowMode) {
+    context.crate_graph.set_overflow_mode(crate_id, overflow);
+}
+
 /// Propagates a given dependency to every other crate.
 pub fn propagate_dep(
     context: &mut Context,
@@ -154,6 +260,8 @@ pub fn propagate_dep(
 ///
 /// This returns a (possibly empty) vector of any warnings found on success.
 /// On error, this returns a non-empty vector of warnings and error messages, with at least one error.
+/// The warnings of lints allowed by `context.lint_levels` are dropped, and those of denied lints
+/// are reported as errors. A panic within these passes is reported as an internal compiler error.
 pub fn check_crate(
     context: &mut Context,
     deny_warnings: bool,
@@ -170,8 +278,13 @@ pub fn check_crate(
 
     context.def_interner.enable_slices = enable_slices;
 
-    let mut errors = vec![];
-    CrateDefMap::collect_defs(LOCAL_CRATE, context, &mut errors);
+    let errors = catch_internal_errors("parsing, name resolution and type checking", || {
+        let mut errors = vec![];
+        CrateDefMap::collect_defs(LOCAL_CRATE, context, &mut errors);
+        errors
+    })
+    .unwrap_or_else(|internal_error| vec![internal_error]);
+    let errors = context.lint_levels.apply(errors);
 
     if has_errors(&errors, deny_warnings) {
         Err(errors)
@@ -198,6 +311,9 @@ pub fn compile_main(
     is_opcode_supported: &impl Fn(&Opcode) -> bool,
     options: &CompileOptions,
 ) -> Result<(CompiledProgram, Warnings), ErrorsAndWarnings> {
+    if let Some(overflow) = options.overflow {
+        set_overflow_mode(context, LOCAL_CRATE, overflow);
+    }
     let warnings = check_crate(context, options.deny_warnings, options.experimental_ssa)?;
 
     let main = match context.get_main_function(&LOCAL_CRATE) {
@@ -214,9 +330,19 @@ pub fn compile_main(
     let compiled_program =
         compile_no_check(context, options, main, np_language, is_opcode_supported)?;
 
+    let mut warnings = warnings;
+    warnings.extend(brillig_memory_warnings(&compiled_program.brillig_debug_info.statistics));
+    if has_errors(&warnings, options.deny_warnings) {
+        return Err(warnings);
+    }
+
     if options.print_acir {
         println!("Compiled ACIR for main:");
-        println!("{}", compiled_program.circuit);
+        print!("{}", disassemble_acir(&compiled_program.circuit));
+    }
+
+    if options.should_print_brillig() {
+        print_brillig_disassembly(context, &compiled_program.brillig_debug_info.disassembly);
     }
 
     Ok((compiled_program, warnings))
@@ -229,6 +355,9 @@ pub fn compile_contracts(
     is_opcode_supported: &impl Fn(&Opcode) -> bool,
     options: &CompileOptions,
 ) -> Result<(Vec<CompiledContract>, Warnings), ErrorsAndWarnings> {
+    if let Some(overflow) = options.overflow {
+        set_overflow_mode(context, LOCAL_CRATE, overflow);
+    }
     let warnings = check_crate(context, options.deny_warnings, options.experimental_ssa)?;
 
     let contracts = context.get_all_contracts(&LOCAL_CRATE);
@@ -237,7 +366,13 @@ pub fn compile_contracts(
 
     for contract in contracts {
         match compile_contract(context, contract, np_language, is_opcode_supported, options) {
-            Ok(contract) => compiled_contracts.push(contract),
+            Ok(contract) => {
+                for function in &contract.functions {
+                    let statistics = &function.brillig_debug_info.statistics;
+                    errors.extend(brillig_memory_warnings(statistics));
+                }
+                compiled_contracts.push(contract);
+            }
             Err(mut more_errors) => errors.append(&mut more_errors),
         }
     }
@@ -252,7 +387,17 @@ pub fn compile_contracts(
                         "Compiled ACIR for {}::{}:",
                         compiled_contract.name, contract_function.name
                     );
-                    println!("{}", contract_function.bytecode);
+                    print!("{}", disassemble_acir(&contract_function.bytecode));
+                }
+            }
+        }
+        if options.should_print_brillig() {
+            for compiled_contract in &compiled_contracts {
+                for contract_function in &compiled_contract.functions {
+                    print_brillig_disassembly(
+                        context,
+                        &contract_function.brillig_debug_info.disassembly,
+                    );
                 }
             }
         }
@@ -261,6 +406,32 @@ pub fn compile_contracts(
     }
 }
 
+/// Prints the Brillig bytecode of each unconstrained function, annotated with source locations
+fn print_brillig_disassembly(context: &Context, brillig_disassembly: &BrilligDisassembly) {
+    for (function_name, disassembly) in brillig_disassembly {
+        let format_location = |location| location_to_string(&context.file_manager, location);
+        println!("Compiled Brillig for {function_name}:");
+        println!("{}", disassembly.annotated_instructions(format_location));
+    }
+}
+
+/// Warns about the unconstrained functions which could exhaust the memory of the Brillig VM at runtime
+fn brillig_memory_warnings(brillig_statistics: &BrilligStatistics) -> Warnings {
+    brillig_statistics
+        .iter()
+        .filter_map(|(function_name, statistics)| {
+            let message = if statistics.max_call_depth.is_none() {
+                format!("Unconstrained function `{function_name}` may recurse without bound, which could exhaust the memory of the Brillig VM")
+            } else if statistics.is_heap_size_unbounded {
+                format!("Unconstrained function `{function_name}` allocates memory in loops or for vectors of unknown size, which could exhaust the memory of the Brillig VM")
+            } else {
+                return None;
+            };
+            Some(CustomDiagnostic::warning_from_message(&message).in_file(FileId::dummy()))
+        })
+        .collect()
+}
+
 /// True if there are (non-warning) errors present and we should halt compilation
 fn has_errors(errors: &[FileDiagnostic], deny_warnings: bool) -> bool {
     if deny_warnings {
@@ -307,6 +478,9 @@ fn compile_contract(
             function_type,
             abi: function.abi,
             bytecode: function.circuit,
+            brillig_debug_info: function.brillig_debug_info,
+            ssa_passes: function.ssa_passes,
+            debug_info: function.debug_info,
         });
     }
 
@@ -320,7 +494,7 @@ fn compile_contract(
 /// Compile the current crate. Assumes self.check_crate is called beforehand!
 ///
 /// This function also assumes all errors in experimental_create_circuit and create_circuit
-/// are not warnings.
+/// are not warnings. A panic within any of the passes is returned as an internal compiler error.
 #[allow(deprecated)]
 pub fn compile_no_check(
     context: &Context,
@@ -329,24 +503,84 @@ pub fn compile_no_check(
     np_language: Language,
     is_opcode_supported: &impl Fn(&Opcode) -> bool,
 ) -> Result<CompiledProgram, FileDiagnostic> {
-    let program = monomorphize(main_function, &context.def_interner);
-
-    let (circuit, abi) = if options.experimental_ssa {
-        experimental_create_circuit(program, options.show_ssa, options.show_output)?
-    } else {
-        create_circuit(program, options.show_ssa, options.show_output)?
-    };
+    let fallbacks = black_box_fallbacks(context, is_opcode_supported);
+    let program = catch_internal_errors("monomorphization", || {
+        monomorphize_with_fallbacks(main_function, &context.def_interner, fallbacks)
+    })??;
+
+    let (circuit, abi, brillig_debug_info, ssa_passes, mut opcode_locations) =
+        if options.experimental_ssa {
+            catch_internal_errors("SSA optimization and ACIR generation", || {
+                experimental_create_circuit(
+                    program,
+                    options.show_ssa || options.show_ssa_cfg,
+                    options.show_output,
+                    options.show_brillig || options.should_print_brillig(),
+                    options.inline_brillig_threshold,
+                    options.max_unroll,
+                    is_opcode_supported,
+                )
+            })??
+        } else {
+            let (circuit, abi) = catch_internal_errors("ACIR generation", || {
+                create_circuit(program, options.show_ssa, options.show_output)
+            })??;
+            (circuit, abi, BrilligDebugInfo::default(), SsaPasses::new(), OpcodeLocations::new())
+        };
 
+    let circuit = normalize_opcodes(circuit, &mut opcode_locations);
+    let (circuit, deduplication) = deduplicate_opcodes(circuit, &mut opcode_locations);
+    let opcode_profile =
+        OpcodeProfile::new(&context.file_manager, &opcode_locations, circuit.opcodes.len());
     let abi_len = abi.field_count();
 
+    let opcodes = circuit.opcodes.clone();
     let simplifier = CircuitSimplifier::new(abi_len);
-    let optimized_circuit =
-        acvm::compiler::compile(circuit, np_language, is_opcode_supported, &simplifier).map_err(
-            |_| FileDiagnostic {
-                file_id: FileId::dummy(),
-                diagnostic: CustomDiagnostic::from_message("produced an acvm compile error"),
-            },
-        )?;
-
-    Ok(CompiledProgram { circuit: optimized_circuit, abi })
+    let optimized_circuit = catch_internal_errors("ACIR optimization", || {
+        acvm::compiler::compile(circuit, np_language, is_opcode_supported, &simplifier)
+    })?
+    .map_err(|_| FileDiagnostic {
+        file_id: FileId::dummy(),
+        diagnostic: CustomDiagnostic::from_message("produced an acvm compile error"),
+    })?;
+    let opcode_locations =
+        transfer_opcode_locations(&opcodes, &opcode_locations, &optimized_circuit.opcodes);
+    let debug_info = DebugInfo::new(&context.file_manager, &opcode_locations);
+
+    Ok(CompiledProgram {
+        circuit: optimized_circuit,
+        abi,
+        brillig_debug_info,
+        ssa_passes,
+        deduplication,
+        opcode_profile,
+        debug_info,
+    })
+}
+
+/// Returns the functions of the standard library to call in place of the black box functions
+/// which the backend does not support, keyed by the opcode name of each black box function.
+fn black_box_fallbacks(
+    context: &Context,
+    is_opcode_supported: &impl Fn(&Opcode) -> bool,
+) -> HashMap<String, FuncId> {
+    let std_crate = context.crate_graph[LOCAL_CRATE]
+        .dependencies
+        .iter()
+        .find(|dependency| dependency.as_name() == "std")
+        .and_then(|dependency| context.def_map(&dependency.crate_id));
+    let std_crate = match std_crate {
+        Some(std_crate) => std_crate,
+        None => return HashMap::new(),
+    };
+
+    unsupported_black_box_fallbacks(is_opcode_supported)
+        .into_iter()
+        .map(|(name, path)| {
+            let function = std_crate
+                .find_function_by_path(path)
+                .unwrap_or_else(|| panic!("ICE: the fallback std::{path} was not found"));
+            (name.to_owned(), function)
+        })
+        .collect()
 }
@@ -0,0 +1,48 @@
+use std::collections::BTreeMap;
+
+use fm::FileManager;
+use noirc_errors::{reporter::location_to_line, Location};
+
+/// The number of ACIR opcodes generated from each line of the Noir code of a program.
+///
+/// Opcodes are counted before the backend optimizes the circuit, so that they can still
+/// be attributed to the code they were generated from.
+#[derive(Debug, Clone, Default)]
+pub struct OpcodeProfile {
+    /// The number of opcodes generated from each line, keyed by file name and line number
+    pub lines: BTreeMap<(String, usize), usize>,
+    /// The number of opcodes which are not generated from a single line, such as the memory
+    /// opcodes of arrays, or all opcodes if the program is not compiled with the experimental SSA
+    pub unattributed: usize,
+}
+
+impl OpcodeProfile {
+    pub(crate) fn new(
+        files: &FileManager,
+        opcode_locations: &[Option<Location>],
+        num_opcodes: usize,
+    ) -> Self {
+        let mut lines = BTreeMap::new();
+        for location in opcode_locations.iter().flatten() {
+            if let Some(line) = location_to_line(files, *location) {
+                *lines.entry(line).or_default() += 1;
+            }
+        }
+        let attributed: usize = lines.values().sum();
+        OpcodeProfile { lines, unattributed: num_opcodes - attributed }
+    }
+
+    /// The total number of opcodes of the program.
+    pub fn total(&self) -> usize {
+        self.lines.values().sum::<usize>() + self.unattributed
+    }
+
+    /// The number of opcodes generated from each file.
+    pub fn files(&self) -> BTreeMap<&str, usize> {
+        let mut files = BTreeMap::new();
+        for ((file, _), count) in &self.lines {
+            *files.entry(file.as_str()).or_default() += count;
+        }
+        files
+    }
+}
@@ -1,12 +1,31 @@
 use acvm::acir::circuit::Circuit;
+use noirc_evaluator::{
+    brillig::BrilligDebugInfo, ssa_refactor::SsaPasses, DeduplicationStatistics,
+};
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::{DebugInfo, OpcodeProfile};
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CompiledProgram {
     #[serde(serialize_with = "serialize_circuit", deserialize_with = "deserialize_circuit")]
     pub circuit: Circuit,
     pub abi: noirc_abi::Abi,
+    /// Disassembly of and statistics about the program's unconstrained functions.
+    #[serde(skip)]
+    pub brillig_debug_info: BrilligDebugInfo,
+    /// The SSA after each optimization pass, only recorded when compiled with `show_ssa`.
+    #[serde(skip)]
+    pub ssa_passes: SsaPasses,
+    /// The number of ACIR opcodes before and after removing the redundant constraints.
+    #[serde(skip)]
+    pub deduplication: DeduplicationStatistics,
+    /// The number of ACIR opcodes generated from each line of the program.
+    #[serde(skip)]
+    pub opcode_profile: OpcodeProfile,
+    /// The location in the program of each opcode of the circuit.
+    pub debug_info: DebugInfo,
 }
 
 pub(crate) fn serialize_circuit<S>(circuit: &Circuit, s: S) -> Result<S::Ok, S::Error>
@@ -3,9 +3,13 @@
 #![warn(unreachable_pub)]
 #![warn(clippy::semicolon_if_nothing_returned)]
 
+pub mod lint;
 mod position;
+pub mod registry;
 pub mod reporter;
+pub use lint::{Lint, LintLevel, LintLevels};
 pub use position::{Location, Position, Span, Spanned};
+pub use registry::ErrorCode;
 pub use reporter::{CustomDiagnostic, DiagnosticKind};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -0,0 +1,130 @@
+//! Lints are the groups of warnings whose severity may be configured per package through
+//! the `[lints]` table of its Nargo.toml, e.g.
+//!
+//! ```toml
+//! [lints]
+//! unused = "deny"
+//! deprecated = "warn"
+//! unreachable_code = "allow"
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::{DiagnosticKind, FileDiagnostic};
+
+/// A group of related warnings which can be allowed, warned about or denied together
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Lint {
+    /// Variables, imports and private functions which are never used, and struct fields
+    /// which are never read
+    Unused,
+    /// Code which is never evaluated since the program has already returned or failed
+    UnreachableCode,
+    /// Uses of deprecated items and syntax
+    Deprecated,
+}
+
+impl Lint {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::Unused => "unused",
+            Lint::UnreachableCode => "unreachable_code",
+            Lint::Deprecated => "deprecated",
+        }
+    }
+}
+
+impl std::fmt::Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintLevel {
+    /// The lint's warnings are not reported
+    Allow,
+    /// The lint's warnings are reported as warnings
+    #[default]
+    Warn,
+    /// The lint's warnings are reported as errors, failing compilation
+    Deny,
+}
+
+/// The level of each lint configured for a package. Lints which aren't configured are warned about.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct LintLevels(BTreeMap<Lint, LintLevel>);
+
+impl LintLevels {
+    pub fn set(&mut self, lint: Lint, level: LintLevel) {
+        self.0.insert(lint, level);
+    }
+
+    pub fn level(&self, lint: Lint) -> LintLevel {
+        self.0.get(&lint).copied().unwrap_or_default()
+    }
+
+    /// Removes the warnings of allowed lints and turns the warnings of denied lints into errors.
+    /// Errors, and warnings which don't belong to any lint, are kept as they are.
+    pub fn apply(&self, diagnostics: Vec<FileDiagnostic>) -> Vec<FileDiagnostic> {
+        diagnostics
+            .into_iter()
+            .filter_map(|mut file_diagnostic| {
+                let diagnostic = &mut file_diagnostic.diagnostic;
+                let lint = match (diagnostic.kind, diagnostic.lint) {
+                    (DiagnosticKind::Warning, Some(lint)) => lint,
+                    _ => return Some(file_diagnostic),
+                };
+
+                match self.level(lint) {
+                    LintLevel::Allow => return None,
+                    LintLevel::Warn => (),
+                    LintLevel::Deny => {
+                        diagnostic.kind = DiagnosticKind::Error;
+                        diagnostic.add_note(format!("the `{lint}` lint is denied in Nargo.toml"));
+                    }
+                }
+                Some(file_diagnostic)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lint, LintLevel, LintLevels};
+    use crate::{CustomDiagnostic, FileDiagnostic, Span};
+
+    fn warning(lint: Option<Lint>) -> FileDiagnostic {
+        let mut diagnostic =
+            CustomDiagnostic::simple_warning("warning".into(), String::new(), Span::single_char(0));
+        diagnostic.lint = lint;
+        diagnostic.in_file(fm::FileId::dummy())
+    }
+
+    #[test]
+    fn applies_configured_levels() {
+        let mut levels = LintLevels::default();
+        levels.set(Lint::Unused, LintLevel::Deny);
+        levels.set(Lint::Deprecated, LintLevel::Allow);
+
+        let diagnostics = vec![
+            warning(Some(Lint::Unused)),
+            warning(Some(Lint::Deprecated)),
+            warning(Some(Lint::UnreachableCode)),
+            warning(None),
+        ];
+        let diagnostics = levels.apply(diagnostics);
+
+        assert_eq!(diagnostics.len(), 3);
+        assert!(diagnostics[0].diagnostic.is_error());
+        assert_eq!(diagnostics[1].diagnostic.lint, Some(Lint::UnreachableCode));
+        assert!(!diagnostics[1].diagnostic.is_error());
+        assert!(!diagnostics[2].diagnostic.is_error());
+    }
+}
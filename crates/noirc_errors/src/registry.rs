@@ -0,0 +1,96 @@
+//! The registry of stable codes assigned to each kind of diagnostic the frontend may report.
+//!
+//! Each code has an extended explanation, with examples and common fixes, which is kept in
+//! `error_codes/EXXXX.md` and printed by `nargo explain EXXXX`. Codes are never renumbered
+//! or reused once assigned, so a code which is no longer reported should keep its entry here.
+
+/// A stable code identifying a kind of diagnostic, e.g. `E0001`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ErrorCode {
+    code: &'static str,
+    explanation: &'static str,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        self.code
+    }
+
+    /// The extended explanation of this code, formatted as markdown
+    pub fn explanation(&self) -> &'static str {
+        self.explanation
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code)
+    }
+}
+
+/// Looks up a registered code. The leading `E` and any leading zeroes may be omitted,
+/// so `E0012`, `e12` and `12` all refer to the same code.
+pub fn lookup(code: &str) -> Option<ErrorCode> {
+    let code = code.trim();
+    let number = code.strip_prefix('E').or_else(|| code.strip_prefix('e')).unwrap_or(code);
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let number: u32 = number.parse().ok()?;
+    let code = format!("E{number:04}");
+    ALL_CODES.iter().find(|registered| registered.code == code).copied()
+}
+
+macro_rules! register_diagnostics {
+    ($($code:ident,)*) => {
+        $(
+            pub const $code: ErrorCode = ErrorCode {
+                code: stringify!($code),
+                explanation: include_str!(concat!("error_codes/", stringify!($code), ".md")),
+            };
+        )*
+
+        /// Every registered code, in ascending order
+        pub const ALL_CODES: &[ErrorCode] = &[$($code),*];
+    };
+}
+
+register_diagnostics! {
+    E0001, E0002, E0003, E0004, E0005, E0006, E0007, E0008, E0009, E0010,
+    E0011, E0012, E0013, E0014, E0015, E0016, E0017, E0018, E0019, E0020,
+    E0021, E0022, E0023, E0024, E0025, E0026, E0027, E0028, E0029, E0030,
+    E0031, E0032, E0033, E0034, E0035, E0036, E0037, E0038, E0039, E0040,
+    E0041, E0042, E0043, E0044, E0045, E0046, E0047, E0048, E0049, E0050,
+    E0051, E0052, E0053, E0054, E0055, E0056, E0057, E0058, E0059, E0060,
+    E0061, E0062, E0063, E0064, E0065, E0066, E0067, E0068, E0069, E0070,
+    E0071, E0072, E0073, E0074, E0075, E0076, E0077, E0078, E0079, E0080,
+    E0081, E0082, E0083, E0084, E0085, E0086, E0087, E0088, E0089, E0090,
+    E0091, E0092, E0093, E0094, E0095,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lookup, ALL_CODES, E0012};
+
+    #[test]
+    fn codes_are_ascending_and_explained() {
+        for (index, code) in ALL_CODES.iter().enumerate() {
+            assert_eq!(code.as_str(), format!("E{:04}", index + 1));
+
+            let explanation = code.explanation();
+            assert!(!explanation.trim().is_empty(), "{code} has no explanation");
+            assert!(explanation.contains("```noir"), "{code} has no example");
+        }
+    }
+
+    #[test]
+    fn lookup_normalizes_codes() {
+        assert_eq!(lookup("E0012"), Some(E0012));
+        assert_eq!(lookup("e12"), Some(E0012));
+        assert_eq!(lookup("12"), Some(E0012));
+        assert_eq!(lookup("E9999"), None);
+        assert_eq!(lookup("E"), None);
+        assert_eq!(lookup("E-1"), None);
+    }
+}
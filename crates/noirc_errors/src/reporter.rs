@@ -1,26 +1,39 @@
-use crate::{FileDiagnostic, Span};
+use crate::{ErrorCode, FileDiagnostic, Lint, Location, Span};
 use codespan_reporting::diagnostic::{Diagnostic, Label};
+use codespan_reporting::files::Files;
 use codespan_reporting::term;
 use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
+use serde::Serialize;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CustomDiagnostic {
     pub message: String,
+    /// The locations this diagnostic refers to. The first is its primary location, while the
+    /// others relate to it, such as the definitions conflicting with it.
     pub secondaries: Vec<CustomLabel>,
     notes: Vec<String>,
+    suggestions: Vec<CustomSuggestion>,
     pub kind: DiagnosticKind,
+    /// The registered code of this kind of diagnostic, if it has been assigned one
+    pub code: Option<ErrorCode>,
+    /// The lint this warning belongs to, if its level may be configured
+    pub lint: Option<Lint>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum DiagnosticKind {
     Error,
     Warning,
+    /// An internal compiler error, caused by a bug in the compiler rather than in the program
+    Bug,
 }
 
 /// A count of errors that have been already reported to stderr
 #[derive(Debug, Copy, Clone)]
 pub struct ReportedErrors {
     pub error_count: u32,
+    /// How many of the errors were internal compiler errors
+    pub bug_count: u32,
 }
 
 impl CustomDiagnostic {
@@ -29,7 +42,34 @@ impl CustomDiagnostic {
             message: msg.to_owned(),
             secondaries: Vec::new(),
             notes: Vec::new(),
+            suggestions: Vec::new(),
             kind: DiagnosticKind::Error,
+            code: None,
+            lint: None,
+        }
+    }
+
+    pub fn warning_from_message(msg: &str) -> CustomDiagnostic {
+        Self {
+            message: msg.to_owned(),
+            secondaries: Vec::new(),
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+            kind: DiagnosticKind::Warning,
+            code: None,
+            lint: None,
+        }
+    }
+
+    pub fn bug_from_message(msg: &str) -> CustomDiagnostic {
+        Self {
+            message: msg.to_owned(),
+            secondaries: Vec::new(),
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+            kind: DiagnosticKind::Bug,
+            code: None,
+            lint: None,
         }
     }
 
@@ -42,7 +82,10 @@ impl CustomDiagnostic {
             message: primary_message,
             secondaries: vec![CustomLabel::new(secondary_message, secondary_span)],
             notes: Vec::new(),
+            suggestions: Vec::new(),
             kind: DiagnosticKind::Error,
+            code: None,
+            lint: None,
         }
     }
 
@@ -55,10 +98,23 @@ impl CustomDiagnostic {
             message: primary_message,
             secondaries: vec![CustomLabel::new(secondary_message, secondary_span)],
             notes: Vec::new(),
+            suggestions: Vec::new(),
             kind: DiagnosticKind::Warning,
+            code: None,
+            lint: None,
         }
     }
 
+    pub fn with_code(mut self, code: ErrorCode) -> CustomDiagnostic {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_lint(mut self, lint: Lint) -> CustomDiagnostic {
+        self.lint = Some(lint);
+        self
+    }
+
     pub fn in_file(self, file_id: fm::FileId) -> FileDiagnostic {
         FileDiagnostic { file_id, diagnostic: self }
     }
@@ -71,8 +127,27 @@ impl CustomDiagnostic {
         self.secondaries.push(CustomLabel::new(message, span));
     }
 
+    /// Adds a label in another file than the one this diagnostic is reported in, such as the
+    /// definition of an item used by the code this diagnostic is about
+    pub fn add_secondary_in_file(&mut self, message: String, span: Span, file: fm::FileId) {
+        self.secondaries.push(CustomLabel { message, span, file: Some(file) });
+    }
+
+    /// Suggests replacing the source code within `span` with `replacement` as a fix
+    pub fn add_suggestion(&mut self, message: String, span: Span, replacement: String) {
+        self.suggestions.push(CustomSuggestion { message, span, replacement });
+    }
+
+    pub fn suggestions(&self) -> &[CustomSuggestion] {
+        &self.suggestions
+    }
+
     pub fn is_error(&self) -> bool {
-        matches!(self.kind, DiagnosticKind::Error)
+        matches!(self.kind, DiagnosticKind::Error | DiagnosticKind::Bug)
+    }
+
+    pub fn is_bug(&self) -> bool {
+        matches!(self.kind, DiagnosticKind::Bug)
     }
 }
 
@@ -88,6 +163,10 @@ impl std::fmt::Display for CustomDiagnostic {
             write!(f, "\nnote: {note}")?;
         }
 
+        for suggestion in &self.suggestions {
+            write!(f, "\nhelp: {}: `{}`", suggestion.message, suggestion.replacement)?;
+        }
+
         Ok(())
     }
 }
@@ -96,14 +175,25 @@ impl std::fmt::Display for CustomDiagnostic {
 pub struct CustomLabel {
     message: String,
     pub span: Span,
+    /// The file of the label, if it is not the file the diagnostic is reported in
+    pub file: Option<fm::FileId>,
 }
 
 impl CustomLabel {
     fn new(message: String, span: Span) -> CustomLabel {
-        CustomLabel { message, span }
+        CustomLabel { message, span, file: None }
     }
 }
 
+/// A suggested fix replacing the source code within `span`, which is exact so that editors
+/// may apply it automatically. An empty span inserts the replacement at that position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomSuggestion {
+    pub message: String,
+    pub span: Span,
+    pub replacement: String,
+}
+
 /// Writes the given diagnostics to stderr and returns the count
 /// of diagnostics that were errors.
 pub fn report_all(
@@ -115,8 +205,9 @@ pub fn report_all(
         .iter()
         .map(|error| report(files, &error.diagnostic, Some(error.file_id), deny_warnings) as u32)
         .sum();
+    let bug_count = diagnostics.iter().filter(|error| error.diagnostic.is_bug()).count() as u32;
 
-    ReportedErrors { error_count }
+    ReportedErrors { error_count, bug_count }
 }
 
 /// Report the given diagnostic, and return true if it was an error
@@ -129,34 +220,235 @@ pub fn report(
     let writer = StandardStream::stderr(ColorChoice::Always);
     let config = codespan_reporting::term::Config::default();
 
-    let diagnostic = convert_diagnostic(custom_diagnostic, file, deny_warnings);
+    let diagnostic = convert_diagnostic(files, custom_diagnostic, file, deny_warnings);
     term::emit(&mut writer.lock(), &config, files.as_simple_files(), &diagnostic).unwrap();
 
     deny_warnings || custom_diagnostic.is_error()
 }
 
+/// Formats `location` as `path:line:column`, pointing at the start of its span
+/// in the same way a diagnostic would.
+pub fn location_to_string(files: &fm::FileManager, location: Location) -> String {
+    let files = files.as_simple_files();
+    let file_id = location.file.as_usize();
+    let byte_index = location.span.start() as usize;
+
+    match (files.name(file_id), files.location(file_id, byte_index)) {
+        (Ok(name), Ok(position)) => {
+            format!("{name}:{}:{}", position.line_number, position.column_number)
+        }
+        _ => format!("<unknown file {file_id}>:{byte_index}"),
+    }
+}
+
+/// Returns the name of the file and the line number of the start of the location.
+pub fn location_to_line(files: &fm::FileManager, location: Location) -> Option<(String, usize)> {
+    location_to_line_and_column(files, location).map(|(name, line, _)| (name, line))
+}
+
+/// Returns the name of the file along with the line and column numbers of the start
+/// of the location.
+pub fn location_to_line_and_column(
+    files: &fm::FileManager,
+    location: Location,
+) -> Option<(String, usize, usize)> {
+    let files = files.as_simple_files();
+    let file_id = location.file.as_usize();
+    let byte_index = location.span.start() as usize;
+
+    let name = files.name(file_id).ok()?;
+    let position = files.location(file_id, byte_index).ok()?;
+    Some((name.to_string(), position.line_number, position.column_number))
+}
+
 fn convert_diagnostic(
+    files: &fm::FileManager,
     cd: &CustomDiagnostic,
     file: Option<fm::FileId>,
     deny_warnings: bool,
 ) -> Diagnostic<usize> {
     let diagnostic = match (cd.kind, deny_warnings) {
         (DiagnosticKind::Warning, false) => Diagnostic::warning(),
+        (DiagnosticKind::Bug, _) => Diagnostic::bug(),
         _ => Diagnostic::error(),
     };
 
     let secondary_labels = if let Some(file_id) = file {
         cd.secondaries
             .iter()
-            .map(|sl| {
+            .enumerate()
+            .map(|(index, sl)| {
+                let label_file = sl.file.unwrap_or(file_id).as_usize();
                 let start_span = sl.span.start() as usize;
                 let end_span = sl.span.end() as usize + 1;
-                Label::secondary(file_id.as_usize(), start_span..end_span).with_message(&sl.message)
+                let range = start_span..end_span;
+                let label = if index == 0 {
+                    Label::primary(label_file, range)
+                } else {
+                    Label::secondary(label_file, range)
+                };
+                label.with_message(&sl.message)
             })
             .collect()
     } else {
         vec![]
     };
 
-    diagnostic.with_message(&cd.message).with_labels(secondary_labels).with_notes(cd.notes.clone())
+    let diagnostic = match cd.code {
+        Some(code) => diagnostic.with_code(code.as_str()),
+        None => diagnostic,
+    };
+
+    let mut notes = cd.notes.clone();
+    notes.extend(cd.suggestions.iter().map(|suggestion| suggestion_note(files, file, suggestion)));
+
+    diagnostic.with_message(&cd.message).with_labels(secondary_labels).with_notes(notes)
+}
+
+/// Formats a suggestion as a note. If the suggestion lies within a single line, that line is
+/// shown with the replacement applied, otherwise only the replacement itself is shown.
+fn suggestion_note(
+    files: &fm::FileManager,
+    file: Option<fm::FileId>,
+    suggestion: &CustomSuggestion,
+) -> String {
+    match file.and_then(|file| apply_suggestion_to_line(files, file, suggestion)) {
+        Some(fixed_line) => format!("help: {}\n    {fixed_line}", suggestion.message),
+        None => format!("help: {}: `{}`", suggestion.message, suggestion.replacement),
+    }
+}
+
+fn apply_suggestion_to_line(
+    files: &fm::FileManager,
+    file: fm::FileId,
+    suggestion: &CustomSuggestion,
+) -> Option<String> {
+    let files = files.as_simple_files();
+    let source = files.source(file.as_usize()).ok()?;
+    let start = suggestion.span.start() as usize;
+    let end = suggestion.span.end() as usize;
+
+    let line_index = files.line_index(file.as_usize(), start).ok()?;
+    let line = files.line_range(file.as_usize(), line_index).ok()?;
+    if end > line.end {
+        return None;
+    }
+
+    let before = &source[line.start..start];
+    let after = &source[end..line.end];
+    let fixed_line = format!("{before}{}{after}", suggestion.replacement);
+    Some(fixed_line.trim().to_owned())
+}
+
+/// A diagnostic as it is written by `report_all_json`, for consumption by editors and other tools
+#[derive(Debug, Serialize)]
+struct JsonDiagnostic<'a> {
+    severity: &'static str,
+    code: Option<&'static str>,
+    lint: Option<&'static str>,
+    message: &'a str,
+    file: Option<String>,
+    spans: Vec<JsonSpan<'a>>,
+    notes: &'a [String],
+    suggestions: Vec<JsonSuggestion<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonSpan<'a> {
+    /// The file of the span, which may differ from the file of the diagnostic
+    file: Option<String>,
+    /// Byte offset of the start of the span
+    start: u32,
+    /// Byte offset just past the end of the span
+    end: u32,
+    line: Option<usize>,
+    column: Option<usize>,
+    label: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonSuggestion<'a> {
+    message: &'a str,
+    span: JsonSpan<'a>,
+    replacement: &'a str,
+}
+
+/// Writes the given diagnostics to stdout as JSON, one object per line, and returns the
+/// count of diagnostics that were errors. Warnings are reported with an `error` severity
+/// when `deny_warnings` is set, as they are by `report_all`.
+pub fn report_all_json(
+    files: &fm::FileManager,
+    diagnostics: &[FileDiagnostic],
+    deny_warnings: bool,
+) -> ReportedErrors {
+    let mut error_count = 0;
+    let mut bug_count = 0;
+
+    for FileDiagnostic { file_id, diagnostic } in diagnostics {
+        let is_error = deny_warnings || diagnostic.is_error();
+        error_count += is_error as u32;
+        bug_count += diagnostic.is_bug() as u32;
+
+        let json = json_diagnostic(files, diagnostic, *file_id, is_error);
+        println!("{}", serde_json::to_string(&json).expect("diagnostics are serializable"));
+    }
+
+    ReportedErrors { error_count, bug_count }
+}
+
+fn json_diagnostic<'a>(
+    files: &fm::FileManager,
+    diagnostic: &'a CustomDiagnostic,
+    file_id: fm::FileId,
+    is_error: bool,
+) -> JsonDiagnostic<'a> {
+    let simple_files = files.as_simple_files();
+    let file = simple_files.name(file_id.as_usize()).ok().map(|name| name.to_string());
+
+    let json_span = |span: Span, label: &'a str, span_file: fm::FileId| {
+        let location = simple_files.location(span_file.as_usize(), span.start() as usize).ok();
+        JsonSpan {
+            file: simple_files.name(span_file.as_usize()).ok().map(|name| name.to_string()),
+            start: span.start(),
+            end: span.end(),
+            line: location.as_ref().map(|location| location.line_number),
+            column: location.as_ref().map(|location| location.column_number),
+            label,
+        }
+    };
+
+    let spans = diagnostic
+        .secondaries
+        .iter()
+        .map(|secondary| {
+            json_span(secondary.span, &secondary.message, secondary.file.unwrap_or(file_id))
+        })
+        .collect();
+
+    let suggestions = diagnostic
+        .suggestions
+        .iter()
+        .map(|suggestion| JsonSuggestion {
+            message: &suggestion.message,
+            span: json_span(suggestion.span, "", file_id),
+            replacement: &suggestion.replacement,
+        })
+        .collect();
+
+    let severity = match (diagnostic.kind, is_error) {
+        (DiagnosticKind::Bug, _) => "bug",
+        (_, true) => "error",
+        (_, false) => "warning",
+    };
+
+    JsonDiagnostic {
+        severity,
+        code: diagnostic.code.map(|code| code.as_str()),
+        lint: diagnostic.lint.map(|lint| lint.name()),
+        message: &diagnostic.message,
+        file,
+        spans,
+        notes: &diagnostic.notes,
+        suggestions,
+    }
 }
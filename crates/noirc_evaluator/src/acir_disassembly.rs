@@ -0,0 +1,273 @@
+//! This module prints ACIR circuits in a human-readable textual format.
+//!
+//! Each opcode is printed on its own line, prefixed with its index in the circuit. Witnesses are
+//! written `_N` and expressions are written as sums of terms, so that the text of a circuit only
+//! depends on its opcodes and two builds of a program can be compared line by line. The bytecode
+//! of Brillig calls is summarized by its length, as it is printed by the Brillig disassembler.
+use acvm::{
+    acir::{
+        circuit::{
+            brillig::{Brillig, BrilligInputs, BrilligOutputs},
+            directives::{Directive, LogInfo, QuotientDirective},
+            opcodes::{BlackBoxFuncCall, FunctionInput, MemOp, MemoryBlock},
+            Circuit, Opcode,
+        },
+        native_types::{Expression, Witness},
+    },
+    FieldElement,
+};
+use iter_extended::vecmap;
+
+/// Prints the opcodes of a circuit along with its public inputs and return values.
+pub fn disassemble_acir(circuit: &Circuit) -> String {
+    let mut text = String::new();
+    text.push_str(&format!(
+        "current witness index: {}\n",
+        witness_to_string(&Witness(circuit.current_witness_index))
+    ));
+    text.push_str(&format!(
+        "public parameters: {}\n",
+        witnesses_to_string(circuit.public_parameters.0.iter())
+    ));
+    text.push_str(&format!(
+        "return values: {}\n",
+        witnesses_to_string(circuit.return_values.0.iter())
+    ));
+    for (index, opcode) in circuit.opcodes.iter().enumerate() {
+        text.push_str(&format!("{index}: {}\n", opcode_to_string(opcode)));
+    }
+    text
+}
+
+fn opcode_to_string(opcode: &Opcode) -> String {
+    match opcode {
+        Opcode::Arithmetic(expression) => {
+            format!("ASSERT {} = 0", expression_to_string(expression))
+        }
+        Opcode::BlackBoxFuncCall(call) => black_box_call_to_string(call),
+        Opcode::Directive(directive) => format!("DIR {}", directive_to_string(directive)),
+        Opcode::Brillig(brillig) => brillig_to_string(brillig),
+        Opcode::Block(block) => memory_block_to_string("BLOCK", block),
+        Opcode::ROM(block) => memory_block_to_string("ROM", block),
+        Opcode::RAM(block) => memory_block_to_string("RAM", block),
+    }
+}
+
+fn black_box_call_to_string(call: &BlackBoxFuncCall) -> String {
+    if let BlackBoxFuncCall::RANGE { input } = call {
+        return format!("RANGE {} TO {} BITS", witness_to_string(&input.witness), input.num_bits);
+    }
+    let inputs = vecmap(call.get_inputs_vec(), |input| function_input_to_string(&input));
+    format!(
+        "BLACKBOX {} [{}] -> {}",
+        call.get_black_box_func().name(),
+        inputs.join(", "),
+        witnesses_to_string(call.get_outputs_vec().iter())
+    )
+}
+
+fn directive_to_string(directive: &Directive) -> String {
+    match directive {
+        Directive::Invert { x, result } => {
+            format!("INVERT {} -> {}", witness_to_string(x), witness_to_string(result))
+        }
+        Directive::Quotient(QuotientDirective { a, b, q, r, predicate }) => {
+            let predicate = predicate
+                .as_ref()
+                .map(|predicate| format!(" IF {}", expression_to_string(predicate)))
+                .unwrap_or_default();
+            format!(
+                "QUOTIENT ({}) / ({}) -> q: {}, r: {}{predicate}",
+                expression_to_string(a),
+                expression_to_string(b),
+                witness_to_string(q),
+                witness_to_string(r)
+            )
+        }
+        Directive::ToLeRadix { a, b, radix } => format!(
+            "TO_LE_RADIX ({}) RADIX {radix} -> {}",
+            expression_to_string(a),
+            witnesses_to_string(b.iter())
+        ),
+        Directive::PermutationSort { inputs, tuple, bits, sort_by } => {
+            let inputs = vecmap(inputs, |tuple| {
+                let elements = vecmap(tuple, expression_to_string);
+                format!("({})", elements.join(", "))
+            });
+            format!(
+                "PERMUTATION_SORT [{}] TUPLE {tuple} SORT_BY {sort_by:?} -> {}",
+                inputs.join(", "),
+                witnesses_to_string(bits.iter())
+            )
+        }
+        Directive::Log(LogInfo::FinalizedOutput(output)) => format!("LOG {output:?}"),
+        Directive::Log(LogInfo::WitnessOutput(witnesses)) => {
+            format!("LOG {}", witnesses_to_string(witnesses.iter()))
+        }
+    }
+}
+
+fn brillig_to_string(brillig: &Brillig) -> String {
+    let inputs = vecmap(&brillig.inputs, |input| match input {
+        BrilligInputs::Single(expression) => expression_to_string(expression),
+        BrilligInputs::Array(expressions) => {
+            format!("[{}]", vecmap(expressions, expression_to_string).join(", "))
+        }
+    });
+    let outputs = vecmap(&brillig.outputs, |output| match output {
+        BrilligOutputs::Simple(witness) => witness_to_string(witness),
+        BrilligOutputs::Array(witnesses) => witnesses_to_string(witnesses.iter()),
+    });
+    let predicate = brillig
+        .predicate
+        .as_ref()
+        .map(|predicate| format!(" IF {}", expression_to_string(predicate)))
+        .unwrap_or_default();
+    format!(
+        "BRILLIG ({} opcodes) [{}] -> [{}]{predicate}",
+        brillig.bytecode.len(),
+        inputs.join(", "),
+        outputs.join(", ")
+    )
+}
+
+/// Prints a memory block opcode followed by its operations, one per line.
+fn memory_block_to_string(kind: &str, block: &MemoryBlock) -> String {
+    let mut text = format!("{kind} {} LEN {}", block.id.0, block.len);
+    for MemOp { operation, index, value } in &block.trace {
+        let (operation, index, value) = (
+            expression_to_string(operation),
+            expression_to_string(index),
+            expression_to_string(value),
+        );
+        let line = match operation.as_str() {
+            "0" => format!("READ {value} AT {index}"),
+            "1" => format!("WRITE {value} AT {index}"),
+            _ => format!("OP ({operation}) {value} AT {index}"),
+        };
+        text.push_str(&format!("\n    {line}"));
+    }
+    text
+}
+
+/// Prints an expression as a sum of terms, the constant term coming last.
+fn expression_to_string(expression: &Expression) -> String {
+    let mut terms = Vec::new();
+    for (coefficient, lhs, rhs) in &expression.mul_terms {
+        let witnesses = format!("{}*{}", witness_to_string(lhs), witness_to_string(rhs));
+        terms.push(term_to_string(*coefficient, Some(witnesses)));
+    }
+    for (coefficient, witness) in &expression.linear_combinations {
+        terms.push(term_to_string(*coefficient, Some(witness_to_string(witness))));
+    }
+    if !expression.q_c.is_zero() || terms.is_empty() {
+        terms.push(term_to_string(expression.q_c, None));
+    }
+
+    let mut text = String::new();
+    for (index, (is_negative, term)) in terms.into_iter().enumerate() {
+        match (index, is_negative) {
+            (0, false) => text.push_str(&term),
+            (0, true) => text.push_str(&format!("-{term}")),
+            (_, false) => text.push_str(&format!(" + {term}")),
+            (_, true) => text.push_str(&format!(" - {term}")),
+        }
+    }
+    text
+}
+
+/// Prints the absolute value of a term and returns whether it is negative. Coefficients which
+/// are closer to the field modulus than to zero are considered negative.
+fn term_to_string(coefficient: FieldElement, witnesses: Option<String>) -> (bool, String) {
+    let is_negative = (-coefficient).num_bits() < coefficient.num_bits();
+    let magnitude = if is_negative { -coefficient } else { coefficient };
+    let text = match witnesses {
+        Some(witnesses) if magnitude.is_one() => witnesses,
+        Some(witnesses) => format!("{}*{witnesses}", field_to_string(magnitude)),
+        None => field_to_string(magnitude),
+    };
+    (is_negative, text)
+}
+
+fn field_to_string(field: FieldElement) -> String {
+    if field.num_bits() <= 128 {
+        field.to_u128().to_string()
+    } else {
+        format!("0x{}", field.to_hex())
+    }
+}
+
+fn function_input_to_string(input: &FunctionInput) -> String {
+    format!("{}:{}", witness_to_string(&input.witness), input.num_bits)
+}
+
+fn witness_to_string(witness: &Witness) -> String {
+    format!("_{}", witness.witness_index())
+}
+
+fn witnesses_to_string<'a>(witnesses: impl Iterator<Item = &'a Witness>) -> String {
+    format!("[{}]", witnesses.map(witness_to_string).collect::<Vec<_>>().join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use acvm::{
+        acir::{
+            circuit::{
+                opcodes::{BlackBoxFuncCall, BlockId, FunctionInput, MemOp, MemoryBlock},
+                Circuit, Opcode, PublicInputs,
+            },
+            native_types::{Expression, Witness},
+        },
+        FieldElement,
+    };
+
+    use super::disassemble_acir;
+
+    #[test]
+    fn disassembles_circuit() {
+        // 2*_1*_2 - _3 + 5 = 0
+        let mut expression = Expression::default();
+        expression.push_multiplication_term(FieldElement::from(2_i128), Witness(1), Witness(2));
+        expression.push_addition_term(-FieldElement::one(), Witness(3));
+        expression.q_c = FieldElement::from(5_i128);
+
+        let range = FunctionInput { witness: Witness(3), num_bits: 8 };
+        let trace = vec![
+            MemOp {
+                operation: Expression::one(),
+                index: Expression::from(Witness(1)),
+                value: Expression::from(Witness(2)),
+            },
+            MemOp {
+                operation: Expression::zero(),
+                index: Expression::from(Witness(1)),
+                value: Expression::from(Witness(4)),
+            },
+        ];
+        let circuit = Circuit {
+            current_witness_index: 4,
+            opcodes: vec![
+                Opcode::Arithmetic(expression),
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input: range }),
+                Opcode::RAM(MemoryBlock { id: BlockId(0), len: 1, trace }),
+            ],
+            public_parameters: PublicInputs(BTreeSet::from([Witness(1)])),
+            return_values: PublicInputs(BTreeSet::from([Witness(4)])),
+        };
+
+        let expected = "\
+current witness index: _4
+public parameters: [_1]
+return values: [_4]
+0: ASSERT 2*_1*_2 - _3 + 5 = 0
+1: RANGE _3 TO 8 BITS
+2: RAM 0 LEN 1
+    WRITE _2 AT _1
+    READ _4 AT _1
+";
+        assert_eq!(disassemble_acir(&circuit), expected);
+    }
+}
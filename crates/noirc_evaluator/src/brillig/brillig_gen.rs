@@ -2,31 +2,52 @@ pub(crate) mod brillig_black_box;
 pub(crate) mod brillig_block;
 pub(crate) mod brillig_directive;
 pub(crate) mod brillig_fn;
+pub(crate) mod heap_reuse;
+pub(crate) mod variable_liveness;
 
 use crate::ssa_refactor::ir::{function::Function, post_order::PostOrder};
 
 use std::collections::HashMap;
 
-use self::{brillig_block::BrilligBlock, brillig_fn::FunctionContext};
+use self::{
+    brillig_block::BrilligBlock, brillig_fn::FunctionContext, heap_reuse::unaliased_arrays,
+    variable_liveness::VariableLiveness,
+};
 
-use super::brillig_ir::{artifact::BrilligArtifact, BrilligContext};
+use super::brillig_ir::{artifact::BrilligArtifact, debug_show::DebugShow, BrilligContext};
 
 /// Converting an SSA function into Brillig bytecode.
 ///
 /// TODO: Change this to use `dfg.basic_blocks_iter` which will return an
 /// TODO iterator of all of the basic blocks.
 /// TODO(Jake): what order is this ^
-pub(crate) fn convert_ssa_function(func: &Function) -> BrilligArtifact {
+///
+/// Alongside the artifact, this returns the debug trace of the generated
+/// bytecode, which is empty unless `enable_debug_trace` is set.
+pub(crate) fn convert_ssa_function(
+    func: &Function,
+    enable_debug_trace: bool,
+) -> (BrilligArtifact, DebugShow) {
     let mut reverse_post_order = Vec::new();
     reverse_post_order.extend_from_slice(PostOrder::with_function(func).as_slice());
     reverse_post_order.reverse();
 
-    let mut function_context =
-        FunctionContext { function_id: func.id(), ssa_value_to_register: HashMap::new() };
+    let mut function_context = FunctionContext {
+        function_id: func.id(),
+        ssa_value_to_register: HashMap::new(),
+        ssa_value_names: if enable_debug_trace {
+            FunctionContext::value_names(func)
+        } else {
+            HashMap::new()
+        },
+        liveness: VariableLiveness::from_function(func),
+        unaliased_arrays: unaliased_arrays(func),
+    };
 
     let mut brillig_context = BrilligContext::new(
         FunctionContext::parameters(func),
         FunctionContext::return_values(func),
+        enable_debug_trace,
     );
 
     brillig_context.enter_context(FunctionContext::function_id_to_function_label(func.id()));
@@ -34,5 +55,6 @@ pub(crate) fn convert_ssa_function(func: &Function) -> BrilligArtifact {
         BrilligBlock::compile(&mut function_context, &mut brillig_context, block, &func.dfg);
     }
 
-    brillig_context.artifact()
+    let debug_show = brillig_context.take_debug_show();
+    (brillig_context.artifact(), debug_show)
 }
@@ -5,6 +5,16 @@ use acvm::acir::{
 
 use crate::brillig::brillig_ir::BrilligContext;
 
+/// The prefix of the foreign calls through which unconstrained functions call the black box
+/// functions the Brillig VM has no opcodes for. `::` cannot appear in the name of an oracle, so
+/// these foreign calls are never mistaken for a user's oracle of the same name.
+pub const BLACK_BOX_FOREIGN_CALL_PREFIX: &str = "black_box::";
+
+/// The name of the foreign call delegating the given black box function to the executor
+fn black_box_foreign_call_name(bb_func: &BlackBoxFunc) -> String {
+    format!("{BLACK_BOX_FOREIGN_CALL_PREFIX}{}", bb_func.name())
+}
+
 /// Transforms SSA's black box function calls into the corresponding brillig instructions
 /// Extracting arguments and results from the SSA function call
 /// And making any necessary type conversions to adapt noir's blackbox calls to brillig's
@@ -96,6 +106,54 @@ pub(crate) fn convert_black_box_call(
                 )
             }
         }
+        // The Brillig VM has no opcodes for these black box functions, so they are
+        // delegated to the executor as foreign calls named after the black box function under
+        // a reserved prefix.
+        BlackBoxFunc::Pedersen => {
+            if let (
+                [RegisterOrMemory::HeapArray(_), RegisterOrMemory::RegisterIndex(_)],
+                [RegisterOrMemory::HeapArray(_)],
+            ) = (function_arguments, function_results)
+            {
+                brillig_context.foreign_call_instruction(
+                    black_box_foreign_call_name(bb_func),
+                    function_arguments,
+                    function_results,
+                );
+            } else {
+                unreachable!("ICE: Pedersen expects one array argument, a domain separator and one array result")
+            }
+        }
+        BlackBoxFunc::SchnorrVerify => {
+            if let (
+                [RegisterOrMemory::RegisterIndex(_), RegisterOrMemory::RegisterIndex(_), RegisterOrMemory::HeapArray(_), RegisterOrMemory::HeapArray(_)],
+                [RegisterOrMemory::RegisterIndex(_)],
+            ) = (function_arguments, function_results)
+            {
+                brillig_context.foreign_call_instruction(
+                    black_box_foreign_call_name(bb_func),
+                    function_arguments,
+                    function_results,
+                );
+            } else {
+                unreachable!("ICE: SchnorrVerify expects two register and two array arguments, and one register result")
+            }
+        }
+        BlackBoxFunc::FixedBaseScalarMul => {
+            if let ([RegisterOrMemory::RegisterIndex(_)], [RegisterOrMemory::HeapArray(_)]) =
+                (function_arguments, function_results)
+            {
+                brillig_context.foreign_call_instruction(
+                    black_box_foreign_call_name(bb_func),
+                    function_arguments,
+                    function_results,
+                );
+            } else {
+                unreachable!(
+                    "ICE: FixedBaseScalarMul expects one register argument and one array result"
+                )
+            }
+        }
         _ => unimplemented!("ICE: Black box function {:?} is not implemented", bb_func),
     }
 }
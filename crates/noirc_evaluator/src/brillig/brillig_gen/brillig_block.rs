@@ -3,7 +3,6 @@ use crate::brillig::brillig_ir::{
 };
 use crate::ssa_refactor::ir::function::FunctionId;
 use crate::ssa_refactor::ir::instruction::Intrinsic;
-use crate::ssa_refactor::ir::types::CompositeType;
 use crate::ssa_refactor::ir::{
     basic_block::{BasicBlock, BasicBlockId},
     dfg::DataFlowGraph,
@@ -12,7 +11,7 @@ use crate::ssa_refactor::ir::{
     value::{Value, ValueId},
 };
 use acvm::acir::brillig_vm::{
-    BinaryFieldOp, BinaryIntOp, HeapArray, RegisterIndex, RegisterOrMemory,
+    BinaryFieldOp, BinaryIntOp, HeapArray, HeapVector, RegisterIndex, RegisterOrMemory,
 };
 use acvm::FieldElement;
 use iter_extended::vecmap;
@@ -54,12 +53,21 @@ impl<'block> BrilligBlock<'block> {
         // Convert all of the instructions into the block
         for instruction_id in block.instructions() {
             self.convert_ssa_instruction(*instruction_id, dfg);
+
+            // Free the registers of the values which are no longer needed
+            let dead_values =
+                self.function_context.liveness.dead_after_instruction(*instruction_id).to_vec();
+            self.function_context.deallocate_registers(self.brillig_context, &dead_values);
         }
 
         // Process the block's terminator instruction
         let terminator_instruction =
             block.terminator().expect("block is expected to be constructed");
         self.convert_ssa_terminator(terminator_instruction, dfg);
+
+        let dead_values =
+            self.function_context.liveness.dead_after_terminator(self.block_id).to_vec();
+        self.function_context.deallocate_registers(self.brillig_context, &dead_values);
     }
 
     fn get_bit_size_from_ssa_type(typ: Type) -> u32 {
@@ -142,7 +150,7 @@ impl<'block> BrilligBlock<'block> {
                 // Simple parameters and arrays are passed as already filled registers
                 // In the case of arrays, the values should already be in memory and the register should
                 // Be a valid pointer to the array.
-                Type::Numeric(_) | Type::Array(..) => {
+                Type::Numeric(_) | Type::Array(..) | Type::Slice(..) => {
                     self.function_context.get_or_create_register(self.brillig_context, *param_id);
                 }
                 _ => {
@@ -155,6 +163,9 @@ impl<'block> BrilligBlock<'block> {
     /// Converts an SSA instruction into a sequence of Brillig opcodes.
     fn convert_ssa_instruction(&mut self, instruction_id: InstructionId, dfg: &DataFlowGraph) {
         let instruction = &dfg[instruction_id];
+        if let Some(location) = dfg.get_location(&instruction_id) {
+            self.brillig_context.set_location(location);
+        }
 
         match instruction {
             Instruction::Binary(binary) => {
@@ -231,6 +242,8 @@ impl<'block> BrilligBlock<'block> {
 
                     // Important: resolve after pre_call_save_registers_prep_args
                     // This ensures we don't save the results to registers unnecessarily.
+                    let return_value_registers =
+                        self.brillig_context.allocate_free_return_value_registers(result_ids.len());
                     let result_registers = vecmap(result_ids, |a| {
                         self.function_context.get_or_create_register(self.brillig_context, *a)
                     });
@@ -240,6 +253,9 @@ impl<'block> BrilligBlock<'block> {
                     );
                     self.brillig_context
                         .post_call_prep_returns_load_registers(&result_registers, &saved_registers);
+                    for register in return_value_registers {
+                        self.brillig_context.deallocate_register(register);
+                    }
                 }
                 Value::Intrinsic(Intrinsic::BlackBox(bb_func)) => {
                     let function_arguments = vecmap(arguments, |arg| {
@@ -256,6 +272,28 @@ impl<'block> BrilligBlock<'block> {
                         &function_results,
                     );
                 }
+                Value::Intrinsic(intrinsic @ (Intrinsic::Println | Intrinsic::AssertMessage)) => {
+                    let function = match intrinsic {
+                        Intrinsic::Println => "print",
+                        _ => "assert_message",
+                    };
+                    let input_registers = vecmap(arguments, |value_id| {
+                        self.convert_ssa_value_to_register_value_or_array(*value_id, dfg)
+                    });
+                    self.brillig_context.foreign_call_instruction(
+                        function.to_owned(),
+                        &input_registers,
+                        &[],
+                    );
+                }
+                Value::Intrinsic(
+                    intrinsic @ (Intrinsic::SlicePushBack
+                    | Intrinsic::SlicePushFront
+                    | Intrinsic::SlicePopBack
+                    | Intrinsic::SlicePopFront),
+                ) => {
+                    self.convert_slice_intrinsic_call(*intrinsic, instruction_id, arguments, dfg);
+                }
                 _ => {
                     unreachable!("unsupported function call type {:?}", dfg[*func])
                 }
@@ -283,7 +321,13 @@ impl<'block> BrilligBlock<'block> {
                     .get_or_create_register(self.brillig_context, result_ids[0]);
                 let array_register = self.convert_ssa_value(*array, dfg);
                 let index_register = self.convert_ssa_value(*index, dfg);
-                self.brillig_context.array_get(array_register, index_register, destination);
+                if let Type::Slice(_) = dfg.type_of_value(*array) {
+                    let elements = self.slice_elements_pointer(array_register);
+                    self.brillig_context.array_get(elements, index_register, destination);
+                    self.brillig_context.deallocate_register(elements);
+                } else {
+                    self.brillig_context.array_get(array_register, index_register, destination);
+                }
             }
             // Array set operation in SSA returns a new array that is a copy of the parameter array
             // With a specific value changed.
@@ -292,27 +336,48 @@ impl<'block> BrilligBlock<'block> {
                 let destination = self
                     .function_context
                     .get_or_create_register(self.brillig_context, result_ids[0]);
-
-                // First issue a array copy to the destination
-                let array_size = compute_size_of_type(&dfg.type_of_value(*array));
-                self.brillig_context.allocate_fixed_length_array(destination, array_size);
                 let source_array_register: RegisterIndex = self.convert_ssa_value(*array, dfg);
-                let size_register = self.brillig_context.make_constant(array_size.into());
-                self.brillig_context.copy_array_instruction(
-                    source_array_register,
-                    destination,
-                    size_register,
-                );
-
-                // Then set the value in the newly created array
                 let index_register = self.convert_ssa_value(*index, dfg);
                 let value_register = self.convert_ssa_value(*value, dfg);
-                self.brillig_context.array_set(destination, index_register, value_register);
+
+                if let Type::Slice(_) = dfg.type_of_value(*array) {
+                    // Slices are always copied, as their size is only known at runtime
+                    self.copy_slice(source_array_register, destination);
+                    let elements = self.slice_elements_pointer(destination);
+                    self.brillig_context.array_set(elements, index_register, value_register);
+                    self.brillig_context.deallocate_register(elements);
+                } else {
+                    if self.can_set_in_place(instruction_id, *array) {
+                        // The source array is no longer used, so its memory is reused by the new
+                        // array rather than freed along with its register.
+                        self.function_context.unaliased_arrays.remove(array);
+                        self.brillig_context.mov_instruction(destination, source_array_register);
+                    } else {
+                        // First issue a array copy to the destination
+                        let array_size = compute_size_of_type(&dfg.type_of_value(*array));
+                        self.brillig_context.allocate_fixed_length_array(destination, array_size);
+                        self.brillig_context.copy_fixed_length_array_instruction(
+                            source_array_register,
+                            destination,
+                            array_size,
+                        );
+                    }
+
+                    // Then set the value in the newly created array
+                    self.brillig_context.array_set(destination, index_register, value_register);
+                }
             }
             _ => todo!("ICE: Instruction not supported {instruction:?}"),
         };
     }
 
+    /// True if the array set by the given instruction can be modified instead of copied,
+    /// as nothing else points to it and it is not used afterwards.
+    fn can_set_in_place(&self, instruction_id: InstructionId, array: ValueId) -> bool {
+        let dead_values = self.function_context.liveness.dead_after_instruction(instruction_id);
+        self.function_context.unaliased_arrays.contains_key(&array) && dead_values.contains(&array)
+    }
+
     /// This function allows storing a Value in memory starting at the address specified by the
     /// address_register. The value can be a single value or an array. The function will recursively
     /// store the value in memory.
@@ -328,37 +393,40 @@ impl<'block> BrilligBlock<'block> {
                 let value_register = self.convert_ssa_value(value_id, dfg);
                 self.brillig_context.store_instruction(address_register, value_register);
             }
-            Value::Array { array, element_type } => {
+            Value::Array { array, typ } => {
                 // Allocate a register for the iterator
                 let iterator_register = self.brillig_context.allocate_register();
                 // Set the iterator to the address of the array
                 self.brillig_context.mov_instruction(iterator_register, address_register);
 
-                let size_of_item_register = self
-                    .brillig_context
-                    .make_constant(compute_size_of_composite_type(element_type).into());
+                if let Type::Slice(_) = typ {
+                    // Slices start with the number of cells taken by their contents
+                    let num_cells_register = self.brillig_context.make_constant(array.len().into());
+                    self.brillig_context.store_instruction(iterator_register, num_cells_register);
+                    self.brillig_context.deallocate_register(num_cells_register);
+                    self.brillig_context.usize_op(iterator_register, BinaryIntOp::Add, 1);
+                }
 
                 for element_id in array.iter() {
                     // Store the item in memory
                     self.store_in_memory(iterator_register, *element_id, dfg);
-                    // Increment the iterator by the size of the items
-                    self.brillig_context.binary_instruction(
-                        iterator_register,
-                        size_of_item_register,
+                    // Increment the iterator by the size of the item
+                    let size_of_item = compute_size_of_type(&dfg.type_of_value(*element_id));
+                    self.brillig_context.usize_op(
                         iterator_register,
-                        BrilligBinaryOp::Integer {
-                            op: BinaryIntOp::Add,
-                            bit_size: BRILLIG_MEMORY_ADDRESSING_BIT_SIZE,
-                        },
+                        BinaryIntOp::Add,
+                        size_of_item,
                     );
                 }
+                self.brillig_context.deallocate_register(iterator_register);
             }
             _ => unimplemented!("ICE: Value {:?} not storeable in memory", value),
         }
     }
 
     /// Converts an SSA cast to a sequence of Brillig opcodes.
-    /// Casting is only necessary when shrinking the bit size of a numeric value.
+    /// Casting is only necessary when shrinking the bit size of a numeric value,
+    /// or when widening a signed integer which needs to be sign-extended.
     fn convert_cast(
         &mut self,
         destination: RegisterIndex,
@@ -382,6 +450,21 @@ impl<'block> BrilligBlock<'block> {
         };
         let source_bit_size = numeric_to_bit_size(source_numeric_type);
         let target_bit_size = numeric_to_bit_size(target_numeric_type);
+        if matches!(source_numeric_type, NumericType::Signed { .. })
+            && source_bit_size < target_bit_size
+        {
+            let target_bit_size = match target_numeric_type {
+                NumericType::NativeField => None,
+                _ => Some(target_bit_size),
+            };
+            self.brillig_context.sign_extend_instruction(
+                destination,
+                source,
+                source_bit_size,
+                target_bit_size,
+            );
+            return;
+        }
         // Casting from a larger bit size to a smaller bit size (narrowing cast)
         // requires a cast instruction.
         // If its a widening cast, ie casting from a smaller bit size to a larger bit size
@@ -434,12 +517,13 @@ impl<'block> BrilligBlock<'block> {
                 self.brillig_context.const_instruction(register_index, (*constant).into());
                 register_index
             }
-            Value::Array { .. } => {
+            Value::Array { array, typ } => {
+                let size = match typ {
+                    Type::Slice(_) => array.len() + 1,
+                    typ => compute_size_of_type(typ),
+                };
                 let address_register = self.brillig_context.allocate_register();
-                self.brillig_context.allocate_fixed_length_array(
-                    address_register,
-                    compute_size_of_type(&dfg.type_of_value(value_id)),
-                );
+                self.brillig_context.allocate_fixed_length_array(address_register, size);
                 self.store_in_memory(address_register, value_id, dfg);
                 address_register
             }
@@ -462,12 +546,210 @@ impl<'block> BrilligBlock<'block> {
             Type::Array(_, size) => {
                 RegisterOrMemory::HeapArray(HeapArray { pointer: register_index, size })
             }
+            Type::Slice(_) => {
+                let size = self.brillig_context.allocate_register();
+                self.brillig_context.load_instruction(size, register_index);
+                let pointer = self.slice_elements_pointer(register_index);
+                RegisterOrMemory::HeapVector(HeapVector { pointer, size })
+            }
             _ => {
                 unreachable!("type not supported for conversion into brillig register")
             }
         }
     }
 
+    /// Converts a call to one of the slice intrinsics into a sequence of Brillig opcodes.
+    ///
+    /// The first two arguments of each slice intrinsic are the length and the contents of the
+    /// slice. Pushing to or popping from a slice copies its contents into a new allocation, and
+    /// popping from an empty slice traps.
+    fn convert_slice_intrinsic_call(
+        &mut self,
+        intrinsic: Intrinsic,
+        instruction_id: InstructionId,
+        arguments: &[ValueId],
+        dfg: &DataFlowGraph,
+    ) {
+        let element_size = dfg.type_of_value(arguments[1]).element_size();
+        let length = self.convert_ssa_value(arguments[0], dfg);
+        let slice = self.convert_ssa_value(arguments[1], dfg);
+        let results = vecmap(dfg.instruction_results(instruction_id), |result| {
+            self.function_context.get_or_create_register(self.brillig_context, *result)
+        });
+
+        let num_cells = self.brillig_context.allocate_register();
+        self.brillig_context.mov_instruction(num_cells, length);
+        self.brillig_context.usize_op(num_cells, BinaryIntOp::Mul, element_size);
+        let elements = self.slice_elements_pointer(slice);
+
+        match intrinsic {
+            Intrinsic::SlicePushBack | Intrinsic::SlicePushFront => {
+                let (new_length, new_slice) = (results[0], results[1]);
+                self.length_op(length, BinaryFieldOp::Add, new_length);
+
+                let new_num_cells = self.brillig_context.allocate_register();
+                self.brillig_context.mov_instruction(new_num_cells, num_cells);
+                self.brillig_context.usize_op(new_num_cells, BinaryIntOp::Add, element_size);
+                self.allocate_slice(new_slice, new_num_cells);
+                self.brillig_context.deallocate_register(new_num_cells);
+
+                // The pushed element goes either after or before the current contents
+                let new_elements = self.slice_elements_pointer(new_slice);
+                let (contents_offset, element_offset) = if intrinsic == Intrinsic::SlicePushBack {
+                    (0, None)
+                } else {
+                    (element_size, Some(0))
+                };
+                let destination = self.brillig_context.allocate_register();
+                self.brillig_context.mov_instruction(destination, new_elements);
+                self.brillig_context.usize_op(destination, BinaryIntOp::Add, contents_offset);
+                self.brillig_context.copy_array_instruction(elements, destination, num_cells);
+
+                match element_offset {
+                    Some(offset) => {
+                        self.brillig_context.mov_instruction(destination, new_elements);
+                        self.brillig_context.usize_op(destination, BinaryIntOp::Add, offset);
+                    }
+                    None => {
+                        self.brillig_context.binary_instruction(
+                            new_elements,
+                            num_cells,
+                            destination,
+                            BrilligBinaryOp::Integer {
+                                op: BinaryIntOp::Add,
+                                bit_size: BRILLIG_MEMORY_ADDRESSING_BIT_SIZE,
+                            },
+                        );
+                    }
+                }
+                for argument in &arguments[2..] {
+                    let value = self.convert_ssa_value(*argument, dfg);
+                    self.brillig_context.store_instruction(destination, value);
+                    self.brillig_context.usize_op(destination, BinaryIntOp::Add, 1);
+                }
+
+                self.brillig_context.deallocate_register(destination);
+                self.brillig_context.deallocate_register(new_elements);
+            }
+            Intrinsic::SlicePopBack => {
+                let (new_length, new_slice) = (results[0], results[1]);
+                self.assert_slice_is_not_empty(length);
+                self.length_op(length, BinaryFieldOp::Sub, new_length);
+
+                let new_num_cells = self.brillig_context.allocate_register();
+                self.brillig_context.mov_instruction(new_num_cells, num_cells);
+                self.brillig_context.usize_op(new_num_cells, BinaryIntOp::Sub, element_size);
+                self.allocate_slice(new_slice, new_num_cells);
+                let new_elements = self.slice_elements_pointer(new_slice);
+                self.brillig_context.copy_array_instruction(elements, new_elements, new_num_cells);
+                self.brillig_context.deallocate_register(new_elements);
+                self.brillig_context.deallocate_register(new_num_cells);
+
+                // The popped element takes the last cells of the contents
+                let source = self.brillig_context.allocate_register();
+                self.brillig_context.binary_instruction(
+                    elements,
+                    num_cells,
+                    source,
+                    BrilligBinaryOp::Integer {
+                        op: BinaryIntOp::Add,
+                        bit_size: BRILLIG_MEMORY_ADDRESSING_BIT_SIZE,
+                    },
+                );
+                self.brillig_context.usize_op(source, BinaryIntOp::Sub, element_size);
+                for result in &results[2..] {
+                    self.brillig_context.load_instruction(*result, source);
+                    self.brillig_context.usize_op(source, BinaryIntOp::Add, 1);
+                }
+                self.brillig_context.deallocate_register(source);
+            }
+            Intrinsic::SlicePopFront => {
+                let (new_length, new_slice) = (results[element_size], results[element_size + 1]);
+                self.assert_slice_is_not_empty(length);
+
+                // The popped element takes the first cells of the contents
+                let source = self.brillig_context.allocate_register();
+                self.brillig_context.mov_instruction(source, elements);
+                for result in &results[..element_size] {
+                    self.brillig_context.load_instruction(*result, source);
+                    self.brillig_context.usize_op(source, BinaryIntOp::Add, 1);
+                }
+
+                self.length_op(length, BinaryFieldOp::Sub, new_length);
+                self.brillig_context.usize_op(num_cells, BinaryIntOp::Sub, element_size);
+                self.allocate_slice(new_slice, num_cells);
+                let new_elements = self.slice_elements_pointer(new_slice);
+                self.brillig_context.copy_array_instruction(source, new_elements, num_cells);
+
+                self.brillig_context.deallocate_register(new_elements);
+                self.brillig_context.deallocate_register(source);
+            }
+            _ => unreachable!("Expected a slice intrinsic, found {intrinsic}"),
+        }
+
+        self.brillig_context.deallocate_register(elements);
+        self.brillig_context.deallocate_register(num_cells);
+    }
+
+    /// Traps if the given length of a slice is zero, as nothing can be popped from it.
+    fn assert_slice_is_not_empty(&mut self, length: RegisterIndex) {
+        let zero = self.brillig_context.make_constant(FieldElement::zero().into());
+        let is_not_empty = self.brillig_context.allocate_register();
+        self.brillig_context.binary_instruction(
+            length,
+            zero,
+            is_not_empty,
+            BrilligBinaryOp::Field { op: BinaryFieldOp::Equals },
+        );
+        self.brillig_context.not_instruction(is_not_empty, 1, is_not_empty);
+        self.brillig_context.constrain_instruction(is_not_empty);
+        self.brillig_context.deallocate_register(is_not_empty);
+        self.brillig_context.deallocate_register(zero);
+    }
+
+    /// Adds one to or subtracts one from the length of a slice.
+    fn length_op(&mut self, length: RegisterIndex, op: BinaryFieldOp, result: RegisterIndex) {
+        let one = self.brillig_context.make_constant(FieldElement::one().into());
+        self.brillig_context.binary_instruction(length, one, result, BrilligBinaryOp::Field { op });
+        self.brillig_context.deallocate_register(one);
+    }
+
+    /// Allocates a slice whose contents take `num_cells` memory cells.
+    ///
+    /// As the size of a slice is only known at runtime, the first cell of its allocation
+    /// holds the number of cells taken by its contents, which follow it.
+    fn allocate_slice(&mut self, pointer: RegisterIndex, num_cells: RegisterIndex) {
+        let size = self.brillig_context.allocate_register();
+        self.brillig_context.mov_instruction(size, num_cells);
+        self.brillig_context.usize_op(size, BinaryIntOp::Add, 1);
+        self.brillig_context.allocate_array_instruction(pointer, size);
+        self.brillig_context.store_instruction(pointer, num_cells);
+        self.brillig_context.deallocate_register(size);
+    }
+
+    /// Copies the given slice into a new allocation pointed by `destination`.
+    fn copy_slice(&mut self, source: RegisterIndex, destination: RegisterIndex) {
+        let num_cells = self.brillig_context.allocate_register();
+        self.brillig_context.load_instruction(num_cells, source);
+        self.allocate_slice(destination, num_cells);
+
+        let source_elements = self.slice_elements_pointer(source);
+        let elements = self.slice_elements_pointer(destination);
+        self.brillig_context.copy_array_instruction(source_elements, elements, num_cells);
+
+        self.brillig_context.deallocate_register(elements);
+        self.brillig_context.deallocate_register(source_elements);
+        self.brillig_context.deallocate_register(num_cells);
+    }
+
+    /// Returns a new register pointing to the first element of the given slice.
+    fn slice_elements_pointer(&mut self, slice: RegisterIndex) -> RegisterIndex {
+        let pointer = self.brillig_context.allocate_register();
+        self.brillig_context.mov_instruction(pointer, slice);
+        self.brillig_context.usize_op(pointer, BinaryIntOp::Add, 1);
+        pointer
+    }
+
     fn allocate_external_call_result(
         &mut self,
         result: ValueId,
@@ -569,12 +851,22 @@ pub(crate) fn convert_ssa_binary_op_to_brillig_binary_op(
                 return BrilligBinaryOp::Modulo { is_signed_integer: is_signed, bit_size }
             }
             BinaryOp::Eq => BinaryIntOp::Equals,
-            BinaryOp::Lt => BinaryIntOp::LessThan,
+            BinaryOp::Lt => {
+                if is_signed {
+                    return BrilligBinaryOp::SignedLessThan { bit_size };
+                }
+                BinaryIntOp::LessThan
+            }
             BinaryOp::And => BinaryIntOp::And,
             BinaryOp::Or => BinaryIntOp::Or,
             BinaryOp::Xor => BinaryIntOp::Xor,
             BinaryOp::Shl => BinaryIntOp::Shl,
-            BinaryOp::Shr => BinaryIntOp::Shr,
+            BinaryOp::Shr => {
+                if is_signed {
+                    return BrilligBinaryOp::ArithmeticShiftRight { bit_size };
+                }
+                BinaryIntOp::Shr
+            }
         };
 
         BrilligBinaryOp::Integer { op: operation, bit_size }
@@ -587,17 +879,14 @@ pub(crate) fn convert_ssa_binary_op_to_brillig_binary_op(
     }
 }
 
-/// Computes the size of an SSA composite type
-fn compute_size_of_composite_type(typ: &CompositeType) -> usize {
-    typ.iter().map(compute_size_of_type).sum()
-}
-
 /// Finds out the size of a given SSA type
 /// This is needed to store values in memory
 pub(crate) fn compute_size_of_type(typ: &Type) -> usize {
     match typ {
         Type::Numeric(_) => 1,
-        Type::Array(types, item_count) => compute_size_of_composite_type(types) * item_count,
+        Type::Array(types, item_count) => {
+            types.iter().map(compute_size_of_type).sum::<usize>() * item_count
+        }
         _ => todo!("ICE: Type not supported {typ:?}"),
     }
 }
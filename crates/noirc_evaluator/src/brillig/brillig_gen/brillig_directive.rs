@@ -1,4 +1,7 @@
-use acvm::acir::brillig_vm::{BinaryFieldOp, Opcode as BrilligOpcode, RegisterIndex, Value};
+use acvm::acir::brillig_vm::{
+    BinaryFieldOp, HeapArray, Opcode as BrilligOpcode, RegisterIndex, RegisterOrMemory, Value,
+};
+use iter_extended::vecmap;
 
 /// Generates brillig bytecode which computes the inverse of its input if not null, and zero else.
 pub(crate) fn directive_invert() -> Vec<BrilligOpcode> {
@@ -29,3 +32,24 @@ pub(crate) fn directive_invert() -> Vec<BrilligOpcode> {
         BrilligOpcode::Stop,
     ]
 }
+
+/// Generates brillig bytecode which passes its inputs to the given foreign call, ignoring any
+/// results. Each input is either a single value, or an array of the given length whose
+/// register points to its contents in memory.
+pub(crate) fn directive_foreign_call(
+    function: &str,
+    input_lengths: &[Option<usize>],
+) -> Vec<BrilligOpcode> {
+    let inputs = vecmap(input_lengths.iter().enumerate(), |(index, length)| {
+        let register = RegisterIndex::from(index);
+        match length {
+            None => RegisterOrMemory::RegisterIndex(register),
+            Some(size) => RegisterOrMemory::HeapArray(HeapArray { pointer: register, size: *size }),
+        }
+    });
+
+    vec![
+        BrilligOpcode::ForeignCall { function: function.to_owned(), destinations: vec![], inputs },
+        BrilligOpcode::Stop,
+    ]
+}
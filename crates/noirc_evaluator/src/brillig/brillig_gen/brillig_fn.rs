@@ -14,12 +14,19 @@ use crate::{
     },
 };
 
-use super::brillig_block::compute_size_of_type;
+use super::{brillig_block::compute_size_of_type, variable_liveness::VariableLiveness};
 
 pub(crate) struct FunctionContext {
     pub(crate) function_id: FunctionId,
     /// Map from SSA values to Register Indices.
     pub(crate) ssa_value_to_register: HashMap<ValueId, RegisterIndex>,
+    /// Names of the Noir variables SSA values are bound to, shown in the debug trace.
+    pub(crate) ssa_value_names: HashMap<ValueId, String>,
+    /// The points after which the registers of SSA values can be reused.
+    pub(crate) liveness: VariableLiveness,
+    /// The arrays allocated by the function which can never be aliased, with their size.
+    /// Their memory is freed along with their register.
+    pub(crate) unaliased_arrays: HashMap<ValueId, usize>,
 }
 
 impl FunctionContext {
@@ -36,6 +43,9 @@ impl FunctionContext {
         }
 
         let register = brillig_context.allocate_register();
+        if let Some(name) = self.ssa_value_names.get(&value) {
+            brillig_context.set_register_name(register, name);
+        }
 
         // Cache the `ValueId` so that if we call it again, it will
         // return the register that has just been created.
@@ -48,6 +58,32 @@ impl FunctionContext {
         register
     }
 
+    /// Frees the registers of SSA values which are no longer used, so that they can be reused
+    /// by the values defined afterwards, as well as the memory of unaliased arrays.
+    pub(crate) fn deallocate_registers(
+        &mut self,
+        brillig_context: &mut BrilligContext,
+        values: &[ValueId],
+    ) {
+        // Arrays are freed from the most recently defined one,
+        // so that the memory of each of them is at the end of the heap.
+        let mut values = values.to_vec();
+        values.sort_unstable_by(|a, b| b.cmp(a));
+        for value in values {
+            if let Some(register) = self.ssa_value_to_register.remove(&value) {
+                if let Some(size) = self.unaliased_arrays.remove(&value) {
+                    brillig_context.deallocate_fixed_length_array(register, size);
+                }
+                brillig_context.deallocate_register(register);
+            }
+        }
+    }
+
+    /// Collects the names of the Noir variables bound to the values of a given function
+    pub(crate) fn value_names(func: &Function) -> HashMap<ValueId, String> {
+        func.dfg.value_names().map(|(value, name)| (value, name.to_owned())).collect()
+    }
+
     /// Creates a function label from a given SSA function id.
     pub(crate) fn function_id_to_function_label(function_id: FunctionId) -> Label {
         function_id.to_string()
@@ -62,6 +98,8 @@ impl FunctionContext {
                 match typ {
                     Type::Numeric(_) => BrilligParameter::Register,
                     Type::Array(..) => BrilligParameter::HeapArray(compute_size_of_type(&typ)),
+                    // Slices are passed as a pointer to their contents, which are already in memory
+                    Type::Slice(..) => BrilligParameter::Register,
                     _ => unimplemented!("Unsupported function parameter type {typ:?}"),
                 }
             })
@@ -77,6 +115,7 @@ impl FunctionContext {
                 match typ {
                     Type::Numeric(_) => BrilligParameter::Register,
                     Type::Array(..) => BrilligParameter::HeapArray(compute_size_of_type(&typ)),
+                    Type::Slice(..) => BrilligParameter::Register,
                     _ => unimplemented!("Unsupported return value type {typ:?}"),
                 }
             })
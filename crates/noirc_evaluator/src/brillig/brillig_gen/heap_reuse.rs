@@ -0,0 +1,136 @@
+//! This module finds the arrays whose heap memory can be reused once they are dead.
+//!
+//! The Brillig heap only grows, so every array built by an unconstrained function stays
+//! allocated until the end of the execution. An array can be reused only if nothing else
+//! may point to it: it must be allocated by the function itself and only ever be read from
+//! or copied. Any other use, such as storing it, passing it to a call, returning it or
+//! passing it to another block, may create an alias of its pointer.
+use std::collections::{HashMap, HashSet};
+
+use crate::ssa_refactor::ir::{
+    dfg::DataFlowGraph,
+    function::Function,
+    instruction::{Instruction, Intrinsic},
+    types::Type,
+    value::{Value, ValueId},
+};
+
+use super::brillig_block::compute_size_of_type;
+
+/// Returns the arrays of the given function which are freshly allocated and never aliased,
+/// along with their size in memory.
+pub(crate) fn unaliased_arrays(func: &Function) -> HashMap<ValueId, usize> {
+    let mut allocated_arrays = Vec::new();
+    let mut escaping_values = HashSet::new();
+
+    for block_id in func.reachable_blocks() {
+        let block = &func.dfg[block_id];
+        for instruction_id in block.instructions() {
+            let instruction = &func.dfg[*instruction_id];
+            if allocates_results(instruction, &func.dfg) {
+                allocated_arrays.extend(func.dfg.instruction_results(*instruction_id));
+            }
+
+            match instruction {
+                Instruction::ArrayGet { index, .. } => {
+                    mark_escaping(&func.dfg, *index, &mut escaping_values);
+                }
+                Instruction::ArraySet { index, value, .. } => {
+                    mark_escaping(&func.dfg, *index, &mut escaping_values);
+                    mark_escaping(&func.dfg, *value, &mut escaping_values);
+                }
+                _ => instruction.for_each_value(|value| {
+                    mark_escaping(&func.dfg, value, &mut escaping_values);
+                }),
+            }
+        }
+        if let Some(terminator) = block.terminator() {
+            terminator.for_each_value(|value| {
+                mark_escaping(&func.dfg, value, &mut escaping_values);
+            });
+        }
+    }
+
+    allocated_arrays
+        .into_iter()
+        .filter(|array| !escaping_values.contains(array))
+        .filter_map(|array| match func.dfg.type_of_value(array) {
+            typ @ Type::Array(..) => Some((array, compute_size_of_type(&typ))),
+            _ => None,
+        })
+        .collect()
+}
+
+/// True if the results of the instruction are arrays allocated by the instruction itself.
+fn allocates_results(instruction: &Instruction, dfg: &DataFlowGraph) -> bool {
+    match instruction {
+        // Array sets copy their array into a new one
+        Instruction::ArraySet { .. } => true,
+        // The results of external calls are allocated before the call
+        Instruction::Call { func, .. } => matches!(
+            dfg[*func],
+            Value::ForeignFunction(_) | Value::Intrinsic(Intrinsic::BlackBox(_))
+        ),
+        _ => false,
+    }
+}
+
+/// Marks a value as possibly aliased, along with the elements of constant arrays
+/// which are stored into memory.
+fn mark_escaping(dfg: &DataFlowGraph, value: ValueId, escaping_values: &mut HashSet<ValueId>) {
+    if !escaping_values.insert(value) {
+        return;
+    }
+    if let Value::Array { array, .. } = &dfg[value] {
+        for element in array.iter() {
+            mark_escaping(dfg, *element, escaping_values);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ssa_refactor::{
+        ir::{function::RuntimeType, map::Id, types::Type},
+        ssa_builder::FunctionBuilder,
+    };
+
+    use super::unaliased_arrays;
+
+    #[test]
+    fn arrays_passed_elsewhere_are_aliased() {
+        // brillig fn main f0 {
+        //   b0(v0: [Field; 2]):
+        //     v2 = array_set v0, index Field 0, value Field 1
+        //     v3 = array_set v2, index Field 1, value Field 1
+        //     v4 = array_set v3, index Field 0, value Field 2
+        //     v5 = array_get v4, index Field 0
+        //     v6 = array_set v0, index Field 1, value Field 2
+        //     return v5, v6
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+        let array_type = Type::Array(std::rc::Rc::new(vec![Type::field()]), 2);
+        let v0 = builder.add_parameter(array_type);
+
+        let zero = builder.field_constant(0u128);
+        let one = builder.field_constant(1u128);
+        let two = builder.field_constant(2u128);
+
+        let v2 = builder.insert_array_set(v0, zero, one);
+        let v3 = builder.insert_array_set(v2, one, one);
+        let v4 = builder.insert_array_set(v3, zero, two);
+        let v5 = builder.insert_array_get(v4, zero, Type::field());
+        let v6 = builder.insert_array_set(v0, one, two);
+        builder.terminate_with_return(vec![v5, v6]);
+
+        let ssa = builder.finish();
+        let arrays = unaliased_arrays(ssa.main());
+
+        // The parameter is not allocated by the function and v6 is returned
+        let mut unaliased: Vec<_> = arrays.keys().copied().collect();
+        unaliased.sort();
+        assert_eq!(unaliased, vec![v2, v3, v4]);
+        assert_eq!(arrays[&v2], 2);
+    }
+}
@@ -0,0 +1,164 @@
+//! This module finds the point after which the register of an SSA value can be reused.
+//!
+//! Only values which are defined and used within a single block are considered: a value
+//! used in another block may be needed again when that block is entered through a loop,
+//! so its register is kept for the whole function.
+use std::collections::{HashMap, HashSet};
+
+use crate::ssa_refactor::ir::{
+    basic_block::BasicBlockId, function::Function, instruction::InstructionId, value::ValueId,
+};
+
+/// The point of a block after which a value is no longer used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum LastUse {
+    Instruction(InstructionId),
+    Terminator(BasicBlockId),
+}
+
+/// The values which are dead after each instruction and terminator of a function.
+#[derive(Debug, Default)]
+pub(crate) struct VariableLiveness {
+    dead_values: HashMap<LastUse, Vec<ValueId>>,
+}
+
+impl VariableLiveness {
+    /// Computes the last use of each block local value of the given function.
+    ///
+    /// Instruction results which are never used are dead right after their instruction.
+    pub(crate) fn from_function(func: &Function) -> Self {
+        let blocks = func.reachable_blocks();
+
+        let mut defining_block = HashMap::new();
+        for block_id in &blocks {
+            for instruction_id in func.dfg[*block_id].instructions() {
+                for result in func.dfg.instruction_results(*instruction_id) {
+                    defining_block.insert(*result, *block_id);
+                }
+            }
+        }
+
+        let mut last_uses: HashMap<ValueId, LastUse> = HashMap::new();
+        let mut used_in_other_blocks = HashSet::new();
+        let mut record_use = |value: ValueId, block_id: BasicBlockId, last_use: LastUse| {
+            match defining_block.get(&value) {
+                Some(defined_in) if *defined_in == block_id => {
+                    last_uses.insert(value, last_use);
+                }
+                Some(_) => {
+                    used_in_other_blocks.insert(value);
+                }
+                // Parameters and constants are not block local
+                None => (),
+            }
+        };
+
+        for block_id in &blocks {
+            let block = &func.dfg[*block_id];
+            for instruction_id in block.instructions() {
+                let last_use = LastUse::Instruction(*instruction_id);
+                for result in func.dfg.instruction_results(*instruction_id) {
+                    record_use(*result, *block_id, last_use);
+                }
+                func.dfg[*instruction_id].for_each_value(|value| {
+                    record_use(value, *block_id, last_use);
+                });
+            }
+            if let Some(terminator) = block.terminator() {
+                terminator.for_each_value(|value| {
+                    record_use(value, *block_id, LastUse::Terminator(*block_id));
+                });
+            }
+        }
+
+        let mut dead_values: HashMap<LastUse, Vec<ValueId>> = HashMap::new();
+        for (value, last_use) in last_uses {
+            if !used_in_other_blocks.contains(&value) {
+                dead_values.entry(last_use).or_default().push(value);
+            }
+        }
+        Self { dead_values }
+    }
+
+    /// Returns the values which are no longer used once the given instruction has executed.
+    pub(crate) fn dead_after_instruction(&self, instruction_id: InstructionId) -> &[ValueId] {
+        self.dead_values(LastUse::Instruction(instruction_id))
+    }
+
+    /// Returns the values which are no longer used once the terminator of the given block has executed.
+    pub(crate) fn dead_after_terminator(&self, block_id: BasicBlockId) -> &[ValueId] {
+        self.dead_values(LastUse::Terminator(block_id))
+    }
+
+    fn dead_values(&self, last_use: LastUse) -> &[ValueId] {
+        self.dead_values.get(&last_use).map_or(&[], |values| values.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ssa_refactor::{
+        ir::{function::RuntimeType, instruction::BinaryOp, map::Id, types::Type},
+        ssa_builder::FunctionBuilder,
+    };
+
+    use super::VariableLiveness;
+
+    #[test]
+    fn block_local_values_die_after_their_last_use() {
+        // fn main f0 {
+        //   b0(v0: Field):
+        //     v1 = add v0, Field 1
+        //     v2 = add v1, Field 2
+        //     v3 = add v0, Field 3
+        //     v4 = add v0, Field 4
+        //     jmp b1(v3)
+        //   b1(v5: Field):
+        //     v6 = add v5, v4
+        //     return v6
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::field());
+        let b1 = builder.insert_block();
+
+        let one = builder.field_constant(1u128);
+        let two = builder.field_constant(2u128);
+        let three = builder.field_constant(3u128);
+        let four = builder.field_constant(4u128);
+
+        let v1 = builder.insert_binary(v0, BinaryOp::Add, one);
+        let v2 = builder.insert_binary(v1, BinaryOp::Add, two);
+        let v3 = builder.insert_binary(v0, BinaryOp::Add, three);
+        let v4 = builder.insert_binary(v0, BinaryOp::Add, four);
+        builder.terminate_with_jmp(b1, vec![v3]);
+
+        builder.switch_to_block(b1);
+        let v5 = builder.add_block_parameter(b1, Type::field());
+        let v6 = builder.insert_binary(v5, BinaryOp::Add, v4);
+        builder.terminate_with_return(vec![v6]);
+
+        let ssa = builder.finish();
+        let main = ssa.main();
+        let liveness = VariableLiveness::from_function(main);
+
+        let b0_instructions = main.dfg[main.entry_block()].instructions();
+        let b1_instructions = main.dfg[b1].instructions();
+
+        // v1 is only used by the instruction defining v2, which is itself unused
+        assert!(liveness.dead_after_instruction(b0_instructions[0]).is_empty());
+        let mut dead_after_v2 = liveness.dead_after_instruction(b0_instructions[1]).to_vec();
+        dead_after_v2.sort();
+        let mut expected = vec![v1, v2];
+        expected.sort();
+        assert_eq!(dead_after_v2, expected);
+
+        // v3 is passed to the next block, while v4 is used in another block and kept alive
+        assert!(liveness.dead_after_instruction(b0_instructions[2]).is_empty());
+        assert!(liveness.dead_after_instruction(b0_instructions[3]).is_empty());
+        assert_eq!(liveness.dead_after_terminator(main.entry_block()), &[v3]);
+
+        assert_eq!(liveness.dead_after_terminator(b1), &[v6]);
+        assert!(liveness.dead_after_instruction(b1_instructions[0]).is_empty());
+    }
+}
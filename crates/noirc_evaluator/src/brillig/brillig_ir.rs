@@ -5,11 +5,16 @@
 //! ssa types and types in this module.
 //! A similar paradigm can be seen with the `acir_ir` module.
 pub(crate) mod artifact;
+pub(crate) mod assembly;
+pub(crate) mod constant_folding;
+pub(crate) mod dead_code;
 pub(crate) mod debug_show;
 pub(crate) mod registers;
+pub(crate) mod statistics;
 
 use self::{
     artifact::{BrilligArtifact, BrilligParameter, UnresolvedJumpLocation},
+    debug_show::DebugShow,
     registers::BrilligRegistersContext,
 };
 use acvm::{
@@ -19,8 +24,9 @@ use acvm::{
     },
     FieldElement,
 };
+use noirc_errors::Location;
 
-/// Integer arithmetic in Brillig is limited to 127 bit
+/// Integer arithmetic in Brillig is limited to 128 bit
 /// integers.
 ///
 /// We could lift this in the future and have Brillig
@@ -30,11 +36,13 @@ use acvm::{
 /// Since constrained functions do not have this property, it
 /// would mean that unconstrained functions will differ from
 /// constrained functions in terms of syntax compatibility.
-pub(crate) const BRILLIG_INTEGER_ARITHMETIC_BIT_SIZE: u32 = 127;
+pub(crate) const BRILLIG_INTEGER_ARITHMETIC_BIT_SIZE: u32 = 128;
 /// The Brillig VM does not apply a limit to the memory address space,
 /// As a convention, we take use 64 bits. This means that we assume that
 /// memory has 2^64 memory slots.
 pub(crate) const BRILLIG_MEMORY_ADDRESSING_BIT_SIZE: u32 = 64;
+/// Arrays of a size known at compile time up to this many elements are copied without a loop.
+const MAX_UNROLLED_ARRAY_COPY_SIZE: usize = 16;
 
 // Registers reserved in runtime for special purposes.
 pub(crate) enum ReservedRegisters {
@@ -76,6 +84,8 @@ pub(crate) struct BrilligContext {
     context_label: String,
     /// Section label, used to separate sections of code
     section_label: usize,
+    /// Records a human-readable trace of the emitted instructions
+    debug_show: DebugShow,
 }
 
 impl BrilligContext {
@@ -83,12 +93,14 @@ impl BrilligContext {
     pub(crate) fn new(
         arguments: Vec<BrilligParameter>,
         return_parameters: Vec<BrilligParameter>,
+        enable_debug_trace: bool,
     ) -> BrilligContext {
         BrilligContext {
             obj: BrilligArtifact::new(arguments, return_parameters),
             registers: BrilligRegistersContext::new(),
             context_label: String::default(),
             section_label: 0,
+            debug_show: DebugShow::new(enable_debug_trace),
         }
     }
 
@@ -102,6 +114,21 @@ impl BrilligContext {
         self.obj
     }
 
+    /// Sets the source location of the Noir code the next instructions are generated from
+    pub(crate) fn set_location(&mut self, location: Location) {
+        self.debug_show.set_location(location);
+    }
+
+    /// Records that `register` holds the Noir variable `name`, which is shown in the debug trace
+    pub(crate) fn set_register_name(&mut self, register: RegisterIndex, name: &str) {
+        self.debug_show.set_register_name(register, name);
+    }
+
+    /// Takes the debug trace of the instructions emitted so far
+    pub(crate) fn take_debug_show(&mut self) -> DebugShow {
+        std::mem::take(&mut self.debug_show)
+    }
+
     /// Allocates an array of size `size` and stores the pointer to the array
     /// in `pointer_register`
     pub(crate) fn allocate_fixed_length_array(
@@ -121,7 +148,7 @@ impl BrilligContext {
         pointer_register: RegisterIndex,
         size_register: RegisterIndex,
     ) {
-        debug_show::allocate_array_instruction(pointer_register, size_register);
+        self.debug_show.allocate_array_instruction(pointer_register, size_register);
         self.push_opcode(BrilligOpcode::Mov {
             destination: pointer_register,
             source: ReservedRegisters::stack_pointer(),
@@ -135,6 +162,40 @@ impl BrilligContext {
         });
     }
 
+    /// Frees the `size` slots of the array pointed by `pointer_register` if it is the
+    /// last allocation of the heap, so that the next allocations reuse its memory.
+    ///
+    /// The heap can only shrink from its end, so the memory of an array followed by
+    /// other allocations is not reclaimed.
+    pub(crate) fn deallocate_fixed_length_array(
+        &mut self,
+        pointer_register: RegisterIndex,
+        size: usize,
+    ) {
+        self.debug_show.deallocate_fixed_length_array(pointer_register, size);
+        let size_register = self.make_constant(size.into());
+        let is_last_allocation = self.allocate_register();
+        self.memory_op(pointer_register, size_register, is_last_allocation, BinaryIntOp::Add);
+        self.memory_op(
+            is_last_allocation,
+            ReservedRegisters::stack_pointer(),
+            is_last_allocation,
+            BinaryIntOp::Equals,
+        );
+
+        let end_label = self.next_section_label();
+        self.not_instruction(is_last_allocation, 1, is_last_allocation);
+        self.jump_if_instruction(is_last_allocation, end_label);
+        self.push_opcode(BrilligOpcode::Mov {
+            destination: ReservedRegisters::stack_pointer(),
+            source: pointer_register,
+        });
+        self.enter_next_section();
+
+        self.deallocate_register(is_last_allocation);
+        self.deallocate_register(size_register);
+    }
+
     /// Gets the value in the array at index `index` and stores it in `result`
     pub(crate) fn array_get(
         &mut self,
@@ -142,7 +203,7 @@ impl BrilligContext {
         index: RegisterIndex,
         result: RegisterIndex,
     ) {
-        debug_show::array_get(array_ptr, index, result);
+        self.debug_show.array_get(array_ptr, index, result);
         // Computes array_ptr + index, ie array[index]
         let index_of_element_in_memory = self.allocate_register();
         self.binary_instruction(
@@ -164,7 +225,7 @@ impl BrilligContext {
         index: RegisterIndex,
         value: RegisterIndex,
     ) {
-        debug_show::array_set(array_ptr, index, value);
+        self.debug_show.array_set(array_ptr, index, value);
         // Computes array_ptr + index, ie array[index]
         let index_of_element_in_memory = self.allocate_register();
         self.binary_instruction(
@@ -187,62 +248,106 @@ impl BrilligContext {
         destination: RegisterIndex,
         num_elements_register: RegisterIndex,
     ) {
-        debug_show::copy_array_instruction(source, destination, num_elements_register);
-        let index_register = self.make_constant(0_u128.into());
+        self.debug_show.copy_array_instruction(source, destination, num_elements_register);
+        // Walk both arrays with pointers rather than indices, which saves computing
+        // the address of each element twice.
+        let source_pointer = self.allocate_register();
+        self.mov_instruction(source_pointer, source);
+        let destination_pointer = self.allocate_register();
+        self.mov_instruction(destination_pointer, destination);
+        let source_end = self.allocate_register();
+        self.memory_op(source, num_elements_register, source_end, BinaryIntOp::Add);
+        let one_register = self.make_constant(1_usize.into());
 
         let loop_label = self.next_section_label();
         self.enter_next_section();
 
         // Loop body
 
-        // Check if index < num_elements
-        let index_less_than_array_len = self.allocate_register();
-        self.binary_instruction(
-            index_register,
-            num_elements_register,
-            index_less_than_array_len,
-            BrilligBinaryOp::Integer {
-                op: BinaryIntOp::LessThan,
-                bit_size: BRILLIG_MEMORY_ADDRESSING_BIT_SIZE,
-            },
-        );
+        // Check if source_pointer < source_end
+        let is_in_bounds = self.allocate_register();
+        self.memory_op(source_pointer, source_end, is_in_bounds, BinaryIntOp::LessThan);
 
         let exit_loop_label = self.next_section_label();
 
-        self.not_instruction(index_less_than_array_len, 1, index_less_than_array_len);
-        self.jump_if_instruction(index_less_than_array_len, exit_loop_label);
+        self.not_instruction(is_in_bounds, 1, is_in_bounds);
+        self.jump_if_instruction(is_in_bounds, exit_loop_label);
 
         // Copy the element from source to destination
-        let value_register = self.allocate_register();
-        self.array_get(source, index_register, value_register);
-        self.array_set(destination, index_register, value_register);
-
-        // Increment the index register
-        let one_register = self.make_constant(1u128.into());
-        self.binary_instruction(
-            index_register,
-            one_register,
-            index_register,
-            BrilligBinaryOp::Integer {
-                op: BinaryIntOp::Add,
-                bit_size: BRILLIG_MEMORY_ADDRESSING_BIT_SIZE,
-            },
-        );
+        self.copy_element_and_advance(source_pointer, destination_pointer, one_register);
 
         self.jump_instruction(loop_label);
 
         // Exit the loop
         self.enter_next_section();
         // Deallocate our temporary registers
-        self.deallocate_register(index_less_than_array_len);
-        self.deallocate_register(value_register);
+        self.deallocate_register(is_in_bounds);
         self.deallocate_register(one_register);
-        self.deallocate_register(index_register);
+        self.deallocate_register(source_end);
+        self.deallocate_register(destination_pointer);
+        self.deallocate_register(source_pointer);
+    }
+
+    /// Copies the `num_elements` values of an array pointed by source into the array pointed by destination.
+    ///
+    /// Small arrays are copied without a loop, which avoids the bounds check and the jumps of each iteration.
+    pub(crate) fn copy_fixed_length_array_instruction(
+        &mut self,
+        source: RegisterIndex,
+        destination: RegisterIndex,
+        num_elements: usize,
+    ) {
+        if num_elements > MAX_UNROLLED_ARRAY_COPY_SIZE {
+            let num_elements_register = self.make_constant(num_elements.into());
+            self.copy_array_instruction(source, destination, num_elements_register);
+            self.deallocate_register(num_elements_register);
+            return;
+        }
+
+        self.debug_show.copy_fixed_length_array_instruction(source, destination, num_elements);
+        let source_pointer = self.allocate_register();
+        self.mov_instruction(source_pointer, source);
+        let destination_pointer = self.allocate_register();
+        self.mov_instruction(destination_pointer, destination);
+        let one_register = self.make_constant(1_usize.into());
+
+        for index in 0..num_elements {
+            if index + 1 == num_elements {
+                // There is no need to advance the pointers past the last element
+                let value_register = self.allocate_register();
+                self.load_instruction(value_register, source_pointer);
+                self.store_instruction(destination_pointer, value_register);
+                self.deallocate_register(value_register);
+            } else {
+                self.copy_element_and_advance(source_pointer, destination_pointer, one_register);
+            }
+        }
+
+        self.deallocate_register(one_register);
+        self.deallocate_register(destination_pointer);
+        self.deallocate_register(source_pointer);
+    }
+
+    /// Copies the value pointed by `source_pointer` to `destination_pointer`, then moves
+    /// both pointers to the next element.
+    fn copy_element_and_advance(
+        &mut self,
+        source_pointer: RegisterIndex,
+        destination_pointer: RegisterIndex,
+        one_register: RegisterIndex,
+    ) {
+        let value_register = self.allocate_register();
+        self.load_instruction(value_register, source_pointer);
+        self.store_instruction(destination_pointer, value_register);
+        self.deallocate_register(value_register);
+
+        self.memory_op(source_pointer, one_register, source_pointer, BinaryIntOp::Add);
+        self.memory_op(destination_pointer, one_register, destination_pointer, BinaryIntOp::Add);
     }
 
     /// Adds a label to the next opcode
     pub(crate) fn enter_context<T: ToString>(&mut self, label: T) {
-        debug_show::enter_context(label.to_string());
+        self.debug_show.enter_context(label.to_string());
         self.context_label = label.to_string();
         self.section_label = 0;
         // Add a context label to the next opcode
@@ -255,6 +360,7 @@ impl BrilligContext {
     /// Increments the section label and adds a section label to the next opcode
     fn enter_next_section(&mut self) {
         self.section_label += 1;
+        self.debug_show.enter_section(self.current_section_label());
         self.obj
             .add_label_at_position(self.current_section_label(), self.obj.index_of_next_opcode());
     }
@@ -276,7 +382,7 @@ impl BrilligContext {
 
     /// Adds a unresolved `Jump` instruction to the bytecode.
     pub(crate) fn jump_instruction<T: ToString>(&mut self, target_label: T) {
-        debug_show::jump_instruction(target_label.to_string());
+        self.debug_show.jump_instruction(target_label.to_string());
         self.add_unresolved_jump(BrilligOpcode::Jump { location: 0 }, target_label.to_string());
     }
 
@@ -286,7 +392,7 @@ impl BrilligContext {
         condition: RegisterIndex,
         target_label: T,
     ) {
-        debug_show::jump_if_instruction(condition, target_label.to_string());
+        self.debug_show.jump_if_instruction(condition, target_label.to_string());
         self.add_unresolved_jump(
             BrilligOpcode::JumpIf { condition, location: 0 },
             target_label.to_string(),
@@ -308,9 +414,8 @@ impl BrilligContext {
     }
 
     /// Push a register to the deallocation list, ready for reuse.
-    /// TODO(AD): currently, register deallocation is only done with immediate values.
-    /// TODO(AD): See https://github.com/noir-lang/noir/issues/1720
     pub(crate) fn deallocate_register(&mut self, register_index: RegisterIndex) {
+        self.debug_show.remove_register_name(register_index);
         self.registers.deallocate_register(register_index);
     }
 }
@@ -319,7 +424,7 @@ impl BrilligContext {
     /// Emits brillig bytecode to jump to a trap condition if `condition`
     /// is false.
     pub(crate) fn constrain_instruction(&mut self, condition: RegisterIndex) {
-        debug_show::constrain_instruction(condition);
+        self.debug_show.constrain_instruction(condition);
         self.add_unresolved_jump(
             BrilligOpcode::JumpIf { condition, location: 0 },
             self.next_section_label(),
@@ -338,7 +443,7 @@ impl BrilligContext {
     /// method will move all register values to the first `N` values in
     /// the VM.
     pub(crate) fn return_instruction(&mut self, return_registers: &[RegisterIndex]) {
-        debug_show::return_instruction(return_registers);
+        self.debug_show.return_instruction(return_registers);
         let mut sources = Vec::with_capacity(return_registers.len());
         let mut destinations = Vec::with_capacity(return_registers.len());
 
@@ -377,7 +482,7 @@ impl BrilligContext {
     ///
     /// Copies the value at `source` into `destination`
     pub(crate) fn mov_instruction(&mut self, destination: RegisterIndex, source: RegisterIndex) {
-        debug_show::mov_instruction(destination, source);
+        self.debug_show.mov_instruction(destination, source);
         self.push_opcode(BrilligOpcode::Mov { destination, source });
     }
 
@@ -392,7 +497,7 @@ impl BrilligContext {
         result: RegisterIndex,
         operation: BrilligBinaryOp,
     ) {
-        debug_show::binary_instruction(lhs, rhs, result, operation.clone());
+        self.debug_show.binary_instruction(lhs, rhs, result, operation.clone());
         match operation {
             BrilligBinaryOp::Field { op } => {
                 let opcode = BrilligOpcode::BinaryFieldOp { op, destination: result, lhs, rhs };
@@ -406,12 +511,18 @@ impl BrilligContext {
             BrilligBinaryOp::Modulo { is_signed_integer, bit_size } => {
                 self.modulo_instruction(result, lhs, rhs, bit_size, is_signed_integer);
             }
+            BrilligBinaryOp::SignedLessThan { bit_size } => {
+                self.signed_less_than_instruction(result, lhs, rhs, bit_size);
+            }
+            BrilligBinaryOp::ArithmeticShiftRight { bit_size } => {
+                self.arithmetic_shift_right_instruction(result, lhs, rhs, bit_size);
+            }
         }
     }
 
     /// Stores the value of `constant` in the `result` register
     pub(crate) fn const_instruction(&mut self, result: RegisterIndex, constant: Value) {
-        debug_show::const_instruction(result, constant);
+        self.debug_show.const_instruction(result, constant);
         self.push_opcode(BrilligOpcode::Const { destination: result, value: constant });
     }
 
@@ -425,7 +536,7 @@ impl BrilligContext {
         bit_size: u32,
         result: RegisterIndex,
     ) {
-        debug_show::not_instruction(input, bit_size, result);
+        self.debug_show.not_instruction(input, bit_size, result);
         // Compile !x as ((-1) - x)
         let u_max = FieldElement::from(2_i128).pow(&FieldElement::from(bit_size as i128))
             - FieldElement::one();
@@ -451,7 +562,7 @@ impl BrilligContext {
         inputs: &[RegisterOrMemory],
         outputs: &[RegisterOrMemory],
     ) {
-        debug_show::foreign_call_instruction(func_name.clone(), inputs, outputs);
+        self.debug_show.foreign_call_instruction(func_name.clone(), inputs, outputs);
         let opcode = BrilligOpcode::ForeignCall {
             function: func_name,
             destinations: outputs.to_vec(),
@@ -466,7 +577,7 @@ impl BrilligContext {
         destination: RegisterIndex,
         source_pointer: RegisterIndex,
     ) {
-        debug_show::load_instruction(destination, source_pointer);
+        self.debug_show.load_instruction(destination, source_pointer);
         self.push_opcode(BrilligOpcode::Load { destination, source_pointer });
     }
 
@@ -476,7 +587,7 @@ impl BrilligContext {
         destination_pointer: RegisterIndex,
         source: RegisterIndex,
     ) {
-        debug_show::store_instruction(destination_pointer, source);
+        self.debug_show.store_instruction(destination_pointer, source);
         self.push_opcode(BrilligOpcode::Store { destination_pointer, source });
     }
 
@@ -500,7 +611,7 @@ impl BrilligContext {
 
     /// Emits a stop instruction
     pub(crate) fn stop_instruction(&mut self) {
-        debug_show::stop_instruction();
+        self.debug_show.stop_instruction();
         self.push_opcode(BrilligOpcode::Stop);
     }
 
@@ -566,10 +677,89 @@ impl BrilligContext {
         self.deallocate_register(scratch_register_j);
     }
 
+    /// Computes left < right for two's complement integers.
+    ///
+    /// Flipping the sign bit of both operands maps the signed range
+    /// onto the unsigned range while preserving order, so the result
+    /// is an unsigned comparison of the flipped operands.
+    pub(crate) fn signed_less_than_instruction(
+        &mut self,
+        result_register: RegisterIndex,
+        left: RegisterIndex,
+        right: RegisterIndex,
+        bit_size: u32,
+    ) {
+        // no debug_show, shown in binary instruction
+        let sign_bit = FieldElement::from(2_i128).pow(&FieldElement::from(bit_size as i128 - 1));
+        let sign_bit_register = self.make_constant(Value::from(sign_bit));
+        let flipped_left = self.allocate_register();
+        let flipped_right = self.allocate_register();
+
+        for (source, destination) in [(left, flipped_left), (right, flipped_right)] {
+            self.push_opcode(BrilligOpcode::BinaryIntOp {
+                op: BinaryIntOp::Xor,
+                destination,
+                bit_size,
+                lhs: source,
+                rhs: sign_bit_register,
+            });
+        }
+
+        self.push_opcode(BrilligOpcode::BinaryIntOp {
+            op: BinaryIntOp::LessThan,
+            destination: result_register,
+            bit_size,
+            lhs: flipped_left,
+            rhs: flipped_right,
+        });
+
+        self.deallocate_register(sign_bit_register);
+        self.deallocate_register(flipped_left);
+        self.deallocate_register(flipped_right);
+    }
+
+    /// Computes left >> right for two's complement integers, filling the
+    /// vacated high bits with copies of the sign bit.
+    ///
+    /// This is done by using the following formula:
+    ///
+    /// mask = 0 - (left >> (bit_size - 1))
+    /// left >> right = ((left ^ mask) >> right) ^ mask
+    ///
+    /// The mask is all ones for negative values and zero otherwise, so
+    /// negative values are inverted, shifted as unsigned values and inverted back.
+    pub(crate) fn arithmetic_shift_right_instruction(
+        &mut self,
+        result_register: RegisterIndex,
+        left: RegisterIndex,
+        right: RegisterIndex,
+        bit_size: u32,
+    ) {
+        // no debug_show, shown in binary instruction
+        let sign_shift = self.make_constant(Value::from(FieldElement::from(bit_size as i128 - 1)));
+        let mask = self.allocate_register();
+        let zero = self.make_constant(Value::from(FieldElement::zero()));
+
+        let mut push_int_op = |op, destination, lhs, rhs: RegisterIndex| {
+            self.push_opcode(BrilligOpcode::BinaryIntOp { op, destination, bit_size, lhs, rhs });
+        };
+        // mask = 0 - (left >> (bit_size - 1))
+        push_int_op(BinaryIntOp::Shr, mask, left, sign_shift);
+        push_int_op(BinaryIntOp::Sub, mask, zero, mask);
+        // result = ((left ^ mask) >> right) ^ mask
+        push_int_op(BinaryIntOp::Xor, result_register, left, mask);
+        push_int_op(BinaryIntOp::Shr, result_register, result_register, right);
+        push_int_op(BinaryIntOp::Xor, result_register, result_register, mask);
+
+        self.deallocate_register(sign_shift);
+        self.deallocate_register(mask);
+        self.deallocate_register(zero);
+    }
+
     /// Emits a modulo instruction against 2**target_bit_size
     ///
-    /// Integer arithmetic in Brillig is currently constrained to 127 bit integers.
-    /// We restrict the cast operation, so that integer types over 127 bits
+    /// Integer arithmetic in Brillig is currently constrained to 128 bit integers.
+    /// We restrict the cast operation, so that integer types over 128 bits
     /// cannot be created.
     pub(crate) fn cast_instruction(
         &mut self,
@@ -577,7 +767,7 @@ impl BrilligContext {
         source: RegisterIndex,
         target_bit_size: u32,
     ) {
-        debug_show::cast_instruction(destination, source, target_bit_size);
+        self.debug_show.cast_instruction(destination, source, target_bit_size);
         assert!(
             target_bit_size <= BRILLIG_INTEGER_ARITHMETIC_BIT_SIZE,
             "tried to cast to a bit size greater than allowed {target_bit_size}"
@@ -596,10 +786,65 @@ impl BrilligContext {
         self.deallocate_register(zero_register);
     }
 
+    /// Sign-extends the two's complement integer of `source_bit_size` bits in `source`.
+    ///
+    /// Negative values have 2^{target_bit_size} - 2^{source_bit_size} added, setting
+    /// every bit above the sign bit. When there is no target bit size the result is
+    /// a field element and negative values have 2^{source_bit_size} subtracted instead.
+    pub(crate) fn sign_extend_instruction(
+        &mut self,
+        destination: RegisterIndex,
+        source: RegisterIndex,
+        source_bit_size: u32,
+        target_bit_size: Option<u32>,
+    ) {
+        self.debug_show.sign_extend_instruction(destination, source, target_bit_size);
+        let source_modulus =
+            FieldElement::from(2_i128).pow(&FieldElement::from(source_bit_size as i128));
+        let extension = match target_bit_size {
+            Some(target_bit_size) => {
+                FieldElement::from(2_i128).pow(&FieldElement::from(target_bit_size as i128))
+                    - source_modulus
+            }
+            None => FieldElement::zero() - source_modulus,
+        };
+
+        let sign_shift =
+            self.make_constant(Value::from(FieldElement::from(source_bit_size as i128 - 1)));
+        let extension = self.make_constant(Value::from(extension));
+        let sign_bit = self.allocate_register();
+
+        // sign_bit = source >> (source_bit_size - 1)
+        self.push_opcode(BrilligOpcode::BinaryIntOp {
+            op: BinaryIntOp::Shr,
+            destination: sign_bit,
+            bit_size: source_bit_size,
+            lhs: source,
+            rhs: sign_shift,
+        });
+        // destination = source + sign_bit * extension
+        self.push_opcode(BrilligOpcode::BinaryFieldOp {
+            op: BinaryFieldOp::Mul,
+            destination: sign_bit,
+            lhs: sign_bit,
+            rhs: extension,
+        });
+        self.push_opcode(BrilligOpcode::BinaryFieldOp {
+            op: BinaryFieldOp::Add,
+            destination,
+            lhs: source,
+            rhs: sign_bit,
+        });
+
+        self.deallocate_register(sign_shift);
+        self.deallocate_register(extension);
+        self.deallocate_register(sign_bit);
+    }
+
     /// Adds a unresolved external `Call` instruction to the bytecode.
     /// This calls into another function compiled into this brillig artifact.
     pub(crate) fn add_external_call_instruction<T: ToString>(&mut self, func_label: T) {
-        debug_show::add_external_call_instruction(func_label.to_string());
+        self.debug_show.add_external_call_instruction(func_label.to_string());
         self.obj.add_unresolved_external_call(
             BrilligOpcode::Call { location: 0 },
             func_label.to_string(),
@@ -657,6 +902,22 @@ impl BrilligContext {
         self.deallocate_register(const_register);
     }
 
+    /// Utility method to perform a binary instruction on memory addresses
+    fn memory_op(
+        &mut self,
+        lhs: RegisterIndex,
+        rhs: RegisterIndex,
+        destination: RegisterIndex,
+        op: BinaryIntOp,
+    ) {
+        self.binary_instruction(
+            lhs,
+            rhs,
+            destination,
+            BrilligBinaryOp::Integer { op, bit_size: BRILLIG_MEMORY_ADDRESSING_BIT_SIZE },
+        );
+    }
+
     // Used before a call instruction.
     // Save all the registers we have used to the stack.
     // Move argument values to the front of the register indices.
@@ -677,6 +938,24 @@ impl BrilligContext {
         saved_registers
     }
 
+    /// Used after a call instruction, before allocating the registers of its results.
+    ///
+    /// The return values of the call are held in the first registers, some of which may be
+    /// free in the caller. These are allocated so that no result register overwrites a return
+    /// value before it is read. The returned registers should be deallocated once the
+    /// return values have been moved to the result registers.
+    pub(crate) fn allocate_free_return_value_registers(
+        &mut self,
+        num_return_values: usize,
+    ) -> Vec<RegisterIndex> {
+        let return_value_registers: Vec<_> =
+            (0..num_return_values).map(|i| self.register(i)).collect();
+        return_value_registers
+            .into_iter()
+            .filter(|register| self.registers.allocate_register_if_free(*register))
+            .collect()
+    }
+
     // Used after a call instruction.
     // Move return values to the front of the register indices.
     // Load all the registers we have previous saved in save_registers_prep_args.
@@ -707,7 +986,7 @@ impl BrilligContext {
 
     /// Issues a blackbox operation.
     pub(crate) fn black_box_op_instruction(&mut self, op: BlackBoxOp) {
-        debug_show::black_box_op_instruction(op);
+        self.debug_show.black_box_op_instruction(op);
         self.push_opcode(BrilligOpcode::BlackBox(op));
     }
 }
@@ -720,6 +999,10 @@ pub(crate) enum BrilligBinaryOp {
     // Modulo operation requires more than one opcode
     // Brillig.
     Modulo { is_signed_integer: bool, bit_size: u32 },
+    // The Brillig VM only compares and shifts unsigned integers,
+    // signed versions of these operations require more than one opcode.
+    SignedLessThan { bit_size: u32 },
+    ArithmeticShiftRight { bit_size: u32 },
 }
 
 #[cfg(test)]
@@ -727,11 +1010,16 @@ mod tests {
     use std::vec;
 
     use acvm::acir::brillig_vm::{
-        BinaryIntOp, ForeignCallOutput, ForeignCallResult, HeapVector, RegisterIndex,
-        RegisterOrMemory, Registers, VMStatus, Value, VM,
+        BinaryFieldOp, BinaryIntOp, ForeignCallOutput, ForeignCallResult, HeapVector,
+        RegisterIndex, RegisterOrMemory, Registers, VMStatus, Value, VM,
     };
+    use acvm::FieldElement;
 
-    use crate::brillig::brillig_ir::{BrilligContext, BRILLIG_MEMORY_ADDRESSING_BIT_SIZE};
+    use noirc_errors::{Location, Span};
+
+    use crate::brillig::brillig_ir::{
+        statistics::BrilligFunctionStatistics, BrilligContext, BRILLIG_MEMORY_ADDRESSING_BIT_SIZE,
+    };
 
     use super::{BrilligBinaryOp, BrilligOpcode, ReservedRegisters};
 
@@ -748,7 +1036,7 @@ mod tests {
         //   let the_sequence = make_number_sequence(12);
         //   assert(the_sequence.len() == 12);
         // }
-        let mut context = BrilligContext::new(vec![], vec![]);
+        let mut context = BrilligContext::new(vec![], vec![], false);
         let r_stack = ReservedRegisters::stack_pointer();
         // Start stack pointer at 0
         context.const_instruction(r_stack, Value::from(0_usize));
@@ -803,4 +1091,242 @@ mod tests {
         let status = vm.process_opcodes();
         assert_eq!(status, VMStatus::Finished);
     }
+
+    /// Test copying arrays, both with and without a loop
+    #[test]
+    fn test_brillig_ir_copy_array() {
+        for num_elements in [3_usize, 20] {
+            let mut context = BrilligContext::new(vec![], vec![], false);
+            context.enter_context("copy");
+            let r_source = context.make_constant(Value::from(0_usize));
+            let r_destination = context.make_constant(Value::from(num_elements));
+            context.copy_fixed_length_array_instruction(r_source, r_destination, num_elements);
+
+            // Trap unless each element has been copied
+            for index in 0..num_elements {
+                let r_index = context.make_constant(Value::from(index));
+                let r_value = context.allocate_register();
+                context.array_get(r_destination, r_index, r_value);
+                let r_expected = context.make_constant(Value::from(index * 7));
+                context.binary_instruction(
+                    r_value,
+                    r_expected,
+                    r_value,
+                    BrilligBinaryOp::Integer {
+                        op: BinaryIntOp::Equals,
+                        bit_size: BRILLIG_MEMORY_ADDRESSING_BIT_SIZE,
+                    },
+                );
+                let location = context.obj.index_of_next_opcode() + 2;
+                context.push_opcode(BrilligOpcode::JumpIf { condition: r_value, location });
+                context.push_opcode(BrilligOpcode::Trap);
+                for register in [r_index, r_value, r_expected] {
+                    context.deallocate_register(register);
+                }
+            }
+            context.stop_instruction();
+
+            let bytecode = context.artifact().finish();
+            let mut memory: Vec<Value> = (0..num_elements).map(|i| Value::from(i * 7)).collect();
+            memory.resize(2 * num_elements, Value::from(0_usize));
+            let mut vm = VM::new(Registers { inner: vec![] }, memory, bytecode, vec![]);
+            assert_eq!(vm.process_opcodes(), VMStatus::Finished);
+        }
+    }
+
+    /// Test that arrays are only freed when they are the last allocation of the heap
+    #[test]
+    fn test_brillig_ir_deallocate_array() {
+        let mut context = BrilligContext::new(vec![], vec![], false);
+        context.enter_context("deallocate");
+        let r_first = context.allocate_register();
+        context.allocate_fixed_length_array(r_first, 2);
+        let r_second = context.allocate_register();
+        context.allocate_fixed_length_array(r_second, 3);
+
+        // The first array is followed by the second one, so it is not freed yet
+        context.deallocate_fixed_length_array(r_first, 2);
+        let r_heap_end_after_first = context.allocate_register();
+        context.mov_instruction(r_heap_end_after_first, ReservedRegisters::stack_pointer());
+        context.deallocate_fixed_length_array(r_second, 3);
+        let r_heap_end_after_second = context.allocate_register();
+        context.mov_instruction(r_heap_end_after_second, ReservedRegisters::stack_pointer());
+        context.deallocate_fixed_length_array(r_first, 2);
+        context.stop_instruction();
+
+        let bytecode = context.artifact().finish();
+        let mut vm = VM::new(Registers { inner: vec![] }, vec![], bytecode, vec![]);
+        assert_eq!(vm.process_opcodes(), VMStatus::Finished);
+
+        let registers = vm.get_registers();
+        assert_eq!(registers.get(r_heap_end_after_first), Value::from(5_usize));
+        assert_eq!(registers.get(r_heap_end_after_second), Value::from(2_usize));
+        assert_eq!(registers.get(ReservedRegisters::stack_pointer()), Value::from(0_usize));
+    }
+
+    /// Test that the disassembly is recorded only when the debug trace is enabled
+    #[test]
+    fn test_brillig_ir_debug_output() {
+        let build = |enable_debug_trace| {
+            let mut context = BrilligContext::new(vec![], vec![], enable_debug_trace);
+            context.enter_context("f0");
+            let r_value = context.make_constant(Value::from(7_usize));
+            context.mov_instruction(ReservedRegisters::user_register_index(1), r_value);
+            context.stop_instruction();
+            context.take_debug_show().output().to_owned()
+        };
+
+        assert_eq!(build(true), "f0:\n  CONST R1 = 7\n  MOV R2, R1\n  STOP\n");
+        assert!(build(false).is_empty());
+    }
+
+    /// Test that registers holding Noir variables are shown with the variable's name
+    #[test]
+    fn test_brillig_ir_debug_register_names() {
+        let mut context = BrilligContext::new(vec![], vec![], true);
+        context.enter_context("f0");
+        let r_counter = context.allocate_register();
+        context.set_register_name(r_counter, "counter");
+        context.const_instruction(r_counter, Value::from(11_usize));
+        let r_copy = context.allocate_register();
+        context.mov_instruction(r_copy, r_counter);
+        context.stop_instruction();
+
+        assert_eq!(
+            context.take_debug_show().output(),
+            "f0:\n  CONST R1{counter} = 11\n  MOV R2, R1{counter}\n  STOP\n"
+        );
+    }
+
+    /// Test that jumps between blocks are recorded as edges of the control flow graph
+    #[test]
+    fn test_brillig_ir_debug_control_flow_graph() {
+        let mut context = BrilligContext::new(vec![], vec![], true);
+        context.enter_context("f0");
+        context.enter_context("f0-b0");
+        let r_condition = context.make_constant(Value::from(1_usize));
+        context.jump_if_instruction(r_condition, "f0-b1");
+        context.jump_instruction("f0-b2");
+        context.enter_context("f0-b1");
+        context.stop_instruction();
+        context.enter_context("f0-b2");
+        context.stop_instruction();
+
+        let control_flow_graph = context.take_debug_show().control_flow_graph("f0");
+        assert_eq!(
+            control_flow_graph,
+            "digraph \"f0\" {\n    node [shape=box, fontname=\"monospace\"];\n    \
+             b0 [label=\"f0:\\lCONST R1 = 1\\lJUMP_IF R1 TO f0-b1\\lJUMP_TO f0-b2\\l\"];\n    \
+             b1 [label=\"f0-b1:\\lSTOP\\l\"];\n    \
+             b2 [label=\"f0-b2:\\lSTOP\\l\"];\n    \
+             b0 -> b1;\n    b0 -> b2;\n}\n"
+        );
+    }
+
+    /// Test that the disassembly is annotated with the liveness of registers across blocks
+    #[test]
+    fn test_brillig_ir_debug_liveness() {
+        let mut context = BrilligContext::new(vec![], vec![], true);
+        context.enter_context("f0");
+        context.enter_context("f0-b0");
+        let r_lhs = context.make_constant(Value::from(1_usize));
+        let r_rhs = context.make_constant(Value::from(2_usize));
+        let r_sum = context.allocate_register();
+        context.binary_instruction(
+            r_lhs,
+            r_rhs,
+            r_sum,
+            BrilligBinaryOp::Field { op: BinaryFieldOp::Add },
+        );
+        context.jump_if_instruction(r_sum, "f0-b1");
+        context.jump_instruction("f0-b2");
+        context.enter_context("f0-b1");
+        let r_copy = context.allocate_register();
+        context.mov_instruction(r_copy, r_rhs);
+        context.stop_instruction();
+        context.enter_context("f0-b2");
+        context.stop_instruction();
+
+        assert_eq!(
+            context.take_debug_show().output_with_liveness(),
+            "f0:\n  CONST R1 = 1\n  CONST R2 = 2\n  R3 = R1 f+ R2  // last use: R1\n  \
+             JUMP_IF R3 TO f0-b1  // last use: R3\n  JUMP_TO f0-b2\n\
+             f0-b1:  // live in: R2\n  MOV R4, R2  // last use: R2; unused: R4\n  STOP\n\
+             f0-b2:\n  STOP\n"
+        );
+    }
+
+    /// Test that source locations are attached to the first line generated from them
+    #[test]
+    fn test_brillig_ir_debug_source_locations() {
+        let location = |position| Location::new(Span::single_char(position), Default::default());
+        let first_location = location(1);
+        let second_location = location(2);
+
+        let mut context = BrilligContext::new(vec![], vec![], true);
+        context.enter_context("f0");
+        context.set_location(first_location);
+        let r_value = context.make_constant(Value::from(1_usize));
+        context.mov_instruction(ReservedRegisters::user_register_index(1), r_value);
+        // No instruction is emitted for this location, so it is superseded by the next one
+        context.set_location(location(3));
+        context.set_location(second_location);
+        context.stop_instruction();
+
+        let debug_show = context.take_debug_show();
+        assert_eq!(debug_show.source_locations(), &[(1, first_location), (3, second_location)]);
+    }
+
+    /// Test that allocations are only counted towards the static heap size when their size is known
+    #[test]
+    fn test_brillig_ir_statistics() {
+        let r_pointer = ReservedRegisters::user_register_index(1);
+
+        let mut context = BrilligContext::new(vec![], vec![], false);
+        context.allocate_fixed_length_array(r_pointer, 3);
+        context.foreign_call_instruction(
+            "print".into(),
+            &[RegisterOrMemory::RegisterIndex(r_pointer)],
+            &[],
+        );
+        context.stop_instruction();
+
+        let statistics = context.artifact().statistics();
+        assert_eq!(
+            statistics,
+            BrilligFunctionStatistics {
+                num_opcodes: 5,
+                num_registers: 3,
+                num_heap_allocations: 1,
+                num_foreign_calls: 1,
+                static_heap_size: 3,
+                is_heap_size_unbounded: false,
+                max_call_depth: Some(0),
+                max_stack_size: 0,
+            }
+        );
+        assert_eq!(statistics.estimated_memory(), 6);
+
+        // The same allocation inside of a loop may be executed any number of times
+        let mut context = BrilligContext::new(vec![], vec![], false);
+        context.enter_context("f0");
+        context.allocate_fixed_length_array(r_pointer, 3);
+        context.jump_instruction("f0");
+
+        let statistics = context.artifact().statistics();
+        assert_eq!(statistics.num_heap_allocations, 1);
+        assert_eq!(statistics.static_heap_size, 0);
+        assert!(statistics.is_heap_size_unbounded);
+
+        // A size which does not fit in a usize is treated as if it were only known at runtime
+        let r_size = ReservedRegisters::user_register_index(2);
+        let mut context = BrilligContext::new(vec![], vec![], false);
+        context.const_instruction(r_size, Value::from(-FieldElement::one()));
+        context.allocate_array_instruction(r_pointer, r_size);
+
+        let statistics = context.artifact().statistics();
+        assert_eq!(statistics.num_heap_allocations, 1);
+        assert_eq!(statistics.static_heap_size, 0);
+        assert!(statistics.is_heap_size_unbounded);
+    }
 }
@@ -1,7 +1,9 @@
 use acvm::acir::brillig_vm::{Opcode as BrilligOpcode, RegisterIndex};
 use std::collections::HashMap;
 
-use crate::brillig::brillig_ir::ReservedRegisters;
+use crate::brillig::brillig_ir::{
+    constant_folding::fold_constants, dead_code::eliminate_dead_code, ReservedRegisters,
+};
 
 /// Represents a parameter or a return value of a function.
 #[derive(Debug, Clone)]
@@ -89,10 +91,12 @@ impl BrilligArtifact {
         entry_point_artifact
     }
 
-    /// Resolves all jumps and generates the final bytecode
+    /// Resolves all jumps and generates the final bytecode,
+    /// from which the opcodes that can never be executed are removed.
     pub(crate) fn finish(mut self) -> Vec<BrilligOpcode> {
         self.resolve_jumps();
-        self.byte_code
+        // Folding conditional jumps may leave some code unreachable
+        eliminate_dead_code(fold_constants(self.byte_code))
     }
 
     /// Adds the instructions needed to handle entry point parameters
@@ -262,6 +266,17 @@ impl BrilligArtifact {
         self.byte_code.len()
     }
 
+    /// Returns the position of every internal jump in the bytecode along with the position it jumps to.
+    ///
+    /// Jumps whose label is not known to this artifact are skipped.
+    pub(crate) fn resolved_jump_targets(
+        &self,
+    ) -> impl Iterator<Item = (JumpInstructionPosition, OpcodeLocation)> + '_ {
+        self.unresolved_jumps.iter().filter_map(|(position, label)| {
+            self.labels.get(label).map(|target| (*position, *target))
+        })
+    }
+
     /// Resolves all of the unresolved jumps in the program.
     ///
     /// Note: This should only be called once all blocks are processed and
@@ -0,0 +1,627 @@
+//! This module defines a textual format for Brillig bytecode, along with an assembler
+//! and a disassembler which are inverses of each other.
+//!
+//! Each line holds a single opcode, written the same way `DebugShow` writes instructions,
+//! except that integer operations always show their bit size and jumps show the location
+//! of the opcode they jump to. Jumps may also target a label, declared on its own line as
+//! `label:` before the opcode it refers to. Empty lines and lines starting with `//` are ignored.
+//!
+//! This makes it possible to write Brillig test cases by hand, and to compare generated
+//! bytecode against a golden file.
+use std::collections::HashMap;
+
+use acvm::{
+    acir::brillig_vm::{
+        BinaryFieldOp, BinaryIntOp, BlackBoxOp, HeapArray, HeapVector, Opcode as BrilligOpcode,
+        RegisterIndex, RegisterOrMemory, Value,
+    },
+    FieldElement,
+};
+use thiserror::Error;
+
+use super::{debug_show::DebugToString, ReservedRegisters};
+
+const BINARY_FIELD_OPS: [BinaryFieldOp; 5] = [
+    BinaryFieldOp::Add,
+    BinaryFieldOp::Sub,
+    BinaryFieldOp::Mul,
+    BinaryFieldOp::Div,
+    BinaryFieldOp::Equals,
+];
+
+const BINARY_INT_OPS: [BinaryIntOp; 13] = [
+    BinaryIntOp::Add,
+    BinaryIntOp::Sub,
+    BinaryIntOp::Mul,
+    BinaryIntOp::Equals,
+    BinaryIntOp::SignedDiv,
+    BinaryIntOp::UnsignedDiv,
+    BinaryIntOp::LessThan,
+    BinaryIntOp::LessThanEquals,
+    BinaryIntOp::And,
+    BinaryIntOp::Or,
+    BinaryIntOp::Xor,
+    BinaryIntOp::Shl,
+    BinaryIntOp::Shr,
+];
+
+/// An error encountered while assembling Brillig bytecode from text.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("line {line}: {kind}")]
+pub struct BrilligAssemblyError {
+    /// The line the error was found on, starting from 1
+    pub line: usize,
+    pub kind: BrilligAssemblyErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BrilligAssemblyErrorKind {
+    #[error("invalid instruction `{0}`")]
+    InvalidInstruction(String),
+    #[error("invalid operand `{0}`")]
+    InvalidOperand(String),
+    #[error("unknown label `{0}`")]
+    UnknownLabel(String),
+    #[error("label `{0}` is declared more than once")]
+    DuplicateLabel(String),
+}
+
+type AssemblyResult<T> = Result<T, BrilligAssemblyErrorKind>;
+
+/// Writes `opcodes` in the textual format, one opcode per line.
+pub fn disassemble(opcodes: &[BrilligOpcode]) -> String {
+    opcodes.iter().map(|opcode| format!("{}\n", opcode_to_string(opcode))).collect()
+}
+
+/// Writes `opcodes` in the textual format, declaring a label before each opcode which is
+/// jumped to and referring to it from the jumps instead of the location of the opcode.
+///
+/// Labels are numbered in the order of the opcodes they point to, so inserting or removing
+/// opcodes does not change the text of unrelated instructions. This makes the output suitable
+/// to compare the bytecode of two builds.
+pub fn disassemble_with_labels(opcodes: &[BrilligOpcode]) -> String {
+    let mut targets: Vec<_> = opcodes.iter().filter_map(jump_location).collect();
+    targets.sort_unstable();
+    targets.dedup();
+    let label_of = |location: usize| {
+        targets.binary_search(&location).map(|index| format!("L{index}")).unwrap_or_default()
+    };
+
+    let mut text = String::new();
+    for (location, opcode) in opcodes.iter().enumerate() {
+        if targets.binary_search(&location).is_ok() {
+            text.push_str(&format!("{}:\n", label_of(location)));
+        }
+        let line = match opcode {
+            BrilligOpcode::JumpIfNot { condition, location } => {
+                format!("JUMP_IF_NOT {} TO {}", condition.debug_to_string(), label_of(*location))
+            }
+            BrilligOpcode::JumpIf { condition, location } => {
+                format!("JUMP_IF {} TO {}", condition.debug_to_string(), label_of(*location))
+            }
+            BrilligOpcode::Jump { location } => format!("JUMP_TO {}", label_of(*location)),
+            BrilligOpcode::Call { location } => format!("CALL {}", label_of(*location)),
+            _ => opcode_to_string(opcode),
+        };
+        text.push_str(&format!("  {line}\n"));
+    }
+    // Jumps may target the location right after the last opcode
+    for target in targets.iter().filter(|target| **target >= opcodes.len()) {
+        text.push_str(&format!("{}:\n", label_of(*target)));
+    }
+    text
+}
+
+/// Parses Brillig bytecode from the textual format.
+pub fn assemble(source: &str) -> Result<Vec<BrilligOpcode>, BrilligAssemblyError> {
+    // Labels may be used before they are declared, so they are all collected first.
+    let mut labels = HashMap::new();
+    let mut instructions = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+
+        match line.strip_suffix(':').filter(|label| !label.contains(' ')) {
+            Some(label) => {
+                if labels.insert(label, instructions.len()).is_some() {
+                    return Err(BrilligAssemblyError {
+                        line: line_number,
+                        kind: BrilligAssemblyErrorKind::DuplicateLabel(label.to_owned()),
+                    });
+                }
+            }
+            None => instructions.push((line_number, line)),
+        }
+    }
+
+    let assembler = Assembler { labels };
+    instructions
+        .into_iter()
+        .map(|(line, instruction)| {
+            assembler.parse_opcode(instruction).map_err(|kind| BrilligAssemblyError { line, kind })
+        })
+        .collect()
+}
+
+fn opcode_to_string(opcode: &BrilligOpcode) -> String {
+    match opcode {
+        BrilligOpcode::BinaryFieldOp { destination, op, lhs, rhs } => format!(
+            "{} = {} {} {}",
+            destination.debug_to_string(),
+            lhs.debug_to_string(),
+            op.debug_to_string(),
+            rhs.debug_to_string()
+        ),
+        BrilligOpcode::BinaryIntOp { destination, op, bit_size, lhs, rhs } => format!(
+            "{} = {} i{}::{} {}",
+            destination.debug_to_string(),
+            lhs.debug_to_string(),
+            bit_size,
+            op.debug_to_string(),
+            rhs.debug_to_string()
+        ),
+        BrilligOpcode::JumpIfNot { condition, location } => {
+            format!("JUMP_IF_NOT {} TO {}", condition.debug_to_string(), location)
+        }
+        BrilligOpcode::JumpIf { condition, location } => {
+            format!("JUMP_IF {} TO {}", condition.debug_to_string(), location)
+        }
+        BrilligOpcode::Jump { location } => format!("JUMP_TO {location}"),
+        BrilligOpcode::Call { location } => format!("CALL {location}"),
+        BrilligOpcode::Const { destination, value } => {
+            format!("CONST {} = {}", destination.debug_to_string(), value_to_string(value))
+        }
+        BrilligOpcode::Return => "RETURN".into(),
+        BrilligOpcode::ForeignCall { function, destinations, inputs } => format!(
+            "FOREIGN_CALL {} ({}) => {}",
+            function,
+            inputs.debug_to_string(),
+            destinations.debug_to_string()
+        ),
+        BrilligOpcode::Mov { destination, source } => {
+            format!("MOV {}, {}", destination.debug_to_string(), source.debug_to_string())
+        }
+        BrilligOpcode::Load { destination, source_pointer } => format!(
+            "LOAD {} = *{}",
+            destination.debug_to_string(),
+            source_pointer.debug_to_string()
+        ),
+        BrilligOpcode::Store { destination_pointer, source } => format!(
+            "STORE *{} = {}",
+            destination_pointer.debug_to_string(),
+            source.debug_to_string()
+        ),
+        BrilligOpcode::BlackBox(op) => black_box_op_to_string(op),
+        BrilligOpcode::Trap => "TRAP".into(),
+        BrilligOpcode::Stop => "STOP".into(),
+    }
+}
+
+fn black_box_op_to_string(op: &BlackBoxOp) -> String {
+    match op {
+        BlackBoxOp::Sha256 { message, output } => {
+            format!("SHA256 {} -> {}", message.debug_to_string(), output.debug_to_string())
+        }
+        BlackBoxOp::Keccak256 { message, output } => {
+            format!("KECCAK256 {} -> {}", message.debug_to_string(), output.debug_to_string())
+        }
+        BlackBoxOp::Blake2s { message, output } => {
+            format!("BLAKE2S {} -> {}", message.debug_to_string(), output.debug_to_string())
+        }
+        BlackBoxOp::HashToField128Security { message, output } => format!(
+            "HASH_TO_FIELD_128_SECURITY {} -> {}",
+            message.debug_to_string(),
+            output.debug_to_string()
+        ),
+        BlackBoxOp::EcdsaSecp256k1 {
+            hashed_msg,
+            public_key_x,
+            public_key_y,
+            signature,
+            result,
+        } => {
+            format!(
+                "ECDSA_SECP256K1 {} {} {} {} -> {}",
+                hashed_msg.debug_to_string(),
+                public_key_x.debug_to_string(),
+                public_key_y.debug_to_string(),
+                signature.debug_to_string(),
+                result.debug_to_string()
+            )
+        }
+    }
+}
+
+/// Returns the location an opcode jumps to, if it is a jump.
+fn jump_location(opcode: &BrilligOpcode) -> Option<usize> {
+    match opcode {
+        BrilligOpcode::JumpIfNot { location, .. }
+        | BrilligOpcode::JumpIf { location, .. }
+        | BrilligOpcode::Jump { location }
+        | BrilligOpcode::Call { location } => Some(*location),
+        _ => None,
+    }
+}
+
+/// Unlike `DebugShow`, values are written in full so that they can be read back.
+fn value_to_string(value: &Value) -> String {
+    let field = value.to_field();
+    if field.fits_in_u128() {
+        field.to_u128().to_string()
+    } else {
+        format!("0x{}", field.to_hex())
+    }
+}
+
+struct Assembler<'a> {
+    /// Maps each declared label to the location of the opcode following it.
+    labels: HashMap<&'a str, usize>,
+}
+
+impl Assembler<'_> {
+    fn parse_opcode(&self, instruction: &str) -> AssemblyResult<BrilligOpcode> {
+        let invalid = || BrilligAssemblyErrorKind::InvalidInstruction(instruction.to_owned());
+        let (mnemonic, operands) = instruction.split_once(' ').unwrap_or((instruction, ""));
+
+        let opcode = match mnemonic {
+            "RETURN" | "STOP" | "TRAP" if !operands.is_empty() => return Err(invalid()),
+            "RETURN" => BrilligOpcode::Return,
+            "STOP" => BrilligOpcode::Stop,
+            "TRAP" => BrilligOpcode::Trap,
+            "JUMP_TO" => BrilligOpcode::Jump { location: self.parse_location(operands)? },
+            "CALL" => BrilligOpcode::Call { location: self.parse_location(operands)? },
+            "JUMP_IF" | "JUMP_IF_NOT" => {
+                let (condition, target) = operands.split_once(" TO ").ok_or_else(invalid)?;
+                let condition = parse_register(condition)?;
+                let location = self.parse_location(target)?;
+                if mnemonic == "JUMP_IF" {
+                    BrilligOpcode::JumpIf { condition, location }
+                } else {
+                    BrilligOpcode::JumpIfNot { condition, location }
+                }
+            }
+            "CONST" => {
+                let (destination, value) = operands.split_once(" = ").ok_or_else(invalid)?;
+                BrilligOpcode::Const {
+                    destination: parse_register(destination)?,
+                    value: parse_value(value)?,
+                }
+            }
+            "MOV" => {
+                let (destination, source) = operands.split_once(", ").ok_or_else(invalid)?;
+                BrilligOpcode::Mov {
+                    destination: parse_register(destination)?,
+                    source: parse_register(source)?,
+                }
+            }
+            "LOAD" => {
+                let (destination, source_pointer) =
+                    operands.split_once(" = *").ok_or_else(invalid)?;
+                BrilligOpcode::Load {
+                    destination: parse_register(destination)?,
+                    source_pointer: parse_register(source_pointer)?,
+                }
+            }
+            "STORE" => {
+                let (destination_pointer, source) = operands
+                    .strip_prefix('*')
+                    .and_then(|operands| operands.split_once(" = "))
+                    .ok_or_else(invalid)?;
+                BrilligOpcode::Store {
+                    destination_pointer: parse_register(destination_pointer)?,
+                    source: parse_register(source)?,
+                }
+            }
+            "FOREIGN_CALL" => {
+                let (function, operands) = operands.split_once(" (").ok_or_else(invalid)?;
+                let (inputs, destinations) = operands.split_once(") =>").ok_or_else(invalid)?;
+                BrilligOpcode::ForeignCall {
+                    function: function.to_owned(),
+                    destinations: parse_operand_list(destinations)?,
+                    inputs: parse_operand_list(inputs)?,
+                }
+            }
+            "SHA256" | "KECCAK256" | "BLAKE2S" | "HASH_TO_FIELD_128_SECURITY" => {
+                let (message, output) = operands.split_once(" -> ").ok_or_else(invalid)?;
+                let message = parse_heap_vector(message)?;
+                BrilligOpcode::BlackBox(match mnemonic {
+                    "SHA256" => BlackBoxOp::Sha256 { message, output: parse_heap_array(output)? },
+                    "KECCAK256" => {
+                        BlackBoxOp::Keccak256 { message, output: parse_heap_array(output)? }
+                    }
+                    "BLAKE2S" => BlackBoxOp::Blake2s { message, output: parse_heap_array(output)? },
+                    _ => BlackBoxOp::HashToField128Security {
+                        message,
+                        output: parse_register(output)?,
+                    },
+                })
+            }
+            "ECDSA_SECP256K1" => {
+                let (inputs, result) = operands.split_once(" -> ").ok_or_else(invalid)?;
+                let inputs: Vec<_> = inputs.split(' ').collect();
+                match inputs.as_slice() {
+                    [hashed_msg, public_key_x, public_key_y, signature] => {
+                        BrilligOpcode::BlackBox(BlackBoxOp::EcdsaSecp256k1 {
+                            hashed_msg: parse_heap_vector(hashed_msg)?,
+                            public_key_x: parse_heap_array(public_key_x)?,
+                            public_key_y: parse_heap_array(public_key_y)?,
+                            signature: parse_heap_array(signature)?,
+                            result: parse_register(result)?,
+                        })
+                    }
+                    _ => return Err(invalid()),
+                }
+            }
+            _ => parse_binary_op(instruction).ok_or_else(invalid)??,
+        };
+        Ok(opcode)
+    }
+
+    /// Parses a jump target, either as the location of an opcode or as a label.
+    fn parse_location(&self, target: &str) -> AssemblyResult<usize> {
+        let target = target.trim();
+        if let Ok(location) = target.parse() {
+            return Ok(location);
+        }
+        self.labels
+            .get(target)
+            .copied()
+            .ok_or_else(|| BrilligAssemblyErrorKind::UnknownLabel(target.to_owned()))
+    }
+}
+
+/// Parses an instruction of the form `destination = lhs op rhs`,
+/// returning `None` if the instruction does not have this shape.
+fn parse_binary_op(instruction: &str) -> Option<AssemblyResult<BrilligOpcode>> {
+    let tokens: Vec<_> = instruction.split_whitespace().collect();
+    let (destination, lhs, op, rhs) = match tokens.as_slice() {
+        [destination, "=", lhs, op, rhs] => (*destination, *lhs, *op, *rhs),
+        _ => return None,
+    };
+
+    let parse = || {
+        let destination = parse_register(destination)?;
+        let lhs = parse_register(lhs)?;
+        let rhs = parse_register(rhs)?;
+        let invalid_op = || BrilligAssemblyErrorKind::InvalidOperand(op.to_owned());
+
+        match op.strip_prefix('i').and_then(|op| op.split_once("::")) {
+            Some((bit_size, int_op)) => {
+                let bit_size = bit_size.parse().map_err(|_| invalid_op())?;
+                let op = BINARY_INT_OPS
+                    .iter()
+                    .find(|candidate| candidate.debug_to_string() == int_op)
+                    .cloned()
+                    .ok_or_else(invalid_op)?;
+                Ok(BrilligOpcode::BinaryIntOp { destination, op, bit_size, lhs, rhs })
+            }
+            None => {
+                let op = BINARY_FIELD_OPS
+                    .iter()
+                    .find(|candidate| candidate.debug_to_string() == op)
+                    .cloned()
+                    .ok_or_else(invalid_op)?;
+                Ok(BrilligOpcode::BinaryFieldOp { destination, op, lhs, rhs })
+            }
+        }
+    };
+    Some(parse())
+}
+
+fn parse_register(token: &str) -> AssemblyResult<RegisterIndex> {
+    let token = token.trim();
+    let register = if token == ReservedRegisters::stack_pointer().debug_to_string() {
+        Some(ReservedRegisters::stack_pointer())
+    } else {
+        token
+            .strip_prefix('R')
+            .and_then(|index| index.parse::<usize>().ok())
+            .map(RegisterIndex::from)
+    };
+    register.ok_or_else(|| BrilligAssemblyErrorKind::InvalidOperand(token.to_owned()))
+}
+
+/// Splits an operand of the form `pointer[0..size]` into its pointer and its size.
+fn split_heap_operand(token: &str) -> AssemblyResult<(RegisterIndex, &str)> {
+    let token = token.trim();
+    let (pointer, size) = token
+        .strip_suffix(']')
+        .and_then(|token| token.split_once("[0.."))
+        .ok_or_else(|| BrilligAssemblyErrorKind::InvalidOperand(token.to_owned()))?;
+    Ok((parse_register(pointer)?, size))
+}
+
+fn parse_heap_array(token: &str) -> AssemblyResult<HeapArray> {
+    let (pointer, size) = split_heap_operand(token)?;
+    let size =
+        size.parse().map_err(|_| BrilligAssemblyErrorKind::InvalidOperand(token.to_owned()))?;
+    Ok(HeapArray { pointer, size })
+}
+
+fn parse_heap_vector(token: &str) -> AssemblyResult<HeapVector> {
+    let (pointer, size) = split_heap_operand(token)?;
+    Ok(HeapVector { pointer, size: parse_register(size)? })
+}
+
+fn parse_register_or_memory(token: &str) -> AssemblyResult<RegisterOrMemory> {
+    if !token.contains('[') {
+        parse_register(token).map(RegisterOrMemory::RegisterIndex)
+    } else if let Ok(heap_array) = parse_heap_array(token) {
+        Ok(RegisterOrMemory::HeapArray(heap_array))
+    } else {
+        parse_heap_vector(token).map(RegisterOrMemory::HeapVector)
+    }
+}
+
+fn parse_operand_list(operands: &str) -> AssemblyResult<Vec<RegisterOrMemory>> {
+    let operands = operands.trim();
+    if operands.is_empty() {
+        return Ok(Vec::new());
+    }
+    operands.split(", ").map(parse_register_or_memory).collect()
+}
+
+fn parse_value(token: &str) -> AssemblyResult<Value> {
+    let token = token.trim();
+    let field = match token.strip_prefix("0x") {
+        Some(hex) => FieldElement::from_hex(hex),
+        None => token.parse::<u128>().ok().map(FieldElement::from),
+    };
+    field.map(Value::from).ok_or_else(|| BrilligAssemblyErrorKind::InvalidOperand(token.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::brillig_vm::{
+        BinaryFieldOp, BinaryIntOp, BlackBoxOp, HeapArray, HeapVector, Opcode as BrilligOpcode,
+        RegisterIndex, RegisterOrMemory, Registers, VMStatus, Value, VM,
+    };
+    use acvm::FieldElement;
+
+    use super::{
+        assemble, disassemble, disassemble_with_labels, BrilligAssemblyError,
+        BrilligAssemblyErrorKind,
+    };
+
+    /// Test that every kind of opcode survives a round trip through the textual format
+    #[test]
+    fn test_brillig_assembly_round_trip() {
+        let r = RegisterIndex::from;
+        let opcodes = vec![
+            BrilligOpcode::Const { destination: r(0), value: Value::from(3_usize) },
+            BrilligOpcode::Const { destination: r(1), value: Value::from(-FieldElement::one()) },
+            BrilligOpcode::BinaryFieldOp {
+                destination: r(2),
+                op: BinaryFieldOp::Div,
+                lhs: r(1),
+                rhs: r(3),
+            },
+            BrilligOpcode::BinaryIntOp {
+                destination: r(2),
+                op: BinaryIntOp::UnsignedDiv,
+                bit_size: 64,
+                lhs: r(1),
+                rhs: r(3),
+            },
+            BrilligOpcode::JumpIf { condition: r(2), location: 0 },
+            BrilligOpcode::JumpIfNot { condition: r(2), location: 11 },
+            BrilligOpcode::Jump { location: 3 },
+            BrilligOpcode::Call { location: 12 },
+            BrilligOpcode::Mov { destination: r(4), source: r(0) },
+            BrilligOpcode::Load { destination: r(5), source_pointer: r(4) },
+            BrilligOpcode::Store { destination_pointer: r(4), source: r(5) },
+            BrilligOpcode::ForeignCall {
+                function: "oracle".into(),
+                destinations: vec![RegisterOrMemory::HeapVector(HeapVector {
+                    pointer: r(6),
+                    size: r(7),
+                })],
+                inputs: vec![
+                    RegisterOrMemory::RegisterIndex(r(5)),
+                    RegisterOrMemory::HeapArray(HeapArray { pointer: r(4), size: 2 }),
+                ],
+            },
+            BrilligOpcode::ForeignCall {
+                function: "print".into(),
+                destinations: vec![],
+                inputs: vec![],
+            },
+            BrilligOpcode::BlackBox(BlackBoxOp::Sha256 {
+                message: HeapVector { pointer: r(4), size: r(5) },
+                output: HeapArray { pointer: r(6), size: 32 },
+            }),
+            BrilligOpcode::BlackBox(BlackBoxOp::HashToField128Security {
+                message: HeapVector { pointer: r(4), size: r(5) },
+                output: r(6),
+            }),
+            BrilligOpcode::BlackBox(BlackBoxOp::EcdsaSecp256k1 {
+                hashed_msg: HeapVector { pointer: r(4), size: r(5) },
+                public_key_x: HeapArray { pointer: r(6), size: 32 },
+                public_key_y: HeapArray { pointer: r(7), size: 32 },
+                signature: HeapArray { pointer: r(8), size: 64 },
+                result: r(9),
+            }),
+            BrilligOpcode::Return,
+            BrilligOpcode::Trap,
+            BrilligOpcode::Stop,
+        ];
+
+        let text = disassemble(&opcodes);
+        assert_eq!(assemble(&text), Ok(opcodes));
+    }
+
+    /// Test that a hand written program using labels runs as expected
+    #[test]
+    fn test_brillig_assembly_labels() {
+        // Sums the integers from 0 to 4 into R1, then traps unless the sum is 10
+        let source = "
+            CONST R1 = 0
+            CONST R2 = 0
+            CONST R3 = 5
+            CONST R4 = 1
+            loop:
+            R5 = R2 i32::< R3
+            JUMP_IF_NOT R5 TO done
+            R1 = R1 i32::+ R2
+            R2 = R2 i32::+ R4
+            JUMP_TO loop
+            // The loop jumps here once R2 reaches R3
+            done:
+            CONST R6 = 10
+            R7 = R1 i32::== R6
+            JUMP_IF R7 TO end
+            TRAP
+            end:
+            STOP
+        ";
+        let opcodes = assemble(source).expect("should assemble");
+        assert_eq!(
+            opcodes[5],
+            BrilligOpcode::JumpIfNot { condition: RegisterIndex::from(5), location: 9 }
+        );
+
+        // Labels written by the disassembler can be read back as well
+        let text = disassemble_with_labels(&opcodes);
+        assert!(text.contains("L0:\n  R5 = R2 i32::< R3\n  JUMP_IF_NOT R5 TO L1\n"));
+        assert_eq!(assemble(&text).as_ref(), Ok(&opcodes));
+
+        let mut vm = VM::new(Registers { inner: vec![] }, vec![], opcodes, vec![]);
+        assert_eq!(vm.process_opcodes(), VMStatus::Finished);
+    }
+
+    /// Test that errors point to the offending line
+    #[test]
+    fn test_brillig_assembly_errors() {
+        assert_eq!(
+            assemble("STOP\nJUMP_TO nowhere"),
+            Err(BrilligAssemblyError {
+                line: 2,
+                kind: BrilligAssemblyErrorKind::UnknownLabel("nowhere".into())
+            })
+        );
+        assert_eq!(
+            assemble("MOV R1, X2"),
+            Err(BrilligAssemblyError {
+                line: 1,
+                kind: BrilligAssemblyErrorKind::InvalidOperand("X2".into())
+            })
+        );
+        assert_eq!(
+            assemble("a:\na:\nSTOP"),
+            Err(BrilligAssemblyError {
+                line: 2,
+                kind: BrilligAssemblyErrorKind::DuplicateLabel("a".into())
+            })
+        );
+        assert_eq!(
+            assemble("HALT"),
+            Err(BrilligAssemblyError {
+                line: 1,
+                kind: BrilligAssemblyErrorKind::InvalidInstruction("HALT".into())
+            })
+        );
+    }
+}
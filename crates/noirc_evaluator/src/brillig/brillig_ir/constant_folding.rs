@@ -0,0 +1,278 @@
+//! This module folds the opcodes of linked Brillig bytecode whose operands are known at compile time.
+//!
+//! The values written by `CONST` opcodes are followed through straight-line code. Binary
+//! operations on known values are evaluated by running them on the Brillig VM, so that they
+//! follow the runtime semantics exactly, and replaced by a `CONST` of their result. Conditional
+//! jumps on known conditions are resolved, and the constants which are overwritten before
+//! being read are removed.
+use std::collections::{HashMap, HashSet};
+
+use acvm::acir::brillig_vm::{
+    BinaryFieldOp, BinaryIntOp, Opcode as BrilligOpcode, RegisterIndex, Registers, VMStatus, Value,
+    VM,
+};
+
+use super::{artifact::OpcodeLocation, dead_code::remove_opcodes, ReservedRegisters};
+
+/// Folds the opcodes of `byte_code` whose operands are all known constants.
+///
+/// This must be called on bytecode whose jumps have all been resolved.
+pub(crate) fn fold_constants(mut byte_code: Vec<BrilligOpcode>) -> Vec<BrilligOpcode> {
+    let block_starts = block_starts(&byte_code);
+    let mut keep = vec![true; byte_code.len()];
+
+    // The known values of registers, by register index
+    let mut known_values: HashMap<usize, Value> = HashMap::new();
+    for (location, opcode) in byte_code.iter_mut().enumerate() {
+        if block_starts.contains(&location) {
+            known_values.clear();
+        }
+        let known = |register: &RegisterIndex| known_values.get(&register.to_usize()).copied();
+
+        match opcode {
+            BrilligOpcode::Const { destination, value } => {
+                let value = *value;
+                set_known_value(&mut known_values, *destination, Some(value));
+            }
+            BrilligOpcode::Mov { destination, source } => {
+                let value = known(source);
+                set_known_value(&mut known_values, *destination, value);
+            }
+            BrilligOpcode::BinaryFieldOp { destination, lhs, rhs, .. }
+            | BrilligOpcode::BinaryIntOp { destination, lhs, rhs, .. } => {
+                let destination = *destination;
+                let value = match (known(lhs), known(rhs)) {
+                    (Some(lhs), Some(rhs)) => evaluate_binary_op(opcode, lhs, rhs),
+                    _ => None,
+                };
+                if let (Some(value), false) = (value, is_reserved(destination)) {
+                    *opcode = BrilligOpcode::Const { destination, value };
+                }
+                set_known_value(&mut known_values, destination, value);
+            }
+            BrilligOpcode::Load { destination, .. } => {
+                let destination = *destination;
+                set_known_value(&mut known_values, destination, None);
+            }
+            BrilligOpcode::JumpIf { condition, location: target } => {
+                match known(condition).map(|condition| condition.to_field().is_zero()) {
+                    Some(false) => *opcode = BrilligOpcode::Jump { location: *target },
+                    Some(true) => keep[location] = false,
+                    None => (),
+                }
+            }
+            BrilligOpcode::JumpIfNot { condition, location: target } => {
+                match known(condition).map(|condition| condition.to_field().is_zero()) {
+                    Some(true) => *opcode = BrilligOpcode::Jump { location: *target },
+                    Some(false) => keep[location] = false,
+                    None => (),
+                }
+            }
+            BrilligOpcode::Store { .. } | BrilligOpcode::Jump { .. } => (),
+            // Calls, foreign calls and black boxes may write any register
+            _ => known_values.clear(),
+        }
+    }
+
+    remove_overwritten_constants(&byte_code, &block_starts, &mut keep);
+    remove_opcodes(byte_code, keep)
+}
+
+/// Records the value of a register, if known. The values of reserved registers are never tracked.
+fn set_known_value(
+    known_values: &mut HashMap<usize, Value>,
+    register: RegisterIndex,
+    value: Option<Value>,
+) {
+    match value {
+        Some(value) if !is_reserved(register) => known_values.insert(register.to_usize(), value),
+        _ => known_values.remove(&register.to_usize()),
+    };
+}
+
+fn is_reserved(register: RegisterIndex) -> bool {
+    register.to_usize() < ReservedRegisters::len()
+}
+
+/// Evaluates a binary operation on known operands by running it on the Brillig VM.
+///
+/// Returns `None` if the operation fails, so that it fails at runtime instead.
+fn evaluate_binary_op(opcode: &BrilligOpcode, lhs: Value, rhs: Value) -> Option<Value> {
+    // Divisions by zero are not evaluated as the VM may not fail gracefully on them
+    let is_division = matches!(
+        opcode,
+        BrilligOpcode::BinaryFieldOp { op: BinaryFieldOp::Div, .. }
+            | BrilligOpcode::BinaryIntOp {
+                op: BinaryIntOp::SignedDiv | BinaryIntOp::UnsignedDiv,
+                ..
+            }
+    );
+    if is_division && rhs.to_field().is_zero() {
+        return None;
+    }
+
+    let (lhs_register, rhs_register, destination) =
+        (RegisterIndex::from(0), RegisterIndex::from(1), RegisterIndex::from(2));
+    let opcode = match opcode {
+        BrilligOpcode::BinaryFieldOp { op, .. } => BrilligOpcode::BinaryFieldOp {
+            op: *op,
+            lhs: lhs_register,
+            rhs: rhs_register,
+            destination,
+        },
+        BrilligOpcode::BinaryIntOp { op, bit_size, .. } => BrilligOpcode::BinaryIntOp {
+            op: *op,
+            bit_size: *bit_size,
+            lhs: lhs_register,
+            rhs: rhs_register,
+            destination,
+        },
+        _ => unreachable!("ICE: only binary operations can be evaluated"),
+    };
+
+    let registers = Registers { inner: vec![lhs, rhs, Value::from(0_usize)] };
+    let mut vm = VM::new(registers, vec![], vec![opcode], vec![]);
+    match vm.process_opcodes() {
+        VMStatus::Finished => Some(vm.get_registers().get(destination)),
+        _ => None,
+    }
+}
+
+/// Returns the locations which can be jumped to, where the values of registers are unknown.
+fn block_starts(byte_code: &[BrilligOpcode]) -> HashSet<OpcodeLocation> {
+    byte_code
+        .iter()
+        .filter_map(|opcode| match opcode {
+            BrilligOpcode::Jump { location }
+            | BrilligOpcode::JumpIf { location, .. }
+            | BrilligOpcode::JumpIfNot { location, .. }
+            | BrilligOpcode::Call { location } => Some(*location),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Marks the `CONST` opcodes whose register is overwritten before being read for removal.
+///
+/// Only straight-line code is considered: any register may be read after a jump,
+/// a call or the end of the program.
+fn remove_overwritten_constants(
+    byte_code: &[BrilligOpcode],
+    block_starts: &HashSet<OpcodeLocation>,
+    keep: &mut [bool],
+) {
+    // The registers which are written before being read by the opcodes following the current one
+    let mut overwritten: HashSet<usize> = HashSet::new();
+    for (location, opcode) in byte_code.iter().enumerate().rev() {
+        match opcode {
+            // Opcodes already removed neither read nor write registers
+            _ if !keep[location] => (),
+            BrilligOpcode::Const { destination, .. } => {
+                if !overwritten.insert(destination.to_usize()) {
+                    keep[location] = false;
+                }
+            }
+            BrilligOpcode::Mov { destination, source } => {
+                overwritten.insert(destination.to_usize());
+                overwritten.remove(&source.to_usize());
+            }
+            BrilligOpcode::BinaryFieldOp { destination, lhs, rhs, .. }
+            | BrilligOpcode::BinaryIntOp { destination, lhs, rhs, .. } => {
+                overwritten.insert(destination.to_usize());
+                overwritten.remove(&lhs.to_usize());
+                overwritten.remove(&rhs.to_usize());
+            }
+            BrilligOpcode::Load { destination, source_pointer } => {
+                overwritten.insert(destination.to_usize());
+                overwritten.remove(&source_pointer.to_usize());
+            }
+            BrilligOpcode::Store { destination_pointer, source } => {
+                overwritten.remove(&destination_pointer.to_usize());
+                overwritten.remove(&source.to_usize());
+            }
+            _ => overwritten.clear(),
+        }
+        if block_starts.contains(&location) {
+            overwritten.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::brillig::brillig_ir::assembly::assemble;
+
+    use super::fold_constants;
+
+    #[test]
+    fn folds_known_operations() {
+        let byte_code = assemble(
+            "
+            CONST R1 = 2
+            CONST R2 = 3
+            R3 = R1 i32::* R2
+            R3 = R3 i32::+ R1
+            MOV R4, R3
+            JUMP_IF_NOT R4 TO end
+            R5 = R4 f/ R6
+            STOP
+            end:
+            TRAP
+            ",
+        )
+        .unwrap();
+        // The first constants are not overwritten, so they are kept for the code after `STOP`
+        let expected = assemble(
+            "
+            CONST R1 = 2
+            CONST R2 = 3
+            CONST R3 = 8
+            MOV R4, R3
+            R5 = R4 f/ R6
+            STOP
+            end:
+            TRAP
+            ",
+        )
+        .unwrap();
+        assert_eq!(fold_constants(byte_code), expected);
+    }
+
+    #[test]
+    fn removes_overwritten_constants() {
+        let byte_code = assemble(
+            "
+            CONST R1 = 2
+            CONST R2 = 3
+            R1 = R1 f+ R2
+            R1 = R1 f+ R2
+            R2 = R6 f+ R1
+            STOP
+            ",
+        )
+        .unwrap();
+        let expected = assemble(
+            "
+            CONST R1 = 8
+            R2 = R6 f+ R1
+            STOP
+            ",
+        )
+        .unwrap();
+        assert_eq!(fold_constants(byte_code), expected);
+    }
+
+    #[test]
+    fn does_not_fold_divisions_by_zero() {
+        let byte_code = assemble(
+            "
+            CONST R1 = 2
+            CONST R2 = 0
+            R3 = R1 i32::/ R2
+            STOP
+            ",
+        )
+        .unwrap();
+        assert_eq!(fold_constants(byte_code.clone()), byte_code);
+    }
+}
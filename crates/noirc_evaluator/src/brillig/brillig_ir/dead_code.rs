@@ -0,0 +1,132 @@
+//! This module removes the opcodes of linked Brillig bytecode which can never be executed.
+//!
+//! Functions are only linked when they are called, but a call may itself sit in a block
+//! which is never reached, e.g. behind a condition which is always false. Removing the
+//! unreachable opcodes drops such blocks along with the functions only called from them.
+use acvm::acir::brillig_vm::Opcode as BrilligOpcode;
+
+use super::artifact::OpcodeLocation;
+
+/// Removes the opcodes of `byte_code` which are not reachable from its first opcode,
+/// updating the locations of all jumps and calls accordingly.
+///
+/// This must be called on bytecode whose jumps have all been resolved.
+pub(crate) fn eliminate_dead_code(byte_code: Vec<BrilligOpcode>) -> Vec<BrilligOpcode> {
+    let is_reachable = reachable_opcodes(&byte_code);
+    remove_opcodes(byte_code, is_reachable)
+}
+
+/// Removes the opcodes of `byte_code` which are not kept, updating the locations of all
+/// jumps and calls accordingly. A jump to a removed opcode goes to the next kept one instead.
+pub(super) fn remove_opcodes(byte_code: Vec<BrilligOpcode>, keep: Vec<bool>) -> Vec<BrilligOpcode> {
+    if keep.iter().all(|kept| *kept) {
+        return byte_code;
+    }
+
+    // Maps the location of each opcode in the original bytecode to its location once opcodes are removed
+    let mut new_locations = Vec::with_capacity(byte_code.len() + 1);
+    let mut num_kept = 0;
+    for kept in &keep {
+        new_locations.push(num_kept);
+        num_kept += usize::from(*kept);
+    }
+    // Jumping past the last opcode ends the execution, which must remain the case
+    new_locations.push(num_kept);
+    let new_location = |location: OpcodeLocation| {
+        new_locations.get(location).copied().unwrap_or(num_kept + location - byte_code.len())
+    };
+
+    byte_code
+        .iter()
+        .zip(keep)
+        .filter(|(_, kept)| *kept)
+        .map(|(opcode, _)| match opcode {
+            BrilligOpcode::Jump { location } => {
+                BrilligOpcode::Jump { location: new_location(*location) }
+            }
+            BrilligOpcode::JumpIf { condition, location } => {
+                BrilligOpcode::JumpIf { condition: *condition, location: new_location(*location) }
+            }
+            BrilligOpcode::JumpIfNot { condition, location } => BrilligOpcode::JumpIfNot {
+                condition: *condition,
+                location: new_location(*location),
+            },
+            BrilligOpcode::Call { location } => {
+                BrilligOpcode::Call { location: new_location(*location) }
+            }
+            _ => opcode.clone(),
+        })
+        .collect()
+}
+
+/// Returns whether each opcode of `byte_code` can be reached from the first opcode.
+fn reachable_opcodes(byte_code: &[BrilligOpcode]) -> Vec<bool> {
+    let mut is_reachable = vec![false; byte_code.len()];
+    let mut to_visit = vec![0];
+
+    while let Some(location) = to_visit.pop() {
+        if location >= byte_code.len() || is_reachable[location] {
+            continue;
+        }
+        is_reachable[location] = true;
+
+        match &byte_code[location] {
+            BrilligOpcode::Jump { location } => to_visit.push(*location),
+            // Calls return to the next opcode
+            BrilligOpcode::JumpIf { location: target, .. }
+            | BrilligOpcode::JumpIfNot { location: target, .. }
+            | BrilligOpcode::Call { location: target } => {
+                to_visit.push(*target);
+                to_visit.push(location + 1);
+            }
+            BrilligOpcode::Return | BrilligOpcode::Stop | BrilligOpcode::Trap => (),
+            _ => to_visit.push(location + 1),
+        }
+    }
+    is_reachable
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::brillig::brillig_ir::assembly::assemble;
+
+    use super::eliminate_dead_code;
+
+    #[test]
+    fn removes_unreachable_blocks_and_functions() {
+        let byte_code = assemble(
+            "
+            CONST R1 = 0
+            CALL used_function
+            STOP
+            dead_block:
+            CALL unused_function
+            STOP
+            unused_function:
+            CONST R2 = 2
+            RETURN
+            used_function:
+            CONST R2 = 1
+            JUMP_TO end
+            CONST R2 = 3
+            end:
+            RETURN
+            ",
+        )
+        .unwrap();
+        let expected = assemble(
+            "
+            CONST R1 = 0
+            CALL used_function
+            STOP
+            used_function:
+            CONST R2 = 1
+            JUMP_TO end
+            end:
+            RETURN
+            ",
+        )
+        .unwrap();
+        assert_eq!(eliminate_dead_code(byte_code), expected);
+    }
+}
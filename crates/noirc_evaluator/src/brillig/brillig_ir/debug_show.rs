@@ -1,16 +1,19 @@
 ///! This module contains functions for producing a higher level view disassembler of Brillig.
 use super::BrilligBinaryOp;
 use crate::brillig::brillig_ir::{ReservedRegisters, BRILLIG_MEMORY_ADDRESSING_BIT_SIZE};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Write,
+};
+
 use acvm::acir::brillig_vm::{
     BinaryFieldOp, BinaryIntOp, BlackBoxOp, HeapArray, HeapVector, RegisterIndex, RegisterOrMemory,
     Value,
 };
-
-/// Controls whether debug traces are enabled
-const ENABLE_DEBUG_TRACE: bool = true;
+use noirc_errors::Location;
 
 /// Trait for converting values into debug-friendly strings.
-trait DebugToString {
+pub(super) trait DebugToString {
     fn debug_to_string(&self) -> String;
 }
 
@@ -101,6 +104,8 @@ impl DebugToString for BrilligBinaryOp {
                     format!("{}:{}", op, bit_size)
                 }
             }
+            BrilligBinaryOp::SignedLessThan { bit_size } => format!("i{}::<s", bit_size),
+            BrilligBinaryOp::ArithmeticShiftRight { bit_size } => format!("i{}::>>s", bit_size),
         }
     }
 }
@@ -128,157 +133,663 @@ impl<T: DebugToString> DebugToString for [T] {
 }
 
 macro_rules! debug_println {
-    ( $literal:expr ) => {
-        if ENABLE_DEBUG_TRACE {
-            println!("{}", $literal);
+    ( $debug_show:expr, $literal:expr ) => {
+        if $debug_show.enable_debug_trace {
+            $debug_show.push_line(format!("{}", $literal));
         }
     };
-    ( $format_message:expr, $( $x:expr ),* ) => {
-        if ENABLE_DEBUG_TRACE {
-            println!($format_message, $( $x.debug_to_string(), )*)
+    ( $debug_show:expr, $format_message:expr, $( $x:expr ),* ) => {
+        if $debug_show.enable_debug_trace {
+            $debug_show.push_line(format!($format_message, $( $x.debug_to_string(), )*));
         }
     };
 }
 
-/// Emits brillig bytecode to jump to a trap condition if `condition`
-/// is false.
-pub(crate) fn constrain_instruction(condition: RegisterIndex) {
-    debug_println!("  ASSERT {} != 0", condition);
+/// Accumulates a human-readable disassembly of the Brillig
+/// instructions emitted by a `BrilligContext`.
+///
+/// Each function is compiled with its own context, so the output
+/// of a `DebugShow` is the disassembly of a single function, with
+/// each of its blocks introduced by its label.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct DebugShow {
+    enable_debug_trace: bool,
+    output: String,
+    /// The number of lines written to `output` so far.
+    num_lines: usize,
+    /// Pairs of the index of the first line of `output` generated from a source location
+    /// and that location, in increasing line order.
+    source_locations: Vec<(usize, Location)>,
+    /// The blocks of straight-line code seen so far, in emission order.
+    blocks: Vec<DebugShowBlock>,
+    /// Maps every label that has been entered to the index of the block it starts.
+    label_to_block: HashMap<String, usize>,
+    /// Pairs of the index of each label line in `output` and that label.
+    label_lines: Vec<(usize, String)>,
+    /// Names of the Noir variables held by registers, shown next to the register.
+    register_names: HashMap<usize, String>,
+}
+
+/// A line of disassembly along with the registers read and written by the instruction.
+///
+/// Registers are stored by index, and reserved registers are left out.
+#[derive(Debug, Default, Clone)]
+struct DebugShowInstruction {
+    text: String,
+    /// The index of the line of the output the instruction was written to.
+    line: usize,
+    defined: Vec<usize>,
+    used: Vec<usize>,
+}
+
+impl DebugShowInstruction {
+    /// Turns the set of registers live after this instruction into the set live before it.
+    fn update_live_registers(&self, live_registers: &mut BTreeSet<usize>) {
+        for register in &self.defined {
+            live_registers.remove(register);
+        }
+        live_registers.extend(&self.used);
+    }
 }
 
-/// Processes a return instruction.
-pub(crate) fn return_instruction(return_registers: &[RegisterIndex]) {
-    let registers_string = return_registers
-        .iter()
-        .map(RegisterIndex::debug_to_string)
-        .collect::<Vec<String>>()
-        .join(", ");
-
-    debug_println!("  // return {};", registers_string);
+/// A block of straight-line instructions in the control flow graph of a function.
+#[derive(Debug, Default, Clone)]
+struct DebugShowBlock {
+    label: String,
+    instructions: Vec<DebugShowInstruction>,
+    /// Labels of the blocks which control can flow to at the end of this block.
+    successors: Vec<String>,
+    /// True if the block ends with an unconditional jump or a stop,
+    /// meaning that control cannot fall through to the next block.
+    is_terminated: bool,
 }
 
-/// Emits a `mov` instruction.
-pub(crate) fn mov_instruction(destination: RegisterIndex, source: RegisterIndex) {
-    debug_println!("  MOV {}, {}", destination, source);
-}
+impl DebugShow {
+    /// Creates a `DebugShow` which only records instructions if `enable_debug_trace` is set
+    pub(crate) fn new(enable_debug_trace: bool) -> DebugShow {
+        DebugShow { enable_debug_trace, ..Default::default() }
+    }
 
-/// Processes a binary instruction according `operation`.
-pub(crate) fn binary_instruction(
-    lhs: RegisterIndex,
-    rhs: RegisterIndex,
-    result: RegisterIndex,
-    operation: BrilligBinaryOp,
-) {
-    debug_println!("  {} = {} {} {}", result, lhs, operation, rhs);
-}
+    /// Returns the disassembly recorded so far
+    pub(crate) fn output(&self) -> &str {
+        &self.output
+    }
 
-/// Stores the value of `constant` in the `result` register
-pub(crate) fn const_instruction(result: RegisterIndex, constant: Value) {
-    debug_println!("  CONST {} = {}", result, constant);
-}
+    /// Returns the source locations of the disassembly recorded so far,
+    /// as pairs of the index of the first line generated from a location and that location.
+    pub(crate) fn source_locations(&self) -> &[(usize, Location)] {
+        &self.source_locations
+    }
 
-/// Processes a not instruction. Append with "_" as this is a high-level instruction.
-pub(crate) fn not_instruction(condition: RegisterIndex, bit_size: u32, result: RegisterIndex) {
-    debug_println!("  i{}_NOT {} = !{}", bit_size, result, condition);
-}
+    /// Records that the following instructions are generated from the Noir code at `location`
+    pub(crate) fn set_location(&mut self, location: Location) {
+        if !self.enable_debug_trace {
+            return;
+        }
+        match self.source_locations.last_mut() {
+            Some((_, last_location)) if *last_location == location => (),
+            // Nothing was emitted for the previous location, so the new one supersedes it
+            Some((line, last_location)) if *line == self.num_lines => *last_location = location,
+            _ => self.source_locations.push((self.num_lines, location)),
+        }
+    }
 
-/// Processes a foreign call instruction.
-pub(crate) fn foreign_call_instruction(
-    func_name: String,
-    inputs: &[RegisterOrMemory],
-    outputs: &[RegisterOrMemory],
-) {
-    debug_println!("  FOREIGN_CALL {} ({}) => {}", func_name, inputs, outputs);
-}
+    /// Renders the control flow graph recorded so far in Graphviz dot format.
+    ///
+    /// Each node is a block of straight-line code listing its instructions,
+    /// and each edge is a jump or a fall through between two blocks.
+    pub(crate) fn control_flow_graph(&self, graph_name: &str) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph \"{}\" {{", escape_dot_string(graph_name)).unwrap();
+        writeln!(dot, "    node [shape=box, fontname=\"monospace\"];").unwrap();
+
+        for (index, block) in self.blocks.iter().enumerate() {
+            let mut node_label = format!("{}:\\l", escape_dot_string(&block.label));
+            for instruction in &block.instructions {
+                write!(node_label, "{}\\l", escape_dot_string(&instruction.text)).unwrap();
+            }
+            writeln!(dot, "    b{index} [label=\"{node_label}\"];").unwrap();
+        }
 
-/// Emits a load instruction
-pub(crate) fn load_instruction(destination: RegisterIndex, source_pointer: RegisterIndex) {
-    debug_println!("  LOAD {} = *{}", destination, source_pointer);
-}
+        for (index, block) in self.blocks.iter().enumerate() {
+            for successor in &block.successors {
+                if let Some(successor_index) = self.label_to_block.get(successor) {
+                    writeln!(dot, "    b{index} -> b{successor_index};").unwrap();
+                }
+            }
+        }
 
-/// Emits a store instruction
-pub(crate) fn store_instruction(destination_pointer: RegisterIndex, source: RegisterIndex) {
-    debug_println!("  STORE *{} = {}", destination_pointer, source);
-}
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
 
-/// Emits a stop instruction
-pub(crate) fn stop_instruction() {
-    debug_println!("  STOP");
-}
+    /// Renders the disassembly recorded so far annotated with the liveness of registers.
+    ///
+    /// Each label is followed by the registers live when entering its block, and each
+    /// instruction by the registers it reads for the last time and the registers it
+    /// writes which are never read afterwards.
+    pub(crate) fn output_with_liveness(&self) -> String {
+        let live_in = self.live_registers_on_entry();
+        let mut annotations: HashMap<usize, String> = HashMap::new();
+
+        for (line, label) in &self.label_lines {
+            if let Some(block_index) = self.label_to_block.get(label) {
+                if !live_in[*block_index].is_empty() {
+                    let registers = registers_to_string(&live_in[*block_index]);
+                    annotations.insert(*line, format!("live in: {registers}"));
+                }
+            }
+        }
 
-/// Debug function for allocate_array_instruction
-pub(crate) fn allocate_array_instruction(
-    pointer_register: RegisterIndex,
-    size_register: RegisterIndex,
-) {
-    debug_println!("  ALLOCATE_ARRAY {} SIZE {}", pointer_register, size_register);
-}
+        for (block_index, block) in self.blocks.iter().enumerate() {
+            let mut live_registers = self.live_registers_on_exit(block_index, &live_in);
+            for instruction in block.instructions.iter().rev() {
+                let last_uses: BTreeSet<_> = instruction
+                    .used
+                    .iter()
+                    .filter(|register| !live_registers.contains(register))
+                    .copied()
+                    .collect();
+                let unused: BTreeSet<_> = instruction
+                    .defined
+                    .iter()
+                    .filter(|register| !live_registers.contains(register))
+                    .copied()
+                    .collect();
+                instruction.update_live_registers(&mut live_registers);
+
+                let mut notes = Vec::new();
+                if !last_uses.is_empty() {
+                    notes.push(format!("last use: {}", registers_to_string(&last_uses)));
+                }
+                if !unused.is_empty() {
+                    notes.push(format!("unused: {}", registers_to_string(&unused)));
+                }
+                if !notes.is_empty() {
+                    annotations.insert(instruction.line, notes.join("; "));
+                }
+            }
+        }
 
-/// Debug function for array_get
-pub(crate) fn array_get(array_ptr: RegisterIndex, index: RegisterIndex, result: RegisterIndex) {
-    debug_println!("  ARRAY_GET {}[{}] -> {}", array_ptr, index, result);
-}
+        let mut output = String::new();
+        for (line_index, line) in self.output.lines().enumerate() {
+            match annotations.get(&line_index) {
+                Some(annotation) => writeln!(output, "{line}  // {annotation}").unwrap(),
+                None => writeln!(output, "{line}").unwrap(),
+            }
+        }
+        output
+    }
 
-/// Debug function for array_set
-pub(crate) fn array_set(array_ptr: RegisterIndex, index: RegisterIndex, value: RegisterIndex) {
-    debug_println!("  ARRAY_SET {}[{}] = {}", array_ptr, index, value);
-}
+    /// Computes the registers live on entry of each block, iterating until a fixed point is reached.
+    fn live_registers_on_entry(&self) -> Vec<BTreeSet<usize>> {
+        let mut live_in = vec![BTreeSet::new(); self.blocks.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (block_index, block) in self.blocks.iter().enumerate().rev() {
+                let mut live_registers = self.live_registers_on_exit(block_index, &live_in);
+                for instruction in block.instructions.iter().rev() {
+                    instruction.update_live_registers(&mut live_registers);
+                }
+                if live_registers != live_in[block_index] {
+                    live_in[block_index] = live_registers;
+                    changed = true;
+                }
+            }
+        }
+        live_in
+    }
 
-/// Debug function for copy_array_instruction
-pub(crate) fn copy_array_instruction(
-    source: RegisterIndex,
-    destination: RegisterIndex,
-    num_elements_register: RegisterIndex,
-) {
-    debug_println!(
-        "  COPY_ARRAY {} -> {} ({} ELEMENTS)",
-        source,
-        destination,
-        num_elements_register
-    );
-}
+    /// Returns the registers live on exit of a block, which are those live on entry of its successors.
+    fn live_registers_on_exit(
+        &self,
+        block_index: usize,
+        live_in: &[BTreeSet<usize>],
+    ) -> BTreeSet<usize> {
+        self.blocks[block_index]
+            .successors
+            .iter()
+            .filter_map(|label| self.label_to_block.get(label))
+            .flat_map(|successor| live_in[*successor].iter().copied())
+            .collect()
+    }
 
-/// Debug function for enter_context
-pub(crate) fn enter_context(label: String) {
-    if !label.ends_with("-b0") {
-        // Hacky readability fix: don't print labels e.g. f1 then f1-b0 one after another, they mean the same thing
-        debug_println!("{}:", label);
+    /// Records that `register` holds the Noir variable `name`
+    pub(crate) fn set_register_name(&mut self, register: RegisterIndex, name: &str) {
+        if self.enable_debug_trace {
+            self.register_names.insert(register.to_usize(), name.to_owned());
+        }
     }
-}
 
-/// Debug function for jump_instruction
-pub(crate) fn jump_instruction(target_label: String) {
-    debug_println!("  JUMP_TO {}", target_label);
-}
+    /// Forgets the name of the variable held by `register`, as it is about to be reused
+    pub(crate) fn remove_register_name(&mut self, register: RegisterIndex) {
+        self.register_names.remove(&register.to_usize());
+    }
+
+    /// Appends the name of the variable held by each register in `line`, e.g. `R5{counter}`.
+    fn name_registers(&self, line: &str) -> String {
+        if self.register_names.is_empty() {
+            return line.to_owned();
+        }
+
+        let is_identifier_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        let mut named_line = String::with_capacity(line.len());
+        let mut previous_char = None;
+        let mut chars = line.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            named_line.push(c);
+            let is_token_start = c == 'R' && !previous_char.map_or(false, is_identifier_char);
+            previous_char = Some(c);
+            if !is_token_start {
+                continue;
+            }
 
-/// Debug function for jump_if_instruction
-pub(crate) fn jump_if_instruction<T: ToString>(condition: RegisterIndex, target_label: T) {
-    debug_println!("  JUMP_IF {} TO {}", condition, target_label.to_string());
+            let mut end = start + 1;
+            while let Some((index, digit)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                named_line.push(digit);
+                previous_char = Some(digit);
+                end = index + 1;
+            }
+            let is_token_end = !chars.peek().map_or(false, |(_, c)| is_identifier_char(*c));
+            let name = line[start + 1..end]
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| self.register_names.get(&index));
+            if let (true, Some(name)) = (is_token_end, name) {
+                write!(named_line, "{{{name}}}").unwrap();
+            }
+        }
+        named_line
+    }
+
+    /// Records a line of disassembly in the output and in the current block
+    fn push_line(&mut self, line: String) {
+        let line = self.name_registers(&line);
+        self.output.push_str(&line);
+        self.output.push('\n');
+
+        if self.blocks.is_empty() {
+            self.blocks.push(DebugShowBlock::default());
+        }
+        let current_block = self.blocks.last_mut().expect("a block was pushed above");
+        current_block.instructions.push(DebugShowInstruction {
+            text: line.trim().to_owned(),
+            line: self.num_lines,
+            ..Default::default()
+        });
+        self.num_lines += 1;
+    }
+
+    /// Records the registers written and read by the last instruction pushed
+    fn set_registers(&mut self, defined: &[RegisterIndex], used: &[RegisterIndex]) {
+        if !self.enable_debug_trace {
+            return;
+        }
+        let user_registers = |registers: &[RegisterIndex]| {
+            registers
+                .iter()
+                .map(|register| register.to_usize())
+                .filter(|index| *index >= ReservedRegisters::len())
+                .collect()
+        };
+        if let Some(instruction) =
+            self.blocks.last_mut().and_then(|block| block.instructions.last_mut())
+        {
+            instruction.defined = user_registers(defined);
+            instruction.used = user_registers(used);
+        }
+    }
+
+    /// Starts a new block in the control flow graph at `label`.
+    ///
+    /// If nothing has been emitted since the previous label, both labels refer to the same block.
+    fn enter_block(&mut self, label: String) {
+        if !self.enable_debug_trace {
+            return;
+        }
+
+        if let Some(current_block) = self.blocks.last_mut() {
+            if current_block.instructions.is_empty() && current_block.successors.is_empty() {
+                if current_block.label.is_empty() {
+                    current_block.label = label.clone();
+                }
+                self.label_to_block.insert(label, self.blocks.len() - 1);
+                return;
+            }
+            if !current_block.is_terminated {
+                current_block.successors.push(label.clone());
+            }
+        }
+
+        self.label_to_block.insert(label.clone(), self.blocks.len());
+        self.blocks.push(DebugShowBlock { label, ..Default::default() });
+    }
+
+    /// Records an edge from the current block to the block at `target_label`
+    fn add_successor(&mut self, target_label: String, is_unconditional: bool) {
+        if let Some(current_block) = self.blocks.last_mut() {
+            current_block.successors.push(target_label);
+            current_block.is_terminated |= is_unconditional;
+        }
+    }
+
+    /// Emits brillig bytecode to jump to a trap condition if `condition`
+    /// is false.
+    pub(crate) fn constrain_instruction(&mut self, condition: RegisterIndex) {
+        debug_println!(self, "  ASSERT {} != 0", condition);
+        self.set_registers(&[], &[condition]);
+    }
+
+    /// Processes a return instruction.
+    pub(crate) fn return_instruction(&mut self, return_registers: &[RegisterIndex]) {
+        let registers_string = return_registers
+            .iter()
+            .map(RegisterIndex::debug_to_string)
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        debug_println!(self, "  // return {};", registers_string);
+        self.set_registers(&[], return_registers);
+    }
+
+    /// Emits a `mov` instruction.
+    pub(crate) fn mov_instruction(&mut self, destination: RegisterIndex, source: RegisterIndex) {
+        debug_println!(self, "  MOV {}, {}", destination, source);
+        self.set_registers(&[destination], &[source]);
+    }
+
+    /// Processes a binary instruction according `operation`.
+    pub(crate) fn binary_instruction(
+        &mut self,
+        lhs: RegisterIndex,
+        rhs: RegisterIndex,
+        result: RegisterIndex,
+        operation: BrilligBinaryOp,
+    ) {
+        debug_println!(self, "  {} = {} {} {}", result, lhs, operation, rhs);
+        self.set_registers(&[result], &[lhs, rhs]);
+    }
+
+    /// Stores the value of `constant` in the `result` register
+    pub(crate) fn const_instruction(&mut self, result: RegisterIndex, constant: Value) {
+        debug_println!(self, "  CONST {} = {}", result, constant);
+        self.set_registers(&[result], &[]);
+    }
+
+    /// Processes a not instruction. Append with "_" as this is a high-level instruction.
+    pub(crate) fn not_instruction(
+        &mut self,
+        condition: RegisterIndex,
+        bit_size: u32,
+        result: RegisterIndex,
+    ) {
+        debug_println!(self, "  i{}_NOT {} = !{}", bit_size, result, condition);
+        self.set_registers(&[result], &[condition]);
+    }
+
+    /// Processes a foreign call instruction.
+    pub(crate) fn foreign_call_instruction(
+        &mut self,
+        func_name: String,
+        inputs: &[RegisterOrMemory],
+        outputs: &[RegisterOrMemory],
+    ) {
+        debug_println!(self, "  FOREIGN_CALL {} ({}) => {}", func_name, inputs, outputs);
+        // Outputs in memory are written through their pointer, which is read.
+        let mut defined = Vec::new();
+        let mut used: Vec<_> = inputs.iter().flat_map(registers_of_operand).collect();
+        for output in outputs {
+            match output {
+                RegisterOrMemory::RegisterIndex(register) => defined.push(*register),
+                RegisterOrMemory::HeapArray(HeapArray { pointer, .. }) => used.push(*pointer),
+                RegisterOrMemory::HeapVector(HeapVector { pointer, size }) => {
+                    used.push(*pointer);
+                    defined.push(*size);
+                }
+            }
+        }
+        self.set_registers(&defined, &used);
+    }
+
+    /// Emits a load instruction
+    pub(crate) fn load_instruction(
+        &mut self,
+        destination: RegisterIndex,
+        source_pointer: RegisterIndex,
+    ) {
+        debug_println!(self, "  LOAD {} = *{}", destination, source_pointer);
+        self.set_registers(&[destination], &[source_pointer]);
+    }
+
+    /// Emits a store instruction
+    pub(crate) fn store_instruction(
+        &mut self,
+        destination_pointer: RegisterIndex,
+        source: RegisterIndex,
+    ) {
+        debug_println!(self, "  STORE *{} = {}", destination_pointer, source);
+        self.set_registers(&[], &[destination_pointer, source]);
+    }
+
+    /// Emits a stop instruction
+    pub(crate) fn stop_instruction(&mut self) {
+        debug_println!(self, "  STOP");
+        if let Some(current_block) = self.blocks.last_mut() {
+            current_block.is_terminated = true;
+        }
+    }
+
+    /// Debug function for allocate_array_instruction
+    pub(crate) fn allocate_array_instruction(
+        &mut self,
+        pointer_register: RegisterIndex,
+        size_register: RegisterIndex,
+    ) {
+        debug_println!(self, "  ALLOCATE_ARRAY {} SIZE {}", pointer_register, size_register);
+        self.set_registers(&[pointer_register], &[size_register]);
+    }
+
+    /// Debug function for deallocate_fixed_length_array
+    pub(crate) fn deallocate_fixed_length_array(
+        &mut self,
+        pointer_register: RegisterIndex,
+        size: usize,
+    ) {
+        debug_println!(self, "  DEALLOCATE_ARRAY {} SIZE {}", pointer_register, size);
+        self.set_registers(&[], &[pointer_register]);
+    }
+
+    /// Debug function for array_get
+    pub(crate) fn array_get(
+        &mut self,
+        array_ptr: RegisterIndex,
+        index: RegisterIndex,
+        result: RegisterIndex,
+    ) {
+        debug_println!(self, "  ARRAY_GET {}[{}] -> {}", array_ptr, index, result);
+        self.set_registers(&[result], &[array_ptr, index]);
+    }
+
+    /// Debug function for array_set
+    pub(crate) fn array_set(
+        &mut self,
+        array_ptr: RegisterIndex,
+        index: RegisterIndex,
+        value: RegisterIndex,
+    ) {
+        debug_println!(self, "  ARRAY_SET {}[{}] = {}", array_ptr, index, value);
+        self.set_registers(&[], &[array_ptr, index, value]);
+    }
+
+    /// Debug function for copy_array_instruction
+    pub(crate) fn copy_array_instruction(
+        &mut self,
+        source: RegisterIndex,
+        destination: RegisterIndex,
+        num_elements_register: RegisterIndex,
+    ) {
+        debug_println!(
+            self,
+            "  COPY_ARRAY {} -> {} ({} ELEMENTS)",
+            source,
+            destination,
+            num_elements_register
+        );
+        self.set_registers(&[], &[source, destination, num_elements_register]);
+    }
+
+    /// Debug function for copy_fixed_length_array_instruction
+    pub(crate) fn copy_fixed_length_array_instruction(
+        &mut self,
+        source: RegisterIndex,
+        destination: RegisterIndex,
+        num_elements: usize,
+    ) {
+        debug_println!(
+            self,
+            "  COPY_ARRAY {} -> {} ({} ELEMENTS)",
+            source,
+            destination,
+            num_elements
+        );
+        self.set_registers(&[], &[source, destination]);
+    }
+
+    /// Debug function for enter_context
+    pub(crate) fn enter_context(&mut self, label: String) {
+        if !self.enable_debug_trace {
+            return;
+        }
+        if !label.ends_with("-b0") {
+            // Hacky readability fix: don't print labels e.g. f1 then f1-b0 one after another, they mean the same thing
+            writeln!(self.output, "{label}:").unwrap();
+            self.label_lines.push((self.num_lines, label.clone()));
+            self.num_lines += 1;
+        }
+        self.enter_block(label);
+    }
+
+    /// Debug function for entering a section within a block.
+    ///
+    /// Sections are not shown in the disassembly, but they start a new
+    /// block in the control flow graph since they can be jumped to.
+    pub(crate) fn enter_section(&mut self, label: String) {
+        self.enter_block(label);
+    }
+
+    /// Debug function for jump_instruction
+    pub(crate) fn jump_instruction(&mut self, target_label: String) {
+        debug_println!(self, "  JUMP_TO {}", target_label);
+        self.add_successor(target_label, true);
+    }
+
+    /// Debug function for jump_if_instruction
+    pub(crate) fn jump_if_instruction<T: ToString>(
+        &mut self,
+        condition: RegisterIndex,
+        target_label: T,
+    ) {
+        debug_println!(self, "  JUMP_IF {} TO {}", condition, target_label.to_string());
+        self.set_registers(&[], &[condition]);
+        self.add_successor(target_label.to_string(), false);
+    }
+
+    /// Debug function for cast_instruction
+    pub(crate) fn cast_instruction(
+        &mut self,
+        destination: RegisterIndex,
+        source: RegisterIndex,
+        target_bit_size: u32,
+    ) {
+        debug_println!(self, "  CAST {} FROM {} TO {} BITS", destination, source, target_bit_size);
+        self.set_registers(&[destination], &[source]);
+    }
+
+    /// Debug function for sign_extend_instruction
+    pub(crate) fn sign_extend_instruction(
+        &mut self,
+        destination: RegisterIndex,
+        source: RegisterIndex,
+        target_bit_size: Option<u32>,
+    ) {
+        match target_bit_size {
+            Some(target_bit_size) => debug_println!(
+                self,
+                "  SIGN_EXTEND {} FROM {} TO {} BITS",
+                destination,
+                source,
+                target_bit_size
+            ),
+            None => debug_println!(self, "  SIGN_EXTEND {} FROM {} TO FIELD", destination, source),
+        }
+        self.set_registers(&[destination], &[source]);
+    }
+
+    /// Debug function for black_box_op
+    pub(crate) fn black_box_op_instruction(&mut self, op: BlackBoxOp) {
+        let (defined, used) = registers_of_black_box_op(&op);
+        match op {
+            BlackBoxOp::Sha256 { message, output } => {
+                debug_println!(self, "  SHA256 {} -> {}", message, output);
+            }
+            BlackBoxOp::Keccak256 { message, output } => {
+                debug_println!(self, "  KECCAK256 {} -> {}", message, output);
+            }
+            BlackBoxOp::Blake2s { message, output } => {
+                debug_println!(self, "  BLAKE2S {} -> {}", message, output);
+            }
+            BlackBoxOp::HashToField128Security { message, output } => {
+                debug_println!(self, "  HASH_TO_FIELD_128_SECURITY {} -> {}", message, output);
+            }
+            BlackBoxOp::EcdsaSecp256k1 {
+                hashed_msg,
+                public_key_x,
+                public_key_y,
+                signature,
+                result,
+            } => {
+                debug_println!(
+                    self,
+                    "  ECDSA_SECP256K1 {} {} {} {} -> {}",
+                    hashed_msg,
+                    public_key_x,
+                    public_key_y,
+                    signature,
+                    result
+                );
+            }
+        }
+        self.set_registers(&defined, &used);
+    }
+
+    /// Debug function for add_external_call_instruction
+    pub(crate) fn add_external_call_instruction(&mut self, func_label: String) {
+        debug_println!(self, "  CALL {}", func_label);
+    }
 }
 
-/// Debug function for cast_instruction
-pub(crate) fn cast_instruction(
-    destination: RegisterIndex,
-    source: RegisterIndex,
-    target_bit_size: u32,
-) {
-    debug_println!("  CAST {} FROM {} TO {} BITS", destination, source, target_bit_size);
+/// Returns the registers read through an operand of a foreign call or black box operation
+fn registers_of_operand(operand: &RegisterOrMemory) -> Vec<RegisterIndex> {
+    match operand {
+        RegisterOrMemory::RegisterIndex(register) => vec![*register],
+        RegisterOrMemory::HeapArray(HeapArray { pointer, .. }) => vec![*pointer],
+        RegisterOrMemory::HeapVector(HeapVector { pointer, size }) => vec![*pointer, *size],
+    }
 }
 
-/// Debug function for black_box_op
-pub(crate) fn black_box_op_instruction(op: BlackBoxOp) {
+/// Returns the registers written and read by a black box operation.
+///
+/// Outputs in memory are written through their pointer, which is read.
+fn registers_of_black_box_op(op: &BlackBoxOp) -> (Vec<RegisterIndex>, Vec<RegisterIndex>) {
     match op {
-        BlackBoxOp::Sha256 { message, output } => {
-            debug_println!("  SHA256 {} -> {}", message, output);
-        }
-        BlackBoxOp::Keccak256 { message, output } => {
-            debug_println!("  KECCAK256 {} -> {}", message, output);
-        }
-        BlackBoxOp::Blake2s { message, output } => {
-            debug_println!("  BLAKE2S {} -> {}", message, output);
+        BlackBoxOp::Sha256 { message, output }
+        | BlackBoxOp::Keccak256 { message, output }
+        | BlackBoxOp::Blake2s { message, output } => {
+            (vec![], vec![message.pointer, message.size, output.pointer])
         }
         BlackBoxOp::HashToField128Security { message, output } => {
-            debug_println!("  HASH_TO_FIELD_128_SECURITY {} -> {}", message, output);
+            (vec![*output], vec![message.pointer, message.size])
         }
         BlackBoxOp::EcdsaSecp256k1 {
             hashed_msg,
@@ -286,20 +797,24 @@ pub(crate) fn black_box_op_instruction(op: BlackBoxOp) {
             public_key_y,
             signature,
             result,
-        } => {
-            debug_println!(
-                "  ECDSA_SECP256K1 {} {} {} {} -> {}",
-                hashed_msg,
-                public_key_x,
-                public_key_y,
-                signature,
-                result
-            );
-        }
+        } => (
+            vec![*result],
+            vec![
+                hashed_msg.pointer,
+                hashed_msg.size,
+                public_key_x.pointer,
+                public_key_y.pointer,
+                signature.pointer,
+            ],
+        ),
     }
 }
 
-/// Debug function for cast_instruction
-pub(crate) fn add_external_call_instruction(func_label: String) {
-    debug_println!("  CALL {}", func_label);
+fn registers_to_string(registers: &BTreeSet<usize>) -> String {
+    registers.iter().map(|index| format!("R{index}")).collect::<Vec<_>>().join(", ")
+}
+
+/// Escapes a string so that it can be used within a quoted Graphviz identifier
+fn escape_dot_string(string: &str) -> String {
+    string.replace('\\', "\\\\").replace('"', "\\\"")
 }
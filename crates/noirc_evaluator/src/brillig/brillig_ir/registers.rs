@@ -8,8 +8,6 @@ use super::ReservedRegisters;
 /// Each has a stack base pointer from which all stack allocations can be offset.
 pub(crate) struct BrilligRegistersContext {
     /// A free-list of registers that have been deallocated and can be used again.
-    /// TODO(AD): currently, register deallocation is only done with immediate values.
-    /// TODO(AD): See https://github.com/noir-lang/noir/issues/1720
     deallocated_registers: Vec<RegisterIndex>,
     /// A usize indicating the next un-used register.
     next_free_register_index: usize,
@@ -57,9 +55,23 @@ impl BrilligRegistersContext {
         register
     }
 
+    /// Allocates a specific register if it is free, returning whether it was.
+    pub(crate) fn allocate_register_if_free(&mut self, register: RegisterIndex) -> bool {
+        let index = register.to_usize();
+        if index < self.next_free_register_index {
+            let was_free = self.deallocated_registers.contains(&register);
+            self.deallocated_registers.retain(|&r| r != register);
+            was_free
+        } else {
+            // The registers skipped over remain free
+            self.deallocated_registers
+                .extend((self.next_free_register_index..index).map(RegisterIndex::from));
+            self.next_free_register_index = index + 1;
+            true
+        }
+    }
+
     /// Push a register to the deallocation list, ready for reuse.
-    /// TODO(AD): currently, register deallocation is only done with immediate values.
-    /// TODO(AD): See https://github.com/noir-lang/noir/issues/1720
     pub(crate) fn deallocate_register(&mut self, register_index: RegisterIndex) {
         assert!(!self.deallocated_registers.contains(&register_index));
         self.deallocated_registers.push(register_index);
@@ -0,0 +1,191 @@
+//! This module computes statistics about the Brillig bytecode of a function,
+//! which help to decide when an unconstrained function is getting out of hand.
+use std::collections::HashMap;
+
+use acvm::acir::brillig_vm::{
+    BinaryIntOp, BlackBoxOp, HeapArray, HeapVector, Opcode as BrilligOpcode, RegisterIndex,
+    RegisterOrMemory,
+};
+
+use super::{artifact::BrilligArtifact, ReservedRegisters};
+
+/// Statistics about the Brillig bytecode generated for an unconstrained function.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BrilligFunctionStatistics {
+    /// The number of opcodes in the function's bytecode.
+    pub num_opcodes: usize,
+    /// The number of registers used, including the reserved registers.
+    pub num_registers: usize,
+    /// The number of heap allocations in the function's bytecode.
+    pub num_heap_allocations: usize,
+    /// The number of calls to foreign functions in the function's bytecode.
+    pub num_foreign_calls: usize,
+    /// The number of memory slots allocated by heap allocations whose size is
+    /// known at compile time, in the function and the functions it calls,
+    /// assuming each of them is executed once.
+    pub static_heap_size: usize,
+    /// True if some heap allocation has a size only known at runtime or may be
+    /// executed several times by a loop or a recursive call, in which case
+    /// `static_heap_size` is only a lower bound of the memory allocated.
+    pub is_heap_size_unbounded: bool,
+    /// The maximum depth of nested calls made by the function,
+    /// or `None` if it may call itself recursively.
+    pub max_call_depth: Option<usize>,
+    /// The maximum number of memory slots used to save registers across nested calls.
+    pub max_stack_size: usize,
+}
+
+impl BrilligFunctionStatistics {
+    /// Estimates the worst case number of memory slots used by the function, registers included.
+    ///
+    /// This is only a lower bound if `is_heap_size_unbounded` is set.
+    pub fn estimated_memory(&self) -> usize {
+        self.num_registers + self.static_heap_size + self.max_stack_size
+    }
+}
+
+impl BrilligArtifact {
+    /// Computes statistics about the bytecode of this artifact.
+    ///
+    /// This is meant to be called on the artifact of a single function before linking,
+    /// so the functions it calls are not taken into account.
+    pub(crate) fn statistics(&self) -> BrilligFunctionStatistics {
+        let mut statistics = BrilligFunctionStatistics {
+            num_opcodes: self.byte_code.len(),
+            max_call_depth: Some(0),
+            ..Default::default()
+        };
+
+        // Ranges of opcodes which may be executed several times, from a jump target to a jump backwards.
+        let loops: Vec<_> =
+            self.resolved_jump_targets().filter(|(position, target)| target <= position).collect();
+        let is_in_loop = |position: usize| {
+            loops
+                .iter()
+                .any(|(jump_position, target)| (*target..=*jump_position).contains(&position))
+        };
+
+        // The last constant known to be held by each register.
+        let mut constants: HashMap<usize, usize> = HashMap::new();
+        let mut max_register = ReservedRegisters::len().saturating_sub(1);
+
+        for (position, opcode) in self.byte_code.iter().enumerate() {
+            for register in registers_of_opcode(opcode) {
+                max_register = max_register.max(register.to_usize());
+            }
+
+            match (opcode, self.byte_code.get(position + 1)) {
+                // An allocation copies the stack pointer into the array pointer, then bumps the stack pointer by the array size.
+                (
+                    BrilligOpcode::Mov { source, .. },
+                    Some(BrilligOpcode::BinaryIntOp {
+                        destination,
+                        op: BinaryIntOp::Add,
+                        lhs,
+                        rhs,
+                        ..
+                    }),
+                ) if *source == ReservedRegisters::stack_pointer()
+                    && *destination == ReservedRegisters::stack_pointer()
+                    && *lhs == ReservedRegisters::stack_pointer() =>
+                {
+                    statistics.num_heap_allocations += 1;
+                    match constants.get(&rhs.to_usize()) {
+                        Some(size) if !is_in_loop(position) => {
+                            statistics.static_heap_size =
+                                statistics.static_heap_size.saturating_add(*size);
+                        }
+                        _ => statistics.is_heap_size_unbounded = true,
+                    }
+                }
+                (BrilligOpcode::ForeignCall { .. }, _) => statistics.num_foreign_calls += 1,
+                _ => (),
+            }
+
+            if let BrilligOpcode::Const { destination, value } = opcode {
+                // Constants which do not fit in a usize are too large to be heap sizes
+                let value = value.to_field().try_to_u64().and_then(|value| value.try_into().ok());
+                match value {
+                    Some(value) => constants.insert(destination.to_usize(), value),
+                    None => constants.remove(&destination.to_usize()),
+                };
+            } else if let Some(destination) = destination_of_opcode(opcode) {
+                constants.remove(&destination.to_usize());
+            }
+        }
+
+        statistics.num_registers = max_register + 1;
+        statistics
+    }
+}
+
+/// Returns the register written by `opcode`, if it writes to a single register.
+fn destination_of_opcode(opcode: &BrilligOpcode) -> Option<RegisterIndex> {
+    match opcode {
+        BrilligOpcode::BinaryFieldOp { destination, .. }
+        | BrilligOpcode::BinaryIntOp { destination, .. }
+        | BrilligOpcode::Const { destination, .. }
+        | BrilligOpcode::Mov { destination, .. }
+        | BrilligOpcode::Load { destination, .. } => Some(*destination),
+        _ => None,
+    }
+}
+
+/// Returns all of the registers read or written by `opcode`.
+fn registers_of_opcode(opcode: &BrilligOpcode) -> Vec<RegisterIndex> {
+    match opcode {
+        BrilligOpcode::BinaryFieldOp { destination, lhs, rhs, .. }
+        | BrilligOpcode::BinaryIntOp { destination, lhs, rhs, .. } => {
+            vec![*destination, *lhs, *rhs]
+        }
+        BrilligOpcode::JumpIf { condition, .. } | BrilligOpcode::JumpIfNot { condition, .. } => {
+            vec![*condition]
+        }
+        BrilligOpcode::Const { destination, .. } => vec![*destination],
+        BrilligOpcode::Mov { destination, source } => vec![*destination, *source],
+        BrilligOpcode::Load { destination, source_pointer } => vec![*destination, *source_pointer],
+        BrilligOpcode::Store { destination_pointer, source } => {
+            vec![*destination_pointer, *source]
+        }
+        BrilligOpcode::ForeignCall { destinations, inputs, .. } => {
+            destinations.iter().chain(inputs).flat_map(registers_of_register_or_memory).collect()
+        }
+        BrilligOpcode::BlackBox(op) => registers_of_black_box_op(op),
+        _ => Vec::new(),
+    }
+}
+
+fn registers_of_register_or_memory(value: &RegisterOrMemory) -> Vec<RegisterIndex> {
+    match value {
+        RegisterOrMemory::RegisterIndex(register) => vec![*register],
+        RegisterOrMemory::HeapArray(HeapArray { pointer, .. }) => vec![*pointer],
+        RegisterOrMemory::HeapVector(HeapVector { pointer, size }) => vec![*pointer, *size],
+    }
+}
+
+fn registers_of_black_box_op(op: &BlackBoxOp) -> Vec<RegisterIndex> {
+    match op {
+        BlackBoxOp::Sha256 { message, output }
+        | BlackBoxOp::Keccak256 { message, output }
+        | BlackBoxOp::Blake2s { message, output } => {
+            vec![message.pointer, message.size, output.pointer]
+        }
+        BlackBoxOp::HashToField128Security { message, output } => {
+            vec![message.pointer, message.size, *output]
+        }
+        BlackBoxOp::EcdsaSecp256k1 {
+            hashed_msg,
+            public_key_x,
+            public_key_y,
+            signature,
+            result,
+        } => vec![
+            hashed_msg.pointer,
+            hashed_msg.size,
+            public_key_x.pointer,
+            public_key_y.pointer,
+            signature.pointer,
+            *result,
+        ],
+    }
+}
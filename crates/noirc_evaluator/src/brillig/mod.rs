@@ -1,15 +1,82 @@
 pub(crate) mod brillig_gen;
 pub(crate) mod brillig_ir;
 
+pub use self::brillig_gen::brillig_black_box::BLACK_BOX_FOREIGN_CALL_PREFIX;
+pub use self::brillig_ir::{
+    assembly::{
+        assemble, disassemble, disassemble_with_labels, BrilligAssemblyError,
+        BrilligAssemblyErrorKind,
+    },
+    statistics::BrilligFunctionStatistics,
+};
 use self::{
     brillig_gen::{brillig_fn::FunctionContext, convert_ssa_function},
-    brillig_ir::artifact::{BrilligArtifact, Label},
+    brillig_ir::{
+        artifact::{BrilligArtifact, Label},
+        debug_show::DebugShow,
+    },
 };
 use crate::ssa_refactor::{
-    ir::function::{Function, FunctionId, RuntimeType},
+    ir::{
+        function::{Function, FunctionId, RuntimeType},
+        instruction::Instruction,
+        value::Value,
+    },
     ssa_gen::Ssa,
 };
-use std::collections::HashMap;
+use noirc_errors::Location;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Human-readable views of the Brillig bytecode of an unconstrained function.
+#[derive(Debug, Clone, Default)]
+pub struct BrilligFunctionDisassembly {
+    /// The disassembled instructions, with each block introduced by its label
+    /// and annotated with the liveness of registers
+    pub instructions: String,
+    /// The source locations the instructions were generated from, as pairs of
+    /// the index of the first line of `instructions` generated from a location
+    /// and that location.
+    pub source_locations: Vec<(usize, Location)>,
+    /// The control flow graph between the function's blocks, in Graphviz dot format
+    pub control_flow_graph: String,
+}
+
+impl BrilligFunctionDisassembly {
+    /// Renders the disassembled instructions with each group of instructions preceded
+    /// by a comment showing the source location it was generated from.
+    ///
+    /// As the SSA only knows about file ids, it is left to the caller to turn
+    /// a location into a file name and line through `format_location`.
+    pub fn annotated_instructions(&self, format_location: impl Fn(Location) -> String) -> String {
+        let mut annotated = String::new();
+        let mut source_locations = self.source_locations.iter().peekable();
+        for (line_index, line) in self.instructions.lines().enumerate() {
+            if let Some((_, location)) = source_locations.next_if(|(index, _)| *index == line_index)
+            {
+                annotated.push_str(&format!("  // {}\n", format_location(*location)));
+            }
+            annotated.push_str(line);
+            annotated.push('\n');
+        }
+        annotated
+    }
+}
+
+/// Maps the name of each unconstrained function to the disassembly of its Brillig bytecode.
+pub type BrilligDisassembly = BTreeMap<String, BrilligFunctionDisassembly>;
+
+/// Maps the name of each unconstrained function to statistics about its Brillig bytecode.
+pub type BrilligStatistics = BTreeMap<String, BrilligFunctionStatistics>;
+
+/// Information about the Brillig bytecode of a program's unconstrained functions,
+/// meant to be displayed to developers rather than executed.
+#[derive(Debug, Clone, Default)]
+pub struct BrilligDebugInfo {
+    /// The disassembly of each function, only populated when compiled with `show_brillig`
+    pub disassembly: BrilligDisassembly,
+    /// Statistics about the bytecode of each function
+    pub statistics: BrilligStatistics,
+}
 
 /// Context structure for the brillig pass.
 /// It stores brillig-related data required for brillig generation.
@@ -17,13 +84,21 @@ use std::collections::HashMap;
 pub struct Brillig {
     /// Maps SSA function labels to their brillig artifact
     ssa_function_to_brillig: HashMap<FunctionId, BrilligArtifact>,
+    /// Maps SSA function labels to the debug trace of their brillig bytecode
+    ssa_function_to_debug_show: HashMap<FunctionId, DebugShow>,
 }
 
 impl Brillig {
     /// Compiles a function into brillig and store the compilation artifacts
-    pub(crate) fn compile(&mut self, func: &Function) {
-        let obj = convert_ssa_function(func);
+    pub(crate) fn compile(&mut self, func: &Function, enable_debug_trace: bool) {
+        let (obj, debug_show) = convert_ssa_function(func, enable_debug_trace);
         self.ssa_function_to_brillig.insert(func.id(), obj);
+        self.ssa_function_to_debug_show.insert(func.id(), debug_show);
+    }
+
+    /// Returns the debug trace recorded when compiling the given function
+    pub(crate) fn debug_show(&self, function_id: FunctionId) -> Option<&DebugShow> {
+        self.ssa_function_to_debug_show.get(&function_id)
     }
 
     /// Finds a brillig function artifact by its function label
@@ -46,17 +121,193 @@ impl std::ops::Index<FunctionId> for Brillig {
 }
 
 impl Ssa {
-    /// Generate compilation artifacts for brillig functions
-    pub(crate) fn to_brillig(&self) -> Brillig {
-        // Collect all of the brillig functions
-        let brillig_functions =
-            self.functions.values().filter(|func| func.runtime() == RuntimeType::Brillig);
+    /// Collects all of the brillig functions
+    fn brillig_functions(&self) -> impl Iterator<Item = &Function> {
+        self.functions.values().filter(|func| func.runtime() == RuntimeType::Brillig)
+    }
 
+    /// Generate compilation artifacts for brillig functions
+    ///
+    /// If `enable_debug_trace` is set, the disassembly of each function is
+    /// recorded and can be retrieved through `Ssa::brillig_disassembly`.
+    pub(crate) fn to_brillig(&self, enable_debug_trace: bool) -> Brillig {
         let mut brillig = Brillig::default();
-        for brillig_function in brillig_functions {
-            brillig.compile(brillig_function);
+        for brillig_function in self.brillig_functions() {
+            brillig.compile(brillig_function, enable_debug_trace);
         }
 
         brillig
     }
+
+    /// Collects the disassembly recorded by `brillig` for each brillig function,
+    /// keyed by a name which is unique among the functions of this program.
+    pub(crate) fn brillig_disassembly(&self, brillig: &Brillig) -> BrilligDisassembly {
+        self.brillig_functions()
+            .filter_map(|func| {
+                let debug_show = brillig.debug_show(func.id())?;
+                let name = Self::brillig_function_name(func);
+                let disassembly = BrilligFunctionDisassembly {
+                    instructions: debug_show.output_with_liveness(),
+                    source_locations: debug_show.source_locations().to_vec(),
+                    control_flow_graph: debug_show.control_flow_graph(&name),
+                };
+                Some((name, disassembly))
+            })
+            .collect()
+    }
+
+    /// Computes statistics about the bytecode compiled by `brillig` for each brillig function,
+    /// keyed by the same names as `Ssa::brillig_disassembly`.
+    ///
+    /// The statistics of each function account for the functions it calls.
+    pub(crate) fn brillig_statistics(&self, brillig: &Brillig) -> BrilligStatistics {
+        let mut statistics = HashMap::new();
+        for func in self.brillig_functions() {
+            self.brillig_call_graph_statistics(
+                func.id(),
+                brillig,
+                &mut statistics,
+                &mut HashSet::new(),
+            );
+        }
+
+        self.brillig_functions()
+            .map(|func| (Self::brillig_function_name(func), statistics[&func.id()]))
+            .collect()
+    }
+
+    /// Computes the statistics of a function from the statistics of its own bytecode
+    /// and of the functions it calls, caching them in `statistics`.
+    ///
+    /// `call_stack` holds the functions currently being visited. A call to one of them is
+    /// recursive, in which case this returns `None`.
+    fn brillig_call_graph_statistics(
+        &self,
+        function_id: FunctionId,
+        brillig: &Brillig,
+        statistics: &mut HashMap<FunctionId, BrilligFunctionStatistics>,
+        call_stack: &mut HashSet<FunctionId>,
+    ) -> Option<BrilligFunctionStatistics> {
+        if let Some(function_statistics) = statistics.get(&function_id) {
+            return Some(*function_statistics);
+        }
+        if !call_stack.insert(function_id) {
+            return None;
+        }
+
+        let mut function_statistics = brillig[function_id].statistics();
+        for callee in self.brillig_callees(&self.functions[&function_id], brillig) {
+            let callee_statistics =
+                self.brillig_call_graph_statistics(callee, brillig, statistics, call_stack);
+            match callee_statistics {
+                Some(callee_statistics) => {
+                    function_statistics.static_heap_size += callee_statistics.static_heap_size;
+                    function_statistics.is_heap_size_unbounded |=
+                        callee_statistics.is_heap_size_unbounded;
+                    function_statistics.max_call_depth = function_statistics
+                        .max_call_depth
+                        .zip(callee_statistics.max_call_depth)
+                        .map(|(depth, callee_depth)| depth.max(callee_depth + 1));
+                    // The registers of the caller are saved on the stack during the call
+                    function_statistics.max_stack_size = function_statistics
+                        .max_stack_size
+                        .max(function_statistics.num_registers + callee_statistics.max_stack_size);
+                }
+                None => {
+                    function_statistics.max_call_depth = None;
+                    function_statistics.is_heap_size_unbounded = true;
+                }
+            }
+        }
+
+        call_stack.remove(&function_id);
+        statistics.insert(function_id, function_statistics);
+        Some(function_statistics)
+    }
+
+    /// Returns the brillig functions called by `func`, once for each call.
+    fn brillig_callees(&self, func: &Function, brillig: &Brillig) -> Vec<FunctionId> {
+        let mut callees = Vec::new();
+        for block in func.reachable_blocks() {
+            for instruction in func.dfg[block].instructions() {
+                if let Instruction::Call { func: callee, .. } = &func.dfg[*instruction] {
+                    if let Value::Function(callee) = &func.dfg[*callee] {
+                        if brillig.ssa_function_to_brillig.contains_key(callee) {
+                            callees.push(*callee);
+                        }
+                    }
+                }
+            }
+        }
+        callees
+    }
+
+    /// Returns a name for `func` which is unique among the functions of this program.
+    fn brillig_function_name(func: &Function) -> String {
+        format!("{}_{}", func.name(), func.id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ssa_refactor::{
+        ir::{function::RuntimeType, map::Id},
+        ssa_builder::FunctionBuilder,
+    };
+
+    #[test]
+    fn brillig_statistics_follow_calls() {
+        // brillig fn leaf f0 {
+        //   b0():
+        //     return
+        // }
+        // brillig fn caller f1 {
+        //   b0():
+        //     call f0()
+        //     call f0()
+        //     return
+        // }
+        // brillig fn recursive f2 {
+        //   b0():
+        //     call f0()
+        //     call f2()
+        //     return
+        // }
+        let leaf_id = Id::test_new(0);
+        let caller_id = Id::test_new(1);
+        let recursive_id = Id::test_new(2);
+
+        let mut builder = FunctionBuilder::new("leaf".into(), leaf_id, RuntimeType::Brillig);
+        builder.terminate_with_return(vec![]);
+
+        builder.new_brillig_function("caller".into(), caller_id);
+        let leaf = builder.import_function(leaf_id);
+        builder.insert_call(leaf, vec![], vec![]);
+        builder.insert_call(leaf, vec![], vec![]);
+        builder.terminate_with_return(vec![]);
+
+        builder.new_brillig_function("recursive".into(), recursive_id);
+        let leaf = builder.import_function(leaf_id);
+        let recursive = builder.import_function(recursive_id);
+        builder.insert_call(leaf, vec![], vec![]);
+        builder.insert_call(recursive, vec![], vec![]);
+        builder.terminate_with_return(vec![]);
+
+        let ssa = builder.finish();
+        let brillig = ssa.to_brillig(false);
+        let statistics = ssa.brillig_statistics(&brillig);
+
+        let leaf = statistics["leaf_f0"];
+        assert_eq!(leaf.max_call_depth, Some(0));
+        assert_eq!(leaf.max_stack_size, 0);
+
+        let caller = statistics["caller_f1"];
+        assert_eq!(caller.max_call_depth, Some(1));
+        assert_eq!(caller.max_stack_size, caller.num_registers);
+        assert!(!caller.is_heap_size_unbounded);
+
+        let recursive = statistics["recursive_f2"];
+        assert_eq!(recursive.max_call_depth, None);
+        assert!(recursive.is_heap_size_unbounded);
+    }
 }
@@ -0,0 +1,340 @@
+//! Removes the redundant constraints of a circuit once its ACIR is generated.
+//!
+//! Separate parts of a program often end up asserting the same thing, such as the same range
+//! of a witness or the same arithmetic relation between witnesses. An arithmetic opcode is
+//! redundant if another one asserts the same expression, up to the order of its terms and a
+//! constant factor, while a range constraint is redundant if the same witness is constrained
+//! to a range at least as small elsewhere.
+//!
+//! A range constraint is also redundant if its witness is decomposed into range constrained
+//! limbs, such as the remainder and quotient of a truncation: an arithmetic opcode equating the
+//! witness to a sum of limbs with non-negative coefficients bounds it by the largest value of
+//! this sum. Several truncations of the same value then only cost the range constraints of the
+//! narrowest limbs rather than one for each intermediate result.
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::ssa_refactor::OpcodeLocations;
+use acvm::{
+    acir::{
+        circuit::{
+            opcodes::{BlackBoxFuncCall, FunctionInput},
+            Circuit, Opcode,
+        },
+        native_types::{Expression, Witness},
+    },
+    FieldElement,
+};
+use num_bigint::BigUint;
+
+/// The number of opcodes of a circuit before and after removing its redundant constraints.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DeduplicationStatistics {
+    pub opcodes_before: usize,
+    pub opcodes_after: usize,
+}
+
+impl DeduplicationStatistics {
+    /// The number of redundant opcodes which were removed.
+    pub fn removed_opcodes(&self) -> usize {
+        self.opcodes_before - self.opcodes_after
+    }
+}
+
+/// Removes the duplicate arithmetic opcodes and range constraints of the circuit.
+///
+/// Only the tightest range constraint of each witness is kept, at the position of the
+/// first range constraint of this witness, unless it is implied by the range constraints
+/// of the limbs the witness is decomposed into. The locations of the removed opcodes are removed
+/// from `opcode_locations` as well, unless no locations were recorded.
+pub fn deduplicate_opcodes(
+    mut circuit: Circuit,
+    opcode_locations: &mut OpcodeLocations,
+) -> (Circuit, DeduplicationStatistics) {
+    let opcodes_before = circuit.opcodes.len();
+
+    let mut range_bits: HashMap<Witness, u32> = HashMap::new();
+    for opcode in &circuit.opcodes {
+        if let Some(FunctionInput { witness, num_bits }) = range_constraint(opcode) {
+            let bits = range_bits.entry(witness).or_insert(num_bits);
+            *bits = (*bits).min(num_bits);
+        }
+    }
+    for witness in implied_range_constraints(&circuit.opcodes, &range_bits) {
+        range_bits.remove(&witness);
+    }
+
+    let mut expressions = HashSet::new();
+    let mut opcodes = Vec::with_capacity(circuit.opcodes.len());
+    let mut is_kept = Vec::with_capacity(circuit.opcodes.len());
+    for opcode in circuit.opcodes {
+        let opcodes_len = opcodes.len();
+        match &opcode {
+            Opcode::Arithmetic(expression) => {
+                if expressions.insert(CanonicalExpression::new(expression)) {
+                    opcodes.push(opcode);
+                }
+            }
+            _ => {
+                if let Some(FunctionInput { witness, .. }) = range_constraint(&opcode) {
+                    // The tightest range is removed from the map once added
+                    if let Some(num_bits) = range_bits.remove(&witness) {
+                        let input = FunctionInput { witness, num_bits };
+                        opcodes.push(Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input }));
+                    }
+                } else {
+                    opcodes.push(opcode);
+                }
+            }
+        }
+        is_kept.push(opcodes.len() > opcodes_len);
+    }
+
+    if opcode_locations.len() == is_kept.len() {
+        let mut is_kept = is_kept.into_iter();
+        opcode_locations.retain(|_| is_kept.next().unwrap_or_default());
+    }
+    circuit.opcodes = opcodes;
+    let statistics =
+        DeduplicationStatistics { opcodes_before, opcodes_after: circuit.opcodes.len() };
+    (circuit, statistics)
+}
+
+/// Returns the input of the opcode if it is a range constraint.
+fn range_constraint(opcode: &Opcode) -> Option<FunctionInput> {
+    match opcode {
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input }) => Some(*input),
+        _ => None,
+    }
+}
+
+/// Returns the witnesses whose range constraint is implied by an arithmetic opcode expressing
+/// them as a sum of range constrained witnesses with non-negative coefficients.
+///
+/// The range constraint of a limb is kept once it is used to imply another one, so that no
+/// two range constraints are removed because of each other.
+fn implied_range_constraints(
+    opcodes: &[Opcode],
+    range_bits: &HashMap<Witness, u32>,
+) -> HashSet<Witness> {
+    let mut implied = HashSet::new();
+    let mut required = HashSet::new();
+
+    for opcode in opcodes {
+        let expression = match opcode {
+            Opcode::Arithmetic(expression) if expression.mul_terms.is_empty() => expression,
+            _ => continue,
+        };
+        for (_, witness) in &expression.linear_combinations {
+            if implied.contains(witness) || required.contains(witness) {
+                continue;
+            }
+            let num_bits = match range_bits.get(witness) {
+                Some(num_bits) => *num_bits,
+                None => continue,
+            };
+            let limbs = match decomposition_limbs(expression, *witness, range_bits, &implied) {
+                Some((limbs, max_value)) if max_value < BigUint::from(2_u32).pow(num_bits) => limbs,
+                _ => continue,
+            };
+            implied.insert(*witness);
+            required.extend(limbs);
+            break;
+        }
+    }
+    implied
+}
+
+/// Solves `expression = 0` for `witness` and returns the other witnesses along with the largest
+/// value the solution can take, if it is a sum of range constrained witnesses with non-negative
+/// coefficients.
+fn decomposition_limbs(
+    expression: &Expression,
+    witness: Witness,
+    range_bits: &HashMap<Witness, u32>,
+    implied: &HashSet<Witness>,
+) -> Option<(Vec<Witness>, BigUint)> {
+    let mut coefficient = None;
+    let mut terms = Vec::new();
+    for (term_coefficient, term_witness) in &expression.linear_combinations {
+        if *term_witness != witness {
+            terms.push((*term_coefficient, *term_witness));
+        } else if coefficient.replace(*term_coefficient).is_some() {
+            return None;
+        }
+    }
+    let coefficient = coefficient.filter(|coefficient| !coefficient.is_zero())?;
+    let inverse = -coefficient.inverse();
+
+    // Coefficients which would be negative are close to the field modulus, so that the
+    // largest value then exceeds any range
+    let to_biguint = |field: FieldElement| BigUint::from_bytes_be(&field.to_be_bytes());
+    let mut max_value = to_biguint(expression.q_c * inverse);
+    let mut limbs = Vec::with_capacity(terms.len());
+    for (term_coefficient, limb) in terms {
+        if implied.contains(&limb) {
+            return None;
+        }
+        let max_limb = BigUint::from(2_u32).pow(*range_bits.get(&limb)?) - 1_u32;
+        max_value += to_biguint(term_coefficient * inverse) * max_limb;
+        limbs.push(limb);
+    }
+    Some((limbs, max_value))
+}
+
+/// An arithmetic expression whose terms are merged and sorted by witness, and scaled so
+/// that its first coefficient is one. Two expressions asserted to be zero are equivalent
+/// if their canonical forms are equal.
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct CanonicalExpression {
+    mul_terms: Vec<(Witness, Witness, FieldElement)>,
+    linear_combinations: Vec<(Witness, FieldElement)>,
+    q_c: FieldElement,
+}
+
+impl CanonicalExpression {
+    fn new(expression: &Expression) -> Self {
+        let mut mul_terms: BTreeMap<(Witness, Witness), FieldElement> = BTreeMap::new();
+        for (coefficient, lhs, rhs) in &expression.mul_terms {
+            let witnesses = if lhs <= rhs { (*lhs, *rhs) } else { (*rhs, *lhs) };
+            let term = mul_terms.entry(witnesses).or_insert_with(FieldElement::zero);
+            *term = *term + *coefficient;
+        }
+        let mut linear_combinations: BTreeMap<Witness, FieldElement> = BTreeMap::new();
+        for (coefficient, witness) in &expression.linear_combinations {
+            let term = linear_combinations.entry(*witness).or_insert_with(FieldElement::zero);
+            *term = *term + *coefficient;
+        }
+
+        let mut canonical = CanonicalExpression {
+            mul_terms: mul_terms
+                .into_iter()
+                .filter(|(_, coefficient)| !coefficient.is_zero())
+                .map(|((lhs, rhs), coefficient)| (lhs, rhs, coefficient))
+                .collect(),
+            linear_combinations: linear_combinations
+                .into_iter()
+                .filter(|(_, coefficient)| !coefficient.is_zero())
+                .collect(),
+            q_c: expression.q_c,
+        };
+
+        let first_coefficient =
+            canonical.mul_terms.first().map(|(_, _, coefficient)| *coefficient).or_else(|| {
+                canonical.linear_combinations.first().map(|(_, coefficient)| *coefficient)
+            });
+        if let Some(first_coefficient) = first_coefficient {
+            let inverse = first_coefficient.inverse();
+            for (_, _, coefficient) in &mut canonical.mul_terms {
+                *coefficient = *coefficient * inverse;
+            }
+            for (_, coefficient) in &mut canonical.linear_combinations {
+                *coefficient = *coefficient * inverse;
+            }
+            canonical.q_c = canonical.q_c * inverse;
+        }
+        canonical
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use acvm::{
+        acir::{
+            circuit::{
+                opcodes::{BlackBoxFuncCall, FunctionInput},
+                Circuit, Opcode, PublicInputs,
+            },
+            native_types::{Expression, Witness},
+        },
+        FieldElement,
+    };
+
+    use super::deduplicate_opcodes;
+
+    fn range(witness: u32, num_bits: u32) -> Opcode {
+        let input = FunctionInput { witness: Witness(witness), num_bits };
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input })
+    }
+
+    #[test]
+    fn removes_redundant_constraints() {
+        // w1 - w2 = 0, asserted three times in different forms
+        let expression = &Expression::from(Witness(1)) - &Expression::from(Witness(2));
+        let negated = &Expression::from(Witness(2)) - &Expression::from(Witness(1));
+        let doubled = &expression * FieldElement::from(2_i128);
+        let other = &Expression::from(Witness(1)) - &Expression::from(Witness(3));
+
+        let opcodes = vec![
+            range(1, 32),
+            Opcode::Arithmetic(expression.clone()),
+            range(2, 8),
+            Opcode::Arithmetic(negated),
+            range(1, 16),
+            Opcode::Arithmetic(doubled),
+            Opcode::Arithmetic(other.clone()),
+            range(2, 8),
+        ];
+        let circuit = Circuit {
+            current_witness_index: 3,
+            opcodes,
+            public_parameters: PublicInputs(BTreeSet::new()),
+            return_values: PublicInputs(BTreeSet::new()),
+        };
+
+        let mut opcode_locations = vec![None; 8];
+        let (circuit, statistics) = deduplicate_opcodes(circuit, &mut opcode_locations);
+        assert_eq!(
+            circuit.opcodes,
+            vec![
+                range(1, 16),
+                Opcode::Arithmetic(expression),
+                range(2, 8),
+                Opcode::Arithmetic(other)
+            ]
+        );
+        assert_eq!(opcode_locations.len(), 4);
+        assert_eq!(statistics.opcodes_before, 8);
+        assert_eq!(statistics.removed_opcodes(), 4);
+    }
+
+    #[test]
+    fn removes_range_constraints_implied_by_limbs() {
+        // w1 = w2 + 2^8 * w3, where w2 and w3 are bytes
+        let decomposition = &(&Expression::from(Witness(2))
+            + &(&Expression::from(Witness(3)) * FieldElement::from(256_i128)))
+            - &Expression::from(Witness(1));
+        // w4 = w1 - w2 may be negative, so its range constraint is kept
+        let difference = &(&Expression::from(Witness(1)) - &Expression::from(Witness(2)))
+            - &Expression::from(Witness(4));
+
+        let opcodes = vec![
+            range(2, 8),
+            range(3, 8),
+            Opcode::Arithmetic(decomposition.clone()),
+            range(1, 16),
+            Opcode::Arithmetic(difference.clone()),
+            range(4, 16),
+        ];
+        let circuit = Circuit {
+            current_witness_index: 4,
+            opcodes,
+            public_parameters: PublicInputs(BTreeSet::new()),
+            return_values: PublicInputs(BTreeSet::new()),
+        };
+
+        let (circuit, statistics) = deduplicate_opcodes(circuit, &mut Vec::new());
+        assert_eq!(
+            circuit.opcodes,
+            vec![
+                range(2, 8),
+                range(3, 8),
+                Opcode::Arithmetic(decomposition),
+                Opcode::Arithmetic(difference),
+                range(4, 16),
+            ]
+        );
+        assert_eq!(statistics.removed_opcodes(), 1);
+    }
+}
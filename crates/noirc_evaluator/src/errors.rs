@@ -70,6 +70,20 @@ pub enum RuntimeErrorKind {
 
     #[error("Unsupported operation error")]
     UnsupportedOp { op: String, first_type: String, second_type: String },
+
+    // Loop unrolling errors
+    #[error("Loop bounds are not known at compile-time")]
+    UnknownLoopBound,
+
+    #[error("Loop has too many iterations to be unrolled")]
+    UnrollLimitExceeded { trip_count: u128, max_unroll: usize },
+
+    // ACIR generation errors
+    #[error("Dynamic indexing of arrays whose elements have different sizes is not supported")]
+    UnsupportedDynamicIndexing,
+
+    #[error("Cannot pop from an empty slice")]
+    EmptySlicePop,
 }
 
 impl RuntimeErrorKind {
@@ -112,6 +126,38 @@ impl From<RuntimeError> for Diagnostic {
                 format!("function {func_name} can only be called in main"),
                 span,
             ),
+            RuntimeErrorKind::UnknownLoopBound => {
+                let mut diagnostic = Diagnostic::simple_error(
+                    "for loop bounds must be known at compile-time".to_owned(),
+                    "this range could not be evaluated to constants".to_owned(),
+                    span,
+                );
+                diagnostic.add_note(
+                    "loops in constrained functions are unrolled, consider moving this loop into an unconstrained function".to_owned(),
+                );
+                diagnostic
+            }
+            RuntimeErrorKind::UnrollLimitExceeded { trip_count, max_unroll } => {
+                let mut diagnostic = Diagnostic::simple_error(
+                    format!("for loop would be unrolled {trip_count} times"),
+                    format!("this range has {trip_count} iterations but at most {max_unroll} can be unrolled"),
+                    span,
+                );
+                diagnostic.add_note(
+                    "reduce the range of the loop or raise the limit with --max-unroll".to_owned(),
+                );
+                diagnostic
+            }
+            RuntimeErrorKind::UnsupportedDynamicIndexing => Diagnostic::simple_error(
+                "cannot index this array with a value unknown at compile-time".to_owned(),
+                "the elements of this array do not all have the same size".to_owned(),
+                span,
+            ),
+            RuntimeErrorKind::EmptySlicePop => Diagnostic::simple_error(
+                "cannot pop from an empty slice".to_owned(),
+                "this slice is always empty".to_owned(),
+                span,
+            ),
         }
     }
 }
@@ -0,0 +1,57 @@
+//! Constrained implementations of the black box functions, written in Noir in the standard
+//! library, which the compiler uses in place of those the backend does not support.
+//!
+//! The frontend calls the function at the path of the fallback instead of the foreign function
+//! linked to the black box function, so that the circuit is made of opcodes the backend supports
+//! at the cost of many more of them.
+use std::collections::BTreeMap;
+
+use acvm::acir::{
+    circuit::{opcodes::BlackBoxFuncCall, Opcode},
+    BlackBoxFunc,
+};
+
+/// The black box functions with a fallback, along with the path of the function implementing
+/// them within the standard library.
+const FALLBACKS: &[(BlackBoxFunc, &str)] = &[(BlackBoxFunc::SHA256, "sha256::digest")];
+
+/// Returns the path within the standard library of the function implementing each black box
+/// function with a fallback which is not supported by the backend, keyed by the name of the
+/// black box function.
+pub fn unsupported_black_box_fallbacks(
+    is_opcode_supported: &impl Fn(&Opcode) -> bool,
+) -> BTreeMap<&'static str, &'static str> {
+    FALLBACKS
+        .iter()
+        .filter(|(func, _)| !is_opcode_supported(&Opcode::BlackBoxFuncCall(probe_call(*func))))
+        .map(|(func, path)| (func.name(), *path))
+        .collect()
+}
+
+/// A call to the black box function, only used to ask the backend whether it supports it.
+fn probe_call(func: BlackBoxFunc) -> BlackBoxFuncCall {
+    match func {
+        BlackBoxFunc::SHA256 => {
+            BlackBoxFuncCall::SHA256 { inputs: Vec::new(), outputs: Vec::new() }
+        }
+        _ => unreachable!("ICE: black box function {} has no fallback", func.name()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::circuit::{opcodes::BlackBoxFuncCall, Opcode};
+
+    use super::unsupported_black_box_fallbacks;
+
+    #[test]
+    fn only_unsupported_functions_fall_back() {
+        let fallbacks = unsupported_black_box_fallbacks(&|_| true);
+        assert!(fallbacks.is_empty());
+
+        let fallbacks = unsupported_black_box_fallbacks(&|opcode| {
+            !matches!(opcode, Opcode::BlackBoxFuncCall(BlackBoxFuncCall::SHA256 { .. }))
+        });
+        assert_eq!(fallbacks.get("sha256"), Some(&"sha256::digest"));
+    }
+}
@@ -3,7 +3,11 @@
 #![warn(unreachable_pub)]
 #![warn(clippy::semicolon_if_nothing_returned)]
 
+mod acir_disassembly;
+mod deduplication;
 mod errors;
+mod fallbacks;
+mod normalization;
 mod ssa;
 
 // SSA code to create the SSA based IR
@@ -12,6 +16,11 @@ pub mod ssa_refactor;
 
 pub mod brillig;
 
+pub use acir_disassembly::disassemble_acir;
+pub use deduplication::{deduplicate_opcodes, DeduplicationStatistics};
+pub use fallbacks::unsupported_black_box_fallbacks;
+pub use normalization::normalize_opcodes;
+
 use acvm::{
     acir::circuit::{opcodes::Opcode as AcirOpcode, Circuit, PublicInputs},
     acir::native_types::{Expression, Witness},
@@ -265,6 +274,7 @@ impl Evaluator {
                 ir_gen.abi_array(name, Some(def), &typ, *length, &witnesses);
                 witnesses
             }
+            AbiType::Tuple { .. } => return Err(tuple_parameters_unimplemented()),
         };
 
         if param_visibility == &AbiVisibility::Public {
@@ -312,6 +322,7 @@ impl Evaluator {
                     let internal_str_witnesses = self.generate_array_witnesses(length, &typ)?;
                     struct_witnesses.insert(name.clone(), internal_str_witnesses);
                 }
+                AbiType::Tuple { .. } => return Err(tuple_parameters_unimplemented()),
             }
         }
         Ok(())
@@ -360,3 +371,7 @@ impl Evaluator {
         self.num_witnesses_abi_len = self.current_witness_index as usize;
     }
 }
+
+fn tuple_parameters_unimplemented() -> RuntimeErrorKind {
+    RuntimeErrorKind::Unimplemented("tuple parameters to main require --experimental-ssa".into())
+}
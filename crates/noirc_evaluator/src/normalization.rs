@@ -0,0 +1,156 @@
+//! Normalizes the arithmetic opcodes of a circuit once its ACIR is generated.
+//!
+//! ACIR generation builds expressions by adding and scaling other expressions, which can leave
+//! several terms over the same witnesses, or terms whose coefficients cancel out. Each term
+//! takes up room in the fixed width of the backend's gates, so that these are rewritten with
+//! their like terms combined and their zero terms removed. The terms of an expression are then
+//! sorted by witness and its sign is chosen so that its first coefficient is positive, which
+//! lets equivalent expressions be recognized as such. An opcode which is left asserting that
+//! zero is zero is removed altogether.
+use std::collections::BTreeMap;
+
+use crate::ssa_refactor::OpcodeLocations;
+use acvm::{
+    acir::{
+        circuit::{Circuit, Opcode},
+        native_types::{Expression, Witness},
+    },
+    FieldElement,
+};
+
+/// Normalizes the expression of each arithmetic opcode of the circuit, removing those which
+/// always hold. The locations of the removed opcodes are removed from `opcode_locations` as
+/// well, unless no locations were recorded.
+pub fn normalize_opcodes(mut circuit: Circuit, opcode_locations: &mut OpcodeLocations) -> Circuit {
+    let mut opcodes = Vec::with_capacity(circuit.opcodes.len());
+    let mut is_kept = Vec::with_capacity(circuit.opcodes.len());
+    for opcode in circuit.opcodes {
+        let opcode = match opcode {
+            Opcode::Arithmetic(expression) => {
+                let expression = normalize_expression(&expression);
+                if expression.mul_terms.is_empty()
+                    && expression.linear_combinations.is_empty()
+                    && expression.q_c.is_zero()
+                {
+                    is_kept.push(false);
+                    continue;
+                }
+                Opcode::Arithmetic(expression)
+            }
+            other => other,
+        };
+        opcodes.push(opcode);
+        is_kept.push(true);
+    }
+
+    if opcode_locations.len() == is_kept.len() {
+        let mut is_kept = is_kept.into_iter();
+        opcode_locations.retain(|_| is_kept.next().unwrap_or_default());
+    }
+    circuit.opcodes = opcodes;
+    circuit
+}
+
+/// Returns an expression equal to zero exactly when the given one is, with its like terms
+/// combined, its zero terms removed, its terms sorted and its first coefficient positive.
+fn normalize_expression(expression: &Expression) -> Expression {
+    let mut mul_terms: BTreeMap<(Witness, Witness), FieldElement> = BTreeMap::new();
+    for (coefficient, lhs, rhs) in &expression.mul_terms {
+        let witnesses = if lhs <= rhs { (*lhs, *rhs) } else { (*rhs, *lhs) };
+        let term = mul_terms.entry(witnesses).or_insert_with(FieldElement::zero);
+        *term = *term + *coefficient;
+    }
+    let mut linear_combinations: BTreeMap<Witness, FieldElement> = BTreeMap::new();
+    for (coefficient, witness) in &expression.linear_combinations {
+        let term = linear_combinations.entry(*witness).or_insert_with(FieldElement::zero);
+        *term = *term + *coefficient;
+    }
+
+    let mut normalized = Expression {
+        mul_terms: mul_terms
+            .into_iter()
+            .filter(|(_, coefficient)| !coefficient.is_zero())
+            .map(|((lhs, rhs), coefficient)| (coefficient, lhs, rhs))
+            .collect(),
+        linear_combinations: linear_combinations
+            .into_iter()
+            .filter(|(_, coefficient)| !coefficient.is_zero())
+            .map(|(witness, coefficient)| (coefficient, witness))
+            .collect(),
+        q_c: expression.q_c,
+    };
+
+    let first_coefficient = normalized
+        .mul_terms
+        .first()
+        .map(|(coefficient, _, _)| *coefficient)
+        .or_else(|| normalized.linear_combinations.first().map(|(coefficient, _)| *coefficient))
+        .unwrap_or(normalized.q_c);
+    // Coefficients which are closer to the field modulus than to zero are considered negative
+    if (-first_coefficient).num_bits() < first_coefficient.num_bits() {
+        for (coefficient, _, _) in &mut normalized.mul_terms {
+            *coefficient = -*coefficient;
+        }
+        for (coefficient, _) in &mut normalized.linear_combinations {
+            *coefficient = -*coefficient;
+        }
+        normalized.q_c = -normalized.q_c;
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use acvm::{
+        acir::{
+            circuit::{Circuit, Opcode, PublicInputs},
+            native_types::{Expression, Witness},
+        },
+        FieldElement,
+    };
+
+    use super::normalize_opcodes;
+
+    #[test]
+    fn normalizes_arithmetic_opcodes() {
+        // -(w2 * w1) + w2 * w1 - 2 * w3 + w3 - w1 + 1
+        let expression = Expression {
+            mul_terms: vec![
+                (-FieldElement::one(), Witness(2), Witness(1)),
+                (FieldElement::one(), Witness(2), Witness(1)),
+                (FieldElement::from(3_i128), Witness(1), Witness(2)),
+            ],
+            linear_combinations: vec![
+                (-FieldElement::from(2_i128), Witness(3)),
+                (FieldElement::one(), Witness(3)),
+                (-FieldElement::one(), Witness(1)),
+            ],
+            q_c: FieldElement::one(),
+        };
+        // w1 - w1 = 0
+        let trivial = &Expression::from(Witness(1)) - &Expression::from(Witness(1));
+        let circuit = Circuit {
+            current_witness_index: 3,
+            opcodes: vec![Opcode::Arithmetic(expression), Opcode::Arithmetic(trivial)],
+            public_parameters: PublicInputs(BTreeSet::new()),
+            return_values: PublicInputs(BTreeSet::new()),
+        };
+
+        let mut opcode_locations = vec![None; 2];
+        let circuit = normalize_opcodes(circuit, &mut opcode_locations);
+
+        // 3 * w1 * w2 - w1 - w3 + 1
+        let expected = Expression {
+            mul_terms: vec![(FieldElement::from(3_i128), Witness(1), Witness(2))],
+            linear_combinations: vec![
+                (-FieldElement::one(), Witness(1)),
+                (-FieldElement::one(), Witness(3)),
+            ],
+            q_c: FieldElement::one(),
+        };
+        assert_eq!(circuit.opcodes, vec![Opcode::Arithmetic(expected)]);
+        assert_eq!(opcode_locations.len(), 1);
+    }
+}
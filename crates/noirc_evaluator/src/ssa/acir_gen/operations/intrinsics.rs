@@ -337,7 +337,8 @@ fn evaluate_println(
     ctx: &SsaContext,
     evaluator: &mut Evaluator,
 ) {
-    assert_eq!(args.len(), 1, "print statements can only support one argument");
+    // The final argument describes the type of the value being printed, which is unused here
+    assert_eq!(args.len(), 2, "print statements can only support one argument");
     let node_id = args[0];
 
     let mut log_string = "".to_owned();
@@ -1160,10 +1160,12 @@ impl SsaContext {
         match &self[node_id] {
             NodeObject::Function(FunctionKind::Builtin(opcode), ..) => match opcode {
                 builtin::Opcode::Println(_) => {
-                    // Compiler sanity check. This should be caught during typechecking
+                    // Compiler sanity check. This should be caught during typechecking.
+                    // The second argument describes the type of the value being printed,
+                    // which is only used by the new ssa pass.
                     assert_eq!(
                         arguments.len(),
-                        1,
+                        2,
                         "print statements currently only support one argument"
                     );
                     let is_string = match &arguments[0] {
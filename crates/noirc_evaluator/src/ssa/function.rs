@@ -379,13 +379,15 @@ fn is_leaf(call_graph: &[Vec<u8>], i: FuncIndex) -> bool {
     true
 }
 
+// Functions are stored in a hash map, so the leaf with the lowest index is chosen to keep the
+// inlining order, and thus the generated circuit, the same from one build to the next
 fn get_new_leaf(ctx: &SsaContext, processed: &[FuncIndex]) -> (FuncIndex, FuncId) {
-    for f in ctx.functions.values() {
-        if !processed.contains(&(f.idx)) && is_leaf(&ctx.call_graph, f.idx) {
-            return (f.idx, f.id);
-        }
-    }
-    unimplemented!("Recursive function call is not supported");
+    ctx.functions
+        .values()
+        .filter(|f| !processed.contains(&(f.idx)) && is_leaf(&ctx.call_graph, f.idx))
+        .min_by_key(|f| f.idx.0)
+        .map(|f| (f.idx, f.id))
+        .unwrap_or_else(|| unimplemented!("Recursive function call is not supported"))
 }
 
 //inline all functions of the call graph such that every inlining operates with a fully flattened function
@@ -404,6 +406,7 @@ pub(super) fn inline_all(ctx: &mut SsaContext) -> Result<(), RuntimeError> {
                 to_inline.push((f.id, f.idx));
             }
         }
+        to_inline.sort_by_key(|(_, func_idx)| func_idx.0);
         for (func_id, func_idx) in to_inline {
             super::inline::inline_cfg(ctx, func_id, Some(i.1))?;
             ctx.call_graph[func_idx.0][i.0 .0] = 0;
@@ -64,7 +64,9 @@ fn add_dummy_store(ctx: &mut SsaContext, entry: BlockId, join: BlockId) {
         block::written_along(ctx, ctx[entry].right.unwrap(), join, &mut modified);
     }
 
-    //add dummy store
+    //add dummy store, in a fixed order so that builds are reproducible
+    let mut modified: Vec<_> = modified.into_iter().collect();
+    modified.sort();
     for a in modified {
         let store = node::Operation::Store {
             array_id: a,
@@ -18,7 +18,7 @@ use noirc_frontend::{
         ArrayLiteral, Definition, Expression, For, Ident, If, LValue, Let, Literal, LocalId,
         Program, Type,
     },
-    BinaryOpKind, UnaryOp,
+    BinaryOpKind, OverflowMode, UnaryOp,
 };
 use num_bigint::BigUint;
 use num_traits::Zero;
@@ -89,6 +89,9 @@ impl IrGenerator {
             noirc_abi::AbiType::String { .. } => {
                 unreachable!("array of strings are not supported for now")
             }
+            noirc_abi::AbiType::Tuple { .. } => {
+                unreachable!("array of tuples are not supported for now")
+            }
         }
     }
 
@@ -510,6 +513,15 @@ impl IrGenerator {
                 // Note: we disallow structs/tuples in infix expressions.
                 // The type checker currently disallows this as well but not if they come from a generic type
                 // We could allow some in the future, e.g. struct == struct
+                if binary.overflow != OverflowMode::Wrapping {
+                    return Err(RuntimeError {
+                        location: Some(binary.location),
+                        kind: RuntimeErrorKind::Unimplemented(format!(
+                            "{} integer arithmetic requires --experimental-ssa",
+                            binary.overflow
+                        )),
+                    });
+                }
                 let lhs = self.ssa_gen_expression(&binary.lhs)?.to_node_ids();
                 let rhs = self.ssa_gen_expression(&binary.rhs)?.to_node_ids();
                 if lhs.len() != 1 || rhs.len() != 1 {
@@ -558,6 +570,12 @@ impl IrGenerator {
                 Ok(Value::from_slice(&call_expr.return_type, &results))
             }
             Expression::For(for_expr) => self.ssa_gen_for(for_expr),
+            Expression::While(while_expr) => Err(RuntimeError::new(
+                RuntimeErrorKind::Unimplemented(
+                    "`while` and `loop` are only supported by the experimental ssa pass".into(),
+                ),
+                Some(while_expr.location),
+            )),
             Expression::Tuple(fields) => self.ssa_gen_tuple(fields),
             Expression::If(if_expr) => self.handle_if_expr(if_expr),
             Expression::Unary(prefix) => {
@@ -571,7 +589,8 @@ impl IrGenerator {
                 Ok(tuple.into_field_member(*field))
             }
             Expression::Let(let_expr) => self.ssa_gen_let(let_expr),
-            Expression::Constrain(expr, location) => {
+            // Constraint messages are only printed by the new ssa pass
+            Expression::Constrain(expr, location, _) => {
                 self.ssa_gen_constrain(expr.as_ref(), *location)
             }
             Expression::Assign(assign) => {
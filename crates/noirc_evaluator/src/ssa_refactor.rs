@@ -7,9 +7,14 @@
 //! This module heavily borrows from Cranelift
 #![allow(dead_code)]
 
-use crate::errors::RuntimeError;
-use acvm::acir::circuit::{Circuit, PublicInputs};
+use crate::{
+    brillig::{BrilligDebugInfo, BrilligDisassembly},
+    errors::RuntimeError,
+};
+use acvm::acir::circuit::{Circuit, Opcode, PublicInputs};
 use noirc_abi::Abi;
+use noirc_errors::Location;
+use std::collections::BTreeMap;
 
 use noirc_frontend::monomorphization::ast::Program;
 
@@ -22,53 +27,124 @@ mod opt;
 mod ssa_builder;
 pub mod ssa_gen;
 
+/// The SSA of a program after one of the passes of the SSA pipeline.
+#[derive(Debug, Clone)]
+pub struct SsaPass {
+    /// The name of the pass, e.g. `mem2reg`
+    pub name: String,
+    /// The SSA of every function after the pass
+    pub ssa: String,
+    /// The control flow graph of each function after the pass in Graphviz dot format,
+    /// keyed by the function's name and id
+    pub control_flow_graphs: BTreeMap<String, String>,
+}
+
+/// The SSA of a program after each pass, in the order the passes were run.
+pub type SsaPasses = Vec<SsaPass>;
+
+/// The source location of the Noir code each ACIR opcode of a circuit was generated from,
+/// indexed like the opcodes of the circuit.
+pub type OpcodeLocations = Vec<Option<Location>>;
+
 /// Optimize the given program by converting it into SSA
 /// form and performing optimizations there. When finished,
 /// convert the final SSA into ACIR and return it.
+///
+/// Statistics about the bytecode of every unconstrained function are returned
+/// alongside the ACIR, as well as their disassembly if `show_brillig` is set.
+/// The SSA after each pass is also returned if `record_ssa_passes` is set.
+///
+/// Calls between unconstrained functions to functions with at most
+/// `brillig_inline_threshold` instructions are inlined, 0 disabling this.
+///
+/// Loops of constrained code are unrolled, which fails if the bounds of a loop are not
+/// known at compile-time or if it has more than `max_unroll` iterations.
+///
+/// Arrays are only accessed through memory opcodes if `is_opcode_supported` accepts them.
 pub(crate) fn optimize_into_acir(
     program: Program,
     allow_log_ops: bool,
-    print_ssa_passes: bool,
-) -> GeneratedAcir {
+    record_ssa_passes: bool,
+    show_brillig: bool,
+    brillig_inline_threshold: usize,
+    max_unroll: usize,
+    is_opcode_supported: &impl Fn(&Opcode) -> bool,
+) -> Result<(GeneratedAcir, BrilligDebugInfo, SsaPasses), RuntimeError> {
+    let mut ssa_passes = record_ssa_passes.then(SsaPasses::new);
     let abi_distinctness = program.return_distinctness;
     let mut ssa = ssa_gen::generate_ssa(program)
-        .print(print_ssa_passes, "Initial SSA:")
+        .record(&mut ssa_passes, "initial")
         .defunctionalize()
-        .print(print_ssa_passes, "After Defunctionalization:");
+        .record(&mut ssa_passes, "defunctionalization")
+        .inline_small_brillig_functions(brillig_inline_threshold)
+        .record(&mut ssa_passes, "brillig_inlining")
+        .remove_dead_parameters()
+        .record(&mut ssa_passes, "dead_parameter_elimination");
+
+    let brillig = ssa.to_brillig(show_brillig);
+    let brillig_debug_info = BrilligDebugInfo {
+        disassembly: if show_brillig {
+            ssa.brillig_disassembly(&brillig)
+        } else {
+            BrilligDisassembly::new()
+        },
+        statistics: ssa.brillig_statistics(&brillig),
+    };
 
-    let brillig = ssa.to_brillig();
     if let RuntimeType::Acir = ssa.main().runtime() {
         ssa = ssa
             .inline_functions()
-            .print(print_ssa_passes, "After Inlining:")
-            .unroll_loops()
-            .print(print_ssa_passes, "After Unrolling:")
+            .record(&mut ssa_passes, "inlining")
+            .unroll_loops(max_unroll)?
+            .record(&mut ssa_passes, "unrolling")
             .simplify_cfg()
-            .print(print_ssa_passes, "After Simplifying:")
+            .record(&mut ssa_passes, "simplify_cfg")
             .flatten_cfg()
-            .print(print_ssa_passes, "After Flattening:")
+            .record(&mut ssa_passes, "flattening")
             .mem2reg()
-            .print(print_ssa_passes, "After Mem2Reg:")
+            .record(&mut ssa_passes, "mem2reg")
             .fold_constants()
-            .print(print_ssa_passes, "After Constant Folding:")
+            .record(&mut ssa_passes, "constant_folding")
+            .remove_redundant_truncations()
+            .record(&mut ssa_passes, "range_analysis")
             .dead_instruction_elimination()
-            .print(print_ssa_passes, "After Dead Instruction Elimination:");
+            .record(&mut ssa_passes, "dead_instruction_elimination");
     }
-    ssa.into_acir(brillig, abi_distinctness, allow_log_ops)
+    let generated_acir =
+        ssa.into_acir(brillig, abi_distinctness, allow_log_ops, is_opcode_supported)?;
+    Ok((generated_acir, brillig_debug_info, ssa_passes.unwrap_or_default()))
 }
 
 /// Compiles the Program into ACIR and applies optimizations to the arithmetic gates
 /// This is analogous to `ssa:create_circuit` and this method is called when one wants
 /// to use the new ssa module to process Noir code.
-// TODO: This no longer needs to return a result, but it is kept to match the signature of `create_circuit`
+///
+/// The source location each opcode was generated from is returned alongside the circuit.
 pub fn experimental_create_circuit(
     program: Program,
-    enable_logging: bool,
+    record_ssa_passes: bool,
     show_output: bool,
-) -> Result<(Circuit, Abi), RuntimeError> {
+    show_brillig: bool,
+    brillig_inline_threshold: usize,
+    max_unroll: usize,
+    is_opcode_supported: &impl Fn(&Opcode) -> bool,
+) -> Result<(Circuit, Abi, BrilligDebugInfo, SsaPasses, OpcodeLocations), RuntimeError> {
     let func_sig = program.main_function_signature.clone();
-    let GeneratedAcir { current_witness_index, opcodes, return_witnesses } =
-        optimize_into_acir(program, show_output, enable_logging);
+    let (
+        GeneratedAcir {
+            current_witness_index, opcodes, opcode_locations, return_witnesses, ..
+        },
+        brillig_debug_info,
+        ssa_passes,
+    ) = optimize_into_acir(
+        program,
+        show_output,
+        record_ssa_passes,
+        show_brillig,
+        brillig_inline_threshold,
+        max_unroll,
+        is_opcode_supported,
+    )?;
 
     let abi = gen_abi(func_sig, return_witnesses.clone());
     let public_abi = abi.clone().public_abi();
@@ -79,13 +155,24 @@ pub fn experimental_create_circuit(
 
     let circuit = Circuit { current_witness_index, opcodes, public_parameters, return_values };
 
-    Ok((circuit, abi))
+    Ok((circuit, abi, brillig_debug_info, ssa_passes, opcode_locations))
 }
 
 impl Ssa {
-    fn print(self, print_ssa_passes: bool, msg: &str) -> Ssa {
-        if print_ssa_passes {
-            println!("{msg}\n{self}");
+    /// Records the SSA after the pass `name`, if the passes are being recorded.
+    ///
+    /// Debug builds also verify the SSA after each pass so that a pass producing
+    /// invalid SSA is caught right away.
+    fn record(self, ssa_passes: &mut Option<SsaPasses>, name: &str) -> Ssa {
+        if cfg!(debug_assertions) {
+            self.verify(name);
+        }
+        if let Some(ssa_passes) = ssa_passes {
+            ssa_passes.push(SsaPass {
+                name: name.to_owned(),
+                ssa: self.to_string(),
+                control_flow_graphs: self.control_flow_graphs(),
+            });
         }
         self
     }
@@ -10,13 +10,14 @@ use acvm::acir::{
 
 use acvm::{
     acir::{
-        circuit::opcodes::FunctionInput,
+        circuit::opcodes::{BlockId, FunctionInput},
         native_types::{Expression, Witness},
         BlackBoxFunc,
     },
     FieldElement,
 };
-use iter_extended::vecmap;
+use iter_extended::{try_vecmap, vecmap};
+use noirc_errors::Location;
 use std::collections::HashMap;
 use std::{borrow::Cow, hash::Hash};
 
@@ -209,6 +210,14 @@ impl AcirContext {
         rhs: AcirVar,
         typ: AcirType,
     ) -> Result<AcirVar, AcirGenError> {
+        if typ.bit_size() == 1 {
+            // Operands are booleans
+            // a + b - 2ab
+            let sum = self.add_var(lhs, rhs)?;
+            let mul = self.mul_var(lhs, rhs)?;
+            let double_mul = self.add_var(mul, mul)?;
+            return self.sub_var(sum, double_mul);
+        }
         let inputs = vec![AcirValue::Var(lhs, typ.clone()), AcirValue::Var(rhs, typ)];
         let outputs = self.black_box_function(BlackBoxFunc::XOR, inputs)?;
         Ok(outputs[0])
@@ -245,7 +254,7 @@ impl AcirContext {
             // max - ((max - a) AND (max -b))
             // Subtracting from max flips the bits, so this is effectively:
             // (NOT a) NAND (NOT b)
-            let max = self.add_constant(FieldElement::from((1_u128 << bit_size) - 1));
+            let max = self.add_constant(power_of_two(bit_size) - FieldElement::one());
             let a = self.sub_var(max, lhs)?;
             let b = self.sub_var(max, rhs)?;
             let inputs = vec![AcirValue::Var(a, typ.clone()), AcirValue::Var(b, typ)];
@@ -355,6 +364,53 @@ impl AcirContext {
         Ok(result)
     }
 
+    /// Adds a new Variable to context whose value will be constrained to be
+    /// `lhs * rhs mod 2^{bit_size}`, for integers which are too wide for
+    /// their product to fit into a field element.
+    ///
+    /// Both operands are split into a low and a high limb of k = ceil(bit_size / 2) bits:
+    /// lhs * rhs = lhs_lo * rhs_lo + 2^k * (lhs_hi * rhs_lo + lhs_lo * rhs_hi) + 2^{2k} * lhs_hi * rhs_hi
+    /// The last term vanishes modulo 2^{bit_size} while the others are small enough
+    /// to be computed and truncated without overflowing the field modulus.
+    pub(crate) fn wide_mul_var(
+        &mut self,
+        lhs: AcirVar,
+        rhs: AcirVar,
+        bit_size: u32,
+    ) -> Result<AcirVar, AcirGenError> {
+        let limb_bits = (bit_size + 1) / 2;
+        let (lhs_low, lhs_high) = self.split_var(lhs, limb_bits, bit_size)?;
+        let (rhs_low, rhs_high) = self.split_var(rhs, limb_bits, bit_size)?;
+
+        let low = self.mul_var(lhs_low, rhs_low)?;
+
+        // Only the lowest (bit_size - k) bits of the cross terms remain once they are multiplied by 2^k
+        let lhs_cross = self.mul_var(lhs_high, rhs_low)?;
+        let rhs_cross = self.mul_var(lhs_low, rhs_high)?;
+        let cross = self.add_var(lhs_cross, rhs_cross)?;
+        let cross = self.truncate_var(cross, bit_size - limb_bits, bit_size + 1)?;
+        let two_pow_limb_bits = self.add_constant(power_of_two(limb_bits));
+        let cross = self.mul_var(cross, two_pow_limb_bits)?;
+
+        let product = self.add_var(low, cross)?;
+        self.truncate_var(product, bit_size, bit_size + 2)
+    }
+
+    /// Splits `variable` of `bit_size` bits into its lowest `limb_bits` bits
+    /// and its remaining high bits.
+    fn split_var(
+        &mut self,
+        variable: AcirVar,
+        limb_bits: u32,
+        bit_size: u32,
+    ) -> Result<(AcirVar, AcirVar), AcirGenError> {
+        let low = self.truncate_var(variable, limb_bits, bit_size)?;
+        let high = self.sub_var(variable, low)?;
+        let inverse = self.add_constant(power_of_two(limb_bits).inverse());
+        let high = self.mul_var(high, inverse)?;
+        Ok((low, high))
+    }
+
     /// Adds a new Variable to context whose value will
     /// be constrained to be the subtraction of `lhs` and `rhs`
     pub(crate) fn sub_var(&mut self, lhs: AcirVar, rhs: AcirVar) -> Result<AcirVar, AcirGenError> {
@@ -382,7 +438,7 @@ impl AcirContext {
     pub(crate) fn not_var(&mut self, x: AcirVar, typ: AcirType) -> Result<AcirVar, AcirGenError> {
         let bit_size = typ.bit_size();
         // Subtracting from max flips the bits
-        let max = self.add_constant(FieldElement::from((1_u128 << bit_size) - 1));
+        let max = self.add_constant(power_of_two(bit_size) - FieldElement::one());
         self.sub_var(max, x)
     }
 
@@ -467,16 +523,35 @@ impl AcirContext {
     }
 
     /// Returns a variable which is constrained to be `lhs mod rhs`
+    ///
+    /// For signed integers the remainder has the same sign as `lhs`.
     pub(crate) fn modulo_var(
         &mut self,
         lhs: AcirVar,
         rhs: AcirVar,
-        bit_size: u32,
+        typ: AcirType,
     ) -> Result<AcirVar, AcirGenError> {
-        let (_, remainder) = self.euclidean_division_var(lhs, rhs, bit_size)?;
+        let bit_size = typ.bit_size();
+        let (_, remainder) = if typ.is_signed() {
+            self.signed_division_var(lhs, rhs, bit_size)?
+        } else {
+            self.euclidean_division_var(lhs, rhs, bit_size)?
+        };
         Ok(remainder)
     }
 
+    /// Returns an `AcirVar` which is `1` if the two's complement representation
+    /// of `variable` is negative and `0` otherwise.
+    pub(crate) fn sign_bit_var(
+        &mut self,
+        variable: AcirVar,
+        bit_size: u32,
+    ) -> Result<AcirVar, AcirGenError> {
+        let half = self.add_constant(power_of_two(bit_size - 1));
+        let (sign_bit, _) = self.euclidean_division_var(variable, half, bit_size)?;
+        Ok(sign_bit)
+    }
+
     /// Returns an `AcirVar` that is constrained to be `lhs >> rhs`.
     ///
     /// We convert right shifts to divisions, so this is equivalent to
@@ -487,6 +562,10 @@ impl AcirContext {
     ///
     /// This code is doing a field division instead of an integer division,
     /// see #1479 about how this is expected to change.
+    ///
+    /// Signed integers are shifted arithmetically: the two's complement
+    /// representation is shifted as an unsigned value and the vacated
+    /// high bits are then filled with the sign bit.
     pub(crate) fn shift_right_var(
         &mut self,
         lhs: AcirVar,
@@ -495,11 +574,26 @@ impl AcirContext {
     ) -> Result<AcirVar, AcirGenError> {
         let rhs_data = &self.vars[&rhs];
 
-        // Compute 2^{rhs}
-        let two_pow_rhs = match rhs_data.as_constant() {
-            Some(exponent) => FieldElement::from(2_i128).pow(&exponent),
+        let exponent = match rhs_data.as_constant() {
+            Some(exponent) => exponent,
             None => unimplemented!("rhs must be a constant when doing a right shift"),
         };
+
+        if let AcirType::NumericType(NumericType::Signed { bit_size }) = typ {
+            // Shifting by the bit size or more leaves only copies of the sign bit
+            let shift = exponent.to_u128().min(bit_size as u128) as u32;
+            let two_pow_shift = self.add_constant(power_of_two(shift));
+            let (shifted, _) = self.euclidean_division_var(lhs, two_pow_shift, bit_size)?;
+
+            // 2^{bit_size} - 2^{bit_size - shift} has its top `shift` bits set
+            let sign_bit = self.sign_bit_var(lhs, bit_size)?;
+            let fill = self.add_constant(power_of_two(bit_size) - power_of_two(bit_size - shift));
+            let fill = self.mul_var(sign_bit, fill)?;
+            return self.add_var(shifted, fill);
+        }
+
+        // Compute 2^{rhs}
+        let two_pow_rhs = FieldElement::from(2_i128).pow(&exponent);
         let two_pow_rhs_var = self.add_constant(two_pow_rhs);
 
         self.div_var(lhs, two_pow_rhs_var, typ)
@@ -589,15 +683,27 @@ impl AcirContext {
         &mut self,
         lhs: AcirVar,
         rhs: AcirVar,
-        bit_size: u32,
+        typ: AcirType,
         predicate: Option<AcirVar>,
     ) -> Result<AcirVar, AcirGenError> {
+        let bit_size = typ.bit_size();
         // Flip the result of calling more than equal method to
         // compute less than.
         let comparison = self.more_than_eq_var(lhs, rhs, bit_size, predicate)?;
 
         let one = self.add_constant(FieldElement::one());
-        self.sub_var(one, comparison) // comparison_negated
+        let unsigned_less_than = self.sub_var(one, comparison)?; // comparison_negated
+
+        if !typ.is_signed() {
+            return Ok(unsigned_less_than);
+        }
+
+        // Comparing two's complement representations as unsigned integers gives
+        // the wrong answer exactly when the operands have different signs.
+        let lhs_sign = self.sign_bit_var(lhs, bit_size)?;
+        let rhs_sign = self.sign_bit_var(rhs, bit_size)?;
+        let signs_differ = self.xor_var(lhs_sign, rhs_sign, AcirType::boolean())?;
+        self.xor_var(unsigned_less_than, signs_differ, AcirType::boolean())
     }
 
     /// Calls a Blackbox function on the given inputs and returns a given set of outputs
@@ -607,6 +713,11 @@ impl AcirContext {
         name: BlackBoxFunc,
         mut inputs: Vec<AcirValue>,
     ) -> Result<Vec<AcirVar>, AcirGenError> {
+        // The arrays which recursive aggregation takes must be kept apart
+        if matches!(name, BlackBoxFunc::RecursiveAggregation) {
+            return self.recursive_aggregation(inputs);
+        }
+
         // Separate out any arguments that should be constants
         let constants = match name {
             BlackBoxFunc::Pedersen => {
@@ -637,6 +748,27 @@ impl AcirContext {
         Ok(vecmap(&outputs, |witness_index| self.add_data(AcirVarData::Witness(*witness_index))))
     }
 
+    /// Verifies a proof within the circuit given the verification key, proof, public inputs,
+    /// key hash and input aggregation object, returning the output aggregation object.
+    fn recursive_aggregation(
+        &mut self,
+        inputs: Vec<AcirValue>,
+    ) -> Result<Vec<AcirVar>, AcirGenError> {
+        let inputs =
+            try_vecmap(inputs, |input| self.prepare_inputs_for_black_box_func_call(vec![input]))?;
+        let [verification_key, proof, public_inputs, key_hash, input_aggregation_object]: [_; 5] =
+            inputs.try_into().expect("ICE: recursive aggregation takes five arguments");
+
+        let outputs = self.acir_ir.recursive_aggregation(
+            verification_key,
+            proof,
+            public_inputs,
+            key_hash[0],
+            input_aggregation_object,
+        );
+        Ok(vecmap(&outputs, |witness_index| self.add_data(AcirVarData::Witness(*witness_index))))
+    }
+
     /// Black box function calls expect their inputs to be in a specific data structure (FunctionInput).
     ///
     /// This function will convert `AcirVar` into `FunctionInput` for a blackbox function call.
@@ -724,30 +856,25 @@ impl AcirContext {
     }
 
     /// Prints the given `AcirVar`s as witnesses.
-    pub(crate) fn print(&mut self, input: Vec<AcirValue>) -> Result<(), AcirGenError> {
-        let input = Self::flatten_values(input);
-
-        let witnesses = vecmap(input, |acir_var| {
-            let var_data = &self.vars[&acir_var];
-            let expr = var_data.to_expression();
-            self.acir_ir.get_or_create_witness(&expr)
+    /// Passes the given values to a foreign call which returns nothing, such as `print`.
+    /// The call is skipped whenever the predicate is false.
+    pub(crate) fn foreign_call(
+        &mut self,
+        function: &str,
+        predicate: Option<AcirVar>,
+        input: Vec<AcirValue>,
+    ) -> Result<(), AcirGenError> {
+        let input_lengths = vecmap(&input, |value| match value {
+            AcirValue::Var(..) => None,
+            AcirValue::Array(_) => Some(value.clone().flatten().len()),
         });
-        self.acir_ir.call_print(witnesses);
+        let code = brillig_directive::directive_foreign_call(function, &input_lengths);
+        self.brillig(predicate, code, input, Vec::new());
         Ok(())
     }
 
-    /// Flatten the given Vector of AcirValues into a single vector of only variables.
-    /// Each AcirValue::Array in the vector is recursively flattened, so each element
-    /// will flattened into the resulting Vec. E.g. flatten_values([1, [2, 3]) == [1, 2, 3].
-    fn flatten_values(values: Vec<AcirValue>) -> Vec<AcirVar> {
-        let mut acir_vars = Vec::with_capacity(values.len());
-        for value in values {
-            Self::flatten_value(&mut acir_vars, value);
-        }
-        acir_vars
-    }
-
-    /// Recursive helper for flatten_values to flatten a single AcirValue into the result vector.
+    /// Flatten a single AcirValue into the result vector. Arrays are flattened recursively,
+    /// e.g. flattening [1, [2, 3]] pushes 1, 2 and 3.
     pub(crate) fn flatten_value(acir_vars: &mut Vec<AcirVar>, value: AcirValue) {
         match value {
             AcirValue::Var(acir_var, _) => acir_vars.push(acir_var),
@@ -759,11 +886,38 @@ impl AcirContext {
         }
     }
 
+    /// Sets the source location of the opcodes generated from now on.
+    pub(crate) fn set_location(&mut self, location: Option<Location>) {
+        self.acir_ir.set_location(location);
+    }
+
     /// Terminates the context and takes the resulting `GeneratedAcir`
-    pub(crate) fn finish(self) -> GeneratedAcir {
+    pub(crate) fn finish(mut self) -> GeneratedAcir {
+        self.acir_ir.add_memory_block_opcodes();
         self.acir_ir
     }
 
+    /// Creates a new memory block initialized with the given values, so that they
+    /// can be accessed at indices unknown at compile-time.
+    pub(crate) fn initialize_array(&mut self, values: &[AcirVar]) -> BlockId {
+        let values = vecmap(values, |value| self.vars[value].to_expression().into_owned());
+        self.acir_ir.new_memory_block(&values)
+    }
+
+    /// Returns a new variable holding the value stored at `index` in the given memory block.
+    pub(crate) fn read_from_memory(&mut self, block_id: BlockId, index: AcirVar) -> AcirVar {
+        let index = self.vars[&index].to_expression().into_owned();
+        let value = self.acir_ir.read_from_memory_block(block_id, &index);
+        self.add_data(AcirVarData::Witness(value))
+    }
+
+    /// Stores `value` at `index` in the given memory block.
+    pub(crate) fn write_to_memory(&mut self, block_id: BlockId, index: AcirVar, value: AcirVar) {
+        let index = self.vars[&index].to_expression().into_owned();
+        let value = self.vars[&value].to_expression().into_owned();
+        self.acir_ir.write_to_memory_block(block_id, &index, &value);
+    }
+
     /// Adds `Data` into the context and assigns it a Variable.
     ///
     /// Variable can be seen as an index into the context.
@@ -928,3 +1082,8 @@ impl AcirVarData {
 /// A Reference to an `AcirVarData`
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct AcirVar(usize);
+
+/// Returns 2^{bits} as a `FieldElement`.
+pub(crate) fn power_of_two(bits: u32) -> FieldElement {
+    FieldElement::from(2_i128).pow(&FieldElement::from(bits as i128))
+}
@@ -7,8 +7,10 @@ use acvm::acir::{
     brillig_vm::Opcode as BrilligOpcode,
     circuit::{
         brillig::{Brillig as AcvmBrillig, BrilligInputs, BrilligOutputs},
-        directives::{LogInfo, QuotientDirective},
-        opcodes::{BlackBoxFuncCall, FunctionInput, Opcode as AcirOpcode},
+        directives::QuotientDirective,
+        opcodes::{
+            BlackBoxFuncCall, BlockId, FunctionInput, MemOp, MemoryBlock, Opcode as AcirOpcode,
+        },
     },
     native_types::Witness,
     BlackBoxFunc,
@@ -18,7 +20,9 @@ use acvm::{
     FieldElement,
 };
 use iter_extended::{try_vecmap, vecmap};
+use noirc_errors::Location;
 use num_bigint::BigUint;
+use std::collections::HashMap;
 
 #[derive(Debug, Default)]
 /// The output of the Acir-gen pass
@@ -31,11 +35,36 @@ pub(crate) struct GeneratedAcir {
     /// The opcodes of which the compiled ACIR will comprise.
     pub(crate) opcodes: Vec<AcirOpcode>,
 
+    /// The source location of the Noir code each opcode was generated from, if any.
+    pub(crate) opcode_locations: Vec<Option<Location>>,
+
+    /// The location given to the opcodes being added.
+    current_location: Option<Location>,
+
     /// All witness indices that comprise the final return value of the program
     ///
     /// Note: This may contain repeated indices, which is necessary for later mapping into the
     /// abi's return type.
     pub(crate) return_witnesses: Vec<Witness>,
+
+    /// The smallest number of bits each witness has been range constrained to, so that
+    /// range constraints implied by a previous one are not added again.
+    range_constraints: HashMap<Witness, u32>,
+
+    /// The memory blocks holding the arrays which are accessed at indices unknown at
+    /// compile-time. Their opcodes are only added once the whole program is generated,
+    /// since a memory block opcode contains every operation made on its block.
+    memory_blocks: Vec<GeneratedMemoryBlock>,
+}
+
+/// The operations made on a block of memory so far.
+#[derive(Debug)]
+struct GeneratedMemoryBlock {
+    len: u32,
+    trace: Vec<MemOp>,
+    /// True if the block is written to after its initialization, in which case it
+    /// must be a RAM rather than a ROM.
+    is_written: bool,
 }
 
 impl GeneratedAcir {
@@ -47,6 +76,12 @@ impl GeneratedAcir {
     /// Adds a new opcode into ACIR.
     fn push_opcode(&mut self, opcode: AcirOpcode) {
         self.opcodes.push(opcode);
+        self.opcode_locations.push(self.current_location);
+    }
+
+    /// Sets the source location of the opcodes added from now on.
+    pub(crate) fn set_location(&mut self, location: Option<Location>) {
+        self.current_location = location;
     }
 
     /// Updates the witness index counter and returns
@@ -252,17 +287,47 @@ impl GeneratedAcir {
                 let var_message_size = inputs.pop().expect("ICE: Missing message_size arg");
                 BlackBoxFuncCall::Keccak256VariableLength { inputs, var_message_size, outputs }
             }
-            // TODO(#1570): Generate ACIR for recursive aggregation
             BlackBoxFunc::RecursiveAggregation => {
-                panic!("ICE: Cannot generate ACIR for recursive aggregation")
+                unreachable!("ICE: recursive aggregation is generated by `recursive_aggregation`")
             }
         };
 
-        self.opcodes.push(AcirOpcode::BlackBoxFuncCall(black_box_func_call));
+        self.push_opcode(AcirOpcode::BlackBoxFuncCall(black_box_func_call));
 
         outputs_clone
     }
 
+    /// Verifies a proof within the circuit, returning the output aggregation object which has as
+    /// many elements as the input aggregation object. The input aggregation object is only used
+    /// once a proof has been verified, as the first proof verified has nothing to aggregate.
+    pub(crate) fn recursive_aggregation(
+        &mut self,
+        verification_key: Vec<FunctionInput>,
+        proof: Vec<FunctionInput>,
+        public_inputs: Vec<FunctionInput>,
+        key_hash: FunctionInput,
+        input_aggregation_object: Vec<FunctionInput>,
+    ) -> Vec<Witness> {
+        let has_previous_aggregation = self.opcodes.iter().any(|opcode| {
+            matches!(
+                opcode,
+                AcirOpcode::BlackBoxFuncCall(BlackBoxFuncCall::RecursiveAggregation { .. })
+            )
+        });
+        let output_aggregation_object =
+            vecmap(&input_aggregation_object, |_| self.next_witness_index());
+
+        self.push_opcode(AcirOpcode::BlackBoxFuncCall(BlackBoxFuncCall::RecursiveAggregation {
+            verification_key,
+            proof,
+            public_inputs,
+            key_hash,
+            input_aggregation_object: has_previous_aggregation.then_some(input_aggregation_object),
+            output_aggregation_object: output_aggregation_object.clone(),
+        }));
+        output_aggregation_object
+    }
+
     /// Takes an input expression and returns witnesses that are constrained to be limbs
     /// decomposed from the input for the given radix and limb count.
     ///
@@ -363,11 +428,13 @@ impl GeneratedAcir {
         let unsigned_l_witness = self.get_or_create_witness(&unsigned_lhs);
         let unsigned_r_witness = self.get_or_create_witness(&unsigned_rhs);
 
-        // Performs the division using the unsigned values of lhs and rhs
+        // Performs the division using the unsigned values of lhs and rhs.
+        // The absolute value of the smallest signed integer is 2^{max_bit_size-1},
+        // so the full bit size is needed here.
         let (q1, r1) = self.euclidean_division(
             &unsigned_l_witness.into(),
             &unsigned_r_witness.into(),
-            max_bit_size - 1,
+            max_bit_size,
             &Expression::one(),
         )?;
 
@@ -378,11 +445,23 @@ impl GeneratedAcir {
             &(&Expression::from(lhs_leading) * &Expression::from(rhs_leading)).unwrap(),
         );
         let q_sign_witness = self.get_or_create_witness(&q_sign);
+        // A zero quotient or remainder has no sign, negating it would produce 2^{max_bit_size}
+        // instead of 0.
+        let q_sign_witness = self.sign_if_non_zero(q1, q_sign_witness);
+        let r_sign_witness = self.sign_if_non_zero(r1, lhs_leading);
         let quotient = self.two_complement(&q1.into(), q_sign_witness, max_bit_size);
-        let remainder = self.two_complement(&r1.into(), lhs_leading, max_bit_size);
+        let remainder = self.two_complement(&r1.into(), r_sign_witness, max_bit_size);
         Ok((quotient, remainder))
     }
 
+    /// Returns a witness which is equal to `sign` when `value` is non-zero and 0 otherwise.
+    fn sign_if_non_zero(&mut self, value: Witness, sign: Witness) -> Witness {
+        let is_zero = self.is_equal(&value.into(), &Expression::zero());
+        let non_zero = &Expression::one() - &Expression::from(is_zero);
+        let signed = (&non_zero * &Expression::from(sign)).expect("degree is at most 2");
+        self.get_or_create_witness(&signed)
+    }
+
     /// Computes lhs/rhs by using euclidean division.
     ///
     /// Returns `q` for quotient and `r` for remainder such
@@ -417,6 +496,12 @@ impl GeneratedAcir {
         // Constrain q to be 0 <= q < 2^{max_bit_size}
         self.range_constraint(q_witness, max_bit_size)?;
 
+        // For wide integers `rhs * q` may overflow the field modulus, which would let
+        // the prover pick a quotient which only satisfies the equation below modulo p.
+        if 2 * max_bit_size + 1 >= FieldElement::max_num_bits() {
+            self.assert_product_fits(&q_witness.into(), rhs, max_bit_size)?;
+        }
+
         // a * predicate == (b * q + r) * predicate
         // => predicate * ( a - b * q - r) == 0
         // When the predicate is 0, the equation always passes.
@@ -433,6 +518,41 @@ impl GeneratedAcir {
         Ok((q_witness, r_witness))
     }
 
+    /// Constrains the product of `lhs` and `rhs`, both `max_bit_size` bit integers,
+    /// to fit into `max_bit_size` bits when computed over the integers.
+    ///
+    /// Splitting both operands into limbs of k = ceil(max_bit_size / 2) bits, the product
+    /// can only fit if one of the high limbs is zero. Requiring this bounds the product
+    /// by 2^{2k} + 2^{max_bit_size + k + 1}, so it can not overflow the field modulus.
+    fn assert_product_fits(
+        &mut self,
+        lhs: &Expression,
+        rhs: &Expression,
+        max_bit_size: u32,
+    ) -> Result<(), AcirGenError> {
+        let limb_bits = (max_bit_size + 1) / 2;
+        let lhs_high = self.high_limb(lhs, limb_bits, max_bit_size)?;
+        let rhs_high = self.high_limb(rhs, limb_bits, max_bit_size)?;
+        let product = (&Expression::from(lhs_high) * &Expression::from(rhs_high))
+            .expect("the product of two witnesses has degree 2");
+        self.push_opcode(AcirOpcode::Arithmetic(product));
+        Ok(())
+    }
+
+    /// Returns a witness holding the bits of `value` above its lowest `limb_bits` bits.
+    fn high_limb(
+        &mut self,
+        value: &Expression,
+        limb_bits: u32,
+        max_bit_size: u32,
+    ) -> Result<Witness, AcirGenError> {
+        let low = self.truncate(value, limb_bits, max_bit_size)?;
+        let inverse =
+            FieldElement::from(2_i128).pow(&FieldElement::from(limb_bits as i128)).inverse();
+        let high = inverse * &(value - &low);
+        Ok(self.get_or_create_witness(&high))
+    }
+
     /// Generate constraints that are satisfied iff
     /// lhs < rhs , when offset is 1, or
     /// lhs <= rhs, when offset is 0
@@ -468,30 +588,35 @@ impl GeneratedAcir {
             "range check with bit size of the prime field is not implemented yet"
         );
 
-        let mut lhs_offset = lhs + offset;
+        let lhs_offset = lhs + offset;
 
         // Optimization when rhs is const and fits within a u128
         if rhs.is_const() && rhs.q_c.fits_in_u128() {
             // We try to move the offset to rhs
-            let rhs_offset = if *offset == Expression::one() && rhs.q_c.to_u128() >= 1 {
-                lhs_offset = lhs.clone();
-                rhs.q_c.to_u128() - 1
+            let (lhs_offset, rhs_offset) = if *offset == Expression::one() && rhs.q_c.to_u128() >= 1
+            {
+                (lhs.clone(), rhs.q_c.to_u128() - 1)
             } else {
-                rhs.q_c.to_u128()
+                (lhs_offset.clone(), rhs.q_c.to_u128())
             };
             // we now have lhs+offset <= rhs <=> lhs_offset <= rhs_offset
 
             let bit_size = bit_size_u128(rhs_offset);
-            // r = 2^bit_size - rhs_offset
-            let r = (1_u128 << bit_size) - rhs_offset - 1;
-            // witness = lhs_offset + r
-            assert!(bits + bit_size < FieldElement::max_num_bits()); //we need to ensure lhs_offset + r does not overflow
-            let mut aor = lhs_offset;
-            aor.q_c += FieldElement::from(r);
-            let witness = self.create_witness_for_expression(&aor);
-            // lhs_offset<=rhs_offset <=> lhs_offset + r < rhs_offset + r = 2^bit_size <=> witness < 2^bit_size
-            self.range_constraint(witness, bit_size)?;
-            return Ok(());
+            // 2^bit_size must fit in a u128 and lhs_offset + r must not overflow the field,
+            // otherwise we fall back to the general case below
+            if bit_size < num_bits::<u128>() as u32
+                && bits + bit_size < FieldElement::max_num_bits()
+            {
+                // r = 2^bit_size - rhs_offset
+                let r = (1_u128 << bit_size) - rhs_offset - 1;
+                // witness = lhs_offset + r
+                let mut aor = lhs_offset;
+                aor.q_c += FieldElement::from(r);
+                let witness = self.create_witness_for_expression(&aor);
+                // lhs_offset<=rhs_offset <=> lhs_offset + r < rhs_offset + r = 2^bit_size <=> witness < 2^bit_size
+                self.range_constraint(witness, bit_size)?;
+                return Ok(());
+            }
         }
 
         // General case:  lhs_offset<=rhs <=> rhs-lhs_offset>=0 <=> rhs-lhs_offset is a 'bits' bit integer
@@ -517,13 +642,6 @@ impl GeneratedAcir {
         &expr_squared - expr
     }
 
-    /// Adds a log directive to print the provided witnesses.
-    ///
-    /// Logging of strings is currently unsupported.
-    pub(crate) fn call_print(&mut self, witnesses: Vec<Witness>) {
-        self.push_opcode(AcirOpcode::Directive(Directive::Log(LogInfo::WitnessOutput(witnesses))));
-    }
-
     /// Adds an inversion brillig opcode.
     ///
     /// This code will invert `expr` without applying constraints
@@ -662,6 +780,11 @@ impl GeneratedAcir {
             });
         };
 
+        if self.range_constraints.get(&witness).map_or(false, |bits| *bits <= num_bits) {
+            return Ok(());
+        }
+        self.range_constraints.insert(witness, num_bits);
+
         let constraint = AcirOpcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
             input: FunctionInput { witness, num_bits },
         });
@@ -762,6 +885,80 @@ impl GeneratedAcir {
         self.push_opcode(opcode);
     }
 
+    /// Creates a new memory block initialized with the given values and returns its id.
+    pub(crate) fn new_memory_block(&mut self, values: &[Expression]) -> BlockId {
+        let block_id = BlockId(self.memory_blocks.len() as u32);
+        let mut trace = Vec::with_capacity(values.len());
+        for (index, value) in values.iter().enumerate() {
+            let index = self
+                .get_or_create_witness(&Expression::from_field(FieldElement::from(index as u128)));
+            let value = self.get_or_create_witness(value);
+            trace.push(MemOp {
+                operation: Expression::one(),
+                index: index.into(),
+                value: value.into(),
+            });
+        }
+        self.memory_blocks.push(GeneratedMemoryBlock {
+            len: values.len() as u32,
+            trace,
+            is_written: false,
+        });
+        block_id
+    }
+
+    /// Returns a new witness holding the value stored at `index` in the given memory block.
+    pub(crate) fn read_from_memory_block(
+        &mut self,
+        block_id: BlockId,
+        index: &Expression,
+    ) -> Witness {
+        let index = self.get_or_create_witness(index);
+        let value = self.next_witness_index();
+        let block = &mut self.memory_blocks[block_id.0 as usize];
+        block.trace.push(MemOp {
+            operation: Expression::zero(),
+            index: index.into(),
+            value: value.into(),
+        });
+        value
+    }
+
+    /// Stores `value` at `index` in the given memory block.
+    pub(crate) fn write_to_memory_block(
+        &mut self,
+        block_id: BlockId,
+        index: &Expression,
+        value: &Expression,
+    ) {
+        let index = self.get_or_create_witness(index);
+        let value = self.get_or_create_witness(value);
+        let block = &mut self.memory_blocks[block_id.0 as usize];
+        block.trace.push(MemOp {
+            operation: Expression::one(),
+            index: index.into(),
+            value: value.into(),
+        });
+        block.is_written = true;
+    }
+
+    /// Adds the opcodes of the memory blocks: blocks which are only read from once initialized
+    /// become ROM opcodes while the others become RAM opcodes.
+    pub(crate) fn add_memory_block_opcodes(&mut self) {
+        // A memory block is accessed from many places, so its opcode has no single location
+        self.set_location(None);
+        for (id, block) in std::mem::take(&mut self.memory_blocks).into_iter().enumerate() {
+            let memory_block =
+                MemoryBlock { id: BlockId(id as u32), len: block.len, trace: block.trace };
+            let opcode = if block.is_written {
+                AcirOpcode::RAM(memory_block)
+            } else {
+                AcirOpcode::ROM(memory_block)
+            };
+            self.push_opcode(opcode);
+        }
+    }
+
     /// Generate gates and control bits witnesses which ensure that out_expr is a permutation of in_expr
     /// Add the control bits of the sorting network used to generate the constrains
     /// into the PermutationSort directive for solving in ACVM.
@@ -810,11 +1007,9 @@ fn black_box_func_expected_input_size(name: BlackBoxFunc) -> Option<usize> {
         // Inputs for fixed based scalar multiplication
         // is just a scalar
         BlackBoxFunc::FixedBaseScalarMul => Some(1),
-        // TODO(#1570): Generate ACIR for recursive aggregation
-        // RecursiveAggregation has variable inputs and we could return `None` here,
-        // but as it is not fully implemented we panic for now
+        // Recursive aggregation has variable inputs, which are checked by `recursive_aggregation`
         BlackBoxFunc::RecursiveAggregation => {
-            panic!("ICE: Cannot generate ACIR for recursive aggregation")
+            unreachable!("ICE: recursive aggregation is generated by `recursive_aggregation`")
         }
     }
 }
@@ -840,9 +1035,9 @@ fn black_box_expected_output_size(name: BlackBoxFunc) -> u32 {
         // Output of fixed based scalar mul over the embedded curve
         // will be 2 field elements representing the point.
         BlackBoxFunc::FixedBaseScalarMul => 2,
-        // TODO(#1570): Generate ACIR for recursive aggregation
+        // Recursive aggregation returns as many outputs as its input aggregation object has
         BlackBoxFunc::RecursiveAggregation => {
-            panic!("ICE: Cannot generate ACIR for recursive aggregation")
+            unreachable!("ICE: recursive aggregation is generated by `recursive_aggregation`")
         }
     }
 }
@@ -2,13 +2,16 @@
 
 use std::collections::HashMap;
 
-use crate::brillig::{
-    brillig_gen::brillig_fn::FunctionContext as BrilligFunctionContext,
-    brillig_ir::artifact::BrilligArtifact, Brillig,
+use crate::{
+    brillig::{
+        brillig_gen::brillig_fn::FunctionContext as BrilligFunctionContext,
+        brillig_ir::artifact::BrilligArtifact, Brillig,
+    },
+    errors::{RuntimeError, RuntimeErrorKind},
 };
 
 use self::acir_ir::{
-    acir_variable::{AcirContext, AcirType, AcirVar},
+    acir_variable::{power_of_two, AcirContext, AcirType, AcirVar},
     errors::AcirGenError,
 };
 use super::{
@@ -25,7 +28,14 @@ use super::{
     ssa_gen::Ssa,
 };
 use acvm::{
-    acir::{brillig_vm::Opcode, native_types::Expression},
+    acir::{
+        brillig_vm::Opcode,
+        circuit::{
+            opcodes::{BlockId, MemoryBlock},
+            Opcode as AcirOpcode,
+        },
+        native_types::Expression,
+    },
     FieldElement,
 };
 use iter_extended::vecmap;
@@ -35,6 +45,12 @@ use noirc_abi::AbiDistinctness;
 
 mod acir_ir;
 
+/// The widest integer type supported in ACIR.
+///
+/// Products of integers wider than half the field's bit size are computed
+/// by splitting the operands into limbs, see `AcirContext::wide_mul_var`.
+const MAX_INTEGER_BIT_SIZE: u32 = 128;
+
 /// Context struct for the acir generation pass.
 /// May be similar to the Evaluator struct in the current SSA IR.
 #[derive(Default)]
@@ -53,6 +69,22 @@ struct Context {
 
     /// Manages and builds the `AcirVar`s to which the converted SSA values refer.
     acir_context: AcirContext,
+
+    /// Maps the SSA arrays which are accessed at indices unknown at compile-time to the
+    /// memory block holding their flattened elements.
+    memory_blocks: HashMap<ValueId, BlockId>,
+
+    /// Maps each memory block to the number of flattened elements it holds. Slices do not carry
+    /// their capacity in their type, so it is remembered here when the block is initialized.
+    memory_block_lengths: HashMap<BlockId, usize>,
+
+    /// Maps each SSA value to the last instruction using it. Values used by the
+    /// terminator of the block are missing from this map.
+    last_uses: HashMap<ValueId, InstructionId>,
+
+    /// True if the backend does not support both ROM and RAM opcodes, in which case arrays are
+    /// accessed at indices unknown at compile-time by selecting among each of their elements.
+    memory_opcodes_unsupported: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -83,9 +115,15 @@ impl Ssa {
         brillig: Brillig,
         abi_distinctness: AbiDistinctness,
         allow_log_ops: bool,
-    ) -> GeneratedAcir {
-        let context = Context::default();
-        let mut generated_acir = context.convert_ssa(self, brillig, allow_log_ops);
+        is_opcode_supported: &impl Fn(&AcirOpcode) -> bool,
+    ) -> Result<GeneratedAcir, RuntimeError> {
+        let memory_block = MemoryBlock { id: BlockId(0), len: 0, trace: Vec::new() };
+        let memory_opcodes_unsupported =
+            !is_opcode_supported(&AcirOpcode::ROM(memory_block.clone()))
+                || !is_opcode_supported(&AcirOpcode::RAM(memory_block));
+
+        let context = Context { memory_opcodes_unsupported, ..Context::default() };
+        let mut generated_acir = context.convert_ssa(self, brillig, allow_log_ops)?;
 
         match abi_distinctness {
             AbiDistinctness::Distinct => {
@@ -102,20 +140,25 @@ impl Ssa {
                     .collect();
 
                 generated_acir.return_witnesses = distinct_return_witness;
-                generated_acir
+                Ok(generated_acir)
             }
-            AbiDistinctness::DuplicationAllowed => generated_acir,
+            AbiDistinctness::DuplicationAllowed => Ok(generated_acir),
         }
     }
 }
 
 impl Context {
     /// Converts SSA into ACIR
-    fn convert_ssa(self, ssa: Ssa, brillig: Brillig, allow_log_ops: bool) -> GeneratedAcir {
+    fn convert_ssa(
+        self,
+        ssa: Ssa,
+        brillig: Brillig,
+        allow_log_ops: bool,
+    ) -> Result<GeneratedAcir, RuntimeError> {
         let main_func = ssa.main();
         match main_func.runtime() {
             RuntimeType::Acir => self.convert_acir_main(main_func, &ssa, brillig, allow_log_ops),
-            RuntimeType::Brillig => self.convert_brillig_main(main_func, brillig),
+            RuntimeType::Brillig => Ok(self.convert_brillig_main(main_func, brillig)),
         }
     }
 
@@ -125,19 +168,22 @@ impl Context {
         ssa: &Ssa,
         brillig: Brillig,
         allow_log_ops: bool,
-    ) -> GeneratedAcir {
+    ) -> Result<GeneratedAcir, RuntimeError> {
         let dfg = &main_func.dfg;
         let entry_block = &dfg[main_func.entry_block()];
 
         self.convert_ssa_block_params(entry_block.parameters(), dfg);
+        self.last_uses = find_last_uses(entry_block.instructions(), entry_block.terminator(), dfg);
 
         for instruction_id in entry_block.instructions() {
-            self.convert_ssa_instruction(*instruction_id, dfg, ssa, &brillig, allow_log_ops);
+            self.acir_context.set_location(dfg.get_location(instruction_id));
+            self.convert_ssa_instruction(*instruction_id, dfg, ssa, &brillig, allow_log_ops)?;
         }
+        self.acir_context.set_location(None);
 
         self.convert_ssa_return(entry_block.terminator().unwrap(), dfg);
 
-        self.acir_context.finish()
+        Ok(self.acir_context.finish())
     }
 
     fn convert_brillig_main(mut self, main_func: &Function, brillig: Brillig) -> GeneratedAcir {
@@ -228,7 +274,7 @@ impl Context {
         ssa: &Ssa,
         brillig: &Brillig,
         allow_log_ops: bool,
-    ) {
+    ) -> Result<(), RuntimeError> {
         let instruction = &dfg[instruction_id];
 
         match instruction {
@@ -277,13 +323,17 @@ impl Context {
                         }
                     }
                     Value::Intrinsic(intrinsic) => {
-                        let outputs = self.convert_ssa_intrinsic_call(
-                            *intrinsic,
-                            arguments,
-                            dfg,
-                            allow_log_ops,
-                            result_ids,
-                        );
+                        let outputs = self
+                            .convert_ssa_intrinsic_call(
+                                *intrinsic,
+                                arguments,
+                                dfg,
+                                allow_log_ops,
+                                result_ids,
+                            )
+                            .map_err(|kind| {
+                                RuntimeError::new(kind, dfg.get_location(&instruction_id))
+                            })?;
 
                         // Issue #1438 causes this check to fail with intrinsics that return 0
                         // results but the ssa form instead creates 1 unit result value.
@@ -321,10 +371,10 @@ impl Context {
                 self.current_side_effects_enabled_var = Some(acir_var);
             }
             Instruction::ArrayGet { array, index } => {
-                self.handle_array_operation(instruction_id, *array, *index, None, dfg);
+                self.handle_array_operation(instruction_id, *array, *index, None, dfg)?;
             }
             Instruction::ArraySet { array, index, value } => {
-                self.handle_array_operation(instruction_id, *array, *index, Some(*value), dfg);
+                self.handle_array_operation(instruction_id, *array, *index, Some(*value), dfg)?;
             }
             Instruction::Allocate => {
                 unreachable!("Expected all allocate instructions to be removed before acir_gen")
@@ -336,6 +386,7 @@ impl Context {
                 unreachable!("Expected all load instructions to be removed before acir_gen")
             }
         }
+        Ok(())
     }
 
     fn gen_brillig_for(&self, func: &Function, brillig: &Brillig) -> Vec<Opcode> {
@@ -366,10 +417,21 @@ impl Context {
         index: ValueId,
         store_value: Option<ValueId>,
         dfg: &DataFlowGraph,
-    ) {
+    ) -> Result<(), RuntimeError> {
+        let array = dfg.resolve(array);
+        let constant_index = dfg.get_numeric_constant(index);
+        if constant_index.is_none() || self.memory_blocks.contains_key(&array) {
+            return self.handle_dynamic_array_operation(
+                instruction,
+                array,
+                index,
+                store_value,
+                dfg,
+            );
+        }
+
         let array = self.convert_array_value(array, dfg);
-        let index = dfg
-            .get_numeric_constant(index)
+        let index = constant_index
             .expect("Expected array index to be a known constant")
             .try_to_u64()
             .expect("Expected array index to fit into a u64") as usize;
@@ -385,7 +447,7 @@ impl Context {
             let result_type = dfg.type_of_value(dfg.instruction_results(instruction)[0]);
             let value = self.create_default_value(&result_type);
             self.define_result(dfg, instruction, value);
-            return;
+            return Ok(());
         }
 
         let value = match store_value {
@@ -397,6 +459,217 @@ impl Context {
         };
 
         self.define_result(dfg, instruction, value);
+        Ok(())
+    }
+
+    /// Handles an ArrayGet or ArraySet instruction whose index is unknown at compile-time, or
+    /// whose array is already held in a memory block. The elements of the array are accessed
+    /// through memory opcodes rather than by selecting among every element of the array,
+    /// unless the backend does not support them.
+    fn handle_dynamic_array_operation(
+        &mut self,
+        instruction: InstructionId,
+        array: ValueId,
+        index: ValueId,
+        store_value: Option<ValueId>,
+        dfg: &DataFlowGraph,
+    ) -> Result<(), RuntimeError> {
+        // Each element of the array spans as many entries of the memory block
+        let element_size = match dfg.type_of_value(array) {
+            Type::Array(element_types, _) | Type::Slice(element_types) => {
+                let sizes = vecmap(element_types.iter(), flattened_size);
+                if sizes.iter().any(|size| *size != sizes[0]) {
+                    let kind = RuntimeErrorKind::UnsupportedDynamicIndexing;
+                    return Err(RuntimeError::new(kind, dfg.get_location(&instruction)));
+                }
+                sizes[0]
+            }
+            typ => unreachable!("Expected an array, found {typ}"),
+        };
+
+        // Accesses made while side effects are disabled are redirected to the first element,
+        // as the memory opcode would fail on an index out of bounds
+        let mut index = self.convert_numeric_value(index, dfg);
+        let predicate = self
+            .current_side_effects_enabled_var
+            .filter(|predicate| !self.acir_context.is_constant_one(predicate));
+        if let Some(predicate) = predicate {
+            index = self
+                .acir_context
+                .mul_var(index, predicate)
+                .expect("add Result types to all methods so errors bubble up");
+        }
+
+        if self.memory_opcodes_unsupported {
+            self.select_array_element(instruction, array, index, predicate, store_value, dfg);
+            return Ok(());
+        }
+
+        let element_size_var =
+            self.acir_context.add_constant(FieldElement::from(element_size as u128));
+        let base_index = self
+            .acir_context
+            .mul_var(index, element_size_var)
+            .expect("add Result types to all methods so errors bubble up");
+        let indices = vecmap(0..element_size, |offset| {
+            let offset = self.acir_context.add_constant(FieldElement::from(offset as u128));
+            self.acir_context
+                .add_var(base_index, offset)
+                .expect("add Result types to all methods so errors bubble up")
+        });
+
+        match store_value {
+            None => {
+                let block_id = match self.memory_blocks.get(&array) {
+                    Some(block_id) => *block_id,
+                    None => {
+                        let block_id = self.initialize_memory_block(array, dfg);
+                        self.memory_blocks.insert(array, block_id);
+                        block_id
+                    }
+                };
+                let values =
+                    vecmap(indices, |index| self.acir_context.read_from_memory(block_id, index));
+                let result_type = dfg.type_of_value(dfg.instruction_results(instruction)[0]);
+                let value = Self::convert_var_type_to_values(&result_type, &mut values.into_iter());
+                self.define_result(dfg, instruction, value);
+            }
+            Some(store_value) => {
+                // The block of the array is updated in place if the array is not used afterwards,
+                // otherwise the new array is written to a copy of it
+                let block_id = match self.memory_blocks.get(&array) {
+                    Some(block_id) if self.last_uses.get(&array) == Some(&instruction) => *block_id,
+                    _ => self.initialize_memory_block(array, dfg),
+                };
+
+                let mut store_values = Vec::with_capacity(element_size);
+                AcirContext::flatten_value(&mut store_values, self.convert_value(store_value, dfg));
+
+                for (index, value) in indices.into_iter().zip(store_values) {
+                    // Writes made while side effects are disabled store the current value back
+                    let value = match predicate {
+                        Some(predicate) => {
+                            let current = self.acir_context.read_from_memory(block_id, index);
+                            self.acir_context
+                                .sub_var(value, current)
+                                .and_then(|diff| self.acir_context.mul_var(diff, predicate))
+                                .and_then(|diff| self.acir_context.add_var(current, diff))
+                                .expect("add Result types to all methods so errors bubble up")
+                        }
+                        None => value,
+                    };
+                    self.acir_context.write_to_memory(block_id, index, value);
+                }
+
+                let result = dfg.instruction_results(instruction)[0];
+                self.memory_blocks.insert(result, block_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles an ArrayGet or ArraySet instruction whose index is unknown at compile-time
+    /// without memory opcodes, for backends which do not support them. Each element of the
+    /// array is compared with the index: a read sums the elements weighted by these comparisons
+    /// while a write updates every element, keeping its value unless it is at the index.
+    fn select_array_element(
+        &mut self,
+        instruction: InstructionId,
+        array: ValueId,
+        index: AcirVar,
+        predicate: Option<AcirVar>,
+        store_value: Option<ValueId>,
+        dfg: &DataFlowGraph,
+    ) {
+        let elements = self.convert_array_value(array, dfg);
+        let is_at_index = vecmap(0..elements.len(), |position| {
+            let position = self.acir_context.add_constant(FieldElement::from(position as u128));
+            self.acir_context
+                .eq_var(index, position)
+                .expect("add Result types to all methods so errors bubble up")
+        });
+
+        match store_value {
+            None => {
+                // Exactly one element is at the index, as a memory opcode would fail otherwise
+                let zero = self.acir_context.add_constant(FieldElement::zero());
+                let one = self.acir_context.add_constant(FieldElement::one());
+                let in_bounds = is_at_index.iter().fold(zero, |sum, is_at_index| {
+                    self.acir_context
+                        .add_var(sum, *is_at_index)
+                        .expect("add Result types to all methods so errors bubble up")
+                });
+                self.acir_context
+                    .assert_eq_var(in_bounds, one)
+                    .expect("add Result types to all methods so errors bubble up");
+
+                let result_type = dfg.type_of_value(dfg.instruction_results(instruction)[0]);
+                let mut values = vec![zero; flattened_size(&result_type)];
+                for (element, is_at_index) in elements.into_iter().zip(is_at_index) {
+                    for (value, (element, _)) in values.iter_mut().zip(element.flatten()) {
+                        *value = self
+                            .acir_context
+                            .mul_var(element, is_at_index)
+                            .and_then(|selected| self.acir_context.add_var(*value, selected))
+                            .expect("add Result types to all methods so errors bubble up");
+                    }
+                }
+                let value = Self::convert_var_type_to_values(&result_type, &mut values.into_iter());
+                self.define_result(dfg, instruction, value);
+            }
+            Some(store_value) => {
+                let mut store_values = Vec::new();
+                AcirContext::flatten_value(&mut store_values, self.convert_value(store_value, dfg));
+
+                let elements =
+                    vecmap(elements.into_iter().zip(is_at_index), |(element, is_at_index)| {
+                        // Writes made while side effects are disabled leave every element unchanged
+                        let is_written = match predicate {
+                            Some(predicate) => self
+                                .acir_context
+                                .mul_var(is_at_index, predicate)
+                                .expect("add Result types to all methods so errors bubble up"),
+                            None => is_at_index,
+                        };
+                        let mut store_values = store_values.iter();
+                        self.map_flattened_value(element, &mut |this, current| {
+                            let value = *store_values
+                                .next()
+                                .expect("the stored value has the size of an element");
+                            this.acir_context
+                                .sub_var(value, current)
+                                .and_then(|diff| this.acir_context.mul_var(diff, is_written))
+                                .and_then(|diff| this.acir_context.add_var(current, diff))
+                                .expect("add Result types to all methods so errors bubble up")
+                        })
+                    });
+                self.define_result(dfg, instruction, AcirValue::Array(elements.into()));
+            }
+        }
+    }
+
+    /// Returns a value of the same shape as `value`, whose variables are mapped by `f`.
+    fn map_flattened_value(
+        &mut self,
+        value: AcirValue,
+        f: &mut impl FnMut(&mut Self, AcirVar) -> AcirVar,
+    ) -> AcirValue {
+        match value {
+            AcirValue::Var(var, typ) => AcirValue::Var(f(self, var), typ),
+            AcirValue::Array(elements) => {
+                let elements = vecmap(elements, |element| self.map_flattened_value(element, f));
+                AcirValue::Array(elements.into())
+            }
+        }
+    }
+
+    /// Creates a new memory block holding the flattened elements of the given array.
+    fn initialize_memory_block(&mut self, array: ValueId, dfg: &DataFlowGraph) -> BlockId {
+        let mut values = Vec::new();
+        AcirContext::flatten_value(&mut values, self.convert_value(array, dfg));
+        let block_id = self.acir_context.initialize_array(&values);
+        self.memory_block_lengths.insert(block_id, values.len());
+        block_id
     }
 
     /// Remember the result of an instruction returning a single value
@@ -457,6 +730,25 @@ impl Context {
             return acir_value.clone();
         }
 
+        // Arrays held in memory are read back in full when used as a whole
+        if let Some(block_id) = self.memory_blocks.get(&value_id).copied() {
+            let size = self.memory_block_lengths[&block_id];
+            let vars = vecmap(0..size, |index| {
+                let index = self.acir_context.add_constant(FieldElement::from(index as u128));
+                self.acir_context.read_from_memory(block_id, index)
+            });
+            let typ = match dfg.type_of_value(value_id) {
+                Type::Slice(element_types) => {
+                    let element_size = element_types.iter().map(flattened_size).sum::<usize>();
+                    Type::Array(element_types, size / element_size)
+                }
+                typ => typ,
+            };
+            let acir_value = Self::convert_var_type_to_values(&typ, &mut vars.into_iter());
+            self.ssa_values.insert(value_id, acir_value.clone());
+            return acir_value;
+        }
+
         let acir_value = match value {
             Value::NumericConstant { constant, typ } => {
                 AcirValue::Var(self.acir_context.add_constant(*constant), typ.into())
@@ -509,14 +801,10 @@ impl Context {
         match &binary_type {
             Type::Numeric(NumericType::Unsigned { bit_size })
             | Type::Numeric(NumericType::Signed { bit_size }) => {
-                // Conservative max bit size that is small enough such that two operands can be
-                // multiplied and still fit within the field modulus. This is necessary for the
-                // truncation technique: result % 2^bit_size to be valid.
-                let max_integer_bit_size = FieldElement::max_num_bits() / 2;
-                if *bit_size > max_integer_bit_size {
+                if *bit_size > MAX_INTEGER_BIT_SIZE {
                     return Err(AcirGenError::UnsupportedIntegerSize {
                         num_bits: *bit_size,
-                        max_num_bits: max_integer_bit_size,
+                        max_num_bits: MAX_INTEGER_BIT_SIZE,
                     });
                 }
             }
@@ -524,12 +812,18 @@ impl Context {
         }
 
         let binary_type = AcirType::from(binary_type);
-        let bit_count = binary_type.bit_size();
 
         match binary.operator {
             BinaryOp::Add => self.acir_context.add_var(lhs, rhs),
             BinaryOp::Sub => self.acir_context.sub_var(lhs, rhs),
-            BinaryOp::Mul => self.acir_context.mul_var(lhs, rhs),
+            BinaryOp::Mul => match binary_type {
+                AcirType::NumericType(
+                    NumericType::Signed { bit_size } | NumericType::Unsigned { bit_size },
+                ) if product_may_overflow_field(bit_size) => {
+                    self.acir_context.wide_mul_var(lhs, rhs, bit_size)
+                }
+                _ => self.acir_context.mul_var(lhs, rhs),
+            },
             BinaryOp::Div => self.acir_context.div_var(lhs, rhs, binary_type),
             // Note: that this produces unnecessary constraints when
             // this Eq instruction is being used for a constrain statement
@@ -537,7 +831,7 @@ impl Context {
             BinaryOp::Lt => self.acir_context.less_than_var(
                 lhs,
                 rhs,
-                bit_count,
+                binary_type,
                 self.current_side_effects_enabled_var,
             ),
             BinaryOp::Shl => self.acir_context.shift_left_var(lhs, rhs, binary_type),
@@ -545,7 +839,7 @@ impl Context {
             BinaryOp::Xor => self.acir_context.xor_var(lhs, rhs, binary_type),
             BinaryOp::And => self.acir_context.and_var(lhs, rhs, binary_type),
             BinaryOp::Or => self.acir_context.or_var(lhs, rhs, binary_type),
-            BinaryOp::Mod => self.acir_context.modulo_var(lhs, rhs, bit_count),
+            BinaryOp::Mod => self.acir_context.modulo_var(lhs, rhs, binary_type),
         }
     }
 
@@ -600,7 +894,9 @@ impl Context {
 
     /// Returns an `AcirVar` that is constrained to fit in the target type by truncating the input.
     /// If the target cast is to a `NativeField`, no truncation is required so the cast becomes a
-    /// no-op.
+    /// no-op, unless the input is a negative signed integer which is mapped to its field negation.
+    ///
+    /// Signed integers are sign-extended when cast into a larger integer type.
     fn convert_ssa_cast(
         &mut self,
         value_id: &ValueId,
@@ -617,21 +913,36 @@ impl Context {
         };
         match target_numeric {
             NumericType::NativeField => {
-                // Casting into a Field as a no-op
-                Ok(variable)
-            }
-            NumericType::Unsigned { bit_size } => {
-                if incoming_type.is_signed() {
-                    todo!("Cast from unsigned to signed")
+                if !incoming_type.is_signed() {
+                    // Casting into a Field as a no-op
+                    return Ok(variable);
                 }
+                // x - sign(x) * 2^{bit_size}
+                let incoming_bit_size = incoming_type.bit_size();
+                let sign_bit = self.acir_context.sign_bit_var(variable, incoming_bit_size)?;
+                let modulus = self.acir_context.add_constant(power_of_two(incoming_bit_size));
+                let offset = self.acir_context.mul_var(sign_bit, modulus)?;
+                self.acir_context.sub_var(variable, offset)
+            }
+            NumericType::Signed { bit_size } | NumericType::Unsigned { bit_size } => {
+                // Signed and unsigned integers share the same representation,
+                // so only the bit size and the sign of the input matter here.
                 let max_bit_size = incoming_type.bit_size();
+                if incoming_type.is_signed() && max_bit_size < *bit_size {
+                    // Sign-extend by setting every bit above the incoming sign bit:
+                    // x + sign(x) * (2^{bit_size} - 2^{max_bit_size})
+                    let sign_bit = self.acir_context.sign_bit_var(variable, max_bit_size)?;
+                    let extension = power_of_two(*bit_size) - power_of_two(max_bit_size);
+                    let extension = self.acir_context.add_constant(extension);
+                    let extension = self.acir_context.mul_var(sign_bit, extension)?;
+                    return self.acir_context.add_var(variable, extension);
+                }
                 if max_bit_size <= *bit_size {
                     // Incoming variable already fits into target bit size -  this is a no-op
                     return Ok(variable);
                 }
                 self.acir_context.truncate_var(variable, *bit_size, max_bit_size)
             }
-            NumericType::Signed { .. } => todo!("Cast into signed"),
         }
     }
 
@@ -648,12 +959,17 @@ impl Context {
             Value::Instruction { instruction, .. } => &dfg[*instruction],
             _ => unreachable!("ICE: Truncates are only ever applied to the result of a binary op"),
         };
+        if matches!(truncation_target, Instruction::Binary(Binary { operator: BinaryOp::Mul, .. }))
+            && product_may_overflow_field(bit_size)
+        {
+            // Wide products are already reduced modulo 2^{bit_size} by `wide_mul_var`.
+            return Ok(var);
+        }
         if matches!(truncation_target, Instruction::Binary(Binary { operator: BinaryOp::Sub, .. }))
         {
             // Subtractions must first have the integer modulus added before truncation can be
             // applied. This is done in order to prevent underflow.
-            let integer_modulus =
-                self.acir_context.add_constant(FieldElement::from(2_u128.pow(bit_size)));
+            let integer_modulus = self.acir_context.add_constant(power_of_two(bit_size));
             var = self.acir_context.add_var(var, integer_modulus)?;
         }
 
@@ -670,8 +986,8 @@ impl Context {
         dfg: &DataFlowGraph,
         allow_log_ops: bool,
         result_ids: &[ValueId],
-    ) -> Vec<AcirValue> {
-        match intrinsic {
+    ) -> Result<Vec<AcirValue>, RuntimeErrorKind> {
+        let values = match intrinsic {
             Intrinsic::BlackBox(black_box) => {
                 let inputs = vecmap(arguments, |arg| self.convert_value(*arg, dfg));
 
@@ -705,12 +1021,27 @@ impl Context {
                 let inputs = vecmap(arguments, |arg| self.convert_value(*arg, dfg));
                 if allow_log_ops {
                     self.acir_context
-                        .print(inputs)
+                        .foreign_call("print", self.current_side_effects_enabled_var, inputs)
                         .expect("add Result types to all methods so errors bubble up");
                 }
                 Vec::new()
             }
+            Intrinsic::AssertMessage => {
+                // Unlike printing, the messages of failing assertions are always reported
+                let inputs = vecmap(arguments, |arg| self.convert_value(*arg, dfg));
+                self.acir_context
+                    .foreign_call("assert_message", self.current_side_effects_enabled_var, inputs)
+                    .expect("add Result types to all methods so errors bubble up");
+                Vec::new()
+            }
             Intrinsic::Sort => {
+                // Only the contents of a slice are sorted, its length is returned as is
+                let (length, arguments) = match dfg.type_of_value(arguments[arguments.len() - 1]) {
+                    Type::Slice(_) => {
+                        (Some(self.convert_numeric_value(arguments[0], dfg)), &arguments[1..])
+                    }
+                    _ => (None, arguments),
+                };
                 let inputs = vecmap(arguments, |arg| self.convert_value(*arg, dfg));
                 // We flatten the inputs and retrieve the bit_size of the elements
                 let mut input_vars = Vec::new();
@@ -730,13 +1061,199 @@ impl Context {
                     }
                 }
                 // Generate the sorted output variables
-                let out_vars =
+                let mut out_vars =
                     self.acir_context.sort(input_vars, bit_size).expect("Could not sort");
+                if let Some(length) = length {
+                    out_vars.insert(0, length);
+                }
 
                 Self::convert_vars_to_values(out_vars, dfg, result_ids)
             }
+            Intrinsic::SlicePushBack
+            | Intrinsic::SlicePushFront
+            | Intrinsic::SlicePopBack
+            | Intrinsic::SlicePopFront => {
+                self.convert_slice_intrinsic_call(intrinsic, arguments, dfg, result_ids)?
+            }
             _ => todo!("expected a black box function"),
-        }
+        };
+        Ok(values)
+    }
+
+    /// Returns the `AcirValue`s resulting from pushing to or popping from a slice.
+    ///
+    /// The first two arguments of each slice intrinsic are the length and the contents of the
+    /// slice. The contents may hold more elements than the length when the length is only known
+    /// at runtime, in which case the back of the slice is found by comparing the length against
+    /// every position of the contents.
+    fn convert_slice_intrinsic_call(
+        &mut self,
+        intrinsic: Intrinsic,
+        arguments: &[ValueId],
+        dfg: &DataFlowGraph,
+        result_ids: &[ValueId],
+    ) -> Result<Vec<AcirValue>, RuntimeErrorKind> {
+        let element_types = match dfg.type_of_value(arguments[1]) {
+            Type::Slice(element_types) => element_types,
+            typ => unreachable!("Expected a slice, found {typ}"),
+        };
+        let element_size = element_types.iter().map(flattened_size).sum::<usize>();
+
+        let length = self.convert_numeric_value(arguments[0], dfg);
+        let constant_length =
+            dfg.get_numeric_constant(arguments[0]).map(|length| length.to_u128() as usize);
+        let mut contents = Vec::new();
+        AcirContext::flatten_value(&mut contents, self.convert_value(arguments[1], dfg));
+        let capacity = contents.len() / element_size;
+
+        let one = self.acir_context.add_constant(FieldElement::one());
+        let zero = self.acir_context.add_constant(FieldElement::zero());
+
+        let vars = match intrinsic {
+            Intrinsic::SlicePushBack => {
+                let element = self.flatten_value_list(&arguments[2..], dfg);
+                let new_length = self
+                    .acir_context
+                    .add_var(length, one)
+                    .expect("add Result types to all methods so errors bubble up");
+
+                contents.extend(std::iter::repeat(zero).take(element_size));
+                match constant_length {
+                    Some(length) => {
+                        let start = length * element_size;
+                        contents[start..start + element_size].copy_from_slice(&element);
+                    }
+                    None => {
+                        for position in 0..=capacity {
+                            let is_back = self.is_position(length, position);
+                            for (offset, value) in element.iter().enumerate() {
+                                let index = position * element_size + offset;
+                                contents[index] = self.select_var(is_back, *value, contents[index]);
+                            }
+                        }
+                    }
+                }
+
+                let mut vars = vec![new_length];
+                vars.extend(contents);
+                vars
+            }
+            Intrinsic::SlicePushFront => {
+                let mut vars = vec![self
+                    .acir_context
+                    .add_var(length, one)
+                    .expect("add Result types to all methods so errors bubble up")];
+                vars.extend(self.flatten_value_list(&arguments[2..], dfg));
+                vars.extend(contents);
+                vars
+            }
+            Intrinsic::SlicePopBack => {
+                self.assert_slice_is_not_empty(length, constant_length)?;
+                let new_length = self
+                    .acir_context
+                    .sub_var(length, one)
+                    .expect("add Result types to all methods so errors bubble up");
+
+                let element = match constant_length {
+                    Some(length) if length > 0 && length <= capacity => {
+                        let start = (length - 1) * element_size;
+                        contents[start..start + element_size].to_vec()
+                    }
+                    _ => {
+                        let mut element = vec![zero; element_size];
+                        for position in 0..capacity {
+                            let is_back = self.is_position(new_length, position);
+                            for (offset, value) in element.iter_mut().enumerate() {
+                                let index = position * element_size + offset;
+                                *value = self.select_var(is_back, contents[index], *value);
+                            }
+                        }
+                        element
+                    }
+                };
+
+                let mut vars = vec![new_length];
+                vars.extend(contents);
+                vars.extend(element);
+                vars
+            }
+            Intrinsic::SlicePopFront => {
+                self.assert_slice_is_not_empty(length, constant_length)?;
+                let new_length = self
+                    .acir_context
+                    .sub_var(length, one)
+                    .expect("add Result types to all methods so errors bubble up");
+
+                // Popping from a slice without any capacity can only happen in a branch
+                // which is never taken, so a default element is returned instead
+                let (mut vars, rest) = if capacity == 0 {
+                    (vec![zero; element_size], Vec::new())
+                } else {
+                    let rest = contents.split_off(element_size);
+                    (contents, rest)
+                };
+                vars.push(new_length);
+                vars.extend(rest);
+                vars
+            }
+            _ => unreachable!("Expected a slice intrinsic, found {intrinsic}"),
+        };
+
+        Ok(Self::convert_vars_to_values(vars, dfg, result_ids))
+    }
+
+    /// Constrains a slice being popped from to have a non-zero `length`, unless side effects
+    /// are disabled. Popping from a slice which is always empty is reported as an error.
+    fn assert_slice_is_not_empty(
+        &mut self,
+        length: AcirVar,
+        constant_length: Option<usize>,
+    ) -> Result<(), RuntimeErrorKind> {
+        let zero = self.acir_context.add_constant(FieldElement::zero());
+        let predicate = self
+            .current_side_effects_enabled_var
+            .filter(|predicate| !self.acir_context.is_constant_one(predicate));
+
+        let is_empty = match (constant_length, predicate) {
+            (Some(length), _) if length > 0 => return Ok(()),
+            (Some(_), None) => return Err(RuntimeErrorKind::EmptySlicePop),
+            // The slice is always empty so the pop must be in a branch which is not taken
+            (Some(_), Some(predicate)) => predicate,
+            (None, predicate) => {
+                let is_empty = self
+                    .acir_context
+                    .eq_var(length, zero)
+                    .expect("add Result types to all methods so errors bubble up");
+                match predicate {
+                    Some(predicate) => self
+                        .acir_context
+                        .mul_var(is_empty, predicate)
+                        .expect("add Result types to all methods so errors bubble up"),
+                    None => is_empty,
+                }
+            }
+        };
+        self.acir_context
+            .assert_eq_var(is_empty, zero)
+            .expect("add Result types to all methods so errors bubble up");
+        Ok(())
+    }
+
+    /// Returns an `AcirVar` which is `1` if `index` refers to the given position and `0` otherwise.
+    fn is_position(&mut self, index: AcirVar, position: usize) -> AcirVar {
+        let position = self.acir_context.add_constant(FieldElement::from(position as u128));
+        self.acir_context
+            .eq_var(index, position)
+            .expect("add Result types to all methods so errors bubble up")
+    }
+
+    /// Returns `if_true` if `condition` is `1`, or `if_false` if `condition` is `0`.
+    fn select_var(&mut self, condition: AcirVar, if_true: AcirVar, if_false: AcirVar) -> AcirVar {
+        self.acir_context
+            .sub_var(if_true, if_false)
+            .and_then(|diff| self.acir_context.mul_var(diff, condition))
+            .and_then(|diff| self.acir_context.add_var(if_false, diff))
+            .expect("add Result types to all methods so errors bubble up")
     }
 
     /// Given an array value, return the numerical type of its element.
@@ -779,14 +1296,31 @@ impl Context {
     /// Convert a Vec<AcirVar> into a Vec<AcirValue> using the given result ids.
     /// If the type of a result id is an array, several acir vars are collected into
     /// a single AcirValue::Array of the same length.
+    ///
+    /// The length of a slice is not part of its type, so the contents of a slice result
+    /// are made of the acir vars left over by the other results.
     fn convert_vars_to_values(
         vars: Vec<AcirVar>,
         dfg: &DataFlowGraph,
         result_ids: &[ValueId],
     ) -> Vec<AcirValue> {
+        let result_types = vecmap(result_ids, |result| dfg.type_of_value(*result));
+        let fixed_size: usize = result_types
+            .iter()
+            .filter(|typ| !matches!(typ, Type::Slice(_)))
+            .map(flattened_size)
+            .sum();
+        let slice_size = vars.len().saturating_sub(fixed_size);
+
         let mut vars = vars.into_iter();
-        vecmap(result_ids, |result| {
-            let result_type = dfg.type_of_value(*result);
+        vecmap(result_types, |result_type| {
+            let result_type = match result_type {
+                Type::Slice(element_types) => {
+                    let element_size = element_types.iter().map(flattened_size).sum::<usize>();
+                    Type::Array(element_types, slice_size / element_size)
+                }
+                typ => typ,
+            };
             Self::convert_var_type_to_values(&result_type, &mut vars)
         })
     }
@@ -825,22 +1359,83 @@ impl Context {
     }
 }
 
+/// Returns true if the product of two integers of `bit_size` bits may not fit into a field element.
+fn product_may_overflow_field(bit_size: u32) -> bool {
+    2 * bit_size >= FieldElement::max_num_bits()
+}
+
+/// Returns the number of field elements a value of the given type is flattened into.
+fn flattened_size(typ: &Type) -> usize {
+    match typ {
+        Type::Array(element_types, length) => {
+            element_types.iter().map(flattened_size).sum::<usize>() * length
+        }
+        _ => 1,
+    }
+}
+
+/// Maps each value used by the given instructions to the last instruction using it, leaving out
+/// the values used by the terminator.
+fn find_last_uses(
+    instructions: &[InstructionId],
+    terminator: Option<&TerminatorInstruction>,
+    dfg: &DataFlowGraph,
+) -> HashMap<ValueId, InstructionId> {
+    let mut last_uses = HashMap::new();
+    for instruction in instructions {
+        let mut used_values = Vec::new();
+        dfg[*instruction].for_each_value(|value| collect_used_values(value, dfg, &mut used_values));
+        for value in used_values {
+            last_uses.insert(value, *instruction);
+        }
+    }
+
+    let mut used_values = Vec::new();
+    if let Some(terminator) = terminator {
+        terminator.for_each_value(|value| collect_used_values(value, dfg, &mut used_values));
+    }
+    for value in used_values {
+        last_uses.remove(&value);
+    }
+    last_uses
+}
+
+/// Collects the given value, along with the elements of constant arrays.
+fn collect_used_values(value: ValueId, dfg: &DataFlowGraph, used_values: &mut Vec<ValueId>) {
+    let value = dfg.resolve(value);
+    used_values.push(value);
+    if let Value::Array { array, .. } = &dfg[value] {
+        for element in array.iter() {
+            collect_used_values(*element, dfg, used_values);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::rc::Rc;
 
     use acvm::{
         acir::{
-            circuit::Opcode,
+            circuit::{opcodes::BlackBoxFuncCall, Opcode},
             native_types::{Expression, Witness},
+            BlackBoxFunc,
         },
         FieldElement,
     };
 
+    use iter_extended::vecmap;
+
     use crate::{
         brillig::Brillig,
+        errors::RuntimeErrorKind,
         ssa_refactor::{
-            ir::{function::RuntimeType, map::Id, types::Type},
+            ir::{
+                function::RuntimeType,
+                instruction::{BinaryOp, Intrinsic},
+                map::Id,
+                types::Type,
+            },
             ssa_builder::FunctionBuilder,
         },
     };
@@ -858,19 +1453,242 @@ mod tests {
 
         let one = builder.field_constant(FieldElement::one());
 
-        let element_type = Rc::new(vec![Type::field()]);
-        let array = builder.array_constant(im::Vector::unit(one), element_type);
+        let array_type = Type::Array(Rc::new(vec![Type::field()]), 1);
+        let array = builder.array_constant(im::Vector::unit(one), array_type);
 
         builder.terminate_with_return(vec![array]);
 
         let ssa = builder.finish();
 
         let context = Context::default();
-        let acir = context.convert_ssa(ssa, Brillig::default(), false);
+        let acir = context.convert_ssa(ssa, Brillig::default(), false).unwrap();
 
         let expected_opcodes =
             vec![Opcode::Arithmetic(&Expression::one() - &Expression::from(Witness(1)))];
         assert_eq!(acir.opcodes, expected_opcodes);
         assert_eq!(acir.return_witnesses, vec![Witness(1)]);
     }
+
+    #[test]
+    fn dynamic_array_reads_use_rom() {
+        // fn main {
+        //   b0(v0: [Field; 3], v1: Field):
+        //     v2 = array_get v0, index v1
+        //     return v2
+        // }
+        let func_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("func".into(), func_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::Array(Rc::new(vec![Type::field()]), 3));
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.insert_array_get(v0, v1, Type::field());
+        builder.terminate_with_return(vec![v2]);
+
+        let ssa = builder.finish();
+        let acir = Context::default().convert_ssa(ssa, Brillig::default(), false).unwrap();
+
+        let blocks: Vec<_> = acir
+            .opcodes
+            .iter()
+            .filter_map(|opcode| match opcode {
+                Opcode::ROM(block) => Some(block),
+                Opcode::RAM(_) | Opcode::Block(_) => panic!("Expected a ROM, found {opcode:?}"),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].len, 3);
+
+        // The block is initialized with the array before it is read at index v1
+        let read = blocks[0].trace.last().unwrap();
+        assert_eq!(blocks[0].trace.len(), 4);
+        assert_eq!(read.operation, Expression::zero());
+        assert_eq!(read.index, Expression::from(Witness(4)));
+        assert_eq!(acir.return_witnesses, vec![read.value.to_witness().unwrap()]);
+    }
+
+    #[test]
+    fn dynamic_array_writes_use_ram() {
+        // fn main {
+        //   b0(v0: [Field; 3], v1: Field):
+        //     v3 = array_set v0, index v1, value Field 5
+        //     return v3
+        // }
+        let func_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("func".into(), func_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::Array(Rc::new(vec![Type::field()]), 3));
+        let v1 = builder.add_parameter(Type::field());
+        let five = builder.field_constant(5u128);
+        let v3 = builder.insert_array_set(v0, v1, five);
+        builder.terminate_with_return(vec![v3]);
+
+        let ssa = builder.finish();
+        let acir = Context::default().convert_ssa(ssa, Brillig::default(), false).unwrap();
+
+        let blocks: Vec<_> = acir
+            .opcodes
+            .iter()
+            .filter_map(|opcode| match opcode {
+                Opcode::RAM(block) => Some(block),
+                Opcode::ROM(_) | Opcode::Block(_) => panic!("Expected a RAM, found {opcode:?}"),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(blocks.len(), 1);
+
+        // The array is initialized, written at index v1 and then read back to be returned
+        let operations = vecmap(&blocks[0].trace, |op| op.operation.clone());
+        let (write, read) = (Expression::one(), Expression::zero());
+        assert_eq!(
+            operations,
+            vec![
+                write.clone(),
+                write.clone(),
+                write.clone(),
+                write,
+                read.clone(),
+                read.clone(),
+                read
+            ]
+        );
+        let returned = vecmap(&blocks[0].trace[4..], |op| op.value.to_witness().unwrap());
+        assert_eq!(acir.return_witnesses, returned);
+    }
+
+    #[test]
+    fn dynamic_array_accesses_without_memory_opcodes() {
+        // fn main {
+        //   b0(v0: [Field; 3], v1: Field):
+        //     v2 = array_get v0, index v1
+        //     v3 = array_set v0, index v1, value Field 5
+        //     return v2, v3
+        // }
+        let func_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("func".into(), func_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::Array(Rc::new(vec![Type::field()]), 3));
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.insert_array_get(v0, v1, Type::field());
+        let five = builder.field_constant(5u128);
+        let v3 = builder.insert_array_set(v0, v1, five);
+        builder.terminate_with_return(vec![v2, v3]);
+
+        let ssa = builder.finish();
+        let context = Context { memory_opcodes_unsupported: true, ..Context::default() };
+        let acir = context.convert_ssa(ssa, Brillig::default(), false).unwrap();
+
+        assert!(acir
+            .opcodes
+            .iter()
+            .all(|opcode| !matches!(opcode, Opcode::ROM(_) | Opcode::RAM(_) | Opcode::Block(_))));
+        assert_eq!(acir.return_witnesses.len(), 4);
+    }
+
+    #[test]
+    fn dynamic_indexing_of_elements_of_different_sizes_is_an_error() {
+        // fn main {
+        //   b0(v0: [(Field, [Field; 2]); 2], v1: Field):
+        //     v2 = array_get v0, index v1
+        //     return v2
+        // }
+        let func_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("func".into(), func_id, RuntimeType::Acir);
+        let inner_array = Type::Array(Rc::new(vec![Type::field()]), 2);
+        let element_types = Rc::new(vec![Type::field(), inner_array]);
+        let v0 = builder.add_parameter(Type::Array(element_types, 2));
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.insert_array_get(v0, v1, Type::field());
+        builder.terminate_with_return(vec![v2]);
+
+        let ssa = builder.finish();
+        let error = Context::default().convert_ssa(ssa, Brillig::default(), false).unwrap_err();
+        assert!(matches!(error.kind, RuntimeErrorKind::UnsupportedDynamicIndexing));
+    }
+
+    #[test]
+    fn u128_division_by_large_constant() {
+        // fn main {
+        //   b0(v0: u128):
+        //     v2 = div v0, u128 2^127
+        //     return v2
+        // }
+        //
+        // The remainder is bounded by a constant too large for a range check of its offset
+        let func_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("func".into(), func_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::unsigned(128));
+        let divisor = builder.numeric_constant(1u128 << 127, Type::unsigned(128));
+        let v2 = builder.insert_binary(v0, BinaryOp::Div, divisor);
+        builder.terminate_with_return(vec![v2]);
+
+        let ssa = builder.finish();
+        let acir = Context::default().convert_ssa(ssa, Brillig::default(), false).unwrap();
+
+        let range_sizes: Vec<_> = acir
+            .opcodes
+            .iter()
+            .filter_map(|opcode| match opcode {
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input }) => Some(input.num_bits),
+                _ => None,
+            })
+            .collect();
+        assert!(range_sizes.iter().all(|num_bits| *num_bits <= 128));
+        assert!(range_sizes.contains(&128));
+    }
+
+    #[test]
+    fn only_later_recursive_aggregations_take_an_input_aggregation_object() {
+        // fn main {
+        //   b0(v0: [Field; 2], v1: [Field; 2], v2: [Field; 1], v3: Field, v4: [Field; 2]):
+        //     v6 = call recursive_aggregation(v0, v1, v2, v3, v4)
+        //     v7 = call recursive_aggregation(v0, v1, v2, v3, v6)
+        //     return v7
+        // }
+        //
+        // The first proof verified has nothing to aggregate, so its input aggregation object is
+        // left out, while the second aggregates the output of the first
+        let func_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("func".into(), func_id, RuntimeType::Acir);
+        let field_array = |length| Type::Array(Rc::new(vec![Type::field()]), length);
+        let verification_key = builder.add_parameter(field_array(2));
+        let proof = builder.add_parameter(field_array(2));
+        let public_inputs = builder.add_parameter(field_array(1));
+        let key_hash = builder.add_parameter(Type::field());
+        let aggregation_object = builder.add_parameter(field_array(2));
+
+        let recursive_aggregation =
+            builder.import_intrinsic_id(Intrinsic::BlackBox(BlackBoxFunc::RecursiveAggregation));
+        let aggregate = |builder: &mut FunctionBuilder, aggregation_object| {
+            let arguments =
+                vec![verification_key, proof, public_inputs, key_hash, aggregation_object];
+            builder.insert_call(recursive_aggregation, arguments, vec![field_array(2)])[0]
+        };
+        let first_aggregation = aggregate(&mut builder, aggregation_object);
+        let second_aggregation = aggregate(&mut builder, first_aggregation);
+        builder.terminate_with_return(vec![second_aggregation]);
+
+        let ssa = builder.finish();
+        let acir = Context::default().convert_ssa(ssa, Brillig::default(), false).unwrap();
+
+        let aggregations: Vec<_> = acir
+            .opcodes
+            .iter()
+            .filter_map(|opcode| match opcode {
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RecursiveAggregation {
+                    input_aggregation_object,
+                    output_aggregation_object,
+                    ..
+                }) => Some((input_aggregation_object.clone(), output_aggregation_object.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(aggregations.len(), 2);
+
+        let (first_input, first_output) = &aggregations[0];
+        assert_eq!(*first_input, None);
+        assert_eq!(first_output.len(), 2);
+
+        let (second_input, second_output) = &aggregations[1];
+        let second_input = second_input.as_ref().expect("the first aggregation should be input");
+        assert_eq!(vecmap(second_input, |input| input.witness), *first_output);
+        assert_eq!(acir.return_witnesses, *second_output);
+    }
 }
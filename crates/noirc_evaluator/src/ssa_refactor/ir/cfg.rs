@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap};
 
 use super::{
     basic_block::{BasicBlock, BasicBlockId},
@@ -9,12 +9,13 @@ use super::{
 #[derive(Clone, Default)]
 struct CfgNode {
     /// Set of blocks that containing jumps that target this block.
-    /// The predecessor set has no meaningful order.
-    pub(crate) predecessors: HashSet<BasicBlockId>,
+    /// The predecessor set has no meaningful order, but is kept sorted so that passes
+    /// iterating over it compile a program the same way every time.
+    pub(crate) predecessors: BTreeSet<BasicBlockId>,
 
     /// Set of blocks that are the targets of jumps in this block.
-    /// The successors set has no meaningful order.
-    pub(crate) successors: HashSet<BasicBlockId>,
+    /// The successors set has no meaningful order, but is kept sorted for the same reason.
+    pub(crate) successors: BTreeSet<BasicBlockId>,
 }
 
 /// The Control Flow Graph maintains a mapping of blocks to their predecessors
@@ -31,7 +32,7 @@ impl ControlFlowGraph {
         // therefore we must ensure that a node exists for the entry block, regardless of whether
         // it later comes to describe any edges after calling compute.
         let entry_block = func.entry_block();
-        let empty_node = CfgNode { predecessors: HashSet::new(), successors: HashSet::new() };
+        let empty_node = CfgNode { predecessors: BTreeSet::new(), successors: BTreeSet::new() };
         let data = HashMap::from([(entry_block, empty_node)]);
 
         let mut cfg = ControlFlowGraph { data };
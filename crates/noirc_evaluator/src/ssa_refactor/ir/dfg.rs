@@ -1,4 +1,4 @@
-use std::{borrow::Cow, collections::HashMap, rc::Rc};
+use std::{borrow::Cow, collections::HashMap};
 
 use crate::ssa_refactor::ir::instruction::SimplifyResult;
 
@@ -9,12 +9,13 @@ use super::{
         Instruction, InstructionId, InstructionResultType, Intrinsic, TerminatorInstruction,
     },
     map::DenseMap,
-    types::{CompositeType, Type},
+    types::Type,
     value::{Value, ValueId},
 };
 
 use acvm::FieldElement;
 use iter_extended::vecmap;
+use noirc_errors::Location;
 
 /// The DataFlowGraph contains most of the actual data in a function including
 /// its blocks, instructions, and values. This struct is largely responsible for
@@ -69,6 +70,14 @@ pub(crate) struct DataFlowGraph {
     /// for that of another. This information is purely used for printing the SSA, and has no
     /// material effect on the SSA itself.
     replaced_value_ids: HashMap<ValueId, ValueId>,
+
+    /// Source locations of the Noir code each instruction was generated from.
+    /// Like `replaced_value_ids`, this is purely debugging information.
+    locations: HashMap<InstructionId, Location>,
+
+    /// Names of the Noir variables values were bound to.
+    /// Like `locations`, this is purely debugging information.
+    value_names: HashMap<ValueId, String>,
 }
 
 impl DataFlowGraph {
@@ -142,19 +151,59 @@ impl DataFlowGraph {
         instruction: Instruction,
         block: BasicBlockId,
         ctrl_typevars: Option<Vec<Type>>,
+    ) -> InsertInstructionResult {
+        self.insert_instruction_with_location(instruction, block, ctrl_typevars, None)
+    }
+
+    /// Like `insert_instruction_and_results`, also recording the source location of the
+    /// instruction if it is not simplified away.
+    pub(crate) fn insert_instruction_with_location(
+        &mut self,
+        instruction: Instruction,
+        block: BasicBlockId,
+        ctrl_typevars: Option<Vec<Type>>,
+        location: Option<Location>,
     ) -> InsertInstructionResult {
         use InsertInstructionResult::*;
         match instruction.simplify(self, block) {
             SimplifyResult::SimplifiedTo(simplification) => SimplifiedTo(simplification),
+            SimplifyResult::SimplifiedToMultiple(simplification) => {
+                SimplifiedToMultiple(simplification)
+            }
             SimplifyResult::Remove => InstructionRemoved,
             SimplifyResult::None => {
                 let id = self.make_instruction(instruction, ctrl_typevars);
                 self.blocks[block].insert_instruction(id);
+                if let Some(location) = location {
+                    self.locations.insert(id, location);
+                }
                 InsertInstructionResult::Results(self.instruction_results(id))
             }
         }
     }
 
+    /// Returns the source location of the Noir code the given instruction was generated from, if known.
+    pub(crate) fn get_location(&self, instruction: &InstructionId) -> Option<Location> {
+        self.locations.get(instruction).copied()
+    }
+
+    /// Records the source location of the Noir code the given instruction was generated from.
+    pub(crate) fn set_location(&mut self, instruction: InstructionId, location: Location) {
+        self.locations.insert(instruction, location);
+    }
+
+    /// Iterates over the values bound to a Noir variable along with the variable's name.
+    pub(crate) fn value_names(&self) -> impl Iterator<Item = (ValueId, &str)> {
+        self.value_names.iter().map(|(value, name)| (*value, name.as_str()))
+    }
+
+    /// Records the name of a Noir variable bound to the given value.
+    ///
+    /// A value bound to several variables keeps the name of the first one.
+    pub(crate) fn set_value_name(&mut self, value: ValueId, name: String) {
+        self.value_names.entry(value).or_insert(name);
+    }
+
     /// Insert a value into the dfg's storage and return an id to reference it.
     /// Until the value is used in an instruction it is unreachable.
     pub(crate) fn make_value(&mut self, value: Value) -> ValueId {
@@ -211,13 +260,10 @@ impl DataFlowGraph {
         id
     }
 
-    /// Create a new constant array value from the given elements
-    pub(crate) fn make_array(
-        &mut self,
-        array: im::Vector<ValueId>,
-        element_type: Rc<CompositeType>,
-    ) -> ValueId {
-        self.make_value(Value::Array { array, element_type })
+    /// Create a new constant array or slice value from the given elements
+    pub(crate) fn make_array(&mut self, array: im::Vector<ValueId>, typ: Type) -> ValueId {
+        assert!(matches!(typ, Type::Array(..) | Type::Slice(_)));
+        self.make_value(Value::Array { array, typ })
     }
 
     /// Gets or creates a ValueId for the given FunctionId.
@@ -353,29 +399,21 @@ impl DataFlowGraph {
         }
     }
 
-    /// Returns the Value::Array associated with this ValueId if it refers to an array constant.
-    /// Otherwise, this returns None.
-    pub(crate) fn get_array_constant(
-        &self,
-        value: ValueId,
-    ) -> Option<(im::Vector<ValueId>, Rc<CompositeType>)> {
+    /// Returns the Value::Array associated with this ValueId if it refers to an array
+    /// or slice constant. Otherwise, this returns None.
+    pub(crate) fn get_array_constant(&self, value: ValueId) -> Option<(im::Vector<ValueId>, Type)> {
         match &self.values[self.resolve(value)] {
             // Vectors are shared, so cloning them is cheap
-            Value::Array { array, element_type } => Some((array.clone(), element_type.clone())),
+            Value::Array { array, typ } => Some((array.clone(), typ.clone())),
             _ => None,
         }
     }
 
-    /// Returns the Type::Array associated with this ValueId if it refers to an array parameter.
-    /// Otherwise, this returns None.
-    pub(crate) fn get_array_parameter_type(
-        &self,
-        value: ValueId,
-    ) -> Option<(Rc<CompositeType>, usize)> {
-        match &self.values[self.resolve(value)] {
-            Value::Param { typ: Type::Array(element_type, size), .. } => {
-                Some((element_type.clone(), *size))
-            }
+    /// Returns the length of the given value if it is an array, since the length of
+    /// arrays is known at compile-time. Otherwise, this returns None.
+    pub(crate) fn try_get_array_length(&self, value: ValueId) -> Option<usize> {
+        match self.type_of_value(value) {
+            Type::Array(_, length) => Some(length),
             _ => None,
         }
     }
@@ -444,6 +482,7 @@ impl std::ops::IndexMut<BasicBlockId> for DataFlowGraph {
 pub(crate) enum InsertInstructionResult<'dfg> {
     Results(&'dfg [ValueId]),
     SimplifiedTo(ValueId),
+    SimplifiedToMultiple(Vec<ValueId>),
     InstructionRemoved,
 }
 
@@ -452,6 +491,7 @@ impl<'dfg> InsertInstructionResult<'dfg> {
     pub(crate) fn first(&self) -> ValueId {
         match self {
             InsertInstructionResult::SimplifiedTo(value) => *value,
+            InsertInstructionResult::SimplifiedToMultiple(values) => values[0],
             InsertInstructionResult::Results(results) => results[0],
             InsertInstructionResult::InstructionRemoved => {
                 panic!("Instruction was removed, no results")
@@ -465,6 +505,7 @@ impl<'dfg> InsertInstructionResult<'dfg> {
         match self {
             InsertInstructionResult::Results(results) => Cow::Borrowed(results),
             InsertInstructionResult::SimplifiedTo(result) => Cow::Owned(vec![*result]),
+            InsertInstructionResult::SimplifiedToMultiple(results) => Cow::Owned(results.clone()),
             InsertInstructionResult::InstructionRemoved => {
                 panic!("InsertInstructionResult::results called on a removed instruction")
             }
@@ -475,6 +516,7 @@ impl<'dfg> InsertInstructionResult<'dfg> {
     pub(crate) fn len(&self) -> usize {
         match self {
             InsertInstructionResult::SimplifiedTo(_) => 1,
+            InsertInstructionResult::SimplifiedToMultiple(results) => results.len(),
             InsertInstructionResult::Results(results) => results.len(),
             InsertInstructionResult::InstructionRemoved => 0,
         }
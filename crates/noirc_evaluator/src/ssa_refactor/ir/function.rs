@@ -1,4 +1,6 @@
-use std::collections::HashSet;
+use std::collections::BTreeSet;
+
+use noirc_frontend::token::InlineType;
 
 use super::basic_block::BasicBlockId;
 use super::dfg::DataFlowGraph;
@@ -33,6 +35,9 @@ pub(crate) struct Function {
 
     runtime: RuntimeType,
 
+    /// Whether calls to this function should be inlined, if requested in the source
+    inline_type: Option<InlineType>,
+
     /// The DataFlowGraph holds the majority of data pertaining to the function
     /// including its blocks, instructions, and values.
     pub(crate) dfg: DataFlowGraph,
@@ -45,7 +50,7 @@ impl Function {
     pub(crate) fn new(name: String, id: FunctionId) -> Self {
         let mut dfg = DataFlowGraph::default();
         let entry_block = dfg.make_block();
-        Self { name, id, entry_block, dfg, runtime: RuntimeType::Acir }
+        Self { name, id, entry_block, dfg, runtime: RuntimeType::Acir, inline_type: None }
     }
 
     /// The name of the function.
@@ -69,6 +74,16 @@ impl Function {
         self.runtime = runtime;
     }
 
+    /// Whether calls to the function should be inlined, if requested in the source.
+    pub(crate) fn inline_type(&self) -> Option<InlineType> {
+        self.inline_type
+    }
+
+    /// Set whether calls to the function should be inlined.
+    pub(crate) fn set_inline_type(&mut self, inline_type: Option<InlineType>) {
+        self.inline_type = inline_type;
+    }
+
     /// Retrieves the entry block of a function.
     ///
     /// A function's entry block contains the instructions
@@ -104,9 +119,10 @@ impl Function {
     ///
     /// Note that self.dfg.basic_blocks_iter() iterates over all blocks,
     /// whether reachable or not. This function should be used if you
-    /// want to iterate only reachable blocks.
-    pub(crate) fn reachable_blocks(&self) -> HashSet<BasicBlockId> {
-        let mut blocks = HashSet::new();
+    /// want to iterate only reachable blocks. The blocks are ordered by their ids, so that
+    /// iterating over them visits blocks in the same order on every compilation.
+    pub(crate) fn reachable_blocks(&self) -> BTreeSet<BasicBlockId> {
+        let mut blocks = BTreeSet::new();
         let mut stack = vec![self.entry_block];
 
         while let Some(block) = stack.pop() {
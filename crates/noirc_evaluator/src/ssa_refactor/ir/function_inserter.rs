@@ -32,11 +32,11 @@ impl<'f> FunctionInserter<'f> {
         match self.values.get(&value) {
             Some(value) => *value,
             None => match &self.function.dfg[value] {
-                super::value::Value::Array { array, element_type } => {
+                super::value::Value::Array { array, typ } => {
                     let array = array.clone();
-                    let element_type = element_type.clone();
+                    let typ = typ.clone();
                     let new_array = array.iter().map(|id| self.resolve(*id)).collect();
-                    let new_id = self.function.dfg.make_array(new_array, element_type);
+                    let new_id = self.function.dfg.make_array(new_array, typ);
                     self.values.insert(value, new_id);
                     new_id
                 }
@@ -77,8 +77,13 @@ impl<'f> FunctionInserter<'f> {
             .requires_ctrl_typevars()
             .then(|| vecmap(&results, |result| self.function.dfg.type_of_value(*result)));
 
-        let new_results =
-            self.function.dfg.insert_instruction_and_results(instruction, block, ctrl_typevars);
+        let location = self.function.dfg.get_location(&id);
+        let new_results = self.function.dfg.insert_instruction_with_location(
+            instruction,
+            block,
+            ctrl_typevars,
+            location,
+        );
 
         Self::insert_new_instruction_results(&mut self.values, &results, &new_results);
         new_results
@@ -97,6 +102,11 @@ impl<'f> FunctionInserter<'f> {
             InsertInstructionResult::SimplifiedTo(new_result) => {
                 values.insert(old_results[0], *new_result);
             }
+            InsertInstructionResult::SimplifiedToMultiple(new_results) => {
+                for (old_result, new_result) in old_results.iter().zip(new_results) {
+                    values.insert(*old_result, *new_result);
+                }
+            }
             InsertInstructionResult::Results(new_results) => {
                 for (old_result, new_result) in old_results.iter().zip(*new_results) {
                     values.insert(*old_result, *new_result);
@@ -34,7 +34,11 @@ pub(crate) enum Intrinsic {
     Sort,
     ArrayLen,
     SlicePushBack,
+    SlicePushFront,
+    SlicePopBack,
+    SlicePopFront,
     Println,
+    AssertMessage,
     ToBits(Endian),
     ToRadix(Endian),
     BlackBox(BlackBoxFunc),
@@ -44,9 +48,13 @@ impl std::fmt::Display for Intrinsic {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Intrinsic::Println => write!(f, "println"),
+            Intrinsic::AssertMessage => write!(f, "assert_message"),
             Intrinsic::Sort => write!(f, "arraysort"),
             Intrinsic::ArrayLen => write!(f, "array_len"),
             Intrinsic::SlicePushBack => write!(f, "slice_push_back"),
+            Intrinsic::SlicePushFront => write!(f, "slice_push_front"),
+            Intrinsic::SlicePopBack => write!(f, "slice_pop_back"),
+            Intrinsic::SlicePopFront => write!(f, "slice_pop_front"),
             Intrinsic::ToBits(Endian::Big) => write!(f, "to_be_bits"),
             Intrinsic::ToBits(Endian::Little) => write!(f, "to_le_bits"),
             Intrinsic::ToRadix(Endian::Big) => write!(f, "to_be_radix"),
@@ -62,9 +70,13 @@ impl Intrinsic {
     pub(crate) fn lookup(name: &str) -> Option<Intrinsic> {
         match name {
             "println" => Some(Intrinsic::Println),
+            "assert_message" => Some(Intrinsic::AssertMessage),
             "arraysort" => Some(Intrinsic::Sort),
             "array_len" => Some(Intrinsic::ArrayLen),
             "slice_push_back" => Some(Intrinsic::SlicePushBack),
+            "slice_push_front" => Some(Intrinsic::SlicePushFront),
+            "slice_pop_back" => Some(Intrinsic::SlicePopBack),
+            "slice_pop_front" => Some(Intrinsic::SlicePopFront),
             "to_le_radix" => Some(Intrinsic::ToRadix(Endian::Little)),
             "to_be_radix" => Some(Intrinsic::ToRadix(Endian::Big)),
             "to_le_bits" => Some(Intrinsic::ToBits(Endian::Little)),
@@ -295,22 +307,31 @@ impl Instruction {
             Instruction::ArraySet { array, index, value } => {
                 let array = dfg.get_array_constant(*array);
                 let index = dfg.get_numeric_constant(*index);
-                if let (Some((array, element_type)), Some(index)) = (array, index) {
+                if let (Some((array, typ)), Some(index)) = (array, index) {
                     let index =
                         index.try_to_u64().expect("Expected array index to fit in u64") as usize;
 
                     if index < array.len() {
-                        let new_array = dfg.make_array(array.update(index, *value), element_type);
+                        let new_array = dfg.make_array(array.update(index, *value), typ);
                         return SimplifiedTo(new_array);
                     }
                 }
                 None
             }
-            Instruction::Truncate { value, bit_size, .. } => {
+            Instruction::Truncate { value, bit_size, max_bit_size } => {
                 if let Some((numeric_constant, typ)) = dfg.get_numeric_constant_with_type(*value) {
-                    let integer_modulus = 2_u128.pow(*bit_size);
-                    let truncated = numeric_constant.to_u128() % integer_modulus;
-                    SimplifiedTo(dfg.make_constant(truncated.into(), typ))
+                    let integer_modulus = BigUint::from(2u128).pow(*bit_size);
+                    let truncated = if numeric_constant.num_bits() > *max_bit_size {
+                        // The constant is the field representation of a negative number,
+                        // e.g. the result of a subtraction that underflowed.
+                        let negated = FieldElement::zero() - numeric_constant;
+                        let negated = BigUint::from_bytes_be(&negated.to_be_bytes());
+                        (&integer_modulus - negated % &integer_modulus) % &integer_modulus
+                    } else {
+                        BigUint::from_bytes_be(&numeric_constant.to_be_bytes()) % integer_modulus
+                    };
+                    let truncated = FieldElement::from_be_bytes_reduce(&truncated.to_bytes_be());
+                    SimplifiedTo(dfg.make_constant(truncated, typ))
                 } else {
                     None
                 }
@@ -353,15 +374,42 @@ fn simplify_cast(value: ValueId, dst_typ: &Type, dfg: &mut DataFlowGraph) -> Sim
             }
             (
                 Type::Numeric(NumericType::NativeField | NumericType::Unsigned { .. }),
-                Type::Numeric(NumericType::Unsigned { bit_size }),
+                Type::Numeric(
+                    NumericType::Unsigned { bit_size } | NumericType::Signed { bit_size },
+                ),
             ) => {
-                // Field/Unsigned -> unsigned: truncate
+                // Field/Unsigned -> integer: truncate
                 let integer_modulus = BigUint::from(2u128).pow(*bit_size);
                 let constant: BigUint = BigUint::from_bytes_be(&constant.to_be_bytes());
                 let truncated = constant % integer_modulus;
                 let truncated = FieldElement::from_be_bytes_reduce(&truncated.to_bytes_be());
                 SimplifiedTo(dfg.make_constant(truncated, dst_typ.clone()))
             }
+            (Type::Numeric(NumericType::Signed { bit_size: src_bit_size }), _)
+                if src_bit_size <= 128 =>
+            {
+                // Signed -> Field: negative values become their field negation
+                // Signed -> integer: sign-extend, then truncate
+                let constant = match constant.try_into_u128() {
+                    Some(constant) => sign_extend(constant, src_bit_size),
+                    Option::None => return None,
+                };
+                let value = match dst_typ {
+                    Type::Numeric(NumericType::NativeField) => {
+                        let magnitude = FieldElement::from(constant.unsigned_abs());
+                        if constant < 0 {
+                            FieldElement::zero() - magnitude
+                        } else {
+                            magnitude
+                        }
+                    }
+                    Type::Numeric(
+                        NumericType::Unsigned { bit_size } | NumericType::Signed { bit_size },
+                    ) if *bit_size <= 128 => wrap_to_bit_size(constant, *bit_size).into(),
+                    _ => return None,
+                };
+                SimplifiedTo(dfg.make_constant(value, dst_typ.clone()))
+            }
             _ => None,
         }
     } else if *dst_typ == dfg.type_of_value(value) {
@@ -404,32 +452,84 @@ fn simplify_call(func: ValueId, arguments: &[ValueId], dfg: &mut DataFlowGraph)
             }
         }
         Intrinsic::ArrayLen => {
-            let slice = dfg.get_array_constant(arguments[0]);
-            if let Some((slice, _)) = slice {
-                let slice_len =
-                    dfg.make_constant(FieldElement::from(slice.len() as u128), Type::field());
-                SimplifiedTo(slice_len)
-            } else if let Some((_, slice_len)) = dfg.get_array_parameter_type(arguments[0]) {
-                let slice_len = dfg.make_constant(
-                    FieldElement::from(slice_len as u128),
-                    Type::Numeric(NumericType::NativeField),
-                );
-                SimplifiedTo(slice_len)
+            if let Some(length) = dfg.try_get_array_length(arguments[0]) {
+                let length = FieldElement::from(length as u128);
+                SimplifiedTo(dfg.make_constant(length, Type::field()))
+            } else if matches!(dfg.type_of_value(arguments[1]), Type::Slice(_)) {
+                // Slices are passed as their (length, contents) pair
+                SimplifiedTo(arguments[0])
             } else {
                 None
             }
         }
+        Intrinsic::SlicePushBack
+        | Intrinsic::SlicePushFront
+        | Intrinsic::SlicePopBack
+        | Intrinsic::SlicePopFront => simplify_slice_intrinsic(intrinsic, arguments, dfg),
+        Intrinsic::BlackBox(_)
+        | Intrinsic::Println
+        | Intrinsic::AssertMessage
+        | Intrinsic::Sort => None,
+    }
+}
+
+/// Try to simplify a call to a slice intrinsic. The first two arguments of each slice intrinsic
+/// are the length and contents of the slice, which can be simplified if both are constants.
+///
+/// The contents of a slice may hold more elements than its length, only the first `length`
+/// elements are kept by the simplified slice.
+fn simplify_slice_intrinsic(
+    intrinsic: Intrinsic,
+    arguments: &[ValueId],
+    dfg: &mut DataFlowGraph,
+) -> SimplifyResult {
+    use SimplifyResult::*;
+    let length = dfg.get_numeric_constant(arguments[0]).and_then(|length| length.try_to_u64());
+    let (length, (mut contents, typ)) = match (length, dfg.get_array_constant(arguments[1])) {
+        (Some(length), Some(contents)) => (length as usize, contents),
+        _ => return None,
+    };
+
+    let element_size = typ.element_size();
+    if contents.len() < length * element_size {
+        return None;
+    }
+    contents.truncate(length * element_size);
+
+    match intrinsic {
         Intrinsic::SlicePushBack => {
-            let slice = dfg.get_array_constant(arguments[0]);
-            if let (Some((mut slice, element_type)), elem) = (slice, arguments[1]) {
-                slice.push_back(elem);
-                let new_slice = dfg.make_array(slice, element_type);
-                SimplifiedTo(new_slice)
-            } else {
-                None
+            contents.extend(arguments[2..].iter().copied());
+            let new_length =
+                dfg.make_constant(FieldElement::from(length as u128 + 1), Type::field());
+            SimplifiedToMultiple(vec![new_length, dfg.make_array(contents, typ)])
+        }
+        Intrinsic::SlicePushFront => {
+            for element in arguments[2..].iter().rev() {
+                contents.push_front(*element);
             }
+            let new_length =
+                dfg.make_constant(FieldElement::from(length as u128 + 1), Type::field());
+            SimplifiedToMultiple(vec![new_length, dfg.make_array(contents, typ)])
+        }
+        // Popping from an empty slice is left for the backend to report
+        Intrinsic::SlicePopBack if length > 0 => {
+            let element = contents.split_off((length - 1) * element_size);
+            let new_length =
+                dfg.make_constant(FieldElement::from(length as u128 - 1), Type::field());
+            let mut results = vec![new_length, dfg.make_array(contents, typ)];
+            results.extend(element);
+            SimplifiedToMultiple(results)
+        }
+        Intrinsic::SlicePopFront if length > 0 => {
+            let rest = contents.split_off(element_size);
+            let new_length =
+                dfg.make_constant(FieldElement::from(length as u128 - 1), Type::field());
+            let mut results: Vec<_> = contents.into_iter().collect();
+            results.push(new_length);
+            results.push(dfg.make_array(rest, typ));
+            SimplifiedToMultiple(results)
         }
-        Intrinsic::BlackBox(_) | Intrinsic::Println | Intrinsic::Sort => None,
+        _ => None,
     }
 }
 
@@ -464,9 +564,10 @@ fn constant_to_radix(
     while limbs.len() < limb_count_with_padding as usize {
         limbs.push(FieldElement::zero());
     }
-    let result_constants =
+    let result_constants: im::Vector<_> =
         limbs.into_iter().map(|limb| dfg.make_constant(limb, Type::unsigned(bit_size))).collect();
-    dfg.make_array(result_constants, Rc::new(vec![Type::unsigned(bit_size)]))
+    let typ = Type::Array(Rc::new(vec![Type::unsigned(bit_size)]), result_constants.len());
+    dfg.make_array(result_constants, typ)
 }
 
 /// The possible return values for Instruction::return_types
@@ -705,6 +806,23 @@ impl Binary {
         rhs: FieldElement,
         mut operand_type: Type,
     ) -> Option<Id<Value>> {
+        if let Type::Numeric(
+            NumericType::Unsigned { bit_size } | NumericType::Signed { bit_size },
+        ) = operand_type
+        {
+            if matches!(
+                self.operator,
+                BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod | BinaryOp::Lt | BinaryOp::Shr
+            ) {
+                let is_signed = matches!(operand_type, Type::Numeric(NumericType::Signed { .. }));
+                let value = self.eval_constant_integer_operations(lhs, rhs, bit_size, is_signed)?;
+                if self.operator == BinaryOp::Lt {
+                    operand_type = Type::bool();
+                }
+                return Some(dfg.make_constant(value, operand_type));
+            }
+        }
+
         let value = match self.operator {
             BinaryOp::Add => lhs + rhs,
             BinaryOp::Sub => lhs - rhs,
@@ -730,6 +848,52 @@ impl Binary {
         Some(dfg.make_constant(value, operand_type))
     }
 
+    /// Try to evaluate the given operands as integers of `bit_size` bits for operators whose
+    /// result differs from the equivalent field operation. Signed operands are interpreted
+    /// as two's complement, so division truncates towards zero and right shifts are arithmetic.
+    /// Multiplications wrap around as the product of wide integers may not fit in a field element.
+    fn eval_constant_integer_operations(
+        &self,
+        lhs: FieldElement,
+        rhs: FieldElement,
+        bit_size: u32,
+        is_signed: bool,
+    ) -> Option<FieldElement> {
+        let lhs = lhs.try_into_u128()?;
+        let rhs = rhs.try_into_u128()?;
+        if !is_signed {
+            return match self.operator {
+                BinaryOp::Mul if bit_size <= 128 => {
+                    Some(wrap_to_bit_size(lhs.wrapping_mul(rhs) as i128, bit_size).into())
+                }
+                BinaryOp::Mul => None,
+                BinaryOp::Div => lhs.checked_div(rhs).map(Into::into),
+                BinaryOp::Mod => lhs.checked_rem(rhs).map(Into::into),
+                BinaryOp::Lt => Some((lhs < rhs).into()),
+                BinaryOp::Shr => Some(lhs.checked_shr(rhs.try_into().ok()?).unwrap_or(0).into()),
+                op => panic!("eval_constant_integer_operations invalid for {op:?}"),
+            };
+        }
+
+        if bit_size > 128 {
+            return None;
+        }
+        let lhs = sign_extend(lhs, bit_size);
+        let rhs = sign_extend(rhs, bit_size);
+        let value = match self.operator {
+            BinaryOp::Mul => lhs.wrapping_mul(rhs),
+            BinaryOp::Div => lhs.checked_div(rhs)?,
+            BinaryOp::Mod => lhs.checked_rem(rhs)?,
+            BinaryOp::Lt => return Some((lhs < rhs).into()),
+            BinaryOp::Shr => {
+                let rhs: u32 = rhs.try_into().ok()?;
+                lhs >> rhs.min(127)
+            }
+            op => panic!("eval_constant_integer_operations invalid for {op:?}"),
+        };
+        Some(wrap_to_bit_size(value, bit_size).into())
+    }
+
     /// Try to evaluate the given operands as u128s for operators that are only valid on u128s,
     /// like the bitwise operators and modulus.
     fn eval_constant_u128_operations(
@@ -766,6 +930,18 @@ impl Binary {
     }
 }
 
+/// Interprets the lower `bit_size` bits of `value` as a two's complement integer.
+fn sign_extend(value: u128, bit_size: u32) -> i128 {
+    let unused_bits = 128 - bit_size;
+    ((value << unused_bits) as i128) >> unused_bits
+}
+
+/// Returns the two's complement representation of `value` in `bit_size` bits.
+fn wrap_to_bit_size(value: i128, bit_size: u32) -> u128 {
+    let unused_bits = 128 - bit_size;
+    ((value as u128) << unused_bits) >> unused_bits
+}
+
 /// Binary Operations allowed in the IR.
 /// Aside from the comparison operators (Eq and Lt), all operators
 /// will return the same type as their operands.
@@ -831,6 +1007,11 @@ pub(crate) enum SimplifyResult {
     /// Replace this function's result with the given value
     SimplifiedTo(ValueId),
 
+    /// Replace this function's results with the given values
+    /// Used for when there are multiple return values from
+    /// a function such as a tuple
+    SimplifiedToMultiple(Vec<ValueId>),
+
     /// Remove the instruction, it is unnecessary
     Remove,
 
@@ -1,13 +1,14 @@
 //! This file is for pretty-printing the SSA IR in a human-readable form for debugging.
 use std::{
     collections::HashSet,
-    fmt::{Formatter, Result},
+    fmt::{Display, Formatter, Result, Write},
 };
 
 use iter_extended::vecmap;
 
 use super::{
     basic_block::BasicBlockId,
+    dom::DominatorTree,
     function::Function,
     instruction::{Instruction, InstructionId, TerminatorInstruction},
     value::ValueId,
@@ -57,6 +58,70 @@ pub(crate) fn display_block(
     display_terminator(function, block.terminator(), f)
 }
 
+/// Renders the control flow graph of a function in Graphviz dot format.
+///
+/// Each node is a reachable block listing its instructions and terminator, and each solid
+/// edge goes from a block to one of its successors. The dominator tree is drawn with dashed
+/// edges from the immediate dominator of each block.
+pub(crate) fn function_to_dot(function: &Function) -> String {
+    /// Displays a single block through `display_block`
+    struct BlockDisplay<'f>(&'f Function, BasicBlockId);
+
+    impl Display for BlockDisplay<'_> {
+        fn fmt(&self, f: &mut Formatter) -> Result {
+            display_block(self.0, self.1, f)
+        }
+    }
+
+    let mut blocks: Vec<_> = function.reachable_blocks().into_iter().collect();
+    blocks.sort();
+    let dominator_tree = DominatorTree::with_function(function);
+
+    let mut dot = String::new();
+    let graph_name = format!("{} {}", function.name(), function.id());
+    writeln!(dot, "digraph \"{}\" {{", escape_dot_string(&graph_name)).unwrap();
+    writeln!(dot, "    node [shape=box, fontname=\"monospace\"];").unwrap();
+
+    for block_id in &blocks {
+        let node_label: String = BlockDisplay(function, *block_id)
+            .to_string()
+            .lines()
+            .map(|line| format!("{}\\l", escape_dot_string(line.trim())))
+            .collect();
+        writeln!(dot, "    {block_id} [label=\"{node_label}\"];").unwrap();
+    }
+
+    for block_id in &blocks {
+        match function.dfg[*block_id].terminator() {
+            Some(TerminatorInstruction::JmpIf { then_destination, else_destination, .. }) => {
+                writeln!(dot, "    {block_id} -> {then_destination} [label=\"then\"];").unwrap();
+                writeln!(dot, "    {block_id} -> {else_destination} [label=\"else\"];").unwrap();
+            }
+            Some(TerminatorInstruction::Jmp { destination, .. }) => {
+                writeln!(dot, "    {block_id} -> {destination};").unwrap();
+            }
+            Some(TerminatorInstruction::Return { .. }) | None => (),
+        }
+    }
+
+    for block_id in &blocks {
+        if let Some(dominator) = dominator_tree.immediate_dominator(*block_id) {
+            writeln!(
+                dot,
+                "    {dominator} -> {block_id} [style=dashed, color=gray, constraint=false];"
+            )
+            .unwrap();
+        }
+    }
+
+    writeln!(dot, "}}").unwrap();
+    dot
+}
+
+fn escape_dot_string(string: &str) -> String {
+    string.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
 /// Specialize displaying value ids so that if they refer to a numeric
 /// constant or a function we print those directly.
 fn value(function: &Function, id: ValueId) -> String {
@@ -173,3 +238,55 @@ pub(crate) fn display_instruction(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::ssa_refactor::{
+        ir::{function::RuntimeType, map::Id, types::Type},
+        ssa_builder::FunctionBuilder,
+    };
+
+    use super::function_to_dot;
+
+    #[test]
+    fn control_flow_graph_shows_branches_and_dominators() {
+        // fn main f0 {
+        //   b0(v0: u1):
+        //     jmpif v0 then: b1, else: b2
+        //   b1():
+        //     jmp b3()
+        //   b2():
+        //     jmp b3()
+        //   b3():
+        //     return
+        // }
+        let mut builder = FunctionBuilder::new("main".into(), Id::test_new(0), RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::bool());
+        let b1 = builder.insert_block();
+        let b2 = builder.insert_block();
+        let b3 = builder.insert_block();
+        builder.terminate_with_jmpif(v0, b1, b2);
+
+        builder.switch_to_block(b1);
+        builder.terminate_with_jmp(b3, vec![]);
+        builder.switch_to_block(b2);
+        builder.terminate_with_jmp(b3, vec![]);
+        builder.switch_to_block(b3);
+        builder.terminate_with_return(vec![]);
+
+        let ssa = builder.finish();
+        assert_eq!(
+            function_to_dot(ssa.main()),
+            "digraph \"main f0\" {\n    node [shape=box, fontname=\"monospace\"];\n    \
+             b0 [label=\"b0(v0: u1):\\ljmpif v0 then: b1, else: b2\\l\"];\n    \
+             b1 [label=\"b1():\\ljmp b3()\\l\"];\n    \
+             b2 [label=\"b2():\\ljmp b3()\\l\"];\n    \
+             b3 [label=\"b3():\\lreturn\\l\"];\n    \
+             b0 -> b1 [label=\"then\"];\n    b0 -> b2 [label=\"else\"];\n    \
+             b1 -> b3;\n    b2 -> b3;\n    \
+             b0 -> b1 [style=dashed, color=gray, constraint=false];\n    \
+             b0 -> b2 [style=dashed, color=gray, constraint=false];\n    \
+             b0 -> b3 [style=dashed, color=gray, constraint=false];\n}\n"
+        );
+    }
+}
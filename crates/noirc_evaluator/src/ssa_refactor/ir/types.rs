@@ -61,6 +61,16 @@ impl Type {
     pub(crate) fn field() -> Type {
         Type::Numeric(NumericType::NativeField)
     }
+
+    /// Returns the amount of values each element of this array or slice type is flattened into.
+    ///
+    /// Panics if this is not an array or slice type.
+    pub(crate) fn element_size(&self) -> usize {
+        match self {
+            Type::Array(elements, _) | Type::Slice(elements) => elements.len(),
+            other => panic!("element_size: Expected an array or slice type, found {other}"),
+        }
+    }
 }
 
 /// Composite Types are essentially flattened struct or tuple types.
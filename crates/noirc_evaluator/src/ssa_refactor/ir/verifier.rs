@@ -0,0 +1,380 @@
+//! The SSA verifier checks the invariants every pass expects of the functions it is given:
+//! - Each reachable block ends in a terminator. Jumps pass as many arguments to their
+//!   destination as it has parameters, and conditional jumps only go to blocks without any.
+//! - Each value is defined before it is used, either earlier in the same block or in a block
+//!   dominating the block it is used in.
+//! - The operands of each instruction have the kind of type the instruction expects.
+//!
+//! A pass breaking these invariants would otherwise only be noticed much later, if ever,
+//! as nonsensical ACIR or Brillig bytecode.
+use std::collections::HashMap;
+
+use super::{
+    basic_block::BasicBlockId,
+    dom::DominatorTree,
+    function::Function,
+    instruction::{Instruction, InstructionId, TerminatorInstruction},
+    types::Type,
+    value::{Value, ValueId},
+};
+
+/// Checks that the given function is well-formed, returning a description of the first
+/// broken invariant found otherwise.
+pub(crate) fn verify_function(function: &Function) -> Result<(), String> {
+    Verifier::new(function)?.verify()
+}
+
+struct Verifier<'f> {
+    function: &'f Function,
+    dom_tree: DominatorTree,
+
+    /// The reachable blocks of the function, in a deterministic order
+    blocks: Vec<BasicBlockId>,
+
+    /// The block each reachable instruction is in, along with its position in the block
+    instruction_positions: HashMap<InstructionId, (BasicBlockId, usize)>,
+}
+
+impl<'f> Verifier<'f> {
+    fn new(function: &'f Function) -> Result<Self, String> {
+        let mut blocks: Vec<_> = function.reachable_blocks().into_iter().collect();
+        blocks.sort();
+
+        let mut instruction_positions = HashMap::new();
+        for block in &blocks {
+            for (position, instruction) in function.dfg[*block].instructions().iter().enumerate() {
+                if let Some((other_block, _)) =
+                    instruction_positions.insert(*instruction, (*block, position))
+                {
+                    return Err(format!("an instruction is in both {other_block} and {block}"));
+                }
+            }
+        }
+
+        let dom_tree = DominatorTree::with_function(function);
+        Ok(Self { function, dom_tree, blocks, instruction_positions })
+    }
+
+    fn verify(mut self) -> Result<(), String> {
+        let function = self.function;
+        let mut return_types = None;
+
+        for block in std::mem::take(&mut self.blocks) {
+            let instructions = function.dfg[block].instructions();
+            for (position, instruction) in instructions.iter().enumerate() {
+                let mut operands = Vec::new();
+                function.dfg[*instruction].for_each_value(|value| operands.push(value));
+                for operand in operands {
+                    self.verify_use(operand, block, position)?;
+                }
+                self.verify_instruction_types(*instruction, block)?;
+            }
+
+            let terminator = function.dfg[block]
+                .terminator()
+                .ok_or_else(|| format!("{block} has no terminator"))?;
+
+            let mut operands = Vec::new();
+            terminator.for_each_value(|value| operands.push(value));
+            for operand in operands {
+                self.verify_use(operand, block, instructions.len())?;
+            }
+            self.verify_terminator(terminator, block, &mut return_types)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the given value is defined before its use at `position` in `block`,
+    /// the terminator being at the position following the last instruction.
+    fn verify_use(
+        &mut self,
+        value: ValueId,
+        block: BasicBlockId,
+        position: usize,
+    ) -> Result<(), String> {
+        let function = self.function;
+        let value = function.dfg.resolve(value);
+        let (definition_block, definition_position) = match &function.dfg[value] {
+            Value::Instruction { instruction, .. } => {
+                match self.instruction_positions.get(instruction) {
+                    Some((definition_block, position)) => (*definition_block, Some(*position)),
+                    None => {
+                        return Err(format!(
+                            "{value} is used in {block} but is not defined in any reachable block"
+                        ))
+                    }
+                }
+            }
+            Value::Param { block: definition_block, .. } => {
+                if !self.dom_tree.is_reachable(*definition_block) {
+                    return Err(format!(
+                        "{value} is used in {block} but is a parameter of the unreachable block {definition_block}"
+                    ));
+                }
+                (*definition_block, None)
+            }
+            // The elements of an array are used wherever the array is
+            Value::Array { array, .. } => {
+                for element in array.iter() {
+                    self.verify_use(*element, block, position)?;
+                }
+                return Ok(());
+            }
+            Value::NumericConstant { .. }
+            | Value::Function(_)
+            | Value::Intrinsic(_)
+            | Value::ForeignFunction(_) => return Ok(()),
+        };
+
+        let defined_before_use = if definition_block == block {
+            definition_position.map_or(true, |definition_position| definition_position < position)
+        } else {
+            self.dom_tree.dominates(definition_block, block)
+        };
+
+        if defined_before_use {
+            Ok(())
+        } else {
+            Err(format!("{value} is used in {block} before being defined in {definition_block}"))
+        }
+    }
+
+    /// Checks that the operands of the given instruction have the kind of type it expects.
+    fn verify_instruction_types(
+        &self,
+        instruction: InstructionId,
+        block: BasicBlockId,
+    ) -> Result<(), String> {
+        let is_numeric = |typ: &Type| matches!(typ, Type::Numeric(_));
+        let is_array = |typ: &Type| matches!(typ, Type::Array(..) | Type::Slice(_));
+        let is_reference = |typ: &Type| matches!(typ, Type::Reference);
+        let is_function = |typ: &Type| matches!(typ, Type::Function);
+
+        let expect = |value: ValueId, is_expected: &dyn Fn(&Type) -> bool, expected: &str| {
+            let typ = self.function.dfg.type_of_value(value);
+            if is_expected(&typ) {
+                Ok(())
+            } else {
+                let value = self.function.dfg.resolve(value);
+                Err(format!(
+                    "{value} of type {typ} is used in {block} where {expected} is expected"
+                ))
+            }
+        };
+
+        match &self.function.dfg[instruction] {
+            Instruction::Binary(binary) => {
+                expect(binary.lhs, &is_numeric, "a numeric value")?;
+                expect(binary.rhs, &is_numeric, "a numeric value")
+            }
+            Instruction::Cast(value, typ) => {
+                if !is_numeric(typ) {
+                    return Err(format!(
+                        "a value is cast to the non-numeric type {typ} in {block}"
+                    ));
+                }
+                expect(*value, &is_numeric, "a numeric value")
+            }
+            Instruction::Not(value)
+            | Instruction::Truncate { value, .. }
+            | Instruction::Constrain(value)
+            | Instruction::EnableSideEffects { condition: value } => {
+                expect(*value, &is_numeric, "a numeric value")
+            }
+            Instruction::Call { func, .. } => expect(*func, &is_function, "a function"),
+            Instruction::Allocate => Ok(()),
+            Instruction::Load { address } | Instruction::Store { address, .. } => {
+                expect(*address, &is_reference, "a reference")
+            }
+            Instruction::ArrayGet { array, index } | Instruction::ArraySet { array, index, .. } => {
+                expect(*array, &is_array, "an array")?;
+                expect(*index, &is_numeric, "a numeric index")
+            }
+        }
+    }
+
+    /// Checks that the terminator jumps to blocks with matching parameters, and that all the
+    /// return terminators of the function return the same kinds of values.
+    fn verify_terminator(
+        &self,
+        terminator: &TerminatorInstruction,
+        block: BasicBlockId,
+        return_types: &mut Option<(BasicBlockId, Vec<Type>)>,
+    ) -> Result<(), String> {
+        let dfg = &self.function.dfg;
+        match terminator {
+            TerminatorInstruction::JmpIf { condition, then_destination, else_destination } => {
+                let condition_type = dfg.type_of_value(*condition);
+                if !matches!(condition_type, Type::Numeric(_)) {
+                    return Err(format!(
+                        "{block} branches on a condition of the non-numeric type {condition_type}"
+                    ));
+                }
+                for destination in [then_destination, else_destination] {
+                    if !dfg.block_parameters(*destination).is_empty() {
+                        return Err(format!(
+                            "{block} conditionally jumps to {destination} which has parameters"
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            TerminatorInstruction::Jmp { destination, arguments } => {
+                let parameters = dfg.block_parameters(*destination);
+                if parameters.len() != arguments.len() {
+                    return Err(format!(
+                        "{block} jumps to {destination} with {} arguments but it has {} parameters",
+                        arguments.len(),
+                        parameters.len()
+                    ));
+                }
+                for (argument, parameter) in arguments.iter().zip(parameters) {
+                    let argument_type = dfg.type_of_value(*argument);
+                    let parameter_type = dfg.type_of_value(*parameter);
+                    if !same_kind_of_type(&argument_type, &parameter_type) {
+                        return Err(format!(
+                            "{block} passes a value of type {argument_type} to the parameter {parameter} of {destination} of type {parameter_type}"
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            TerminatorInstruction::Return { return_values } => {
+                let types: Vec<_> =
+                    return_values.iter().map(|value| dfg.type_of_value(*value)).collect();
+                match return_types {
+                    Some((other_block, other_types)) => {
+                        let consistent = types.len() == other_types.len()
+                            && types
+                                .iter()
+                                .zip(other_types.iter())
+                                .all(|(a, b)| same_kind_of_type(a, b));
+                        if consistent {
+                            Ok(())
+                        } else {
+                            Err(format!(
+                                "{block} and {other_block} return values of different types"
+                            ))
+                        }
+                    }
+                    None => {
+                        *return_types = Some((block, types));
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// True if both types are the same kind of type. Numeric types are not compared any further
+/// as SSA generation freely mixes fields and integers, e.g. when incrementing loop indices.
+fn same_kind_of_type(a: &Type, b: &Type) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ssa_refactor::{
+        ir::{function::RuntimeType, instruction::BinaryOp, map::Id, types::Type},
+        ssa_builder::FunctionBuilder,
+    };
+
+    use super::verify_function;
+
+    #[test]
+    fn well_formed_function() {
+        // fn main f0 {
+        //   b0(v0: Field):
+        //     v1 = eq v0, Field 0
+        //     jmpif v1, then: b1, else: b2
+        //   b1():
+        //     v3 = add v0, Field 1
+        //     jmp b3(v3)
+        //   b2():
+        //     jmp b3(v0)
+        //   b3(v2: Field):
+        //     return v2
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let b1 = builder.insert_block();
+        let b2 = builder.insert_block();
+        let b3 = builder.insert_block();
+
+        let v0 = builder.add_parameter(Type::field());
+        let v2 = builder.add_block_parameter(b3, Type::field());
+
+        let zero = builder.field_constant(0u128);
+        let v1 = builder.insert_binary(v0, BinaryOp::Eq, zero);
+        builder.terminate_with_jmpif(v1, b1, b2);
+
+        builder.switch_to_block(b1);
+        let one = builder.field_constant(1u128);
+        let v3 = builder.insert_binary(v0, BinaryOp::Add, one);
+        builder.terminate_with_jmp(b3, vec![v3]);
+
+        builder.switch_to_block(b2);
+        builder.terminate_with_jmp(b3, vec![v0]);
+
+        builder.switch_to_block(b3);
+        builder.terminate_with_return(vec![v2]);
+
+        let ssa = builder.finish();
+        assert_eq!(verify_function(ssa.main()), Ok(()));
+    }
+
+    #[test]
+    fn use_in_non_dominated_block() {
+        // fn main f0 {
+        //   b0(v0: Field):
+        //     jmpif v0, then: b1, else: b2
+        //   b1():
+        //     v1 = add v0, Field 1
+        //     jmp b2()
+        //   b2():
+        //     return v1
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let b1 = builder.insert_block();
+        let b2 = builder.insert_block();
+
+        let v0 = builder.add_parameter(Type::field());
+        builder.terminate_with_jmpif(v0, b1, b2);
+
+        builder.switch_to_block(b1);
+        let one = builder.field_constant(1u128);
+        let v1 = builder.insert_binary(v0, BinaryOp::Add, one);
+        builder.terminate_with_jmp(b2, vec![]);
+
+        builder.switch_to_block(b2);
+        builder.terminate_with_return(vec![v1]);
+
+        let ssa = builder.finish();
+        let error = verify_function(ssa.main()).unwrap_err();
+        assert_eq!(error, format!("{v1} is used in {b2} before being defined in {b1}"));
+    }
+
+    #[test]
+    fn jump_with_missing_arguments() {
+        // fn main f0 {
+        //   b0():
+        //     jmp b1()
+        //   b1(v0: Field):
+        //     return v0
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+        let b1 = builder.insert_block();
+        let v0 = builder.add_block_parameter(b1, Type::field());
+        builder.terminate_with_jmp(b1, vec![]);
+
+        builder.switch_to_block(b1);
+        builder.terminate_with_return(vec![v0]);
+
+        let ssa = builder.finish();
+        let error = verify_function(ssa.main()).unwrap_err();
+        assert!(error.contains("with 0 arguments but it has 1 parameters"), "{error}");
+    }
+}
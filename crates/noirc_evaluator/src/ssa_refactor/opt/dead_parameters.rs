@@ -0,0 +1,321 @@
+//! Dead parameter elimination: removes the parameters and return values of functions which
+//! are never used.
+//!
+//! A return value is dead if none of the calls to its function use it, and a parameter is dead
+//! if its function never uses it other than by returning it as a dead return value. Only the
+//! functions which are exclusively called directly can have their signature changed, as every
+//! call to them is updated alongside. The main function is left untouched since its parameters
+//! and return values make up the ABI of the program.
+use std::collections::{HashMap, HashSet};
+
+use iter_extended::vecmap;
+
+use crate::ssa_refactor::{
+    ir::{
+        basic_block::BasicBlockId,
+        dfg::DataFlowGraph,
+        function::{Function, FunctionId},
+        instruction::{Instruction, InstructionId, TerminatorInstruction},
+        value::{Value, ValueId},
+    },
+    ssa_gen::Ssa,
+};
+
+impl Ssa {
+    /// Removes the parameters and return values of functions which are never used,
+    /// updating every call to these functions accordingly.
+    pub(crate) fn remove_dead_parameters(mut self) -> Ssa {
+        let live_values = self.find_live_values();
+        if live_values.is_empty() {
+            return self;
+        }
+
+        for function in self.functions.values_mut() {
+            if let Some(live) = live_values.get(&function.id()) {
+                remove_dead_values(function, live);
+            }
+            update_calls(function, &live_values);
+        }
+        self
+    }
+
+    /// Returns which parameters and return values are live for each function having dead ones.
+    fn find_live_values(&self) -> HashMap<FunctionId, LiveValues> {
+        let used_as_values = self.functions_used_as_values();
+
+        // A return value is live if any call to its function uses it
+        let mut live_returns: HashMap<FunctionId, Vec<bool>> = HashMap::new();
+        for caller in self.functions.values() {
+            let used_values = used_values(caller, None);
+            for (_, call, callee) in calls(caller) {
+                let results = caller.dfg.instruction_results(call);
+                let live = live_returns.entry(callee).or_insert_with(|| vec![false; results.len()]);
+                for (position, result) in results.iter().enumerate() {
+                    // A result replaced by another value is conservatively considered used
+                    let resolved = caller.dfg.resolve(*result);
+                    if resolved != *result || used_values.contains(result) {
+                        live[position] = true;
+                    }
+                }
+            }
+        }
+
+        let mut live_values = HashMap::new();
+        for (id, function) in &self.functions {
+            if *id == self.main_id || used_as_values.contains(id) {
+                continue;
+            }
+            // Functions which are never called are left as-is
+            let returns = match live_returns.remove(id) {
+                Some(returns) => returns,
+                None => continue,
+            };
+
+            // The parameters of an entry block which is jumped to are also passed by these jumps
+            let used_values = used_values(function, Some(returns.as_slice()));
+            let parameters = if is_jumped_to(function, function.entry_block()) {
+                vec![true; function.parameters().len()]
+            } else {
+                vecmap(function.parameters(), |parameter| used_values.contains(parameter))
+            };
+
+            if parameters.contains(&false) || returns.contains(&false) {
+                live_values.insert(*id, LiveValues { parameters, returns });
+            }
+        }
+        live_values
+    }
+
+    /// Returns the functions which are used other than by calling them directly.
+    fn functions_used_as_values(&self) -> HashSet<FunctionId> {
+        let mut functions = HashSet::new();
+        for function in self.functions.values() {
+            let dfg = &function.dfg;
+            for (_, block) in dfg.basic_blocks_iter() {
+                for instruction in block.instructions() {
+                    match &dfg[*instruction] {
+                        Instruction::Call { arguments, .. } => {
+                            for argument in arguments {
+                                collect_functions(dfg, *argument, &mut functions);
+                            }
+                        }
+                        other => other.for_each_value(|value| {
+                            collect_functions(dfg, value, &mut functions);
+                        }),
+                    }
+                }
+                if let Some(terminator) = block.terminator() {
+                    terminator
+                        .for_each_value(|value| collect_functions(dfg, value, &mut functions));
+                }
+            }
+        }
+        functions
+    }
+}
+
+/// Whether each parameter and return value of a function is live, by position.
+struct LiveValues {
+    parameters: Vec<bool>,
+    returns: Vec<bool>,
+}
+
+/// Collects the functions referenced by the given value, including within arrays.
+fn collect_functions(dfg: &DataFlowGraph, value: ValueId, functions: &mut HashSet<FunctionId>) {
+    match &dfg[dfg.resolve(value)] {
+        Value::Function(id) => {
+            functions.insert(*id);
+        }
+        Value::Array { array, .. } => {
+            for element in array.iter() {
+                collect_functions(dfg, *element, functions);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Returns the values used by the instructions and terminators of the given function, ignoring
+/// the return values whose position is not live in `live_returns` if given.
+fn used_values(function: &Function, live_returns: Option<&[bool]>) -> HashSet<ValueId> {
+    let dfg = &function.dfg;
+    let mut used_values = HashSet::new();
+
+    for (_, block) in dfg.basic_blocks_iter() {
+        for instruction in block.instructions() {
+            dfg[*instruction].for_each_value(|value| mark_used(dfg, value, &mut used_values));
+        }
+        match (block.terminator(), live_returns) {
+            (Some(TerminatorInstruction::Return { return_values }), Some(live_returns)) => {
+                for value in retain_live(return_values, live_returns) {
+                    mark_used(dfg, value, &mut used_values);
+                }
+            }
+            (Some(terminator), _) => {
+                terminator.for_each_value(|value| mark_used(dfg, value, &mut used_values));
+            }
+            (None, _) => (),
+        }
+    }
+    used_values
+}
+
+/// Marks a value as used, along with the elements of constant arrays.
+fn mark_used(dfg: &DataFlowGraph, value: ValueId, used_values: &mut HashSet<ValueId>) {
+    let value = dfg.resolve(value);
+    if used_values.insert(value) {
+        if let Value::Array { array, .. } = &dfg[value] {
+            for element in array.iter() {
+                mark_used(dfg, *element, used_values);
+            }
+        }
+    }
+}
+
+/// Returns each direct call of the given function, along with its block and callee.
+fn calls(function: &Function) -> Vec<(BasicBlockId, InstructionId, FunctionId)> {
+    let dfg = &function.dfg;
+    let mut calls = Vec::new();
+    for (block_id, block) in dfg.basic_blocks_iter() {
+        for instruction in block.instructions() {
+            if let Instruction::Call { func, .. } = &dfg[*instruction] {
+                if let Value::Function(callee) = &dfg[dfg.resolve(*func)] {
+                    calls.push((block_id, *instruction, *callee));
+                }
+            }
+        }
+    }
+    calls
+}
+
+/// True if any block of the function jumps to the given block.
+fn is_jumped_to(function: &Function, target: BasicBlockId) -> bool {
+    function
+        .dfg
+        .basic_blocks_iter()
+        .any(|(_, block)| block.successors().any(|successor| successor == target))
+}
+
+/// Returns the values at the live positions.
+fn retain_live(values: &[ValueId], live: &[bool]) -> Vec<ValueId> {
+    values.iter().zip(live).filter(|(_, live)| **live).map(|(value, _)| *value).collect()
+}
+
+/// Removes the dead parameters and return values of the given function.
+fn remove_dead_values(function: &mut Function, live: &LiveValues) {
+    let entry_block = function.entry_block();
+    let parameters = retain_live(function.parameters(), &live.parameters);
+    function.dfg[entry_block].set_parameters(parameters);
+
+    let blocks = vecmap(function.dfg.basic_blocks_iter(), |(block, _)| block);
+    for block in blocks {
+        if let Some(TerminatorInstruction::Return { return_values }) =
+            function.dfg[block].terminator()
+        {
+            let return_values = retain_live(return_values, &live.returns);
+            function.dfg[block].set_terminator(TerminatorInstruction::Return { return_values });
+        }
+    }
+}
+
+/// Replaces each call of the given function to a function having dead parameters or return
+/// values by a call which only passes the live parameters and returns the live values.
+fn update_calls(function: &mut Function, live_values: &HashMap<FunctionId, LiveValues>) {
+    for (block, call, callee) in calls(function) {
+        let live = match live_values.get(&callee) {
+            Some(live) => live,
+            None => continue,
+        };
+
+        let dfg = &mut function.dfg;
+        let (func, arguments) = match &dfg[call] {
+            Instruction::Call { func, arguments } => {
+                (*func, retain_live(arguments, &live.parameters))
+            }
+            _ => unreachable!("Expected {call:?} to be a call"),
+        };
+        let results = retain_live(dfg.instruction_results(call), &live.returns);
+        let result_types = vecmap(&results, |result| dfg.type_of_value(*result));
+
+        let new_call =
+            dfg.make_instruction(Instruction::Call { func, arguments }, Some(result_types));
+        if let Some(location) = dfg.get_location(&call) {
+            dfg.set_location(new_call, location);
+        }
+        let new_results = dfg.instruction_results(new_call).to_vec();
+        for (result, new_result) in results.into_iter().zip(new_results) {
+            dfg.set_value_from_id(result, new_result);
+        }
+
+        for instruction in dfg[block].instructions_mut() {
+            if *instruction == call {
+                *instruction = new_call;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ssa_refactor::{
+        ir::{
+            function::RuntimeType, instruction::BinaryOp, instruction::Instruction, map::Id,
+            types::Type,
+        },
+        ssa_builder::FunctionBuilder,
+    };
+
+    #[test]
+    fn remove_dead_parameters_and_returns() {
+        // acir fn main f0 {
+        //   b0(v0: Field, v1: Field):
+        //     v3, v4 = call f1(v0, v1)
+        //     return v3
+        // }
+        // brillig fn foo f1 {
+        //   b0(v5: Field, v6: Field):
+        //     v8 = add v5, Field 1
+        //     return v8, v6
+        // }
+        //
+        // The second return value of foo is never used, so neither is its second parameter.
+        let main_id = Id::test_new(0);
+        let foo_id = Id::test_new(1);
+
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::field());
+        let v1 = builder.add_parameter(Type::field());
+        let foo = builder.import_function(foo_id);
+        let results = builder.insert_call(foo, vec![v0, v1], vec![Type::field(), Type::field()]);
+        let v3 = results[0];
+        builder.terminate_with_return(vec![v3]);
+
+        builder.new_brillig_function("foo".into(), foo_id);
+        let v5 = builder.add_parameter(Type::field());
+        let v6 = builder.add_parameter(Type::field());
+        let one = builder.field_constant(1u128);
+        let v8 = builder.insert_binary(v5, BinaryOp::Add, one);
+        builder.terminate_with_return(vec![v8, v6]);
+
+        let ssa = builder.finish().remove_dead_parameters();
+
+        let foo = &ssa.functions[&foo_id];
+        assert_eq!(foo.parameters(), &[v5]);
+        assert_eq!(foo.returns(), &[v8]);
+
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 1);
+        match &main.dfg[instructions[0]] {
+            Instruction::Call { arguments, .. } => assert_eq!(arguments, &[v0]),
+            other => panic!("Expected a call, found {other:?}"),
+        }
+        let results = main.dfg.instruction_results(instructions[0]);
+        assert_eq!(results.len(), 1);
+        assert_eq!(main.returns(), &[v3]);
+        assert_eq!(main.dfg.resolve(v3), results[0]);
+
+        // The parameters of main are never removed
+        assert_eq!(main.parameters(), &[v0, v1]);
+    }
+}
@@ -4,7 +4,7 @@
 //! with a non-literal target can be replaced with a call to an apply function.
 //! The apply function is a dispatch function that takes the function id as a parameter
 //! and dispatches to the correct target.
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use acvm::FieldElement;
 use iter_extended::vecmap;
@@ -94,7 +94,7 @@ impl DefunctionalizationContext {
 
     /// Defunctionalize a single function
     fn defunctionalize(&mut self, func: &mut Function) {
-        let mut target_function_ids = HashSet::new();
+        let mut call_targets = HashSet::new();
 
         for block_id in func.reachable_blocks() {
             let block = &func.dfg[block_id];
@@ -128,18 +128,17 @@ impl DefunctionalizationContext {
                             returns: result_types,
                             runtime: func.runtime(),
                         });
-                        target_function_ids.insert(apply_function.id);
-
                         // Replace the instruction with a call to apply
                         let apply_function_value_id = func.dfg.import_function(apply_function.id);
+                        call_targets.insert(apply_function_value_id);
                         if apply_function.dispatches_to_multiple_functions {
                             arguments.insert(0, target_func_id);
                         }
                         let func = apply_function_value_id;
                         replacement_instruction = Some(Instruction::Call { func, arguments });
                     }
-                    Value::Function(id) => {
-                        target_function_ids.insert(id);
+                    Value::Function(_) => {
+                        call_targets.insert(target_func_id);
                     }
                     _ => {}
                 }
@@ -156,7 +155,7 @@ impl DefunctionalizationContext {
                 match &func.dfg[value_id] {
                     // If the value is a static function, transform it to the function id
                     Value::Function(id) => {
-                        if !target_function_ids.contains(id) {
+                        if !call_targets.contains(&value_id) {
                             let new_value =
                                 func.dfg.make_constant(function_id_to_field(*id), Type::field());
                             func.dfg.set_value_from_id(value_id, new_value);
@@ -181,7 +180,7 @@ impl DefunctionalizationContext {
 /// Collects all functions used as a value by their signatures
 fn find_variants(ssa: &Ssa) -> HashMap<FunctionSignature, Vec<FunctionId>> {
     let mut variants: HashMap<FunctionSignature, Vec<FunctionId>> = HashMap::new();
-    let mut functions_used_as_values = HashSet::new();
+    let mut functions_used_as_values = BTreeSet::new();
 
     for function in ssa.functions.values() {
         functions_used_as_values.extend(functions_as_values(function));
@@ -196,41 +195,40 @@ fn find_variants(ssa: &Ssa) -> HashMap<FunctionSignature, Vec<FunctionId>> {
     variants
 }
 
-/// Finds all literal functions used as values in the given function
+/// Finds all literal functions used as values in the given function.
+/// A function which is also called directly is still used as a value if
+/// any other reference to it is not the target of a call.
 fn functions_as_values(func: &Function) -> HashSet<FunctionId> {
-    let mut literal_functions: HashSet<_> = func
-        .dfg
-        .values_iter()
-        .filter_map(|(id, _)| match func.dfg[id] {
-            Value::Function(id) => Some(id),
-            _ => None,
-        })
-        .collect();
-
+    let mut call_targets = HashSet::new();
     for block_id in func.reachable_blocks() {
         let block = &func.dfg[block_id];
         for instruction_id in block.instructions() {
-            let instruction = &func.dfg[*instruction_id];
-            let target_value = match instruction {
-                Instruction::Call { func, .. } => func,
-                _ => continue,
-            };
-            let target_id = match func.dfg[*target_value] {
-                Value::Function(id) => id,
-                _ => continue,
-            };
-            literal_functions.remove(&target_id);
+            if let Instruction::Call { func, .. } = &func.dfg[*instruction_id] {
+                call_targets.insert(*func);
+            }
         }
     }
-    literal_functions
+
+    func.dfg
+        .values_iter()
+        .filter_map(|(value_id, value)| match value {
+            Value::Function(id) if !call_targets.contains(&value_id) => Some(*id),
+            _ => None,
+        })
+        .collect()
 }
 
 fn create_apply_functions(
     ssa: &mut Ssa,
     variants_map: &HashMap<FunctionSignature, Vec<FunctionId>>,
 ) -> HashMap<FunctionSignature, ApplyFunction> {
+    // Apply functions are created in the order of their first variant rather than in the
+    // arbitrary order of the map so that their ids are the same in every compilation
+    let mut variants_map: Vec<_> = variants_map.iter().collect();
+    variants_map.sort_by_key(|(_, variants)| variants[0]);
+
     let mut apply_functions = HashMap::new();
-    for (signature, variants) in variants_map.iter() {
+    for (signature, variants) in variants_map {
         let dispatches_to_multiple_functions = variants.len() > 1;
         let id = if dispatches_to_multiple_functions {
             create_apply_function(ssa, signature, variants)
@@ -10,6 +10,9 @@
 //! while merging branches. These extra instructions can be cleaned up by a later dead instruction
 //! elimination (DIE) pass.
 //!
+//! Before flattening, instructions found at the end of both blocks jumping to the same block are
+//! moved into that block so that they are only emitted once, see the `common_tails` module.
+//!
 //! Though CFG information is lost during this pass, some key information is retained in the form
 //! of `EnableSideEffect` instructions. Each time the flattening pass enters and exits a branch of
 //! a jmpif, an instruction is inserted to capture a condition that is analogous to the activeness
@@ -132,7 +135,7 @@
 //!   v12 = add v10, v11
 //!   store v12 at v5         (new store)
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     rc::Rc,
 };
 
@@ -146,14 +149,15 @@ use crate::ssa_refactor::{
         dfg::InsertInstructionResult,
         function::Function,
         function_inserter::FunctionInserter,
-        instruction::{BinaryOp, Instruction, InstructionId, TerminatorInstruction},
+        instruction::{BinaryOp, Instruction, InstructionId, Intrinsic, TerminatorInstruction},
         types::{CompositeType, Type},
-        value::ValueId,
+        value::{Value, ValueId},
     },
     ssa_gen::Ssa,
 };
 
 mod branch_analysis;
+mod common_tails;
 
 impl Ssa {
     /// Flattens the control flow graph of each function such that the function is left with a
@@ -179,8 +183,9 @@ struct Context<'f> {
     /// Maps start of branch -> end of branch
     branch_ends: HashMap<BasicBlockId, BasicBlockId>,
 
-    /// Maps an address to the old and new value of the element at that address
-    store_values: HashMap<ValueId, Store>,
+    /// Maps an address to the old and new value of the element at that address.
+    /// This is ordered by address so that merged stores are inserted in a deterministic order.
+    store_values: BTreeMap<ValueId, Store>,
 
     /// Stores all allocations local to the current branch.
     /// Since these branches are local to the current branch (ie. only defined within one branch of
@@ -196,6 +201,10 @@ struct Context<'f> {
     /// condition. If we are under multiple conditions (a nested if), the topmost condition is
     /// the most recent condition combined with all previous conditions via `And` instructions.
     conditions: Vec<(BasicBlockId, ValueId)>,
+
+    /// Each condition cast to the numeric type of values merged under it, so that merging
+    /// several values of the same type reuses a single cast.
+    condition_casts: HashMap<(ValueId, Type), ValueId>,
 }
 
 struct Store {
@@ -206,7 +215,7 @@ struct Store {
 struct Branch {
     condition: ValueId,
     last_block: BasicBlockId,
-    store_values: HashMap<ValueId, Store>,
+    store_values: BTreeMap<ValueId, Store>,
     local_allocations: HashSet<ValueId>,
 }
 
@@ -220,16 +229,19 @@ fn flatten_function_cfg(function: &mut Function) {
     if let crate::ssa_refactor::ir::function::RuntimeType::Brillig = function.runtime() {
         return;
     }
+    common_tails::sink_common_tails(function);
+
     let cfg = ControlFlowGraph::with_function(function);
     let branch_ends = branch_analysis::find_branch_ends(function, &cfg);
 
     let mut context = Context {
         inserter: FunctionInserter::new(function),
         cfg,
-        store_values: HashMap::new(),
+        store_values: BTreeMap::new(),
         local_allocations: HashSet::new(),
         branch_ends,
         conditions: Vec::new(),
+        condition_casts: HashMap::new(),
     };
     context.flatten();
 }
@@ -315,8 +327,16 @@ impl<'f> Context<'f> {
     fn push_condition(&mut self, start_block: BasicBlockId, condition: ValueId) {
         let end_block = self.branch_ends[&start_block];
 
-        if let Some((_, previous_condition)) = self.conditions.last() {
-            let and = Instruction::binary(BinaryOp::And, *previous_condition, condition);
+        if let Some((_, previous_condition)) = self.conditions.last().copied() {
+            // A nested branch whose condition is always true, or the same as the condition of
+            // its parent branch, is taken exactly when its parent branch is
+            let dfg = &self.inserter.function.dfg;
+            let is_true = dfg.get_numeric_constant(condition).map_or(false, |value| value.is_one());
+            if is_true || previous_condition == condition {
+                self.conditions.push((end_block, previous_condition));
+                return;
+            }
+            let and = Instruction::binary(BinaryOp::And, previous_condition, condition);
             let new_condition = self.insert_instruction(and);
             self.conditions.push((end_block, new_condition));
         } else {
@@ -350,6 +370,9 @@ impl<'f> Context<'f> {
     ///
     /// If the stack is empty, a "true" u1 constant is taken to be the active condition. This is
     /// necessary for re-enabling side-effects when re-emerging to a branch depth of 0.
+    ///
+    /// If the last instruction of the entry block already enables side effects, it is replaced
+    /// rather than followed by another, since no instruction would be under its condition.
     fn insert_current_side_effects_enabled(&mut self) {
         let condition = match self.conditions.last() {
             Some((_, cond)) => *cond,
@@ -358,6 +381,15 @@ impl<'f> Context<'f> {
             }
         };
         let enable_side_effects = Instruction::EnableSideEffects { condition };
+
+        let entry = self.inserter.function.entry_block();
+        let dfg = &mut self.inserter.function.dfg;
+        if let Some(last) = dfg[entry].instructions().last().copied() {
+            if matches!(dfg[last], Instruction::EnableSideEffects { .. }) {
+                dfg[last] = enable_side_effects;
+                return;
+            }
+        }
         self.insert_instruction_with_typevars(enable_side_effects, None);
     }
 
@@ -376,6 +408,10 @@ impl<'f> Context<'f> {
         then_value: ValueId,
         else_value: ValueId,
     ) -> ValueId {
+        // Values left unchanged by both branches need no merging
+        if then_value == else_value {
+            return then_value;
+        }
         match self.inserter.function.dfg.type_of_value(then_value) {
             Type::Numeric(_) => {
                 self.merge_numeric_values(then_condition, else_condition, then_value, else_value)
@@ -388,7 +424,13 @@ impl<'f> Context<'f> {
                 then_value,
                 else_value,
             ),
-            Type::Slice(_) => panic!("Cannot return slices from an if expression"),
+            Type::Slice(element_types) => self.merge_slice_values(
+                element_types,
+                then_condition,
+                else_condition,
+                then_value,
+                else_value,
+            ),
             Type::Reference => panic!("Cannot return references from an if expression"),
             Type::Function => panic!("Cannot return functions from an if expression"),
         }
@@ -432,7 +474,111 @@ impl<'f> Context<'f> {
             }
         }
 
-        self.inserter.function.dfg.make_array(merged, element_types)
+        let typ = Type::Array(element_types, len);
+        self.inserter.function.dfg.make_array(merged, typ)
+    }
+
+    /// Given an if expression that returns a slice: `if c { slice1 } else { slice2 }`, this
+    /// function merges the contents of both slices. The lengths of the slices are separate
+    /// numeric values which are merged like any other.
+    ///
+    /// The contents of each slice may hold a different amount of elements. The merged contents
+    /// hold as many elements as the largest of the two. Past the end of the smaller contents,
+    /// the elements of the larger contents are used as they are, since they can only be read
+    /// when the larger slice is chosen.
+    fn merge_slice_values(
+        &mut self,
+        element_types: Rc<CompositeType>,
+        then_condition: ValueId,
+        else_condition: ValueId,
+        then_value: ValueId,
+        else_value: ValueId,
+    ) -> ValueId {
+        let then_capacity = self.slice_capacity(then_value);
+        let else_capacity = self.slice_capacity(else_value);
+        let mut merged = im::Vector::new();
+
+        for i in 0..then_capacity.max(else_capacity) {
+            for (element_index, element_type) in element_types.iter().enumerate() {
+                let index = ((i * element_types.len() + element_index) as u128).into();
+                let index = self.inserter.function.dfg.make_constant(index, Type::field());
+
+                let typevars = Some(vec![element_type.clone()]);
+
+                let mut get_element = |array, typevars| {
+                    let get = Instruction::ArrayGet { array, index };
+                    self.insert_instruction_with_typevars(get, typevars).first()
+                };
+
+                let element = if i >= else_capacity {
+                    get_element(then_value, typevars)
+                } else if i >= then_capacity {
+                    get_element(else_value, typevars)
+                } else {
+                    let then_element = get_element(then_value, typevars.clone());
+                    let else_element = get_element(else_value, typevars);
+                    self.merge_values(then_condition, else_condition, then_element, else_element)
+                };
+                merged.push_back(element);
+            }
+        }
+
+        let typ = Type::Slice(element_types);
+        self.inserter.function.dfg.make_array(merged, typ)
+    }
+
+    /// Returns the amount of elements held by the contents of the given slice. This is known at
+    /// compile-time for slices in a flattened function although their length may not be, since
+    /// the contents of a slice are only grown or shrunk by slice intrinsics.
+    fn slice_capacity(&self, slice: ValueId) -> usize {
+        let dfg = &self.inserter.function.dfg;
+        let slice = dfg.resolve(slice);
+
+        match &dfg[slice] {
+            Value::Array { array, typ } => array.len() / typ.element_size(),
+            Value::Instruction { instruction, .. } => match &dfg[*instruction] {
+                Instruction::ArraySet { array, .. } => self.slice_capacity(*array),
+                Instruction::Load { address } => {
+                    self.slice_capacity(self.last_stored_value(*instruction, *address))
+                }
+                Instruction::Call { func, arguments } => match &dfg[*func] {
+                    Value::Intrinsic(Intrinsic::SlicePushBack | Intrinsic::SlicePushFront) => {
+                        self.slice_capacity(arguments[1]) + 1
+                    }
+                    Value::Intrinsic(Intrinsic::SlicePopBack | Intrinsic::Sort) => {
+                        self.slice_capacity(arguments[1])
+                    }
+                    Value::Intrinsic(Intrinsic::SlicePopFront) => {
+                        self.slice_capacity(arguments[1]).saturating_sub(1)
+                    }
+                    other => unreachable!("ICE: slice returned by unexpected call to {other:?}"),
+                },
+                other => unreachable!("ICE: slice returned by unexpected instruction {other:?}"),
+            },
+            other => unreachable!("ICE: unable to determine the capacity of slice {other:?}"),
+        }
+    }
+
+    /// Returns the value stored at the given address by the last store preceding the given load
+    /// instruction. Since the function is being flattened, the store must be in the entry block.
+    fn last_stored_value(&self, load: InstructionId, address: ValueId) -> ValueId {
+        let dfg = &self.inserter.function.dfg;
+        let address = dfg.resolve(address);
+        let instructions = dfg[self.inserter.function.entry_block()].instructions();
+        let load_position = instructions.iter().position(|instruction| *instruction == load);
+
+        instructions[..load_position.unwrap_or(instructions.len())]
+            .iter()
+            .rev()
+            .find_map(|instruction| match &dfg[*instruction] {
+                Instruction::Store { address: store_address, value }
+                    if dfg.resolve(*store_address) == address =>
+                {
+                    Some(*value)
+                }
+                _ => None,
+            })
+            .expect("ICE: slice loaded from an address it was never stored to")
     }
 
     /// Merge two numeric values a and b from separate basic blocks to a single value. This
@@ -453,8 +599,8 @@ impl<'f> Context<'f> {
         );
 
         // We must cast the bool conditions to the actual numeric type used by each value.
-        let then_condition = self.insert_instruction(Instruction::Cast(then_condition, then_type));
-        let else_condition = self.insert_instruction(Instruction::Cast(else_condition, else_type));
+        let then_condition = self.cast_condition(then_condition, then_type);
+        let else_condition = self.cast_condition(else_condition, else_type);
 
         let mul = Instruction::binary(BinaryOp::Mul, then_condition, then_value);
         let then_value =
@@ -468,6 +614,16 @@ impl<'f> Context<'f> {
         self.inserter.function.dfg.insert_instruction_and_results(add, block, None).first()
     }
 
+    /// Casts a condition to the given numeric type, reusing a previous cast if there is one.
+    fn cast_condition(&mut self, condition: ValueId, typ: Type) -> ValueId {
+        if let Some(cast) = self.condition_casts.get(&(condition, typ.clone())) {
+            return *cast;
+        }
+        let cast = self.insert_instruction(Instruction::Cast(condition, typ.clone()));
+        self.condition_casts.insert((condition, typ), cast);
+        cast
+    }
+
     /// Inline one branch of a jmpif instruction.
     ///
     /// This will continue inlining recursively until the next end block is reached where each branch
@@ -494,7 +650,7 @@ impl<'f> Context<'f> {
                 // args that will be merged by inline_branch_end. Since jmpifs don't have
                 // block arguments, it is safe to use the jmpif block here.
                 last_block: jmpif_block,
-                store_values: HashMap::new(),
+                store_values: BTreeMap::new(),
                 local_allocations: HashSet::new(),
             }
         } else {
@@ -578,7 +734,7 @@ impl<'f> Context<'f> {
     /// this function also needs to be changed to reflect that.
     fn merge_stores(&mut self, then_branch: Branch, else_branch: Branch) {
         // Address -> (then_value, else_value, value_before_the_if)
-        let mut new_map = HashMap::with_capacity(then_branch.store_values.len());
+        let mut new_map = BTreeMap::new();
 
         for (address, store) in then_branch.store_values {
             new_map.insert(address, (store.new_value, store.old_value, store.old_value));
@@ -759,7 +915,6 @@ mod test {
         //   b0(v0: u1):
         //     enable_side_effects v0
         //     v5 = not v0
-        //     enable_side_effects v5
         //     enable_side_effects u1 1
         //     v7 = mul v0, Field 3
         //     v8 = mul v5, Field 4
@@ -810,7 +965,6 @@ mod test {
         //     v4 = eq v3, v0
         //     constrain v4
         //     v5 = not v0
-        //     enable_side_effects v5
         //     enable_side_effects u1 1
         //     return
         // }
@@ -1297,7 +1451,8 @@ mod test {
 
         let element_type = Rc::new(vec![Type::field()]);
         let zero = builder.field_constant(0_u128);
-        let zero_array = builder.array_constant(im::Vector::unit(zero), element_type.clone());
+        let array_type = Type::Array(element_type.clone(), 1);
+        let zero_array = builder.array_constant(im::Vector::unit(zero), array_type);
         let i_zero = builder.numeric_constant(0_u128, Type::unsigned(32));
         let pedersen =
             builder.import_intrinsic_id(Intrinsic::BlackBox(acvm::acir::BlackBoxFunc::Pedersen));
@@ -1469,4 +1624,67 @@ mod test {
             _ => unreachable!(),
         }
     }
+
+    #[test]
+    fn sink_common_tails() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     jmpif v0, then: b1, else: b2
+        //   b1():
+        //     v5 = call f1(v1)
+        //     jmp b3(v5)
+        //   b2():
+        //     v6 = call f1(v2)
+        //     jmp b3(v6)
+        //   b3(v3: Field):
+        //     return v3
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+
+        let b1 = builder.insert_block();
+        let b2 = builder.insert_block();
+        let b3 = builder.insert_block();
+
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.add_parameter(Type::field());
+        let v3 = builder.add_block_parameter(b3, Type::field());
+        let f1 = builder.import_function(Id::test_new(1));
+
+        builder.terminate_with_jmpif(v0, b1, b2);
+
+        builder.switch_to_block(b1);
+        let v5 = builder.insert_call(f1, vec![v1], vec![Type::field()])[0];
+        builder.terminate_with_jmp(b3, vec![v5]);
+
+        builder.switch_to_block(b2);
+        let v6 = builder.insert_call(f1, vec![v2], vec![Type::field()])[0];
+        builder.terminate_with_jmp(b3, vec![v6]);
+
+        builder.switch_to_block(b3);
+        builder.terminate_with_return(vec![v3]);
+
+        // Expected output, where f1 is only called once on the merged arguments:
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     enable_side_effects v0
+        //     v8 = not v0
+        //     enable_side_effects u1 1
+        //     v10 = cast v0 as Field
+        //     v11 = cast v8 as Field
+        //     v12 = mul v10, v1
+        //     v13 = mul v11, v2
+        //     v14 = add v12, v13
+        //     v9 = call f1(v14)
+        //     return v9
+        // }
+        let ssa = builder.finish().flatten_cfg();
+        let main = ssa.main();
+        assert_eq!(main.reachable_blocks().len(), 1);
+
+        let call_count =
+            count_instruction(main, |instruction| matches!(instruction, Instruction::Call { .. }));
+        assert_eq!(call_count, 1);
+    }
 }
@@ -0,0 +1,205 @@
+//! This is an algorithm for moving the instructions shared by the end of both branches of a
+//! jmpif into the block where the branches join.
+//!
+//! Flattening inlines both branches of each jmpif, so an instruction found at the end of both
+//! branches is otherwise emitted twice, once under each condition, along with the merge of its
+//! results. When the last instructions of the two blocks jumping to a join block are the same
+//! instruction applied to different operands, a single copy of it is moved to the start of the
+//! join block instead. Its operands which differ are passed as new parameters of the join block,
+//! so that flattening merges them rather than the results:
+//!
+//! b1():
+//!   v5 = call f1(v3)
+//!   jmp b3(v5)
+//! b2():
+//!   v6 = call f1(v4)
+//!   jmp b3(v6)
+//! b3(v7: Field):
+//!   ... b3 instructions using v7 ...
+//! =========================
+//! b1():
+//!   jmp b3(v3)
+//! b2():
+//!   jmp b3(v4)
+//! b3(v8: Field):
+//!   v9 = call f1(v8)
+//!   ... b3 instructions using v9 ...
+//!
+//! This is repeated for as long as the last instructions of both blocks match. Memory
+//! instructions are never moved since flattening already merges the stores of both branches.
+use iter_extended::vecmap;
+
+use crate::ssa_refactor::ir::{
+    basic_block::BasicBlockId,
+    cfg::ControlFlowGraph,
+    dfg::DataFlowGraph,
+    function::Function,
+    instruction::{Instruction, TerminatorInstruction},
+    types::Type,
+    value::ValueId,
+};
+
+/// Moves the instructions which end both predecessors of a block into this block.
+pub(super) fn sink_common_tails(function: &mut Function) {
+    let cfg = ControlFlowGraph::with_function(function);
+    let mut blocks: Vec<_> = function.reachable_blocks().into_iter().collect();
+    blocks.sort();
+
+    for block in blocks {
+        let mut predecessors: Vec<_> = cfg.predecessors(block).collect();
+        predecessors.sort();
+        if let [first, second] = predecessors[..] {
+            if jumps_to(&function.dfg, first, block) && jumps_to(&function.dfg, second, block) {
+                while sink_last_instruction(&mut function.dfg, first, second, block) {}
+            }
+        }
+    }
+}
+
+fn jumps_to(dfg: &DataFlowGraph, block: BasicBlockId, destination: BasicBlockId) -> bool {
+    matches!(
+        dfg[block].terminator(),
+        Some(TerminatorInstruction::Jmp { destination: target, .. }) if *target == destination
+    )
+}
+
+/// Moves the last instruction of the `first` and `second` blocks to the start of the `join`
+/// block they both jump to, if they are the same instruction. Returns true if it was moved.
+fn sink_last_instruction(
+    dfg: &mut DataFlowGraph,
+    first: BasicBlockId,
+    second: BasicBlockId,
+    join: BasicBlockId,
+) -> bool {
+    let (first_id, second_id) =
+        match (dfg[first].instructions().last(), dfg[second].instructions().last()) {
+            (Some(first_id), Some(second_id)) => (*first_id, *second_id),
+            _ => return false,
+        };
+    let instruction = dfg[first_id].clone();
+    if !is_same_operation(&instruction, &dfg[second_id]) {
+        return false;
+    }
+
+    let mut first_operands = Vec::new();
+    instruction.for_each_value(|value| first_operands.push(dfg.resolve(value)));
+    let mut second_operands = Vec::new();
+    dfg[second_id].for_each_value(|value| second_operands.push(dfg.resolve(value)));
+
+    // Each operand which differs is merged by flattening in place of the results, which is
+    // only worth it for instructions other than calls if there is at most one of them.
+    let differing_operands: Vec<_> = first_operands
+        .iter()
+        .zip(&second_operands)
+        .filter(|(first_operand, second_operand)| first_operand != second_operand)
+        .collect();
+    let is_call = matches!(instruction, Instruction::Call { .. });
+    if !is_call && differing_operands.len() > 1 {
+        return false;
+    }
+    let can_merge = |value: &ValueId| match dfg.type_of_value(*value) {
+        Type::Numeric(_) => true,
+        Type::Array(..) => is_call,
+        Type::Slice(_) | Type::Reference | Type::Function => false,
+    };
+    if !differing_operands.iter().all(|(first_operand, _)| can_merge(first_operand)) {
+        return false;
+    }
+
+    // The results may only be used by the jumps to the join block, at the same positions
+    let first_results = dfg.instruction_results(first_id).to_vec();
+    let second_results = dfg.instruction_results(second_id).to_vec();
+    if first_results.len() != second_results.len() {
+        return false;
+    }
+    let first_arguments = vecmap(dfg[first].terminator_arguments(), |value| dfg.resolve(*value));
+    let second_arguments = vecmap(dfg[second].terminator_arguments(), |value| dfg.resolve(*value));
+    let mut replaced_positions = Vec::new();
+    for (first_result, second_result) in first_results.iter().zip(&second_results) {
+        let positions = positions_of(&first_arguments, *first_result);
+        if positions != positions_of(&second_arguments, *second_result)
+            || dfg.type_of_value(*first_result) != dfg.type_of_value(*second_result)
+        {
+            return false;
+        }
+        replaced_positions.push(positions);
+    }
+
+    // Build the instruction in the join block, taking the differing operands as new parameters
+    let mut new_first_arguments = Vec::new();
+    let mut new_second_arguments = Vec::new();
+    let mut operands = Vec::with_capacity(first_operands.len());
+    for (first_operand, second_operand) in first_operands.into_iter().zip(second_operands) {
+        if first_operand == second_operand {
+            operands.push(first_operand);
+        } else {
+            let typ = dfg.type_of_value(first_operand);
+            operands.push(dfg.add_block_parameter(join, typ));
+            new_first_arguments.push(first_operand);
+            new_second_arguments.push(second_operand);
+        }
+    }
+    let mut operands = operands.into_iter();
+    let new_instruction = instruction.map_values(|_| operands.next().unwrap());
+    let result_types = vecmap(&first_results, |result| dfg.type_of_value(*result));
+    let new_id = dfg.make_instruction(new_instruction, Some(result_types));
+    if let Some(location) = dfg.get_location(&first_id) {
+        dfg.set_location(new_id, location);
+    }
+    dfg[join].instructions_mut().insert(0, new_id);
+    dfg[first].instructions_mut().pop();
+    dfg[second].instructions_mut().pop();
+
+    // The parameters receiving the results are replaced by the results of the new instruction
+    let new_results = dfg.instruction_results(new_id).to_vec();
+    let parameters = dfg.block_parameters(join).to_vec();
+    let mut removed = vec![false; parameters.len()];
+    for (positions, new_result) in replaced_positions.into_iter().zip(new_results) {
+        for position in positions {
+            dfg.set_value_from_id(parameters[position], new_result);
+            removed[position] = true;
+        }
+    }
+    let retain = |values: Vec<ValueId>| -> Vec<ValueId> {
+        let kept = values.into_iter().zip(&removed).filter(|(_, removed)| !**removed);
+        kept.map(|(value, _)| value).collect()
+    };
+    dfg[join].set_parameters(retain(parameters));
+
+    let mut first_arguments = retain(first_arguments);
+    first_arguments.extend(new_first_arguments);
+    dfg[first].set_terminator(TerminatorInstruction::Jmp {
+        destination: join,
+        arguments: first_arguments,
+    });
+    let mut second_arguments = retain(second_arguments);
+    second_arguments.extend(new_second_arguments);
+    dfg[second].set_terminator(TerminatorInstruction::Jmp {
+        destination: join,
+        arguments: second_arguments,
+    });
+    true
+}
+
+/// True if both instructions are the same once their operands are ignored, and do not
+/// access memory.
+fn is_same_operation(first: &Instruction, second: &Instruction) -> bool {
+    if matches!(
+        first,
+        Instruction::Allocate
+            | Instruction::Load { .. }
+            | Instruction::Store { .. }
+            | Instruction::EnableSideEffects { .. }
+    ) {
+        return false;
+    }
+    let mut placeholder = None;
+    let first = first.map_values(|value| *placeholder.get_or_insert(value));
+    let second = second.map_values(|value| *placeholder.get_or_insert(value));
+    first == second
+}
+
+fn positions_of(values: &[ValueId], value: ValueId) -> Vec<usize> {
+    let positions = values.iter().enumerate().filter(|(_, other)| **other == value);
+    positions.map(|(position, _)| position).collect()
+}
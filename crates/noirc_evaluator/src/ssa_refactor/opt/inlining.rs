@@ -5,6 +5,7 @@
 use std::collections::{HashMap, HashSet};
 
 use iter_extended::vecmap;
+use noirc_frontend::token::InlineType;
 
 use crate::ssa_refactor::{
     ir::{
@@ -38,6 +39,96 @@ impl Ssa {
     pub(crate) fn inline_functions(self) -> Ssa {
         InlineContext::new(&self).inline_all(self)
     }
+
+    /// Inline the calls between brillig functions to small brillig functions.
+    ///
+    /// Brillig functions are kept as separate functions, so each call to them saves and
+    /// restores the caller's registers. Inlining the calls to brillig functions with at most
+    /// `max_instructions` instructions and which do not call any function themselves avoids
+    /// this overhead for trivial helpers, 0 disabling this. Calls from ACIR functions are
+    /// left untouched.
+    ///
+    /// Functions marked `#[inline(always)]` are inlined whatever their size unless they are
+    /// recursive, while functions marked `#[inline(never)]` are never inlined.
+    pub(crate) fn inline_small_brillig_functions(mut self, max_instructions: usize) -> Ssa {
+        let functions_to_inline: HashSet<FunctionId> = self
+            .functions
+            .values()
+            .filter(|function| {
+                function.runtime() == RuntimeType::Brillig
+                    && self.should_inline_brillig_function(function, max_instructions)
+            })
+            .map(|function| function.id())
+            .collect();
+        if functions_to_inline.is_empty() {
+            return self;
+        }
+
+        let brillig_functions: Vec<FunctionId> = self
+            .functions
+            .values()
+            .filter(|function| function.runtime() == RuntimeType::Brillig)
+            .map(|function| function.id())
+            .collect();
+        for id in brillig_functions {
+            let context =
+                InlineContext::new_brillig(&self.functions[&id], functions_to_inline.clone());
+            let function = context.inline_brillig_function(&self, id);
+            self.functions.insert(id, function);
+        }
+        self
+    }
+
+    /// True if the calls to the given brillig function from other brillig functions should be
+    /// inlined. Recursive functions are never inlined, so that inlining always terminates.
+    fn should_inline_brillig_function(&self, function: &Function, max_instructions: usize) -> bool {
+        match function.inline_type() {
+            Some(InlineType::Never) => false,
+            Some(InlineType::Always) => !self.is_recursive(function.id()),
+            None => max_instructions > 0 && is_small_leaf_function(function, max_instructions),
+        }
+    }
+
+    /// True if the given function may call itself, directly or through other functions.
+    fn is_recursive(&self, id: FunctionId) -> bool {
+        let mut visited = HashSet::new();
+        let mut to_visit = called_functions(&self.functions[&id]);
+        while let Some(callee) = to_visit.pop() {
+            if callee == id {
+                return true;
+            }
+            if visited.insert(callee) {
+                to_visit.extend(called_functions(&self.functions[&callee]));
+            }
+        }
+        false
+    }
+}
+
+/// True if `function` has at most `max_instructions` instructions and does not call any
+/// other function, so inlining it cannot recurse.
+fn is_small_leaf_function(function: &Function, max_instructions: usize) -> bool {
+    let num_instructions: usize = function
+        .reachable_blocks()
+        .into_iter()
+        .map(|block| function.dfg[block].instructions().len())
+        .sum();
+    num_instructions <= max_instructions && called_functions(function).is_empty()
+}
+
+/// Returns the functions called by `function`, excluding foreign functions and intrinsics.
+fn called_functions(function: &Function) -> Vec<FunctionId> {
+    let mut called_functions = Vec::new();
+    for block in function.reachable_blocks() {
+        for instruction in function.dfg[block].instructions() {
+            if let Instruction::Call { func, .. } = &function.dfg[*instruction] {
+                if let Value::Function(callee) = function.dfg[*func] {
+                    called_functions.push(callee);
+                }
+            }
+        }
+    }
+    called_functions
 }
 
 /// The context for the function inlining pass.
@@ -53,6 +144,10 @@ struct InlineContext {
     /// inlining we can remove all other functions from the resulting Ssa struct and keep only
     /// the function that was inlined into.
     failed_to_inline_a_call: bool,
+
+    /// When inlining into a brillig function, the only functions to inline.
+    /// Calls to any other function are kept.
+    brillig_functions_to_inline: Option<HashSet<FunctionId>>,
 }
 
 /// The per-function inlining context contains information that is only valid for one function.
@@ -98,13 +193,45 @@ impl InlineContext {
     fn new(ssa: &Ssa) -> InlineContext {
         let main_name = ssa.main().name().to_owned();
         let builder = FunctionBuilder::new(main_name, ssa.next_id.next(), RuntimeType::Acir);
-        Self { builder, recursion_level: 0, failed_to_inline_a_call: false }
+        Self {
+            builder,
+            recursion_level: 0,
+            failed_to_inline_a_call: false,
+            brillig_functions_to_inline: None,
+        }
+    }
+
+    /// Create a new context object to inline the given brillig functions into a brillig function.
+    /// The function is rebuilt with the same id so that the calls to it remain valid.
+    fn new_brillig(function: &Function, functions_to_inline: HashSet<FunctionId>) -> InlineContext {
+        let mut builder =
+            FunctionBuilder::new(function.name().to_owned(), function.id(), RuntimeType::Brillig);
+        builder.current_function.set_inline_type(function.inline_type());
+        Self {
+            builder,
+            recursion_level: 0,
+            failed_to_inline_a_call: false,
+            brillig_functions_to_inline: Some(functions_to_inline),
+        }
     }
 
     /// Start inlining the main function and all functions reachable from it.
     fn inline_all(mut self, ssa: Ssa) -> Ssa {
-        let main = ssa.main();
-        let mut context = PerFunctionContext::new(&mut self, main);
+        self.inline_root(&ssa, ssa.main());
+        self.finish(ssa)
+    }
+
+    /// Inline the calls to small functions within a brillig function, returning the new function.
+    fn inline_brillig_function(mut self, ssa: &Ssa, id: FunctionId) -> Function {
+        self.inline_root(ssa, &ssa.functions[&id]);
+        let mut new_ssa = self.builder.finish();
+        assert_eq!(new_ssa.functions.len(), 1);
+        new_ssa.functions.pop_first().unwrap().1
+    }
+
+    /// Inline the function being built into, which is a copy of `root`.
+    fn inline_root(&mut self, ssa: &Ssa, root: &Function) {
+        let mut context = PerFunctionContext::new(self, root);
         context.inlining_main = true;
 
         // The main block is already inserted so we have to add it to context.blocks and add
@@ -120,8 +247,7 @@ impl InlineContext {
         }
 
         context.blocks.insert(context.source_function.entry_block(), entry_block);
-        context.inline_blocks(&ssa);
-        self.finish(ssa)
+        context.inline_blocks(ssa);
     }
 
     /// Inlines a function into the current function and returns the translated return values
@@ -217,9 +343,9 @@ impl<'function> PerFunctionContext<'function> {
             Value::ForeignFunction(function) => {
                 self.context.builder.import_foreign_function(function)
             }
-            Value::Array { array, element_type } => {
+            Value::Array { array, typ } => {
                 let elements = array.iter().map(|value| self.translate_value(*value)).collect();
-                self.context.builder.array_constant(elements, element_type.clone())
+                self.context.builder.array_constant(elements, typ.clone())
             }
         };
 
@@ -346,12 +472,23 @@ impl<'function> PerFunctionContext<'function> {
         for id in block.instructions() {
             match &self.source_function.dfg[*id] {
                 Instruction::Call { func, arguments } => match self.get_function(*func) {
-                    Some(function) => match ssa.functions[&function].runtime() {
-                        RuntimeType::Acir => self.inline_function(ssa, *id, function, arguments),
-                        RuntimeType::Brillig => {
-                            self.context.failed_to_inline_a_call = true;
-                            self.push_instruction(*id);
+                    Some(function) => match &self.context.brillig_functions_to_inline {
+                        Some(functions_to_inline) => {
+                            if functions_to_inline.contains(&function) {
+                                self.inline_function(ssa, *id, function, arguments);
+                            } else {
+                                self.push_instruction(*id);
+                            }
                         }
+                        None => match ssa.functions[&function].runtime() {
+                            RuntimeType::Acir => {
+                                self.inline_function(ssa, *id, function, arguments);
+                            }
+                            RuntimeType::Brillig => {
+                                self.context.failed_to_inline_a_call = true;
+                                self.push_instruction(*id);
+                            }
+                        },
                     },
                     None => self.push_instruction(*id),
                 },
@@ -386,6 +523,10 @@ impl<'function> PerFunctionContext<'function> {
             .requires_ctrl_typevars()
             .then(|| vecmap(&results, |result| self.source_function.dfg.type_of_value(*result)));
 
+        if let Some(location) = self.source_function.dfg.get_location(&id) {
+            self.context.builder.set_location(location);
+        }
+
         let new_results = self.context.builder.insert_instruction(instruction, ctrl_typevars);
         Self::insert_new_instruction_results(&mut self.values, &results, new_results);
     }
@@ -403,6 +544,11 @@ impl<'function> PerFunctionContext<'function> {
             InsertInstructionResult::SimplifiedTo(new_result) => {
                 values.insert(old_results[0], new_result);
             }
+            InsertInstructionResult::SimplifiedToMultiple(new_results) => {
+                for (old_result, new_result) in old_results.iter().zip(new_results) {
+                    values.insert(*old_result, new_result);
+                }
+            }
             InsertInstructionResult::Results(new_results) => {
                 for (old_result, new_result) in old_results.iter().zip(new_results) {
                     values.insert(*old_result, *new_result);
@@ -472,14 +618,16 @@ impl<'function> PerFunctionContext<'function> {
 #[cfg(test)]
 mod test {
     use acvm::FieldElement;
+    use noirc_frontend::token::InlineType;
 
     use crate::ssa_refactor::{
         ir::{
             basic_block::BasicBlockId,
             function::RuntimeType,
-            instruction::{BinaryOp, Intrinsic, TerminatorInstruction},
+            instruction::{BinaryOp, Instruction, Intrinsic, TerminatorInstruction},
             map::Id,
             types::Type,
+            value::Value,
         },
         ssa_builder::FunctionBuilder,
     };
@@ -750,4 +898,126 @@ mod test {
         let main = ssa.main();
         assert_eq!(main.reachable_blocks().len(), 4);
     }
+
+    #[test]
+    fn inline_small_brillig_functions() {
+        // fn main f0 {
+        //   b0(v0: Field):
+        //     v2 = call f1(v0)
+        //     return v2
+        // }
+        // brillig fn caller f1 {
+        //   b0(v0: Field):
+        //     v2 = call f2(v0)
+        //     v3 = call f1(v2)
+        //     return v3
+        // }
+        // brillig fn double f2 {
+        //   b0(v0: Field):
+        //     v1 = add v0, v0
+        //     return v1
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let main_v0 = builder.add_parameter(Type::field());
+        let caller_id = Id::test_new(1);
+        let caller = builder.import_function(caller_id);
+        let results = builder.insert_call(caller, vec![main_v0], vec![Type::field()]).to_vec();
+        builder.terminate_with_return(results);
+
+        builder.new_brillig_function("caller".into(), caller_id);
+        let caller_v0 = builder.add_parameter(Type::field());
+        let double = builder.import_function(Id::test_new(2));
+        let caller = builder.import_function(caller_id);
+        let doubled = builder.insert_call(double, vec![caller_v0], vec![Type::field()])[0];
+        let results = builder.insert_call(caller, vec![doubled], vec![Type::field()]).to_vec();
+        builder.terminate_with_return(results);
+
+        builder.new_brillig_function("double".into(), Id::test_new(2));
+        let double_v0 = builder.add_parameter(Type::field());
+        let v1 = builder.insert_binary(double_v0, BinaryOp::Add, double_v0);
+        builder.terminate_with_return(vec![v1]);
+
+        let ssa = builder.finish().inline_small_brillig_functions(10);
+        assert_eq!(ssa.functions.len(), 3);
+
+        let called_functions = |id| {
+            let function = &ssa.functions[&id];
+            let block = &function.dfg[function.entry_block()];
+            block
+                .instructions()
+                .iter()
+                .filter_map(|instruction| match &function.dfg[*instruction] {
+                    Instruction::Call { func, .. } => match function.dfg[*func] {
+                        Value::Function(id) => Some(id),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        // The call from the ACIR main function is kept, as is the recursive call in caller
+        assert_eq!(called_functions(main_id), vec![caller_id]);
+        assert_eq!(called_functions(caller_id), vec![caller_id]);
+        assert_eq!(ssa.functions[&caller_id].runtime(), RuntimeType::Brillig);
+    }
+
+    #[test]
+    fn inline_attributes_override_brillig_threshold() {
+        // brillig fn main f0 {
+        //   b0(v0: Field):
+        //     v2 = call f1(v0)
+        //     return v2
+        // }
+        // #[inline(always)]
+        // brillig fn large f1 {
+        //   b0(v0: Field):
+        //     v1 = add v0, v0
+        //     v2 = mul v1, v1
+        //     v3 = call f2(v2)
+        //     return v3
+        // }
+        // #[inline(never)]
+        // brillig fn small f2 {
+        //   b0(v0: Field):
+        //     return v0
+        // }
+        let main_id = Id::test_new(0);
+        let large_id = Id::test_new(1);
+        let small_id = Id::test_new(2);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+        let main_v0 = builder.add_parameter(Type::field());
+        let large = builder.import_function(large_id);
+        let results = builder.insert_call(large, vec![main_v0], vec![Type::field()]).to_vec();
+        builder.terminate_with_return(results);
+
+        builder.new_brillig_function("large".into(), large_id);
+        builder.current_function.set_inline_type(Some(InlineType::Always));
+        let large_v0 = builder.add_parameter(Type::field());
+        let v1 = builder.insert_binary(large_v0, BinaryOp::Add, large_v0);
+        let v2 = builder.insert_binary(v1, BinaryOp::Mul, v1);
+        let small = builder.import_function(small_id);
+        let results = builder.insert_call(small, vec![v2], vec![Type::field()]).to_vec();
+        builder.terminate_with_return(results);
+
+        builder.new_brillig_function("small".into(), small_id);
+        builder.current_function.set_inline_type(Some(InlineType::Never));
+        let small_v0 = builder.add_parameter(Type::field());
+        builder.terminate_with_return(vec![small_v0]);
+
+        let ssa = builder.finish().inline_small_brillig_functions(1);
+
+        // large is inlined into main despite its size, but not the call to small within it
+        let main = ssa.main();
+        let calls: Vec<_> = main.dfg[main.entry_block()]
+            .instructions()
+            .iter()
+            .filter_map(|instruction| match &main.dfg[*instruction] {
+                Instruction::Call { func, .. } => Some(main.dfg[*func].clone()),
+                _ => None,
+            })
+            .collect();
+        assert!(matches!(calls.as_slice(), [Value::Function(id)] if *id == small_id));
+        assert_eq!(ssa.functions[&large_id].inline_type(), Some(InlineType::Always));
+    }
 }
@@ -70,7 +70,7 @@ impl PerBlockContext {
         let mut loads_to_substitute = HashMap::new();
 
         // Maps Load result id -> value to replace the result of the load with
-        let mut load_values_to_substitute = HashMap::new();
+        let mut load_values_to_substitute = BTreeMap::new();
 
         for instruction_id in block.instructions() {
             match &dfg[*instruction_id] {
@@ -211,11 +211,11 @@ mod tests {
         let one = builder.field_constant(FieldElement::one());
         let two = builder.field_constant(FieldElement::one());
 
-        let element_type = Rc::new(vec![Type::field()]);
-        let array = builder.array_constant(vector![one, two], element_type.clone());
+        let array_type = Type::Array(Rc::new(vec![Type::field()]), 2);
+        let array = builder.array_constant(vector![one, two], array_type.clone());
 
         builder.insert_store(v0, array);
-        let v1 = builder.insert_load(v0, Type::Array(element_type, 2));
+        let v1 = builder.insert_load(v0, array_type);
         let v2 = builder.insert_array_get(v1, one, Type::field());
         builder.terminate_with_return(vec![v2]);
 
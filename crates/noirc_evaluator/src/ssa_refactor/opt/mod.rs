@@ -4,10 +4,12 @@
 //! simpler form until the IR only has a single function remaining with 1 block within it.
 //! Generally, these passes are also expected to minimize the final amount of instructions.
 mod constant_folding;
+mod dead_parameters;
 mod defunctionalize;
 mod die;
 mod flatten_cfg;
 mod inlining;
 mod mem2reg;
+mod range_analysis;
 mod simplify_cfg;
 mod unrolling;
@@ -0,0 +1,306 @@
+//! The range analysis pass removes truncations which cannot change the value they truncate.
+//!
+//! SSA generation truncates the result of each integer operation back to the bit size of its
+//! type, and every truncation costs range constraints in ACIR. Many of them are redundant: the
+//! sum of two `u8`s produced by comparisons is never more than 2 bits wide for example.
+//! This pass tracks an upper bound on the bit size of each value through the instructions
+//! defining it, starting from constants and the range constrained parameters of the function.
+//! A truncation of a value known to fit in the truncated bit size is then replaced by the value
+//! itself, while the other truncations are given the tightest maximum bit size known for their
+//! value so that their quotient needs a smaller range constraint.
+use std::collections::HashMap;
+
+use acvm::FieldElement;
+
+use crate::ssa_refactor::{
+    ir::{
+        basic_block::BasicBlockId,
+        dfg::DataFlowGraph,
+        function::{Function, RuntimeType},
+        instruction::{Binary, BinaryOp, Instruction, InstructionId},
+        post_order::PostOrder,
+        types::{NumericType, Type},
+        value::ValueId,
+    },
+    ssa_gen::Ssa,
+};
+
+impl Ssa {
+    /// Removes the truncations of ACIR functions which are implied by the known
+    /// ranges of the values they truncate.
+    pub(crate) fn remove_redundant_truncations(mut self) -> Ssa {
+        for function in self.functions.values_mut() {
+            if function.runtime() == RuntimeType::Acir {
+                remove_redundant_truncations(function);
+            }
+        }
+        self
+    }
+}
+
+fn remove_redundant_truncations(function: &mut Function) {
+    let mut context = Context::default();
+
+    // The integer parameters of an ACIR function are range constrained to their type
+    for parameter in function.parameters() {
+        if let Type::Numeric(
+            NumericType::Unsigned { bit_size } | NumericType::Signed { bit_size },
+        ) = function.dfg.type_of_value(*parameter)
+        {
+            context.max_bit_sizes.insert(*parameter, bit_size);
+        }
+    }
+
+    // Blocks are visited in reverse post order, so that the values used by an instruction
+    // are visited before it unless they come from a loop's back edge
+    let mut blocks = PostOrder::with_function(function).as_slice().to_vec();
+    blocks.reverse();
+
+    for block in blocks {
+        context.remove_redundant_truncations_in_block(&mut function.dfg, block);
+    }
+}
+
+#[derive(Default)]
+struct Context {
+    /// An upper bound on the number of bits of each value known to be smaller than a field.
+    /// Values missing from this map may be any field element.
+    max_bit_sizes: HashMap<ValueId, u32>,
+}
+
+impl Context {
+    fn remove_redundant_truncations_in_block(
+        &mut self,
+        dfg: &mut DataFlowGraph,
+        block: BasicBlockId,
+    ) {
+        let instructions = std::mem::take(dfg[block].instructions_mut());
+        let mut kept_instructions = Vec::with_capacity(instructions.len());
+
+        for instruction in instructions {
+            if !self.is_redundant_truncation(dfg, instruction) {
+                kept_instructions.push(instruction);
+            }
+        }
+
+        *dfg[block].instructions_mut() = kept_instructions;
+    }
+
+    /// Records the bit size of the results of the given instruction and returns true if it is
+    /// a truncation which can be removed, in which case its result is replaced by its input.
+    fn is_redundant_truncation(
+        &mut self,
+        dfg: &mut DataFlowGraph,
+        instruction: InstructionId,
+    ) -> bool {
+        let max_bit_size = match &dfg[instruction] {
+            Instruction::Truncate { value, bit_size, max_bit_size } => {
+                let (value, bit_size, max_bit_size) = (*value, *bit_size, *max_bit_size);
+                let value_bit_size = self.max_bit_size(dfg, value);
+
+                if value_bit_size <= bit_size {
+                    let result = dfg.instruction_results(instruction)[0];
+                    dfg.set_value_from_id(result, value);
+                    return true;
+                }
+                if value_bit_size < max_bit_size {
+                    let max_bit_size = value_bit_size;
+                    dfg[instruction] = Instruction::Truncate { value, bit_size, max_bit_size };
+                }
+                Some(bit_size)
+            }
+            Instruction::Binary(binary) => self.binary_max_bit_size(dfg, binary),
+            Instruction::Cast(value, Type::Numeric(target_type)) => {
+                let value_bit_size = self.max_bit_size(dfg, *value);
+                match (dfg.type_of_value(*value), target_type) {
+                    // Casts to a smaller integer type truncate their input
+                    (
+                        Type::Numeric(NumericType::Signed { bit_size: signed_bit_size }),
+                        NumericType::Unsigned { bit_size },
+                    ) if signed_bit_size > *bit_size => Some(*bit_size),
+                    // A negative value is a large field element once it is cast to a field or
+                    // to a wider unsigned type, so its bit size is unknown
+                    (Type::Numeric(NumericType::Signed { .. }), _) => None,
+                    (_, NumericType::NativeField) => Some(value_bit_size),
+                    (Type::Numeric(value_type), NumericType::Unsigned { bit_size })
+                        if numeric_bit_size(&value_type) > *bit_size =>
+                    {
+                        Some(value_bit_size.min(*bit_size))
+                    }
+                    (_, NumericType::Unsigned { .. }) => Some(value_bit_size),
+                    (_, NumericType::Signed { .. }) => None,
+                }
+            }
+            // Not flips the bits of a value fitting in its type
+            Instruction::Not(value) => match dfg.type_of_value(*value) {
+                Type::Numeric(NumericType::Unsigned { bit_size }) => {
+                    (self.max_bit_size(dfg, *value) <= bit_size).then_some(bit_size)
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+
+        if let Some(max_bit_size) = max_bit_size {
+            if max_bit_size < FieldElement::max_num_bits() {
+                let result = dfg.instruction_results(instruction)[0];
+                self.max_bit_sizes.insert(result, max_bit_size);
+            }
+        }
+        false
+    }
+
+    /// Returns an upper bound on the bit size of the result of the given binary operation.
+    fn binary_max_bit_size(&self, dfg: &DataFlowGraph, binary: &Binary) -> Option<u32> {
+        let lhs = self.max_bit_size(dfg, binary.lhs);
+        let rhs = self.max_bit_size(dfg, binary.rhs);
+        let is_unsigned =
+            matches!(dfg.type_of_value(binary.lhs), Type::Numeric(NumericType::Unsigned { .. }));
+
+        match binary.operator {
+            BinaryOp::Add => Some(lhs.max(rhs) + 1),
+            BinaryOp::Mul => Some(lhs + rhs),
+            BinaryOp::Eq | BinaryOp::Lt => Some(1),
+            BinaryOp::And => Some(lhs.min(rhs)),
+            BinaryOp::Or | BinaryOp::Xor => Some(lhs.max(rhs)),
+            BinaryOp::Div | BinaryOp::Shr if is_unsigned => Some(lhs),
+            BinaryOp::Mod if is_unsigned => Some(lhs.min(rhs)),
+            BinaryOp::Shl => {
+                let shift = dfg.get_numeric_constant(binary.rhs)?.try_to_u64()?;
+                Some(lhs.saturating_add(u32::try_from(shift).ok()?))
+            }
+            // Subtractions may underflow and wrap around the field
+            BinaryOp::Sub | BinaryOp::Div | BinaryOp::Shr | BinaryOp::Mod => None,
+        }
+    }
+
+    /// Returns an upper bound on the number of bits of the given value.
+    fn max_bit_size(&self, dfg: &DataFlowGraph, value: ValueId) -> u32 {
+        let value = dfg.resolve(value);
+        match dfg.get_numeric_constant(value) {
+            Some(constant) => constant.num_bits(),
+            None => {
+                self.max_bit_sizes.get(&value).copied().unwrap_or_else(FieldElement::max_num_bits)
+            }
+        }
+    }
+}
+
+fn numeric_bit_size(typ: &NumericType) -> u32 {
+    match typ {
+        NumericType::Signed { bit_size } | NumericType::Unsigned { bit_size } => *bit_size,
+        NumericType::NativeField => FieldElement::max_num_bits(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ssa_refactor::{
+        ir::{
+            function::RuntimeType, instruction::BinaryOp, instruction::Instruction, map::Id,
+            types::Type,
+        },
+        ssa_builder::FunctionBuilder,
+    };
+
+    #[test]
+    fn remove_truncations_of_small_values() {
+        // fn main f0 {
+        //   b0(v0: u8, v1: u8):
+        //     v2 = lt v0, v1
+        //     v3 = add v2, u8 1
+        //     v4 = truncate v3 to 8 bits, max_bit_size: 9
+        //     v5 = mul v0, v1
+        //     v6 = truncate v5 to 8 bits, max_bit_size: 16
+        //     v7 = add v4, v6
+        //     return v7
+        // }
+        //
+        // v3 fits in 2 bits so its truncation is removed, while v5 may need up to 16 bits
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::unsigned(8));
+        let v1 = builder.add_parameter(Type::unsigned(8));
+
+        let v2 = builder.insert_binary(v0, BinaryOp::Lt, v1);
+        let one = builder.numeric_constant(1u128, Type::unsigned(8));
+        let v3 = builder.insert_binary(v2, BinaryOp::Add, one);
+        let v4 = builder.insert_truncate(v3, 8, 9);
+        let v5 = builder.insert_binary(v0, BinaryOp::Mul, v1);
+        let v6 = builder.insert_truncate(v5, 8, 16);
+        let v7 = builder.insert_binary(v4, BinaryOp::Add, v6);
+        builder.terminate_with_return(vec![v7]);
+
+        let ssa = builder.finish().remove_redundant_truncations();
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 5);
+
+        let truncations: Vec<_> = instructions
+            .iter()
+            .filter_map(|instruction| match &main.dfg[*instruction] {
+                Instruction::Truncate { value, bit_size, max_bit_size } => {
+                    Some((*value, *bit_size, *max_bit_size))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(truncations, vec![(v5, 8, 16)]);
+        assert_eq!(main.dfg.resolve(v4), v3);
+    }
+
+    #[test]
+    fn tighten_max_bit_size_of_truncations() {
+        // fn main f0 {
+        //   b0(v0: u8):
+        //     v1 = mul v0, u8 3
+        //     v2 = truncate v1 to 8 bits, max_bit_size: 16
+        //     return v2
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::unsigned(8));
+        let three = builder.numeric_constant(3u128, Type::unsigned(8));
+        let v1 = builder.insert_binary(v0, BinaryOp::Mul, three);
+        let v2 = builder.insert_truncate(v1, 8, 16);
+        builder.terminate_with_return(vec![v2]);
+
+        let ssa = builder.finish().remove_redundant_truncations();
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert!(matches!(
+            main.dfg[instructions[1]],
+            Instruction::Truncate { bit_size: 8, max_bit_size: 10, .. }
+        ));
+    }
+
+    #[test]
+    fn keep_truncations_of_signed_values_cast_to_wider_types() {
+        // fn main f0 {
+        //   b0(v0: i8):
+        //     v1 = cast v0 as u16
+        //     v2 = add v1, u16 1
+        //     v3 = truncate v2 to 16 bits, max_bit_size: 17
+        //     return v3
+        // }
+        //
+        // A negative v0 is a large field element once cast, e.g. `(-1 as u16) + 1` must wrap to 0
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::signed(8));
+        let v1 = builder.insert_cast(v0, Type::unsigned(16));
+        let one = builder.numeric_constant(1u128, Type::unsigned(16));
+        let v2 = builder.insert_binary(v1, BinaryOp::Add, one);
+        let v3 = builder.insert_truncate(v2, 16, 17);
+        builder.terminate_with_return(vec![v3]);
+
+        let ssa = builder.finish().remove_redundant_truncations();
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 3);
+        assert!(matches!(
+            main.dfg[instructions[2]],
+            Instruction::Truncate { bit_size: 16, max_bit_size: 17, .. }
+        ));
+    }
+}
@@ -12,25 +12,40 @@
 //!
 //! Note that this pass also often creates superfluous jmp instructions in the
 //! program that will need to be removed by a later simplify cfg pass.
+//!
+//! ACIR cannot express loops, so a loop of an ACIR function which fails to unroll, or which
+//! would be unrolled more times than the given limit, is an error. Such loops are simply left
+//! as-is in brillig functions.
 use std::collections::{HashMap, HashSet};
 
-use crate::ssa_refactor::{
-    ir::{
-        basic_block::BasicBlockId, cfg::ControlFlowGraph, dfg::DataFlowGraph, dom::DominatorTree,
-        function::Function, function_inserter::FunctionInserter,
-        instruction::TerminatorInstruction, post_order::PostOrder, value::ValueId,
+use noirc_errors::Location;
+
+use crate::{
+    errors::{RuntimeError, RuntimeErrorKind},
+    ssa_refactor::{
+        ir::{
+            basic_block::BasicBlockId,
+            cfg::ControlFlowGraph,
+            dfg::DataFlowGraph,
+            dom::DominatorTree,
+            function::{Function, RuntimeType},
+            function_inserter::FunctionInserter,
+            instruction::{Binary, BinaryOp, Instruction, InstructionId, TerminatorInstruction},
+            post_order::PostOrder,
+            value::{Value, ValueId},
+        },
+        ssa_gen::Ssa,
     },
-    ssa_gen::Ssa,
 };
 
 impl Ssa {
-    /// Unroll all loops in each SSA function.
-    /// If any loop cannot be unrolled, it is left as-is or in a partially unrolled state.
-    pub(crate) fn unroll_loops(mut self) -> Ssa {
+    /// Unroll all loops in each SSA function, unrolling each loop at most `max_unroll` times.
+    /// If a loop of a brillig function cannot be unrolled, it is left as-is.
+    pub(crate) fn unroll_loops(mut self, max_unroll: usize) -> Result<Ssa, RuntimeError> {
         for function in self.functions.values_mut() {
-            find_all_loops(function).unroll_each_loop(function);
+            find_all_loops(function).unroll_each_loop(function, max_unroll)?;
         }
-        self
+        Ok(self)
     }
 }
 
@@ -95,26 +110,38 @@ fn find_all_loops(function: &Function) -> Loops {
 
 impl Loops {
     /// Unroll all loops within a given function.
-    /// Any loops which fail to be unrolled (due to using non-constant indices) will be unmodified.
-    fn unroll_each_loop(mut self, function: &mut Function) {
+    /// Any loops of a brillig function which fail to be unrolled (due to using non-constant
+    /// indices or having too many iterations) will be unmodified, while they are an error
+    /// in an ACIR function.
+    fn unroll_each_loop(
+        mut self,
+        function: &mut Function,
+        max_unroll: usize,
+    ) -> Result<(), RuntimeError> {
         while let Some(next_loop) = self.yet_to_unroll.pop() {
             // If we've previously modified a block in this loop we need to refresh the context.
             // This happens any time we have nested loops.
             if next_loop.blocks.iter().any(|block| self.modified_blocks.contains(block)) {
                 let mut new_context = find_all_loops(function);
                 new_context.failed_to_unroll = self.failed_to_unroll;
-                return new_context.unroll_each_loop(function);
+                return new_context.unroll_each_loop(function, max_unroll);
             }
 
             // Don't try to unroll the loop again if it is known to fail
             if !self.failed_to_unroll.contains(&next_loop.header) {
-                if unroll_loop(function, &self.cfg, &next_loop).is_ok() {
-                    self.modified_blocks.extend(next_loop.blocks);
-                } else {
-                    self.failed_to_unroll.insert(next_loop.header);
+                match unroll_loop(function, &self.cfg, &next_loop, max_unroll) {
+                    Ok(()) => self.modified_blocks.extend(next_loop.blocks),
+                    Err(kind) if function.runtime() == RuntimeType::Acir => {
+                        let location = get_loop_location(function, &next_loop);
+                        return Err(RuntimeError::new(kind, location));
+                    }
+                    Err(_) => {
+                        self.failed_to_unroll.insert(next_loop.header);
+                    }
                 }
             }
         }
+        Ok(())
     }
 }
 
@@ -150,12 +177,27 @@ fn find_blocks_in_loop(
 }
 
 /// Unroll a single loop in the function.
-/// Returns Err(()) if it failed to unroll and Ok(()) otherwise.
-fn unroll_loop(function: &mut Function, cfg: &ControlFlowGraph, loop_: &Loop) -> Result<(), ()> {
+/// Returns an error if the loop bounds are not constants or if the loop has more than
+/// `max_unroll` iterations, in which case the loop is left unmodified.
+fn unroll_loop(
+    function: &mut Function,
+    cfg: &ControlFlowGraph,
+    loop_: &Loop,
+    max_unroll: usize,
+) -> Result<(), RuntimeErrorKind> {
     let mut unroll_into = get_pre_header(cfg, loop_);
-    let mut jump_value = get_induction_variable(function, unroll_into)?;
+    let mut jump_value = get_induction_variable(function, unroll_into)
+        .map_err(|_| RuntimeErrorKind::UnknownLoopBound)?;
+
+    if let Some(trip_count) = get_trip_count(function, loop_, jump_value) {
+        if trip_count > max_unroll as u128 {
+            return Err(RuntimeErrorKind::UnrollLimitExceeded { trip_count, max_unroll });
+        }
+    }
 
-    while let Some(context) = unroll_loop_header(function, loop_, unroll_into, jump_value)? {
+    while let Some(context) = unroll_loop_header(function, loop_, unroll_into, jump_value)
+        .map_err(|_| RuntimeErrorKind::UnknownLoopBound)?
+    {
         let (last_block, last_value) = context.unroll_loop_iteration();
         unroll_into = last_block;
         jump_value = last_value;
@@ -187,19 +229,50 @@ fn get_induction_variable(function: &Function, block: BasicBlockId) -> Result<Va
             // This assumption will no longer be valid if e.g. mutable variables are represented as
             // block parameters. If that becomes the case we'll need to figure out which variable
             // is generally constant and increasing to guess which parameter is the induction
-            // variable.
-            assert_eq!(arguments.len(), 1, "It is expected that a loop's induction variable is the only block parameter of the loop header");
-            let value = arguments[0];
-            if function.dfg.get_numeric_constant(value).is_some() {
-                Ok(value)
-            } else {
-                Err(())
+            // variable. The headers of `while` loops, which cannot be unrolled, have no parameters.
+            match arguments.as_slice() {
+                [value] if function.dfg.get_numeric_constant(*value).is_some() => Ok(*value),
+                _ => Err(()),
             }
         }
         _ => Err(()),
     }
 }
 
+/// Return the `lt i, end` comparison the loop header branches on.
+///
+/// Expects the header to terminate in `jmpif v` where `v = lt i, end`.
+fn get_loop_condition(function: &Function, loop_: &Loop) -> Option<InstructionId> {
+    match function.dfg[loop_.header].terminator()? {
+        TerminatorInstruction::JmpIf { condition, .. } => match function.dfg[*condition] {
+            Value::Instruction { instruction, .. } => Some(instruction),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Return the number of iterations of the loop when starting from the given induction value,
+/// if the end of the loop range is a known constant.
+fn get_trip_count(function: &Function, loop_: &Loop, induction_value: ValueId) -> Option<u128> {
+    let condition = get_loop_condition(function, loop_)?;
+    match &function.dfg[condition] {
+        Instruction::Binary(Binary { rhs, operator: BinaryOp::Lt, .. }) => {
+            let start = function.dfg.get_numeric_constant(induction_value)?.to_u128();
+            let end = function.dfg.get_numeric_constant(*rhs)?.to_u128();
+            Some(end.saturating_sub(start))
+        }
+        _ => None,
+    }
+}
+
+/// Return the source location of the range of the loop, which is attached to the
+/// comparison of its header.
+fn get_loop_location(function: &Function, loop_: &Loop) -> Option<Location> {
+    let condition = get_loop_condition(function, loop_)?;
+    function.dfg.get_location(&condition)
+}
+
 /// Unrolls the header block of the loop. This is the block that dominates all other blocks in the
 /// loop and contains the jmpif instruction that lets us know if we should continue looping.
 /// Returns Some(iteration context) if we should perform another iteration.
@@ -424,11 +497,17 @@ impl<'f> LoopIteration<'f> {
 
 #[cfg(test)]
 mod tests {
-    use crate::ssa_refactor::{
-        ir::{function::RuntimeType, instruction::BinaryOp, map::Id, types::Type},
-        ssa_builder::FunctionBuilder,
+    use crate::{
+        errors::RuntimeErrorKind,
+        ssa_refactor::{
+            ir::{function::RuntimeType, instruction::BinaryOp, map::Id, types::Type},
+            ssa_builder::FunctionBuilder,
+            ssa_gen::Ssa,
+        },
     };
 
+    use super::find_all_loops;
+
     #[test]
     fn unroll_nested_loops() {
         // fn main() {
@@ -547,27 +626,27 @@ mod tests {
         // }
         // The final block count is not 1 because unrolling creates some unnecessary jmps.
         // If a simplify cfg pass is ran afterward, the expected block count will be 1.
-        let ssa = ssa.unroll_loops();
+        let ssa = ssa.unroll_loops(100).unwrap();
         assert_eq!(ssa.main().reachable_blocks().len(), 5);
     }
 
-    // Test that the pass can still be run on loops which fail to unroll properly
-    #[test]
-    fn fail_to_unroll_loop() {
-        // fn main f0 {
-        //   b0(v0: Field):
-        //     jmp b1(v0)
-        //   b1(v1: Field):
-        //     v2 = lt v1, 5
-        //     jmpif v2, then: b2, else: b3
-        //   b2():
-        //     v3 = add v1, Field 1
-        //     jmp b1(v3)
-        //   b3():
-        //     return Field 0
-        // }
+    /// Builds the following loop, starting from the parameter of main if `start` is None:
+    ///
+    /// fn main f0 {
+    ///   b0(v0: Field):
+    ///     jmp b1(v0)
+    ///   b1(v1: Field):
+    ///     v2 = lt v1, 5
+    ///     jmpif v2, then: b2, else: b3
+    ///   b2():
+    ///     v3 = add v1, Field 1
+    ///     jmp b1(v3)
+    ///   b3():
+    ///     return Field 0
+    /// }
+    fn simple_loop(runtime: RuntimeType, start: Option<u128>) -> Ssa {
         let main_id = Id::test_new(0);
-        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, runtime);
 
         let b1 = builder.insert_block();
         let b2 = builder.insert_block();
@@ -576,7 +655,11 @@ mod tests {
         let v0 = builder.add_parameter(Type::field());
         let v1 = builder.add_block_parameter(b1, Type::field());
 
-        builder.terminate_with_jmp(b1, vec![v0]);
+        let start = match start {
+            Some(start) => builder.field_constant(start),
+            None => v0,
+        };
+        builder.terminate_with_jmp(b1, vec![start]);
 
         builder.switch_to_block(b1);
         let five = builder.field_constant(5u128);
@@ -592,11 +675,41 @@ mod tests {
         let zero = builder.field_constant(0u128);
         builder.terminate_with_return(vec![zero]);
 
-        let ssa = builder.finish();
+        builder.finish()
+    }
+
+    // Test that the pass can still be run on brillig loops which fail to unroll properly
+    #[test]
+    fn fail_to_unroll_loop() {
+        let ssa = simple_loop(RuntimeType::Brillig, None);
         assert_eq!(ssa.main().reachable_blocks().len(), 4);
 
         // Expected ssa is unchanged
-        let ssa = ssa.unroll_loops();
+        let ssa = ssa.unroll_loops(100).unwrap();
+        assert_eq!(ssa.main().reachable_blocks().len(), 4);
+    }
+
+    #[test]
+    fn unknown_loop_bound_in_acir_function() {
+        let ssa = simple_loop(RuntimeType::Acir, None);
+        let error = ssa.unroll_loops(100).unwrap_err();
+        assert!(matches!(error.kind, RuntimeErrorKind::UnknownLoopBound));
+    }
+
+    #[test]
+    fn unroll_limit() {
+        let ssa = simple_loop(RuntimeType::Acir, Some(1));
+        let error = ssa.unroll_loops(3).unwrap_err();
+        assert!(matches!(
+            error.kind,
+            RuntimeErrorKind::UnrollLimitExceeded { trip_count: 4, max_unroll: 3 }
+        ));
+
+        // Brillig loops over the limit are left as-is
+        let ssa = simple_loop(RuntimeType::Brillig, Some(1)).unroll_loops(3).unwrap();
         assert_eq!(ssa.main().reachable_blocks().len(), 4);
+
+        let ssa = simple_loop(RuntimeType::Acir, Some(1)).unroll_loops(4).unwrap();
+        assert!(find_all_loops(ssa.main()).yet_to_unroll.is_empty());
     }
 }
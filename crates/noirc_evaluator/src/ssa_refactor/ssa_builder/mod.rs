@@ -1,6 +1,7 @@
-use std::{borrow::Cow, rc::Rc};
+use std::borrow::Cow;
 
 use acvm::FieldElement;
+use noirc_errors::Location;
 
 use crate::ssa_refactor::ir::{
     basic_block::BasicBlockId,
@@ -16,7 +17,6 @@ use super::{
         dfg::InsertInstructionResult,
         function::RuntimeType,
         instruction::{InstructionId, Intrinsic},
-        types::CompositeType,
     },
     ssa_gen::Ssa,
 };
@@ -32,6 +32,8 @@ pub(crate) struct FunctionBuilder {
     pub(super) current_function: Function,
     current_block: BasicBlockId,
     finished_functions: Vec<Function>,
+    /// The source location attached to each instruction inserted from now on
+    current_location: Option<Location>,
 }
 
 impl FunctionBuilder {
@@ -48,7 +50,12 @@ impl FunctionBuilder {
         new_function.set_runtime(runtime);
         let current_block = new_function.entry_block();
 
-        Self { current_function: new_function, current_block, finished_functions: Vec::new() }
+        Self {
+            current_function: new_function,
+            current_block,
+            finished_functions: Vec::new(),
+            current_location: None,
+        }
     }
 
     /// Finish the current function and create a new function.
@@ -65,6 +72,7 @@ impl FunctionBuilder {
         let mut new_function = Function::new(name, function_id);
         new_function.set_runtime(runtime_type);
         self.current_block = new_function.entry_block();
+        self.current_location = None;
 
         let old_function = std::mem::replace(&mut self.current_function, new_function);
         self.finished_functions.push(old_function);
@@ -107,13 +115,9 @@ impl FunctionBuilder {
         self.numeric_constant(value.into(), Type::field())
     }
 
-    /// Insert an array constant into the current function with the given element values.
-    pub(crate) fn array_constant(
-        &mut self,
-        elements: im::Vector<ValueId>,
-        element_types: Rc<CompositeType>,
-    ) -> ValueId {
-        self.current_function.dfg.make_array(elements, element_types)
+    /// Insert an array or slice constant into the current function with the given element values.
+    pub(crate) fn array_constant(&mut self, elements: im::Vector<ValueId>, typ: Type) -> ValueId {
+        self.current_function.dfg.make_array(elements, typ)
     }
 
     /// Returns the type of the given value.
@@ -144,13 +148,26 @@ impl FunctionBuilder {
         instruction: Instruction,
         ctrl_typevars: Option<Vec<Type>>,
     ) -> InsertInstructionResult {
-        self.current_function.dfg.insert_instruction_and_results(
+        self.current_function.dfg.insert_instruction_with_location(
             instruction,
             self.current_block,
             ctrl_typevars,
+            self.current_location,
         )
     }
 
+    /// Sets the source location which is attached to every instruction inserted
+    /// from now on, until another location is set.
+    pub(crate) fn set_location(&mut self, location: Location) -> &mut FunctionBuilder {
+        self.current_location = Some(location);
+        self
+    }
+
+    /// Records the name of the Noir variable the given value is bound to.
+    pub(crate) fn set_value_name(&mut self, value: ValueId, name: String) {
+        self.current_function.dfg.set_value_name(value, name);
+    }
+
     /// Switch to inserting instructions in the given block.
     /// Expects the given block to be within the same function. If you want to insert
     /// instructions into a new function, call new_function instead.
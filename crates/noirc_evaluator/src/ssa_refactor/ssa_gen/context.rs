@@ -6,7 +6,7 @@ use acvm::FieldElement;
 use iter_extended::vecmap;
 use noirc_frontend::monomorphization::ast::{self, LocalId, Parameters};
 use noirc_frontend::monomorphization::ast::{FuncId, Program};
-use noirc_frontend::Signedness;
+use noirc_frontend::{OverflowMode, Signedness};
 
 use crate::ssa_refactor::ir::dfg::DataFlowGraph;
 use crate::ssa_refactor::ir::function::FunctionId as IrFunctionId;
@@ -110,6 +110,7 @@ impl<'a> FunctionContext<'a> {
         } else {
             self.builder.new_function(func.name.clone(), id);
         }
+        self.builder.current_function.set_inline_type(func.inline_type);
         self.add_parameters_to_scope(&func.parameters);
     }
 
@@ -118,8 +119,10 @@ impl<'a> FunctionContext<'a> {
     /// The returned parameter type list will be flattened, so any struct parameters will
     /// be returned as one entry for each field (recursively).
     fn add_parameters_to_scope(&mut self, parameters: &Parameters) {
-        for (id, mutable, _, typ) in parameters {
+        for (id, mutable, name, typ) in parameters {
             self.add_parameter_to_scope(*id, typ, *mutable);
+            let parameter_values = self.lookup(*id);
+            self.name_values(&parameter_values, name);
         }
     }
 
@@ -146,6 +149,22 @@ impl<'a> FunctionContext<'a> {
         self.definitions.insert(parameter_id, parameter_value);
     }
 
+    /// Records `name` as the name of each immutable value in `values`.
+    ///
+    /// When a variable is a tuple, the name of each field is suffixed with its index
+    /// in the flattened tuple.
+    pub(super) fn name_values(&mut self, values: &Values, name: &str) {
+        let values = values.clone().flatten();
+        let is_tuple = values.len() > 1;
+        for (index, value) in values.into_iter().enumerate() {
+            // Mutable variables are stored in memory rather than bound to a single value
+            if let Value::Normal(value) = value {
+                let name = if is_tuple { format!("{name}.{index}") } else { name.to_owned() };
+                self.builder.set_value_name(value, name);
+            }
+        }
+    }
+
     /// Allocate a single slot of memory and store into it the given initial value of the variable.
     /// Always returns a Value::Mutable wrapping the allocate instruction.
     pub(super) fn new_mutable_variable(&mut self, value_to_store: ValueId) -> Value {
@@ -171,6 +190,13 @@ impl<'a> FunctionContext<'a> {
                 Tree::Branch(vecmap(fields, |field| Self::map_type_helper(field, f)))
             }
             ast::Type::Unit => Tree::empty(),
+            // Slices are represented as a (length, contents) pair since their
+            // length is not known at compile-time.
+            ast::Type::Slice(_) => {
+                let length = Tree::Leaf(f(Type::field()));
+                let contents = Tree::Leaf(f(Self::convert_non_tuple_type(typ)));
+                Tree::Branch(vec![length, contents])
+            }
             // A mutable reference wraps each element into a reference.
             // This can be multiple values if the element type is a tuple.
             ast::Type::MutableReference(element) => {
@@ -227,11 +253,15 @@ impl<'a> FunctionContext<'a> {
     /// Converts the form of the binary instruction as necessary
     /// (e.g. swapping arguments, inserting a not) to represent it in the IR.
     /// For example, (a <= b) is represented as !(b < a)
+    ///
+    /// The overflow mode is only applied to integer addition, subtraction and multiplication,
+    /// which the monomorphizer ensures by passing `Wrapping` for every other operation.
     pub(super) fn insert_binary(
         &mut self,
         mut lhs: ValueId,
         operator: noirc_frontend::BinaryOpKind,
         mut rhs: ValueId,
+        overflow: OverflowMode,
     ) -> Values {
         let op = convert_operator(operator);
 
@@ -262,12 +292,183 @@ impl<'a> FunctionContext<'a> {
             result = self.builder.insert_truncate(result, bit_size, max_bit_size);
         }
 
+        if overflow != OverflowMode::Wrapping {
+            match self.builder.type_of_value(lhs) {
+                Type::Numeric(NumericType::Unsigned { bit_size }) => {
+                    result = self.handle_overflow(lhs, op, rhs, result, bit_size, overflow);
+                }
+                Type::Numeric(NumericType::Signed { bit_size }) => {
+                    result = self.handle_signed_overflow(lhs, op, rhs, result, bit_size, overflow);
+                }
+                _ => (),
+            }
+        }
+
         if operator_requires_not(operator) {
             result = self.builder.insert_not(result);
         }
         result.into()
     }
 
+    /// Applies a checked or saturating overflow mode to the wrapped result of an unsigned
+    /// addition, subtraction or multiplication. Brillig integer arithmetic wraps implicitly, so
+    /// overflow is detected by comparing the wrapped result against the operands, which works
+    /// the same way in ACIR and Brillig:
+    ///
+    ///   add: result < lhs
+    ///   sub: lhs < rhs
+    ///   mul: lhs != 0 && result / lhs != rhs
+    ///
+    /// These comparisons only detect the overflow of unsigned integers, signed integers are
+    /// handled by [`Self::handle_signed_overflow`].
+    fn handle_overflow(
+        &mut self,
+        lhs: ValueId,
+        op: BinaryOp,
+        rhs: ValueId,
+        result: ValueId,
+        bit_size: u32,
+        overflow: OverflowMode,
+    ) -> ValueId {
+        let typ = self.builder.type_of_value(lhs);
+
+        let overflowed = match op {
+            BinaryOp::Add => self.builder.insert_binary(result, BinaryOp::Lt, lhs),
+            BinaryOp::Sub => self.builder.insert_binary(lhs, BinaryOp::Lt, rhs),
+            BinaryOp::Mul => {
+                // Dividing by one instead of zero keeps the division defined when lhs is zero
+                let zero = self.builder.numeric_constant(0u128, typ.clone());
+                let lhs_is_zero = self.builder.insert_binary(lhs, BinaryOp::Eq, zero);
+                let lhs_is_zero_int = self.builder.insert_cast(lhs_is_zero, typ.clone());
+                let divisor = self.builder.insert_binary(lhs, BinaryOp::Add, lhs_is_zero_int);
+                let quotient = self.builder.insert_binary(result, BinaryOp::Div, divisor);
+                let fits = self.builder.insert_binary(quotient, BinaryOp::Eq, rhs);
+                let fits = self.builder.insert_binary(fits, BinaryOp::Or, lhs_is_zero);
+                self.builder.insert_not(fits)
+            }
+            _ => unreachable!("ICE: Overflow mode applied to {op:?}"),
+        };
+
+        match overflow {
+            OverflowMode::Wrapping => result,
+            OverflowMode::Checked => {
+                let no_overflow = self.builder.insert_not(overflowed);
+                self.builder.insert_constrain(no_overflow);
+                result
+            }
+            // Subtraction saturates to zero, addition and multiplication to the maximum value
+            OverflowMode::Saturating if op == BinaryOp::Sub => {
+                let no_overflow = self.builder.insert_not(overflowed);
+                let no_overflow = self.builder.insert_cast(no_overflow, typ);
+                self.builder.insert_binary(result, BinaryOp::Mul, no_overflow)
+            }
+            OverflowMode::Saturating => {
+                let max = power_of_two(bit_size) - FieldElement::one();
+                let max = self.builder.numeric_constant(max, typ.clone());
+                let overflowed = self.builder.insert_cast(overflowed, typ);
+                let headroom = self.builder.insert_binary(max, BinaryOp::Sub, result);
+                let correction = self.builder.insert_binary(overflowed, BinaryOp::Mul, headroom);
+                self.builder.insert_binary(result, BinaryOp::Add, correction)
+            }
+        }
+    }
+
+    /// Applies a checked or saturating overflow mode to the wrapped result of a signed
+    /// addition, subtraction or multiplication. Overflow is detected from the signs of the
+    /// operands and of the wrapped result, which keeps every value within the bit size of the
+    /// operands:
+    ///
+    ///   add: lhs and rhs have the same sign, and the result has the other sign
+    ///   sub: lhs and rhs have different signs, and the result has the sign of rhs
+    ///   mul: lhs != 0 && result / lhs != rhs, or lhs == -1 && rhs == MIN
+    ///
+    /// The last case of multiplication is needed as dividing `MIN` by -1 overflows back to
+    /// `MIN`. Results saturate to `MIN` when the exact result is negative, and to `MAX` otherwise.
+    fn handle_signed_overflow(
+        &mut self,
+        lhs: ValueId,
+        op: BinaryOp,
+        rhs: ValueId,
+        result: ValueId,
+        bit_size: u32,
+        overflow: OverflowMode,
+    ) -> ValueId {
+        let typ = self.builder.type_of_value(lhs);
+        let lhs_is_negative = self.is_negative(lhs, bit_size);
+        let rhs_is_negative = self.is_negative(rhs, bit_size);
+        let same_signs = self.builder.insert_binary(lhs_is_negative, BinaryOp::Eq, rhs_is_negative);
+
+        // The sign of the exact result of an overflowing addition or subtraction is that of lhs
+        let (overflowed, is_negative) = match op {
+            BinaryOp::Add | BinaryOp::Sub => {
+                let result_is_negative = self.is_negative(result, bit_size);
+                let kept_sign =
+                    self.builder.insert_binary(result_is_negative, BinaryOp::Eq, lhs_is_negative);
+                let changed_sign = self.builder.insert_not(kept_sign);
+                let may_overflow = if op == BinaryOp::Add {
+                    same_signs
+                } else {
+                    self.builder.insert_not(same_signs)
+                };
+                let overflowed =
+                    self.builder.insert_binary(may_overflow, BinaryOp::And, changed_sign);
+                (overflowed, lhs_is_negative)
+            }
+            BinaryOp::Mul => {
+                // Dividing by one instead of zero keeps the division defined when lhs is zero
+                let zero = self.builder.numeric_constant(0u128, typ.clone());
+                let lhs_is_zero = self.builder.insert_binary(lhs, BinaryOp::Eq, zero);
+                let lhs_is_zero_int = self.builder.insert_cast(lhs_is_zero, typ.clone());
+                let divisor = self.builder.insert_binary(lhs, BinaryOp::Add, lhs_is_zero_int);
+                let quotient = self.builder.insert_binary(result, BinaryOp::Div, divisor);
+                let fits = self.builder.insert_binary(quotient, BinaryOp::Eq, rhs);
+                let fits = self.builder.insert_binary(fits, BinaryOp::Or, lhs_is_zero);
+                let overflowed = self.builder.insert_not(fits);
+
+                let minus_one = power_of_two(bit_size) - FieldElement::one();
+                let minus_one = self.builder.numeric_constant(minus_one, typ.clone());
+                let min = self.builder.numeric_constant(power_of_two(bit_size - 1), typ.clone());
+                let lhs_is_minus_one = self.builder.insert_binary(lhs, BinaryOp::Eq, minus_one);
+                let rhs_is_min = self.builder.insert_binary(rhs, BinaryOp::Eq, min);
+                let negates_min =
+                    self.builder.insert_binary(lhs_is_minus_one, BinaryOp::And, rhs_is_min);
+                let overflowed = self.builder.insert_binary(overflowed, BinaryOp::Or, negates_min);
+                (overflowed, self.builder.insert_not(same_signs))
+            }
+            _ => unreachable!("ICE: Overflow mode applied to {op:?}"),
+        };
+
+        match overflow {
+            OverflowMode::Wrapping => result,
+            OverflowMode::Checked => {
+                let no_overflow = self.builder.insert_not(overflowed);
+                self.builder.insert_constrain(no_overflow);
+                result
+            }
+            OverflowMode::Saturating => {
+                // `MIN` is represented by 2^(bit_size - 1), one more than `MAX`
+                let max = power_of_two(bit_size - 1) - FieldElement::one();
+                let max = self.builder.numeric_constant(max, typ.clone());
+                let is_negative = self.builder.insert_cast(is_negative, typ.clone());
+                let bound = self.builder.insert_binary(max, BinaryOp::Add, is_negative);
+                let overflowed = self.builder.insert_cast(overflowed, typ);
+                let headroom = self.builder.insert_binary(bound, BinaryOp::Sub, result);
+                let correction = self.builder.insert_binary(overflowed, BinaryOp::Mul, headroom);
+                self.builder.insert_binary(result, BinaryOp::Add, correction)
+            }
+        }
+    }
+
+    /// Returns whether a signed integer is negative, which is when the highest of its bits is
+    /// set. The bits are compared as an unsigned integer of the same bit size.
+    fn is_negative(&mut self, value: ValueId, bit_size: u32) -> ValueId {
+        let unsigned_type = Type::unsigned(bit_size);
+        let bits = self.builder.insert_cast(value, unsigned_type.clone());
+        let sign_bit = self.builder.numeric_constant(power_of_two(bit_size - 1), unsigned_type);
+        let is_positive = self.builder.insert_binary(bits, BinaryOp::Lt, sign_bit);
+        self.builder.insert_not(is_positive)
+    }
+
     /// The frontend claims to support equality (==) on arrays, so we must support it in SSA here.
     /// The actual BinaryOp::Eq in SSA is meant only for primitive numeric types so we encode an
     /// entire equality loop on each array element. The generated IR is as follows:
@@ -508,16 +709,33 @@ impl<'a> FunctionContext<'a> {
     /// Compile the given `array[index]` expression as a reference.
     /// This will return a triple of (array, index, lvalue_ref) where the lvalue_ref records the
     /// structure of the lvalue expression for use by `assign_new_value`.
+    ///
+    /// If the given lvalue is a slice, the returned array is the contents of the slice.
     fn index_lvalue(
         &mut self,
         array: &ast::LValue,
         index: &ast::Expression,
     ) -> (ValueId, ValueId, LValue) {
         let (old_array, array_lvalue) = self.extract_current_value_recursive(array);
-        let old_array = old_array.into_leaf().eval(self);
+        let old_array = old_array.into_value_list(self);
         let array_lvalue = Box::new(array_lvalue);
         let index = self.codegen_non_tuple_expression(index);
-        (old_array, index, LValue::Index { old_array, index, array_lvalue })
+
+        // Slices are represented as a (length, contents) pair
+        match old_array.as_slice() {
+            [old_array] => {
+                let old_array = *old_array;
+                (old_array, index, LValue::Index { old_array, index, array_lvalue })
+            }
+            [length, old_contents] => {
+                let (length, old_contents) = (*length, *old_contents);
+                self.codegen_slice_access_check(index, length);
+                let slice_lvalue = array_lvalue;
+                let lvalue = LValue::SliceIndex { length, old_contents, index, slice_lvalue };
+                (old_contents, index, lvalue)
+            }
+            _ => unreachable!("Expected an array or slice to index, found {old_array:?}"),
+        }
     }
 
     fn extract_current_value_recursive(&mut self, lvalue: &ast::LValue) -> (Values, LValue) {
@@ -558,6 +776,12 @@ impl<'a> FunctionContext<'a> {
                 let new_array = self.builder.insert_array_set(old_array, index, rvalue);
                 self.assign_new_value(*array_lvalue, new_array.into());
             }
+            LValue::SliceIndex { length, old_contents, index, slice_lvalue } => {
+                let rvalue = new_value.into_leaf().eval(self);
+                let new_contents = self.builder.insert_array_set(old_contents, index, rvalue);
+                let new_slice = Tree::Branch(vec![length.into(), new_contents.into()]);
+                self.assign_new_value(*slice_lvalue, new_slice);
+            }
             LValue::MemberAccess { old_object, index, object_lvalue } => {
                 let new_object = Self::replace_field(old_object, index, new_value);
                 self.assign_new_value(*object_lvalue, new_object);
@@ -669,6 +893,11 @@ fn operator_result_max_bit_size_to_truncate(
 /// Take care when using this to insert a binary instruction: this requires
 /// checking operator_requires_not and operator_requires_swapped_operands
 /// to represent the full operation correctly.
+/// Returns 2^{bits} as a `FieldElement`.
+fn power_of_two(bits: u32) -> FieldElement {
+    FieldElement::from(2_i128).pow(&FieldElement::from(bits as i128))
+}
+
 fn convert_operator(op: noirc_frontend::BinaryOpKind) -> BinaryOp {
     use noirc_frontend::BinaryOpKind;
     match op {
@@ -736,6 +965,7 @@ impl SharedContext {
 pub(super) enum LValue {
     Ident(Values),
     Index { old_array: ValueId, index: ValueId, array_lvalue: Box<LValue> },
+    SliceIndex { length: ValueId, old_contents: ValueId, index: ValueId, slice_lvalue: Box<LValue> },
     MemberAccess { old_object: Values, index: usize, object_lvalue: Box<LValue> },
     Dereference { reference: Values },
 }
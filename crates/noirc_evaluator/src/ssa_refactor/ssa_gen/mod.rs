@@ -10,18 +10,18 @@ use context::SharedContext;
 use iter_extended::vecmap;
 use noirc_errors::Location;
 use noirc_frontend::monomorphization::ast::{self, Expression, Program};
+use noirc_frontend::OverflowMode;
 
 use self::{
     context::FunctionContext,
     value::{Tree, Values},
 };
 
-use super::ir::{
-    function::RuntimeType,
-    instruction::BinaryOp,
-    types::{CompositeType, Type},
-    value::ValueId,
-};
+use super::ir::{function::RuntimeType, instruction::BinaryOp, types::Type, value::ValueId};
+
+/// The bit size that the lengths of slices, and the indices into them,
+/// are cast to when checking that a slice access is within bounds.
+const SLICE_LENGTH_BIT_SIZE: u32 = 64;
 
 /// Generates SSA for the given monomorphized program.
 ///
@@ -75,6 +75,7 @@ impl<'a> FunctionContext<'a> {
             Expression::Index(index) => self.codegen_index(index),
             Expression::Cast(cast) => self.codegen_cast(cast),
             Expression::For(for_expr) => self.codegen_for(for_expr),
+            Expression::While(while_expr) => self.codegen_while(while_expr),
             Expression::If(if_expr) => self.codegen_if(if_expr),
             Expression::Tuple(tuple) => self.codegen_tuple(tuple),
             Expression::ExtractTupleField(tuple, index) => {
@@ -82,8 +83,8 @@ impl<'a> FunctionContext<'a> {
             }
             Expression::Call(call) => self.codegen_call(call),
             Expression::Let(let_expr) => self.codegen_let(let_expr),
-            Expression::Constrain(constrain, location) => {
-                self.codegen_constrain(constrain, *location)
+            Expression::Constrain(constrain, location, message) => {
+                self.codegen_constrain(constrain, *location, message.as_deref())
             }
             Expression::Assign(assign) => self.codegen_assign(assign),
             Expression::Semi(semi) => self.codegen_semi(semi),
@@ -116,7 +117,8 @@ impl<'a> FunctionContext<'a> {
             ast::Literal::Array(array) => {
                 let elements = vecmap(&array.contents, |element| self.codegen_expression(element));
                 let element_types = Self::convert_type(&array.element_type).flatten();
-                self.codegen_array(elements, element_types)
+                let typ = Type::Array(Rc::new(element_types), elements.len());
+                self.codegen_array(elements, typ)
             }
             ast::Literal::Integer(value, typ) => {
                 let typ = Self::convert_non_tuple_type(typ);
@@ -129,7 +131,8 @@ impl<'a> FunctionContext<'a> {
                 let elements = vecmap(string.as_bytes(), |byte| {
                     self.builder.numeric_constant(*byte as u128, Type::field()).into()
                 });
-                self.codegen_array(elements, vec![Type::char()])
+                let typ = Type::Array(Rc::new(vec![Type::char()]), elements.len());
+                self.codegen_array(elements, typ)
             }
         }
     }
@@ -143,7 +146,7 @@ impl<'a> FunctionContext<'a> {
     /// stored the same as the array [1, 2, 3, 4].
     ///
     /// The value returned from this function is always that of the allocate instruction.
-    fn codegen_array(&mut self, elements: Vec<Values>, element_types: CompositeType) -> Values {
+    fn codegen_array(&mut self, elements: Vec<Values>, typ: Type) -> Values {
         let mut array = im::Vector::new();
 
         for element in elements {
@@ -153,7 +156,7 @@ impl<'a> FunctionContext<'a> {
             });
         }
 
-        self.builder.array_constant(array, Rc::new(element_types)).into()
+        self.builder.array_constant(array, typ).into()
     }
 
     fn codegen_block(&mut self, block: &[Expression]) -> Values {
@@ -175,7 +178,11 @@ impl<'a> FunctionContext<'a> {
                 let rhs = rhs.into_leaf().eval(self);
                 let typ = self.builder.type_of_value(rhs);
                 let zero = self.builder.numeric_constant(0u128, typ);
-                self.builder.insert_binary(zero, BinaryOp::Sub, rhs).into()
+                // Negation goes through the context so integer results are truncated,
+                // keeping signed values in their two's complement representation.
+                // Negating an unsigned integer always wraps, whatever the crate's overflow mode.
+                let operator = noirc_frontend::BinaryOpKind::Subtract;
+                self.insert_binary(zero, operator, rhs, OverflowMode::Wrapping)
             }
             noirc_frontend::UnaryOp::MutableReference => {
                 rhs.map(|rhs| {
@@ -198,15 +205,37 @@ impl<'a> FunctionContext<'a> {
     fn codegen_binary(&mut self, binary: &ast::Binary) -> Values {
         let lhs = self.codegen_non_tuple_expression(&binary.lhs);
         let rhs = self.codegen_non_tuple_expression(&binary.rhs);
-        self.insert_binary(lhs, binary.operator, rhs)
+        self.builder.set_location(binary.location);
+        self.insert_binary(lhs, binary.operator, rhs, binary.overflow)
     }
 
     fn codegen_index(&mut self, index: &ast::Index) -> Values {
-        let array = self.codegen_non_tuple_expression(&index.collection);
+        let collection = self.codegen_expression(&index.collection).into_value_list(self);
         let index_value = self.codegen_non_tuple_expression(&index.index);
+        self.builder.set_location(index.location);
+
+        // Slices are represented as a (length, contents) pair
+        let array = match collection.as_slice() {
+            [array] => *array,
+            [length, contents] => {
+                self.codegen_slice_access_check(index_value, *length);
+                *contents
+            }
+            _ => unreachable!("Expected an array or slice to index, found {collection:?}"),
+        };
         self.codegen_array_index(array, index_value, &index.element_type)
     }
 
+    /// Constrains `index` to be less than the length of a slice. Unlike arrays, the contents
+    /// of a slice may hold more elements than its length so an index past its end is not
+    /// always caught when accessing the contents.
+    fn codegen_slice_access_check(&mut self, index: ValueId, length: ValueId) {
+        let index = self.builder.insert_cast(index, Type::unsigned(SLICE_LENGTH_BIT_SIZE));
+        let length = self.builder.insert_cast(length, Type::unsigned(SLICE_LENGTH_BIT_SIZE));
+        let is_in_bounds = self.builder.insert_binary(index, BinaryOp::Lt, length);
+        self.builder.insert_constrain(is_in_bounds);
+    }
+
     /// This is broken off from codegen_index so that it can also be
     /// used to codegen a LValue::Index.
     ///
@@ -235,9 +264,35 @@ impl<'a> FunctionContext<'a> {
     fn codegen_cast(&mut self, cast: &ast::Cast) -> Values {
         let lhs = self.codegen_non_tuple_expression(&cast.lhs);
         let typ = Self::convert_non_tuple_type(&cast.r#type);
+
+        // Casts to slices are inserted by the type checker wherever an array is used as a slice
+        if let Type::Slice(_) = typ {
+            return self.codegen_array_to_slice(lhs, typ);
+        }
         self.builder.insert_cast(lhs, typ).into()
     }
 
+    /// Converts an array into a slice of the same elements, represented as a (length, contents)
+    /// pair. The contents are a new slice value holding each element of the array.
+    fn codegen_array_to_slice(&mut self, array: ValueId, slice_type: Type) -> Values {
+        let (element_types, length) = match self.builder.type_of_value(array) {
+            Type::Array(element_types, length) => (element_types, length),
+            other => unreachable!("Expected an array to convert into a slice, found {other}"),
+        };
+
+        let contents = (0..length * element_types.len())
+            .map(|i| {
+                let index = self.builder.field_constant(i as u128);
+                let element_type = element_types[i % element_types.len()].clone();
+                self.builder.insert_array_get(array, index, element_type)
+            })
+            .collect();
+
+        let contents = self.builder.array_constant(contents, slice_type);
+        let length = self.builder.field_constant(length as u128);
+        Tree::Branch(vec![length.into(), contents.into()])
+    }
+
     /// Codegens a for loop, creating three new blocks in the process.
     /// The return value of a for loop is always a unit literal.
     ///
@@ -271,6 +326,7 @@ impl<'a> FunctionContext<'a> {
 
         // Compile the loop entry block
         self.builder.switch_to_block(loop_entry);
+        self.builder.set_location(for_expr.range_location);
         let jump_condition = self.builder.insert_binary(loop_index, BinaryOp::Lt, end_index);
         self.builder.terminate_with_jmpif(jump_condition, loop_body, loop_end);
 
@@ -286,6 +342,41 @@ impl<'a> FunctionContext<'a> {
         Self::unit_value()
     }
 
+    /// Codegens a while loop, which is only allowed in unconstrained functions since it
+    /// cannot be unrolled. The loop `while cond { body }` is codegen'd as:
+    ///
+    ///   br loop_entry()
+    /// loop_entry():
+    ///   v0 = ... codegen cond ...
+    ///   brif v0, then: loop_body, else: loop_end
+    /// loop_body():
+    ///   v1 = ... codegen body ...
+    ///   br loop_entry()
+    /// loop_end():
+    ///   ... This is the current insert point after codegen_while finishes ...
+    fn codegen_while(&mut self, while_expr: &ast::While) -> Values {
+        let loop_entry = self.builder.insert_block();
+        let loop_body = self.builder.insert_block();
+        let loop_end = self.builder.insert_block();
+
+        self.builder.terminate_with_jmp(loop_entry, vec![]);
+
+        // Compile the loop entry block
+        self.builder.switch_to_block(loop_entry);
+        self.builder.set_location(while_expr.location);
+        let condition = self.codegen_non_tuple_expression(&while_expr.condition);
+        self.builder.terminate_with_jmpif(condition, loop_body, loop_end);
+
+        // Compile the loop body
+        self.builder.switch_to_block(loop_body);
+        self.codegen_expression(&while_expr.block);
+        self.builder.terminate_with_jmp(loop_entry, vec![]);
+
+        // Finish by switching back to the end of the loop
+        self.builder.switch_to_block(loop_end);
+        Self::unit_value()
+    }
+
     /// Codegens an if expression, handling the case of what to do if there is no 'else'.
     ///
     /// For example, the expression `if cond { a } else { b }` is codegen'd as:
@@ -369,6 +460,7 @@ impl<'a> FunctionContext<'a> {
             .flat_map(|argument| self.codegen_expression(argument).into_value_list(self))
             .collect();
 
+        self.builder.set_location(call.location);
         self.insert_call(function, arguments, &call.return_type)
     }
 
@@ -386,13 +478,36 @@ impl<'a> FunctionContext<'a> {
             });
         }
 
+        self.name_values(&values, &let_expr.name);
         self.define(let_expr.id, values);
         Self::unit_value()
     }
 
-    fn codegen_constrain(&mut self, expr: &Expression, _location: Location) -> Values {
+    /// If the constraint has a message, it is reported in a separate block which is only
+    /// entered when the constraint is about to fail.
+    fn codegen_constrain(
+        &mut self,
+        expr: &Expression,
+        location: Location,
+        message: Option<&Expression>,
+    ) -> Values {
         let boolean = self.codegen_non_tuple_expression(expr);
-        self.builder.insert_constrain(boolean);
+
+        if let Some(message) = message {
+            let failed = self.builder.insert_not(boolean);
+            let message_block = self.builder.insert_block();
+            let end_block = self.builder.insert_block();
+
+            self.builder.terminate_with_jmpif(failed, message_block, end_block);
+
+            self.builder.switch_to_block(message_block);
+            self.codegen_expression(message);
+            self.builder.terminate_with_jmp(end_block, vec![]);
+
+            self.builder.switch_to_block(end_block);
+        }
+
+        self.builder.set_location(location).insert_constrain(boolean);
         Self::unit_value()
     }
 
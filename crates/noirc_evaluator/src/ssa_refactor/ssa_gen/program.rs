@@ -5,6 +5,8 @@ use iter_extended::btree_map;
 use crate::ssa_refactor::ir::{
     function::{Function, FunctionId},
     map::AtomicCounter,
+    printer::function_to_dot,
+    verifier::verify_function,
 };
 
 /// Contains the entire SSA representation of the program.
@@ -49,6 +51,28 @@ impl Ssa {
         self.functions.insert(new_id, function);
         new_id
     }
+
+    /// Renders the control flow graph of each function in Graphviz dot format,
+    /// keyed by the name and id of the function as names may be shared.
+    pub(crate) fn control_flow_graphs(&self) -> BTreeMap<String, String> {
+        btree_map(self.functions.values(), |function| {
+            (format!("{}_{}", function.name(), function.id()), function_to_dot(function))
+        })
+    }
+
+    /// Checks that every function is well-formed after the pass `pass`,
+    /// panicking with the offending function otherwise.
+    pub(crate) fn verify(&self, pass: &str) {
+        for function in self.functions.values() {
+            if let Err(error) = verify_function(function) {
+                panic!(
+                    "ICE: invalid SSA in function {} {} after the {pass} pass: {error}\n{function}",
+                    function.name(),
+                    function.id()
+                );
+            }
+        }
+    }
 }
 
 impl Display for Ssa {
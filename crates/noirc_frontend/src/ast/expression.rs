@@ -1,7 +1,9 @@
 use std::fmt::Display;
 
 use crate::token::{Attribute, Token};
-use crate::{Ident, Path, Pattern, Recoverable, Statement, UnresolvedType};
+use crate::{
+    Ident, ItemVisibility, Path, Pattern, Recoverable, Statement, TraitConstraint, UnresolvedType,
+};
 use acvm::FieldElement;
 use iter_extended::vecmap;
 use noirc_errors::{Span, Spanned};
@@ -19,7 +21,9 @@ pub enum ExpressionKind {
     Cast(Box<CastExpression>),
     Infix(Box<InfixExpression>),
     For(Box<ForExpression>),
+    While(Box<WhileExpression>),
     If(Box<IfExpression>),
+    Match(Box<MatchExpression>),
     Variable(Path),
     Tuple(Vec<Expression>),
     Lambda(Box<Lambda>),
@@ -72,6 +76,10 @@ impl ExpressionKind {
         ExpressionKind::Literal(Literal::Str(contents))
     }
 
+    pub fn format_string(contents: String) -> ExpressionKind {
+        ExpressionKind::Literal(Literal::FmtStr(contents))
+    }
+
     pub fn constructor((type_name, fields): (Path, Vec<(Ident, Expression)>)) -> ExpressionKind {
         ExpressionKind::Constructor(Box::new(ConstructorExpression { type_name, fields }))
     }
@@ -172,6 +180,14 @@ pub struct ForExpression {
     pub block: Expression,
 }
 
+/// A `while` loop, which is only allowed within unconstrained functions.
+/// `loop { .. }` is parsed as `while true { .. }`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WhileExpression {
+    pub condition: Expression,
+    pub block: Expression,
+}
+
 pub type BinaryOp = Spanned<BinaryOpKind>;
 
 #[derive(PartialEq, PartialOrd, Eq, Ord, Hash, Debug, Copy, Clone)]
@@ -257,6 +273,53 @@ impl BinaryOpKind {
     }
 }
 
+/// How the addition, subtraction and multiplication of integers behave when their result does
+/// not fit in the integer type of their operands. Each crate chooses its mode with the
+/// `overflow` key of its Nargo.toml, while the `std::overflow` functions choose their own.
+/// Negating an integer always wraps.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowMode {
+    /// The result is constrained to fit in the type, failing the program otherwise
+    Checked,
+    /// The result wraps around, keeping only as many of its lowest bits as the type has
+    #[default]
+    Wrapping,
+    /// The result is clamped to the smallest or largest value of the type
+    Saturating,
+}
+
+impl OverflowMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            OverflowMode::Checked => "checked",
+            OverflowMode::Wrapping => "wrapping",
+            OverflowMode::Saturating => "saturating",
+        }
+    }
+}
+
+impl Display for OverflowMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for OverflowMode {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "checked" => Ok(OverflowMode::Checked),
+            "wrapping" => Ok(OverflowMode::Wrapping),
+            "saturating" => Ok(OverflowMode::Saturating),
+            _ => Err(format!(
+                "unknown overflow mode `{name}`, expected `checked`, `wrapping` or `saturating`"
+            )),
+        }
+    }
+}
+
 #[derive(PartialEq, PartialOrd, Eq, Ord, Hash, Debug, Copy, Clone)]
 pub enum UnaryOp {
     Minus,
@@ -282,6 +345,7 @@ pub enum Literal {
     Bool(bool),
     Integer(FieldElement),
     Str(String),
+    FmtStr(String),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -311,6 +375,24 @@ pub struct IfExpression {
     pub alternative: Option<Expression>,
 }
 
+/// `match expression { pattern => rule, ... }`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MatchExpression {
+    pub expression: Expression,
+    pub rules: Vec<(MatchPattern, Expression)>,
+}
+
+/// The pattern of a rule within a match expression
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum MatchPattern {
+    /// `Enum::Variant(a, b)`, binding each field of the variant to a pattern.
+    /// The parenthesized list of patterns is omitted for variants holding no data.
+    Variant(Path, Vec<Pattern>, Span),
+
+    /// `_`, matching any value
+    Wildcard(Span),
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Lambda {
     pub parameters: Vec<(Pattern, UnresolvedType)>,
@@ -322,9 +404,19 @@ pub struct Lambda {
 pub struct FunctionDefinition {
     pub name: Ident,
 
+    /// Whether this function is visible outside of the module declaring it
+    pub visibility: ItemVisibility,
+
+    /// The lines of the `///` doc comments written above this function
+    pub doc_comments: Vec<String>,
+
     // XXX: Currently we only have one attribute defined. If more attributes are needed per function, we can make this a vector and make attribute definition more expressive
     pub attribute: Option<Attribute>,
 
+    /// True if this function is marked `#[allow(unused)]`, suppressing the warnings issued if
+    /// it is never called or if any variable within it is never used
+    pub allow_unused: bool,
+
     /// True if this function was defined with the 'open' keyword
     pub is_open: bool,
 
@@ -332,9 +424,16 @@ pub struct FunctionDefinition {
     pub is_unconstrained: bool,
 
     pub generics: UnresolvedGenerics,
+
+    /// The traits which the generics of this function are required to implement
+    pub trait_constraints: Vec<TraitConstraint>,
+
     pub parameters: Vec<(Pattern, UnresolvedType, noirc_abi::AbiVisibility)>,
     pub body: BlockExpression,
     pub span: Span,
+    /// The span of the whole function, from its attributes to the end of its body, whereas
+    /// `span` only covers its name
+    pub full_span: Span,
     pub return_type: UnresolvedType,
     pub return_visibility: noirc_abi::AbiVisibility,
     pub return_distinctness: noirc_abi::AbiDistinctness,
@@ -426,7 +525,9 @@ impl Display for ExpressionKind {
             Cast(cast) => cast.fmt(f),
             Infix(infix) => infix.fmt(f),
             For(for_loop) => for_loop.fmt(f),
+            While(while_loop) => while_loop.fmt(f),
             If(if_expr) => if_expr.fmt(f),
+            Match(match_expr) => match_expr.fmt(f),
             Variable(path) => path.fmt(f),
             Constructor(constructor) => constructor.fmt(f),
             MemberAccess(access) => access.fmt(f),
@@ -453,6 +554,7 @@ impl Display for Literal {
             Literal::Bool(boolean) => write!(f, "{}", if *boolean { "true" } else { "false" }),
             Literal::Integer(integer) => write!(f, "{}", integer.to_u128()),
             Literal::Str(string) => write!(f, "\"{string}\""),
+            Literal::FmtStr(string) => write!(f, "f\"{string}\""),
         }
     }
 }
@@ -567,6 +669,12 @@ impl Display for ForExpression {
     }
 }
 
+impl Display for WhileExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "while {} {}", self.condition, self.block)
+    }
+}
+
 impl Display for IfExpression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "if {} {}", self.condition, self.consequence)?;
@@ -577,6 +685,29 @@ impl Display for IfExpression {
     }
 }
 
+impl Display for MatchExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "match {} {{", self.expression)?;
+        for (pattern, rule) in &self.rules {
+            writeln!(f, "    {pattern} => {rule},")?;
+        }
+        write!(f, "}}")
+    }
+}
+
+impl Display for MatchPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchPattern::Variant(path, fields, _) if fields.is_empty() => path.fmt(f),
+            MatchPattern::Variant(path, fields, _) => {
+                let fields = vecmap(fields, ToString::to_string);
+                write!(f, "{path}({})", fields.join(", "))
+            }
+            MatchPattern::Wildcard(_) => write!(f, "_"),
+        }
+    }
+}
+
 impl Display for Lambda {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let parameters = vecmap(&self.parameters, |(name, r#type)| format!("{name}: {type}"));
@@ -587,6 +718,9 @@ impl Display for Lambda {
 
 impl Display for FunctionDefinition {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.allow_unused {
+            writeln!(f, "{}", Attribute::AllowUnused)?;
+        }
         if let Some(attribute) = &self.attribute {
             writeln!(f, "{attribute}")?;
         }
@@ -597,7 +731,8 @@ impl Display for FunctionDefinition {
 
         write!(
             f,
-            "fn {}({}) -> {} {}",
+            "{}fn {}({}) -> {} {}",
+            self.visibility,
             self.name,
             parameters.join(", "),
             self.return_type,
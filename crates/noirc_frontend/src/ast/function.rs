@@ -80,7 +80,12 @@ impl From<FunctionDefinition> for NoirFunction {
         let kind = match fd.attribute {
             Some(Attribute::Builtin(_)) => FunctionKind::Builtin,
             Some(Attribute::Foreign(_)) => FunctionKind::LowLevel,
-            Some(Attribute::Test) => FunctionKind::Normal,
+            Some(Attribute::Test)
+            | Some(Attribute::Inline(_))
+            | Some(Attribute::Derive(_))
+            | Some(Attribute::Deprecated(_))
+            | Some(Attribute::Cfg(_))
+            | Some(Attribute::AllowUnused) => FunctionKind::Normal,
             Some(Attribute::Oracle(_)) => FunctionKind::Oracle,
             None => FunctionKind::Normal,
         };
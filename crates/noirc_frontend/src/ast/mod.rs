@@ -8,6 +8,7 @@ mod expression;
 mod function;
 mod statement;
 mod structure;
+mod traits;
 
 pub use expression::*;
 pub use function::*;
@@ -15,6 +16,7 @@ pub use function::*;
 use noirc_errors::Span;
 pub use statement::*;
 pub use structure::*;
+pub use traits::*;
 
 use crate::{
     parser::{ParserError, ParserErrorReason},
@@ -143,6 +145,40 @@ pub enum Signedness {
     Signed,
 }
 
+/// The visibility of an item declared in a module. Items are private to the module declaring
+/// them (and its submodules) unless declared `pub`, or `pub(crate)` to be visible anywhere in
+/// the crate but not to dependents.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum ItemVisibility {
+    Public,
+    PublicCrate,
+    Private,
+}
+
+impl std::fmt::Display for ItemVisibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ItemVisibility::Public => write!(f, "pub "),
+            ItemVisibility::PublicCrate => write!(f, "pub(crate) "),
+            ItemVisibility::Private => Ok(()),
+        }
+    }
+}
+
+/// An item along with the `///` doc comments written above it, for items which are represented
+/// by a type also used outside of item declarations.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Documented<T> {
+    pub item: T,
+    pub doc_comments: Vec<String>,
+}
+
+impl<T> Documented<T> {
+    pub fn new(item: T, doc_comments: Vec<String>) -> Documented<T> {
+        Documented { item, doc_comments }
+    }
+}
+
 impl UnresolvedTypeExpression {
     // This large error size is justified because it improves parsing speeds by around 40% in
     // release mode. See `ParserError` definition for further explanation.
@@ -2,8 +2,11 @@ use std::fmt::Display;
 
 use crate::lexer::token::SpannedToken;
 use crate::parser::{ParserError, ParserErrorReason};
-use crate::token::Token;
-use crate::{Expression, ExpressionKind, IndexExpression, MemberAccessExpression, UnresolvedType};
+use crate::token::{Attribute, Token};
+use crate::{
+    Expression, ExpressionKind, IndexExpression, ItemVisibility, MemberAccessExpression,
+    UnresolvedType,
+};
 use iter_extended::vecmap;
 use noirc_errors::{Span, Spanned};
 
@@ -23,6 +26,8 @@ pub enum Statement {
     Constrain(ConstrainStatement),
     Expression(Expression),
     Assign(AssignStatement),
+    // An early `return expr;` from the enclosing function
+    Return(Expression),
     // This is an expression with a trailing semi-colon
     Semi(Expression),
     // This statement is the result of a recovered parse error.
@@ -66,12 +71,22 @@ impl Statement {
                 self
             }
 
+            // Like rust, the semicolon may be omitted when returning at the end of a block
+            Statement::Return(_) => {
+                if semi.is_none() && !last_statement_in_block {
+                    emit_error(missing_semicolon);
+                }
+                self
+            }
+
             Statement::Expression(expr) => {
                 match (&expr.kind, semi, last_statement_in_block) {
                     // Semicolons are optional for these expressions
                     (ExpressionKind::Block(_), semi, _)
                     | (ExpressionKind::For(_), semi, _)
-                    | (ExpressionKind::If(_), semi, _) => {
+                    | (ExpressionKind::While(_), semi, _)
+                    | (ExpressionKind::If(_), semi, _)
+                    | (ExpressionKind::Match(_), semi, _) => {
                         if semi.is_some() {
                             Statement::Semi(expr)
                         } else {
@@ -229,6 +244,12 @@ pub trait Recoverable {
 pub struct ImportStatement {
     pub path: Path,
     pub alias: Option<Ident>,
+    /// True if this import is marked `#[allow(unused)]`, suppressing the warning issued if
+    /// the imported item is never used
+    pub allow_unused: bool,
+    /// The visibility of the imported name when it is referenced from other modules.
+    /// Imports are private unless they are declared with `pub use`.
+    pub visibility: ItemVisibility,
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
@@ -284,7 +305,12 @@ impl UseTree {
 
         match self.kind {
             UseTreeKind::Path(name, alias) => {
-                vec![ImportStatement { path: prefix.join(name), alias }]
+                vec![ImportStatement {
+                    path: prefix.join(name),
+                    alias,
+                    allow_unused: false,
+                    visibility: ItemVisibility::Private,
+                }]
             }
             UseTreeKind::List(trees) => {
                 trees.into_iter().flat_map(|tree| tree.desugar(Some(prefix.clone()))).collect()
@@ -406,8 +432,25 @@ pub enum LValue {
     Dereference(Box<LValue>),
 }
 
+/// An assertion that the given expression is true, along with an optional message
+/// to display if it is not.
 #[derive(Debug, PartialEq, Eq, Clone)]
-pub struct ConstrainStatement(pub Expression);
+pub struct ConstrainStatement(pub Expression, pub Option<Expression>, pub ConstrainKind);
+
+impl Recoverable for ConstrainStatement {
+    fn error(span: Span) -> Self {
+        ConstrainStatement(Expression::error(span), None, ConstrainKind::Assert)
+    }
+}
+
+/// The statement a constraint was written as. `assert_eq(a, b)` is parsed as a constraint
+/// that `a == b`, but reports the values of both sides if they differ.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ConstrainKind {
+    Assert,
+    AssertEq,
+    Constrain,
+}
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Pattern {
@@ -424,6 +467,15 @@ impl Pattern {
             _ => panic!("only the identifier pattern can return a name"),
         }
     }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Pattern::Identifier(ident) => ident.span(),
+            Pattern::Mutable(_, span) | Pattern::Tuple(_, span) | Pattern::Struct(_, _, span) => {
+                *span
+            }
+        }
+    }
 }
 
 impl Recoverable for Pattern {
@@ -464,6 +516,7 @@ impl Display for Statement {
             Statement::Constrain(constrain) => constrain.fmt(f),
             Statement::Expression(expression) => expression.fmt(f),
             Statement::Assign(assign) => assign.fmt(f),
+            Statement::Return(expr) => write!(f, "return {expr}"),
             Statement::Semi(semi) => write!(f, "{semi};"),
             Statement::Error => write!(f, "Error"),
         }
@@ -478,7 +531,18 @@ impl Display for LetStatement {
 
 impl Display for ConstrainStatement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "constrain {}", self.0)
+        let ConstrainStatement(expr, message, kind) = self;
+        match (kind, &expr.kind) {
+            (ConstrainKind::Constrain, _) => return write!(f, "constrain {expr}"),
+            (ConstrainKind::AssertEq, ExpressionKind::Infix(infix)) => {
+                write!(f, "assert_eq({}, {}", infix.lhs, infix.rhs)?;
+            }
+            _ => write!(f, "assert({expr}")?,
+        }
+        match message {
+            Some(message) => write!(f, ", {message})"),
+            None => write!(f, ")"),
+        }
     }
 }
 
@@ -518,7 +582,10 @@ impl Display for PathKind {
 
 impl Display for ImportStatement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "use {}", self.path)?;
+        if self.allow_unused {
+            writeln!(f, "{}", Attribute::AllowUnused)?;
+        }
+        write!(f, "{}use {}", self.visibility, self.path)?;
         if let Some(alias) = &self.alias {
             write!(f, " as {alias}")?;
         }
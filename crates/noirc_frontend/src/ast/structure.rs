@@ -1,6 +1,8 @@
 use std::fmt::Display;
 
-use crate::{Ident, NoirFunction, UnresolvedGenerics, UnresolvedType};
+use crate::{
+    token::Attribute, Ident, ItemVisibility, NoirFunction, UnresolvedGenerics, UnresolvedType,
+};
 use iter_extended::vecmap;
 use noirc_errors::Span;
 
@@ -8,6 +10,13 @@ use noirc_errors::Span;
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct NoirStruct {
     pub name: Ident,
+    pub visibility: ItemVisibility,
+    /// The traits listed by the `#[derive(..)]` attribute of the struct, if it has one
+    pub derives: Vec<Ident>,
+    /// Set if the struct is marked `#[deprecated]`, to the message of the attribute if it has one
+    pub deprecated: Option<Option<String>>,
+    /// The lines of the `///` doc comments written above the struct
+    pub doc_comments: Vec<String>,
     pub generics: UnresolvedGenerics,
     pub fields: Vec<(Ident, UnresolvedType)>,
     pub span: Span,
@@ -16,14 +25,40 @@ pub struct NoirStruct {
 impl NoirStruct {
     pub fn new(
         name: Ident,
+        visibility: ItemVisibility,
+        derives: Vec<Ident>,
+        deprecated: Option<Option<String>>,
         generics: Vec<Ident>,
         fields: Vec<(Ident, UnresolvedType)>,
         span: Span,
     ) -> NoirStruct {
-        NoirStruct { name, generics, fields, span }
+        let doc_comments = Vec::new();
+        NoirStruct { name, visibility, derives, deprecated, doc_comments, generics, fields, span }
     }
 }
 
+/// Ast node for an enum. Each variant has a name and the types of the unnamed fields it holds,
+/// which are empty for variants holding no data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoirEnum {
+    pub name: Ident,
+    pub visibility: ItemVisibility,
+    pub generics: UnresolvedGenerics,
+    pub variants: Vec<(Ident, Vec<UnresolvedType>)>,
+    pub span: Span,
+}
+
+/// Ast node for a type alias such as `type Hash = [u8; 32];`. The aliased type may
+/// refer to the alias's own generics.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoirTypeAlias {
+    pub name: Ident,
+    pub visibility: ItemVisibility,
+    pub generics: UnresolvedGenerics,
+    pub typ: UnresolvedType,
+    pub span: Span,
+}
+
 /// Ast node for an impl
 #[derive(Clone, Debug)]
 pub struct NoirImpl {
@@ -38,7 +73,16 @@ impl Display for NoirStruct {
         let generics = vecmap(&self.generics, |generic| generic.to_string());
         let generics = if generics.is_empty() { "".into() } else { generics.join(", ") };
 
-        writeln!(f, "struct {}{} {{", self.name, generics)?;
+        if !self.derives.is_empty() {
+            let derives = vecmap(&self.derives, ToString::to_string);
+            writeln!(f, "#[derive({})]", derives.join(", "))?;
+        }
+
+        if let Some(note) = &self.deprecated {
+            writeln!(f, "{}", Attribute::Deprecated(note.clone()))?;
+        }
+
+        writeln!(f, "{}struct {}{} {{", self.visibility, self.name, generics)?;
 
         for (name, typ) in self.fields.iter() {
             writeln!(f, "    {name}: {typ},")?;
@@ -48,6 +92,36 @@ impl Display for NoirStruct {
     }
 }
 
+impl Display for NoirEnum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let generics = vecmap(&self.generics, |generic| generic.to_string());
+        let generics = if generics.is_empty() { "".into() } else { generics.join(", ") };
+
+        writeln!(f, "{}enum {}{} {{", self.visibility, self.name, generics)?;
+
+        for (name, fields) in self.variants.iter() {
+            if fields.is_empty() {
+                writeln!(f, "    {name},")?;
+            } else {
+                let fields = vecmap(fields, ToString::to_string);
+                writeln!(f, "    {name}({}),", fields.join(", "))?;
+            }
+        }
+
+        write!(f, "}}")
+    }
+}
+
+impl Display for NoirTypeAlias {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let generics = vecmap(&self.generics, |generic| generic.to_string());
+        let generics =
+            if generics.is_empty() { "".into() } else { format!("<{}>", generics.join(", ")) };
+
+        write!(f, "{}type {}{} = {};", self.visibility, self.name, generics, self.typ)
+    }
+}
+
 impl Display for NoirImpl {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let generics = vecmap(&self.generics, |generic| generic.to_string());
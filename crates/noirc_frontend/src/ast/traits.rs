@@ -0,0 +1,87 @@
+use std::fmt::Display;
+
+use iter_extended::vecmap;
+use noirc_errors::Span;
+
+use crate::{
+    Ident, ItemVisibility, NoirFunction, Path, Pattern, UnresolvedGenerics, UnresolvedType,
+};
+
+/// AST node for trait definitions:
+/// `trait name { ... method signatures ... }`
+#[derive(Clone, Debug)]
+pub struct NoirTrait {
+    pub name: Ident,
+    pub visibility: ItemVisibility,
+    pub methods: Vec<TraitFunction>,
+    pub span: Span,
+}
+
+/// The signature of a method declared by a trait, which each impl of the trait must define.
+#[derive(Clone, Debug)]
+pub struct TraitFunction {
+    pub name: Ident,
+    pub parameters: Vec<(Pattern, UnresolvedType)>,
+    pub return_type: UnresolvedType,
+}
+
+/// Ast node for an impl of a trait for a type:
+/// `impl<generics> trait_name for object_type { ... methods ... }`
+#[derive(Clone, Debug)]
+pub struct NoirTraitImpl {
+    pub trait_name: Path,
+    pub object_type: UnresolvedType,
+    pub type_span: Span,
+    pub generics: UnresolvedGenerics,
+    pub methods: Vec<NoirFunction>,
+}
+
+/// A constraint that a generic of a function implements a trait, as in `T: Hasher`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraitConstraint {
+    pub generic: Ident,
+    pub trait_name: Path,
+}
+
+impl Display for NoirTrait {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}trait {} {{", self.visibility, self.name)?;
+
+        for method in self.methods.iter() {
+            writeln!(f, "    {method}")?;
+        }
+
+        write!(f, "}}")
+    }
+}
+
+impl Display for TraitFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parameters = vecmap(&self.parameters, |(name, typ)| format!("{name}: {typ}"));
+        write!(f, "fn {}({}) -> {};", self.name, parameters.join(", "), self.return_type)
+    }
+}
+
+impl Display for NoirTraitImpl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let generics = vecmap(&self.generics, |generic| generic.to_string());
+        let generics = if generics.is_empty() { "".into() } else { generics.join(", ") };
+
+        writeln!(f, "impl{} {} for {} {{", generics, self.trait_name, self.object_type)?;
+
+        for method in self.methods.iter() {
+            let method = method.to_string();
+            for line in method.lines() {
+                writeln!(f, "    {line}")?;
+            }
+        }
+
+        write!(f, "}}")
+    }
+}
+
+impl Display for TraitConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.generic, self.trait_name)
+    }
+}
@@ -8,6 +8,8 @@ use fm::FileId;
 use rustc_hash::{FxHashMap, FxHashSet};
 use smol_str::SmolStr;
 
+use crate::OverflowMode;
+
 /// The local crate is the crate being compiled.
 /// The caller should ensure that this crate has a CrateId(0).
 pub const LOCAL_CRATE: CrateId = CrateId(0);
@@ -70,6 +72,11 @@ pub struct CrateData {
     pub root_file_id: FileId,
     pub crate_type: CrateType,
     pub dependencies: Vec<Dependency>,
+    /// The features of the crate which are enabled. Items marked `#[cfg(feature = "..")]`
+    /// are only compiled if their feature is one of these.
+    pub features: FxHashSet<String>,
+    /// How the integer arithmetic of the crate behaves on overflow
+    pub overflow_mode: OverflowMode,
 }
 
 /// A dependency is a crate name and a crate_id
@@ -96,7 +103,13 @@ impl CrateGraph {
             return *file_id.0;
         }
 
-        let data = CrateData { root_file_id: file_id, crate_type, dependencies: Vec::new() };
+        let data = CrateData {
+            root_file_id: file_id,
+            crate_type,
+            dependencies: Vec::new(),
+            features: FxHashSet::default(),
+            overflow_mode: OverflowMode::default(),
+        };
         let crate_id = CrateId(self.arena.len());
         let prev = self.arena.insert(crate_id, data);
         assert!(prev.is_none());
@@ -171,6 +184,18 @@ impl CrateGraph {
     pub fn number_of_crates(&self) -> usize {
         self.arena.len()
     }
+
+    pub fn enable_features(
+        &mut self,
+        crate_id: CrateId,
+        features: impl IntoIterator<Item = String>,
+    ) {
+        self.arena.get_mut(&crate_id).unwrap().features.extend(features);
+    }
+
+    pub fn set_overflow_mode(&mut self, crate_id: CrateId, overflow_mode: OverflowMode) {
+        self.arena.get_mut(&crate_id).unwrap().overflow_mode = overflow_mode;
+    }
 }
 impl CrateData {
     fn add_dep(&mut self, name: CrateName, crate_id: CrateId) {
@@ -0,0 +1,190 @@
+//! Completions offered by the language server.
+//!
+//! Rather than matching against the text of a file, completions are found from the def maps of
+//! each crate, which record the items visible in every module, and from the types the type
+//! checker recorded for each expression.
+use fm::FileId;
+
+use super::def_map::{LocalModuleId, ModuleDefId, ModuleId};
+use super::resolution::import::can_reference_item;
+use super::Context;
+use crate::token::Attribute;
+use crate::{Ident, Type};
+
+/// The kind of item a completion refers to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CompletionKind {
+    Module,
+    Function,
+    Struct,
+    Enum,
+    TypeAlias,
+    Trait,
+    Global,
+    Field,
+    Attribute,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    /// The type of the item, for functions and fields
+    pub detail: Option<String>,
+}
+
+impl Completion {
+    fn new(label: impl Into<String>, kind: CompletionKind) -> Completion {
+        Completion { label: label.into(), kind, detail: None }
+    }
+}
+
+impl Context {
+    /// Returns the items which may follow the given segments of a `use` path written in the
+    /// given file, such as the public items of the module `foo::bar` for `use foo::bar::`.
+    pub fn path_completions(&self, file: FileId, segments: &[&str]) -> Vec<Completion> {
+        let current = match self.modules_in_file(file).first() {
+            Some(module) => *module,
+            None => return Vec::new(),
+        };
+        let def_map = &self.def_maps[&current.krate];
+
+        match segments {
+            [] => {
+                let mut completions = self.module_items(current, current);
+                completions.push(Completion::new("crate", CompletionKind::Module));
+                completions.push(Completion::new("dep", CompletionKind::Module));
+                completions
+            }
+            ["dep"] => def_map
+                .extern_prelude
+                .keys()
+                .map(|name| Completion::new(name, CompletionKind::Module))
+                .collect(),
+            _ => match self.resolve_module_path(current, segments) {
+                Some(module) => self.module_items(current, module),
+                None => Vec::new(),
+            },
+        }
+    }
+
+    /// Returns the fields of the struct the expression ending at the given byte offset
+    /// evaluates to, which is where a `.` is written to access one of them.
+    pub fn field_completions(&self, file: FileId, offset: u32) -> Vec<Completion> {
+        let typ = match self.def_interner.find_type_of_expression_ending_at(file, offset) {
+            Some(typ) => typ.follow_bindings(),
+            None => return Vec::new(),
+        };
+
+        // Fields of a mutable reference to a struct are accessed through the reference
+        let typ = match typ {
+            Type::MutableReference(element) => element.follow_bindings(),
+            other => other,
+        };
+
+        match typ {
+            Type::Struct(struct_type, generic_args) => {
+                let fields = struct_type.borrow().get_fields(&generic_args);
+                fields
+                    .into_iter()
+                    .map(|(name, typ)| Completion {
+                        detail: Some(typ.to_string()),
+                        ..Completion::new(name, CompletionKind::Field)
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the functions in scope in each module declared in the given file.
+    pub fn function_completions(&self, file: FileId) -> Vec<Completion> {
+        let mut completions: Vec<Completion> = Vec::new();
+        for module in self.modules_in_file(file) {
+            for (name, (id, _)) in module.module(&self.def_maps).scope().values() {
+                let completion = self.item_completion(&name.0.contents, *id);
+                if completion.kind == CompletionKind::Function && !completions.contains(&completion)
+                {
+                    completions.push(completion);
+                }
+            }
+        }
+        completions
+    }
+
+    /// Returns the modules whose items are declared in the given file: the module the file
+    /// declares along with any contracts declared within it.
+    pub(super) fn modules_in_file(&self, file: FileId) -> Vec<ModuleId> {
+        let mut modules = Vec::new();
+        for def_map in self.def_maps.values() {
+            for (index, module) in def_map.modules().iter() {
+                if module.origin.file_id() == file {
+                    let local_id = LocalModuleId(index);
+                    modules.push(ModuleId { krate: def_map.krate(), local_id });
+                }
+            }
+        }
+        // Visit the module the file declares before any contract declared within it
+        modules.sort_by_key(|module| module.module(&self.def_maps).is_contract);
+        modules
+    }
+
+    /// Resolves the path of a module as written in a `use` statement of the `current` module.
+    fn resolve_module_path(&self, current: ModuleId, segments: &[&str]) -> Option<ModuleId> {
+        let def_map = &self.def_maps[&current.krate];
+        let (mut module, rest) = match segments.split_first() {
+            Some((&"crate", rest)) => {
+                (ModuleId { krate: current.krate, local_id: def_map.root() }, rest)
+            }
+            Some((&"dep", rest)) => {
+                let (name, rest) = rest.split_first()?;
+                (*def_map.extern_prelude.get(*name)?, rest)
+            }
+            _ => (current, segments),
+        };
+
+        for segment in rest {
+            let (id, _) = module.module(&self.def_maps).find_name(&Ident::from(*segment)).types?;
+            module = match id {
+                ModuleDefId::ModuleId(id) => id,
+                _ => return None,
+            };
+        }
+        Some(module)
+    }
+
+    /// Returns the items of `module` which may be referred to from the `importing` module.
+    fn module_items(&self, importing: ModuleId, module: ModuleId) -> Vec<Completion> {
+        let scope = module.module(&self.def_maps).scope();
+        scope
+            .types()
+            .iter()
+            .chain(scope.values())
+            .filter(|(_, (_, visibility))| {
+                can_reference_item(&self.def_maps, importing, module, *visibility)
+            })
+            .map(|(name, (id, _))| self.item_completion(&name.0.contents, *id))
+            .collect()
+    }
+
+    fn item_completion(&self, name: &str, id: ModuleDefId) -> Completion {
+        let (kind, detail) = match id {
+            ModuleDefId::ModuleId(_) => (CompletionKind::Module, None),
+            ModuleDefId::FunctionId(func_id) => {
+                let meta = self.def_interner.try_function_meta(&func_id);
+                (CompletionKind::Function, meta.map(|meta| meta.typ.to_string()))
+            }
+            ModuleDefId::TypeId(_) => (CompletionKind::Struct, None),
+            ModuleDefId::EnumId(_) => (CompletionKind::Enum, None),
+            ModuleDefId::TypeAliasId(_) => (CompletionKind::TypeAlias, None),
+            ModuleDefId::TraitId(_) => (CompletionKind::Trait, None),
+            ModuleDefId::GlobalId(_) => (CompletionKind::Global, None),
+        };
+        Completion { label: name.to_owned(), kind, detail }
+    }
+}
+
+/// Returns the names of the attributes which may be written after `#[`.
+pub fn attribute_completions() -> Vec<Completion> {
+    Attribute::NAMES.iter().map(|name| Completion::new(*name, CompletionKind::Attribute)).collect()
+}
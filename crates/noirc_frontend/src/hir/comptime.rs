@@ -0,0 +1,519 @@
+//! Evaluation of expressions at compile-time.
+//!
+//! Globals may be initialized by calling functions, yet their values can still be required
+//! where only constants are allowed, such as in array lengths or the bounds of loops. Once
+//! every function is resolved, such globals are evaluated by interpreting the HIR of their
+//! initializers here. Only a pure subset of the language is supported: integer and boolean
+//! arithmetic, local variables, `if`, `for` loops, assertions, early returns, and calls to
+//! other functions within this same subset.
+//!
+//! Since this happens before type checking, integers are evaluated as unbounded values which
+//! are only checked against the bounds of their type where that type is declared: in the
+//! parameters and return types of functions, in annotated `let` statements, and in casts. For
+//! the same reason, a value is only known to be an integer rather than a field element once it
+//! passes through such a declared type, or is combined with a value which has.
+use std::collections::HashMap;
+
+use noirc_errors::registry::{self, ErrorCode};
+use noirc_errors::{CustomDiagnostic as Diagnostic, Location};
+use thiserror::Error;
+
+use crate::hir_def::expr::{HirExpression, HirIdent, HirLiteral};
+use crate::hir_def::stmt::{HirLValue, HirPattern, HirStatement};
+use crate::node_interner::{DefinitionId, DefinitionKind, ExprId, NodeInterner, StmtId};
+use crate::{BinaryOpKind, FunctionKind, Signedness, Type, TypeBinding, UnaryOp};
+
+/// The number of expressions an evaluation may evaluate before it is assumed not to terminate
+pub const STEP_LIMIT: usize = 1_000_000;
+
+/// The number of nested calls an evaluation may make before it is assumed not to terminate
+pub const CALL_DEPTH_LIMIT: usize = 500;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Value {
+    Integer(u128, IntegerKind),
+    Bool(bool),
+    Unit,
+}
+
+/// Whether an integer value is known to be of an integer type
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IntegerKind {
+    /// The value is of a declared integer type, so it is divided with truncation
+    Typed,
+    /// The value may be a field element, such as an integer literal
+    Untyped,
+}
+
+impl Value {
+    fn is_typed_integer(&self) -> bool {
+        matches!(self, Value::Integer(_, IntegerKind::Typed))
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ComptimeError {
+    #[error("Expression cannot be evaluated at compile-time")]
+    Unsupported { location: Location },
+    #[error("Expected a value of type {expected} but found {found:?}")]
+    TypeMismatch { expected: &'static str, found: Value, location: Location },
+    #[error("Arithmetic overflow during compile-time evaluation")]
+    Overflow { location: Location },
+    #[error("Division by zero during compile-time evaluation")]
+    DivisionByZero { location: Location },
+    #[error("Assertion failed during compile-time evaluation")]
+    AssertionFailed { location: Location },
+    #[error("Compile-time evaluation exceeded {limit} steps")]
+    StepLimitExceeded { limit: usize, location: Location },
+    #[error("Compile-time evaluation exceeded {limit} nested calls")]
+    CallDepthExceeded { limit: usize, location: Location },
+}
+
+impl ComptimeError {
+    pub fn location(&self) -> Location {
+        match self {
+            ComptimeError::Unsupported { location }
+            | ComptimeError::TypeMismatch { location, .. }
+            | ComptimeError::Overflow { location }
+            | ComptimeError::DivisionByZero { location }
+            | ComptimeError::AssertionFailed { location }
+            | ComptimeError::StepLimitExceeded { location, .. }
+            | ComptimeError::CallDepthExceeded { location, .. } => *location,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ComptimeError::Unsupported { .. } => registry::E0083,
+            ComptimeError::TypeMismatch { .. } => registry::E0084,
+            ComptimeError::Overflow { .. } => registry::E0085,
+            ComptimeError::DivisionByZero { .. } => registry::E0086,
+            ComptimeError::AssertionFailed { .. } => registry::E0087,
+            ComptimeError::StepLimitExceeded { .. } => registry::E0088,
+            ComptimeError::CallDepthExceeded { .. } => registry::E0089,
+        }
+    }
+}
+
+impl From<ComptimeError> for Diagnostic {
+    fn from(error: ComptimeError) -> Diagnostic {
+        let span = error.location().span;
+        let diagnostic = match error {
+            ComptimeError::Unsupported { .. } => Diagnostic::simple_error(
+                error.to_string(),
+                "Only integer and boolean arithmetic, variables, ifs, loops, assertions, returns, and calls to functions using the same are supported".into(),
+                span,
+            ),
+            ComptimeError::TypeMismatch { expected, found, .. } => {
+                let found = match found {
+                    Value::Integer(..) => "an integer",
+                    Value::Bool(_) => "a bool",
+                    Value::Unit => "()",
+                };
+                Diagnostic::simple_error(
+                    format!("Expected {expected} during compile-time evaluation, found {found}"),
+                    String::new(),
+                    span,
+                )
+            }
+            ComptimeError::StepLimitExceeded { .. } | ComptimeError::CallDepthExceeded { .. } => {
+                let mut diag =
+                    Diagnostic::simple_error(error.to_string(), "while evaluating this".into(), span);
+                diag.add_note("The evaluation may not terminate".into());
+                diag
+            }
+            ComptimeError::Overflow { .. }
+            | ComptimeError::DivisionByZero { .. }
+            | ComptimeError::AssertionFailed { .. } => {
+                Diagnostic::simple_error(error.to_string(), String::new(), span)
+            }
+        };
+        diagnostic.with_code(error.code())
+    }
+}
+
+/// Interprets the HIR of an expression to find its value at compile-time
+pub struct Interpreter<'interner> {
+    interner: &'interner NodeInterner,
+
+    /// The values of the variables in scope of the function being evaluated
+    locals: HashMap<DefinitionId, Value>,
+
+    /// Set by a `return` statement to the value being returned. While set, the remaining
+    /// statements of each enclosing block and loop are skipped until the call returns.
+    returned: Option<Value>,
+
+    steps: usize,
+    call_depth: usize,
+}
+
+impl<'interner> Interpreter<'interner> {
+    pub fn new(interner: &'interner NodeInterner) -> Self {
+        Self { interner, locals: HashMap::new(), returned: None, steps: 0, call_depth: 0 }
+    }
+
+    /// Evaluates the initializer of the given global
+    pub fn evaluate_global(&mut self, global: StmtId) -> Result<Value, ComptimeError> {
+        match self.interner.statement(&global) {
+            HirStatement::Let(let_statement) => self.evaluate(let_statement.expression),
+            _ => unreachable!("ice: globals must be let statements"),
+        }
+    }
+
+    pub fn evaluate(&mut self, expr: ExprId) -> Result<Value, ComptimeError> {
+        let location = self.interner.expr_location(&expr);
+
+        self.steps += 1;
+        if self.steps > STEP_LIMIT {
+            return Err(ComptimeError::StepLimitExceeded { limit: STEP_LIMIT, location });
+        }
+
+        match self.interner.expression(&expr) {
+            HirExpression::Ident(ident) => self.evaluate_ident(ident),
+            HirExpression::Literal(HirLiteral::Integer(value)) => {
+                let value = value.try_into_u128().ok_or(ComptimeError::Overflow { location })?;
+                Ok(Value::Integer(value, IntegerKind::Untyped))
+            }
+            HirExpression::Literal(HirLiteral::Bool(value)) => Ok(Value::Bool(value)),
+            HirExpression::Block(block) => {
+                let mut result = Value::Unit;
+                for statement in block.statements() {
+                    result = self.evaluate_statement(*statement)?;
+                    if self.returned.is_some() {
+                        break;
+                    }
+                }
+                Ok(result)
+            }
+            HirExpression::Prefix(prefix) => {
+                let rhs = self.evaluate(prefix.rhs)?;
+                match (prefix.operator, rhs) {
+                    (UnaryOp::Not, Value::Bool(value)) => Ok(Value::Bool(!value)),
+                    (UnaryOp::Minus, Value::Integer(0, kind)) => Ok(Value::Integer(0, kind)),
+                    (UnaryOp::Minus, Value::Integer(..)) => {
+                        Err(ComptimeError::Overflow { location })
+                    }
+                    _ => Err(ComptimeError::Unsupported { location }),
+                }
+            }
+            HirExpression::Infix(infix) => {
+                let lhs = self.evaluate(infix.lhs)?;
+                let rhs = self.evaluate(infix.rhs)?;
+                // Array lengths are evaluated before type checking, in which case the type of
+                // the operands is only known from the values themselves
+                let is_integer = lhs.is_typed_integer()
+                    || rhs.is_typed_integer()
+                    || matches!(
+                        self.interner.id_type(infix.lhs).follow_bindings(),
+                        Type::Integer(..)
+                    );
+                evaluate_infix(lhs, infix.operator.kind, rhs, is_integer, location)
+            }
+            HirExpression::Call(call) => {
+                let func = match self.interner.expression(&call.func) {
+                    HirExpression::Ident(ident) => match self.interner.definition(ident.id).kind {
+                        DefinitionKind::Function(func) => func,
+                        _ => return Err(ComptimeError::Unsupported { location }),
+                    },
+                    _ => return Err(ComptimeError::Unsupported { location }),
+                };
+
+                let meta = self.interner.function_meta(&func);
+                if meta.kind != FunctionKind::Normal {
+                    return Err(ComptimeError::Unsupported { location });
+                }
+
+                let mut arguments = Vec::with_capacity(call.arguments.len());
+                for argument in &call.arguments {
+                    arguments.push(self.evaluate(*argument)?);
+                }
+
+                self.call_depth += 1;
+                if self.call_depth > CALL_DEPTH_LIMIT {
+                    let limit = CALL_DEPTH_LIMIT;
+                    return Err(ComptimeError::CallDepthExceeded { limit, location });
+                }
+
+                let caller_locals = std::mem::take(&mut self.locals);
+                for (parameter, argument) in meta.parameters.0.iter().zip(arguments) {
+                    let argument = check_bounds(argument, &parameter.1, location)?;
+                    self.define_pattern(&parameter.0, argument, location)?;
+                }
+                let result = self.evaluate(*self.interner.function(&func).as_expr())?;
+                let result = self.returned.take().unwrap_or(result);
+                let result = check_bounds(result, meta.return_type(), location)?;
+                self.locals = caller_locals;
+                self.call_depth -= 1;
+                Ok(result)
+            }
+            HirExpression::Cast(cast) => {
+                let value = self.evaluate(cast.lhs)?;
+                match (value, cast.r#type) {
+                    (Value::Integer(value, _), Type::Integer(_, Signedness::Unsigned, bits)) => {
+                        let value = value & max_integer(Signedness::Unsigned, bits);
+                        Ok(Value::Integer(value, IntegerKind::Typed))
+                    }
+                    (Value::Integer(value, _), Type::FieldElement(_)) => {
+                        Ok(Value::Integer(value, IntegerKind::Untyped))
+                    }
+                    (Value::Bool(value), Type::Integer(..)) => {
+                        Ok(Value::Integer(value as u128, IntegerKind::Typed))
+                    }
+                    (Value::Bool(value), Type::FieldElement(_)) => {
+                        Ok(Value::Integer(value as u128, IntegerKind::Untyped))
+                    }
+                    _ => Err(ComptimeError::Unsupported { location }),
+                }
+            }
+            HirExpression::For(for_loop) => {
+                let (start, start_kind) = self.evaluate_integer(for_loop.start_range)?;
+                let (end, end_kind) = self.evaluate_integer(for_loop.end_range)?;
+                let kind = if start_kind == IntegerKind::Typed || end_kind == IntegerKind::Typed {
+                    IntegerKind::Typed
+                } else {
+                    IntegerKind::Untyped
+                };
+                for index in start..end {
+                    self.locals.insert(for_loop.identifier.id, Value::Integer(index, kind));
+                    self.evaluate(for_loop.block)?;
+                    if self.returned.is_some() {
+                        break;
+                    }
+                }
+                Ok(Value::Unit)
+            }
+            HirExpression::If(if_expr) => {
+                if self.evaluate_bool(if_expr.condition)? {
+                    self.evaluate(if_expr.consequence)
+                } else if let Some(alternative) = if_expr.alternative {
+                    self.evaluate(alternative)
+                } else {
+                    Ok(Value::Unit)
+                }
+            }
+            _ => Err(ComptimeError::Unsupported { location }),
+        }
+    }
+
+    fn evaluate_ident(&mut self, ident: HirIdent) -> Result<Value, ComptimeError> {
+        match self.interner.definition(ident.id).kind {
+            DefinitionKind::Global(expr) => {
+                // A global defined in terms of itself would otherwise be evaluated forever
+                self.call_depth += 1;
+                if self.call_depth > CALL_DEPTH_LIMIT {
+                    let (limit, location) = (CALL_DEPTH_LIMIT, ident.location);
+                    return Err(ComptimeError::CallDepthExceeded { limit, location });
+                }
+
+                // Globals are evaluated without the locals of the function using them
+                let locals = std::mem::take(&mut self.locals);
+                let result = self.evaluate(expr);
+                self.locals = locals;
+                self.call_depth -= 1;
+                result
+            }
+            DefinitionKind::Local(_) => match self.locals.get(&ident.id) {
+                Some(value) => Ok(*value),
+                None => Err(ComptimeError::Unsupported { location: ident.location }),
+            },
+            // Numeric generics only have a value once bound during monomorphization
+            DefinitionKind::GenericType(type_variable) => match &*type_variable.borrow() {
+                TypeBinding::Bound(binding) => match binding.evaluate_to_u64() {
+                    Some(value) => Ok(Value::Integer(value as u128, IntegerKind::Untyped)),
+                    None => Err(ComptimeError::Unsupported { location: ident.location }),
+                },
+                TypeBinding::Unbound(_) => {
+                    Err(ComptimeError::Unsupported { location: ident.location })
+                }
+            },
+            _ => Err(ComptimeError::Unsupported { location: ident.location }),
+        }
+    }
+
+    fn evaluate_statement(&mut self, statement: StmtId) -> Result<Value, ComptimeError> {
+        match self.interner.statement(&statement) {
+            HirStatement::Let(let_statement) => {
+                let value = self.evaluate(let_statement.expression)?;
+                if self.returned.is_some() {
+                    return Ok(Value::Unit);
+                }
+                let location = self.interner.expr_location(&let_statement.expression);
+                let value = check_bounds(value, &let_statement.r#type, location)?;
+                self.define_pattern(&let_statement.pattern, value, location)?;
+                Ok(Value::Unit)
+            }
+            HirStatement::Constrain(constrain) => {
+                if self.evaluate_bool(constrain.0)? {
+                    Ok(Value::Unit)
+                } else {
+                    let location = self.interner.expr_location(&constrain.0);
+                    Err(ComptimeError::AssertionFailed { location })
+                }
+            }
+            HirStatement::Assign(assign) => {
+                let value = self.evaluate(assign.expression)?;
+                match assign.lvalue {
+                    HirLValue::Ident(ident, _) => {
+                        self.locals.insert(ident.id, value);
+                        Ok(Value::Unit)
+                    }
+                    _ => {
+                        let location = self.interner.expr_location(&assign.expression);
+                        Err(ComptimeError::Unsupported { location })
+                    }
+                }
+            }
+            HirStatement::Expression(expr) => self.evaluate(expr),
+            HirStatement::Semi(expr) => {
+                self.evaluate(expr)?;
+                Ok(Value::Unit)
+            }
+            HirStatement::Return(expr) => {
+                let value = self.evaluate(expr)?;
+                self.returned = Some(value);
+                Ok(Value::Unit)
+            }
+            HirStatement::Error => {
+                let location = self.interner.id_location(statement);
+                Err(ComptimeError::Unsupported { location })
+            }
+        }
+    }
+
+    fn define_pattern(
+        &mut self,
+        pattern: &HirPattern,
+        value: Value,
+        location: Location,
+    ) -> Result<(), ComptimeError> {
+        match pattern {
+            HirPattern::Identifier(ident) => {
+                self.locals.insert(ident.id, value);
+                Ok(())
+            }
+            HirPattern::Mutable(pattern, _) => self.define_pattern(pattern, value, location),
+            HirPattern::Tuple(..) | HirPattern::Struct(..) => {
+                Err(ComptimeError::Unsupported { location })
+            }
+        }
+    }
+
+    fn evaluate_integer(&mut self, expr: ExprId) -> Result<(u128, IntegerKind), ComptimeError> {
+        match self.evaluate(expr)? {
+            Value::Integer(value, kind) => Ok((value, kind)),
+            found => {
+                let location = self.interner.expr_location(&expr);
+                Err(ComptimeError::TypeMismatch { expected: "an integer", found, location })
+            }
+        }
+    }
+
+    fn evaluate_bool(&mut self, expr: ExprId) -> Result<bool, ComptimeError> {
+        match self.evaluate(expr)? {
+            Value::Bool(value) => Ok(value),
+            found => {
+                let location = self.interner.expr_location(&expr);
+                Err(ComptimeError::TypeMismatch { expected: "a bool", found, location })
+            }
+        }
+    }
+}
+
+/// Checks that an integer value is within the bounds of the given type, if it is an integer type,
+/// returning the value as one of that type
+fn check_bounds(value: Value, typ: &Type, location: Location) -> Result<Value, ComptimeError> {
+    match (value, typ.follow_bindings()) {
+        (Value::Integer(value, _), Type::Integer(_, signedness, bits)) => {
+            if value > max_integer(signedness, bits) {
+                Err(ComptimeError::Overflow { location })
+            } else {
+                Ok(Value::Integer(value, IntegerKind::Typed))
+            }
+        }
+        (Value::Integer(value, _), Type::FieldElement(_)) => {
+            Ok(Value::Integer(value, IntegerKind::Untyped))
+        }
+        _ => Ok(value),
+    }
+}
+
+/// Returns the largest positive value of an integer type
+fn max_integer(signedness: Signedness, bits: u32) -> u128 {
+    let bits = match signedness {
+        Signedness::Unsigned => bits,
+        Signedness::Signed => bits.saturating_sub(1),
+    };
+    if bits >= 128 {
+        u128::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+/// Evaluates a binary operation on two values.
+///
+/// Field elements are evaluated as integers, which only agree with the field arithmetic as long
+/// as nothing wraps around the field. Divisions are only evaluated when the operands are known to
+/// be integers or when the division is exact, since the division of field elements is a
+/// multiplication by an inverse: `7 / 2` in a field is not `3`.
+fn evaluate_infix(
+    lhs: Value,
+    operator: BinaryOpKind,
+    rhs: Value,
+    is_integer: bool,
+    location: Location,
+) -> Result<Value, ComptimeError> {
+    use BinaryOpKind::*;
+    let overflow = ComptimeError::Overflow { location };
+    let kind = if is_integer { IntegerKind::Typed } else { IntegerKind::Untyped };
+
+    match (lhs, rhs) {
+        (Value::Integer(..), Value::Integer(0, _)) if matches!(operator, Divide | Modulo) => {
+            Err(ComptimeError::DivisionByZero { location })
+        }
+        (Value::Integer(lhs, _), Value::Integer(rhs, _))
+            if !is_integer && (operator == Modulo || (operator == Divide && lhs % rhs != 0)) =>
+        {
+            Err(ComptimeError::Unsupported { location })
+        }
+        (Value::Integer(lhs, _), Value::Integer(rhs, _)) => Ok(match operator {
+            Add => Value::Integer(lhs.checked_add(rhs).ok_or(overflow)?, kind),
+            Subtract => Value::Integer(lhs.checked_sub(rhs).ok_or(overflow)?, kind),
+            Multiply => Value::Integer(lhs.checked_mul(rhs).ok_or(overflow)?, kind),
+            Divide => Value::Integer(
+                lhs.checked_div(rhs).ok_or(ComptimeError::DivisionByZero { location })?,
+                kind,
+            ),
+            Modulo => Value::Integer(
+                lhs.checked_rem(rhs).ok_or(ComptimeError::DivisionByZero { location })?,
+                kind,
+            ),
+            Equal => Value::Bool(lhs == rhs),
+            NotEqual => Value::Bool(lhs != rhs),
+            Less => Value::Bool(lhs < rhs),
+            LessEqual => Value::Bool(lhs <= rhs),
+            Greater => Value::Bool(lhs > rhs),
+            GreaterEqual => Value::Bool(lhs >= rhs),
+            And => Value::Integer(lhs & rhs, kind),
+            Or => Value::Integer(lhs | rhs, kind),
+            Xor => Value::Integer(lhs ^ rhs, kind),
+            ShiftLeft => {
+                let shift = u32::try_from(rhs).map_err(|_| overflow.clone())?;
+                Value::Integer(lhs.checked_shl(shift).ok_or(overflow)?, kind)
+            }
+            ShiftRight => {
+                let shift = u32::try_from(rhs).map_err(|_| overflow.clone())?;
+                Value::Integer(lhs.checked_shr(shift).ok_or(overflow)?, kind)
+            }
+        }),
+        (Value::Bool(lhs), Value::Bool(rhs)) => match operator {
+            Equal => Ok(Value::Bool(lhs == rhs)),
+            NotEqual => Ok(Value::Bool(lhs != rhs)),
+            And => Ok(Value::Bool(lhs & rhs)),
+            Or => Ok(Value::Bool(lhs | rhs)),
+            Xor => Ok(Value::Bool(lhs ^ rhs)),
+            _ => Err(ComptimeError::Unsupported { location }),
+        },
+        _ => Err(ComptimeError::Unsupported { location }),
+    }
+}
@@ -1,24 +1,35 @@
 use super::dc_mod::collect_defs;
+use super::derive::{collect_derive_requests, derive_impls};
 use super::errors::DefCollectorErrorKind;
 use crate::graph::{CrateId, LOCAL_CRATE};
-use crate::hir::def_map::{CrateDefMap, LocalModuleId, ModuleId};
+use crate::hir::comptime::{ComptimeError, Interpreter, Value};
+use crate::hir::def_map::{CrateDefMap, LocalModuleId, ModuleDefId, ModuleId, MAIN_FUNCTION};
 use crate::hir::resolution::errors::ResolverError;
 use crate::hir::resolution::resolver::Resolver;
 use crate::hir::resolution::{
     import::{resolve_imports, ImportDirective},
-    path_resolver::StandardPathResolver,
+    path_resolver::{PathResolver, StandardPathResolver},
 };
-use crate::hir::type_check::{type_check_func, TypeChecker};
+use crate::hir::type_check::{explain_generic_call, type_check_func, TypeCheckError, TypeChecker};
 use crate::hir::Context;
-use crate::node_interner::{FuncId, NodeInterner, StmtId, StructId};
+use crate::hir_def::expr::{HirArrayLiteral, HirExpression, HirLiteral};
+use crate::hir_def::stmt::{HirConstrainStatement, HirLValue, HirStatement};
+use crate::hir_def::traits::{OverloadableOperator, Trait, TraitImpl, TraitMethod, TraitMethodId};
+use crate::node_interner::{
+    DefinitionId, EnumId, ExprId, FuncId, NodeInterner, ReferenceId, StmtId, StructId, TraitId,
+    TypeAliasId,
+};
+use crate::token::Attribute;
 use crate::{
-    ExpressionKind, Generics, Ident, LetStatement, NoirFunction, NoirStruct, ParsedModule, Shared,
-    Type, TypeBinding, UnresolvedGenerics, UnresolvedType,
+    ExpressionKind, Generics, Ident, ItemVisibility, LetStatement, Literal, NoirEnum, NoirFunction,
+    NoirStruct, NoirTrait, NoirTypeAlias, ParsedModule, Path, Shared, Type, TypeBinding,
+    TypeBindings, UnresolvedGenerics, UnresolvedType,
 };
+use acvm::FieldElement;
 use fm::FileId;
 use iter_extended::vecmap;
-use noirc_errors::Span;
 use noirc_errors::{CustomDiagnostic, FileDiagnostic};
+use noirc_errors::{Location, Span};
 use std::collections::HashMap;
 use std::rc::Rc;
 
@@ -40,12 +51,40 @@ pub struct UnresolvedStruct {
     pub struct_def: NoirStruct,
 }
 
+pub struct UnresolvedEnum {
+    pub file_id: FileId,
+    pub module_id: LocalModuleId,
+    pub enum_def: NoirEnum,
+}
+
+pub struct UnresolvedTypeAlias {
+    pub file_id: FileId,
+    pub module_id: LocalModuleId,
+    pub type_alias_def: NoirTypeAlias,
+}
+
+pub struct UnresolvedTrait {
+    pub file_id: FileId,
+    pub module_id: LocalModuleId,
+    pub trait_def: NoirTrait,
+}
+
+pub struct UnresolvedTraitImpl {
+    pub module_id: LocalModuleId,
+    pub trait_name: Path,
+    pub object_type: UnresolvedType,
+    pub type_span: Span,
+    pub generics: UnresolvedGenerics,
+    pub methods: UnresolvedFunctions,
+}
+
 #[derive(Clone)]
 pub struct UnresolvedGlobal {
     pub file_id: FileId,
     pub module_id: LocalModuleId,
     pub stmt_id: StmtId,
     pub stmt_def: LetStatement,
+    pub doc_comments: Vec<String>,
 }
 
 /// Given a Crate root, collect all definitions in that crate
@@ -54,14 +93,18 @@ pub struct DefCollector {
     pub(crate) collected_imports: Vec<ImportDirective>,
     pub(crate) collected_functions: Vec<UnresolvedFunctions>,
     pub(crate) collected_types: HashMap<StructId, UnresolvedStruct>,
+    pub(crate) collected_enums: HashMap<EnumId, UnresolvedEnum>,
+    pub(crate) collected_type_aliases: HashMap<TypeAliasId, UnresolvedTypeAlias>,
+    pub(crate) collected_traits: HashMap<TraitId, UnresolvedTrait>,
     pub(crate) collected_globals: Vec<UnresolvedGlobal>,
     pub(crate) collected_impls: ImplMap,
+    pub(crate) collected_trait_impls: Vec<UnresolvedTraitImpl>,
 }
 
 /// Maps the type and the module id in which the impl is defined to the functions contained in that
 /// impl along with the generics declared on the impl itself. This also contains the Span
 /// of the object_type of the impl, used to issue an error if the object type fails to resolve.
-type ImplMap =
+pub(super) type ImplMap =
     HashMap<(UnresolvedType, LocalModuleId), Vec<(UnresolvedGenerics, Span, UnresolvedFunctions)>>;
 
 impl DefCollector {
@@ -71,7 +114,11 @@ impl DefCollector {
             collected_imports: vec![],
             collected_functions: vec![],
             collected_types: HashMap::new(),
+            collected_enums: HashMap::new(),
+            collected_type_aliases: HashMap::new(),
+            collected_traits: HashMap::new(),
             collected_impls: HashMap::new(),
+            collected_trait_impls: vec![],
             collected_globals: vec![],
         }
     }
@@ -103,6 +150,10 @@ impl DefCollector {
             let module_id = ModuleId { krate: dep.crate_id, local_id: dep_def_root };
             // Add this crate as a dependency by linking it's root module
             def_map.extern_prelude.insert(dep.as_name(), module_id);
+
+            if dep.as_name() == "std" {
+                collect_operator_traits(context, dep.crate_id);
+            }
         }
 
         // At this point, all dependencies are resolved and type checked.
@@ -134,11 +185,20 @@ impl DefCollector {
 
         // Populate module namespaces according to the imports used
         let current_def_map = context.def_maps.get_mut(&crate_id).unwrap();
+        let mut imports_to_check = Vec::new();
         for resolved_import in resolved {
             let name = resolved_import.name;
+            // Re-exported imports may be used from other modules, so they are never unused
+            let is_reexported = resolved_import.visibility != ItemVisibility::Private;
+            if !resolved_import.allow_unused && !is_reexported {
+                imports_to_check.push((resolved_import.module_scope, name.clone()));
+            }
+
+            let module = &mut current_def_map.modules[resolved_import.module_scope.0];
+            let location = Location::new(resolved_import.path_span, module.origin.file_id());
             for ns in resolved_import.resolved_namespace.iter_defs() {
-                let result = current_def_map.modules[resolved_import.module_scope.0]
-                    .import(name.clone(), ns);
+                context.def_interner.add_reference(location, ReferenceId::Item(ns));
+                let result = module.import(name.clone(), ns, resolved_import.visibility);
 
                 if let Err((first_def, second_def)) = result {
                     let err = DefCollectorErrorKind::DuplicateImport { first_def, second_def };
@@ -157,14 +217,57 @@ impl DefCollector {
 
         let mut file_global_ids = resolve_globals(context, integer_globals, crate_id, errors);
 
+        // Enums and structs may refer to each other, so every enum must be pushed to the
+        // def_interner before the fields of any struct are resolved.
+        for (type_id, typ) in &def_collector.collected_enums {
+            context.def_interner.push_empty_enum(*type_id, typ);
+        }
+
+        // Likewise, every struct must be pushed to the def_interner before any type alias is
+        // resolved, and every type alias must be resolved before the fields of any struct are,
+        // since struct fields may use type aliases of other structs.
+        for (type_id, typ) in &def_collector.collected_types {
+            context.def_interner.push_empty_struct(*type_id, typ);
+        }
+
+        resolve_type_aliases(context, def_collector.collected_type_aliases, crate_id, errors);
+
+        // The impls requested by `#[derive(..)]` are generated from the resolved fields of each
+        // struct, so the structs requesting them must be noted before they are resolved.
+        let derive_requests = collect_derive_requests(&def_collector.collected_types);
+
+        // The fields of public structs may be read by dependent crates, so only the structs
+        // private to this crate are checked for unread fields
+        let structs_to_check = def_collector
+            .collected_types
+            .iter()
+            .filter(|(_, unresolved)| unresolved.struct_def.visibility != ItemVisibility::Public)
+            .map(|(id, unresolved)| (unresolved.file_id, *id))
+            .collect();
+
         // Must resolve structs before we resolve globals.
         resolve_structs(context, def_collector.collected_types, crate_id, errors);
 
+        resolve_enums(context, def_collector.collected_enums, crate_id, errors);
+
         // We must wait to resolve non-integer globals until after we resolve structs since structs
         // globals will need to reference the struct type they're initialized to to ensure they are valid.
         let mut more_global_ids = resolve_globals(context, other_globals, crate_id, errors);
 
         file_global_ids.append(&mut more_global_ids);
+        let file_global_ids = order_globals(&context.def_interner, file_global_ids, errors);
+
+        // Traits only refer to types in the signatures of their methods, so they can be resolved
+        // once every struct is.
+        resolve_traits(context, def_collector.collected_traits, crate_id, errors);
+
+        derive_impls(
+            context,
+            derive_requests,
+            &mut def_collector.collected_impls,
+            &mut def_collector.collected_trait_impls,
+            errors,
+        );
 
         // Before we resolve any function symbols we must go through our impls and
         // re-collect the methods within into their proper module. This cannot be
@@ -172,6 +275,9 @@ impl DefCollector {
         // impl since that determines the module we should collect into.
         collect_impls(context, crate_id, &def_collector.collected_impls, errors);
 
+        let functions_to_check =
+            private_functions(&context.def_maps[&crate_id], &def_collector.collected_functions);
+
         // Lower each function in the crate. This is now possible since imports have been resolved
         let file_func_ids = resolve_free_functions(
             &mut context.def_interner,
@@ -190,11 +296,161 @@ impl DefCollector {
             errors,
         );
 
-        type_check_globals(&mut context.def_interner, file_global_ids, errors);
+        // Each impl of a trait must be known before type checking any function since calls to
+        // the methods of a trait are checked against the impls of the trait.
+        let file_trait_method_ids = resolve_trait_impls(
+            &mut context.def_interner,
+            crate_id,
+            &context.def_maps,
+            def_collector.collected_trait_impls,
+            errors,
+        );
+
+        // Globals used as array lengths may call any function, so they can only be evaluated once
+        // every function is resolved, but must be before any array using them is type checked.
+        evaluate_comptime_array_lengths(&mut context.def_interner, errors);
+
+        type_check_globals(&mut context.def_interner, &context.def_maps, &file_global_ids, errors);
+
+        // The initializers of globals are replaced with their values where possible so that
+        // they are known at compile-time and may be used as the bounds of loops. Each global
+        // is evaluated once, after the globals it refers to have been replaced by their values.
+        evaluate_comptime_globals(&mut context.def_interner, &file_global_ids);
 
         // Type check all of the functions in the crate
-        type_check_functions(&mut context.def_interner, file_func_ids, errors);
-        type_check_functions(&mut context.def_interner, file_method_ids, errors);
+        let (interner, def_maps) = (&mut context.def_interner, &context.def_maps);
+        let mut type_errors = Vec::new();
+        type_check_functions(interner, def_maps, file_func_ids, &mut type_errors);
+        type_check_functions(interner, def_maps, file_method_ids, &mut type_errors);
+        type_check_functions(interner, def_maps, file_trait_method_ids, &mut type_errors);
+
+        // Errors in calls to generic functions are explained by how the calling functions are
+        // called in turn, which is only known once every function is type checked
+        for (file, error) in type_errors {
+            extend_errors(errors, file, [explain_generic_call(interner, error)]);
+        }
+
+        // Only the crate being compiled is checked for unused items, since the items of its
+        // dependencies are there to be used by other crates.
+        if crate_id == LOCAL_CRATE {
+            let def_map = &context.def_maps[&crate_id];
+            warn_unused_imports(&context.def_interner, def_map, imports_to_check, errors);
+            warn_unused_functions(&context.def_interner, functions_to_check, errors);
+            warn_unread_fields(&context.def_interner, def_map, structs_to_check, errors);
+        }
+    }
+}
+
+/// Returns the private free functions of a crate which should be warned about if they are never
+/// called, along with the file each is declared in. Entry points such as `main`, tests and the
+/// functions of contracts are excluded, as are functions marked `#[allow(unused)]`.
+fn private_functions(
+    def_map: &CrateDefMap,
+    collected_functions: &[UnresolvedFunctions],
+) -> Vec<(FileId, FuncId, Ident)> {
+    let mut functions = Vec::new();
+
+    for unresolved in collected_functions {
+        for (module_id, func_id, function) in &unresolved.functions {
+            let def = function.def();
+            let is_main = *module_id == def_map.root() && function.name() == MAIN_FUNCTION;
+            let is_test = def.attribute == Some(Attribute::Test);
+
+            if def.visibility == ItemVisibility::Private
+                && !def.allow_unused
+                && !is_main
+                && !is_test
+                && !def_map.modules()[module_id.0].is_contract
+            {
+                functions.push((unresolved.file_id, *func_id, def.name.clone()));
+            }
+        }
+    }
+
+    functions
+}
+
+/// Warns about each of the given imports whose name is never used within the module it is
+/// imported into.
+fn warn_unused_imports(
+    interner: &NodeInterner,
+    def_map: &CrateDefMap,
+    imports: Vec<(LocalModuleId, Ident)>,
+    errors: &mut Vec<FileDiagnostic>,
+) {
+    for (local_id, ident) in imports {
+        let module = ModuleId { krate: def_map.krate(), local_id };
+        if !interner.is_name_used(module, &ident.0.contents) {
+            let file_id = def_map.module_file_id(local_id);
+            errors.push(ResolverError::UnusedImport { ident }.into_file_diagnostic(file_id));
+        }
+    }
+}
+
+/// Warns about each field of the given structs which is never read. Fields whose name starts
+/// with an underscore are not reported, nor are the fields of structs returned from the entry
+/// points given an ABI, `main` and the functions of contracts, since those are read by the
+/// verifier. Public structs should not be given, as their fields may be read by other crates.
+fn warn_unread_fields(
+    interner: &NodeInterner,
+    def_map: &CrateDefMap,
+    structs: Vec<(FileId, StructId)>,
+    errors: &mut Vec<FileDiagnostic>,
+) {
+    let contract_functions =
+        def_map.get_all_contracts().into_iter().flat_map(|contract| contract.functions);
+
+    let mut returned_structs = Vec::new();
+    for entry_point in def_map.main_function().into_iter().chain(contract_functions) {
+        let return_type = interner.function_meta(&entry_point).return_type().clone();
+        structs_within(&return_type, &mut returned_structs);
+    }
+
+    for (file_id, struct_id) in structs {
+        if returned_structs.contains(&struct_id) {
+            continue;
+        }
+
+        let struct_type = interner.get_struct(struct_id);
+        let struct_type = struct_type.borrow();
+        for (index, field) in struct_type.field_idents().into_iter().enumerate() {
+            if !field.0.contents.starts_with('_') && !interner.is_field_read(struct_id, index) {
+                let struct_name = struct_type.name.to_string();
+                let error = TypeCheckError::FieldNeverRead { field, struct_name };
+                errors.push(CustomDiagnostic::from(error).in_file(file_id));
+            }
+        }
+    }
+}
+
+/// Collects the id of each struct type contained within the given type
+fn structs_within(typ: &Type, structs: &mut Vec<StructId>) {
+    match typ.follow_bindings() {
+        Type::Struct(struct_type, generics) => {
+            let struct_type = struct_type.borrow();
+            structs.push(struct_type.id);
+            for (_, field_type) in struct_type.get_fields(&generics) {
+                structs_within(&field_type, structs);
+            }
+        }
+        Type::Array(_, element) => structs_within(&element, structs),
+        Type::Tuple(elements) => {
+            elements.iter().for_each(|element| structs_within(element, structs))
+        }
+        _ => (),
+    }
+}
+
+/// Warns about each of the given functions which is never referred to.
+fn warn_unused_functions(
+    interner: &NodeInterner,
+    functions: Vec<(FileId, FuncId, Ident)>,
+    errors: &mut Vec<FileDiagnostic>,
+) {
+    for (file_id, func_id, ident) in functions {
+        if !interner.is_function_used(func_id) {
+            errors.push(ResolverError::UnusedFunction { ident }.into_file_diagnostic(file_id));
+        }
     }
 }
 
@@ -218,7 +474,7 @@ fn collect_impls(
         for (generics, span, unresolved) in methods {
             let mut resolver = Resolver::new(interner, &path_resolver, def_maps, file);
             resolver.add_generics(generics);
-            let typ = resolver.resolve_type(unresolved_type.clone());
+            let typ = resolver.resolve_impl_type(unresolved_type.clone());
 
             extend_errors(errors, unresolved.file_id, resolver.take_errors());
 
@@ -229,7 +485,8 @@ fn collect_impls(
                 let module = &mut def_maps.get_mut(&crate_id).unwrap().modules[type_module.0];
 
                 for (_, method_id, method) in &unresolved.functions {
-                    let result = module.declare_function(method.name_ident().clone(), *method_id);
+                    let name = method.name_ident().clone();
+                    let result = module.declare_function(name, *method_id, method.def().visibility);
 
                     if let Err((first_def, second_def)) = result {
                         let err =
@@ -264,14 +521,33 @@ where
     errors.extend(new_errors.into_iter().map(|err| err.into().in_file(file)));
 }
 
+/// Registers the trait method overloading each operator, from the `ops` module of the
+/// standard library. Operators whose trait cannot be found are left unregistered, and can then
+/// only be used on the types they are built in for.
+fn collect_operator_traits(context: &mut Context, std_crate: CrateId) {
+    let def_map = &context.def_maps[&std_crate];
+
+    for operator in OverloadableOperator::ALL {
+        let (trait_path, method_name) = operator.trait_method();
+
+        if let Some(trait_id) = def_map.find_trait_by_path(trait_path) {
+            let the_trait = context.def_interner.get_trait(trait_id);
+            if let Some(method_index) = the_trait.find_method(method_name) {
+                let method = TraitMethodId { trait_id, method_index };
+                context.def_interner.set_operator_method(operator, method);
+            }
+        }
+    }
+}
+
 /// Separate the globals Vec into two. The first element in the tuple will be the
 /// integer literal globals, and the second will be all other globals.
 fn filter_integer_globals(
     globals: Vec<UnresolvedGlobal>,
 ) -> (Vec<UnresolvedGlobal>, Vec<UnresolvedGlobal>) {
-    globals
-        .into_iter()
-        .partition(|global| matches!(&global.stmt_def.expression.kind, ExpressionKind::Literal(_)))
+    globals.into_iter().partition(|global| {
+        matches!(&global.stmt_def.expression.kind, ExpressionKind::Literal(Literal::Integer(_)))
+    })
 }
 
 fn resolve_globals(
@@ -280,7 +556,9 @@ fn resolve_globals(
     crate_id: CrateId,
     errors: &mut Vec<FileDiagnostic>,
 ) -> Vec<(FileId, StmtId)> {
-    vecmap(globals, |global| {
+    // Every global is declared before any initializer is resolved so that initializers may
+    // refer to other globals regardless of the order they are declared in.
+    let initializers = vecmap(globals, |global| {
         let module_id = ModuleId { local_id: global.module_id, krate: crate_id };
         let path_resolver = StandardPathResolver::new(module_id);
         let storage_slot = context.next_storage_slot(module_id);
@@ -294,25 +572,277 @@ fn resolve_globals(
 
         let name = global.stmt_def.pattern.name_ident().clone();
 
-        let hir_stmt = resolver.resolve_global_let(global.stmt_def);
+        let (hir_stmt, initializer) = resolver.resolve_global_let(global.stmt_def);
         extend_errors(errors, global.file_id, resolver.take_errors());
 
         context.def_interner.update_global(global.stmt_id, hir_stmt);
 
-        context.def_interner.push_global(global.stmt_id, name, global.module_id, storage_slot);
+        context.def_interner.push_global(
+            global.stmt_id,
+            name,
+            global.module_id,
+            storage_slot,
+            global.doc_comments,
+        );
+
+        (global.file_id, global.module_id, global.stmt_id, initializer)
+    });
+
+    vecmap(initializers, |(file_id, module_id, stmt_id, initializer)| {
+        let path_resolver =
+            StandardPathResolver::new(ModuleId { local_id: module_id, krate: crate_id });
 
-        (global.file_id, global.stmt_id)
+        let mut resolver =
+            Resolver::new(&mut context.def_interner, &path_resolver, &context.def_maps, file_id);
+        resolver.resolve_global_initializer(stmt_id, initializer);
+        extend_errors(errors, file_id, resolver.take_errors());
+
+        (file_id, stmt_id)
     })
 }
 
+/// Orders globals such that each comes after every global its initializer refers to, so that
+/// the type and value of a global are known before the globals using it are type checked and
+/// evaluated. Globals defined in terms of themselves are reported as errors.
+fn order_globals(
+    interner: &NodeInterner,
+    globals: Vec<(FileId, StmtId)>,
+    errors: &mut Vec<FileDiagnostic>,
+) -> Vec<(FileId, StmtId)> {
+    let definitions: HashMap<_, _> = globals
+        .iter()
+        .map(|(file_id, global)| (interner.let_statement(global).ident().id, (*file_id, *global)))
+        .collect();
+
+    let mut ordered = Vec::with_capacity(globals.len());
+    for global in globals {
+        order_global(interner, global, &definitions, &mut Vec::new(), &mut ordered, errors);
+    }
+    ordered
+}
+
+/// Orders a single global after the globals it refers to. `in_progress` holds the globals
+/// currently being ordered further up the call stack, any of which being referred to again
+/// means the global is defined in terms of itself.
+fn order_global(
+    interner: &NodeInterner,
+    global: (FileId, StmtId),
+    definitions: &HashMap<DefinitionId, (FileId, StmtId)>,
+    in_progress: &mut Vec<StmtId>,
+    ordered: &mut Vec<(FileId, StmtId)>,
+    errors: &mut Vec<FileDiagnostic>,
+) {
+    let (file_id, stmt_id) = global;
+    if ordered.contains(&global) {
+        return;
+    }
+
+    if in_progress.contains(&stmt_id) {
+        let name = interner.get_global(&stmt_id).expect("ice: global should be interned").ident;
+        let error = DefCollectorErrorKind::CyclicGlobal { name };
+        errors.push(error.into_file_diagnostic(file_id));
+        return;
+    }
+
+    let mut referenced = Vec::new();
+    referenced_globals(interner, interner.let_statement(&stmt_id).expression, &mut referenced);
+
+    in_progress.push(stmt_id);
+    for definition in referenced {
+        if let Some(dependency) = definitions.get(&definition) {
+            order_global(interner, *dependency, definitions, in_progress, ordered, errors);
+        }
+    }
+    in_progress.pop();
+
+    ordered.push(global);
+}
+
+/// Collects the definitions of the globals referred to within the given expression
+fn referenced_globals(interner: &NodeInterner, expr: ExprId, found: &mut Vec<DefinitionId>) {
+    let visit_all = |exprs: &[ExprId], found: &mut Vec<DefinitionId>| {
+        for expr in exprs {
+            referenced_globals(interner, *expr, found);
+        }
+    };
+
+    match interner.expression(&expr) {
+        HirExpression::Ident(ident) => {
+            if interner.definition(ident.id).is_global() {
+                found.push(ident.id);
+            }
+        }
+        HirExpression::Literal(HirLiteral::Array(HirArrayLiteral::Standard(elements))) => {
+            visit_all(&elements, found);
+        }
+        HirExpression::Literal(HirLiteral::Array(HirArrayLiteral::Repeated {
+            repeated_element,
+            ..
+        })) => visit_all(&[repeated_element], found),
+        HirExpression::Literal(HirLiteral::FmtStr(_, fields)) => visit_all(&fields, found),
+        HirExpression::Literal(_) => (),
+        HirExpression::Block(block) => {
+            for statement in block.statements() {
+                referenced_globals_in_statement(interner, *statement, found);
+            }
+        }
+        HirExpression::Prefix(prefix) => visit_all(&[prefix.rhs], found),
+        HirExpression::Infix(infix) => visit_all(&[infix.lhs, infix.rhs], found),
+        HirExpression::Index(index) => visit_all(&[index.collection, index.index], found),
+        HirExpression::Constructor(constructor) => {
+            visit_all(&vecmap(constructor.fields, |(_, field)| field), found);
+        }
+        HirExpression::EnumConstructor(constructor) => visit_all(&constructor.arguments, found),
+        HirExpression::MemberAccess(access) => visit_all(&[access.lhs], found),
+        HirExpression::Call(call) => {
+            visit_all(&[call.func], found);
+            visit_all(&call.arguments, found);
+        }
+        HirExpression::MethodCall(call) => {
+            visit_all(&[call.object], found);
+            visit_all(&call.arguments, found);
+        }
+        HirExpression::Cast(cast) => visit_all(&[cast.lhs], found),
+        HirExpression::For(for_expr) => {
+            visit_all(&[for_expr.start_range, for_expr.end_range, for_expr.block], found);
+        }
+        HirExpression::While(while_expr) => {
+            visit_all(&[while_expr.condition, while_expr.block], found);
+        }
+        HirExpression::If(if_expr) => {
+            visit_all(&[if_expr.condition, if_expr.consequence], found);
+            if let Some(alternative) = if_expr.alternative {
+                visit_all(&[alternative], found);
+            }
+        }
+        HirExpression::Match(match_expr) => {
+            visit_all(&[match_expr.expression], found);
+            visit_all(&vecmap(match_expr.rules, |(_, branch)| branch), found);
+        }
+        HirExpression::Tuple(elements) => visit_all(&elements, found),
+        HirExpression::Lambda(lambda) => visit_all(&[lambda.body], found),
+        HirExpression::TraitMethodReference(_) | HirExpression::Error => (),
+    }
+}
+
+fn referenced_globals_in_statement(
+    interner: &NodeInterner,
+    statement: StmtId,
+    found: &mut Vec<DefinitionId>,
+) {
+    match interner.statement(&statement) {
+        HirStatement::Let(let_statement) => {
+            referenced_globals(interner, let_statement.expression, found);
+        }
+        HirStatement::Constrain(HirConstrainStatement(expr, _, message, _)) => {
+            referenced_globals(interner, expr, found);
+            if let Some(message) = message {
+                referenced_globals(interner, message, found);
+            }
+        }
+        HirStatement::Assign(assign) => {
+            let mut lvalue = &assign.lvalue;
+            loop {
+                lvalue = match lvalue {
+                    HirLValue::Ident(..) => break,
+                    HirLValue::MemberAccess { object, .. } => object.as_ref(),
+                    HirLValue::Index { array, index, .. } => {
+                        referenced_globals(interner, *index, found);
+                        array.as_ref()
+                    }
+                    HirLValue::Dereference { lvalue, .. } => lvalue.as_ref(),
+                };
+            }
+            referenced_globals(interner, assign.expression, found);
+        }
+        HirStatement::Expression(expr) | HirStatement::Semi(expr) | HirStatement::Return(expr) => {
+            referenced_globals(interner, expr, found);
+        }
+        HirStatement::Error => (),
+    }
+}
+
 fn type_check_globals(
     interner: &mut NodeInterner,
-    global_ids: Vec<(FileId, StmtId)>,
+    def_maps: &HashMap<CrateId, CrateDefMap>,
+    global_ids: &[(FileId, StmtId)],
     all_errors: &mut Vec<FileDiagnostic>,
 ) {
     for (file_id, stmt_id) in global_ids {
-        let errors = TypeChecker::check_global(&stmt_id, interner);
-        extend_errors(all_errors, file_id, errors);
+        let errors = TypeChecker::check_global(stmt_id, interner, def_maps);
+        extend_errors(all_errors, *file_id, errors);
+    }
+}
+
+/// Binds the type variables standing in for globals used as array lengths to the values of
+/// those globals, evaluating any initializer which is not a literal at compile-time.
+fn evaluate_comptime_array_lengths(interner: &mut NodeInterner, errors: &mut Vec<FileDiagnostic>) {
+    for (global, typevar) in interner.take_comptime_array_lengths() {
+        let location = match interner.statement(&global) {
+            HirStatement::Let(let_statement) => interner.expr_location(&let_statement.expression),
+            _ => continue,
+        };
+
+        let result =
+            Interpreter::new(interner).evaluate_global(global).and_then(|value| match value {
+                Value::Integer(length, _) => Ok(length),
+                found => {
+                    Err(ComptimeError::TypeMismatch { expected: "an integer", found, location })
+                }
+            });
+
+        let length = match result.map(u64::try_from) {
+            Ok(Ok(length)) => Type::Constant(length),
+            Ok(Err(_)) => {
+                let error = ResolverError::IntegerTooLarge { span: location.span };
+                errors.push(CustomDiagnostic::from(error).in_file(location.file));
+                Type::Error
+            }
+            Err(error) => {
+                let error_location = error.location();
+                let mut diagnostic = CustomDiagnostic::from(error);
+                if error_location != location && error_location.file == location.file {
+                    diagnostic.add_secondary("while evaluating this global".into(), location.span);
+                }
+                diagnostic.add_note(
+                    "The value of this global is required at compile-time since it is used as an array length".into(),
+                );
+                errors.push(diagnostic.in_file(error_location.file));
+                Type::Error
+            }
+        };
+
+        *typevar.borrow_mut() = TypeBinding::Bound(length);
+    }
+}
+
+/// Replaces the initializer of each global which can be evaluated at compile-time with its
+/// value. Globals which cannot be evaluated are left as they are to be evaluated at runtime.
+fn evaluate_comptime_globals(interner: &mut NodeInterner, global_ids: &[(FileId, StmtId)]) {
+    for (_, global) in global_ids {
+        let expression = match interner.statement(global) {
+            HirStatement::Let(let_statement) => let_statement.expression,
+            _ => continue,
+        };
+
+        let is_literal = |expr| matches!(interner.expression(expr), HirExpression::Literal(_));
+        let is_constant = match interner.expression(&expression) {
+            HirExpression::Cast(cast) => is_literal(&cast.lhs),
+            _ => is_literal(&expression),
+        };
+        if is_constant {
+            continue;
+        }
+
+        let literal = match Interpreter::new(interner).evaluate_global(*global) {
+            Ok(Value::Integer(value, _)) => HirLiteral::Integer(FieldElement::from(value)),
+            Ok(Value::Bool(value)) => HirLiteral::Bool(value),
+            Ok(Value::Unit) | Err(_) => continue,
+        };
+
+        // Globals are type checked before being evaluated, so the type of the initializer is
+        // already known and is kept by the literal replacing it.
+        interner.replace_expr(&expression, HirExpression::Literal(literal));
     }
 }
 
@@ -324,14 +854,9 @@ fn resolve_structs(
     crate_id: CrateId,
     errors: &mut Vec<FileDiagnostic>,
 ) {
-    // We must first go through the struct list once to ensure all IDs are pushed to
-    // the def_interner map. This lets structs refer to each other regardless of declaration order
-    // without resolve_struct_fields non-deterministically unwrapping a value
-    // that isn't in the HashMap.
-    for (type_id, typ) in &structs {
-        context.def_interner.push_empty_struct(*type_id, typ);
-    }
-
+    // Every struct was already pushed to the def_interner map. This lets structs refer to each
+    // other regardless of declaration order without resolve_struct_fields non-deterministically
+    // unwrapping a value that isn't in the HashMap.
     for (type_id, typ) in structs {
         let (generics, fields) = resolve_struct_fields(context, crate_id, typ, errors);
         context.def_interner.update_struct(type_id, |struct_def| {
@@ -360,6 +885,171 @@ fn resolve_struct_fields(
     (generics, fields)
 }
 
+/// Resolve the aliased type of each type alias. Since aliases may refer to other aliases, each
+/// alias is resolved only after the aliases it refers to, and cyclic aliases are reported.
+fn resolve_type_aliases(
+    context: &mut Context,
+    mut type_aliases: HashMap<TypeAliasId, UnresolvedTypeAlias>,
+    krate: CrateId,
+    all_errors: &mut Vec<FileDiagnostic>,
+) {
+    let mut ids: Vec<_> = type_aliases.keys().copied().collect();
+    ids.sort();
+
+    for id in ids {
+        resolve_type_alias(context, id, &mut type_aliases, &mut Vec::new(), krate, all_errors);
+    }
+}
+
+/// Resolve a single type alias, first resolving any still unresolved aliases it refers to.
+/// `in_progress` holds the aliases currently being resolved further up the call stack, any of
+/// which being referred to again means the alias is defined in terms of itself.
+fn resolve_type_alias(
+    context: &mut Context,
+    alias_id: TypeAliasId,
+    type_aliases: &mut HashMap<TypeAliasId, UnresolvedTypeAlias>,
+    in_progress: &mut Vec<TypeAliasId>,
+    krate: CrateId,
+    all_errors: &mut Vec<FileDiagnostic>,
+) {
+    let unresolved = match type_aliases.remove(&alias_id) {
+        Some(unresolved) => unresolved,
+        None => return,
+    };
+
+    let path_resolver =
+        StandardPathResolver::new(ModuleId { local_id: unresolved.module_id, krate });
+
+    let mut paths = Vec::new();
+    named_type_paths(&unresolved.type_alias_def.typ, &mut paths);
+
+    in_progress.push(alias_id);
+    for path in paths {
+        if let Ok(ModuleDefId::TypeAliasId(id)) = path_resolver.resolve(&context.def_maps, path) {
+            if in_progress.contains(&id) {
+                let name = unresolved.type_alias_def.name.clone();
+                let error = DefCollectorErrorKind::CyclicTypeAlias { name };
+                all_errors.push(error.into_file_diagnostic(unresolved.file_id));
+            } else {
+                resolve_type_alias(context, id, type_aliases, in_progress, krate, all_errors);
+            }
+        }
+    }
+    in_progress.pop();
+
+    let file = unresolved.file_id;
+
+    let (generics, typ, errors) =
+        Resolver::new(&mut context.def_interner, &path_resolver, &context.def_maps, file)
+            .resolve_type_alias(unresolved.type_alias_def);
+
+    extend_errors(all_errors, file, errors);
+    context.def_interner.update_type_alias(alias_id, |type_alias| {
+        type_alias.typ = typ;
+        type_alias.generics = generics;
+    });
+}
+
+/// Collect the path of each named type within the given type.
+fn named_type_paths(typ: &UnresolvedType, paths: &mut Vec<Path>) {
+    match typ {
+        UnresolvedType::Named(path, args) => {
+            paths.push(path.clone());
+            args.iter().for_each(|arg| named_type_paths(arg, paths));
+        }
+        UnresolvedType::Array(_, element) | UnresolvedType::MutableReference(element) => {
+            named_type_paths(element, paths);
+        }
+        UnresolvedType::Tuple(elements) => {
+            elements.iter().for_each(|element| named_type_paths(element, paths));
+        }
+        UnresolvedType::Function(args, ret) => {
+            args.iter().for_each(|arg| named_type_paths(arg, paths));
+            named_type_paths(ret, paths);
+        }
+        UnresolvedType::FieldElement(_)
+        | UnresolvedType::Integer(..)
+        | UnresolvedType::Bool(_)
+        | UnresolvedType::Expression(_)
+        | UnresolvedType::String(_)
+        | UnresolvedType::Unit
+        | UnresolvedType::Unspecified
+        | UnresolvedType::Error => (),
+    }
+}
+
+/// Resolve the fields of each variant of each enum. The enums themselves must already have
+/// been pushed to the def_interner.
+fn resolve_enums(
+    context: &mut Context,
+    enums: HashMap<EnumId, UnresolvedEnum>,
+    krate: CrateId,
+    all_errors: &mut Vec<FileDiagnostic>,
+) {
+    for (type_id, unresolved) in enums {
+        let path_resolver =
+            StandardPathResolver::new(ModuleId { local_id: unresolved.module_id, krate });
+
+        let file = unresolved.file_id;
+
+        let (generics, variants, errors) =
+            Resolver::new(&mut context.def_interner, &path_resolver, &context.def_maps, file)
+                .resolve_enum_variants(unresolved.enum_def);
+
+        extend_errors(all_errors, unresolved.file_id, errors);
+        context.def_interner.update_enum(type_id, |enum_def| {
+            enum_def.set_variants(variants);
+            enum_def.generics = generics;
+        });
+    }
+}
+
+/// Resolve the signature of each method of each trait, pushing the traits to the def_interner.
+fn resolve_traits(
+    context: &mut Context,
+    traits: HashMap<TraitId, UnresolvedTrait>,
+    crate_id: CrateId,
+    errors: &mut Vec<FileDiagnostic>,
+) {
+    for (trait_id, unresolved) in traits {
+        let path_resolver =
+            StandardPathResolver::new(ModuleId { local_id: unresolved.module_id, krate: crate_id });
+
+        // `Self` within the trait is a generic standing for the type of each impl
+        let self_type_variable = context.def_interner.next_type_variable_id();
+        let self_binding = Shared::new(TypeBinding::Unbound(self_type_variable));
+        let self_type = Type::NamedGeneric(self_binding.clone(), Rc::new("Self".into()));
+
+        let mut resolver = Resolver::new(
+            &mut context.def_interner,
+            &path_resolver,
+            &context.def_maps,
+            unresolved.file_id,
+        );
+        resolver.set_self_type(Some(self_type));
+
+        let trait_def = unresolved.trait_def;
+        let methods = vecmap(trait_def.methods, |method| {
+            let parameters = vecmap(method.parameters, |(_, typ)| resolver.resolve_type(typ));
+            let return_type = Box::new(resolver.resolve_type(method.return_type));
+            let typ = Type::Function(parameters, return_type, Box::new(Type::Unit));
+            let generics = vec![(self_type_variable, self_binding.clone())];
+            TraitMethod { name: method.name, typ: Type::Forall(generics, Box::new(typ)) }
+        });
+
+        extend_errors(errors, unresolved.file_id, resolver.take_errors());
+
+        context.def_interner.push_trait(Trait {
+            id: trait_id,
+            name: trait_def.name,
+            span: trait_def.span,
+            file: unresolved.file_id,
+            self_type_variable,
+            methods,
+        });
+    }
+}
+
 fn resolve_impls(
     interner: &mut NodeInterner,
     crate_id: CrateId,
@@ -379,7 +1069,7 @@ fn resolve_impls(
             let mut resolver = Resolver::new(interner, &path_resolver, def_maps, file);
             resolver.add_generics(&generics);
             let generics = resolver.get_generics().to_vec();
-            let self_type = resolver.resolve_type(unresolved_type.clone());
+            let self_type = resolver.resolve_impl_type(unresolved_type.clone());
 
             let mut file_func_ids = resolve_function_set(
                 interner,
@@ -415,6 +1105,179 @@ fn resolve_impls(
     file_method_ids
 }
 
+fn resolve_trait_impls(
+    interner: &mut NodeInterner,
+    crate_id: CrateId,
+    def_maps: &HashMap<CrateId, CrateDefMap>,
+    collected_trait_impls: Vec<UnresolvedTraitImpl>,
+    errors: &mut Vec<FileDiagnostic>,
+) -> Vec<(FileId, FuncId)> {
+    let mut file_method_ids = Vec::new();
+
+    for trait_impl in collected_trait_impls {
+        let path_resolver =
+            StandardPathResolver::new(ModuleId { local_id: trait_impl.module_id, krate: crate_id });
+
+        let file = trait_impl.methods.file_id;
+        let type_span = trait_impl.type_span;
+
+        let mut resolver = Resolver::new(interner, &path_resolver, def_maps, file);
+        let generics = resolver.add_generics(&trait_impl.generics);
+        let impl_generics = resolver.get_generics().to_vec();
+        let self_type = resolver.resolve_impl_type(trait_impl.object_type);
+        let trait_id = resolver.lookup_trait_or_error(trait_impl.trait_name);
+        extend_errors(errors, file, resolver.take_errors());
+
+        let mut file_func_ids = resolve_function_set(
+            interner,
+            crate_id,
+            def_maps,
+            trait_impl.methods,
+            Some(self_type.clone()),
+            impl_generics,
+            errors,
+        );
+
+        match (trait_id, &self_type) {
+            (_, Type::Error) | (None, _) => (),
+            (Some(_), Type::NamedGeneric(..)) => {
+                let error = DefCollectorErrorKind::TraitImplOnGeneric { span: type_span };
+                errors.push(error.into_file_diagnostic(file));
+            }
+            (Some(trait_id), _) => {
+                let typ = if generics.is_empty() {
+                    self_type
+                } else {
+                    Type::Forall(generics, Box::new(self_type))
+                };
+                let methods = check_trait_impl(
+                    interner,
+                    trait_id,
+                    &typ,
+                    &file_func_ids,
+                    type_span,
+                    file,
+                    errors,
+                );
+
+                if let Some(methods) = methods {
+                    let location = Location::new(type_span, file);
+                    interner.add_trait_impl(trait_id, TraitImpl { typ, location, methods });
+                }
+            }
+        }
+
+        file_method_ids.append(&mut file_func_ids);
+    }
+
+    file_method_ids
+}
+
+/// Checks that no other impl of the trait applies to any of the types the impl does, given by
+/// `impl_type` quantified over the generics of the impl, and that the methods of the impl are
+/// exactly those declared by the trait, with the signatures declared by the trait once `Self` is
+/// replaced by the type of the impl. Returns the methods of the impl in the order the trait
+/// declares them if so.
+fn check_trait_impl(
+    interner: &NodeInterner,
+    trait_id: TraitId,
+    impl_type: &Type,
+    method_ids: &[(FileId, FuncId)],
+    type_span: Span,
+    file: FileId,
+    errors: &mut Vec<FileDiagnostic>,
+) -> Option<Vec<FuncId>> {
+    let the_trait = interner.get_trait(trait_id);
+    let trait_name = the_trait.name.to_string();
+    let self_type = match impl_type {
+        Type::Forall(_, typ) => typ.as_ref(),
+        typ => typ,
+    };
+    let error_count = errors.len();
+
+    if let Some(existing_impl) = interner.find_overlapping_trait_impl(trait_id, impl_type) {
+        let typ = self_type.to_string();
+        let error = DefCollectorErrorKind::OverlappingTraitImpl {
+            trait_name,
+            typ,
+            span: type_span,
+            existing_impl: existing_impl.location,
+        };
+        errors.push(error.into_file_diagnostic(file));
+        return None;
+    }
+
+    for (_, method_id) in method_ids {
+        if the_trait.find_method(interner.function_name(method_id)).is_none() {
+            let trait_name = trait_name.clone();
+            let method = interner.function_ident(method_id);
+            let error = DefCollectorErrorKind::MethodNotInTrait { trait_name, method };
+            errors.push(error.into_file_diagnostic(file));
+        }
+    }
+
+    let mut methods = Vec::new();
+    for trait_method in &the_trait.methods {
+        let method_name = &trait_method.name.0.contents;
+        let method_id = method_ids
+            .iter()
+            .map(|(_, method_id)| *method_id)
+            .find(|method_id| interner.function_name(method_id) == method_name);
+
+        let method_id = match method_id {
+            Some(method_id) => method_id,
+            None => {
+                let error = DefCollectorErrorKind::MissingTraitMethod {
+                    trait_name: trait_name.clone(),
+                    method_name: method_name.clone(),
+                    span: type_span,
+                    declaration: Location::new(trait_method.name.span(), the_trait.file),
+                };
+                errors.push(error.into_file_diagnostic(file));
+                continue;
+            }
+        };
+
+        let expected = match &trait_method.typ {
+            Type::Forall(generics, typ) => {
+                let bindings: TypeBindings = generics
+                    .iter()
+                    .map(|(id, var)| (*id, (var.clone(), self_type.clone())))
+                    .collect();
+                typ.substitute(&bindings)
+            }
+            typ => typ.clone(),
+        };
+        let found = match interner.function_meta(&method_id).typ {
+            Type::Forall(_, typ) => *typ,
+            typ => typ,
+        };
+
+        let method = interner.function_ident(&method_id);
+        let mut type_errors = Vec::new();
+        found.unify(&expected, method.span(), &mut type_errors, || TypeCheckError::TypeMismatch {
+            expected_typ: expected.to_string(),
+            expr_typ: found.to_string(),
+            expr_span: method.span(),
+        });
+
+        if !type_errors.is_empty() {
+            let (expected, found) = (expected.to_string(), found.to_string());
+            let declaration = Location::new(trait_method.name.span(), the_trait.file);
+            let error = DefCollectorErrorKind::TraitMethodSignatureMismatch {
+                method,
+                expected,
+                found,
+                declaration,
+            };
+            errors.push(error.into_file_diagnostic(file));
+        }
+        methods.push(method_id);
+    }
+
+    (errors.len() == error_count).then_some(methods)
+}
+
 fn resolve_free_functions(
     interner: &mut NodeInterner,
     crate_id: CrateId,
@@ -472,10 +1335,12 @@ fn resolve_function_set(
 
 fn type_check_functions(
     interner: &mut NodeInterner,
+    def_maps: &HashMap<CrateId, CrateDefMap>,
     file_func_ids: Vec<(FileId, FuncId)>,
-    errors: &mut Vec<FileDiagnostic>,
+    errors: &mut Vec<(FileId, TypeCheckError)>,
 ) {
     for (file, func) in file_func_ids {
-        extend_errors(errors, file, type_check_func(interner, func));
+        let func_errors = type_check_func(interner, def_maps, func);
+        errors.extend(func_errors.into_iter().map(|error| (file, error)));
     }
 }
@@ -2,12 +2,18 @@ use fm::FileId;
 use noirc_errors::FileDiagnostic;
 
 use crate::{
-    graph::CrateId, hir::def_collector::dc_crate::UnresolvedStruct, node_interner::StructId,
-    parser::SubModule, Ident, LetStatement, NoirFunction, NoirImpl, NoirStruct, ParsedModule,
+    graph::CrateId,
+    hir::def_collector::dc_crate::{UnresolvedEnum, UnresolvedStruct, UnresolvedTypeAlias},
+    node_interner::{EnumId, StructId, TraitId},
+    parser::SubModule,
+    Documented, Ident, ItemVisibility, LetStatement, NoirEnum, NoirFunction, NoirImpl, NoirStruct,
+    NoirTrait, NoirTraitImpl, NoirTypeAlias, ParsedModule,
 };
 
 use super::{
-    dc_crate::{DefCollector, UnresolvedFunctions, UnresolvedGlobal},
+    dc_crate::{
+        DefCollector, UnresolvedFunctions, UnresolvedGlobal, UnresolvedTrait, UnresolvedTraitImpl,
+    },
     errors::DefCollectorErrorKind,
 };
 use crate::hir::def_map::{parse_file, LocalModuleId, ModuleData, ModuleId, ModuleOrigin};
@@ -26,18 +32,21 @@ struct ModCollector<'a> {
 /// This performs the entirety of the definition collection phase of the name resolution pass.
 pub fn collect_defs(
     def_collector: &mut DefCollector,
-    ast: ParsedModule,
+    mut ast: ParsedModule,
     file_id: FileId,
     module_id: LocalModuleId,
     crate_id: CrateId,
     context: &mut Context,
     errors: &mut Vec<FileDiagnostic>,
 ) {
+    let enabled_features = &context.crate_graph[crate_id].features;
+    ast.enable_features(|feature| enabled_features.contains(feature));
+
     let mut collector = ModCollector { def_collector, file_id, module_id };
 
     // First resolve the module declarations
-    for decl in ast.module_decls {
-        collector.parse_module_declaration(context, &decl, crate_id, errors);
+    for (visibility, decl) in ast.module_decls {
+        collector.parse_module_declaration(context, decl, visibility, crate_id, errors);
     }
 
     collector.collect_submodules(context, crate_id, ast.submodules, file_id, errors);
@@ -48,6 +57,8 @@ pub fn collect_defs(
             module_id: collector.module_id,
             path: import.path,
             alias: import.alias,
+            allow_unused: import.allow_unused,
+            visibility: import.visibility,
         });
     }
 
@@ -55,19 +66,27 @@ pub fn collect_defs(
 
     collector.collect_structs(ast.types, crate_id, errors);
 
+    collector.collect_enums(ast.enums, crate_id, errors);
+
+    collector.collect_type_aliases(context, ast.type_aliases, errors);
+
+    collector.collect_traits(ast.traits, crate_id, errors);
+
     collector.collect_functions(context, ast.functions, errors);
 
     collector.collect_impls(context, ast.impls);
+
+    collector.collect_trait_impls(context, ast.trait_impls);
 }
 
 impl<'a> ModCollector<'a> {
     fn collect_globals(
         &mut self,
         context: &mut Context,
-        globals: Vec<LetStatement>,
+        globals: Vec<(ItemVisibility, Documented<LetStatement>)>,
         errors: &mut Vec<FileDiagnostic>,
     ) {
-        for global in globals {
+        for (visibility, Documented { item: global, doc_comments }) in globals {
             let name = global.pattern.name_ident().clone();
 
             // First create dummy function in the DefInterner
@@ -75,8 +94,8 @@ impl<'a> ModCollector<'a> {
             let stmt_id = context.def_interner.push_empty_global();
 
             // Add the statement to the scope so its path can be looked up later
-            let result =
-                self.def_collector.def_map.modules[self.module_id.0].declare_global(name, stmt_id);
+            let result = self.def_collector.def_map.modules[self.module_id.0]
+                .declare_global(name, stmt_id, visibility);
 
             if let Err((first_def, second_def)) = result {
                 let err = DefCollectorErrorKind::DuplicateGlobal { first_def, second_def };
@@ -88,6 +107,7 @@ impl<'a> ModCollector<'a> {
                 module_id: self.module_id,
                 stmt_id,
                 stmt_def: global,
+                doc_comments,
             });
         }
     }
@@ -109,6 +129,29 @@ impl<'a> ModCollector<'a> {
         }
     }
 
+    fn collect_trait_impls(&mut self, context: &mut Context, trait_impls: Vec<NoirTraitImpl>) {
+        for trait_impl in trait_impls {
+            let mut methods = UnresolvedFunctions { file_id: self.file_id, functions: Vec::new() };
+
+            // Unlike the methods of other impls, these are not declared in the module of their
+            // type. They are only reachable by calling the method of the trait.
+            for method in trait_impl.methods {
+                let func_id = context.def_interner.push_empty_fn();
+                context.def_interner.push_function_definition(method.name().to_owned(), func_id);
+                methods.push_fn(self.module_id, func_id, method);
+            }
+
+            self.def_collector.collected_trait_impls.push(UnresolvedTraitImpl {
+                module_id: self.module_id,
+                trait_name: trait_impl.trait_name,
+                object_type: trait_impl.object_type,
+                type_span: trait_impl.type_span,
+                generics: trait_impl.generics,
+                methods,
+            });
+        }
+    }
+
     fn collect_functions(
         &mut self,
         context: &mut Context,
@@ -120,6 +163,7 @@ impl<'a> ModCollector<'a> {
 
         for function in functions {
             let name = function.name_ident().clone();
+            let visibility = function.def().visibility;
 
             // First create dummy function in the DefInterner
             // So that we can get a FuncId
@@ -136,7 +180,7 @@ impl<'a> ModCollector<'a> {
 
             // Add function to scope/ns of the module
             let result = self.def_collector.def_map.modules[self.module_id.0]
-                .declare_function(name, func_id);
+                .declare_function(name, func_id, visibility);
 
             if let Err((first_def, second_def)) = result {
                 let error = DefCollectorErrorKind::DuplicateFunction { first_def, second_def };
@@ -159,14 +203,15 @@ impl<'a> ModCollector<'a> {
             let name = struct_definition.name.clone();
 
             // Create the corresponding module for the struct namespace
-            let id = match self.push_child_module(&name, self.file_id, false, false, errors) {
+            let id = match self.push_child_module(&name, self.file_id, None, false, errors) {
                 Some(local_id) => StructId(ModuleId { krate, local_id }),
                 None => continue,
             };
 
             // Add the struct to scope so its path can be looked up later
-            let result =
-                self.def_collector.def_map.modules[self.module_id.0].declare_struct(name, id);
+            let visibility = struct_definition.visibility;
+            let result = self.def_collector.def_map.modules[self.module_id.0]
+                .declare_struct(name, id, visibility);
 
             if let Err((first_def, second_def)) = result {
                 let err = DefCollectorErrorKind::DuplicateFunction { first_def, second_def };
@@ -183,6 +228,133 @@ impl<'a> ModCollector<'a> {
         }
     }
 
+    /// Collect any enum definitions declared within the ast.
+    /// Returns a vector of errors if any enums were already defined, or if an enum declares
+    /// the same variant twice.
+    fn collect_enums(
+        &mut self,
+        enums: Vec<NoirEnum>,
+        krate: CrateId,
+        errors: &mut Vec<FileDiagnostic>,
+    ) {
+        for enum_definition in enums {
+            let name = enum_definition.name.clone();
+
+            // Create the corresponding module for the enum namespace
+            let id = match self.push_child_module(&name, self.file_id, None, false, errors) {
+                Some(local_id) => EnumId(ModuleId { krate, local_id }),
+                None => continue,
+            };
+
+            // Add the enum to scope so its path can be looked up later
+            let visibility = enum_definition.visibility;
+            let result = self.def_collector.def_map.modules[self.module_id.0]
+                .declare_enum(name, id, visibility);
+
+            if let Err((first_def, second_def)) = result {
+                let err = DefCollectorErrorKind::DuplicateFunction { first_def, second_def };
+                errors.push(err.into_file_diagnostic(self.file_id));
+            }
+
+            for (i, (variant, _)) in enum_definition.variants.iter().enumerate() {
+                let previous = &enum_definition.variants[..i];
+                if let Some((first, _)) = previous.iter().find(|(first, _)| first == variant) {
+                    let first_def = first.clone();
+                    let second_def = variant.clone();
+                    let err = DefCollectorErrorKind::DuplicateEnumVariant { first_def, second_def };
+                    errors.push(err.into_file_diagnostic(self.file_id));
+                }
+            }
+
+            let unresolved = UnresolvedEnum {
+                file_id: self.file_id,
+                module_id: self.module_id,
+                enum_def: enum_definition,
+            };
+            self.def_collector.collected_enums.insert(id, unresolved);
+        }
+    }
+
+    /// Collect any type alias definitions declared within the ast.
+    /// Returns a vector of errors if any type aliases were already defined.
+    fn collect_type_aliases(
+        &mut self,
+        context: &mut Context,
+        type_aliases: Vec<NoirTypeAlias>,
+        errors: &mut Vec<FileDiagnostic>,
+    ) {
+        for type_alias in type_aliases {
+            let name = type_alias.name.clone();
+            let visibility = type_alias.visibility;
+
+            let unresolved = UnresolvedTypeAlias {
+                file_id: self.file_id,
+                module_id: self.module_id,
+                type_alias_def: type_alias,
+            };
+
+            let id = context.def_interner.push_type_alias(&unresolved);
+
+            // Add the type alias to scope so its path can be looked up later
+            let result = self.def_collector.def_map.modules[self.module_id.0]
+                .declare_type_alias(name, id, visibility);
+
+            if let Err((first_def, second_def)) = result {
+                let err = DefCollectorErrorKind::DuplicateFunction { first_def, second_def };
+                errors.push(err.into_file_diagnostic(self.file_id));
+            }
+
+            self.def_collector.collected_type_aliases.insert(id, unresolved);
+        }
+    }
+
+    /// Collect any trait definitions declared within the ast.
+    /// Returns a vector of errors if any traits were already defined, or if a trait declares
+    /// the same method twice.
+    fn collect_traits(
+        &mut self,
+        traits: Vec<NoirTrait>,
+        krate: CrateId,
+        errors: &mut Vec<FileDiagnostic>,
+    ) {
+        for trait_definition in traits {
+            let name = trait_definition.name.clone();
+
+            // Create the corresponding module for the trait namespace
+            let id = match self.push_child_module(&name, self.file_id, None, false, errors) {
+                Some(local_id) => TraitId(ModuleId { krate, local_id }),
+                None => continue,
+            };
+
+            // Add the trait to scope so its path can be looked up later
+            let visibility = trait_definition.visibility;
+            let result = self.def_collector.def_map.modules[self.module_id.0]
+                .declare_trait(name, id, visibility);
+
+            if let Err((first_def, second_def)) = result {
+                let err = DefCollectorErrorKind::DuplicateFunction { first_def, second_def };
+                errors.push(err.into_file_diagnostic(self.file_id));
+            }
+
+            for (i, method) in trait_definition.methods.iter().enumerate() {
+                let previous = &trait_definition.methods[..i];
+                if let Some(first) = previous.iter().find(|first| first.name == method.name) {
+                    let first_def = first.name.clone();
+                    let second_def = method.name.clone();
+                    let err = DefCollectorErrorKind::DuplicateFunction { first_def, second_def };
+                    errors.push(err.into_file_diagnostic(self.file_id));
+                }
+            }
+
+            let unresolved = UnresolvedTrait {
+                file_id: self.file_id,
+                module_id: self.module_id,
+                trait_def: trait_definition,
+            };
+            self.def_collector.collected_traits.insert(id, unresolved);
+        }
+    }
+
     fn collect_submodules(
         &mut self,
         context: &mut Context,
@@ -195,10 +367,11 @@ impl<'a> ModCollector<'a> {
             if let Some(child) = self.push_child_module(
                 &submodule.name,
                 file_id,
-                true,
+                Some(submodule.visibility),
                 submodule.is_contract,
                 errors,
             ) {
+                self.def_collector.def_map.modules[child.0].doc_comments = submodule.doc_comments;
                 collect_defs(
                     self.def_collector,
                     submodule.contents,
@@ -218,10 +391,12 @@ impl<'a> ModCollector<'a> {
     fn parse_module_declaration(
         &mut self,
         context: &mut Context,
-        mod_name: &Ident,
+        mod_decl: Documented<Ident>,
+        visibility: ItemVisibility,
         crate_id: CrateId,
         errors: &mut Vec<FileDiagnostic>,
     ) {
+        let mod_name = &mod_decl.item;
         let child_file_id =
             match context.file_manager.resolve_path(self.file_id, &mod_name.0.contents) {
                 Ok(child_file_id) => child_file_id,
@@ -238,8 +413,10 @@ impl<'a> ModCollector<'a> {
 
         // Add module into def collector and get a ModuleId
         if let Some(child_mod_id) =
-            self.push_child_module(mod_name, child_file_id, true, false, errors)
+            self.push_child_module(mod_name, child_file_id, Some(visibility), false, errors)
         {
+            let child = &mut self.def_collector.def_map.modules[child_mod_id.0];
+            child.doc_comments = mod_decl.doc_comments;
             collect_defs(
                 self.def_collector,
                 ast,
@@ -252,13 +429,14 @@ impl<'a> ModCollector<'a> {
         }
     }
 
-    /// Add a child module to the current def_map.
+    /// Add a child module to the current def_map, adding it to the scope of the parent
+    /// module with the given visibility if there is one.
     /// On error this returns None and pushes to `errors`
     fn push_child_module(
         &mut self,
         mod_name: &Ident,
         file_id: FileId,
-        add_to_parent_scope: bool,
+        visibility_in_parent: Option<ItemVisibility>,
         is_contract: bool,
         errors: &mut Vec<FileDiagnostic>,
     ) -> Option<LocalModuleId> {
@@ -278,15 +456,17 @@ impl<'a> ModCollector<'a> {
         // We do not want to do this in the case of struct modules (each struct type corresponds
         // to a child module containing its methods) since the module name should not shadow
         // the struct name.
-        if add_to_parent_scope {
+        if let Some(visibility) = visibility_in_parent {
             let mod_id = ModuleId {
                 krate: self.def_collector.def_map.krate,
                 local_id: LocalModuleId(module_id),
             };
 
-            if let Err((first_def, second_def)) =
-                modules[self.module_id.0].declare_child_module(mod_name.to_owned(), mod_id)
-            {
+            if let Err((first_def, second_def)) = modules[self.module_id.0].declare_child_module(
+                mod_name.to_owned(),
+                mod_id,
+                visibility,
+            ) {
                 let err = DefCollectorErrorKind::DuplicateModuleDecl { first_def, second_def };
                 errors.push(err.into_file_diagnostic(self.file_id));
                 return None;
@@ -0,0 +1,530 @@
+//! Generates the impls requested by the `#[derive(..)]` attribute of a struct.
+//!
+//! `Eq` and `Default` are derived as impls of the traits of the same name in the standard
+//! library. `Serialize` and `Deserialize` convert a struct to and from the array of fields
+//! it is encoded as in the ABI. Since the length of this array differs for each struct, they
+//! are derived as the `serialize` and `deserialize` methods of the struct rather than as
+//! trait impls. The generated impls are collected alongside those written in the crate, so
+//! they are resolved and type checked like any other impl.
+use std::collections::HashMap;
+
+use acvm::FieldElement;
+use fm::FileId;
+use noirc_abi::{AbiDistinctness, AbiVisibility};
+use noirc_errors::{FileDiagnostic, Span, Spanned};
+
+use crate::hir::def_map::LocalModuleId;
+use crate::hir::Context;
+use crate::node_interner::StructId;
+use crate::{
+    AssignStatement, BinaryOpKind, BlockExpression, CompTime, Expression, ExpressionKind,
+    FunctionDefinition, Ident, InfixExpression, ItemVisibility, LValue, LetStatement, NoirFunction,
+    Path, PathKind, Pattern, Signedness, Statement, Type, UnresolvedType, UnresolvedTypeExpression,
+};
+
+use super::dc_crate::{ImplMap, UnresolvedFunctions, UnresolvedStruct, UnresolvedTraitImpl};
+use super::errors::DefCollectorErrorKind;
+
+/// The traits which may be listed in a `#[derive(..)]` attribute
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum DerivableTrait {
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+}
+
+impl DerivableTrait {
+    fn lookup(name: &str) -> Option<DerivableTrait> {
+        match name {
+            "Eq" => Some(DerivableTrait::Eq),
+            "Default" => Some(DerivableTrait::Default),
+            "Serialize" => Some(DerivableTrait::Serialize),
+            "Deserialize" => Some(DerivableTrait::Deserialize),
+            _ => None,
+        }
+    }
+}
+
+/// A struct with a `#[derive(..)]` attribute. Its impls can only be generated once the
+/// types of its fields are resolved, after its definition has been consumed.
+pub(super) struct DeriveRequest {
+    struct_id: StructId,
+    file_id: FileId,
+    module_id: LocalModuleId,
+    struct_name: Ident,
+    is_generic: bool,
+    traits: Vec<Ident>,
+}
+
+pub(super) fn collect_derive_requests(
+    structs: &HashMap<StructId, UnresolvedStruct>,
+) -> Vec<DeriveRequest> {
+    structs
+        .iter()
+        .filter(|(_, typ)| !typ.struct_def.derives.is_empty())
+        .map(|(struct_id, typ)| DeriveRequest {
+            struct_id: *struct_id,
+            file_id: typ.file_id,
+            module_id: typ.module_id,
+            struct_name: typ.struct_def.name.clone(),
+            is_generic: !typ.struct_def.generics.is_empty(),
+            traits: typ.struct_def.derives.clone(),
+        })
+        .collect()
+}
+
+/// Generate the impls of each derived trait, adding them to the impls collected from the crate.
+pub(super) fn derive_impls(
+    context: &mut Context,
+    requests: Vec<DeriveRequest>,
+    collected_impls: &mut ImplMap,
+    collected_trait_impls: &mut Vec<UnresolvedTraitImpl>,
+    errors: &mut Vec<FileDiagnostic>,
+) {
+    for request in requests {
+        for trait_name in &request.traits {
+            let derivable = match DerivableTrait::lookup(&trait_name.0.contents) {
+                Some(derivable) => derivable,
+                None => {
+                    let error = DefCollectorErrorKind::UnknownDerive { name: trait_name.clone() };
+                    errors.push(error.into_file_diagnostic(request.file_id));
+                    continue;
+                }
+            };
+
+            if request.is_generic {
+                let error = DefCollectorErrorKind::DeriveOnGenericStruct {
+                    name: trait_name.clone(),
+                    struct_name: request.struct_name.to_string(),
+                };
+                errors.push(error.into_file_diagnostic(request.file_id));
+                continue;
+            }
+
+            let struct_type = context.def_interner.get_struct(request.struct_id);
+            let fields = struct_type.borrow().get_fields(&[]);
+            let builder = Builder { span: trait_name.span() };
+
+            let method = match derivable {
+                DerivableTrait::Eq => builder.derive_eq(&fields),
+                DerivableTrait::Default => builder.derive_default(&fields),
+                DerivableTrait::Serialize | DerivableTrait::Deserialize => {
+                    let scalars = match flatten_fields(&fields) {
+                        Ok(scalars) => scalars,
+                        Err((field, typ)) => {
+                            // Fields which failed to resolve have already issued an error
+                            if typ != Type::Error {
+                                let error = DefCollectorErrorKind::UnserializableField {
+                                    name: trait_name.clone(),
+                                    field,
+                                    typ: typ.to_string(),
+                                };
+                                errors.push(error.into_file_diagnostic(request.file_id));
+                            }
+                            continue;
+                        }
+                    };
+
+                    if derivable == DerivableTrait::Serialize {
+                        builder.derive_serialize(&scalars)
+                    } else {
+                        builder.derive_deserialize(&scalars)
+                    }
+                }
+            };
+
+            let mut methods = UnresolvedFunctions { file_id: request.file_id, functions: vec![] };
+            let func_id = context.def_interner.push_empty_fn();
+            context.def_interner.push_function_definition(method.name().to_owned(), func_id);
+            methods.push_fn(request.module_id, func_id, method);
+
+            let object_type =
+                UnresolvedType::Named(Path::from_ident(request.struct_name.clone()), vec![]);
+            let type_span = trait_name.span();
+
+            match derivable {
+                DerivableTrait::Eq | DerivableTrait::Default => {
+                    let module = if derivable == DerivableTrait::Eq { "ops" } else { "default" };
+                    let trait_path = builder.std_path(&[module, &trait_name.0.contents]);
+
+                    collected_trait_impls.push(UnresolvedTraitImpl {
+                        module_id: request.module_id,
+                        trait_name: trait_path,
+                        object_type,
+                        type_span,
+                        generics: vec![],
+                        methods,
+                    });
+                }
+                DerivableTrait::Serialize | DerivableTrait::Deserialize => {
+                    let key = (object_type, request.module_id);
+                    let impls = collected_impls.entry(key).or_default();
+                    impls.push((vec![], type_span, methods));
+                }
+            }
+        }
+    }
+}
+
+/// A single step from a value to one of its elements
+#[derive(Clone)]
+enum Access {
+    Member(String),
+    Index(u64),
+}
+
+/// A value inside of a struct which is serialized as a single field element
+struct Scalar {
+    /// The accesses leading from the struct to this value
+    path: Vec<Access>,
+    typ: ScalarType,
+}
+
+enum ScalarType {
+    Field,
+    Integer(Signedness, u32),
+    Bool,
+}
+
+/// Flatten the given struct fields into the scalars they are serialized as, in declaration
+/// order. On failure, returns the name of the field containing a type which cannot be
+/// serialized, along with that type.
+fn flatten_fields(fields: &[(String, Type)]) -> Result<Vec<Scalar>, (String, Type)> {
+    let mut scalars = Vec::new();
+    for (name, typ) in fields {
+        let mut path = vec![Access::Member(name.clone())];
+        flatten_type(typ, &mut path, &mut scalars).map_err(|typ| (name.clone(), typ))?;
+    }
+    Ok(scalars)
+}
+
+fn flatten_type(typ: &Type, path: &mut Vec<Access>, scalars: &mut Vec<Scalar>) -> Result<(), Type> {
+    let scalar_type = match typ.follow_bindings() {
+        Type::FieldElement(_) => ScalarType::Field,
+        Type::Integer(_, sign, bits) => ScalarType::Integer(sign, bits),
+        Type::Bool(_) => ScalarType::Bool,
+        Type::Array(length, element) => {
+            let length = length.evaluate_to_u64().ok_or_else(|| typ.clone())?;
+            for index in 0..length {
+                path.push(Access::Index(index));
+                flatten_type(&element, path, scalars)?;
+                path.pop();
+            }
+            return Ok(());
+        }
+        Type::Tuple(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                path.push(Access::Member(index.to_string()));
+                flatten_type(element, path, scalars)?;
+                path.pop();
+            }
+            return Ok(());
+        }
+        Type::Struct(definition, generics) => {
+            for (name, field) in definition.borrow().get_fields(&generics) {
+                path.push(Access::Member(name));
+                flatten_type(&field, path, scalars)?;
+                path.pop();
+            }
+            return Ok(());
+        }
+        other => return Err(other),
+    };
+
+    scalars.push(Scalar { path: path.to_vec(), typ: scalar_type });
+    Ok(())
+}
+
+/// Builds the AST of derived methods. Every node is given the span of the derived trait's
+/// name so that any error within the generated code points to the `#[derive(..)]` attribute.
+struct Builder {
+    span: Span,
+}
+
+impl Builder {
+    /// fn eq(self, other: Self) -> bool { (self.a == other.a) & (self.b == other.b) & .. }
+    fn derive_eq(&self, fields: &[(String, Type)]) -> NoirFunction {
+        let mut comparisons = Vec::new();
+        for (name, typ) in fields {
+            let lhs = self.member(self.variable("self"), name);
+            let rhs = self.member(self.variable("other"), name);
+            self.push_comparisons(typ, lhs, rhs, &mut comparisons);
+        }
+
+        let body = comparisons
+            .into_iter()
+            .reduce(|lhs, rhs| self.infix(lhs, BinaryOpKind::And, rhs))
+            .unwrap_or_else(|| self.expression(ExpressionKind::boolean(true)));
+
+        let parameters = vec![("self", self.self_type()), ("other", self.self_type())];
+        let return_type = UnresolvedType::Bool(CompTime::No(None));
+        self.function("eq", ItemVisibility::Private, parameters, return_type, vec![body])
+    }
+
+    /// Tuples, and arrays of structs or tuples, cannot be compared with `==` directly so they
+    /// are compared element by element instead.
+    fn push_comparisons(
+        &self,
+        typ: &Type,
+        lhs: Expression,
+        rhs: Expression,
+        comparisons: &mut Vec<Expression>,
+    ) {
+        match typ.follow_bindings() {
+            Type::Tuple(elements) => {
+                for (index, element) in elements.iter().enumerate() {
+                    let access = Access::Member(index.to_string());
+                    let lhs = self.access(lhs.clone(), &access);
+                    let rhs = self.access(rhs.clone(), &access);
+                    self.push_comparisons(element, lhs, rhs, comparisons);
+                }
+            }
+            Type::Array(length, element) if contains_aggregate(&element) => {
+                match length.evaluate_to_u64() {
+                    Some(length) => {
+                        for index in 0..length {
+                            let lhs = self.access(lhs.clone(), &Access::Index(index));
+                            let rhs = self.access(rhs.clone(), &Access::Index(index));
+                            self.push_comparisons(&element, lhs, rhs, comparisons);
+                        }
+                    }
+                    None => comparisons.push(self.infix(lhs, BinaryOpKind::Equal, rhs)),
+                }
+            }
+            _ => comparisons.push(self.infix(lhs, BinaryOpKind::Equal, rhs)),
+        }
+    }
+
+    /// fn default() -> Self { Self { a: .., b: .. } }
+    fn derive_default(&self, fields: &[(String, Type)]) -> NoirFunction {
+        let fields =
+            fields.iter().map(|(name, typ)| (self.ident(name), self.default_value(typ))).collect();
+
+        let self_path = Path::from_single("Self".to_owned(), self.span);
+        let body = self.expression(ExpressionKind::constructor((self_path, fields)));
+        self.function("default", ItemVisibility::Private, vec![], self.self_type(), vec![body])
+    }
+
+    /// Values containing a struct default to the `Default` impl of that struct, while any
+    /// other value defaults to zero.
+    fn default_value(&self, typ: &Type) -> Expression {
+        match typ.follow_bindings() {
+            Type::Struct(..) | Type::Enum(..) => {
+                let method = self.std_path(&["default", "Default", "default"]);
+                let method = self.expression(ExpressionKind::Variable(method));
+                Expression::call(method, vec![], self.span)
+            }
+            Type::Array(length, element) if contains_user_type(&element) => {
+                match length.evaluate_to_u64() {
+                    Some(length) => {
+                        let element = self.default_value(&element);
+                        let length = self.integer(length);
+                        self.expression(ExpressionKind::repeated_array(element, length))
+                    }
+                    None => self.zeroed(),
+                }
+            }
+            Type::Tuple(elements) if elements.iter().any(contains_user_type) => {
+                let elements = elements.iter().map(|element| self.default_value(element));
+                self.expression(ExpressionKind::Tuple(elements.collect()))
+            }
+            _ => self.zeroed(),
+        }
+    }
+
+    /// pub fn serialize(self) -> [Field; N] { [self.a, self.b as Field, ..] }
+    fn derive_serialize(&self, scalars: &[Scalar]) -> NoirFunction {
+        let elements = scalars.iter().map(|scalar| {
+            let value = scalar
+                .path
+                .iter()
+                .fold(self.variable("self"), |value, access| self.access(value, access));
+
+            match scalar.typ {
+                ScalarType::Field => value,
+                ScalarType::Integer(..) | ScalarType::Bool => Expression::cast(
+                    value,
+                    UnresolvedType::FieldElement(CompTime::No(None)),
+                    self.span,
+                ),
+            }
+        });
+
+        let body = self.expression(ExpressionKind::array(elements.collect()));
+        let parameters = vec![("self", self.self_type())];
+        let return_type = self.field_array_type(scalars.len());
+        self.function("serialize", ItemVisibility::Public, parameters, return_type, vec![body])
+    }
+
+    /// pub fn deserialize(fields: [Field; N]) -> Self {
+    ///     let mut result: Self = dep::std::unsafe::zeroed();
+    ///     result.a = fields[0];
+    ///     result.b = fields[1] as u8;
+    ///     ..
+    ///     result
+    /// }
+    fn derive_deserialize(&self, scalars: &[Scalar]) -> NoirFunction {
+        let mut statements = Vec::with_capacity(scalars.len() + 2);
+
+        statements.push(Statement::Let(LetStatement {
+            pattern: Pattern::Mutable(
+                Box::new(Pattern::Identifier(self.ident("result"))),
+                self.span,
+            ),
+            r#type: self.self_type(),
+            expression: self.zeroed(),
+        }));
+
+        for (index, scalar) in scalars.iter().enumerate() {
+            let lvalue =
+                scalar.path.iter().fold(LValue::Ident(self.ident("result")), |lvalue, access| {
+                    match access {
+                        Access::Member(name) => LValue::MemberAccess {
+                            object: Box::new(lvalue),
+                            field_name: self.ident(name),
+                        },
+                        Access::Index(index) => {
+                            LValue::Index { array: Box::new(lvalue), index: self.integer(*index) }
+                        }
+                    }
+                });
+
+            let field = self.access(self.variable("fields"), &Access::Index(index as u64));
+            let expression = match scalar.typ {
+                ScalarType::Field => field,
+                ScalarType::Integer(sign, bits) => Expression::cast(
+                    field,
+                    UnresolvedType::Integer(CompTime::No(None), sign, bits),
+                    self.span,
+                ),
+                ScalarType::Bool => self.infix(field, BinaryOpKind::NotEqual, self.integer(0)),
+            };
+
+            statements.push(Statement::Assign(AssignStatement { lvalue, expression }));
+        }
+
+        statements.push(Statement::Expression(self.variable("result")));
+
+        let parameters = vec![("fields", self.field_array_type(scalars.len()))];
+        self.function(
+            "deserialize",
+            ItemVisibility::Public,
+            parameters,
+            self.self_type(),
+            statements,
+        )
+    }
+
+    fn function(
+        &self,
+        name: &str,
+        visibility: ItemVisibility,
+        parameters: Vec<(&str, UnresolvedType)>,
+        return_type: UnresolvedType,
+        body: Vec<Statement>,
+    ) -> NoirFunction {
+        let parameters = parameters
+            .into_iter()
+            .map(|(name, typ)| (Pattern::Identifier(self.ident(name)), typ, AbiVisibility::Private))
+            .collect();
+
+        NoirFunction::normal(FunctionDefinition {
+            name: self.ident(name),
+            visibility,
+            doc_comments: Vec::new(),
+            attribute: None,
+            allow_unused: false,
+            is_open: false,
+            is_unconstrained: false,
+            generics: vec![],
+            trait_constraints: vec![],
+            parameters,
+            body: BlockExpression(body),
+            span: self.span,
+            full_span: self.span,
+            return_type,
+            return_visibility: AbiVisibility::Private,
+            return_distinctness: AbiDistinctness::DuplicationAllowed,
+        })
+    }
+
+    fn expression(&self, kind: ExpressionKind) -> Expression {
+        Expression::new(kind, self.span)
+    }
+
+    fn ident(&self, name: &str) -> Ident {
+        Ident::new(name.to_owned(), self.span)
+    }
+
+    fn variable(&self, name: &str) -> Expression {
+        self.expression(ExpressionKind::Variable(Path::from_single(name.to_owned(), self.span)))
+    }
+
+    fn integer(&self, value: u64) -> Expression {
+        self.expression(ExpressionKind::integer(FieldElement::from(value as u128)))
+    }
+
+    fn member(&self, lhs: Expression, name: &str) -> Expression {
+        Expression::member_access_or_method_call(lhs, (self.ident(name), None), self.span)
+    }
+
+    fn access(&self, value: Expression, access: &Access) -> Expression {
+        match access {
+            Access::Member(name) => self.member(value, name),
+            Access::Index(index) => Expression::index(value, self.integer(*index), self.span),
+        }
+    }
+
+    fn infix(&self, lhs: Expression, operator: BinaryOpKind, rhs: Expression) -> Expression {
+        let operator = Spanned::from(self.span, operator);
+        let infix = InfixExpression { lhs, operator, rhs };
+        self.expression(ExpressionKind::Infix(Box::new(infix)))
+    }
+
+    /// dep::std::unsafe::zeroed()
+    fn zeroed(&self) -> Expression {
+        let function =
+            self.expression(ExpressionKind::Variable(self.std_path(&["unsafe", "zeroed"])));
+        Expression::call(function, vec![], self.span)
+    }
+
+    /// A path to an item of the standard library, e.g. `dep::std::ops::Eq`
+    fn std_path(&self, segments: &[&str]) -> Path {
+        let segments = std::iter::once("std").chain(segments.iter().copied());
+        Path {
+            segments: segments.map(|segment| self.ident(segment)).collect(),
+            kind: PathKind::Dep,
+        }
+    }
+
+    fn self_type(&self) -> UnresolvedType {
+        UnresolvedType::Named(Path::from_single("Self".to_owned(), self.span), vec![])
+    }
+
+    fn field_array_type(&self, length: usize) -> UnresolvedType {
+        let length = UnresolvedTypeExpression::Constant(length as u64, self.span);
+        let element = UnresolvedType::FieldElement(CompTime::No(None));
+        UnresolvedType::Array(Some(length), Box::new(element))
+    }
+}
+
+/// True if the given type is or contains a struct or enum, which are defaulted through their
+/// `Default` impl rather than zeroed.
+fn contains_user_type(typ: &Type) -> bool {
+    match typ.follow_bindings() {
+        Type::Struct(..) | Type::Enum(..) => true,
+        Type::Array(_, element) => contains_user_type(&element),
+        Type::Tuple(elements) => elements.iter().any(contains_user_type),
+        _ => false,
+    }
+}
+
+/// True if the given type is or contains a struct, enum, or tuple
+fn contains_aggregate(typ: &Type) -> bool {
+    match typ.follow_bindings() {
+        Type::Struct(..) | Type::Enum(..) | Type::Tuple(_) => true,
+        Type::Array(_, element) => contains_aggregate(&element),
+        _ => false,
+    }
+}
@@ -1,9 +1,10 @@
 use crate::hir::resolution::import::PathResolutionError;
 use crate::Ident;
 
+use noirc_errors::registry::{self, ErrorCode};
 use noirc_errors::CustomDiagnostic as Diagnostic;
 use noirc_errors::FileDiagnostic;
-use noirc_errors::Span;
+use noirc_errors::{Location, Span};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -16,15 +17,70 @@ pub enum DefCollectorErrorKind {
     DuplicateImport { first_def: Ident, second_def: Ident },
     #[error("duplicate global found in namespace")]
     DuplicateGlobal { first_def: Ident, second_def: Ident },
+    #[error("duplicate enum variant")]
+    DuplicateEnumVariant { first_def: Ident, second_def: Ident },
+    #[error("cyclic type alias")]
+    CyclicTypeAlias { name: Ident },
+    #[error("cyclic global")]
+    CyclicGlobal { name: Ident },
     #[error("unresolved import")]
     UnresolvedModuleDecl { mod_name: Ident },
     #[error("path resolution error")]
     PathResolutionError(PathResolutionError),
     #[error("Non-struct type used in impl")]
     NonStructTypeInImpl { span: Span },
+    #[error("Trait impl on a generic type")]
+    TraitImplOnGeneric { span: Span },
+    #[error("Overlapping trait impls")]
+    OverlappingTraitImpl { trait_name: String, typ: String, span: Span, existing_impl: Location },
+    #[error("Missing trait method")]
+    MissingTraitMethod {
+        trait_name: String,
+        method_name: String,
+        span: Span,
+        declaration: Location,
+    },
+    #[error("Method is not a member of the trait")]
+    MethodNotInTrait { trait_name: String, method: Ident },
+    #[error("Method signature does not match the trait")]
+    TraitMethodSignatureMismatch {
+        method: Ident,
+        expected: String,
+        found: String,
+        declaration: Location,
+    },
+    #[error("Unknown trait in derive")]
+    UnknownDerive { name: Ident },
+    #[error("Derive on a generic struct")]
+    DeriveOnGenericStruct { name: Ident, struct_name: String },
+    #[error("Struct field cannot be serialized")]
+    UnserializableField { name: Ident, field: String, typ: String },
 }
 
 impl DefCollectorErrorKind {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            DefCollectorErrorKind::DuplicateFunction { .. } => registry::E0015,
+            DefCollectorErrorKind::DuplicateModuleDecl { .. } => registry::E0016,
+            DefCollectorErrorKind::DuplicateImport { .. } => registry::E0017,
+            DefCollectorErrorKind::DuplicateGlobal { .. } => registry::E0018,
+            DefCollectorErrorKind::DuplicateEnumVariant { .. } => registry::E0019,
+            DefCollectorErrorKind::CyclicTypeAlias { .. } => registry::E0020,
+            DefCollectorErrorKind::CyclicGlobal { .. } => registry::E0021,
+            DefCollectorErrorKind::UnresolvedModuleDecl { .. } => registry::E0022,
+            DefCollectorErrorKind::PathResolutionError(error) => error.code(),
+            DefCollectorErrorKind::NonStructTypeInImpl { .. } => registry::E0023,
+            DefCollectorErrorKind::TraitImplOnGeneric { .. } => registry::E0024,
+            DefCollectorErrorKind::OverlappingTraitImpl { .. } => registry::E0025,
+            DefCollectorErrorKind::MissingTraitMethod { .. } => registry::E0026,
+            DefCollectorErrorKind::MethodNotInTrait { .. } => registry::E0027,
+            DefCollectorErrorKind::TraitMethodSignatureMismatch { .. } => registry::E0028,
+            DefCollectorErrorKind::UnknownDerive { .. } => registry::E0029,
+            DefCollectorErrorKind::DeriveOnGenericStruct { .. } => registry::E0030,
+            DefCollectorErrorKind::UnserializableField { .. } => registry::E0031,
+        }
+    }
+
     pub fn into_file_diagnostic(self, file: fm::FileId) -> FileDiagnostic {
         Diagnostic::from(self).in_file(file)
     }
@@ -32,7 +88,8 @@ impl DefCollectorErrorKind {
 
 impl From<DefCollectorErrorKind> for Diagnostic {
     fn from(error: DefCollectorErrorKind) -> Diagnostic {
-        match error {
+        let code = error.code();
+        let diagnostic = match error {
             DefCollectorErrorKind::DuplicateFunction { first_def, second_def } => {
                 let first_span = first_def.0.span();
                 let second_span = second_def.0.span();
@@ -85,6 +142,39 @@ impl From<DefCollectorErrorKind> for Diagnostic {
                 diag.add_secondary("second global declaration found here".to_string(), second_span);
                 diag
             }
+            DefCollectorErrorKind::DuplicateEnumVariant { first_def, second_def } => {
+                let first_span = first_def.0.span();
+                let second_span = second_def.0.span();
+                let variant_name = &first_def.0.contents;
+
+                let mut diag = Diagnostic::simple_error(
+                    format!("the variant `{variant_name}` is defined multiple times"),
+                    "first variant found here".to_string(),
+                    first_span,
+                );
+                diag.add_secondary("second variant found here".to_string(), second_span);
+                diag
+            }
+            DefCollectorErrorKind::CyclicTypeAlias { name } => {
+                let span = name.0.span();
+                let name = &name.0.contents;
+
+                Diagnostic::simple_error(
+                    format!("type alias `{name}` is defined in terms of itself"),
+                    "cycle in type alias definition".to_string(),
+                    span,
+                )
+            }
+            DefCollectorErrorKind::CyclicGlobal { name } => {
+                let span = name.0.span();
+                let name = &name.0.contents;
+
+                Diagnostic::simple_error(
+                    format!("global `{name}` is defined in terms of itself"),
+                    "cycle in global definition".to_string(),
+                    span,
+                )
+            }
             DefCollectorErrorKind::UnresolvedModuleDecl { mod_name } => {
                 let span = mod_name.0.span();
                 let mod_name = &mod_name.0.contents;
@@ -101,6 +191,89 @@ impl From<DefCollectorErrorKind> for Diagnostic {
                 "Only struct types may have implementation methods".into(),
                 span,
             ),
-        }
+            DefCollectorErrorKind::TraitImplOnGeneric { span } => Diagnostic::simple_error(
+                "Traits cannot be implemented for a generic type".into(),
+                "Implement the trait for each concrete type instead".into(),
+                span,
+            ),
+            DefCollectorErrorKind::OverlappingTraitImpl { trait_name, typ, span, existing_impl } => {
+                let mut diag = Diagnostic::simple_error(
+                    format!("Conflicting impls of trait {trait_name} for type {typ}"),
+                    "An impl of the trait already applies to this type".into(),
+                    span,
+                );
+                diag.add_secondary_in_file(
+                    "The existing impl is here".into(),
+                    existing_impl.span,
+                    existing_impl.file,
+                );
+                diag
+            }
+            DefCollectorErrorKind::MissingTraitMethod {
+                trait_name,
+                method_name,
+                span,
+                declaration,
+            } => {
+                let mut diag = Diagnostic::simple_error(
+                    format!("Method {method_name} of trait {trait_name} is not implemented"),
+                    format!("Missing definition of {method_name}"),
+                    span,
+                );
+                diag.add_secondary_in_file(
+                    format!("{method_name} is declared by the trait here"),
+                    declaration.span,
+                    declaration.file,
+                );
+                diag
+            }
+            DefCollectorErrorKind::MethodNotInTrait { trait_name, method } => {
+                let method_name = &method.0.contents;
+                Diagnostic::simple_error(
+                    format!("Method {method_name} is not a member of trait {trait_name}"),
+                    "Not a member of the trait".into(),
+                    method.0.span(),
+                )
+            }
+            DefCollectorErrorKind::TraitMethodSignatureMismatch {
+                method,
+                expected,
+                found,
+                declaration,
+            } => {
+                let method_name = &method.0.contents;
+                let mut diag = Diagnostic::simple_error(
+                    format!("Method {method_name} has an incompatible type for the trait"),
+                    format!("Expected type {expected}, found type {found}"),
+                    method.0.span(),
+                );
+                diag.add_secondary_in_file(
+                    format!("{method_name} is declared by the trait here"),
+                    declaration.span,
+                    declaration.file,
+                );
+                diag
+            }
+            DefCollectorErrorKind::UnknownDerive { name } => Diagnostic::simple_error(
+                format!("Cannot derive unknown trait {name}"),
+                "Only Eq, Default, Serialize and Deserialize can be derived".into(),
+                name.span(),
+            ),
+            DefCollectorErrorKind::DeriveOnGenericStruct { name, struct_name } => {
+                Diagnostic::simple_error(
+                    format!("Cannot derive {name} for generic struct {struct_name}"),
+                    "Derived impls are only supported for structs without generics".into(),
+                    name.span(),
+                )
+            }
+            DefCollectorErrorKind::UnserializableField { name, field, typ } => {
+                Diagnostic::simple_error(
+                    format!("Cannot derive {name} since field {field} has type {typ}"),
+                    "Only fields, integers, booleans, and arrays, tuples and structs of these can be serialized".into(),
+                    name.span(),
+                )
+            }
+        };
+        diagnostic.with_code(code)
     }
 }
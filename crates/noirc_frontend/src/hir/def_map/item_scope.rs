@@ -1,16 +1,11 @@
 use super::{namespace::PerNs, ModuleDefId, ModuleId};
-use crate::{node_interner::FuncId, Ident};
+use crate::{node_interner::FuncId, Ident, ItemVisibility};
 use std::collections::{hash_map::Entry, HashMap};
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-pub enum Visibility {
-    Public,
-}
-
 #[derive(Default, Debug, PartialEq, Eq)]
 pub struct ItemScope {
-    types: HashMap<Ident, (ModuleDefId, Visibility)>,
-    values: HashMap<Ident, (ModuleDefId, Visibility)>,
+    types: HashMap<Ident, (ModuleDefId, ItemVisibility)>,
+    values: HashMap<Ident, (ModuleDefId, ItemVisibility)>,
 
     defs: Vec<ModuleDefId>,
 }
@@ -20,8 +15,9 @@ impl ItemScope {
         &mut self,
         name: Ident,
         mod_def: ModuleDefId,
+        visibility: ItemVisibility,
     ) -> Result<(), (Ident, Ident)> {
-        self.add_item_to_namespace(name, mod_def)?;
+        self.add_item_to_namespace(name, mod_def, visibility)?;
         self.defs.push(mod_def);
         Ok(())
     }
@@ -33,13 +29,14 @@ impl ItemScope {
         &mut self,
         name: Ident,
         mod_def: ModuleDefId,
+        visibility: ItemVisibility,
     ) -> Result<(), (Ident, Ident)> {
-        let add_item = |map: &mut HashMap<Ident, (ModuleDefId, Visibility)>| {
+        let add_item = |map: &mut HashMap<Ident, (ModuleDefId, ItemVisibility)>| {
             if let Entry::Occupied(o) = map.entry(name.clone()) {
                 let old_ident = o.key();
                 Err((old_ident.clone(), name))
             } else {
-                map.insert(name, (mod_def, Visibility::Public));
+                map.insert(name, (mod_def, visibility));
                 Ok(())
             }
         };
@@ -48,6 +45,9 @@ impl ItemScope {
             ModuleDefId::ModuleId(_) => add_item(&mut self.types),
             ModuleDefId::FunctionId(_) => add_item(&mut self.values),
             ModuleDefId::TypeId(_) => add_item(&mut self.types),
+            ModuleDefId::EnumId(_) => add_item(&mut self.types),
+            ModuleDefId::TypeAliasId(_) => add_item(&mut self.types),
+            ModuleDefId::TraitId(_) => add_item(&mut self.types),
             ModuleDefId::GlobalId(_) => add_item(&mut self.values),
         }
     }
@@ -76,11 +76,11 @@ impl ItemScope {
         self.defs.clone()
     }
 
-    pub fn types(&self) -> &HashMap<Ident, (ModuleDefId, Visibility)> {
+    pub fn types(&self) -> &HashMap<Ident, (ModuleDefId, ItemVisibility)> {
         &self.types
     }
 
-    pub fn values(&self) -> &HashMap<Ident, (ModuleDefId, Visibility)> {
+    pub fn values(&self) -> &HashMap<Ident, (ModuleDefId, ItemVisibility)> {
         &self.values
     }
 }
@@ -1,9 +1,10 @@
 use crate::graph::CrateId;
 use crate::hir::def_collector::dc_crate::DefCollector;
 use crate::hir::Context;
-use crate::node_interner::{FuncId, NodeInterner};
+use crate::node_interner::{FuncId, NodeInterner, TraitId};
 use crate::parser::{parse_program, ParsedModule};
 use crate::token::Attribute;
+use crate::OverflowMode;
 use arena::{Arena, Index};
 use fm::{FileId, FileManager};
 use noirc_errors::FileDiagnostic;
@@ -57,6 +58,9 @@ pub struct CrateDefMap {
     pub(crate) krate: CrateId,
 
     pub(crate) extern_prelude: HashMap<String, ModuleId>,
+
+    /// How the integer arithmetic of the crate behaves on overflow
+    pub(crate) overflow_mode: OverflowMode,
 }
 
 impl CrateDefMap {
@@ -93,7 +97,7 @@ impl CrateDefMap {
                 .to_str()
                 .expect("expected std path to be convertible to str");
             assert_eq!(path_as_str, "std/lib");
-            ast.module_decls.retain(|ident| ident.0.contents != "slice");
+            ast.module_decls.retain(|(_, decl)| decl.item.0.contents != "slice");
         }
 
         // Allocate a default Module for the root, giving it a ModuleId
@@ -106,6 +110,7 @@ impl CrateDefMap {
             modules,
             krate: crate_id,
             extern_prelude: HashMap::new(),
+            overflow_mode: context.crate_graph[crate_id].overflow_mode,
         };
 
         // Now we want to populate the CrateDefMap using the DefCollector
@@ -131,6 +136,31 @@ impl CrateDefMap {
         root_module.find_func_with_name(&MAIN_FUNCTION.into())
     }
 
+    /// Find the function at the given path from the root module of the crate, such as
+    /// `sha256::digest`.
+    pub fn find_function_by_path(&self, path: &str) -> Option<FuncId> {
+        let (module, name) = self.find_parent_module_by_path(path)?;
+        self[module].find_func_with_name(&name.into())
+    }
+
+    /// Find a trait from a path of the form `a::b::Trait`, relative to the crate root
+    pub fn find_trait_by_path(&self, path: &str) -> Option<TraitId> {
+        let (module, name) = self.find_parent_module_by_path(path)?;
+        let (id, _) = self[module].find_name(&name.into()).types?;
+        id.as_trait()
+    }
+
+    /// Returns the module containing the item at the given path, along with the item's name
+    fn find_parent_module_by_path<'a>(&self, path: &'a str) -> Option<(LocalModuleId, &'a str)> {
+        let mut segments: Vec<&str> = path.split("::").collect();
+        let name = segments.pop()?;
+        let mut module = self.root;
+        for segment in segments {
+            module = *self[module].children.get(&segment.into())?;
+        }
+        Some((module, name))
+    }
+
     pub fn root_file_id(&self) -> FileId {
         let root_module = &self.modules()[self.root.0];
         root_module.origin.into()
@@ -3,8 +3,8 @@ use std::collections::HashMap;
 use fm::FileId;
 
 use crate::{
-    node_interner::{FuncId, StmtId, StructId},
-    Ident,
+    node_interner::{EnumId, FuncId, StmtId, StructId, TraitId, TypeAliasId},
+    Ident, ItemVisibility,
 };
 
 use super::{ItemScope, LocalModuleId, ModuleDefId, ModuleId, PerNs};
@@ -27,6 +27,9 @@ pub struct ModuleData {
 
     /// True if this module is a `contract Foo { ... }` module containing contract functions
     pub is_contract: bool,
+
+    /// The lines of the `///` doc comments written above the module's declaration
+    pub doc_comments: Vec<String>,
 }
 
 impl ModuleData {
@@ -42,49 +45,110 @@ impl ModuleData {
             definitions: ItemScope::default(),
             origin,
             is_contract,
+            doc_comments: Vec::new(),
         }
     }
 
-    fn declare(&mut self, name: Ident, item_id: ModuleDefId) -> Result<(), (Ident, Ident)> {
-        self.scope.add_definition(name.clone(), item_id)?;
+    fn declare(
+        &mut self,
+        name: Ident,
+        item_id: ModuleDefId,
+        visibility: ItemVisibility,
+    ) -> Result<(), (Ident, Ident)> {
+        self.scope.add_definition(name.clone(), item_id, visibility)?;
 
         // definitions is a subset of self.scope so it is expected if self.scope.define_func_def
         // returns without error, so will self.definitions.define_func_def.
-        self.definitions.add_definition(name, item_id)
+        self.definitions.add_definition(name, item_id, visibility)
     }
 
-    pub fn declare_function(&mut self, name: Ident, id: FuncId) -> Result<(), (Ident, Ident)> {
-        self.declare(name, id.into())
+    pub fn declare_function(
+        &mut self,
+        name: Ident,
+        id: FuncId,
+        visibility: ItemVisibility,
+    ) -> Result<(), (Ident, Ident)> {
+        self.declare(name, id.into(), visibility)
     }
 
-    pub fn declare_global(&mut self, name: Ident, id: StmtId) -> Result<(), (Ident, Ident)> {
-        self.declare(name, id.into())
+    pub fn declare_global(
+        &mut self,
+        name: Ident,
+        id: StmtId,
+        visibility: ItemVisibility,
+    ) -> Result<(), (Ident, Ident)> {
+        self.declare(name, id.into(), visibility)
     }
 
-    pub fn declare_struct(&mut self, name: Ident, id: StructId) -> Result<(), (Ident, Ident)> {
-        self.declare(name, ModuleDefId::TypeId(id))
+    pub fn declare_struct(
+        &mut self,
+        name: Ident,
+        id: StructId,
+        visibility: ItemVisibility,
+    ) -> Result<(), (Ident, Ident)> {
+        self.declare(name, ModuleDefId::TypeId(id), visibility)
+    }
+
+    pub fn declare_enum(
+        &mut self,
+        name: Ident,
+        id: EnumId,
+        visibility: ItemVisibility,
+    ) -> Result<(), (Ident, Ident)> {
+        self.declare(name, ModuleDefId::EnumId(id), visibility)
+    }
+
+    pub fn declare_type_alias(
+        &mut self,
+        name: Ident,
+        id: TypeAliasId,
+        visibility: ItemVisibility,
+    ) -> Result<(), (Ident, Ident)> {
+        self.declare(name, ModuleDefId::TypeAliasId(id), visibility)
+    }
+
+    pub fn declare_trait(
+        &mut self,
+        name: Ident,
+        id: TraitId,
+        visibility: ItemVisibility,
+    ) -> Result<(), (Ident, Ident)> {
+        self.declare(name, ModuleDefId::TraitId(id), visibility)
     }
 
     pub fn declare_child_module(
         &mut self,
         name: Ident,
         child_id: ModuleId,
+        visibility: ItemVisibility,
     ) -> Result<(), (Ident, Ident)> {
-        self.declare(name, child_id.into())
+        self.declare(name, child_id.into(), visibility)
     }
 
     pub fn find_func_with_name(&self, name: &Ident) -> Option<FuncId> {
         self.scope.find_func_with_name(name)
     }
 
-    pub fn import(&mut self, name: Ident, id: ModuleDefId) -> Result<(), (Ident, Ident)> {
-        self.scope.add_item_to_namespace(name, id)
+    /// Imports are private to the importing module unless they are declared with `pub use`,
+    /// in which case they are re-exported with the given visibility.
+    pub fn import(
+        &mut self,
+        name: Ident,
+        id: ModuleDefId,
+        visibility: ItemVisibility,
+    ) -> Result<(), (Ident, Ident)> {
+        self.scope.add_item_to_namespace(name, id, visibility)
     }
 
     pub fn find_name(&self, name: &Ident) -> PerNs {
         self.scope.find_name(name)
     }
 
+    /// Returns every definition visible to this module, including its imports.
+    pub fn scope(&self) -> &ItemScope {
+        &self.scope
+    }
+
     /// Return an iterator over all definitions defined within this module,
     /// excluding any type definitions.
     pub fn value_definitions(&self) -> impl Iterator<Item = ModuleDefId> + '_ {
@@ -0,0 +1,150 @@
+//! Semantic highlighting by the language server.
+//!
+//! Names are highlighted by what the resolver and type checker recorded them as referring to,
+//! so that editors can tell types from generics, mutable from immutable variables, and calls
+//! to unconstrained functions and oracles from other calls, which text alone can't tell apart.
+use fm::FileId;
+use noirc_errors::{Location, Span};
+
+use super::def_map::ModuleDefId;
+use super::Context;
+use crate::node_interner::{DefinitionKind, FuncId, ReferenceId};
+use crate::FunctionKind;
+
+/// What a highlighted name refers to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HighlightKind {
+    Module,
+    Struct,
+    Enum,
+    TypeAlias,
+    Trait,
+    Generic,
+    Variable,
+    Global,
+    Field,
+    Function,
+    Method,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlight {
+    pub span: Span,
+    pub kind: HighlightKind,
+    /// True if the name is where its variable, function, struct or field is declared
+    pub is_declaration: bool,
+    /// True if the name refers to a mutable variable
+    pub is_mutable: bool,
+    /// True if the name refers to an unconstrained function
+    pub is_unconstrained: bool,
+    /// True if the name refers to an oracle, whose result is not constrained by the circuit
+    pub is_oracle: bool,
+}
+
+impl Highlight {
+    fn new(span: Span, kind: HighlightKind) -> Highlight {
+        Highlight {
+            span,
+            kind,
+            is_declaration: false,
+            is_mutable: false,
+            is_unconstrained: false,
+            is_oracle: false,
+        }
+    }
+}
+
+impl Context {
+    /// Returns the highlighted names of the given file, ordered by where they are written and
+    /// without overlapping each other.
+    pub fn highlights(&self, file: FileId) -> Vec<Highlight> {
+        let interner = &self.def_interner;
+
+        let declarations =
+            interner.declarations_in_file(file).into_iter().filter_map(|(reference, location)| {
+                let highlight = self.reference_highlight(reference, location.span)?;
+                Some(Highlight { is_declaration: true, ..highlight })
+            });
+        let references =
+            interner.references_in_file(file).into_iter().filter_map(|(location, reference)| {
+                let span = self.last_segment(location)?;
+                self.reference_highlight(reference, span)
+            });
+        let generics = interner
+            .generics_in_file(file)
+            .into_iter()
+            .map(|location| Highlight::new(location.span, HighlightKind::Generic));
+
+        // Declarations come first so that they are kept over any reference at the same name
+        let mut highlights: Vec<_> = declarations.chain(references).chain(generics).collect();
+        highlights.sort_by_key(|highlight| highlight.span.start());
+
+        let mut end = 0;
+        highlights.retain(|highlight| {
+            let overlaps = highlight.span.start() < end;
+            end = end.max(highlight.span.end());
+            !overlaps
+        });
+        highlights
+    }
+
+    fn reference_highlight(&self, reference: ReferenceId, span: Span) -> Option<Highlight> {
+        let interner = &self.def_interner;
+        let kind = match reference {
+            ReferenceId::Definition(id) => {
+                let definition = interner.definition(id);
+                match definition.kind {
+                    DefinitionKind::Function(func_id) => {
+                        return self.function_highlight(func_id, span)
+                    }
+                    DefinitionKind::Global(_) => HighlightKind::Global,
+                    DefinitionKind::GenericType(_) => HighlightKind::Generic,
+                    DefinitionKind::Local(_) => {
+                        let highlight = Highlight::new(span, HighlightKind::Variable);
+                        return Some(Highlight { is_mutable: definition.mutable, ..highlight });
+                    }
+                }
+            }
+            ReferenceId::Item(ModuleDefId::FunctionId(func_id)) => {
+                return self.function_highlight(func_id, span)
+            }
+            ReferenceId::Item(ModuleDefId::ModuleId(_)) => HighlightKind::Module,
+            ReferenceId::Item(ModuleDefId::TypeId(_)) => HighlightKind::Struct,
+            ReferenceId::Item(ModuleDefId::EnumId(_)) => HighlightKind::Enum,
+            ReferenceId::Item(ModuleDefId::TypeAliasId(_)) => HighlightKind::TypeAlias,
+            ReferenceId::Item(ModuleDefId::TraitId(_)) => HighlightKind::Trait,
+            ReferenceId::Item(ModuleDefId::GlobalId(_)) => HighlightKind::Global,
+            ReferenceId::Field(..) => HighlightKind::Field,
+        };
+        Some(Highlight::new(span, kind))
+    }
+
+    fn function_highlight(&self, func_id: FuncId, span: Span) -> Option<Highlight> {
+        let interner = &self.def_interner;
+        let meta = interner.try_function_meta(&func_id)?;
+        let kind = if interner.method_struct(func_id).is_some() {
+            HighlightKind::Method
+        } else {
+            HighlightKind::Function
+        };
+        Some(Highlight {
+            is_unconstrained: meta.is_unconstrained,
+            is_oracle: meta.kind == FunctionKind::Oracle,
+            ..Highlight::new(span, kind)
+        })
+    }
+
+    /// Returns the span of the last segment of the path at the given location, which is the
+    /// name of the item the path refers to.
+    fn last_segment(&self, location: Location) -> Option<Span> {
+        let files = self.file_manager.as_simple_files();
+        let source = files.get(location.file.as_usize()).ok()?.source();
+        let span = location.span;
+        let path = source.get(span.start() as usize..span.end() as usize)?;
+
+        let name_start = path.rfind("::").map_or(0, |index| index + 2);
+        let name = path[name_start..].trim_start();
+        let start = span.end() - name.len() as u32;
+        Some(Span::exclusive(start, span.end()))
+    }
+}
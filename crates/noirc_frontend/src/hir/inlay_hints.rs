@@ -0,0 +1,123 @@
+//! Inlay hints shown by the language server.
+//!
+//! The types of variables are those inferred by the type checker, while the number of iterations
+//! of a `for` loop is found by evaluating its bounds at compile-time. Loops are unrolled when a
+//! circuit is generated, so this is the number of times the opcodes of the loop's body appear in
+//! the circuit.
+use fm::FileId;
+
+use super::comptime::{Interpreter, Value};
+use super::Context;
+use crate::hir_def::expr::{HirForExpression, HirIdent};
+use crate::hir_def::stmt::HirPattern;
+use crate::Type;
+
+/// What an inlay hint shows
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InlayHintKind {
+    /// The type of a variable, shown after its name
+    Type,
+    /// The number of iterations of a loop, shown after its range
+    TripCount,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlayHint {
+    /// The byte offset the hint is shown at
+    pub offset: u32,
+    pub label: String,
+    pub kind: InlayHintKind,
+}
+
+impl Context {
+    /// Returns the inlay hints of the given file, ordered by where they are shown.
+    pub fn inlay_hints(&self, file: FileId) -> Vec<InlayHint> {
+        let interner = &self.def_interner;
+        let mut hints = Vec::new();
+
+        for pattern in interner.unannotated_let_patterns_in_file(file) {
+            let mut variables = Vec::new();
+            pattern_variables(&pattern, &mut variables);
+
+            for variable in variables {
+                let typ = interner.id_type(variable.id);
+                if matches!(typ, Type::Error) {
+                    continue;
+                }
+                let offset = variable.location.span.end();
+                hints.push(InlayHint {
+                    offset,
+                    label: format!(": {typ}"),
+                    kind: InlayHintKind::Type,
+                });
+            }
+        }
+
+        // Loops of unconstrained functions are executed rather than unrolled into the circuit
+        let unconstrained: Vec<_> = interner
+            .functions_in_file(file)
+            .into_iter()
+            .filter(|(func_id, _)| interner.function_meta(func_id).is_unconstrained)
+            .map(|(_, location)| location.span)
+            .collect();
+
+        for (location, for_loop) in interner.for_loops_in_file(file) {
+            let span = location.span;
+            let in_unconstrained = unconstrained
+                .iter()
+                .any(|function| function.start() <= span.start() && span.end() <= function.end());
+            if in_unconstrained {
+                continue;
+            }
+
+            if let Some(trip_count) = self.trip_count(&for_loop) {
+                let plural = if trip_count == 1 { "" } else { "s" };
+                hints.push(InlayHint {
+                    offset: interner.expr_span(&for_loop.end_range).end(),
+                    label: format!("{trip_count} iteration{plural}"),
+                    kind: InlayHintKind::TripCount,
+                });
+            }
+        }
+
+        hints.sort_by_key(|hint| hint.offset);
+        hints
+    }
+
+    /// Returns the number of iterations of a loop whose bounds are known at compile-time.
+    fn trip_count(&self, for_loop: &HirForExpression) -> Option<u128> {
+        // Bounds referring to local variables, such as parameters, fail to evaluate
+        let mut interpreter = Interpreter::new(&self.def_interner);
+        let start = interpreter.evaluate(for_loop.start_range).ok()?;
+        let end = interpreter.evaluate(for_loop.end_range).ok()?;
+        match (start, end) {
+            (Value::Integer(start, _), Value::Integer(end, _)) => Some(end.saturating_sub(start)),
+            _ => None,
+        }
+    }
+}
+
+/// Collects the variables declared by a pattern. The variables of the shorthand `Foo { x }`
+/// are left out, as their names are also the names of fields.
+fn pattern_variables<'a>(pattern: &'a HirPattern, variables: &mut Vec<&'a HirIdent>) {
+    match pattern {
+        HirPattern::Identifier(ident) => variables.push(ident),
+        HirPattern::Mutable(pattern, _) => pattern_variables(pattern, variables),
+        HirPattern::Tuple(fields, _) => {
+            for field in fields {
+                pattern_variables(field, variables);
+            }
+        }
+        HirPattern::Struct(_, fields, _) => {
+            for (name, field) in fields {
+                let is_shorthand = match field {
+                    HirPattern::Identifier(ident) => ident.location.span == name.span(),
+                    _ => false,
+                };
+                if !is_shorthand {
+                    pattern_variables(field, variables);
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,10 @@
+pub mod completion;
+pub mod comptime;
 pub mod def_collector;
 pub mod def_map;
+pub mod highlight;
+pub mod inlay_hints;
+pub mod rename;
 pub mod resolution;
 pub mod scope;
 pub mod type_check;
@@ -9,6 +14,7 @@ use crate::hir_def::function::FuncMeta;
 use crate::node_interner::{FuncId, NodeInterner};
 use def_map::{Contract, CrateDefMap};
 use fm::FileManager;
+use noirc_errors::LintLevels;
 use std::collections::HashMap;
 
 /// Helper object which groups together several useful context objects used
@@ -25,6 +31,10 @@ pub struct Context {
     /// Maps a given (contract) module id to the next available storage slot
     /// for that contract.
     pub storage_slots: HashMap<def_map::ModuleId, StorageSlot>,
+
+    /// The level of each lint configured by the package being compiled, applied to the
+    /// warnings found while checking the crate.
+    pub lint_levels: LintLevels,
 }
 
 pub type StorageSlot = u32;
@@ -37,6 +47,7 @@ impl Context {
             crate_graph,
             file_manager,
             storage_slots: HashMap::new(),
+            lint_levels: LintLevels::default(),
         }
     }
 
@@ -0,0 +1,220 @@
+//! Renaming of functions, structs, fields and variables by the language server.
+//!
+//! A definition is renamed along with every name the resolver and type checker recorded as
+//! referring to it, which are the same names found when looking for its references. Renames
+//! which would change what a name refers to are rejected: those to a name already declared
+//! alongside the definition, and those to a name of a variable in scope where it is used.
+use fm::FileId;
+use iter_extended::vecmap;
+use noirc_errors::{Location, Span};
+use thiserror::Error;
+
+use super::def_map::ModuleDefId;
+use super::Context;
+use crate::graph::LOCAL_CRATE;
+use crate::lexer::Lexer;
+use crate::node_interner::{DefinitionKind, ReferenceId};
+use crate::token::Token;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RenameError {
+    #[error("`{0}` is not a valid name")]
+    InvalidName(String),
+    #[error("There is nothing to rename at this position")]
+    NothingToRename,
+    #[error("`{name}` is defined outside of this package and cannot be renamed")]
+    External { name: String },
+    #[error("`{name}` is already used in the same scope")]
+    Conflict { name: String, location: Location },
+}
+
+/// Replaces the text at a location with new text, as one of the edits making up a rename.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameEdit {
+    pub location: Location,
+    pub new_text: String,
+}
+
+impl Context {
+    /// Renames the function, struct, field or variable named at the given byte offset of a file,
+    /// returning the edits renaming its declaration and each of its uses.
+    pub fn rename(
+        &self,
+        file: FileId,
+        offset: u32,
+        new_name: &str,
+    ) -> Result<Vec<RenameEdit>, RenameError> {
+        if !is_identifier(new_name) {
+            return Err(RenameError::InvalidName(new_name.to_owned()));
+        }
+
+        let (reference, declaration) =
+            self.def_interner.find_declaration(file, offset).ok_or(RenameError::NothingToRename)?;
+        let old_name = self.source_text(declaration).ok_or(RenameError::NothingToRename)?;
+
+        let in_local_crate =
+            self.modules_in_file(declaration.file).iter().any(|module| module.krate == LOCAL_CRATE);
+        if !in_local_crate {
+            return Err(RenameError::External { name: old_name });
+        }
+
+        let uses = self.def_interner.find_uses(declaration);
+        if let Some(location) =
+            self.find_conflict(reference, declaration, &uses, &old_name, new_name)
+        {
+            return Err(RenameError::Conflict { name: new_name.to_owned(), location });
+        }
+
+        let mut edits = Vec::new();
+        for location in std::iter::once(declaration).chain(uses) {
+            // Paths such as `foo::bar` end with the name of the item they refer to
+            let end = location.span.end();
+            let start = end.saturating_sub(old_name.len() as u32).max(location.span.start());
+            let location = Location::new(Span::exclusive(start, end), location.file);
+
+            // The shorthand `Foo { x }` names both a field and a variable, so it is expanded to
+            // keep referring to whichever of them is not renamed
+            let is_shorthand = self
+                .def_interner
+                .declarations_at(location)
+                .into_iter()
+                .any(|other| other != declaration);
+            let new_text = match (is_shorthand, reference) {
+                (false, _) => new_name.to_owned(),
+                (true, ReferenceId::Field(..)) => format!("{new_name}: {old_name}"),
+                (true, _) => format!("{old_name}: {new_name}"),
+            };
+
+            let edit = RenameEdit { location, new_text };
+            if !edits.contains(&edit) {
+                edits.push(edit);
+            }
+        }
+        Ok(edits)
+    }
+
+    /// Returns the location of a name which would conflict with the definition declared at
+    /// `declaration` if it were renamed to `new_name`.
+    fn find_conflict(
+        &self,
+        reference: ReferenceId,
+        declaration: Location,
+        uses: &[Location],
+        old_name: &str,
+        new_name: &str,
+    ) -> Option<Location> {
+        let interner = &self.def_interner;
+        let (is_value, function) = match reference {
+            ReferenceId::Field(struct_id, _) => {
+                let struct_type = interner.get_struct(struct_id);
+                let struct_type = struct_type.borrow();
+                let mut fields = struct_type.field_idents().into_iter();
+                let field = fields.find(|field| field.0.contents == new_name)?;
+                return Some(Location::new(field.span(), struct_type.file));
+            }
+            ReferenceId::Definition(id) => match interner.definition(id).kind {
+                DefinitionKind::Function(func_id) => (true, Some(func_id)),
+                DefinitionKind::Global(_) => (true, None),
+                DefinitionKind::Local(_) | DefinitionKind::GenericType(_) => {
+                    let function = interner.enclosing_function(declaration)?;
+                    return interner.find_name_within(function, new_name);
+                }
+            },
+            ReferenceId::Item(ModuleDefId::FunctionId(func_id)) => (true, Some(func_id)),
+            ReferenceId::Item(ModuleDefId::GlobalId(_)) => (true, None),
+            ReferenceId::Item(_) => (false, None),
+        };
+
+        if let Some(location) = self.find_module_conflict(declaration, old_name, new_name, is_value)
+        {
+            return Some(location);
+        }
+
+        // Methods are not declared in a module, but alongside the other methods of their type
+        let existing_method = function
+            .and_then(|func_id| interner.method_struct(func_id))
+            .and_then(|struct_id| interner.lookup_method(struct_id, new_name));
+        if let Some(method) = existing_method {
+            return Some(interner.function_meta(&method).name.location);
+        }
+
+        // Functions and globals used within a function are shadowed by its variables
+        if is_value {
+            for location in uses {
+                let function = match interner.enclosing_function(*location) {
+                    Some(function) => function,
+                    None => continue,
+                };
+                if let Some(conflict) = interner.find_name_within(function, new_name) {
+                    return Some(conflict);
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the location of an item named `new_name` in any module where the item declared
+    /// at `declaration` is in scope under its current name, within the same namespace.
+    fn find_module_conflict(
+        &self,
+        declaration: Location,
+        old_name: &str,
+        new_name: &str,
+        is_value: bool,
+    ) -> Option<Location> {
+        let interner = &self.def_interner;
+        for def_map in self.def_maps.values() {
+            for (_, module) in def_map.modules().iter() {
+                let scope = module.scope();
+                let namespace = if is_value { scope.values() } else { scope.types() };
+
+                let declares_item = namespace.iter().any(|(name, (id, _))| {
+                    name.0.contents == old_name
+                        && interner.reference_location(ReferenceId::Item(*id)) == Some(declaration)
+                });
+                if !declares_item {
+                    continue;
+                }
+
+                let existing = namespace.iter().find(|(name, _)| name.0.contents == new_name);
+                if let Some((name, (id, _))) = existing {
+                    let location = interner.reference_location(ReferenceId::Item(*id));
+                    let file = module.origin.file_id();
+                    return Some(location.unwrap_or_else(|| Location::new(name.span(), file)));
+                }
+            }
+        }
+        None
+    }
+
+    fn source_text(&self, location: Location) -> Option<String> {
+        let files = self.file_manager.as_simple_files();
+        let source = files.get(location.file.as_usize()).ok()?.source();
+        let span = location.span;
+        source.get(span.start() as usize..span.end() as usize).map(ToOwned::to_owned)
+    }
+}
+
+/// Returns true if `name` is lexed as a single identifier, rather than as a keyword, a type or
+/// any other token, so that it may be used as the name of a definition.
+fn is_identifier(name: &str) -> bool {
+    let (tokens, errors) = Lexer::lex(name);
+    let tokens = vecmap(tokens.0, |token| token.into_token());
+    errors.is_empty()
+        && matches!(tokens.as_slice(), [Token::Ident(ident), Token::EOF] if ident == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_identifier;
+
+    #[test]
+    fn only_identifiers_are_valid_names() {
+        for name in ["foo", "_bar", "Baz2", "u_8"] {
+            assert!(is_identifier(name), "{name} should be a valid name");
+        }
+        for name in ["", "_", "2foo", "foo bar", "foo::bar", "fn", "Field", "true", "u8", "i64"] {
+            assert!(!is_identifier(name), "{name} should not be a valid name");
+        }
+    }
+}
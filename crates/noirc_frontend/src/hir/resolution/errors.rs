@@ -1,5 +1,6 @@
+use noirc_errors::registry::{self, ErrorCode};
 pub use noirc_errors::Span;
-use noirc_errors::{CustomDiagnostic as Diagnostic, FileDiagnostic};
+use noirc_errors::{CustomDiagnostic as Diagnostic, FileDiagnostic, Lint};
 use thiserror::Error;
 
 use crate::{parser::ParserError, Ident, Type};
@@ -12,8 +13,12 @@ pub enum ResolverError {
     DuplicateDefinition { name: String, first_span: Span, second_span: Span },
     #[error("Unused variable")]
     UnusedVariable { ident: Ident },
+    #[error("Unused import")]
+    UnusedImport { ident: Ident },
+    #[error("Unused function")]
+    UnusedFunction { ident: Ident },
     #[error("Could not find variable in this scope")]
-    VariableNotDeclared { name: String, span: Span },
+    VariableNotDeclared { name: String, span: Span, similar_name: Option<String> },
     #[error("path is not an identifier")]
     PathIsNotIdent { span: Span },
     #[error("could not resolve path")]
@@ -38,8 +43,6 @@ pub enum ResolverError {
     ExpectedComptimeVariable { name: String, span: Span },
     #[error("Missing expression for declared constant")]
     MissingRhsExpr { name: String, span: Span },
-    #[error("Expression invalid in an array length context")]
-    InvalidArrayLengthExpr { span: Span },
     #[error("Integer too large to be evaluated in an array length context")]
     IntegerTooLarge { span: Span },
     #[error("No global or generic type parameter found with the given name")]
@@ -48,6 +51,8 @@ pub enum ResolverError {
     CapturedMutableVariable { span: Span },
     #[error("Test functions are not allowed to have any parameters")]
     TestFunctionHasParameters { span: Span },
+    #[error("#[inline(never)] has no effect on constrained functions")]
+    InlineNeverOnConstrainedFunction { span: Span },
     #[error("Only struct types can be used in constructor expressions")]
     NonStructUsedInConstructor { typ: Type, span: Span },
     #[error("Only struct types can have generics")]
@@ -55,7 +60,7 @@ pub enum ResolverError {
     #[error("Cannot apply generics on Self type")]
     GenericsOnSelfType { span: Span },
     #[error("Incorrect amount of arguments to generic type constructor")]
-    IncorrectGenericCount { span: Span, struct_type: String, actual: usize, expected: usize },
+    IncorrectGenericCount { span: Span, typ: String, actual: usize, expected: usize },
     #[error("{0}")]
     ParserError(Box<ParserError>),
     #[error("Function is not defined in a contract yet sets its contract visibility")]
@@ -64,9 +69,62 @@ pub enum ResolverError {
     MutableReferenceToImmutableVariable { variable: String, span: Span },
     #[error("Mutable references to array indices are unsupported")]
     MutableReferenceToArrayElement { span: Span },
+    #[error("Trait constraint on a generic which is not declared")]
+    ConstraintOnUndeclaredGeneric { generic: Ident },
+    #[error("Expected an enum variant in match pattern")]
+    ExpectedEnumVariant { path: String, span: Span },
+    #[error("Incorrect amount of fields given to enum variant")]
+    IncorrectVariantFieldCount { variant: String, expected: usize, actual: usize, span: Span },
+    #[error("Enums cannot be used in the abi")]
+    EnumInAbi { typ: Type, span: Span },
+    #[error("Use of deprecated struct {name}")]
+    DeprecatedStruct { name: String, note: Option<String>, span: Span },
+    #[error("Type-level expression cannot be evaluated")]
+    InvalidTypeExpression { expression: String, span: Span },
 }
 
 impl ResolverError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ResolverError::DuplicateDefinition { .. } => registry::E0035,
+            ResolverError::UnusedVariable { .. } => registry::E0036,
+            ResolverError::UnusedImport { .. } => registry::E0037,
+            ResolverError::UnusedFunction { .. } => registry::E0038,
+            ResolverError::VariableNotDeclared { .. } => registry::E0039,
+            ResolverError::PathIsNotIdent { .. } => registry::E0040,
+            ResolverError::PathResolutionError(error) => error.code(),
+            ResolverError::Expected { .. } => registry::E0041,
+            ResolverError::DuplicateField { .. } => registry::E0042,
+            ResolverError::NoSuchField { .. } => registry::E0043,
+            ResolverError::MissingFields { .. } => registry::E0044,
+            ResolverError::UnnecessaryMut { .. } => registry::E0045,
+            ResolverError::UnnecessaryPub { .. } => registry::E0046,
+            ResolverError::NecessaryPub { .. } => registry::E0047,
+            ResolverError::DistinctNotAllowed { .. } => registry::E0048,
+            ResolverError::ExpectedComptimeVariable { .. } => registry::E0049,
+            ResolverError::MissingRhsExpr { .. } => registry::E0050,
+            ResolverError::IntegerTooLarge { .. } => registry::E0051,
+            ResolverError::NoSuchNumericTypeVariable { .. } => registry::E0052,
+            ResolverError::CapturedMutableVariable { .. } => registry::E0053,
+            ResolverError::TestFunctionHasParameters { .. } => registry::E0054,
+            ResolverError::InlineNeverOnConstrainedFunction { .. } => registry::E0055,
+            ResolverError::NonStructUsedInConstructor { .. } => registry::E0056,
+            ResolverError::NonStructWithGenerics { .. } => registry::E0057,
+            ResolverError::GenericsOnSelfType { .. } => registry::E0058,
+            ResolverError::IncorrectGenericCount { .. } => registry::E0059,
+            ResolverError::ParserError(error) => error.code(),
+            ResolverError::ContractFunctionTypeInNormalFunction { .. } => registry::E0060,
+            ResolverError::MutableReferenceToImmutableVariable { .. } => registry::E0061,
+            ResolverError::MutableReferenceToArrayElement { .. } => registry::E0062,
+            ResolverError::ConstraintOnUndeclaredGeneric { .. } => registry::E0063,
+            ResolverError::ExpectedEnumVariant { .. } => registry::E0064,
+            ResolverError::IncorrectVariantFieldCount { .. } => registry::E0065,
+            ResolverError::EnumInAbi { .. } => registry::E0093,
+            ResolverError::DeprecatedStruct { .. } => registry::E0080,
+            ResolverError::InvalidTypeExpression { .. } => registry::E0095,
+        }
+    }
+
     pub fn into_file_diagnostic(self, file: fm::FileId) -> FileDiagnostic {
         Diagnostic::from(self).in_file(file)
     }
@@ -77,7 +135,8 @@ impl From<ResolverError> for Diagnostic {
     /// ICEs will make the compiler panic, as they could affect the
     /// soundness of the generated program
     fn from(error: ResolverError) -> Diagnostic {
-        match error {
+        let code = error.code();
+        let diagnostic = match error {
             ResolverError::DuplicateDefinition { name, first_span, second_span } => {
                 let mut diag = Diagnostic::simple_error(
                     format!("duplicate definitions of {name} found"),
@@ -90,17 +149,54 @@ impl From<ResolverError> for Diagnostic {
             ResolverError::UnusedVariable { ident } => {
                 let name = &ident.0.contents;
 
-                Diagnostic::simple_warning(
+                let mut diag = Diagnostic::simple_warning(
                     format!("unused variable {name}"),
                     "unused variable ".to_string(),
                     ident.span(),
                 )
+                .with_lint(Lint::Unused);
+                diag.add_suggestion(
+                    "if this is intentional, prefix it with an underscore".into(),
+                    ident.span(),
+                    format!("_{name}"),
+                );
+                diag
+            }
+            ResolverError::UnusedImport { ident } => {
+                let name = &ident.0.contents;
+
+                Diagnostic::simple_warning(
+                    format!("unused import {name}"),
+                    "unused import".to_string(),
+                    ident.span(),
+                )
+                .with_lint(Lint::Unused)
+            }
+            ResolverError::UnusedFunction { ident } => {
+                let name = &ident.0.contents;
+
+                Diagnostic::simple_warning(
+                    format!("function {name} is never called"),
+                    "unused function".to_string(),
+                    ident.span(),
+                )
+                .with_lint(Lint::Unused)
+            }
+            ResolverError::VariableNotDeclared { name, span, similar_name } => {
+                let mut diag = Diagnostic::simple_error(
+                    format!("cannot find `{name}` in this scope "),
+                    "not found in this scope".to_string(),
+                    span,
+                );
+                if let Some(similar_name) = similar_name {
+                    diag.add_suggestion(
+                        "a variable with a similar name exists".into(),
+                        span,
+                        similar_name,
+                    );
+                }
+                diag
             }
-            ResolverError::VariableNotDeclared { name, span } => Diagnostic::simple_error(
-                format!("cannot find `{name}` in this scope "),
-                "not found in this scope".to_string(),
-                span,
-            ),
             ResolverError::PathIsNotIdent { span } => Diagnostic::simple_error(
                 "cannot use path as an identifier".to_string(),
                 String::new(),
@@ -206,11 +302,6 @@ impl From<ResolverError> for Diagnostic {
                 "expected expression to be stored for let statement".to_string(),
                 span,
             ),
-            ResolverError::InvalidArrayLengthExpr { span } => Diagnostic::simple_error(
-                "Expression invalid in an array-length context".into(),
-                "Array-length expressions can only have simple integer operations and any variables used must be global constants".into(),
-                span,
-            ),
             ResolverError::IntegerTooLarge { span } => Diagnostic::simple_error(
                 "Integer too large to be evaluated to an array-length".into(),
                 "Array-lengths may be a maximum size of usize::MAX, including intermediate calculations".into(),
@@ -231,6 +322,13 @@ impl From<ResolverError> for Diagnostic {
                 "Try removing the parameters or moving the test into a wrapper function".into(),
                 span,
             ),
+            ResolverError::InlineNeverOnConstrainedFunction { span } => {
+                Diagnostic::simple_warning(
+                    "#[inline(never)] has no effect on constrained functions".into(),
+                    "Constrained functions are always inlined into main".into(),
+                    span,
+                )
+            }
             ResolverError::NonStructUsedInConstructor { typ, span } => Diagnostic::simple_error(
                 "Only struct types can be used in constructor expressions".into(),
                 format!("{typ} has no fields to construct it with"),
@@ -246,12 +344,12 @@ impl From<ResolverError> for Diagnostic {
                 "Use an explicit type name or apply the generics at the start of the impl instead".into(),
                 span,
             ),
-            ResolverError::IncorrectGenericCount { span, struct_type, actual, expected } => {
+            ResolverError::IncorrectGenericCount { span, typ, actual, expected } => {
                 let expected_plural = if expected == 1 { "" } else { "s" };
                 let actual_plural = if actual == 1 { "is" } else { "are" };
 
                 Diagnostic::simple_error(
-                    format!("The struct type {struct_type} has {expected} generic{expected_plural} but {actual} {actual_plural} given here"),
+                    format!("The type {typ} has {expected} generic{expected_plural} but {actual} {actual_plural} given here"),
                     "Incorrect number of generic arguments".into(),
                     span,
                 )
@@ -268,6 +366,49 @@ impl From<ResolverError> for Diagnostic {
             ResolverError::MutableReferenceToArrayElement { span } => {
                 Diagnostic::simple_error("Mutable references to array elements are currently unsupported".into(), "Try storing the element in a fresh variable first".into(), span)
             },
-        }
+            ResolverError::ConstraintOnUndeclaredGeneric { generic } => Diagnostic::simple_error(
+                format!("Cannot find generic {generic} in this scope"),
+                "Trait constraints may only apply to the generics of the function".into(),
+                generic.span(),
+            ),
+            ResolverError::ExpectedEnumVariant { path, span } => Diagnostic::simple_error(
+                format!("Expected an enum variant, but found `{path}`"),
+                "Match patterns must be of the form `Enum::Variant` or `_`".into(),
+                span,
+            ),
+            ResolverError::IncorrectVariantFieldCount { variant, expected, actual, span } => {
+                let expected_plural = if expected == 1 { "" } else { "s" };
+                let actual_plural = if actual == 1 { "was" } else { "were" };
+
+                Diagnostic::simple_error(
+                    format!("The variant {variant} has {expected} field{expected_plural} but {actual} {actual_plural} given here"),
+                    "Incorrect number of fields".into(),
+                    span,
+                )
+            }
+            ResolverError::EnumInAbi { typ, span } => {
+                let mut diag = Diagnostic::simple_error(
+                    format!("Type {typ} cannot be used in the abi as it contains an enum"),
+                    "enums have no abi encoding".into(),
+                    span,
+                );
+                diag.add_note("Pass the fields of the enum's variants as separate parameters and construct it in the function instead".to_owned());
+                diag
+            }
+            ResolverError::DeprecatedStruct { name, note, span } => Diagnostic::simple_warning(
+                format!("use of deprecated struct `{name}`"),
+                note.unwrap_or_default(),
+                span,
+            )
+            .with_lint(Lint::Deprecated),
+            ResolverError::InvalidTypeExpression { expression, span } => {
+                Diagnostic::simple_error(
+                    format!("cannot evaluate `{expression}`"),
+                    "this overflows or divides by zero".into(),
+                    span,
+                )
+            }
+        };
+        diagnostic.with_code(code)
     }
 }
@@ -1,17 +1,20 @@
 use iter_extended::partition_results;
-use noirc_errors::CustomDiagnostic;
+use noirc_errors::registry::{self, ErrorCode};
+use noirc_errors::{CustomDiagnostic, Span};
 
 use crate::graph::CrateId;
 use std::collections::HashMap;
 
 use crate::hir::def_map::{CrateDefMap, LocalModuleId, ModuleDefId, ModuleId, PerNs};
-use crate::{Ident, Path, PathKind};
+use crate::{Ident, ItemVisibility, Path};
 
 #[derive(Debug, Clone)]
 pub struct ImportDirective {
     pub module_id: LocalModuleId,
     pub path: Path,
     pub alias: Option<Ident>,
+    pub allow_unused: bool,
+    pub visibility: ItemVisibility,
 }
 
 pub type PathResolution = Result<PerNs, PathResolutionError>;
@@ -20,6 +23,7 @@ pub type PathResolution = Result<PerNs, PathResolutionError>;
 pub enum PathResolutionError {
     Unresolved(Ident),
     ExternalContractUsed(Ident),
+    Private(Ident),
 }
 
 #[derive(Debug)]
@@ -30,11 +34,28 @@ pub struct ResolvedImport {
     pub resolved_namespace: PerNs,
     // The module which we must add the resolved namespace to
     pub module_scope: LocalModuleId,
+    // Whether the import is marked `#[allow(unused)]`
+    pub allow_unused: bool,
+    // The span of the imported path
+    pub path_span: Span,
+    // The visibility of the imported name, `pub use` re-exports it
+    pub visibility: ItemVisibility,
+}
+
+impl PathResolutionError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            PathResolutionError::Unresolved(_) => registry::E0032,
+            PathResolutionError::ExternalContractUsed(_) => registry::E0033,
+            PathResolutionError::Private(_) => registry::E0034,
+        }
+    }
 }
 
 impl From<PathResolutionError> for CustomDiagnostic {
     fn from(error: PathResolutionError) -> Self {
-        match error {
+        let code = error.code();
+        let diagnostic = match error {
             PathResolutionError::Unresolved(ident) => CustomDiagnostic::simple_error(
                 format!("Could not resolve '{ident}' in path"),
                 String::new(),
@@ -45,7 +66,13 @@ impl From<PathResolutionError> for CustomDiagnostic {
                 "Contracts may only be referenced from within a contract".to_string(),
                 ident.span(),
             ),
-        }
+            PathResolutionError::Private(ident) => CustomDiagnostic::simple_error(
+                format!("'{ident}' is private and not visible from the current module"),
+                format!("'{ident}' is private"),
+                ident.span(),
+            ),
+        };
+        diagnostic.with_code(code)
     }
 }
 
@@ -66,7 +93,17 @@ pub fn resolve_imports(
                 .map_err(|error| (error, module_scope))?;
 
         let name = resolve_path_name(&import_directive);
-        Ok(ResolvedImport { name, resolved_namespace, module_scope })
+        let allow_unused = import_directive.allow_unused;
+        let path_span = import_directive.path.span();
+        let visibility = import_directive.visibility;
+        Ok(ResolvedImport {
+            name,
+            resolved_namespace,
+            module_scope,
+            allow_unused,
+            path_span,
+            visibility,
+        })
     })
 }
 
@@ -85,11 +122,18 @@ pub fn resolve_path_to_ns(
     allow_contracts: bool,
 ) -> PathResolution {
     let import_path = &import_directive.path.segments;
+    let importing_module = ModuleId { krate: def_map.krate, local_id: import_directive.module_id };
 
     match import_directive.path.kind {
         crate::ast::PathKind::Crate => {
             // Resolve from the root of the crate
-            resolve_path_from_crate_root(def_map, import_path, def_maps, allow_contracts)
+            resolve_path_from_crate_root(
+                def_map,
+                import_path,
+                importing_module,
+                def_maps,
+                allow_contracts,
+            )
         }
         crate::ast::PathKind::Dep => {
             resolve_external_dep(def_map, import_directive, def_maps, allow_contracts)
@@ -101,6 +145,7 @@ pub fn resolve_path_to_ns(
                 def_map,
                 import_path,
                 import_directive.module_id,
+                importing_module,
                 def_maps,
                 allow_contracts,
             )
@@ -111,16 +156,27 @@ pub fn resolve_path_to_ns(
 fn resolve_path_from_crate_root(
     def_map: &CrateDefMap,
     import_path: &[Ident],
+    importing_module: ModuleId,
     def_maps: &HashMap<CrateId, CrateDefMap>,
     allow_contracts: bool,
 ) -> PathResolution {
-    resolve_name_in_module(def_map, import_path, def_map.root, def_maps, allow_contracts)
+    resolve_name_in_module(
+        def_map,
+        import_path,
+        def_map.root,
+        importing_module,
+        def_maps,
+        allow_contracts,
+    )
 }
 
+/// Resolve the given path starting from `starting_mod`. Each segment of the path must be
+/// visible from `importing_module`, the module the path is written in.
 fn resolve_name_in_module(
     def_map: &CrateDefMap,
     import_path: &[Ident],
     starting_mod: LocalModuleId,
+    importing_module: ModuleId,
     def_maps: &HashMap<CrateId, CrateDefMap>,
     allow_contracts: bool,
 ) -> PathResolution {
@@ -140,6 +196,10 @@ fn resolve_name_in_module(
         return Err(PathResolutionError::Unresolved(first_segment.clone()));
     }
 
+    let starting_mod = ModuleId { krate: def_map.krate, local_id: starting_mod };
+    current_ns =
+        visible_items(current_ns, starting_mod, importing_module, def_maps, first_segment)?;
+
     for segment in import_path {
         let typ = match current_ns.take_types() {
             None => return Err(PathResolutionError::Unresolved(segment.clone())),
@@ -152,9 +212,21 @@ fn resolve_name_in_module(
             ModuleDefId::FunctionId(_) => panic!("functions cannot be in the type namespace"),
             // TODO: If impls are ever implemented, types can be used in a path
             ModuleDefId::TypeId(id) => id.0,
+            ModuleDefId::EnumId(id) => id.0,
+            ModuleDefId::TypeAliasId(_) => {
+                return Err(PathResolutionError::Unresolved(segment.clone()))
+            }
+            ModuleDefId::TraitId(id) => id.0,
             ModuleDefId::GlobalId(_) => panic!("globals cannot be in the type namespace"),
         };
 
+        // The private methods of a type are visible wherever the type's own private items
+        // are, so they belong to the module the type is declared in rather than its own module.
+        let visibility_module = match typ {
+            ModuleDefId::ModuleId(id) => id,
+            _ => parent_module(def_maps, new_module_id),
+        };
+
         current_mod = &def_maps[&new_module_id.krate].modules[new_module_id.local_id.0];
 
         // Check if namespace
@@ -168,12 +240,75 @@ fn resolve_name_in_module(
             return Err(PathResolutionError::ExternalContractUsed(segment.clone()));
         }
 
-        current_ns = found_ns;
+        current_ns =
+            visible_items(found_ns, visibility_module, importing_module, def_maps, segment)?;
     }
 
     Ok(current_ns)
 }
 
+/// Removes the items of `ns` which are not visible from `importing_module`, given that they
+/// were declared in `defining_module`. Errors if the name only refers to such items.
+fn visible_items(
+    ns: PerNs,
+    defining_module: ModuleId,
+    importing_module: ModuleId,
+    def_maps: &HashMap<CrateId, CrateDefMap>,
+    name: &Ident,
+) -> PathResolution {
+    let is_visible = |(_, visibility): &(ModuleDefId, ItemVisibility)| {
+        can_reference_item(def_maps, importing_module, defining_module, *visibility)
+    };
+
+    let visible =
+        PerNs { types: ns.types.filter(is_visible), values: ns.values.filter(is_visible) };
+
+    if visible.is_none() {
+        Err(PathResolutionError::Private(name.clone()))
+    } else {
+        Ok(visible)
+    }
+}
+
+/// Returns true if an item declared in `defining_module` with the given visibility may be
+/// referred to from `importing_module`. Private items are visible within the module declaring
+/// them along with each of its descendants.
+pub(crate) fn can_reference_item(
+    def_maps: &HashMap<CrateId, CrateDefMap>,
+    importing_module: ModuleId,
+    defining_module: ModuleId,
+    visibility: ItemVisibility,
+) -> bool {
+    if importing_module.krate != defining_module.krate {
+        return visibility == ItemVisibility::Public;
+    }
+
+    if visibility != ItemVisibility::Private {
+        return true;
+    }
+
+    let modules = &def_maps[&importing_module.krate].modules;
+    let mut current = Some(importing_module.local_id);
+
+    while let Some(module) = current {
+        if module == defining_module.local_id {
+            return true;
+        }
+        current = modules[module.0].parent;
+    }
+
+    false
+}
+
+/// Returns the parent of the given module, or the module itself if it is the root of its crate.
+pub(crate) fn parent_module(
+    def_maps: &HashMap<CrateId, CrateDefMap>,
+    module: ModuleId,
+) -> ModuleId {
+    let parent = module.module(def_maps).parent;
+    parent.map_or(module, |local_id| ModuleId { krate: module.krate, local_id })
+}
+
 fn resolve_path_name(import_directive: &ImportDirective) -> Ident {
     match &import_directive.alias {
         None => import_directive.path.segments.last().unwrap().clone(),
@@ -198,14 +333,18 @@ fn resolve_external_dep(
         .get(&crate_name.0.contents)
         .ok_or_else(|| PathResolutionError::Unresolved(crate_name.to_owned()))?;
 
-    // Create an import directive for the dependency crate
+    // Resolve the rest of the path within the dependency crate
     let path_without_crate_name = &path[1..]; // XXX: This will panic if the path is of the form `use dep::std` Ideal algorithm will not distinguish between crate and module
 
-    let path = Path { segments: path_without_crate_name.to_vec(), kind: PathKind::Plain };
-    let dep_directive =
-        ImportDirective { module_id: dep_module.local_id, path, alias: directive.alias.clone() };
-
+    let importing_module = ModuleId { krate: current_def_map.krate, local_id: directive.module_id };
     let dep_def_map = def_maps.get(&dep_module.krate).unwrap();
 
-    resolve_path_to_ns(&dep_directive, dep_def_map, def_maps, allow_contracts)
+    resolve_name_in_module(
+        dep_def_map,
+        path_without_crate_name,
+        dep_module.local_id,
+        importing_module,
+        def_maps,
+        allow_contracts,
+    )
 }
@@ -1,7 +1,7 @@
 use super::import::{
     allow_referencing_contracts, resolve_path_to_ns, ImportDirective, PathResolutionError,
 };
-use crate::Path;
+use crate::{ItemVisibility, Path};
 use std::collections::HashMap;
 
 use crate::graph::CrateId;
@@ -57,7 +57,13 @@ pub fn resolve_path(
     path: Path,
 ) -> Result<ModuleDefId, PathResolutionError> {
     // lets package up the path into an ImportDirective and resolve it using that
-    let import = ImportDirective { module_id: module_id.local_id, path, alias: None };
+    let import = ImportDirective {
+        module_id: module_id.local_id,
+        path,
+        alias: None,
+        allow_unused: false,
+        visibility: ItemVisibility::Private,
+    };
     let allow_referencing_contracts =
         allow_referencing_contracts(def_maps, module_id.krate, module_id.local_id);
 
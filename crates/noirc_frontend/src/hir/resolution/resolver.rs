@@ -9,23 +9,24 @@
 //
 //
 // XXX: Change mentions of intern to resolve. In regards to the above comment
-//
-// XXX: Resolver does not check for unused functions
 use crate::hir_def::expr::{
     HirArrayLiteral, HirBinaryOp, HirBlockExpression, HirCallExpression, HirCastExpression,
-    HirConstructorExpression, HirExpression, HirForExpression, HirIdent, HirIfExpression,
-    HirIndexExpression, HirInfixExpression, HirLambda, HirLiteral, HirMemberAccess,
-    HirMethodCallExpression, HirPrefixExpression,
+    HirConstructorExpression, HirEnumConstructorExpression, HirExpression, HirForExpression,
+    HirIdent, HirIfExpression, HirIndexExpression, HirInfixExpression, HirLambda, HirLiteral,
+    HirMatchExpression, HirMatchPattern, HirMemberAccess, HirMethodCallExpression,
+    HirPrefixExpression, HirWhileExpression,
 };
-use crate::token::Attribute;
+use crate::token::{Attribute, InlineType};
 use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
 use crate::graph::CrateId;
-use crate::hir::def_map::{ModuleDefId, TryFromModuleDefId, MAIN_FUNCTION};
+use crate::hir::def_map::{ModuleDefId, ModuleId, TryFromModuleDefId, MAIN_FUNCTION};
 use crate::hir_def::stmt::{HirAssignStatement, HirLValue, HirPattern};
+use crate::hir_def::traits::{TraitConstraint, TraitMethodId};
 use crate::node_interner::{
-    DefinitionId, DefinitionKind, ExprId, FuncId, NodeInterner, StmtId, StructId,
+    DefinitionId, DefinitionKind, ExprId, FuncId, NodeInterner, ReferenceId, StmtId, StructId,
+    TraitId,
 };
 use crate::{
     hir::{def_map::CrateDefMap, resolution::path_resolver::PathResolver},
@@ -33,12 +34,14 @@ use crate::{
     Statement,
 };
 use crate::{
-    ArrayLiteral, ContractFunctionType, Generics, LValue, NoirStruct, Path, Pattern, Shared,
-    StructType, Type, TypeBinding, TypeVariable, UnaryOp, UnresolvedGenerics, UnresolvedType,
+    ArrayLiteral, ConstrainKind, ContractFunctionType, EnumType, Generics, LValue, MatchPattern,
+    NoirEnum, NoirStruct, NoirTypeAlias, OverflowMode, Path, PathKind, Pattern, Shared, StructType,
+    Type, TypeBinding, TypeVariable, UnaryOp, UnresolvedGenerics, UnresolvedType,
     UnresolvedTypeExpression, ERROR_IDENT,
 };
 use fm::FileId;
 use iter_extended::vecmap;
+use noirc_abi::printable::format_string_variables;
 use noirc_errors::{Location, Span, Spanned};
 
 use crate::hir::scope::{
@@ -46,10 +49,11 @@ use crate::hir::scope::{
 };
 use crate::hir_def::{
     function::{FuncMeta, HirFunction, Param},
-    stmt::{HirConstrainStatement, HirLetStatement, HirStatement},
+    stmt::{HirConstrainKind, HirConstrainStatement, HirLetStatement, HirStatement},
 };
 
 use super::errors::ResolverError;
+use super::import::PathResolutionError;
 
 const SELF_TYPE_NAME: &str = "Self";
 
@@ -83,9 +87,19 @@ pub struct Resolver<'a> {
     /// Lambdas share the function scope of the function they're defined in,
     /// so to identify whether they use any variables from the parent function
     /// we keep track of the scope index a variable is declared in. When a lambda
-    /// is declared we push a scope and set this lambda_index to the scope index.
-    /// Any variable from a scope less than that must be from the parent function.
-    lambda_index: usize,
+    /// is declared we push a scope and a new context holding this scope index.
+    /// Any variable from a scope less than that must be captured by the lambda.
+    lambda_stack: Vec<LambdaContext>,
+
+    /// True if resolving a function marked `#[allow(unused)]`, in which case no warnings are
+    /// issued for its unused variables
+    allow_unused_variables: bool,
+}
+
+/// The variables captured by a lambda being resolved
+struct LambdaContext {
+    captures: Vec<HirIdent>,
+    scope_index: usize,
 }
 
 /// ResolverMetas are tagged onto each definition to track how many times they are used
@@ -111,7 +125,8 @@ impl<'a> Resolver<'a> {
             self_type: None,
             generics: Vec::new(),
             errors: Vec::new(),
-            lambda_index: 0,
+            lambda_stack: Vec::new(),
+            allow_unused_variables: false,
             file,
         }
     }
@@ -124,10 +139,6 @@ impl<'a> Resolver<'a> {
         self.errors.push(err);
     }
 
-    fn current_lambda_index(&self) -> usize {
-        self.scopes.current_scope_index()
-    }
-
     /// Resolving a function involves interning the metadata
     /// interning any statements inside of the function
     /// and interning the function itself
@@ -139,6 +150,7 @@ impl<'a> Resolver<'a> {
         func_id: FuncId,
     ) -> (HirFunction, FuncMeta, Vec<ResolverError>) {
         self.scopes.start_function();
+        self.allow_unused_variables = func.def.allow_unused;
 
         // Check whether the function has globals in the local module and add them to the scope
         self.resolve_local_globals();
@@ -154,6 +166,10 @@ impl<'a> Resolver<'a> {
     }
 
     fn check_for_unused_variables_in_scope_tree(&mut self, scope_decls: ScopeTree) {
+        if self.allow_unused_variables {
+            return;
+        }
+
         let mut unused_vars = Vec::new();
         for scope in scope_decls.0.into_iter() {
             Resolver::check_for_unused_variables_in_local_scope(scope, &mut unused_vars);
@@ -210,6 +226,7 @@ impl<'a> Resolver<'a> {
 
         let id = self.interner.push_definition(name.0.contents.clone(), mutable, definition);
         let location = Location::new(name.span(), self.file);
+        self.interner.push_definition_location(id, location);
         let ident = HirIdent { location, id };
         let resolver_meta = ResolverMeta { num_times_used: 0, ident, warn_if_unused };
 
@@ -254,6 +271,7 @@ impl<'a> Resolver<'a> {
         } else {
             let id = self.interner.push_definition(name.0.contents.clone(), false, definition);
             let location = Location::new(name.span(), self.file);
+            self.interner.push_definition_location(id, location);
             ident = HirIdent { location, id };
             resolver_meta = ResolverMeta { num_times_used: 0, ident, warn_if_unused: true };
         }
@@ -291,18 +309,49 @@ impl<'a> Resolver<'a> {
         let variable = scope_tree.find(&name.0.contents);
 
         let location = Location::new(name.span(), self.file);
-        if let Some((variable_found, _)) = variable {
+        if let Some((variable_found, scope_index)) = variable {
             variable_found.num_times_used += 1;
             let id = variable_found.ident.id;
-            Ok(HirIdent { location, id })
+            let ident = HirIdent { location, id };
+            self.interner.add_reference(location, ReferenceId::Definition(id));
+            self.capture_variable(&ident, scope_index);
+            Ok(ident)
         } else {
+            let names_in_scope = scope_tree.0.iter().flat_map(|scope| scope.0.keys());
+            let similar_name = find_similar_name(&name.0.contents, names_in_scope).cloned();
             Err(ResolverError::VariableNotDeclared {
                 name: name.0.contents.clone(),
                 span: name.0.span(),
+                similar_name,
             })
         }
     }
 
+    /// Records a variable declared in the given scope as captured by each lambda it is used in,
+    /// including the lambdas which only use it through the lambdas nested within them.
+    fn capture_variable(&mut self, ident: &HirIdent, scope_index: usize) {
+        // Globals and functions are in scope everywhere, only locals need to be captured
+        if !matches!(self.interner.definition(ident.id).kind, DefinitionKind::Local(_)) {
+            return;
+        }
+
+        let mut captured = false;
+        for lambda in self.lambda_stack.iter_mut().rev() {
+            if scope_index >= lambda.scope_index {
+                break;
+            }
+            captured = true;
+            if !lambda.captures.iter().any(|capture| capture.id == ident.id) {
+                lambda.captures.push(*ident);
+            }
+        }
+
+        if captured && self.interner.definition(ident.id).mutable {
+            let span = ident.location.span;
+            self.push_err(ResolverError::CapturedMutableVariable { span });
+        }
+    }
+
     fn intern_function(&mut self, func: NoirFunction, id: FuncId) -> (HirFunction, FuncMeta) {
         let func_meta = self.extract_meta(&func, id);
         let hir_func = match func.kind {
@@ -349,7 +398,13 @@ impl<'a> Resolver<'a> {
             UnresolvedType::Function(args, ret) => {
                 let args = vecmap(args, |arg| self.resolve_type_inner(arg, new_variables));
                 let ret = Box::new(self.resolve_type_inner(*ret, new_variables));
-                Type::Function(args, ret)
+
+                // Users are not required to name the environment of a function type, so that any
+                // closure with matching parameter and return types can be given where it is expected.
+                let id = self.interner.next_type_variable_id();
+                let env = Shared::new(TypeBinding::Unbound(id));
+                new_variables.push((id, env.clone()));
+                Type::Function(args, ret, Box::new(Type::TypeVariable(env)))
             }
             UnresolvedType::MutableReference(element) => {
                 Type::MutableReference(Box::new(self.resolve_type_inner(*element, new_variables)))
@@ -389,42 +444,87 @@ impl<'a> Resolver<'a> {
         }
 
         let span = path.span();
-        match self.lookup_struct_or_error(path) {
-            Some(struct_type) => {
-                let mut args = vecmap(args, |arg| self.resolve_type_inner(arg, new_variables));
-                let expected_generic_count = struct_type.borrow().generics.len();
+        if let Ok(ModuleDefId::TypeAliasId(id)) = self.resolve_module_def(path.clone()) {
+            let type_alias = self.interner.get_type_alias(id);
+            let expected_generic_count = type_alias.generics.len();
+            let name = type_alias.to_string();
+            let args =
+                self.resolve_generic_args(args, expected_generic_count, name, span, new_variables);
+            return self.interner.get_type_alias(id).get_type(&args);
+        }
 
-                if args.len() != expected_generic_count {
-                    self.push_err(ResolverError::IncorrectGenericCount {
-                        span,
-                        struct_type: struct_type.borrow().to_string(),
-                        actual: args.len(),
-                        expected: expected_generic_count,
-                    });
+        if let Ok(ModuleDefId::EnumId(id)) = self.resolve_module_def(path.clone()) {
+            let enum_type = self.interner.get_enum(id);
+            let expected_generic_count = enum_type.borrow().generics.len();
+            let name = enum_type.borrow().to_string();
+            let args =
+                self.resolve_generic_args(args, expected_generic_count, name, span, new_variables);
+            return Type::Enum(enum_type, args);
+        }
 
-                    // Fix the generic count so we can continue typechecking
-                    args.resize_with(expected_generic_count, || Type::Error);
+        match self.lookup_struct_or_error(path) {
+            Some(struct_type) => {
+                if let Some(note) = struct_type.borrow().deprecated.clone() {
+                    let name = struct_type.borrow().name.to_string();
+                    self.push_err(ResolverError::DeprecatedStruct { name, note, span });
                 }
 
+                let expected_generic_count = struct_type.borrow().generics.len();
+                let name = struct_type.borrow().to_string();
+                let args = self.resolve_generic_args(
+                    args,
+                    expected_generic_count,
+                    name,
+                    span,
+                    new_variables,
+                );
                 Type::Struct(struct_type, args)
             }
             None => Type::Error,
         }
     }
 
+    /// Resolves the generic arguments given to a struct, enum, or type alias. If the wrong number
+    /// of arguments were given, an error is issued and the arguments are padded or truncated to
+    /// the expected number so that type checking can continue.
+    fn resolve_generic_args(
+        &mut self,
+        args: Vec<UnresolvedType>,
+        expected_generic_count: usize,
+        type_name: String,
+        span: Span,
+        new_variables: &mut Generics,
+    ) -> Vec<Type> {
+        let mut args = vecmap(args, |arg| self.resolve_type_inner(arg, new_variables));
+
+        if args.len() != expected_generic_count {
+            self.push_err(ResolverError::IncorrectGenericCount {
+                span,
+                typ: type_name,
+                actual: args.len(),
+                expected: expected_generic_count,
+            });
+
+            // Fix the generic count so we can continue typechecking
+            args.resize_with(expected_generic_count, || Type::Error);
+        }
+
+        args
+    }
+
     fn lookup_generic_or_global_type(&mut self, path: &Path) -> Option<Type> {
         if path.segments.len() == 1 {
             let name = &path.last_segment().0.contents;
             if let Some((name, var, _)) = self.find_generic(name) {
-                return Some(Type::NamedGeneric(var.clone(), name.clone()));
+                let typ = Type::NamedGeneric(var.clone(), name.clone());
+                self.interner.add_generic_location(Location::new(path.span(), self.file));
+                return Some(typ);
             }
         }
 
         // If we cannot find a local generic of the same name, try to look up a global
-        match self.path_resolver.resolve(self.def_maps, path.clone()) {
-            Ok(ModuleDefId::GlobalId(id)) => {
-                Some(Type::Constant(self.eval_global_as_array_length(id)))
-            }
+        match self.resolve_module_def(path.clone()) {
+            Ok(ModuleDefId::GlobalId(id)) => Some(self.eval_global_as_array_length(id)),
             _ => None,
         }
     }
@@ -458,26 +558,42 @@ impl<'a> Resolver<'a> {
                 })
             }
             UnresolvedTypeExpression::Constant(int, _) => Type::Constant(int),
-            UnresolvedTypeExpression::BinaryOperation(lhs, op, rhs, _) => {
-                let (lhs_span, rhs_span) = (lhs.span(), rhs.span());
+            UnresolvedTypeExpression::BinaryOperation(lhs, op, rhs, span) => {
                 let lhs = self.convert_expression_type(*lhs);
                 let rhs = self.convert_expression_type(*rhs);
 
                 match (lhs, rhs) {
-                    (Type::Constant(lhs), Type::Constant(rhs)) => {
-                        Type::Constant(op.function()(lhs, rhs))
-                    }
-                    (lhs, _) => {
-                        let span =
-                            if !matches!(lhs, Type::Constant(_)) { lhs_span } else { rhs_span };
-                        self.push_err(ResolverError::InvalidArrayLengthExpr { span });
-                        Type::Constant(0)
-                    }
+                    (Type::Constant(lhs), Type::Constant(rhs)) => match op.function()(lhs, rhs) {
+                        Some(value) => Type::Constant(value),
+                        None => {
+                            let expression = format!("{lhs} {op} {rhs}");
+                            self.push_err(ResolverError::InvalidTypeExpression {
+                                expression,
+                                span,
+                            });
+                            Type::Constant(0)
+                        }
+                    },
+                    // Operations on numeric generics are evaluated once the generics are bound
+                    (lhs, rhs) => Type::InfixExpr(Box::new(lhs), op, Box::new(rhs)),
                 }
             }
         }
     }
 
+    /// Resolves each variable interpolated into a format string as `{variable}`
+    fn resolve_format_string_fields(&mut self, string: &str, span: Span) -> Vec<ExprId> {
+        // Skip past the `f"` starting the literal
+        let contents_start = span.start() + 2;
+
+        vecmap(format_string_variables(string), |(offset, name)| {
+            let start = contents_start + offset as u32 + 1;
+            let span = Span::exclusive(start, start + name.len() as u32);
+            let path = Path::from_single(name.to_owned(), span);
+            self.resolve_expression(Expression::new(ExpressionKind::Variable(path), span))
+        })
+    }
+
     fn get_ident_from_path(&mut self, path: Path) -> HirIdent {
         let location = Location::new(path.span(), self.file);
 
@@ -503,6 +619,18 @@ impl<'a> Resolver<'a> {
         self.resolve_type_inner(typ, &mut vec![])
     }
 
+    /// Resolves the type methods or a trait are implemented for. Implementing them for a
+    /// deprecated struct is not a use of it, so no deprecation warning is issued.
+    pub fn resolve_impl_type(&mut self, typ: UnresolvedType) -> Type {
+        let error_count = self.errors.len();
+        let typ = self.resolve_type(typ);
+
+        let mut new_errors = self.errors.split_off(error_count);
+        new_errors.retain(|error| !matches!(error, ResolverError::DeprecatedStruct { .. }));
+        self.errors.extend(new_errors);
+        typ
+    }
+
     pub fn take_errors(self) -> Vec<ResolverError> {
         self.errors
     }
@@ -538,6 +666,7 @@ impl<'a> Resolver<'a> {
             let id = self.interner.next_type_variable_id();
             let typevar = Shared::new(TypeBinding::Unbound(id));
             let span = generic.0.span();
+            self.interner.add_generic_location(Location::new(span, self.file));
 
             // Check for name collisions of this generic
             let name = Rc::new(generic.0.contents.clone());
@@ -571,6 +700,36 @@ impl<'a> Resolver<'a> {
         (generics, fields, self.errors)
     }
 
+    pub fn resolve_enum_variants(
+        mut self,
+        unresolved: NoirEnum,
+    ) -> (Generics, Vec<(Ident, Vec<Type>)>, Vec<ResolverError>) {
+        let generics = self.add_generics(&unresolved.generics);
+
+        // Check whether the enum definition has globals in the local module and add them to the scope
+        self.resolve_local_globals();
+
+        let variants = vecmap(unresolved.variants, |(name, fields)| {
+            (name, vecmap(fields, |typ| self.resolve_type(typ)))
+        });
+
+        (generics, variants, self.errors)
+    }
+
+    pub fn resolve_type_alias(
+        mut self,
+        unresolved: NoirTypeAlias,
+    ) -> (Generics, Type, Vec<ResolverError>) {
+        let generics = self.add_generics(&unresolved.generics);
+
+        // Check whether the type alias has globals in the local module and add them to the scope
+        self.resolve_local_globals();
+
+        let typ = self.resolve_type(unresolved.typ);
+
+        (generics, typ, self.errors)
+    }
+
     fn resolve_local_globals(&mut self) {
         for (stmt_id, global_info) in self.interner.get_all_globals() {
             if global_info.local_id == self.path_resolver.local_module_id() {
@@ -608,9 +767,14 @@ impl<'a> Resolver<'a> {
                 self.push_err(ResolverError::UnnecessaryPub { ident: func.name_ident().clone() });
             }
 
+            let span = pattern.span();
             let pattern = self.resolve_pattern(pattern, DefinitionKind::Local(None));
             let typ = self.resolve_type_inner(typ, &mut generics);
 
+            if self.abi_required(func) && typ.contains_enum() {
+                self.push_err(ResolverError::EnumInAbi { typ: typ.clone(), span });
+            }
+
             parameters.push(Param(pattern, typ.clone(), visibility));
             parameter_types.push(typ);
         }
@@ -619,6 +783,11 @@ impl<'a> Resolver<'a> {
 
         self.declare_numeric_generics(&parameter_types, &return_type);
 
+        if self.abi_required(func) && return_type.contains_enum() {
+            let typ = return_type.as_ref().clone();
+            self.push_err(ResolverError::EnumInAbi { typ, span: func.name_ident().span() });
+        }
+
         // 'pub_allowed' also implies 'pub' is required on return types
         if self.pub_allowed(func)
             && return_type.as_ref() != &Type::Unit
@@ -639,7 +808,15 @@ impl<'a> Resolver<'a> {
             });
         }
 
-        let mut typ = Type::Function(parameter_types, return_type);
+        if attributes == Some(Attribute::Inline(InlineType::Never)) && !func.def.is_unconstrained {
+            self.push_err(ResolverError::InlineNeverOnConstrainedFunction {
+                span: func.name_ident().span(),
+            });
+        }
+
+        let trait_constraints = self.resolve_trait_constraints(func);
+
+        let mut typ = Type::Function(parameter_types, return_type, Box::new(Type::Unit));
 
         if !generics.is_empty() {
             typ = Type::Forall(generics, Box::new(typ));
@@ -651,10 +828,13 @@ impl<'a> Resolver<'a> {
             name: name_ident,
             kind: func.kind,
             attributes,
+            doc_comments: func.def.doc_comments.clone(),
             contract_function_type: self.handle_function_type(func),
             is_unconstrained: func.def.is_unconstrained,
             location,
+            module_id: self.path_resolver.module_id(),
             typ,
+            trait_constraints,
             parameters: parameters.into(),
             return_visibility: func.def.return_visibility,
             return_distinctness: func.def.return_distinctness,
@@ -662,6 +842,27 @@ impl<'a> Resolver<'a> {
         }
     }
 
+    /// Resolve the trait constraints on the generics of a function, as in `fn foo<T: Hasher>`
+    fn resolve_trait_constraints(&mut self, func: &NoirFunction) -> Vec<TraitConstraint> {
+        let mut trait_constraints = Vec::new();
+        for constraint in func.def.trait_constraints.iter().cloned() {
+            let typ = match self.find_generic(&constraint.generic.0.contents) {
+                Some((name, typevar, _)) => Type::NamedGeneric(typevar.clone(), name.clone()),
+                None => {
+                    self.push_err(ResolverError::ConstraintOnUndeclaredGeneric {
+                        generic: constraint.generic,
+                    });
+                    continue;
+                }
+            };
+
+            if let Some(trait_id) = self.lookup_trait_or_error(constraint.trait_name) {
+                trait_constraints.push(TraitConstraint { typ, trait_id });
+            }
+        }
+        trait_constraints
+    }
+
     /// True if the 'pub' keyword is allowed on parameters in this function
     fn pub_allowed(&self, func: &NoirFunction) -> bool {
         if self.in_contract() {
@@ -671,6 +872,12 @@ impl<'a> Resolver<'a> {
         }
     }
 
+    /// True if the parameters and return type of a function are encoded in an abi, which is
+    /// the case for the main function of a program and for every function of a contract
+    fn abi_required(&self, func: &NoirFunction) -> bool {
+        self.in_contract() || func.name() == MAIN_FUNCTION
+    }
+
     /// True if the `distinct` keyword is allowed on a function's return type
     fn distinct_allowed(&self, func: &NoirFunction) -> bool {
         if self.in_contract() {
@@ -719,7 +926,7 @@ impl<'a> Resolver<'a> {
         }
     }
 
-    fn find_numeric_generics(
+    pub(crate) fn find_numeric_generics(
         parameters: &[Type],
         return_type: &Type,
     ) -> Vec<(String, TypeVariable)> {
@@ -731,6 +938,23 @@ impl<'a> Resolver<'a> {
         found.into_iter().collect()
     }
 
+    /// Finds the generics used in an array length, including those within arithmetic on them
+    fn find_numeric_generics_in_length(
+        length: &Type,
+        found: &mut HashMap<String, Shared<TypeBinding>>,
+    ) {
+        match length {
+            Type::NamedGeneric(type_variable, name) => {
+                found.insert(name.to_string(), type_variable.clone());
+            }
+            Type::InfixExpr(lhs, _, rhs) => {
+                Self::find_numeric_generics_in_length(lhs, found);
+                Self::find_numeric_generics_in_length(rhs, found);
+            }
+            _ => (),
+        }
+    }
+
     fn find_numeric_generics_in_type(typ: &Type, found: &mut HashMap<String, Shared<TypeBinding>>) {
         match typ {
             Type::FieldElement(_)
@@ -742,29 +966,32 @@ impl<'a> Resolver<'a> {
             | Type::TypeVariable(_)
             | Type::PolymorphicInteger(_, _)
             | Type::Constant(_)
+            | Type::InfixExpr(..)
             | Type::NamedGeneric(_, _)
             | Type::Forall(_, _) => (),
 
-            Type::Array(length, _) => {
-                if let Type::NamedGeneric(type_variable, name) = length.as_ref() {
-                    found.insert(name.to_string(), type_variable.clone());
-                }
-            }
+            Type::Array(length, _) => Self::find_numeric_generics_in_length(length, found),
 
             Type::Slice(typ) => {
                 Self::find_numeric_generics_in_type(typ, found);
             }
 
+            Type::FmtString(length, fields) => {
+                Self::find_numeric_generics_in_length(length, found);
+                Self::find_numeric_generics_in_type(fields, found);
+            }
+
             Type::Tuple(fields) => {
                 for field in fields {
                     Self::find_numeric_generics_in_type(field, found);
                 }
             }
-            Type::Function(parameters, return_type) => {
+            Type::Function(parameters, return_type, env) => {
                 for parameter in parameters {
                     Self::find_numeric_generics_in_type(parameter, found);
                 }
                 Self::find_numeric_generics_in_type(return_type, found);
+                Self::find_numeric_generics_in_type(env, found);
             }
             Type::Struct(struct_type, generics) => {
                 for (i, generic) in generics.iter().enumerate() {
@@ -777,19 +1004,47 @@ impl<'a> Resolver<'a> {
                     }
                 }
             }
+            Type::Enum(enum_type, generics) => {
+                for (i, generic) in generics.iter().enumerate() {
+                    if let Type::NamedGeneric(type_variable, name) = generic {
+                        if enum_type.borrow().generic_is_numeric(i) {
+                            found.insert(name.to_string(), type_variable.clone());
+                        }
+                    } else {
+                        Self::find_numeric_generics_in_type(generic, found);
+                    }
+                }
+            }
             Type::MutableReference(element) => Self::find_numeric_generics_in_type(element, found),
         }
     }
 
-    pub fn resolve_global_let(&mut self, let_stmt: crate::LetStatement) -> HirStatement {
-        let expression = self.resolve_expression(let_stmt.expression);
+    /// Resolves the name and type of a global. Its initializer is returned to be resolved by
+    /// `resolve_global_initializer` once every global is declared, so that initializers may refer
+    /// to globals declared after them. Until then the statement refers to a placeholder expression.
+    pub fn resolve_global_let(
+        &mut self,
+        let_stmt: crate::LetStatement,
+    ) -> (HirStatement, Expression) {
+        let expression = self.interner.push_expr(HirExpression::Error);
+        self.interner.push_expr_location(expression, let_stmt.expression.span, self.file);
         let definition = DefinitionKind::Global(expression);
 
-        HirStatement::Let(HirLetStatement {
+        let statement = HirStatement::Let(HirLetStatement {
             pattern: self.resolve_pattern(let_stmt.pattern, definition),
             r#type: self.resolve_type(let_stmt.r#type),
             expression,
-        })
+        });
+        (statement, let_stmt.expression)
+    }
+
+    /// Resolves the initializer of a global declared by `resolve_global_let`, replacing the
+    /// placeholder expression the global refers to.
+    pub fn resolve_global_initializer(&mut self, global: StmtId, initializer: Expression) {
+        let placeholder = self.interner.let_statement(&global).expression;
+        let expression = self.resolve_expression(initializer);
+        let expression = self.interner.expression(&expression);
+        self.interner.replace_expr(&placeholder, expression);
     }
 
     pub fn resolve_stmt(&mut self, stmt: Statement) -> HirStatement {
@@ -805,10 +1060,21 @@ impl<'a> Resolver<'a> {
             }
             Statement::Constrain(constrain_stmt) => {
                 let expr_id = self.resolve_expression(constrain_stmt.0);
-                HirStatement::Constrain(HirConstrainStatement(expr_id, self.file))
+                let message = constrain_stmt.1.map(|message| self.resolve_expression(message));
+                let kind = match (constrain_stmt.2, self.interner.expression(&expr_id)) {
+                    (ConstrainKind::AssertEq, HirExpression::Infix(infix)) => {
+                        HirConstrainKind::AssertEq { lhs: infix.lhs, rhs: infix.rhs }
+                    }
+                    (ConstrainKind::Assert | ConstrainKind::AssertEq, _) => {
+                        HirConstrainKind::Assert
+                    }
+                    (ConstrainKind::Constrain, _) => HirConstrainKind::Constrain,
+                };
+                HirStatement::Constrain(HirConstrainStatement(expr_id, self.file, message, kind))
             }
             Statement::Expression(expr) => HirStatement::Expression(self.resolve_expression(expr)),
             Statement::Semi(expr) => HirStatement::Semi(self.resolve_expression(expr)),
+            Statement::Return(expr) => HirStatement::Return(self.resolve_expression(expr)),
             Statement::Assign(assign_stmt) => {
                 let identifier = self.resolve_lvalue(assign_stmt.lvalue);
                 let expression = self.resolve_expression(assign_stmt.expression);
@@ -869,14 +1135,25 @@ impl<'a> Resolver<'a> {
                 }
                 Literal::Integer(integer) => HirLiteral::Integer(integer),
                 Literal::Str(str) => HirLiteral::Str(str),
+                Literal::FmtStr(str) => {
+                    let fields = self.resolve_format_string_fields(&str, expr.span);
+                    HirLiteral::FmtStr(str, fields)
+                }
             }),
             ExpressionKind::Variable(path) => {
                 // If the Path is being used as an Expression, then it is referring to a global from a separate module
                 // Otherwise, then it is referring to an Identifier
                 // This lookup allows support of such statements: let x = foo::bar::SOME_GLOBAL + 10;
                 // If the expression is a singular indent, we search the resolver's current scope as normal.
-                let hir_ident = self.get_ident_from_path(path);
-                HirExpression::Ident(hir_ident)
+                if let Some((enum_type, variant_index)) = self.lookup_enum_variant(&path) {
+                    let span = path.span();
+                    self.resolve_enum_constructor(enum_type, variant_index, Vec::new(), span)
+                } else if let Some(method) = self.lookup_trait_method(&path) {
+                    HirExpression::TraitMethodReference(method)
+                } else {
+                    let hir_ident = self.get_ident_from_path(path);
+                    HirExpression::Ident(hir_ident)
+                }
             }
             ExpressionKind::Prefix(prefix) => {
                 let operator = prefix.operator;
@@ -893,19 +1170,33 @@ impl<'a> Resolver<'a> {
             ExpressionKind::Infix(infix) => {
                 let lhs = self.resolve_expression(infix.lhs);
                 let rhs = self.resolve_expression(infix.rhs);
+                let overflow = self.overflow_mode();
 
                 HirExpression::Infix(HirInfixExpression {
                     lhs,
-                    operator: HirBinaryOp::new(infix.operator, self.file),
+                    operator: HirBinaryOp::new(infix.operator, self.file, overflow),
                     rhs,
                 })
             }
             ExpressionKind::Call(call_expr) => {
-                // Get the span and name of path for error reporting
-                let func = self.resolve_expression(*call_expr.func);
-                let arguments = vecmap(call_expr.arguments, |arg| self.resolve_expression(arg));
-                let location = Location::new(expr.span, self.file);
-                HirExpression::Call(HirCallExpression { func, arguments, location })
+                // Calls to an enum variant such as `Option::Some(x)` construct the enum instead
+                let variant = match &call_expr.func.kind {
+                    ExpressionKind::Variable(path) => {
+                        self.lookup_enum_variant(path).map(|variant| (variant, path.span()))
+                    }
+                    _ => None,
+                };
+
+                if let Some(((enum_type, variant_index), span)) = variant {
+                    let arguments = vecmap(call_expr.arguments, |arg| self.resolve_expression(arg));
+                    self.resolve_enum_constructor(enum_type, variant_index, arguments, span)
+                } else {
+                    // Get the span and name of path for error reporting
+                    let func = self.resolve_expression(*call_expr.func);
+                    let arguments = vecmap(call_expr.arguments, |arg| self.resolve_expression(arg));
+                    let location = Location::new(expr.span, self.file);
+                    HirExpression::Call(HirCallExpression { func, arguments, location })
+                }
             }
             ExpressionKind::MethodCall(call_expr) => {
                 let method = call_expr.method_name;
@@ -947,11 +1238,25 @@ impl<'a> Resolver<'a> {
                     identifier,
                 })
             }
+            ExpressionKind::While(while_expr) => HirExpression::While(HirWhileExpression {
+                condition: self.resolve_expression(while_expr.condition),
+                block: self.resolve_expression(while_expr.block),
+            }),
             ExpressionKind::If(if_expr) => HirExpression::If(HirIfExpression {
                 condition: self.resolve_expression(if_expr.condition),
                 consequence: self.resolve_expression(if_expr.consequence),
                 alternative: if_expr.alternative.map(|e| self.resolve_expression(e)),
             }),
+            ExpressionKind::Match(match_expr) => {
+                let expression = self.resolve_expression(match_expr.expression);
+                let rules = vecmap(match_expr.rules, |(pattern, rule)| {
+                    self.in_new_scope(|this| {
+                        let pattern = this.resolve_match_pattern(pattern);
+                        (pattern, this.resolve_expression(rule))
+                    })
+                });
+                HirExpression::Match(HirMatchExpression { expression, rules })
+            }
             ExpressionKind::Index(indexed_expr) => HirExpression::Index(HirIndexExpression {
                 collection: self.resolve_expression(indexed_expr.collection),
                 index: self.resolve_expression(indexed_expr.index),
@@ -996,8 +1301,8 @@ impl<'a> Resolver<'a> {
             // We must stay in the same function scope as the parent function to allow for closures
             // to capture variables. This is currently limited to immutable variables.
             ExpressionKind::Lambda(lambda) => self.in_new_scope(|this| {
-                let new_index = this.current_lambda_index();
-                let old_index = std::mem::replace(&mut this.lambda_index, new_index);
+                let scope_index = this.scopes.current_scope_index();
+                this.lambda_stack.push(LambdaContext { captures: Vec::new(), scope_index });
 
                 let parameters = vecmap(lambda.parameters, |(pattern, typ)| {
                     let parameter = DefinitionKind::Local(None);
@@ -1007,8 +1312,8 @@ impl<'a> Resolver<'a> {
                 let return_type = this.resolve_inferred_type(lambda.return_type);
                 let body = this.resolve_expression(lambda.body);
 
-                this.lambda_index = old_index;
-                HirExpression::Lambda(HirLambda { parameters, return_type, body })
+                let captures = this.lambda_stack.pop().expect("lambda context was pushed").captures;
+                HirExpression::Lambda(HirLambda { parameters, return_type, body, captures })
             }),
         };
 
@@ -1017,6 +1322,74 @@ impl<'a> Resolver<'a> {
         expr_id
     }
 
+    fn resolve_enum_constructor(
+        &mut self,
+        enum_type: Shared<EnumType>,
+        variant_index: usize,
+        arguments: Vec<ExprId>,
+        span: Span,
+    ) -> HirExpression {
+        let expected = enum_type.borrow().get_variant_field_count(variant_index);
+        if arguments.len() != expected {
+            let variant = enum_type.borrow().variant_name(variant_index).to_string();
+            let actual = arguments.len();
+            self.push_err(ResolverError::IncorrectVariantFieldCount {
+                variant,
+                expected,
+                actual,
+                span,
+            });
+            return HirExpression::Error;
+        }
+
+        let enum_generics = enum_type.borrow().instantiate(self.interner);
+        HirExpression::EnumConstructor(HirEnumConstructorExpression {
+            r#type: enum_type,
+            enum_generics,
+            variant_index,
+            arguments,
+        })
+    }
+
+    fn resolve_match_pattern(&mut self, pattern: MatchPattern) -> HirMatchPattern {
+        match pattern {
+            MatchPattern::Wildcard(span) => HirMatchPattern::Wildcard(span),
+            MatchPattern::Variant(path, fields, span) => {
+                let variant = self.lookup_enum_variant(&path);
+
+                let fields = vecmap(fields, |field| {
+                    self.resolve_pattern(field, DefinitionKind::Local(None))
+                });
+
+                match variant {
+                    Some((enum_type, variant_index)) => {
+                        let expected = enum_type.borrow().get_variant_field_count(variant_index);
+                        if fields.len() != expected {
+                            let variant =
+                                enum_type.borrow().variant_name(variant_index).to_string();
+                            let actual = fields.len();
+                            self.push_err(ResolverError::IncorrectVariantFieldCount {
+                                variant,
+                                expected,
+                                actual,
+                                span,
+                            });
+                        }
+
+                        let generics = enum_type.borrow().instantiate(self.interner);
+                        let typ = Type::Enum(enum_type, generics);
+                        HirMatchPattern::Variant(typ, variant_index, fields, span)
+                    }
+                    None => {
+                        let path = path.to_string();
+                        self.push_err(ResolverError::ExpectedEnumVariant { path, span });
+                        HirMatchPattern::Wildcard(span)
+                    }
+                }
+            }
+        }
+    }
+
     fn resolve_pattern(&mut self, pattern: Pattern, definition: DefinitionKind) -> HirPattern {
         self.resolve_pattern_mutable(pattern, None, definition)
     }
@@ -1104,6 +1477,13 @@ impl<'a> Resolver<'a> {
         for (field, expr) in fields {
             let resolved = resolve_function(self, expr);
 
+            let index = struct_type.borrow().field_idents().iter().position(|name| name == &field);
+            if let Some(index) = index {
+                let location = Location::new(field.span(), self.file);
+                let reference = ReferenceId::Field(struct_type.borrow().id, index);
+                self.interner.add_reference(location, reference);
+            }
+
             if unseen_fields.contains(&field) {
                 unseen_fields.remove(&field);
                 seen_fields.insert(field.clone());
@@ -1151,6 +1531,7 @@ impl<'a> Resolver<'a> {
         let id = self.resolve_path(path)?;
 
         if let Some(function) = TryFromModuleDefId::try_from(id) {
+            self.interner.mark_function_used(function);
             return Ok(self.interner.function_definition_id(function));
         }
 
@@ -1175,6 +1556,59 @@ impl<'a> Resolver<'a> {
         }
     }
 
+    /// Lookup an enum variant by a path of the form `Enum::Variant`, returning the enum
+    /// along with the index of the variant. Returns None without issuing an error if the
+    /// path does not refer to an enum variant.
+    fn lookup_enum_variant(&mut self, path: &Path) -> Option<(Shared<EnumType>, usize)> {
+        if path.segments.len() < 2 {
+            return None;
+        }
+
+        let mut enum_path = path.clone();
+        let variant_name = enum_path.pop();
+
+        match self.resolve_module_def(enum_path) {
+            Ok(ModuleDefId::EnumId(id)) => {
+                let enum_type = self.interner.get_enum(id);
+                let index = enum_type.borrow().find_variant(&variant_name.0.contents)?;
+                Some((enum_type, index))
+            }
+            _ => None,
+        }
+    }
+
+    /// Lookup a method of a trait by a path of the form `Trait::method`, as in
+    /// `Default::default()`. Returns None without issuing an error if the path does not refer
+    /// to one, since it may still refer to a function or global.
+    fn lookup_trait_method(&mut self, path: &Path) -> Option<TraitMethodId> {
+        if path.segments.len() < 2 {
+            return None;
+        }
+
+        let mut trait_path = path.clone();
+        let method_name = trait_path.pop();
+
+        match self.resolve_module_def(trait_path) {
+            Ok(ModuleDefId::TraitId(trait_id)) => {
+                let the_trait = self.interner.try_get_trait(trait_id)?;
+                let method_index = the_trait.find_method(&method_name.0.contents)?;
+                Some(TraitMethodId { trait_id, method_index })
+            }
+            _ => None,
+        }
+    }
+
+    /// Lookup a given trait by name.
+    pub fn lookup_trait_or_error(&mut self, path: Path) -> Option<TraitId> {
+        match self.lookup(path) {
+            Ok(trait_id) => Some(trait_id),
+            Err(error) => {
+                self.push_err(error);
+                None
+            }
+        }
+    }
+
     /// Looks up a given type by name.
     /// This will also instantiate any struct types found.
     fn lookup_type_or_error(&mut self, path: Path) -> Option<Type> {
@@ -1199,7 +1633,30 @@ impl<'a> Resolver<'a> {
     }
 
     fn resolve_path(&mut self, path: Path) -> Result<ModuleDefId, ResolverError> {
-        self.path_resolver.resolve(self.def_maps, path).map_err(ResolverError::PathResolutionError)
+        self.resolve_module_def(path).map_err(ResolverError::PathResolutionError)
+    }
+
+    /// Resolves a path to the item it refers to, noting the name it starts with as used in the
+    /// module it is looked up in so that the imports which are never used can be found.
+    fn resolve_module_def(&mut self, path: Path) -> Result<ModuleDefId, PathResolutionError> {
+        let module_id = self.path_resolver.module_id();
+        let scope = match path.kind {
+            PathKind::Plain => Some(module_id),
+            PathKind::Crate => self
+                .def_maps
+                .get(&module_id.krate)
+                .map(|def_map| ModuleId { krate: module_id.krate, local_id: def_map.root() }),
+            PathKind::Dep => None,
+        };
+
+        if let (Some(scope), Some(first_segment)) = (scope, path.segments.first()) {
+            self.interner.mark_name_used(scope, &first_segment.0.contents);
+        }
+
+        let location = Location::new(path.span(), self.file);
+        let id = self.path_resolver.resolve(self.def_maps, path)?;
+        self.interner.add_reference(location, ReferenceId::Item(id));
+        Ok(id)
     }
 
     fn resolve_block(&mut self, block_expr: BlockExpression) -> HirExpression {
@@ -1213,45 +1670,39 @@ impl<'a> Resolver<'a> {
         self.interner.push_expr(hir_block)
     }
 
-    fn eval_global_as_array_length(&mut self, global: StmtId) -> u64 {
-        let stmt = match self.interner.statement(&global) {
-            HirStatement::Let(let_expr) => let_expr,
-            other => {
-                dbg!(other);
-                return 0;
+    /// Globals initialized with an integer literal can be used as array lengths directly. The
+    /// values of any others are found by evaluating their initializers at compile-time once
+    /// every function they may call has been resolved, so a type variable stands in for them
+    /// until then.
+    fn eval_global_as_array_length(&mut self, global: StmtId) -> Type {
+        if let HirStatement::Let(let_expr) = self.interner.statement(&global) {
+            let length = let_expr.expression;
+            if let HirExpression::Literal(HirLiteral::Integer(int)) =
+                self.interner.expression(&length)
+            {
+                return match int.try_into_u128().and_then(|length| length.try_into().ok()) {
+                    Some(length) => Type::Constant(length),
+                    None => {
+                        let span = self.interner.expr_span(&length);
+                        self.push_err(ResolverError::IntegerTooLarge { span });
+                        Type::Error
+                    }
+                };
             }
-        };
-
-        let length = stmt.expression;
-        let span = self.interner.expr_span(&length);
-        let result = self.try_eval_array_length_id(length, span);
-
-        match result.map(|length| length.try_into()) {
-            Ok(Ok(length_value)) => return length_value,
-            Ok(Err(_cast_err)) => self.push_err(ResolverError::IntegerTooLarge { span }),
-            Err(Some(error)) => self.push_err(error),
-            Err(None) => (),
         }
-        0
-    }
 
-    fn try_eval_array_length_id(
-        &self,
-        rhs: ExprId,
-        span: Span,
-    ) -> Result<u128, Option<ResolverError>> {
-        match self.interner.expression(&rhs) {
-            HirExpression::Literal(HirLiteral::Integer(int)) => {
-                int.try_into_u128().ok_or(Some(ResolverError::IntegerTooLarge { span }))
-            }
-            _other => Err(Some(ResolverError::InvalidArrayLengthExpr { span })),
-        }
+        self.interner.comptime_array_length(global)
     }
 
     fn in_contract(&self) -> bool {
         let module_id = self.path_resolver.module_id();
         module_id.module(self.def_maps).is_contract
     }
+
+    /// The overflow mode of the crate being resolved
+    fn overflow_mode(&self) -> OverflowMode {
+        self.def_maps[&self.path_resolver.module_id().krate].overflow_mode
+    }
 }
 
 /// Gives an error if a user tries to create a mutable reference
@@ -1279,6 +1730,54 @@ pub fn verify_mutable_reference(interner: &NodeInterner, rhs: ExprId) -> Result<
     }
 }
 
+/// Finds the name closest to `name` by edit distance, if any is close enough that it was likely
+/// intended. Names of a single character are never considered similar to any other.
+fn find_similar_name<'a>(
+    name: &str,
+    candidates: impl Iterator<Item = &'a String>,
+) -> Option<&'a String> {
+    let max_distance = std::cmp::max(name.len(), 3) / 3;
+
+    candidates
+        .filter(|candidate| candidate.as_str() != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance && *distance < name.len())
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// The number of single character insertions, deletions, substitutions and transpositions
+/// of adjacent characters needed to turn one string into the other
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // distances[i][j] is the distance between the first i characters of `a` and the first j of `b`
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
 // XXX: These tests repeat a lot of code
 // what we should do is have test cases which are passed to a test harness
 // A test harness will allow for more expressive and readable tests
@@ -1299,7 +1798,7 @@ mod test {
     use crate::node_interner::{FuncId, NodeInterner};
     use crate::{
         hir::def_map::{CrateDefMap, LocalModuleId, ModuleDefId},
-        parse_program, Path,
+        parse_program, OverflowMode, Path,
     };
 
     use super::{PathResolver, Resolver};
@@ -1307,6 +1806,13 @@ mod test {
     // func_namespace is used to emulate the fact that functions can be imported
     // and functions can be forward declared
     fn resolve_src_code(src: &str, func_namespace: Vec<&str>) -> Vec<ResolverError> {
+        resolve_src_code_with_interner(src, func_namespace).0
+    }
+
+    fn resolve_src_code_with_interner(
+        src: &str,
+        func_namespace: Vec<&str>,
+    ) -> (Vec<ResolverError>, NodeInterner) {
         let (program, errors) = parse_program(src);
         assert!(errors.is_empty());
 
@@ -1336,6 +1842,7 @@ mod test {
                 modules,
                 krate: CrateId::dummy_id(),
                 extern_prelude: HashMap::new(),
+                overflow_mode: OverflowMode::default(),
             },
         );
 
@@ -1348,7 +1855,7 @@ mod test {
             errors.extend(err);
         }
 
-        errors
+        (errors, interner)
     }
 
     #[test]
@@ -1374,6 +1881,95 @@ mod test {
         let errors = resolve_src_code(src, vec!["main"]);
         assert!(errors.is_empty());
     }
+    #[test]
+    fn find_definition_of_variables() {
+        let src = r#"
+            fn main(x : Field) {
+                let y = x;
+                assert(y == x);
+            }
+        "#;
+
+        let (errors, interner) = resolve_src_code_with_interner(src, vec!["main"]);
+        assert!(errors.is_empty());
+
+        let find_definition = |usage: &str| {
+            let offset = src.find(usage).unwrap() as u32;
+            interner
+                .find_definition(FileId::default(), offset)
+                .map(|location| location.span.start())
+        };
+        assert_eq!(find_definition("y =="), src.find("y =").map(|offset| offset as u32));
+        assert_eq!(find_definition("x);"), src.find("x :").map(|offset| offset as u32));
+        assert_eq!(find_definition("assert"), None);
+    }
+
+    #[test]
+    fn find_references_of_variables() {
+        let src = r#"
+            fn main(x : Field) {
+                let y = x;
+                assert(y == x);
+            }
+        "#;
+
+        let (errors, interner) = resolve_src_code_with_interner(src, vec!["main"]);
+        assert!(errors.is_empty());
+
+        let find_references = |usage: &str, include_declaration: bool| {
+            let offset = src.find(usage).unwrap() as u32;
+            vecmap(
+                interner.find_references(FileId::default(), offset, include_declaration),
+                |location| location.span.start() as usize,
+            )
+        };
+        let uses_of_x = vec![src.find("x;").unwrap(), src.find("x);").unwrap()];
+        assert_eq!(find_references("x :", false), uses_of_x);
+        assert_eq!(find_references("x);", false), uses_of_x);
+
+        let declaration_and_uses_of_y = vec![src.find("y =").unwrap(), src.find("y ==").unwrap()];
+        assert_eq!(find_references("y ==", true), declaration_and_uses_of_y);
+        assert!(find_references("assert", true).is_empty());
+    }
+
+    #[test]
+    fn find_let_statements_without_type_annotations() {
+        let src = r#"
+            fn main(x : Field) {
+                let y = x;
+                let z: Field = y;
+                for i in 0..3 {
+                    let (a, mut b) = (i, z);
+                }
+            }
+        "#;
+
+        let (errors, interner) = resolve_src_code_with_interner(src, vec!["main"]);
+        assert!(errors.is_empty());
+
+        assert_eq!(interner.unannotated_let_patterns_in_file(FileId::default()).len(), 2);
+        assert_eq!(interner.for_loops_in_file(FileId::default()).len(), 1);
+    }
+
+    #[test]
+    fn record_locations_of_generics() {
+        let src = r#"
+            fn id<T>(x: T) -> T {
+                x
+            }
+        "#;
+
+        let (errors, interner) = resolve_src_code_with_interner(src, vec!["id"]);
+        assert!(errors.is_empty());
+
+        let mut generics =
+            vecmap(interner.generics_in_file(FileId::default()), |location| location.span.start());
+        generics.sort();
+        generics.dedup();
+        let expected: Vec<_> = src.match_indices('T').map(|(index, _)| index as u32).collect();
+        assert_eq!(generics, expected);
+    }
+
     #[test]
     fn resolve_unused_var() {
         let src = r#"
@@ -1397,6 +1993,20 @@ mod test {
         }
     }
 
+    #[test]
+    fn resolve_allowed_unused_var() {
+        let src = r#"
+            #[allow(unused)]
+            fn main(x : Field) {
+                let y = x + x;
+                assert(x == x);
+            }
+        "#;
+
+        let errors = resolve_src_code(src, vec!["main"]);
+        assert!(errors.is_empty(), "Expected no errors, got: {:?}", errors);
+    }
+
     #[test]
     fn resolve_unresolved_var() {
         let src = r#"
@@ -1413,7 +2023,30 @@ mod test {
 
         // It should be regarding the unresolved var `z` (Maybe change to undeclared and special case)
         match &errors[0] {
-            ResolverError::VariableNotDeclared { name, span: _ } => assert_eq!(name, "z"),
+            ResolverError::VariableNotDeclared { name, similar_name, .. } => {
+                assert_eq!(name, "z");
+                assert_eq!(similar_name, &None);
+            }
+            _ => unimplemented!("we should only have an unresolved variable"),
+        }
+    }
+
+    #[test]
+    fn resolve_misspelled_var() {
+        let src = r#"
+            fn main(x : Field) {
+                let value = x + x;
+                assert(valeu == x);
+            }
+        "#;
+
+        let errors = resolve_src_code(src, vec!["main"]);
+        assert_eq!(errors.len(), 1);
+
+        match &errors[0] {
+            ResolverError::VariableNotDeclared { similar_name, .. } => {
+                assert_eq!(similar_name.as_deref(), Some("value"));
+            }
             _ => unimplemented!("we should only have an unresolved variable"),
         }
     }
@@ -1,10 +1,14 @@
+use noirc_errors::registry::{self, ErrorCode};
 use noirc_errors::CustomDiagnostic as Diagnostic;
+use noirc_errors::Lint;
 use noirc_errors::Span;
 use thiserror::Error;
 
 use crate::hir::resolution::errors::ResolverError;
 use crate::hir_def::expr::HirBinaryOp;
 use crate::hir_def::types::Type;
+use crate::node_interner::FuncId;
+use crate::Ident;
 
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum TypeCheckError {
@@ -24,6 +28,10 @@ pub enum TypeCheckError {
     Unstructured { msg: String, span: Span },
     #[error("Error with additional context")]
     Context { err: Box<TypeCheckError>, ctx: &'static str },
+    #[error("Error with a suggested fix")]
+    Suggestion { err: Box<TypeCheckError>, suggestion: Suggestion },
+    #[error("Error in an argument of a call to generic function {}", call.function)]
+    GenericCall { err: Box<TypeCheckError>, call: GenericCallContext },
     #[error("Array is not homogeneous")]
     NonHomogeneousArray {
         first_span: Span,
@@ -35,19 +43,102 @@ pub enum TypeCheckError {
     },
     #[error("Cannot infer type of expression, type annotations needed before this point")]
     TypeAnnotationsNeeded { span: Span },
+    #[error("Type {typ:?} does not implement trait {trait_name:?}")]
+    TraitNotImplemented { typ: String, trait_name: String, span: Span },
+    #[error("Method {method_name:?} is declared by more than one trait implemented by {typ:?}")]
+    AmbiguousTraitMethod { method_name: String, typ: String, trait_names: Vec<String>, span: Span },
+    #[error("Match on enum {enum_name:?} is missing arms for {missing:?}")]
+    MissingMatchArms { enum_name: String, missing: Vec<String>, span: Span },
+    #[error("Loops without a fixed range are only allowed in unconstrained functions")]
+    UnboundedLoopInConstrainedCode { span: Span },
+    #[error("Cannot return outside of a function")]
+    ReturnOutsideFunction { span: Span },
+    #[error("Integer literal {value} does not fit in type {typ}")]
+    IntegerLiteralOverflow { value: String, typ: Type, span: Span },
+    #[error("Use of deprecated {kind} {name}")]
+    Deprecated { kind: &'static str, name: String, note: Option<String>, span: Span },
+    #[error("Unreachable code")]
+    UnreachableCode { span: Span },
+    #[error("Field {field} of struct {struct_name} is never read")]
+    FieldNeverRead { field: Ident, struct_name: String },
+    #[error("Cannot infer the value of numeric generic {generic} of {function}")]
+    NumericGenericNotInferred { generic: String, function: String, span: Span },
+    #[error("Type-level expression {expression} cannot be evaluated")]
+    InvalidTypeExpression { expression: String, function: String, span: Span },
     #[error("{0}")]
     ResolverError(ResolverError),
 }
 
+/// A fix for a type error, replacing the source code within `span` with `replacement`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub message: String,
+    pub span: Span,
+    pub replacement: String,
+}
+
+/// The numeric generics of a generic function at a call to it with a mistyped argument, to
+/// explain where the type the argument was expected to have came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenericCallContext {
+    pub function: String,
+    /// The type of the parameter as declared by the function, in terms of its generics
+    pub declared_type: String,
+    /// Each generic whose value was inferred from a previous argument, along with that value
+    /// and the span of the argument
+    pub inferred: Vec<(String, String, Span)>,
+    /// The generics of the calling function which those of the function were inferred to be,
+    /// followed up the call stack once every function is type checked
+    pub caller_generics: Vec<(FuncId, String)>,
+    /// How each function up the call stack instantiates the generics of the function it calls
+    pub call_chain: Vec<String>,
+}
+
 impl TypeCheckError {
     pub fn add_context(self, ctx: &'static str) -> Self {
         TypeCheckError::Context { err: Box::new(self), ctx }
     }
+
+    pub fn with_suggestion(self, suggestion: Option<Suggestion>) -> Self {
+        match suggestion {
+            Some(suggestion) => TypeCheckError::Suggestion { err: Box::new(self), suggestion },
+            None => self,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            TypeCheckError::OpCannotBeUsed { .. } => registry::E0066,
+            TypeCheckError::TypeCannotBeUsed { .. } => registry::E0067,
+            TypeCheckError::TypeMismatch { .. } => registry::E0068,
+            TypeCheckError::ArityMisMatch { .. } => registry::E0069,
+            TypeCheckError::PublicReturnType { .. } => registry::E0070,
+            TypeCheckError::Unstructured { .. } => registry::E0071,
+            TypeCheckError::Context { err, .. } => err.code(),
+            TypeCheckError::Suggestion { err, .. } => err.code(),
+            TypeCheckError::GenericCall { err, .. } => err.code(),
+            TypeCheckError::NonHomogeneousArray { .. } => registry::E0072,
+            TypeCheckError::TypeAnnotationsNeeded { .. } => registry::E0073,
+            TypeCheckError::TraitNotImplemented { .. } => registry::E0074,
+            TypeCheckError::AmbiguousTraitMethod { .. } => registry::E0075,
+            TypeCheckError::MissingMatchArms { .. } => registry::E0076,
+            TypeCheckError::UnboundedLoopInConstrainedCode { .. } => registry::E0077,
+            TypeCheckError::ReturnOutsideFunction { .. } => registry::E0078,
+            TypeCheckError::IntegerLiteralOverflow { .. } => registry::E0079,
+            TypeCheckError::Deprecated { .. } => registry::E0080,
+            TypeCheckError::UnreachableCode { .. } => registry::E0081,
+            TypeCheckError::FieldNeverRead { .. } => registry::E0082,
+            TypeCheckError::NumericGenericNotInferred { .. } => registry::E0092,
+            TypeCheckError::InvalidTypeExpression { .. } => registry::E0095,
+            TypeCheckError::ResolverError(error) => error.code(),
+        }
+    }
 }
 
 impl From<TypeCheckError> for Diagnostic {
     fn from(error: TypeCheckError) -> Diagnostic {
-        match error {
+        let code = error.code();
+        let diagnostic = match error {
             TypeCheckError::TypeCannotBeUsed { typ, place, span } => Diagnostic::simple_error(
                 format!("The type {} cannot be used in a {}", &typ, place),
                 String::new(),
@@ -58,6 +149,28 @@ impl From<TypeCheckError> for Diagnostic {
                 diag.add_note(ctx.to_owned());
                 diag
             }
+            TypeCheckError::Suggestion { err, suggestion } => {
+                let mut diag = Diagnostic::from(*err);
+                diag.add_suggestion(suggestion.message, suggestion.span, suggestion.replacement);
+                diag
+            }
+            TypeCheckError::GenericCall { err, call } => {
+                let mut diag = Diagnostic::from(*err);
+                for (generic, value, span) in call.inferred {
+                    diag.add_secondary(
+                        format!("`{generic}` was inferred to be {value} from this argument"),
+                        span,
+                    );
+                }
+                diag.add_note(format!(
+                    "`{}` declares this parameter as `{}`",
+                    call.function, call.declared_type
+                ));
+                for note in call.call_chain {
+                    diag.add_note(note);
+                }
+                diag
+            }
             TypeCheckError::OpCannotBeUsed { op, place, span } => Diagnostic::simple_error(
                 format!("The operator {op:?} cannot be used in a {place}"),
                 String::new(),
@@ -106,7 +219,94 @@ impl From<TypeCheckError> for Diagnostic {
                 "Type must be known at this point".to_string(),
                 span,
             ),
+            TypeCheckError::TraitNotImplemented { typ, trait_name, span } => {
+                Diagnostic::simple_error(
+                    format!("The trait {trait_name} is not implemented for type {typ}"),
+                    format!("{typ} must implement {trait_name} here"),
+                    span,
+                )
+            }
+            TypeCheckError::AmbiguousTraitMethod { method_name, typ, trait_names, span } => {
+                Diagnostic::simple_error(
+                    format!(
+                        "Multiple traits implemented by {typ} declare a method named {method_name}"
+                    ),
+                    format!("{method_name} may be a method of any of {}", trait_names.join(", ")),
+                    span,
+                )
+            }
+            TypeCheckError::MissingMatchArms { enum_name, missing, span } => {
+                let plural = if missing.len() == 1 { "" } else { "s" };
+                let missing = missing.join(", ");
+                Diagnostic::simple_error(
+                    format!(
+                        "Non-exhaustive match on {enum_name}, missing variant{plural} {missing}"
+                    ),
+                    "Add an arm for each missing variant or a wildcard `_` arm".into(),
+                    span,
+                )
+            }
+            TypeCheckError::UnboundedLoopInConstrainedCode { span } => {
+                let mut diag = Diagnostic::simple_error(
+                    "`while` and `loop` are only allowed in unconstrained functions".into(),
+                    "constrained loops must be unrolled, which requires a fixed range".into(),
+                    span,
+                );
+                diag.add_note(
+                    "Consider using a `for` loop, or moving this loop into an unconstrained function"
+                        .into(),
+                );
+                diag
+            }
+            TypeCheckError::ReturnOutsideFunction { span } => Diagnostic::simple_error(
+                "Cannot return outside of a function".into(),
+                "`return` may only be used within a function body".into(),
+                span,
+            ),
+            TypeCheckError::IntegerLiteralOverflow { value, typ, span } => {
+                Diagnostic::simple_error(
+                    format!("Integer literal {value} does not fit in type {typ}"),
+                    format!("the range of {typ} does not include {value}"),
+                    span,
+                )
+            }
+            TypeCheckError::Deprecated { kind, name, note, span } => Diagnostic::simple_warning(
+                format!("use of deprecated {kind} `{name}`"),
+                note.unwrap_or_default(),
+                span,
+            )
+            .with_lint(Lint::Deprecated),
+            TypeCheckError::UnreachableCode { span } => Diagnostic::simple_warning(
+                "unreachable code".to_string(),
+                "this is never evaluated since the program has already returned or failed"
+                    .to_string(),
+                span,
+            )
+            .with_lint(Lint::UnreachableCode),
+            TypeCheckError::FieldNeverRead { field, struct_name } => Diagnostic::simple_warning(
+                format!("field `{field}` of struct `{struct_name}` is never read"),
+                "unread field".to_string(),
+                field.span(),
+            )
+            .with_lint(Lint::Unused),
+            TypeCheckError::NumericGenericNotInferred { generic, function, span } => {
+                Diagnostic::simple_error(
+                    format!(
+                        "cannot infer the value of numeric generic `{generic}` of `{function}`"
+                    ),
+                    "consider annotating the type this call is expected to return".to_string(),
+                    span,
+                )
+            }
+            TypeCheckError::InvalidTypeExpression { expression, function, span } => {
+                Diagnostic::simple_error(
+                    format!("cannot evaluate `{expression}` in the type of `{function}`"),
+                    "this overflows or divides by zero for the generics of this call".to_string(),
+                    span,
+                )
+            }
             TypeCheckError::ResolverError(error) => error.into(),
-        }
+        };
+        diagnostic.with_code(code)
     }
 }
@@ -1,20 +1,37 @@
+use std::collections::HashSet;
+
+use acvm::FieldElement;
 use iter_extended::vecmap;
-use noirc_errors::Span;
+use noirc_errors::{Location, Span};
 
 use crate::{
-    hir::resolution::resolver::verify_mutable_reference,
+    hir::{
+        def_map::ModuleDefId,
+        resolution::{
+            errors::ResolverError,
+            import::{can_reference_item, parent_module, PathResolutionError},
+            resolver::{verify_mutable_reference, Resolver},
+        },
+    },
     hir_def::{
         expr::{
-            self, HirArrayLiteral, HirBinaryOp, HirExpression, HirLiteral, HirMethodCallExpression,
-            HirPrefixExpression,
+            self, HirArrayLiteral, HirBinaryOp, HirCallExpression, HirEnumConstructorExpression,
+            HirExpression, HirLiteral, HirMatchExpression, HirMatchPattern,
+            HirMethodCallExpression, HirMethodReference, HirPrefixExpression,
         },
-        types::Type,
+        function::FuncMeta,
+        traits::{OverloadableOperator, TraitConstraint, TraitMethodId},
+        types::{StructType, Type},
     },
-    node_interner::{ExprId, FuncId},
-    CompTime, Shared, TypeBinding, UnaryOp,
+    node_interner::{DefinitionKind, ExprId, FuncId, ReferenceId, TraitId},
+    token::Attribute,
+    BinaryOpKind, CompTime, Ident, Shared, Signedness, TypeBinding, TypeBindings, UnaryOp,
 };
 
-use super::{errors::TypeCheckError, TypeChecker};
+use super::{
+    errors::{GenericCallContext, TypeCheckError},
+    TypeChecker,
+};
 
 impl<'interner> TypeChecker<'interner> {
     /// Infers a type for a given expression, and return this type.
@@ -34,6 +51,17 @@ impl<'interner> TypeChecker<'interner> {
                 // variable to handle generic functions.
                 let t = self.interner.id_type(ident.id);
                 let (typ, bindings) = t.instantiate(self.interner);
+
+                let func_id = match self.interner.definition(ident.id).kind {
+                    DefinitionKind::Function(func_id) => Some(func_id),
+                    _ => None,
+                };
+                if let Some(meta) = func_id.and_then(|id| self.interner.try_function_meta(&id)) {
+                    let span = ident.location.span;
+                    self.check_trait_constraints(&meta.trait_constraints, &bindings, span);
+                    self.check_deprecated_function(&meta, span);
+                }
+
                 self.interner.store_instantiation_bindings(*expr_id, bindings);
                 typ
             }
@@ -81,11 +109,19 @@ impl<'interner> TypeChecker<'interner> {
                         Type::Array(Box::new(length), Box::new(elem_type))
                     }
                     HirLiteral::Bool(_) => Type::Bool(CompTime::new(self.interner)),
-                    HirLiteral::Integer(_) => Type::polymorphic_integer(self.interner),
+                    HirLiteral::Integer(value) => {
+                        let span = self.interner.expr_span(expr_id);
+                        self.check_integer_literal(value, false, span)
+                    }
                     HirLiteral::Str(string) => {
                         let len = Type::Constant(string.len() as u64);
                         Type::String(Box::new(len))
                     }
+                    HirLiteral::FmtStr(string, fields) => {
+                        let len = Type::Constant(string.len() as u64);
+                        let fields = vecmap(fields, |field| self.check_expression(&field));
+                        Type::FmtString(Box::new(len), Box::new(Type::Tuple(fields)))
+                    }
                 }
             }
             HirExpression::Infix(infix_expr) => {
@@ -97,33 +133,60 @@ impl<'interner> TypeChecker<'interner> {
                 let rhs_span = self.interner.expr_span(&infix_expr.rhs);
                 let span = lhs_span.merge(rhs_span);
 
-                self.infix_operand_type_rules(&lhs_type, &infix_expr.operator, &rhs_type, span)
-                    .unwrap_or_else(|error| {
-                        self.errors.push(error);
-                        Type::Error
-                    })
+                let operator = OverloadableOperator::from_binary_op(infix_expr.operator.kind);
+                if let Some(method) = operator.and_then(|op| self.operator_method(&lhs_type, op)) {
+                    let arguments = vec![(infix_expr.lhs, lhs_type), (infix_expr.rhs, rhs_type)];
+                    let location = infix_expr.operator.location;
+                    let (call, typ) = self.check_operator_call(method, arguments, location);
+
+                    if infix_expr.operator.kind == BinaryOpKind::NotEqual {
+                        // `a != b` is desugared to `!a.eq(b)`
+                        let call_id = self.interner.push_expr(call);
+                        self.interner.push_expr_location(call_id, span, location.file);
+                        self.interner.push_expr_type(&call_id, typ.clone());
+
+                        let not = HirPrefixExpression { operator: UnaryOp::Not, rhs: call_id };
+                        self.interner.replace_expr(expr_id, HirExpression::Prefix(not));
+                    } else {
+                        self.interner.replace_expr(expr_id, call);
+                    }
+                    typ
+                } else {
+                    self.infix_operand_type_rules(&lhs_type, &infix_expr.operator, &rhs_type, span)
+                        .unwrap_or_else(|error| {
+                            self.errors.push(error);
+                            Type::Error
+                        })
+                }
             }
-            HirExpression::Index(index_expr) => self.check_index_expression(index_expr),
+            HirExpression::Index(index_expr) => self.check_index_expression(expr_id, index_expr),
             HirExpression::Call(call_expr) => {
                 let function = self.check_expression(&call_expr.func);
                 let args = vecmap(&call_expr.arguments, |arg| {
                     let typ = self.check_expression(arg);
-                    (typ, self.interner.expr_span(arg))
+                    (typ, *arg, self.interner.expr_span(arg))
                 });
                 let span = self.interner.expr_span(expr_id);
-                self.bind_function_type(function, args, span)
+                let generic_call = self.generic_call(&call_expr.func, span);
+                self.bind_function_type(function, args, span, generic_call)
             }
             HirExpression::MethodCall(mut method_call) => {
                 let object_type = self.check_expression(&method_call.object).follow_bindings();
                 let method_name = method_call.method.0.contents.as_str();
                 match self.lookup_method(object_type.clone(), method_name, expr_id) {
-                    Some(method_id) => {
-                        let mut args =
-                            vec![(object_type, self.interner.expr_span(&method_call.object))];
+                    Some(method_ref) => {
+                        if let (HirMethodReference::FuncId(_), Type::Struct(struct_type, _)) =
+                            (method_ref, &object_type)
+                        {
+                            self.check_method_is_visible(struct_type, &method_call.method);
+                        }
+
+                        let object_span = self.interner.expr_span(&method_call.object);
+                        let mut args = vec![(object_type, method_call.object, object_span)];
 
                         let mut arg_types = vecmap(&method_call.arguments, |arg| {
                             let typ = self.check_expression(arg);
-                            (typ, self.interner.expr_span(arg))
+                            (typ, *arg, self.interner.expr_span(arg))
                         });
                         args.append(&mut arg_types);
 
@@ -131,22 +194,36 @@ impl<'interner> TypeChecker<'interner> {
                         // so that the backend doesn't need to worry about methods
                         let location = method_call.location;
 
+                        if let HirMethodReference::FuncId(method_id) = method_ref {
+                            let method_location =
+                                Location::new(method_call.method.span(), location.file);
+                            let method = ReferenceId::Item(ModuleDefId::FunctionId(method_id));
+                            self.interner.add_reference(method_location, method);
+                        }
+
                         // Automatically add `&mut` if the method expects a mutable reference and
                         // the object is not already one.
-                        if method_id != FuncId::dummy_id() {
-                            let func_meta = self.interner.function_meta(&method_id);
+                        if let Some(method_type) = self.method_type(method_ref) {
                             self.try_add_mutable_reference_to_object(
                                 &mut method_call,
-                                &func_meta.typ,
+                                &method_type,
                                 &mut args,
                             );
                         }
 
                         let (function_id, function_call) =
-                            method_call.into_function_call(method_id, location, self.interner);
+                            method_call.into_function_call(method_ref, location, self.interner);
 
                         let span = self.interner.expr_span(expr_id);
-                        let ret = self.check_method_call(&function_id, &method_id, args, span);
+                        let ret = match method_ref {
+                            HirMethodReference::FuncId(method_id) => {
+                                self.check_method_call(&function_id, &method_id, args, span)
+                            }
+                            HirMethodReference::TraitMethodId(_) => {
+                                let function_type = self.check_expression(&function_id);
+                                self.bind_function_type(function_type, args, span, None)
+                            }
+                        };
 
                         self.interner.replace_expr(expr_id, function_call);
                         ret
@@ -204,7 +281,18 @@ impl<'interner> TypeChecker<'interner> {
                 let mut block_type = Type::Unit;
 
                 let statements = block_expr.statements();
+                let mut diverged = false;
+                let mut reported_unreachable = false;
                 for (i, stmt) in statements.iter().enumerate() {
+                    // Only the first unreachable statement of each block is reported
+                    if diverged && !reported_unreachable {
+                        if let Some(span) = self.statement_span(stmt) {
+                            self.errors.push(TypeCheckError::UnreachableCode { span });
+                        }
+                        reported_unreachable = true;
+                    }
+                    diverged = diverged || self.statement_diverges(stmt);
+
                     let expr_type = self.check_statement(stmt);
 
                     if i + 1 < statements.len() {
@@ -229,12 +317,43 @@ impl<'interner> TypeChecker<'interner> {
                 block_type
             }
             HirExpression::Prefix(prefix_expr) => {
-                let rhs_type = self.check_expression(&prefix_expr.rhs);
                 let span = self.interner.expr_span(&prefix_expr.rhs);
+                let rhs_type = match self.interner.expression(&prefix_expr.rhs) {
+                    // A negated literal may be one larger than the largest positive value of its type
+                    HirExpression::Literal(HirLiteral::Integer(value))
+                        if matches!(prefix_expr.operator, UnaryOp::Minus) =>
+                    {
+                        let typ = self.check_integer_literal(value, true, span);
+                        self.interner.push_expr_type(&prefix_expr.rhs, typ.clone());
+                        typ
+                    }
+                    _ => self.check_expression(&prefix_expr.rhs),
+                };
                 self.type_check_prefix_operand(&prefix_expr.operator, &rhs_type, span)
             }
+            HirExpression::While(while_expr) => {
+                let condition_type = self.check_expression(&while_expr.condition);
+                let span = self.interner.expr_span(&while_expr.condition);
+
+                let bool_type = Type::Bool(CompTime::new(self.interner));
+                self.unify(&condition_type, &bool_type, span, || TypeCheckError::TypeMismatch {
+                    expected_typ: Type::Bool(CompTime::No(None)).to_string(),
+                    expr_typ: condition_type.to_string(),
+                    expr_span: span,
+                });
+
+                // Constrained code must unroll its loops, which requires knowing their bounds
+                if !self.is_unconstrained() {
+                    self.errors.push(TypeCheckError::UnboundedLoopInConstrainedCode { span });
+                }
+
+                self.check_expression(&while_expr.block);
+                Type::Unit
+            }
             HirExpression::If(if_expr) => self.check_if_expr(&if_expr, expr_id),
             HirExpression::Constructor(constructor) => self.check_constructor(constructor, expr_id),
+            HirExpression::EnumConstructor(constructor) => self.check_enum_constructor(constructor),
+            HirExpression::Match(match_expr) => self.check_match(match_expr, expr_id),
             HirExpression::MemberAccess(access) => self.check_member_access(access, *expr_id),
             HirExpression::Error => Type::Error,
             HirExpression::Tuple(elements) => {
@@ -246,17 +365,43 @@ impl<'interner> TypeChecker<'interner> {
                     typ
                 });
 
+                self.return_types.push(lambda.return_type.clone());
                 let actual_return = self.check_expression(&lambda.body);
+                self.return_types.pop();
 
                 let span = self.interner.expr_span(&lambda.body);
-                actual_return.make_subtype_of(&lambda.return_type, span, &mut self.errors, || {
-                    TypeCheckError::TypeMismatch {
-                        expected_typ: lambda.return_type.to_string(),
-                        expr_typ: actual_return.to_string(),
-                        expr_span: span,
-                    }
-                });
-                Type::Function(params, Box::new(lambda.return_type))
+                let make_error = || TypeCheckError::TypeMismatch {
+                    expected_typ: lambda.return_type.to_string(),
+                    expr_typ: actual_return.to_string(),
+                    expr_span: span,
+                };
+                self.make_subtype_with_coercions(
+                    &actual_return,
+                    &lambda.return_type,
+                    &lambda.body,
+                    span,
+                    make_error,
+                );
+
+                let captures =
+                    vecmap(&lambda.captures, |capture| self.interner.id_type(capture.id));
+                let env = if captures.is_empty() { Type::Unit } else { Type::Tuple(captures) };
+                Type::Function(params, Box::new(lambda.return_type), Box::new(env))
+            }
+            HirExpression::TraitMethodReference(method) => {
+                // The method of the impl it refers to is only known once `Self` is bound to a
+                // concrete type during monomorphization, which uses these bindings to find it.
+                let (typ, bindings) = self.trait_method_type(method).instantiate(self.interner);
+
+                let self_type_variable =
+                    self.interner.get_trait(method.trait_id).self_type_variable;
+                if let Some((_, self_type)) = bindings.get(&self_type_variable) {
+                    let span = self.interner.expr_span(expr_id);
+                    self.check_trait_implemented(self_type.clone(), method.trait_id, span);
+                }
+
+                self.interner.store_instantiation_bindings(*expr_id, bindings);
+                typ
             }
         };
 
@@ -272,12 +417,12 @@ impl<'interner> TypeChecker<'interner> {
         &mut self,
         method_call: &mut HirMethodCallExpression,
         function_type: &Type,
-        argument_types: &mut [(Type, noirc_errors::Span)],
+        argument_types: &mut [(Type, ExprId, noirc_errors::Span)],
     ) {
         let expected_object_type = match function_type {
-            Type::Function(args, _) => args.get(0),
+            Type::Function(args, _, _) => args.get(0),
             Type::Forall(_, typ) => match typ.as_ref() {
-                Type::Function(args, _) => args.get(0),
+                Type::Function(args, _, _) => args.get(0),
                 typ => unreachable!("Unexpected type for function: {typ}"),
             },
             typ => unreachable!("Unexpected type for function: {typ}"),
@@ -294,19 +439,24 @@ impl<'interner> TypeChecker<'interner> {
                 if !matches!(actual_type, Type::MutableReference(_)) {
                     let new_type = Type::MutableReference(Box::new(actual_type));
 
-                    argument_types[0].0 = new_type.clone();
                     method_call.object =
                         self.interner.push_expr(HirExpression::Prefix(HirPrefixExpression {
                             operator: UnaryOp::MutableReference,
                             rhs: method_call.object,
                         }));
-                    self.interner.push_expr_type(&method_call.object, new_type);
+                    self.interner.push_expr_type(&method_call.object, new_type.clone());
+                    argument_types[0].0 = new_type;
+                    argument_types[0].1 = method_call.object;
                 }
             }
         }
     }
 
-    fn check_index_expression(&mut self, index_expr: expr::HirIndexExpression) -> Type {
+    fn check_index_expression(
+        &mut self,
+        expr_id: &ExprId,
+        index_expr: expr::HirIndexExpression,
+    ) -> Type {
         let index_type = self.check_expression(&index_expr.index);
         let span = self.interner.expr_span(&index_expr.index);
 
@@ -319,6 +469,15 @@ impl<'interner> TypeChecker<'interner> {
         });
 
         let lhs_type = self.check_expression(&index_expr.collection);
+
+        if let Some(method) = self.operator_method(&lhs_type, OverloadableOperator::Index) {
+            let arguments = vec![(index_expr.collection, lhs_type), (index_expr.index, index_type)];
+            let location = self.interner.expr_location(expr_id);
+            let (call, typ) = self.check_operator_call(method, arguments, location);
+            self.interner.replace_expr(expr_id, call);
+            return typ;
+        }
+
         match lhs_type {
             // XXX: We can check the array bounds here also, but it may be better to constant fold first
             // and have ConstId instead of ExprId for constants
@@ -397,19 +556,78 @@ impl<'interner> TypeChecker<'interner> {
         }
     }
 
+    /// Issue a warning if the given function is marked `#[deprecated]`
+    fn check_deprecated_function(&mut self, meta: &FuncMeta, span: Span) {
+        if let Some(Attribute::Deprecated(note)) = &meta.attributes {
+            let name = self.interner.definition_name(meta.name.id).to_owned();
+            let note = note.clone();
+            self.errors.push(TypeCheckError::Deprecated { kind: "function", name, note, span });
+        }
+    }
+
+    /// Returns the numeric generics of the function `callee` refers to along with the type variables
+    /// they were instantiated with, if it is a generic function with any. Since the length of
+    /// each array must be known at compile-time, this also checks that they are all inferred by
+    /// the end of the function being type checked.
+    fn generic_call(&mut self, callee: &ExprId, span: Span) -> Option<GenericCall> {
+        let func_id = self.interner.referenced_function(*callee)?;
+        let (parameters, _) = self.interner.generic_function_type(&func_id)?;
+        let declared_parameters = parameters.to_vec();
+
+        let numeric_generics = self.interner.instantiated_numeric_generics(*callee);
+        if numeric_generics.is_empty() {
+            return None;
+        }
+        let numeric_generics = vecmap(numeric_generics, |(name, typ)| (name, typ, None));
+
+        let function = self.interner.function_name(&func_id).to_owned();
+        for (name, instantiated, _) in &numeric_generics {
+            let generic = name.clone();
+            let function = function.clone();
+            let instantiated = instantiated.clone();
+
+            self.push_delayed_type_check(Box::new(move || match instantiated.follow_bindings() {
+                Type::TypeVariable(_) => {
+                    Err(TypeCheckError::NumericGenericNotInferred { generic, function, span })
+                }
+                _ => Ok(()),
+            }));
+        }
+
+        // Arithmetic on the numeric generics, as in `[u8; N - 1]`, may only be evaluated once
+        // they are inferred, so it is checked not to overflow along with them
+        let instantiated_type = self.interner.id_type(*callee);
+        let callee_name = function.clone();
+        self.push_delayed_type_check(Box::new(move || {
+            match instantiated_type.find_invalid_type_expression() {
+                Some(expression) => Err(TypeCheckError::InvalidTypeExpression {
+                    expression: expression.to_string(),
+                    function: callee_name,
+                    span,
+                }),
+                None => Ok(()),
+            }
+        }));
+
+        let caller =
+            self.current_function.map(|id| (id, self.interner.function_name(&id).to_owned()));
+        Some(GenericCall { function, caller, declared_parameters, numeric_generics })
+    }
+
     // We need a special function to type check method calls since the method
     // is not a Expression::Ident it must be manually instantiated here
     fn check_method_call(
         &mut self,
         function_ident_id: &ExprId,
         func_id: &FuncId,
-        arguments: Vec<(Type, Span)>,
+        arguments: Vec<(Type, ExprId, Span)>,
         span: Span,
     ) -> Type {
         if func_id == &FuncId::dummy_id() {
             Type::Error
         } else {
             let func_meta = self.interner.function_meta(func_id);
+            self.check_deprecated_function(&func_meta, span);
 
             // Check function call arity is correct
             let param_len = func_meta.parameters.len();
@@ -424,11 +642,17 @@ impl<'interner> TypeChecker<'interner> {
             }
 
             let (function_type, instantiation_bindings) = func_meta.typ.instantiate(self.interner);
+            self.check_trait_constraints(
+                &func_meta.trait_constraints,
+                &instantiation_bindings,
+                span,
+            );
 
             self.interner.store_instantiation_bindings(*function_ident_id, instantiation_bindings);
             self.interner.push_expr_type(function_ident_id, function_type.clone());
 
-            self.bind_function_type(function_type, arguments, span)
+            let generic_call = self.generic_call(function_ident_id, span);
+            self.bind_function_type(function_type, arguments, span, generic_call)
         }
     }
 
@@ -482,6 +706,12 @@ impl<'interner> TypeChecker<'interner> {
         let typ = constructor.r#type;
         let generics = constructor.struct_generics;
 
+        if let Some(note) = typ.borrow().deprecated.clone() {
+            let name = typ.borrow().name.to_string();
+            let span = self.interner.expr_span(expr_id);
+            self.errors.push(TypeCheckError::Deprecated { kind: "struct", name, note, span });
+        }
+
         // Sort argument types by name so we can zip with the struct type in the same ordering.
         // Note that we use a Vec to store the original arguments (rather than a BTreeMap) to
         // preserve the evaluation order of the source code.
@@ -500,7 +730,7 @@ impl<'interner> TypeChecker<'interner> {
                 let arg_type = self.check_expression(&arg);
 
                 let span = self.interner.expr_span(expr_id);
-                self.make_subtype_of(&arg_type, &param_type, span, || {
+                self.make_subtype_with_coercions(&arg_type, &param_type, &arg, span, || {
                     TypeCheckError::TypeMismatch {
                         expected_typ: param_type.to_string(),
                         expr_typ: arg_type.to_string(),
@@ -513,6 +743,97 @@ impl<'interner> TypeChecker<'interner> {
         Type::Struct(typ, generics)
     }
 
+    fn check_enum_constructor(&mut self, constructor: HirEnumConstructorExpression) -> Type {
+        let typ = constructor.r#type;
+        let generics = constructor.enum_generics;
+        let fields = typ.borrow().get_variant_fields(constructor.variant_index, &generics);
+
+        // The argument count was already checked during name resolution
+        for (arg, param_type) in constructor.arguments.iter().zip(fields) {
+            let arg_type = self.check_expression(arg);
+
+            let span = self.interner.expr_span(arg);
+            self.make_subtype_with_coercions(&arg_type, &param_type, arg, span, || {
+                TypeCheckError::TypeMismatch {
+                    expected_typ: param_type.to_string(),
+                    expr_typ: arg_type.to_string(),
+                    expr_span: span,
+                }
+            });
+        }
+
+        Type::Enum(typ, generics)
+    }
+
+    fn check_match(&mut self, match_expr: HirMatchExpression, expr_id: &ExprId) -> Type {
+        let scrutinee_type = self.check_expression(&match_expr.expression);
+
+        let mut covered_variants = HashSet::new();
+        let mut has_wildcard = false;
+        let mut result_type: Option<Type> = None;
+
+        for (pattern, rule) in &match_expr.rules {
+            match pattern {
+                HirMatchPattern::Variant(enum_type, variant_index, fields, span) => {
+                    self.unify(enum_type, &scrutinee_type, *span, || {
+                        TypeCheckError::TypeMismatch {
+                            expected_typ: scrutinee_type.to_string(),
+                            expr_typ: enum_type.to_string(),
+                            expr_span: *span,
+                        }
+                    });
+
+                    if let Type::Enum(typ, generics) = enum_type {
+                        let field_types = typ.borrow().get_variant_fields(*variant_index, generics);
+                        for (field, field_type) in fields.iter().zip(field_types) {
+                            self.bind_pattern(field, field_type);
+                        }
+                    }
+
+                    covered_variants.insert(*variant_index);
+                }
+                HirMatchPattern::Wildcard(_) => has_wildcard = true,
+            }
+
+            let rule_type = self.check_expression(rule);
+
+            match &result_type {
+                None => result_type = Some(rule_type),
+                Some(expected) => {
+                    let span = self.interner.expr_span(rule);
+                    self.unify(&rule_type, expected, span, || {
+                        TypeCheckError::TypeMismatch {
+                            expected_typ: expected.to_string(),
+                            expr_typ: rule_type.to_string(),
+                            expr_span: span,
+                        }
+                        .add_context("Expected the types of all match arms to be equal")
+                    });
+                }
+            }
+        }
+
+        if !has_wildcard {
+            if let Type::Enum(typ, _) = scrutinee_type.follow_bindings() {
+                let typ = typ.borrow();
+                let missing = (0..typ.num_variants())
+                    .filter(|index| !covered_variants.contains(index))
+                    .map(|index| typ.variant_name(index).to_string())
+                    .collect::<Vec<_>>();
+
+                if !missing.is_empty() {
+                    self.errors.push(TypeCheckError::MissingMatchArms {
+                        enum_name: typ.name.to_string(),
+                        missing,
+                        span: self.interner.expr_span(expr_id),
+                    });
+                }
+            }
+        }
+
+        result_type.unwrap_or(Type::Unit)
+    }
+
     fn check_member_access(&mut self, mut access: expr::HirMemberAccess, expr_id: ExprId) -> Type {
         let lhs_type = self.check_expression(&access.lhs).follow_bindings();
         let span = self.interner.expr_span(&expr_id);
@@ -531,6 +852,14 @@ impl<'interner> TypeChecker<'interner> {
         match self.check_field_access(&lhs_type, &access.rhs.0.contents, span, dereference_lhs) {
             Some((element_type, index)) => {
                 self.interner.set_field_index(expr_id, index);
+                if let Some(struct_type) = accessed_struct(&lhs_type) {
+                    let struct_id = struct_type.borrow().id;
+                    self.interner.mark_field_read(struct_id, index);
+
+                    let file = self.interner.expr_location(&expr_id).file;
+                    let location = Location::new(access.rhs.span(), file);
+                    self.interner.add_reference(location, ReferenceId::Field(struct_id, index));
+                }
                 // We must update `access` in case we added any dereferences to it
                 self.interner.replace_expr(&expr_id, HirExpression::MemberAccess(access));
                 element_type
@@ -717,63 +1046,242 @@ impl<'interner> TypeChecker<'interner> {
         }
     }
 
+    /// Checks that a method of the given struct type may be called from the current function.
+    /// Methods are visible wherever a path to them would be, so private methods may only be
+    /// called from the module declaring the struct and its descendants.
+    fn check_method_is_visible(&mut self, struct_type: &Shared<StructType>, method: &Ident) {
+        let current_function = match self.current_function {
+            Some(current_function) => current_function,
+            None => return,
+        };
+        let struct_module = struct_type.borrow().id.0;
+        let visibility = match struct_module.module(self.def_maps).find_name(method).values {
+            Some((_, visibility)) => visibility,
+            None => return,
+        };
+
+        let importing_module = self.interner.function_meta(&current_function).module_id;
+        let defining_module = parent_module(self.def_maps, struct_module);
+        if !can_reference_item(self.def_maps, importing_module, defining_module, visibility) {
+            let error = PathResolutionError::Private(method.clone());
+            self.errors
+                .push(TypeCheckError::ResolverError(ResolverError::PathResolutionError(error)));
+        }
+    }
+
     fn lookup_method(
         &mut self,
         object_type: Type,
         method_name: &str,
         expr_id: &ExprId,
-    ) -> Option<FuncId> {
-        match &object_type {
-            Type::Struct(typ, _args) => {
-                match self.interner.lookup_method(typ.borrow().id, method_name) {
-                    Some(method_id) => Some(method_id),
-                    None => {
-                        self.errors.push(TypeCheckError::Unstructured {
-                            span: self.interner.expr_span(expr_id),
-                            msg: format!(
-                                "No method named '{method_name}' found for type '{object_type}'",
-                            ),
-                        });
-                        None
-                    }
-                }
-            }
+    ) -> Option<HirMethodReference> {
+        let method_id = match &object_type {
+            Type::Struct(typ, _args) => self.interner.lookup_method(typ.borrow().id, method_name),
             // If we fail to resolve the object to a struct type, we have no way of type
             // checking its arguments as we can't even resolve the name of the function
-            Type::Error => None,
+            Type::Error => return None,
+
+            // A generic only has the methods of the traits it is constrained to implement
+            Type::NamedGeneric(..) => None,
 
             // In the future we could support methods for non-struct types if we have a context
             // (in the interner?) essentially resembling HashMap<Type, Methods>
-            other => match self.interner.lookup_primitive_method(other, method_name) {
-                Some(method_id) => Some(method_id),
-                None => {
-                    self.errors.push(TypeCheckError::Unstructured {
-                        span: self.interner.expr_span(expr_id),
-                        msg: format!("No method named '{method_name}' found for type '{other}'",),
-                    });
-                    None
+            other => self.interner.lookup_primitive_method(other, method_name),
+        };
+
+        match method_id {
+            Some(method_id) => Some(HirMethodReference::FuncId(method_id)),
+            None => self.lookup_trait_method(object_type, method_name, expr_id),
+        }
+    }
+
+    /// Search for a method of a trait implemented by the given type. A generic implements the
+    /// traits the current function constrains it to implement.
+    fn lookup_trait_method(
+        &mut self,
+        object_type: Type,
+        method_name: &str,
+        expr_id: &ExprId,
+    ) -> Option<HirMethodReference> {
+        let span = self.interner.expr_span(expr_id);
+
+        let methods = if matches!(object_type, Type::NamedGeneric(..)) {
+            let constraints = self.current_trait_constraints();
+            let constraints = constraints.iter().filter(|constraint| constraint.typ == object_type);
+            constraints
+                .filter_map(|constraint| {
+                    let the_trait = self.interner.get_trait(constraint.trait_id);
+                    let method_index = the_trait.find_method(method_name)?;
+                    Some(TraitMethodId { trait_id: constraint.trait_id, method_index })
+                })
+                .collect()
+        } else {
+            self.interner.lookup_trait_methods(&object_type, method_name)
+        };
+
+        match methods.as_slice() {
+            [] => {
+                self.errors.push(TypeCheckError::Unstructured {
+                    span,
+                    msg: format!("No method named '{method_name}' found for type '{object_type}'"),
+                });
+                None
+            }
+            [method] => Some(HirMethodReference::TraitMethodId(*method)),
+            _ => {
+                let trait_names = vecmap(&methods, |method| {
+                    self.interner.get_trait(method.trait_id).name.to_string()
+                });
+                self.errors.push(TypeCheckError::AmbiguousTraitMethod {
+                    method_name: method_name.to_owned(),
+                    typ: object_type.to_string(),
+                    trait_names,
+                    span,
+                });
+                None
+            }
+        }
+    }
+
+    /// Returns the trait method overloading the given operator for values of the given type.
+    /// Operators are only overloadable for structs, and for generics constrained to implement
+    /// the trait of the operator. Other generics keep the built in `==` and `!=`.
+    fn operator_method(
+        &self,
+        object_type: &Type,
+        operator: OverloadableOperator,
+    ) -> Option<TraitMethodId> {
+        let method = self.interner.operator_method(operator)?;
+
+        match object_type.follow_bindings() {
+            Type::Struct(..) => Some(method),
+            generic @ Type::NamedGeneric(..) => {
+                let constraints = self.current_trait_constraints();
+                let is_constrained = constraints.iter().any(|constraint| {
+                    constraint.typ == generic && constraint.trait_id == method.trait_id
+                });
+                is_constrained.then_some(method)
+            }
+            _ => None,
+        }
+    }
+
+    /// Type checks a call to the trait method overloading an operator, returning the call
+    /// the operator desugars to along with its type.
+    fn check_operator_call(
+        &mut self,
+        method: TraitMethodId,
+        arguments: Vec<(ExprId, Type)>,
+        location: Location,
+    ) -> (HirExpression, Type) {
+        let func = self.interner.push_expr(HirExpression::TraitMethodReference(method));
+        self.interner.push_expr_location(func, location.span, location.file);
+
+        let function_type = self.check_expression(&func);
+        let argument_types = vecmap(&arguments, |(argument, typ)| {
+            (typ.clone(), *argument, self.interner.expr_span(argument))
+        });
+        let typ = self.bind_function_type(function_type, argument_types, location.span, None);
+
+        let arguments = vecmap(arguments, |(argument, _)| argument);
+        (HirExpression::Call(HirCallExpression { func, arguments, location }), typ)
+    }
+
+    /// Returns the type of the function a method call refers to, unless it failed to resolve
+    fn method_type(&self, method: HirMethodReference) -> Option<Type> {
+        match method {
+            HirMethodReference::FuncId(func_id) if func_id == FuncId::dummy_id() => None,
+            HirMethodReference::FuncId(func_id) => Some(self.interner.function_meta(&func_id).typ),
+            HirMethodReference::TraitMethodId(method) => Some(self.trait_method_type(method)),
+        }
+    }
+
+    /// Returns the type declared by the trait for one of its methods, quantified over `Self`
+    fn trait_method_type(&self, method: TraitMethodId) -> Type {
+        self.interner.get_trait(method.trait_id).methods[method.method_index].typ.clone()
+    }
+
+    /// Returns the trait constraints on the generics of the function being type checked
+    fn current_trait_constraints(&self) -> Vec<TraitConstraint> {
+        self.current_function.map_or(Vec::new(), |current_function| {
+            self.interner.function_meta(&current_function).trait_constraints
+        })
+    }
+
+    /// Checks that each generic of a function is instantiated to a type implementing the
+    /// traits the function constrains the generic to implement.
+    fn check_trait_constraints(
+        &mut self,
+        trait_constraints: &[TraitConstraint],
+        bindings: &TypeBindings,
+        span: Span,
+    ) {
+        for constraint in trait_constraints {
+            if let Type::NamedGeneric(binding, _) = &constraint.typ {
+                if let TypeBinding::Unbound(id) = &*binding.borrow() {
+                    if let Some((_, typ)) = bindings.get(id) {
+                        self.check_trait_implemented(typ.clone(), constraint.trait_id, span);
+                    }
                 }
-            },
+            }
         }
     }
 
-    fn bind_function_type(&mut self, function: Type, args: Vec<(Type, Span)>, span: Span) -> Type {
+    /// Checks that the given type implements the trait once the types of the function body are
+    /// known, since the type may still be a type variable at this point.
+    fn check_trait_implemented(&mut self, typ: Type, trait_id: TraitId, span: Span) {
+        let trait_name = self.interner.get_trait(trait_id).name.to_string();
+        let impl_types =
+            vecmap(self.interner.trait_impls(trait_id), |trait_impl| trait_impl.typ.clone());
+        let constraints = self.current_trait_constraints();
+
+        self.push_delayed_type_check(Box::new(move || {
+            let typ = typ.follow_bindings();
+            let is_implemented = match &typ {
+                Type::Error => true,
+                // `Self` may only be known from the type a call such as `Default::default()`
+                // is expected to return
+                Type::TypeVariable(_) => {
+                    return Err(TypeCheckError::TypeAnnotationsNeeded { span })
+                }
+                Type::NamedGeneric(..) => constraints
+                    .iter()
+                    .any(|constraint| constraint.trait_id == trait_id && constraint.typ == typ),
+                _ => impl_types.iter().any(|impl_type| impl_type.instantiate_as(&typ).is_some()),
+            };
+
+            if is_implemented {
+                Ok(())
+            } else {
+                Err(TypeCheckError::TraitNotImplemented { typ: typ.to_string(), trait_name, span })
+            }
+        }));
+    }
+
+    fn bind_function_type(
+        &mut self,
+        function: Type,
+        args: Vec<(Type, ExprId, Span)>,
+        span: Span,
+        mut generic_call: Option<GenericCall>,
+    ) -> Type {
         // Could do a single unification for the entire function type, but matching beforehand
         // lets us issue a more precise error on the individual argument that fails to type check.
         match function {
             Type::TypeVariable(binding) => {
                 if let TypeBinding::Bound(typ) = &*binding.borrow() {
-                    return self.bind_function_type(typ.clone(), args, span);
+                    return self.bind_function_type(typ.clone(), args, span, generic_call);
                 }
 
                 let ret = self.interner.next_type_variable();
-                let args = vecmap(args, |(arg, _)| arg);
-                let expected = Type::Function(args, Box::new(ret.clone()));
+                let args = vecmap(args, |(arg, _, _)| arg);
+                let env = Box::new(self.interner.next_type_variable());
+                let expected = Type::Function(args, Box::new(ret.clone()), env);
                 *binding.borrow_mut() = TypeBinding::Bound(expected);
 
                 ret
             }
-            Type::Function(parameters, ret) => {
+            Type::Function(parameters, ret, _) => {
                 if parameters.len() != args.len() {
                     let empty_or_s = if parameters.len() == 1 { "" } else { "s" };
                     let was_or_were = if args.len() == 1 { "was" } else { "were" };
@@ -791,14 +1299,27 @@ impl<'interner> TypeChecker<'interner> {
                     return Type::Error;
                 }
 
-                for (param, (arg, arg_span)) in parameters.iter().zip(args) {
-                    arg.make_subtype_of(param, arg_span, &mut self.errors, || {
-                        TypeCheckError::TypeMismatch {
+                for (index, (param, (arg, arg_id, arg_span))) in
+                    parameters.iter().zip(args).enumerate()
+                {
+                    let context = generic_call.as_ref().and_then(|call| call.context(index));
+                    self.make_subtype_with_coercions(&arg, param, &arg_id, arg_span, || {
+                        let error = TypeCheckError::TypeMismatch {
                             expected_typ: param.to_string(),
                             expr_typ: arg.to_string(),
                             expr_span: arg_span,
+                        };
+                        match context {
+                            Some(call) => {
+                                TypeCheckError::GenericCall { err: Box::new(error), call }
+                            }
+                            None => error,
                         }
                     });
+
+                    if let Some(call) = &mut generic_call {
+                        call.record_inferred_generics(arg_span);
+                    }
                 }
 
                 *ret
@@ -908,6 +1429,44 @@ impl<'interner> TypeChecker<'interner> {
         }
     }
 
+    /// Integer literals are polymorphic until the rest of the function has been type checked,
+    /// so whether the literal fits in its inferred type is checked in a delayed type check.
+    fn check_integer_literal(&mut self, value: FieldElement, negated: bool, span: Span) -> Type {
+        let typ = Type::polymorphic_integer(self.interner);
+        let literal_type = typ.clone();
+
+        self.push_delayed_type_check(Box::new(move || {
+            let typ = literal_type.follow_bindings();
+            let (sign, bits) = match &typ {
+                Type::Integer(_, sign, bits) => (*sign, *bits),
+                _ => return Ok(()),
+            };
+
+            let fits = match sign {
+                // Only zero may be negated in an unsigned type
+                Signedness::Unsigned if negated => value.is_zero(),
+                Signedness::Unsigned => value.num_bits() <= bits,
+                // The magnitude of a negative value may be at most 2^(bits - 1)
+                Signedness::Signed if negated && !value.is_zero() => {
+                    (value - FieldElement::one()).num_bits() < bits
+                }
+                Signedness::Signed => value.num_bits() < bits,
+            };
+
+            if fits {
+                Ok(())
+            } else {
+                let value = value
+                    .try_into_u128()
+                    .map_or_else(|| format!("0x{}", value.to_hex()), |value| value.to_string());
+                let value = if negated { format!("-{value}") } else { value };
+                Err(TypeCheckError::IntegerLiteralOverflow { value, typ, span })
+            }
+        }));
+
+        typ
+    }
+
     fn type_check_prefix_operand(
         &mut self,
         op: &crate::UnaryOp,
@@ -947,6 +1506,69 @@ impl<'interner> TypeChecker<'interner> {
     }
 }
 
+/// The numeric generics of a generic function being called, used to explain a type error in
+/// one of its arguments by the values those generics were inferred to be from the others
+struct GenericCall {
+    function: String,
+    /// The function making the call, with its name, whose own generics the callee's may be
+    /// inferred to be
+    caller: Option<(FuncId, String)>,
+    declared_parameters: Vec<Type>,
+    /// Each numeric generic with the type variable it was instantiated with at this call,
+    /// and the span of the argument its value was inferred from, once it is known
+    numeric_generics: Vec<(String, Type, Option<Span>)>,
+}
+
+impl GenericCall {
+    /// Records the argument at `span` as the origin of the value of each numeric generic which
+    /// was unknown before type checking it
+    fn record_inferred_generics(&mut self, span: Span) {
+        for (_, instantiated, inferred_from) in &mut self.numeric_generics {
+            if inferred_from.is_none()
+                && !matches!(instantiated.follow_bindings(), Type::TypeVariable(_))
+            {
+                *inferred_from = Some(span);
+            }
+        }
+    }
+
+    /// The context of a type error in the argument at `index`, unless the type of its parameter
+    /// doesn't depend on the function's numeric generics
+    fn context(&self, index: usize) -> Option<GenericCallContext> {
+        let declared_type = self.declared_parameters.get(index)?;
+        let generics_used =
+            Resolver::find_numeric_generics(std::slice::from_ref(declared_type), &Type::Unit);
+        if generics_used.is_empty() {
+            return None;
+        }
+
+        let mut inferred = Vec::new();
+        let mut caller_generics = Vec::new();
+        for (name, instantiated, inferred_from) in &self.numeric_generics {
+            let inferred_from = match inferred_from {
+                Some(span) => *span,
+                None => continue,
+            };
+            let value = match (instantiated.follow_bindings(), &self.caller) {
+                (Type::NamedGeneric(_, generic), Some((caller_id, caller))) => {
+                    caller_generics.push((*caller_id, generic.to_string()));
+                    format!("`{generic}`, a generic of `{caller}`")
+                }
+                (value, _) => format!("`{value}`"),
+            };
+            inferred.push((name.clone(), value, inferred_from));
+        }
+
+        Some(GenericCallContext {
+            function: self.function.clone(),
+            declared_type: declared_type.to_string(),
+            inferred,
+            caller_generics,
+            call_chain: Vec::new(),
+        })
+    }
+}
+
 /// Taken from: https://stackoverflow.com/a/47127500
 fn sort_by_key_ref<T, F, K>(xs: &mut [T], key: F)
 where
@@ -955,3 +1577,13 @@ where
 {
     xs.sort_by(|x, y| key(x).cmp(key(y)));
 }
+
+/// Returns the struct whose field is accessed by `lhs.field`, given the type of `lhs`, which
+/// may be a mutable reference to the struct.
+pub(super) fn accessed_struct(lhs_type: &Type) -> Option<Shared<StructType>> {
+    match lhs_type.follow_bindings() {
+        Type::Struct(struct_type, _) => Some(struct_type),
+        Type::MutableReference(element) => accessed_struct(&element),
+        _ => None,
+    }
+}
@@ -14,12 +14,18 @@ mod errors;
 mod expr;
 mod stmt;
 
+use std::collections::HashMap;
+
+use errors::Suggestion;
 pub use errors::TypeCheckError;
 use noirc_errors::Span;
 
 use crate::{
+    graph::CrateId,
+    hir::def_map::CrateDefMap,
+    hir_def::expr::{HirCastExpression, HirExpression},
     node_interner::{ExprId, FuncId, NodeInterner, StmtId},
-    Type,
+    Signedness, Type,
 };
 
 type TypeCheckFn = Box<dyn FnOnce() -> Result<(), TypeCheckError>>;
@@ -28,12 +34,44 @@ pub struct TypeChecker<'interner> {
     delayed_type_checks: Vec<TypeCheckFn>,
     current_function: Option<FuncId>,
     interner: &'interner mut NodeInterner,
+    /// The modules of each crate, used to check that the methods called are visible
+    def_maps: &'interner HashMap<CrateId, CrateDefMap>,
     errors: Vec<TypeCheckError>,
+
+    /// The declared return types of the current function and of any lambdas
+    /// it is nested within, innermost last. Used to check `return` statements.
+    return_types: Vec<Type>,
+}
+
+/// Explains an error in a call to a generic function by how the generics of the calling function
+/// are instantiated, following the calls up the call stack. Every function must be type checked
+/// before, so that every call is known.
+pub fn explain_generic_call(interner: &NodeInterner, error: TypeCheckError) -> TypeCheckError {
+    let (err, mut call) = match error {
+        TypeCheckError::GenericCall { err, call } => (err, call),
+        error => return error,
+    };
+    for (func_id, generic) in &call.caller_generics {
+        let mut callee = *func_id;
+        for (caller, generic, value) in interner.numeric_generic_call_chain(*func_id, generic) {
+            call.call_chain.push(format!(
+                "`{}` is called by `{}` with `{generic}` = `{value}`",
+                interner.function_name(&callee),
+                interner.function_name(&caller),
+            ));
+            callee = caller;
+        }
+    }
+    TypeCheckError::GenericCall { err, call }
 }
 
 /// Type checks a function and assigns the
 /// appropriate types to expressions in a side table
-pub fn type_check_func(interner: &mut NodeInterner, func_id: FuncId) -> Vec<TypeCheckError> {
+pub fn type_check_func(
+    interner: &mut NodeInterner,
+    def_maps: &HashMap<CrateId, CrateDefMap>,
+    func_id: FuncId,
+) -> Vec<TypeCheckError> {
     let meta = interner.function_meta(&func_id);
     let declared_return_type = meta.return_type().clone();
     let can_ignore_ret = meta.can_ignore_return_type();
@@ -41,7 +79,7 @@ pub fn type_check_func(interner: &mut NodeInterner, func_id: FuncId) -> Vec<Type
     let function_body = interner.function(&func_id);
     let function_body_id = function_body.as_expr();
 
-    let mut type_checker = TypeChecker::new(func_id, interner);
+    let mut type_checker = TypeChecker::new(func_id, interner, def_maps);
 
     // Bind each parameter to its annotated type.
     // This is locally obvious, but it must be bound here so that the
@@ -63,6 +101,13 @@ pub fn type_check_func(interner: &mut NodeInterner, func_id: FuncId) -> Vec<Type
     // Check declared return type and actual return type
     if !can_ignore_ret {
         let func_span = interner.expr_span(function_body_id); // XXX: We could be more specific and return the span of the last stmt, however stmts do not have spans yet
+        let function_last_type = coerce_array_to_slice(
+            interner,
+            function_body_id,
+            &function_last_type,
+            &declared_return_type,
+        )
+        .unwrap_or(function_last_type);
         function_last_type.make_subtype_of(&declared_return_type, func_span, &mut errors, || {
             TypeCheckError::TypeMismatch {
                 expected_typ: declared_return_type.to_string(),
@@ -76,12 +121,19 @@ pub fn type_check_func(interner: &mut NodeInterner, func_id: FuncId) -> Vec<Type
 }
 
 impl<'interner> TypeChecker<'interner> {
-    fn new(current_function: FuncId, interner: &'interner mut NodeInterner) -> Self {
+    fn new(
+        current_function: FuncId,
+        interner: &'interner mut NodeInterner,
+        def_maps: &'interner HashMap<CrateId, CrateDefMap>,
+    ) -> Self {
+        let return_type = interner.function_meta(&current_function).return_type().clone();
         Self {
             delayed_type_checks: Vec::new(),
             current_function: Some(current_function),
             interner,
+            def_maps,
             errors: vec![],
+            return_types: vec![return_type],
         }
     }
 
@@ -97,15 +149,28 @@ impl<'interner> TypeChecker<'interner> {
         (body_type, self.delayed_type_checks, self.errors)
     }
 
-    pub fn check_global(id: &StmtId, interner: &'interner mut NodeInterner) -> Vec<TypeCheckError> {
+    pub fn check_global(
+        id: &StmtId,
+        interner: &'interner mut NodeInterner,
+        def_maps: &'interner HashMap<CrateId, CrateDefMap>,
+    ) -> Vec<TypeCheckError> {
         let mut this = Self {
             delayed_type_checks: Vec::new(),
             current_function: None,
             interner,
+            def_maps,
             errors: vec![],
+            return_types: vec![],
         };
         this.check_statement(id);
-        this.errors
+
+        let mut errors = this.errors;
+        for type_check_fn in this.delayed_type_checks {
+            if let Err(error) = type_check_fn() {
+                errors.push(error);
+            }
+        }
+        errors
     }
 
     fn is_unconstrained(&self) -> bool {
@@ -135,6 +200,100 @@ impl<'interner> TypeChecker<'interner> {
     ) {
         actual.make_subtype_of(expected, span, &mut self.errors, make_error);
     }
+
+    /// Wrapper of Type::make_subtype_of which additionally coerces the expression `expr_id`
+    /// into a slice if it is an array used where a slice is expected. If it is a number of
+    /// the wrong numeric type instead, the error suggests casting it.
+    fn make_subtype_with_coercions(
+        &mut self,
+        actual: &Type,
+        expected: &Type,
+        expr_id: &ExprId,
+        span: Span,
+        make_error: impl FnOnce() -> TypeCheckError,
+    ) {
+        let suggestion = self.cast_suggestion(actual, expected, expr_id);
+        let make_error = || make_error().with_suggestion(suggestion);
+
+        match coerce_array_to_slice(self.interner, expr_id, actual, expected) {
+            Some(slice) => self.make_subtype_of(&slice, expected, span, make_error),
+            None => self.make_subtype_of(actual, expected, span, make_error),
+        }
+    }
+
+    /// Suggests appending `as T` to the expression `expr_id` when it is a number of a different
+    /// numeric type than the expected type `T`. Expressions binding less tightly than a cast
+    /// would need parentheses, so no suggestion is made for them.
+    fn cast_suggestion(
+        &self,
+        actual: &Type,
+        expected: &Type,
+        expr_id: &ExprId,
+    ) -> Option<Suggestion> {
+        let target = match (actual.follow_bindings(), expected.follow_bindings()) {
+            (Type::FieldElement(_), Type::FieldElement(_)) => return None,
+            (Type::Integer(_, sign, bits), Type::Integer(_, expected_sign, expected_bits))
+                if sign == expected_sign && bits == expected_bits =>
+            {
+                return None
+            }
+            (Type::FieldElement(_) | Type::Integer(..), Type::FieldElement(_)) => {
+                "Field".to_string()
+            }
+            (Type::FieldElement(_) | Type::Integer(..), Type::Integer(_, sign, bits)) => {
+                let prefix = if sign == Signedness::Signed { 'i' } else { 'u' };
+                format!("{prefix}{bits}")
+            }
+            _ => return None,
+        };
+
+        match self.interner.expression(expr_id) {
+            HirExpression::Ident(_)
+            | HirExpression::Literal(_)
+            | HirExpression::Call(_)
+            | HirExpression::MethodCall(_)
+            | HirExpression::MemberAccess(_)
+            | HirExpression::Index(_) => (),
+            _ => return None,
+        }
+
+        let end = self.interner.expr_span(expr_id).end();
+        Some(Suggestion {
+            message: format!("cast the value to `{target}`"),
+            span: Span::exclusive(end, end),
+            replacement: format!(" as {target}"),
+        })
+    }
+}
+
+/// Arrays can be used wherever a slice is expected. Unlike arrays, slices carry their length
+/// at runtime, so the array expression `expr_id` is replaced with an explicit cast to a slice
+/// for the backend to convert it. Returns the type of the new slice if a coercion was inserted.
+///
+/// Only the outermost type is coerced: an array nested within another type
+/// (e.g. `([Field; 2], u8)`) is not a subtype of the same type containing a slice.
+fn coerce_array_to_slice(
+    interner: &mut NodeInterner,
+    expr_id: &ExprId,
+    actual: &Type,
+    expected: &Type,
+) -> Option<Type> {
+    let element = match (actual.follow_bindings(), expected.follow_bindings()) {
+        (Type::Array(_, element), Type::Slice(_)) => element,
+        _ => return None,
+    };
+
+    let slice = Type::Slice(element);
+    let location = interner.expr_location(expr_id);
+
+    let array = interner.push_expr(interner.expression(expr_id));
+    interner.push_expr_location(array, location.span, location.file);
+    interner.push_expr_type(&array, actual.clone());
+
+    let cast = HirCastExpression { lhs: array, r#type: slice.clone() };
+    interner.replace_expr(expr_id, HirExpression::Cast(cast));
+    interner.push_expr_type(expr_id, slice.clone());
+    Some(slice)
 }
 
 // XXX: These tests are all manual currently.
@@ -148,8 +307,9 @@ mod test {
     use noirc_errors::{Location, Span};
 
     use crate::graph::CrateId;
-    use crate::hir::def_map::{ModuleData, ModuleId, ModuleOrigin};
+    use crate::hir::def_map::{LocalModuleId, ModuleData, ModuleId, ModuleOrigin};
     use crate::hir::resolution::import::PathResolutionError;
+    use crate::hir::type_check::errors::TypeCheckError;
     use crate::hir_def::expr::HirIdent;
     use crate::hir_def::stmt::HirLetStatement;
     use crate::hir_def::stmt::HirPattern::Identifier;
@@ -160,7 +320,6 @@ mod test {
         stmt::HirStatement,
     };
     use crate::node_interner::{DefinitionKind, FuncId, NodeInterner};
-    use crate::BinaryOpKind;
     use crate::{
         hir::{
             def_map::{CrateDefMap, LocalModuleId, ModuleDefId},
@@ -168,6 +327,7 @@ mod test {
         },
         parse_program, FunctionKind, Path,
     };
+    use crate::{BinaryOpKind, OverflowMode};
 
     #[test]
     fn basic_let() {
@@ -198,7 +358,8 @@ mod test {
         let y_expr_id = interner.push_expr(HirExpression::Ident(y));
 
         // Create Infix
-        let operator = HirBinaryOp { location, kind: BinaryOpKind::Add };
+        let operator =
+            HirBinaryOp { location, kind: BinaryOpKind::Add, overflow: OverflowMode::Wrapping };
         let expr = HirInfixExpression { lhs: x_expr_id, operator, rhs: y_expr_id };
         let expr_id = interner.push_expr(HirExpression::Infix(expr));
         interner.push_expr_location(expr_id, Span::single_char(0), file);
@@ -230,10 +391,17 @@ mod test {
             name,
             kind: FunctionKind::Normal,
             attributes: None,
+            doc_comments: Vec::new(),
             location,
+            module_id: ModuleId { krate: CrateId::dummy_id(), local_id: LocalModuleId::dummy_id() },
             contract_function_type: None,
             is_unconstrained: false,
-            typ: Type::Function(vec![Type::field(None), Type::field(None)], Box::new(Type::Unit)),
+            typ: Type::Function(
+                vec![Type::field(None), Type::field(None)],
+                Box::new(Type::Unit),
+                Box::new(Type::Unit),
+            ),
+            trait_constraints: Vec::new(),
             parameters: vec![
                 Param(Identifier(x), Type::field(None), noirc_abi::AbiVisibility::Private),
                 Param(Identifier(y), Type::field(None), noirc_abi::AbiVisibility::Private),
@@ -245,7 +413,7 @@ mod test {
         };
         interner.push_fn_meta(func_meta, func_id);
 
-        let errors = super::type_check_func(&mut interner, func_id);
+        let errors = super::type_check_func(&mut interner, &HashMap::new(), func_id);
         assert!(errors.is_empty());
     }
 
@@ -287,6 +455,25 @@ mod test {
 
         type_check_src_code(src, vec![String::from("main"), String::from("foo")]);
     }
+    #[test]
+    fn find_type_of_expression() {
+        let src = r#"
+            fn main(x : Field) {
+                let y = [x, x];
+                assert(y[0] == x);
+            }
+        "#;
+
+        let (errors, interner) = type_check_src_code_with_interner(src, vec![String::from("main")]);
+        assert_eq!(errors, vec![]);
+
+        let offset = src.find("y[0]").unwrap() as u32;
+        let info = interner.find_expression_info(FileId::default(), offset).unwrap();
+        assert_eq!(info.typ.to_string(), "[Field; 2]");
+        assert_eq!(info.location.span.start(), offset);
+        assert!(info.doc_comments.is_empty());
+    }
+
     #[test]
     fn basic_for_expr() {
         let src = r#"
@@ -303,6 +490,85 @@ mod test {
         type_check_src_code(src, vec![String::from("main"), String::from("foo")]);
     }
 
+    #[test]
+    fn unreachable_code_after_failing_assert() {
+        let src = r#"
+            fn main(x : Field) {
+                assert(false);
+                let _y = x;
+                assert(x == 1);
+            }
+        "#;
+
+        let errors = type_check_src_code_with_errors(src, vec![String::from("foo")]);
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(matches!(errors[0], TypeCheckError::UnreachableCode { .. }));
+    }
+
+    #[test]
+    fn unreachable_code_after_return_in_both_branches() {
+        let src = r#"
+            fn foo(x : Field) -> Field {
+                if x == 0 {
+                    return 1;
+                } else {
+                    return 2;
+                }
+                x
+            }
+        "#;
+
+        let errors = type_check_src_code_with_errors(src, vec![String::from("main")]);
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(matches!(errors[0], TypeCheckError::UnreachableCode { .. }));
+    }
+
+    #[test]
+    fn suggests_casting_mismatched_numbers() {
+        let src = r#"
+            fn main(x : u8) {
+                let y: Field = x;
+                assert(y == 1);
+            }
+        "#;
+
+        let errors = type_check_src_code_with_errors(src, vec![String::from("main")]);
+        assert_eq!(errors.len(), 1, "{errors:?}");
+
+        let end = (src.find("= x;").unwrap() + 3) as u32;
+        match &errors[0] {
+            TypeCheckError::Suggestion { err, suggestion } => {
+                assert!(matches!(**err, TypeCheckError::TypeMismatch { .. }));
+                assert_eq!(suggestion.span, Span::exclusive(end, end));
+                assert_eq!(suggestion.replacement, " as Field");
+            }
+            other => panic!("expected a suggestion to cast, found {other:?}"),
+        }
+    }
+
+    #[test]
+    fn suggests_making_assigned_variable_mutable() {
+        let src = r#"
+            fn main(x : Field) {
+                let y = x;
+                y = 2;
+                assert(y == x);
+            }
+        "#;
+
+        let errors = type_check_src_code_with_errors(src, vec![String::from("main")]);
+        assert_eq!(errors.len(), 1, "{errors:?}");
+
+        let start = src.find("y = x").unwrap() as u32;
+        match &errors[0] {
+            TypeCheckError::Suggestion { suggestion, .. } => {
+                assert_eq!(suggestion.span, Span::exclusive(start, start + 1));
+                assert_eq!(suggestion.replacement, "mut y");
+            }
+            other => panic!("expected a suggestion to add `mut`, found {other:?}"),
+        }
+    }
+
     // This is the same Stub that is in the resolver, maybe we can pull this out into a test module and re-use?
     struct TestPathResolver(HashMap<String, ModuleDefId>);
 
@@ -335,9 +601,24 @@ mod test {
         }
     }
 
+    fn type_check_src_code(src: &str, func_namespace: Vec<String>) {
+        let errors = type_check_src_code_with_errors(src, func_namespace);
+        assert_eq!(errors, vec![]);
+    }
+
     // This function assumes that there is only one function and this is the
     // func id that is returned
-    fn type_check_src_code(src: &str, func_namespace: Vec<String>) {
+    fn type_check_src_code_with_errors(
+        src: &str,
+        func_namespace: Vec<String>,
+    ) -> Vec<TypeCheckError> {
+        type_check_src_code_with_interner(src, func_namespace).0
+    }
+
+    fn type_check_src_code_with_interner(
+        src: &str,
+        func_namespace: Vec<String>,
+    ) -> (Vec<TypeCheckError>, NodeInterner) {
         let (program, errors) = parse_program(src);
         let mut interner = NodeInterner::default();
 
@@ -372,6 +653,7 @@ mod test {
                 modules,
                 krate: CrateId::dummy_id(),
                 extern_prelude: HashMap::new(),
+                overflow_mode: OverflowMode::default(),
             },
         );
 
@@ -388,7 +670,8 @@ mod test {
         }
 
         // Type check section
-        let errors = super::type_check_func(&mut interner, func_ids.first().cloned().unwrap());
-        assert_eq!(errors, vec![]);
+        let func_id = func_ids.first().cloned().unwrap();
+        let errors = super::type_check_func(&mut interner, &def_maps, func_id);
+        (errors, interner)
     }
 }
@@ -1,14 +1,15 @@
 use noirc_errors::{Location, Span};
 
-use crate::hir_def::expr::HirIdent;
+use crate::hir_def::expr::{HirExpression, HirIdent, HirLiteral};
 use crate::hir_def::stmt::{
     HirAssignStatement, HirConstrainStatement, HirLValue, HirLetStatement, HirPattern, HirStatement,
 };
 use crate::hir_def::types::Type;
-use crate::node_interner::{DefinitionId, ExprId, StmtId};
+use crate::node_interner::{DefinitionId, ExprId, ReferenceId, StmtId};
 use crate::CompTime;
 
-use super::errors::TypeCheckError;
+use super::errors::{Suggestion, TypeCheckError};
+use super::expr::accessed_struct;
 use super::TypeChecker;
 
 impl<'interner> TypeChecker<'interner> {
@@ -45,6 +46,9 @@ impl<'interner> TypeChecker<'interner> {
             HirStatement::Semi(expr_id) => {
                 self.check_expression(&expr_id);
             }
+            HirStatement::Return(expr_id) => {
+                return self.check_return_stmt(expr_id);
+            }
             HirStatement::Let(let_stmt) => self.check_let_stmt(let_stmt),
             HirStatement::Constrain(constrain_stmt) => self.check_constrain_stmt(constrain_stmt),
             HirStatement::Assign(assign_stmt) => self.check_assign_stmt(assign_stmt, stmt_id),
@@ -53,6 +57,53 @@ impl<'interner> TypeChecker<'interner> {
         Type::Unit
     }
 
+    /// Returns true if evaluation can never continue past the given statement, since it
+    /// returns from the function or fails an `assert(false)`, possibly within a nested block.
+    pub(crate) fn statement_diverges(&self, stmt_id: &StmtId) -> bool {
+        match self.interner.statement(stmt_id) {
+            HirStatement::Return(_) => true,
+            HirStatement::Constrain(HirConstrainStatement(expr_id, ..)) => matches!(
+                self.interner.expression(&expr_id),
+                HirExpression::Literal(HirLiteral::Bool(false))
+            ),
+            HirStatement::Let(let_stmt) => self.expression_diverges(&let_stmt.expression),
+            HirStatement::Assign(assign_stmt) => self.expression_diverges(&assign_stmt.expression),
+            HirStatement::Expression(expr_id) | HirStatement::Semi(expr_id) => {
+                self.expression_diverges(&expr_id)
+            }
+            HirStatement::Error => false,
+        }
+    }
+
+    /// Returns true if evaluating the given expression always diverges. Only blocks and `if`s
+    /// whose branches both diverge are considered, since a loop may never run its body.
+    fn expression_diverges(&self, expr_id: &ExprId) -> bool {
+        match self.interner.expression(expr_id) {
+            HirExpression::Block(block) => {
+                block.statements().iter().any(|stmt| self.statement_diverges(stmt))
+            }
+            HirExpression::If(if_expr) => if_expr.alternative.map_or(false, |alternative| {
+                self.expression_diverges(&if_expr.consequence)
+                    && self.expression_diverges(&alternative)
+            }),
+            _ => false,
+        }
+    }
+
+    /// Returns the span of the given statement, used to report it as unreachable
+    pub(crate) fn statement_span(&self, stmt_id: &StmtId) -> Option<Span> {
+        let expr_id = match self.interner.statement(stmt_id) {
+            HirStatement::Let(let_stmt) => let_stmt.expression,
+            HirStatement::Constrain(HirConstrainStatement(expr_id, ..)) => expr_id,
+            HirStatement::Assign(assign_stmt) => assign_stmt.expression,
+            HirStatement::Expression(expr_id)
+            | HirStatement::Semi(expr_id)
+            | HirStatement::Return(expr_id) => expr_id,
+            HirStatement::Error => return None,
+        };
+        Some(self.interner.expr_span(&expr_id))
+    }
+
     /// Associate a given HirPattern with the given Type, and remember
     /// this association in the NodeInterner.
     pub(crate) fn bind_pattern(&mut self, pattern: &HirPattern, typ: Type) {
@@ -85,9 +136,10 @@ impl<'interner> TypeChecker<'interner> {
                     let struct_type = struct_type.borrow();
 
                     for (field_name, field_pattern) in fields {
-                        if let Some((type_field, _)) =
+                        if let Some((type_field, index)) =
                             struct_type.get_field(&field_name.0.contents, generics)
                         {
+                            self.interner.mark_field_read(struct_type.id, index);
                             self.bind_pattern(field_pattern, type_field);
                         }
                     }
@@ -96,6 +148,31 @@ impl<'interner> TypeChecker<'interner> {
         }
     }
 
+    /// Checks the returned expression against the return type of the enclosing function.
+    ///
+    /// Evaluation never continues past a `return`, so the statement itself may be given
+    /// any type. A fresh type variable is returned to let it unify with whichever type
+    /// the enclosing block expects, e.g. as the last statement of a function body.
+    fn check_return_stmt(&mut self, expr_id: ExprId) -> Type {
+        let expr_type = self.check_expression(&expr_id);
+        let span = self.interner.expr_span(&expr_id);
+
+        match self.return_types.last().cloned() {
+            Some(return_type) => {
+                self.make_subtype_with_coercions(&expr_type, &return_type, &expr_id, span, || {
+                    TypeCheckError::TypeMismatch {
+                        expected_typ: return_type.to_string(),
+                        expr_typ: expr_type.to_string(),
+                        expr_span: span,
+                    }
+                });
+            }
+            None => self.errors.push(TypeCheckError::ReturnOutsideFunction { span }),
+        }
+
+        self.interner.next_type_variable()
+    }
+
     fn check_assign_stmt(&mut self, assign_stmt: HirAssignStatement, stmt_id: &StmtId) {
         let expr_type = self.check_expression(&assign_stmt.expression);
         let span = self.interner.expr_span(&assign_stmt.expression);
@@ -108,7 +185,8 @@ impl<'interner> TypeChecker<'interner> {
         });
 
         let span = self.interner.expr_span(&assign_stmt.expression);
-        self.make_subtype_of(&expr_type, &lvalue_type, span, || {
+        let expression = &assign_stmt.expression;
+        self.make_subtype_with_coercions(&expr_type, &lvalue_type, expression, span, || {
             let msg = format!(
                 "Cannot assign an expression of type {expr_type} to a value of type {lvalue_type}"
             );
@@ -130,13 +208,24 @@ impl<'interner> TypeChecker<'interner> {
 
                     let definition = self.interner.definition(ident.id);
                     if !definition.mutable && !matches!(typ, Type::MutableReference(_)) {
-                        self.errors.push(TypeCheckError::Unstructured {
+                        let error = TypeCheckError::Unstructured {
                             msg: format!(
                                 "Variable {} must be mutable to be assigned to",
                                 definition.name
                             ),
                             span: ident.location.span,
-                        });
+                        };
+
+                        let suggestion =
+                            self.interner.definition_location(ident.id).map(|location| {
+                                Suggestion {
+                                    message: "consider changing this to be mutable".into(),
+                                    span: location.span,
+                                    replacement: format!("mut {}", definition.name),
+                                }
+                            });
+
+                        self.errors.push(error.with_suggestion(suggestion));
                     }
 
                     typ
@@ -170,6 +259,14 @@ impl<'interner> TypeChecker<'interner> {
                     )
                     .unwrap_or((Type::Error, 0));
 
+                if let (Some(struct_type), Some(function)) =
+                    (accessed_struct(&lhs_type), self.current_function)
+                {
+                    let file = self.interner.function_meta(&function).name.location.file;
+                    let reference = ReferenceId::Field(struct_type.borrow().id, field_index);
+                    self.interner.add_reference(Location::new(span, file), reference);
+                }
+
                 let field_index = Some(field_index);
                 (typ.clone(), HirLValue::MemberAccess { object, field_name, field_index, typ })
             }
@@ -246,6 +343,18 @@ impl<'interner> TypeChecker<'interner> {
             expected_typ: Type::Bool(CompTime::No(None)).to_string(),
             expr_span,
         });
+
+        if let Some(message) = stmt.2 {
+            let message_type = self.check_expression(&message);
+            match message_type.follow_bindings() {
+                Type::String(_) | Type::FmtString(..) | Type::Error => (),
+                _ => self.errors.push(TypeCheckError::TypeMismatch {
+                    expected_typ: "str or fmtstr".into(),
+                    expr_typ: message_type.to_string(),
+                    expr_span: self.interner.expr_span(&message),
+                }),
+            }
+        }
     }
 
     /// All declaration statements check that the user specified type(UST) is equal to the
@@ -259,15 +368,20 @@ impl<'interner> TypeChecker<'interner> {
         // If so, then we give it the same type as the expression
         if annotated_type != Type::Error {
             // Now check if LHS is the same type as the RHS
-            // Importantly, we do not coerce any types implicitly
+            // Importantly, we do not coerce any types implicitly other than arrays into slices
             let expr_span = self.interner.expr_span(&rhs_expr);
-            self.make_subtype_of(&expr_type, &annotated_type, expr_span, || {
-                TypeCheckError::TypeMismatch {
-                    expected_typ: annotated_type.to_string(),
-                    expr_typ: expr_type.to_string(),
-                    expr_span,
-                }
-            });
+            let make_error = || TypeCheckError::TypeMismatch {
+                expected_typ: annotated_type.to_string(),
+                expr_typ: expr_type.to_string(),
+                expr_span,
+            };
+            self.make_subtype_with_coercions(
+                &expr_type,
+                &annotated_type,
+                &rhs_expr,
+                expr_span,
+                make_error,
+            );
             annotated_type
         } else {
             expr_type
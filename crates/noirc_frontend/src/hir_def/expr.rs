@@ -1,12 +1,13 @@
 use acvm::FieldElement;
 use fm::FileId;
-use noirc_errors::Location;
+use noirc_errors::{Location, Span};
 
 use crate::node_interner::{DefinitionId, ExprId, FuncId, NodeInterner, StmtId};
-use crate::{BinaryOp, BinaryOpKind, Ident, Shared, UnaryOp};
+use crate::{BinaryOp, BinaryOpKind, Ident, OverflowMode, Shared, UnaryOp};
 
 use super::stmt::HirPattern;
-use super::types::{StructType, Type};
+use super::traits::TraitMethodId;
+use super::types::{EnumType, StructType, Type};
 
 /// A HirExpression is the result of an Expression in the AST undergoing
 /// name resolution. It is almost identical to the Expression AST node, but
@@ -22,14 +23,21 @@ pub enum HirExpression {
     Infix(HirInfixExpression),
     Index(HirIndexExpression),
     Constructor(HirConstructorExpression),
+    EnumConstructor(HirEnumConstructorExpression),
     MemberAccess(HirMemberAccess),
     Call(HirCallExpression),
     MethodCall(HirMethodCallExpression),
     Cast(HirCastExpression),
     For(HirForExpression),
+    While(HirWhileExpression),
     If(HirIfExpression),
+    Match(HirMatchExpression),
     Tuple(Vec<ExprId>),
     Lambda(HirLambda),
+
+    /// A method of a trait called on a value whose type is not known until monomorphization.
+    /// Only created by type checking when desugaring a method call.
+    TraitMethodReference(TraitMethodId),
     Error,
 }
 
@@ -55,17 +63,25 @@ pub struct HirForExpression {
     pub block: ExprId,
 }
 
+#[derive(Debug, Clone)]
+pub struct HirWhileExpression {
+    pub condition: ExprId,
+    pub block: ExprId,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct HirBinaryOp {
     pub kind: BinaryOpKind,
     pub location: Location,
+    /// The overflow mode of the crate the operation is in
+    pub overflow: OverflowMode,
 }
 
 impl HirBinaryOp {
-    pub fn new(op: BinaryOp, file: FileId) -> Self {
+    pub fn new(op: BinaryOp, file: FileId, overflow: OverflowMode) -> Self {
         let kind = op.contents;
         let location = Location::new(op.span(), file);
-        HirBinaryOp { location, kind }
+        HirBinaryOp { location, kind, overflow }
     }
 
     pub fn is_bitwise(&self) -> bool {
@@ -80,6 +96,8 @@ pub enum HirLiteral {
     Bool(bool),
     Integer(FieldElement),
     Str(String),
+    /// A format string along with the variables it interpolates, in the order they appear
+    FmtStr(String, Vec<ExprId>),
 }
 
 #[derive(Debug, Clone)]
@@ -144,19 +162,38 @@ pub struct HirMethodCallExpression {
     pub location: Location,
 }
 
+/// The function a method call resolves to once the type of its object is known
+#[derive(Debug, Copy, Clone)]
+pub enum HirMethodReference {
+    /// A method defined in an impl of the type of the object
+    FuncId(FuncId),
+
+    /// A method of a trait, resolved to the method of the impl of the trait for the type of
+    /// the object during monomorphization.
+    TraitMethodId(TraitMethodId),
+}
+
 impl HirMethodCallExpression {
     pub fn into_function_call(
         mut self,
-        func: FuncId,
+        method: HirMethodReference,
         location: Location,
         interner: &mut NodeInterner,
     ) -> (ExprId, HirExpression) {
         let mut arguments = vec![self.object];
         arguments.append(&mut self.arguments);
 
-        let id = interner.function_definition_id(func);
-        let ident = HirExpression::Ident(HirIdent { location, id });
-        let func = interner.push_expr(ident);
+        let expr = match method {
+            HirMethodReference::FuncId(func) => {
+                let id = interner.function_definition_id(func);
+                HirExpression::Ident(HirIdent { location, id })
+            }
+            HirMethodReference::TraitMethodId(method) => {
+                HirExpression::TraitMethodReference(method)
+            }
+        };
+        let func = interner.push_expr(expr);
+        interner.push_expr_location(func, location.span, location.file);
 
         (func, HirExpression::Call(HirCallExpression { func, arguments, location }))
     }
@@ -175,6 +212,42 @@ pub struct HirConstructorExpression {
     pub fields: Vec<(Ident, ExprId)>,
 }
 
+/// Constructs a value of an enum from one of its variants, as in `Option::Some(x)`
+#[derive(Debug, Clone)]
+pub struct HirEnumConstructorExpression {
+    pub r#type: Shared<EnumType>,
+    pub enum_generics: Vec<Type>,
+    pub variant_index: usize,
+
+    /// The values of the fields of the variant, in the order they are declared
+    pub arguments: Vec<ExprId>,
+}
+
+/// `match expression { pattern => rule, ... }`
+#[derive(Debug, Clone)]
+pub struct HirMatchExpression {
+    pub expression: ExprId,
+    pub rules: Vec<(HirMatchPattern, ExprId)>,
+}
+
+#[derive(Debug, Clone)]
+pub enum HirMatchPattern {
+    /// Matches the variant with the given index, binding each of its fields to a pattern.
+    /// The type is the enum type the variant belongs to, with its generics instantiated.
+    Variant(Type, usize, Vec<HirPattern>, Span),
+
+    /// `_`, matching any value
+    Wildcard(Span),
+}
+
+impl HirMatchPattern {
+    pub fn span(&self) -> Span {
+        match self {
+            HirMatchPattern::Variant(_, _, _, span) | HirMatchPattern::Wildcard(span) => *span,
+        }
+    }
+}
+
 /// Indexing, as in `array[index]`
 #[derive(Debug, Clone)]
 pub struct HirIndexExpression {
@@ -196,4 +269,7 @@ pub struct HirLambda {
     pub parameters: Vec<(HirPattern, Type)>,
     pub return_type: Type,
     pub body: ExprId,
+    /// The variables of the enclosing function used by the lambda, in the
+    /// order they are stored in its environment.
+    pub captures: Vec<HirIdent>,
 }
@@ -4,6 +4,8 @@ use noirc_errors::{Location, Span};
 
 use super::expr::{HirBlockExpression, HirExpression, HirIdent};
 use super::stmt::HirPattern;
+use super::traits::TraitConstraint;
+use crate::hir::def_map::ModuleId;
 use crate::node_interner::{ExprId, NodeInterner};
 use crate::{token::Attribute, FunctionKind};
 use crate::{ContractFunctionType, Type};
@@ -121,6 +123,9 @@ pub struct FuncMeta {
     /// Attribute per function.
     pub attributes: Option<Attribute>,
 
+    /// The lines of the `///` doc comments written above the function definition
+    pub doc_comments: Vec<String>,
+
     /// This function's visibility in its contract.
     /// If this function is not in a contract, this is always 'Secret'.
     pub contract_function_type: Option<ContractFunctionType>,
@@ -137,8 +142,14 @@ pub struct FuncMeta {
     /// or a Type::Forall for generic functions.
     pub typ: Type,
 
+    /// The traits each generic of the function is constrained to implement
+    pub trait_constraints: Vec<TraitConstraint>,
+
     pub location: Location,
 
+    /// The module this function is declared in, or the module of its impl for methods
+    pub module_id: ModuleId,
+
     // This flag is needed for the attribute check pass
     pub has_body: bool,
 }
@@ -172,9 +183,9 @@ impl FuncMeta {
     /// Gives the (uninstantiated) return type of this function.
     pub fn return_type(&self) -> &Type {
         match &self.typ {
-            Type::Function(_, ret) => ret,
+            Type::Function(_, ret, _) => ret,
             Type::Forall(_, typ) => match typ.as_ref() {
-                Type::Function(_, ret) => ret,
+                Type::Function(_, ret, _) => ret,
                 _ => unreachable!(),
             },
             _ => unreachable!(),
@@ -15,6 +15,7 @@ pub enum HirStatement {
     Assign(HirAssignStatement),
     Expression(ExprId),
     Semi(ExprId),
+    Return(ExprId),
     Error,
 }
 
@@ -46,7 +47,17 @@ pub struct HirAssignStatement {
 /// originates from. This is used later in the SSA pass to issue
 /// an error if a constrain is found to be always false.
 #[derive(Debug, Clone)]
-pub struct HirConstrainStatement(pub ExprId, pub FileId);
+pub struct HirConstrainStatement(pub ExprId, pub FileId, pub Option<ExprId>, pub HirConstrainKind);
+
+/// The statement a constraint was written as. The sides of an `assert_eq` are kept apart from
+/// its condition, since type checking rewrites an `==` between types implementing `Eq` into a
+/// call to `eq`, yet both values are still reported if they differ.
+#[derive(Debug, Copy, Clone)]
+pub enum HirConstrainKind {
+    Assert,
+    AssertEq { lhs: ExprId, rhs: ExprId },
+    Constrain,
+}
 
 #[derive(Debug, Clone)]
 pub enum HirPattern {
@@ -0,0 +1,117 @@
+use fm::FileId;
+use noirc_errors::{Location, Span};
+
+use crate::{
+    node_interner::{FuncId, TraitId},
+    BinaryOpKind, Ident, Type, TypeVariableId,
+};
+
+/// A trait, along with the signature of each method its impls must define.
+#[derive(Debug, Clone)]
+pub struct Trait {
+    pub id: TraitId,
+    pub name: Ident,
+    pub span: Span,
+    pub file: FileId,
+
+    /// The id of the type variable standing for `Self` within the signatures of the methods.
+    /// The type of each method is quantified over this variable alone.
+    pub self_type_variable: TypeVariableId,
+
+    pub methods: Vec<TraitMethod>,
+}
+
+/// The signature of a method declared by a trait
+#[derive(Debug, Clone)]
+pub struct TraitMethod {
+    pub name: Ident,
+
+    /// The type of the method, of the form `forall Self. fn(...) -> ...`
+    pub typ: Type,
+}
+
+/// An impl of a trait for a type
+#[derive(Debug, Clone)]
+pub struct TraitImpl {
+    /// The type the trait is implemented for, quantified over the generics of the impl if it
+    /// has any.
+    pub typ: Type,
+
+    /// The location of the type the trait is implemented for in the impl's header
+    pub location: Location,
+
+    /// The function defining each method of the trait, in the order the trait declares them.
+    pub methods: Vec<FuncId>,
+}
+
+/// Refers to a method of a trait, which is resolved to the method of one of the trait's impls
+/// once the type it is called on is known.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TraitMethodId {
+    pub trait_id: TraitId,
+    pub method_index: usize,
+}
+
+/// A constraint that a generic of a function implements a trait, as in `fn foo<T: Hasher>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraitConstraint {
+    /// The generic type being constrained, always a `Type::NamedGeneric`
+    pub typ: Type,
+    pub trait_id: TraitId,
+}
+
+impl Trait {
+    /// Returns the index of the method with the given name, if the trait declares one.
+    pub fn find_method(&self, method_name: &str) -> Option<usize> {
+        self.methods.iter().position(|method| method.name.0.contents == method_name)
+    }
+}
+
+/// An operator which user-defined types may overload by implementing the corresponding trait
+/// from the `std::ops` module.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum OverloadableOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Equal,
+    Index,
+}
+
+impl OverloadableOperator {
+    pub const ALL: [OverloadableOperator; 6] = [
+        OverloadableOperator::Add,
+        OverloadableOperator::Subtract,
+        OverloadableOperator::Multiply,
+        OverloadableOperator::Divide,
+        OverloadableOperator::Equal,
+        OverloadableOperator::Index,
+    ];
+
+    /// Returns the operator overloaded by a binary operation, if any.
+    /// `!=` is overloaded through the same trait as `==`, with its result negated.
+    pub fn from_binary_op(kind: BinaryOpKind) -> Option<OverloadableOperator> {
+        match kind {
+            BinaryOpKind::Add => Some(OverloadableOperator::Add),
+            BinaryOpKind::Subtract => Some(OverloadableOperator::Subtract),
+            BinaryOpKind::Multiply => Some(OverloadableOperator::Multiply),
+            BinaryOpKind::Divide => Some(OverloadableOperator::Divide),
+            BinaryOpKind::Equal | BinaryOpKind::NotEqual => Some(OverloadableOperator::Equal),
+            _ => None,
+        }
+    }
+
+    /// The path of the trait overloading this operator, relative to the root of the standard
+    /// library, and the name of its method.
+    pub fn trait_method(self) -> (&'static str, &'static str) {
+        match self {
+            OverloadableOperator::Add => ("ops::Add", "add"),
+            OverloadableOperator::Subtract => ("ops::Sub", "sub"),
+            OverloadableOperator::Multiply => ("ops::Mul", "mul"),
+            OverloadableOperator::Divide => ("ops::Div", "div"),
+            OverloadableOperator::Equal => ("ops::Eq", "eq"),
+            OverloadableOperator::Index => ("ops::Index", "index"),
+        }
+    }
+}
@@ -6,11 +6,15 @@ use std::{
 };
 
 use crate::{hir::type_check::TypeCheckError, node_interner::NodeInterner};
+use fm::FileId;
 use iter_extended::vecmap;
-use noirc_abi::AbiType;
+use noirc_abi::{printable::PrintableType, AbiType};
 use noirc_errors::Span;
 
-use crate::{node_interner::StructId, Ident, Signedness};
+use crate::{
+    node_interner::{EnumId, StructId, TypeAliasId},
+    Ident, Signedness,
+};
 
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub enum Type {
@@ -37,6 +41,10 @@ pub enum Type {
     /// is either a type variable of some kind or a Type::Constant.
     String(Box<Type>),
 
+    /// FmtString(N, Fields) is a format string of length N. Fields is a tuple of the
+    /// types of each variable the string interpolates.
+    FmtString(Box<Type>, Box<Type>),
+
     /// The unit type `()`.
     Unit,
 
@@ -45,6 +53,10 @@ pub enum Type {
     /// represents the generic arguments (if any) to this struct type.
     Struct(Shared<StructType>, Vec<Type>),
 
+    /// A user-defined enum type. As with structs, the `Shared<EnumType>` field refers to
+    /// the shared definition of the enum and the `Vec<Type>` to its generic arguments.
+    Enum(Shared<EnumType>, Vec<Type>),
+
     /// A tuple type with the given list of fields in the order they appear in source code.
     Tuple(Vec<Type>),
 
@@ -71,8 +83,9 @@ pub enum Type {
     /// like `fn foo<T, U>(...) {}`. Unlike TypeVariables, they cannot be bound over.
     NamedGeneric(TypeVariable, Rc<String>),
 
-    /// A functions with arguments, and a return type.
-    Function(Vec<Type>, Box<Type>),
+    /// A functions with arguments, a return type, and the type of the environment it captures.
+    /// The environment of functions which do not capture any variables is the unit type.
+    Function(Vec<Type>, Box<Type>, Box<Type>),
 
     /// &mut T
     MutableReference(Box<Type>),
@@ -88,6 +101,10 @@ pub enum Type {
     /// bind to an integer without special checks to bind it to a non-type.
     Constant(u64),
 
+    /// An arithmetic operation on type-level integers, as in `[u8; N + 32]`.
+    /// These are evaluated to a Type::Constant once the generics within them are bound.
+    InfixExpr(Box<Type>, BinaryTypeOperator, Box<Type>),
+
     /// The result of some type error. Remembering type errors as their own type variant lets
     /// us avoid issuing repeat type errors for the same item. For example, a lambda with
     /// an invalid type would otherwise issue a new error each time it is called
@@ -116,6 +133,33 @@ pub struct StructType {
     /// since these will handle applying generic arguments to fields as well.
     fields: Vec<(Ident, Type)>,
 
+    pub generics: Generics,
+    pub span: Span,
+    pub file: FileId,
+
+    /// Set if the struct is marked `#[deprecated]`, to the message of the attribute if it has one
+    pub deprecated: Option<Option<String>>,
+
+    /// The lines of the `///` doc comments written above the struct
+    pub doc_comments: Vec<String>,
+}
+
+/// Represents an enum type in the type system. Each instance of this
+/// rust struct will be shared across all Type::Enum variants that represent
+/// the same enum type.
+#[derive(Debug, Eq)]
+pub struct EnumType {
+    /// A unique id representing this enum type. Used to check if two
+    /// enum types are equal.
+    pub id: EnumId,
+
+    pub name: Ident,
+
+    /// The name of each variant along with the types of the fields it holds, in the order
+    /// they are declared. Like the fields of a struct, these should only be accessed through
+    /// methods applying the generic arguments of the enum to them.
+    variants: Vec<(Ident, Vec<Type>)>,
+
     pub generics: Generics,
     pub span: Span,
 }
@@ -143,10 +187,20 @@ impl StructType {
         id: StructId,
         name: Ident,
         span: Span,
+        file: FileId,
         fields: Vec<(Ident, Type)>,
         generics: Generics,
     ) -> StructType {
-        StructType { id, fields, name, span, generics }
+        StructType {
+            id,
+            fields,
+            name,
+            span,
+            file,
+            generics,
+            deprecated: None,
+            doc_comments: Vec::new(),
+        }
     }
 
     /// To account for cyclic references between structs, a struct's
@@ -201,6 +255,11 @@ impl StructType {
         self.fields.iter().map(|(name, _)| name.clone()).collect()
     }
 
+    /// Returns the name of each field, in the order the fields are declared
+    pub fn field_idents(&self) -> Vec<Ident> {
+        vecmap(&self.fields, |(name, _)| name.clone())
+    }
+
     /// True if the given index is the same index as a generic type of this struct
     /// which is expected to be a numeric generic.
     /// This is needed because we infer type kinds in Noir and don't have extensive kind checking.
@@ -222,6 +281,132 @@ impl std::fmt::Display for StructType {
     }
 }
 
+impl std::hash::Hash for EnumType {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl PartialEq for EnumType {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl EnumType {
+    pub fn new(
+        id: EnumId,
+        name: Ident,
+        span: Span,
+        variants: Vec<(Ident, Vec<Type>)>,
+        generics: Generics,
+    ) -> EnumType {
+        EnumType { id, variants, name, span, generics }
+    }
+
+    /// Like the fields of a struct, the variants of an enum are resolved after the enum
+    /// itself is created so that enums and structs may refer to each other.
+    pub fn set_variants(&mut self, variants: Vec<(Ident, Vec<Type>)>) {
+        assert!(self.variants.is_empty());
+        self.variants = variants;
+    }
+
+    pub fn num_variants(&self) -> usize {
+        self.variants.len()
+    }
+
+    /// Returns the index of the variant with the given name, if there is one.
+    pub fn find_variant(&self, variant_name: &str) -> Option<usize> {
+        self.variants.iter().position(|(name, _)| name.0.contents == variant_name)
+    }
+
+    pub fn variant_name(&self, index: usize) -> &Ident {
+        &self.variants[index].0
+    }
+
+    pub fn get_variant_field_count(&self, index: usize) -> usize {
+        self.variants[index].1.len()
+    }
+
+    /// Returns the types of the fields of the given variant, after being applied to the
+    /// given generic arguments.
+    pub fn get_variant_fields(&self, index: usize, generic_args: &[Type]) -> Vec<Type> {
+        assert_eq!(self.generics.len(), generic_args.len());
+
+        let substitutions = self
+            .generics
+            .iter()
+            .zip(generic_args)
+            .map(|((old_id, old_var), new)| (*old_id, (old_var.clone(), new.clone())))
+            .collect();
+
+        vecmap(&self.variants[index].1, |typ| typ.substitute(&substitutions))
+    }
+
+    /// True if the given index is the same index as a generic type of this enum
+    /// which is expected to be a numeric generic.
+    pub fn generic_is_numeric(&self, index_of_generic: usize) -> bool {
+        let target_id = self.generics[index_of_generic].0;
+        let mut fields = self.variants.iter().flat_map(|(_, fields)| fields);
+        fields.any(|field| field.contains_numeric_typevar(target_id))
+    }
+
+    /// Instantiate this enum type, returning a Vec of the new generic args (in
+    /// the same order as self.generics)
+    pub fn instantiate(&self, interner: &mut NodeInterner) -> Vec<Type> {
+        vecmap(&self.generics, |_| interner.next_type_variable())
+    }
+}
+
+impl std::fmt::Display for EnumType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// A type alias. Unlike structs and enums, an alias is not a distinct type: each use of
+/// it is replaced by the aliased type during name resolution.
+#[derive(Debug, Clone)]
+pub struct TypeAliasType {
+    pub id: TypeAliasId,
+    pub name: Ident,
+    pub typ: Type,
+    pub generics: Generics,
+    pub span: Span,
+}
+
+impl TypeAliasType {
+    pub fn new(
+        id: TypeAliasId,
+        name: Ident,
+        span: Span,
+        typ: Type,
+        generics: Generics,
+    ) -> TypeAliasType {
+        TypeAliasType { id, name, typ, generics, span }
+    }
+
+    /// Returns the aliased type after applying the given generic arguments to it.
+    pub fn get_type(&self, generic_args: &[Type]) -> Type {
+        assert_eq!(self.generics.len(), generic_args.len());
+
+        let substitutions = self
+            .generics
+            .iter()
+            .zip(generic_args)
+            .map(|((old_id, old_var), new)| (*old_id, (old_var.clone(), new.clone())))
+            .collect();
+
+        self.typ.substitute(&substitutions)
+    }
+}
+
+impl std::fmt::Display for TypeAliasType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
 /// A shared, mutable reference to some T.
 /// Wrapper is required for Hash impl of RefCell.
 #[derive(Debug, Eq, PartialOrd, Ord)]
@@ -579,17 +764,33 @@ impl Type {
             | Type::Forall(_, _) => false,
 
             Type::Array(length, elem) => {
-                elem.contains_numeric_typevar(target_id) || named_generic_id_matches_target(length)
+                elem.contains_numeric_typevar(target_id)
+                    || named_generic_id_matches_target(length)
+                    || length.contains_numeric_typevar(target_id)
+            }
+
+            Type::InfixExpr(lhs, _, rhs) => {
+                named_generic_id_matches_target(lhs)
+                    || named_generic_id_matches_target(rhs)
+                    || lhs.contains_numeric_typevar(target_id)
+                    || rhs.contains_numeric_typevar(target_id)
             }
 
             Type::Slice(elem) => elem.contains_numeric_typevar(target_id),
 
+            Type::FmtString(length, fields) => {
+                named_generic_id_matches_target(length)
+                    || length.contains_numeric_typevar(target_id)
+                    || fields.contains_numeric_typevar(target_id)
+            }
+
             Type::Tuple(fields) => {
                 fields.iter().any(|field| field.contains_numeric_typevar(target_id))
             }
-            Type::Function(parameters, return_type) => {
+            Type::Function(parameters, return_type, env) => {
                 parameters.iter().any(|parameter| parameter.contains_numeric_typevar(target_id))
                     || return_type.contains_numeric_typevar(target_id)
+                    || env.contains_numeric_typevar(target_id)
             }
             Type::Struct(struct_type, generics) => {
                 generics.iter().enumerate().any(|(i, generic)| {
@@ -600,6 +801,13 @@ impl Type {
                     }
                 })
             }
+            Type::Enum(enum_type, generics) => generics.iter().enumerate().any(|(i, generic)| {
+                if named_generic_id_matches_target(generic) {
+                    enum_type.borrow().generic_is_numeric(i)
+                } else {
+                    generic.contains_numeric_typevar(target_id)
+                }
+            }),
             Type::MutableReference(element) => element.contains_numeric_typevar(target_id),
         }
     }
@@ -645,12 +853,21 @@ impl std::fmt::Display for Type {
                     write!(f, "{}<{}>", s.borrow(), args.join(", "))
                 }
             }
+            Type::Enum(e, args) => {
+                let args = vecmap(args, |arg| arg.to_string());
+                if args.is_empty() {
+                    write!(f, "{}", e.borrow())
+                } else {
+                    write!(f, "{}<{}>", e.borrow(), args.join(", "))
+                }
+            }
             Type::Tuple(elements) => {
                 let elements = vecmap(elements, ToString::to_string);
                 write!(f, "({})", elements.join(", "))
             }
             Type::Bool(comp_time) => write!(f, "{comp_time}bool"),
             Type::String(len) => write!(f, "str<{len}>"),
+            Type::FmtString(len, fields) => write!(f, "fmtstr<{len}, {fields}>"),
             Type::Unit => write!(f, "()"),
             Type::Error => write!(f, "error"),
             Type::TypeVariable(id) => write!(f, "{}", id.borrow()),
@@ -660,13 +877,20 @@ impl std::fmt::Display for Type {
                 TypeBinding::Unbound(_) => write!(f, "{name}"),
             },
             Type::Constant(x) => x.fmt(f),
+            Type::InfixExpr(lhs, op, rhs) => write!(f, "{lhs} {op} {rhs}"),
             Type::Forall(typevars, typ) => {
                 let typevars = vecmap(typevars, |(var, _)| var.to_string());
                 write!(f, "forall {}. {}", typevars.join(" "), typ)
             }
-            Type::Function(args, ret) => {
+            Type::Function(args, ret, env) => {
                 let args = vecmap(args, ToString::to_string);
-                write!(f, "fn({}) -> {}", args.join(", "), ret)
+                match env.follow_bindings() {
+                    Type::Tuple(captures) if !captures.is_empty() => {
+                        let captures = vecmap(captures, |capture| capture.to_string());
+                        write!(f, "fn[{}]({}) -> {}", captures.join(", "), args.join(", "), ret)
+                    }
+                    _ => write!(f, "fn({}) -> {}", args.join(", "), ret),
+                }
             }
             Type::MutableReference(element) => {
                 write!(f, "&mut {element}")
@@ -940,6 +1164,11 @@ impl Type {
 
             (Slice(elem_a), Slice(elem_b)) => elem_a.try_unify(elem_b, span),
 
+            (FmtString(len_a, fields_a), FmtString(len_b, fields_b)) => {
+                len_a.try_unify(len_b, span)?;
+                fields_a.try_unify(fields_b, span)
+            }
+
             (Tuple(elements_a), Tuple(elements_b)) => {
                 if elements_a.len() != elements_b.len() {
                     Err(SpanKind::None)
@@ -965,6 +1194,17 @@ impl Type {
                 }
             }
 
+            (Enum(enum_a, args_a), Enum(enum_b, args_b)) => {
+                if enum_a == enum_b {
+                    for (a, b) in args_a.iter().zip(args_b) {
+                        a.try_unify(b, span)?;
+                    }
+                    Ok(())
+                } else {
+                    Err(SpanKind::None)
+                }
+            }
+
             (FieldElement(comptime_a), FieldElement(comptime_b)) => {
                 comptime_a.unify(comptime_b, span)
             }
@@ -991,12 +1231,13 @@ impl Type {
                 }
             }
 
-            (Function(params_a, ret_a), Function(params_b, ret_b)) => {
+            (Function(params_a, ret_a, env_a), Function(params_b, ret_b, env_b)) => {
                 if params_a.len() == params_b.len() {
                     for (a, b) in params_a.iter().zip(params_b) {
                         a.try_unify(b, span)?;
                     }
 
+                    env_a.try_unify(env_b, span)?;
                     ret_b.try_unify(ret_a, span)
                 } else {
                     Err(SpanKind::None)
@@ -1005,6 +1246,8 @@ impl Type {
 
             (MutableReference(elem_a), MutableReference(elem_b)) => elem_a.try_unify(elem_b, span),
 
+            (InfixExpr(..), _) | (_, InfixExpr(..)) => self.try_unify_type_expressions(other, span),
+
             (other_a, other_b) => {
                 if other_a == other_b {
                     Ok(())
@@ -1075,7 +1318,10 @@ impl Type {
 
             (Slice(elem_a), Slice(elem_b)) => elem_a.is_subtype_of(elem_b, span),
 
-            (Array(_, elem_a), Slice(elem_b)) => elem_a.is_subtype_of(elem_b, span),
+            (FmtString(len_a, fields_a), FmtString(len_b, fields_b)) => {
+                len_a.is_subtype_of(len_b, span)?;
+                fields_a.is_subtype_of(fields_b, span)
+            }
 
             (Tuple(elements_a), Tuple(elements_b)) => {
                 if elements_a.len() != elements_b.len() {
@@ -1101,6 +1347,17 @@ impl Type {
                 }
             }
 
+            (Enum(enum_a, args_a), Enum(enum_b, args_b)) => {
+                if enum_a == enum_b && args_a.len() == args_b.len() {
+                    for (a, b) in args_a.iter().zip(args_b) {
+                        a.is_subtype_of(b, span)?;
+                    }
+                    Ok(())
+                } else {
+                    Err(SpanKind::None)
+                }
+            }
+
             (FieldElement(comptime_a), FieldElement(comptime_b)) => {
                 comptime_a.is_subtype_of(comptime_b, span)
             }
@@ -1127,12 +1384,16 @@ impl Type {
                 }
             }
 
-            (Function(params_a, ret_a), Function(params_b, ret_b)) => {
+            (Function(params_a, ret_a, env_a), Function(params_b, ret_b, env_b)) => {
                 if params_a.len() == params_b.len() {
                     for (a, b) in params_a.iter().zip(params_b) {
                         a.is_subtype_of(b, span)?;
                     }
 
+                    // Closures are called differently depending on their environment,
+                    // so it must be the same for both functions
+                    env_a.try_unify(env_b, span)?;
+
                     // return types are contravariant, so this must be ret_b <: ret_a instead of the reverse
                     ret_b.is_subtype_of(ret_a, span)
                 } else {
@@ -1156,6 +1417,8 @@ impl Type {
             // ```
             (MutableReference(elem_a), MutableReference(elem_b)) => elem_a.try_unify(elem_b, span),
 
+            (InfixExpr(..), _) | (_, InfixExpr(..)) => self.try_unify_type_expressions(other, span),
+
             (other_a, other_b) => {
                 if other_a == other_b {
                     Ok(())
@@ -1166,6 +1429,25 @@ impl Type {
         }
     }
 
+    /// Type-level integer expressions unify if they evaluate to the same constant. Otherwise,
+    /// as when their generics are not yet known, they must be the same operation on operands
+    /// which unify.
+    fn try_unify_type_expressions(&self, other: &Type, span: Span) -> Result<(), SpanKind> {
+        match (self.evaluate_to_u64(), other.evaluate_to_u64()) {
+            (Some(a), Some(b)) if a == b => Ok(()),
+            (Some(_), Some(_)) => Err(SpanKind::None),
+            _ => match (self, other) {
+                (Type::InfixExpr(lhs_a, op_a, rhs_a), Type::InfixExpr(lhs_b, op_b, rhs_b))
+                    if op_a == op_b =>
+                {
+                    lhs_a.try_unify(lhs_b, span)?;
+                    rhs_a.try_unify(rhs_b, span)
+                }
+                _ => Err(SpanKind::None),
+            },
+        }
+    }
+
     /// If this type is a Type::Constant (used in array lengths), or is bound
     /// to a Type::Constant, return the constant as a u64.
     pub fn evaluate_to_u64(&self) -> Option<u64> {
@@ -1178,10 +1460,67 @@ impl Type {
             },
             Type::Array(len, _elem) => len.evaluate_to_u64(),
             Type::Constant(x) => Some(*x),
+            Type::InfixExpr(lhs, op, rhs) => {
+                op.function()(lhs.evaluate_to_u64()?, rhs.evaluate_to_u64()?)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the first arithmetic on type-level integers within this type whose operands are
+    /// known but which cannot be evaluated, as it overflows or divides by zero. For example,
+    /// `[u8; N - 1]` once `N` is bound to 0.
+    pub fn find_invalid_type_expression(&self) -> Option<Type> {
+        self.follow_bindings().find_invalid_followed_type_expression().cloned()
+    }
+
+    fn find_invalid_followed_type_expression(&self) -> Option<&Type> {
+        match self {
+            // Type expressions which can be evaluated were replaced by constants when following
+            // bindings, so one left with known operands cannot be evaluated
+            Type::InfixExpr(lhs, _, rhs) => lhs
+                .find_invalid_followed_type_expression()
+                .or_else(|| rhs.find_invalid_followed_type_expression())
+                .or_else(|| {
+                    let known = lhs.evaluate_to_u64().is_some() && rhs.evaluate_to_u64().is_some();
+                    known.then_some(self)
+                }),
+            Type::Array(length, element) => length
+                .find_invalid_followed_type_expression()
+                .or_else(|| element.find_invalid_followed_type_expression()),
+            Type::String(length) => length.find_invalid_followed_type_expression(),
+            Type::FmtString(length, fields) => length
+                .find_invalid_followed_type_expression()
+                .or_else(|| fields.find_invalid_followed_type_expression()),
+            Type::Slice(element) | Type::MutableReference(element) => {
+                element.find_invalid_followed_type_expression()
+            }
+            Type::Struct(_, args) | Type::Enum(_, args) | Type::Tuple(args) => {
+                args.iter().find_map(Type::find_invalid_followed_type_expression)
+            }
+            Type::Function(args, ret, _) => args
+                .iter()
+                .find_map(Type::find_invalid_followed_type_expression)
+                .or_else(|| ret.find_invalid_followed_type_expression()),
             _ => None,
         }
     }
 
+    /// True if this type is or contains an enum, which has no encoding in the abi.
+    pub(crate) fn contains_enum(&self) -> bool {
+        match self.follow_bindings() {
+            Type::Enum(..) => true,
+            Type::Array(_, element) | Type::Slice(element) | Type::MutableReference(element) => {
+                element.contains_enum()
+            }
+            Type::Tuple(fields) => fields.iter().any(Type::contains_enum),
+            Type::Struct(def, args) => {
+                def.borrow().get_fields(&args).iter().any(|(_, typ)| typ.contains_enum())
+            }
+            _ => false,
+        }
+    }
+
     // Note; use strict_eq instead of partial_eq when comparing field types
     // in this method, you most likely want to distinguish between public and private
     pub fn as_abi_type(&self) -> AbiType {
@@ -1214,23 +1553,96 @@ impl Type {
             }
             Type::Error => unreachable!(),
             Type::Unit => unreachable!(),
-            Type::Constant(_) => unreachable!(),
+            Type::Constant(_) | Type::InfixExpr(..) => unreachable!(),
             Type::Struct(def, args) => {
                 let struct_type = def.borrow();
                 let fields = struct_type.get_fields(args);
                 let fields = vecmap(fields, |(name, typ)| (name, typ.as_abi_type()));
                 AbiType::Struct { fields }
             }
-            Type::Tuple(_) => todo!("as_abi_type not yet implemented for tuple types"),
+            Type::Tuple(fields) => {
+                let fields = vecmap(fields, |typ| typ.as_abi_type());
+                AbiType::Tuple { fields }
+            }
+            Type::Enum(..) => unreachable!("enums cannot be used in the abi"),
             Type::TypeVariable(_) => unreachable!(),
             Type::NamedGeneric(..) => unreachable!(),
             Type::Forall(..) => unreachable!(),
-            Type::Function(_, _) => unreachable!(),
+            Type::Function(..) => unreachable!(),
             Type::Slice(_) => unreachable!("slices cannot be used in the abi"),
+            Type::FmtString(..) => unreachable!("format strings cannot be used in the abi"),
             Type::MutableReference(_) => unreachable!("&mut cannot be used in the abi"),
         }
     }
 
+    /// Describes how values of this type should be displayed when passed to `println`.
+    /// Tuples are displayed as structs whose fields are named after their index.
+    pub fn as_printable_type(&self) -> PrintableType {
+        match self.follow_bindings() {
+            Type::FmtString(length, fields) => {
+                let length = length.evaluate_to_u64().unwrap_or(0);
+                let fields = match *fields {
+                    Type::Tuple(fields) => fields.iter().map(Type::as_printable_abi_type).collect(),
+                    _ => None,
+                };
+                fields.map_or(PrintableType::Unknown, |fields| PrintableType::FormatString {
+                    length,
+                    fields,
+                })
+            }
+            typ => typ
+                .as_printable_abi_type()
+                .map_or(PrintableType::Unknown, |typ| PrintableType::Value { typ }),
+        }
+    }
+
+    /// Like `as_abi_type` but returns None for any type which cannot be displayed
+    /// rather than panicking. Bindings are expected to already have been followed.
+    fn as_printable_abi_type(&self) -> Option<AbiType> {
+        match self {
+            Type::FieldElement(_) | Type::TypeVariable(_) | Type::NamedGeneric(..) => {
+                Some(AbiType::Field)
+            }
+            Type::Integer(..) | Type::Bool(_) | Type::PolymorphicInteger(..) => {
+                Some(self.as_abi_type())
+            }
+            Type::String(length) => {
+                length.evaluate_to_u64().map(|length| AbiType::String { length })
+            }
+            Type::Array(length, element) => {
+                let length = length.evaluate_to_u64()?;
+                let typ = Box::new(element.as_printable_abi_type()?);
+                Some(AbiType::Array { length, typ })
+            }
+            Type::Struct(def, args) => {
+                let fields = def.borrow().get_fields(args);
+                let fields = fields
+                    .into_iter()
+                    .map(|(name, typ)| Some((name, typ.as_printable_abi_type()?)))
+                    .collect::<Option<_>>()?;
+                Some(AbiType::Struct { fields })
+            }
+            Type::Tuple(fields) => {
+                let fields = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(index, typ)| Some((index.to_string(), typ.as_printable_abi_type()?)))
+                    .collect::<Option<_>>()?;
+                Some(AbiType::Struct { fields })
+            }
+            Type::FmtString(..)
+            | Type::Unit
+            | Type::Error
+            | Type::Constant(_)
+            | Type::InfixExpr(..)
+            | Type::Enum(..)
+            | Type::Forall(..)
+            | Type::Function(..)
+            | Type::Slice(_)
+            | Type::MutableReference(_) => None,
+        }
+    }
+
     /// Iterate over the fields of this type.
     /// Panics if the type is not a struct or tuple.
     pub fn iter_fields(&self) -> impl Iterator<Item = (String, Type)> {
@@ -1287,6 +1699,43 @@ impl Type {
         }
     }
 
+    /// Returns the bindings of the type variables this type is quantified over which make
+    /// it equal to the given type, or None if no such bindings exist. Unlike unifying the
+    /// types, this leaves the type variables this type is quantified over as they were.
+    pub fn instantiate_as(&self, other: &Type) -> Option<TypeBindings> {
+        match self {
+            Type::Forall(typevars, typ) => {
+                // The type variables may still be bound while monomorphizing a function using
+                // them, so their bindings are set aside and restored afterward.
+                let previous_bindings = vecmap(typevars, |(id, var)| {
+                    std::mem::replace(&mut *var.borrow_mut(), TypeBinding::Unbound(*id))
+                });
+
+                let replacements = typevars
+                    .iter()
+                    .map(|(id, var)| (*id, (var.clone(), Type::TypeVariable(var.clone()))))
+                    .collect();
+
+                let unified = typ.substitute(&replacements).try_unify(other, Span::default());
+                let bindings = typevars
+                    .iter()
+                    .filter_map(|(id, var)| match &*var.borrow() {
+                        TypeBinding::Bound(binding) => {
+                            Some((*id, (var.clone(), binding.follow_bindings())))
+                        }
+                        TypeBinding::Unbound(_) => None,
+                    })
+                    .collect();
+
+                for ((_, var), previous_binding) in typevars.iter().zip(previous_bindings) {
+                    *var.borrow_mut() = previous_binding;
+                }
+                unified.ok().map(|_| bindings)
+            }
+            other_type => other_type.try_unify(other, Span::default()).ok().map(|_| HashMap::new()),
+        }
+    }
+
     /// Substitute any type variables found within this type with the
     /// given bindings if found. If a type variable is not found within
     /// the given TypeBindings, it is unchanged.
@@ -1317,6 +1766,11 @@ impl Type {
                 let size = Box::new(size.substitute(type_bindings));
                 Type::String(size)
             }
+            Type::FmtString(size, fields) => {
+                let size = Box::new(size.substitute(type_bindings));
+                let fields = Box::new(fields.substitute(type_bindings));
+                Type::FmtString(size, fields)
+            }
             Type::PolymorphicInteger(_, binding)
             | Type::NamedGeneric(binding, _)
             | Type::TypeVariable(binding) => substitute_binding(binding),
@@ -1327,6 +1781,10 @@ impl Type {
                 let args = vecmap(args, |arg| arg.substitute(type_bindings));
                 Type::Struct(fields.clone(), args)
             }
+            Type::Enum(variants, args) => {
+                let args = vecmap(args, |arg| arg.substitute(type_bindings));
+                Type::Enum(variants.clone(), args)
+            }
             Type::Tuple(fields) => {
                 let fields = vecmap(fields, |field| field.substitute(type_bindings));
                 Type::Tuple(fields)
@@ -1340,14 +1798,20 @@ impl Type {
                 let typ = Box::new(typ.substitute(type_bindings));
                 Type::Forall(typevars.clone(), typ)
             }
-            Type::Function(args, ret) => {
+            Type::Function(args, ret, env) => {
                 let args = vecmap(args, |arg| arg.substitute(type_bindings));
                 let ret = Box::new(ret.substitute(type_bindings));
-                Type::Function(args, ret)
+                let env = Box::new(env.substitute(type_bindings));
+                Type::Function(args, ret, env)
             }
             Type::MutableReference(element) => {
                 Type::MutableReference(Box::new(element.substitute(type_bindings)))
             }
+            Type::InfixExpr(lhs, op, rhs) => {
+                let lhs = Box::new(lhs.substitute(type_bindings));
+                let rhs = Box::new(rhs.substitute(type_bindings));
+                Type::InfixExpr(lhs, *op, rhs)
+            }
 
             Type::FieldElement(_)
             | Type::Integer(_, _, _)
@@ -1364,7 +1828,10 @@ impl Type {
             Type::Array(len, elem) => len.occurs(target_id) || elem.occurs(target_id),
             Type::Slice(element) => element.occurs(target_id),
             Type::String(len) => len.occurs(target_id),
-            Type::Struct(_, generic_args) => generic_args.iter().any(|arg| arg.occurs(target_id)),
+            Type::FmtString(len, fields) => len.occurs(target_id) || fields.occurs(target_id),
+            Type::Struct(_, generic_args) | Type::Enum(_, generic_args) => {
+                generic_args.iter().any(|arg| arg.occurs(target_id))
+            }
             Type::Tuple(fields) => fields.iter().any(|field| field.occurs(target_id)),
             Type::PolymorphicInteger(_, binding)
             | Type::NamedGeneric(binding, _)
@@ -1375,10 +1842,13 @@ impl Type {
             Type::Forall(typevars, typ) => {
                 !typevars.iter().any(|(id, _)| *id == target_id) && typ.occurs(target_id)
             }
-            Type::Function(args, ret) => {
-                args.iter().any(|arg| arg.occurs(target_id)) || ret.occurs(target_id)
+            Type::Function(args, ret, env) => {
+                args.iter().any(|arg| arg.occurs(target_id))
+                    || ret.occurs(target_id)
+                    || env.occurs(target_id)
             }
             Type::MutableReference(element) => element.occurs(target_id),
+            Type::InfixExpr(lhs, _, rhs) => lhs.occurs(target_id) || rhs.occurs(target_id),
 
             Type::FieldElement(_)
             | Type::Integer(_, _, _)
@@ -1403,10 +1873,17 @@ impl Type {
             }
             Slice(elem) => Slice(Box::new(elem.follow_bindings())),
             String(size) => String(Box::new(size.follow_bindings())),
+            FmtString(size, fields) => {
+                FmtString(Box::new(size.follow_bindings()), Box::new(fields.follow_bindings()))
+            }
             Struct(def, args) => {
                 let args = vecmap(args, |arg| arg.follow_bindings());
                 Struct(def.clone(), args)
             }
+            Enum(def, args) => {
+                let args = vecmap(args, |arg| arg.follow_bindings());
+                Enum(def.clone(), args)
+            }
             Tuple(args) => Tuple(vecmap(args, |arg| arg.follow_bindings())),
 
             TypeVariable(var) | PolymorphicInteger(_, var) | NamedGeneric(var, _) => {
@@ -1416,13 +1893,21 @@ impl Type {
                 self.clone()
             }
 
-            Function(args, ret) => {
+            Function(args, ret, env) => {
                 let args = vecmap(args, |arg| arg.follow_bindings());
                 let ret = Box::new(ret.follow_bindings());
-                Function(args, ret)
+                let env = Box::new(env.follow_bindings());
+                Function(args, ret, env)
             }
             MutableReference(element) => MutableReference(Box::new(element.follow_bindings())),
 
+            InfixExpr(lhs, op, rhs) => match self.evaluate_to_u64() {
+                Some(value) => Constant(value),
+                None => {
+                    InfixExpr(Box::new(lhs.follow_bindings()), *op, Box::new(rhs.follow_bindings()))
+                }
+            },
+
             // Expect that this function should only be called on instantiated types
             Forall(..) => unreachable!(),
 
@@ -1434,14 +1919,15 @@ impl Type {
 }
 
 impl BinaryTypeOperator {
-    /// Return the actual rust numeric function associated with this operator
-    pub fn function(self) -> fn(u64, u64) -> u64 {
+    /// Return the actual rust numeric function associated with this operator, which returns
+    /// None if the operation overflows or divides by zero
+    pub fn function(self) -> fn(u64, u64) -> Option<u64> {
         match self {
-            BinaryTypeOperator::Addition => |a, b| a.wrapping_add(b),
-            BinaryTypeOperator::Subtraction => |a, b| a.wrapping_sub(b),
-            BinaryTypeOperator::Multiplication => |a, b| a.wrapping_mul(b),
-            BinaryTypeOperator::Division => |a, b| a.wrapping_div(b),
-            BinaryTypeOperator::Modulo => |a, b| a.wrapping_rem(b), // % b,
+            BinaryTypeOperator::Addition => |a, b| a.checked_add(b),
+            BinaryTypeOperator::Subtraction => |a, b| a.checked_sub(b),
+            BinaryTypeOperator::Multiplication => |a, b| a.checked_mul(b),
+            BinaryTypeOperator::Division => |a, b| a.checked_div(b),
+            BinaryTypeOperator::Modulo => |a, b| a.checked_rem(b),
         }
     }
 }
@@ -1,6 +1,7 @@
 use crate::token::SpannedToken;
 
 use super::token::Token;
+use noirc_errors::registry::{self, ErrorCode};
 use noirc_errors::CustomDiagnostic as Diagnostic;
 use noirc_errors::Span;
 use thiserror::Error;
@@ -13,6 +14,8 @@ pub enum LexerErrorKind {
     NotADoubleChar { span: Span, found: Token },
     #[error("InvalidIntegerLiteral : {:?} is not a integer", found)]
     InvalidIntegerLiteral { span: Span, found: String },
+    #[error("IntegerLiteralTooLarge : {:?} does not fit in a field element", found)]
+    IntegerLiteralTooLarge { span: Span, found: String },
     #[error("MalformedFuncAttribute : {:?} is not a valid attribute", found)]
     MalformedFuncAttribute { span: Span, found: String },
     #[error("TooManyBits")]
@@ -27,12 +30,25 @@ impl LexerErrorKind {
             LexerErrorKind::UnexpectedCharacter { span, .. } => *span,
             LexerErrorKind::NotADoubleChar { span, .. } => *span,
             LexerErrorKind::InvalidIntegerLiteral { span, .. } => *span,
+            LexerErrorKind::IntegerLiteralTooLarge { span, .. } => *span,
             LexerErrorKind::MalformedFuncAttribute { span, .. } => *span,
             LexerErrorKind::TooManyBits { span, .. } => *span,
             LexerErrorKind::LogicalAnd { span } => *span,
         }
     }
 
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            LexerErrorKind::UnexpectedCharacter { .. } => registry::E0001,
+            LexerErrorKind::NotADoubleChar { .. } => registry::E0002,
+            LexerErrorKind::InvalidIntegerLiteral { .. } => registry::E0003,
+            LexerErrorKind::IntegerLiteralTooLarge { .. } => registry::E0004,
+            LexerErrorKind::MalformedFuncAttribute { .. } => registry::E0005,
+            LexerErrorKind::TooManyBits { .. } => registry::E0006,
+            LexerErrorKind::LogicalAnd { .. } => registry::E0007,
+        }
+    }
+
     fn parts(&self) -> (String, String, Span) {
         match self {
             LexerErrorKind::UnexpectedCharacter {
@@ -56,6 +72,11 @@ impl LexerErrorKind {
                 format!(" {found} is not an integer"),
                 *span,
             ),
+            LexerErrorKind::IntegerLiteralTooLarge { span, found } => (
+                "integer literal too large".to_string(),
+                format!(" {found} is not less than the field modulus"),
+                *span,
+            ),
             LexerErrorKind::MalformedFuncAttribute { span, found } => (
                 "malformed function attribute".to_string(),
                 format!(" {found} is not a valid attribute"),
@@ -80,7 +101,13 @@ impl LexerErrorKind {
 impl From<LexerErrorKind> for Diagnostic {
     fn from(error: LexerErrorKind) -> Diagnostic {
         let (primary, secondary, span) = error.parts();
-        Diagnostic::simple_error(primary, secondary, span)
+        let mut diagnostic =
+            Diagnostic::simple_error(primary, secondary, span).with_code(error.code());
+
+        if let LexerErrorKind::LogicalAnd { span } = error {
+            diagnostic.add_suggestion("use the bitwise and operator".into(), span, "&".into());
+        }
+        diagnostic
     }
 }
 
@@ -6,6 +6,7 @@ use acvm::FieldElement;
 use noirc_errors::{Position, Span};
 use std::str::Chars;
 use std::{
+    cell::Cell,
     iter::{Peekable, Zip},
     ops::RangeFrom,
 };
@@ -104,6 +105,7 @@ impl<'a> Lexer<'a> {
             Some(']') => self.single_char_token(Token::RightBracket),
             Some('"') => Ok(self.eat_string_literal()),
             Some('#') => self.eat_attribute(),
+            Some('f') if self.peek_char_is('"') => Ok(self.eat_format_string_literal()),
             Some(ch) if ch.is_ascii_alphanumeric() || ch == '_' => self.eat_alpha_numeric(ch),
             Some(ch) => {
                 // We don't report invalid tokens in the source as errors until parsing to
@@ -169,13 +171,25 @@ impl<'a> Lexer<'a> {
                 }
             }
             Token::Bang => self.single_double_peek_token('=', prev_token, Token::NotEqual),
-            Token::Assign => self.single_double_peek_token('=', prev_token, Token::Equal),
+            Token::Assign => {
+                let start = self.position;
+                if self.peek_char_is('=') {
+                    self.next_char();
+                    Ok(Token::Equal.into_span(start, start + 1))
+                } else if self.peek_char_is('>') {
+                    self.next_char();
+                    Ok(Token::FatArrow.into_span(start, start + 1))
+                } else {
+                    Ok(prev_token.into_single_span(start))
+                }
+            }
             Token::Minus => self.single_double_peek_token('>', prev_token, Token::Arrow),
             Token::Colon => self.single_double_peek_token(':', prev_token, Token::DoubleColon),
             Token::Slash => {
+                let start = self.position;
                 if self.peek_char_is('/') {
                     self.next_char();
-                    return self.parse_comment();
+                    return self.parse_comment(start);
                 }
                 Ok(spanned_prev_token)
             }
@@ -257,10 +271,36 @@ impl<'a> Lexer<'a> {
         }
         self.next_char();
 
-        let (word, start, end) = self.eat_while(None, |ch| {
-            (ch.is_ascii_alphabetic() || ch.is_numeric() || ch == '_' || ch == '(' || ch == ')')
-                && (ch != ']')
+        let (name, start, _) =
+            self.eat_while(None, |ch| ch.is_ascii_alphabetic() || ch.is_numeric() || ch == '_');
+
+        // Besides parentheses, the arguments of an attribute may only contain the separators
+        // that attribute needs, such as the commas between the traits of a `#[derive(..)]`.
+        let separators: &[char] = match name.as_str() {
+            "derive" => &[',', ' '],
+            "cfg" => &['=', ' '],
+            "deprecated" => &[' '],
+            _ => &[],
+        };
+        // Strings within an attribute, such as the message of `#[deprecated("..")]`, may
+        // contain any character other than a quote.
+        let allows_strings = matches!(name.as_str(), "cfg" | "deprecated");
+        let in_string = Cell::new(false);
+        let (arguments, _, end) = self.eat_while(None, |ch| {
+            if allows_strings && ch == '"' {
+                in_string.set(!in_string.get());
+                return true;
+            }
+
+            in_string.get()
+                || ch.is_ascii_alphabetic()
+                || ch.is_numeric()
+                || ch == '_'
+                || ch == '('
+                || ch == ')'
+                || separators.contains(&ch)
         });
+        let word = name + &arguments;
 
         if !self.peek_char_is(']') {
             return Err(LexerErrorKind::UnexpectedCharacter {
@@ -304,22 +344,39 @@ impl<'a> Lexer<'a> {
         Ok(ident_token.into_span(start, end))
     }
 
+    /// Lexes an integer literal. Literals may be written in decimal, or in hexadecimal, octal
+    /// or binary using the `0x`, `0o` and `0b` prefixes, and may contain `_` digit separators.
     fn eat_digit(&mut self, initial_char: char) -> SpannedTokenResult {
         let (integer_str, start, end) = self.eat_while(Some(initial_char), |ch| {
-            ch.is_ascii_digit() | ch.is_ascii_hexdigit() | (ch == 'x')
+            ch.is_ascii_hexdigit() | (ch == 'x') | (ch == 'o') | (ch == '_')
         });
+        let span = Span::exclusive(start, end);
 
-        let integer = match FieldElement::try_from_str(&integer_str) {
-            None => {
-                return Err(LexerErrorKind::InvalidIntegerLiteral {
-                    span: Span::exclusive(start, end),
-                    found: integer_str,
-                })
-            }
-            Some(integer) => integer,
+        let (radix, digits) = match integer_str.get(..2) {
+            Some("0x") => (16, &integer_str[2..]),
+            Some("0o") => (8, &integer_str[2..]),
+            Some("0b") => (2, &integer_str[2..]),
+            _ => (10, integer_str.as_str()),
+        };
+
+        let mut bytes = match parse_integer_digits(digits, radix) {
+            Some(bytes) => bytes,
+            None => return Err(LexerErrorKind::InvalidIntegerLiteral { span, found: integer_str }),
         };
 
-        let integer_token = Token::Int(integer);
+        // Literals are never silently reduced modulo the field
+        let modulus = FieldElement::modulus().to_bytes_le();
+        let exceeds_modulus = bytes
+            .len()
+            .cmp(&modulus.len())
+            .then_with(|| bytes.iter().rev().cmp(modulus.iter().rev()))
+            .is_ge();
+        if exceeds_modulus {
+            return Err(LexerErrorKind::IntegerLiteralTooLarge { span, found: integer_str });
+        }
+
+        bytes.reverse();
+        let integer_token = Token::Int(FieldElement::from_be_bytes_reduce(&bytes));
         Ok(integer_token.into_span(start, end))
     }
 
@@ -330,8 +387,29 @@ impl<'a> Lexer<'a> {
         str_literal_token.into_span(start_span, end_span)
     }
 
-    fn parse_comment(&mut self) -> SpannedTokenResult {
-        let _ = self.eat_while(None, |ch| ch != '\n');
+    fn eat_format_string_literal(&mut self) -> SpannedToken {
+        let start = self.position;
+        self.next_char(); // Advance past the opening quote
+        let (str_literal, _, end_span) = self.eat_while(None, |ch| ch != '"');
+        let str_literal_token = Token::FmtStr(str_literal);
+        self.next_char(); // Advance past the closing quote
+        str_literal_token.into_span(start, end_span)
+    }
+
+    /// Skips a `//` comment, unless it is a `///` doc comment which is kept as a token so that
+    /// the parser can attach it to the item following it. As in Rust, comments starting with
+    /// four or more slashes are ordinary comments.
+    fn parse_comment(&mut self, start: Position) -> SpannedTokenResult {
+        let is_doc_comment = self.peek_char_is('/') && {
+            self.next_char();
+            !self.peek_char_is('/')
+        };
+
+        let (comment, _, end) = self.eat_while(None, |ch| ch != '\n');
+        if is_doc_comment {
+            let comment = comment.strip_prefix(' ').unwrap_or(&comment).trim_end().to_owned();
+            return Ok(Token::DocComment(comment).into_span(start, end));
+        }
         self.next_token()
     }
 
@@ -341,6 +419,30 @@ impl<'a> Lexer<'a> {
     }
 }
 
+/// Parses the digits of an integer literal in the given radix, ignoring `_` separators.
+/// Returns the value as little-endian bytes without trailing zeroes, or `None` if there are
+/// no digits or a digit is invalid for the radix.
+fn parse_integer_digits(digits: &str, radix: u32) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut has_digits = false;
+
+    for ch in digits.chars().filter(|ch| *ch != '_') {
+        let mut carry = ch.to_digit(radix)?;
+        has_digits = true;
+
+        for byte in bytes.iter_mut() {
+            let value = u32::from(*byte) * radix + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        if carry != 0 {
+            bytes.push(carry as u8);
+        }
+    }
+
+    has_digits.then_some(bytes)
+}
+
 impl<'a> Iterator for Lexer<'a> {
     type Item = SpannedTokenResult;
     fn next(&mut self) -> Option<Self::Item> {
@@ -354,7 +456,7 @@ impl<'a> Iterator for Lexer<'a> {
 
 #[test]
 fn test_single_double_char() {
-    let input = "! != + ( ) { } [ ] | , ; : :: < <= > >= & - -> . .. % / * = == << >>";
+    let input = "! != + ( ) { } [ ] | , ; : :: < <= > >= & - -> . .. % / * = == => << >>";
 
     let expected = vec![
         Token::Bang,
@@ -385,6 +487,7 @@ fn test_single_double_char() {
         Token::Star,
         Token::Assign,
         Token::Equal,
+        Token::FatArrow,
         Token::ShiftLeft,
         Token::Greater,
         Token::Greater,
@@ -401,12 +504,33 @@ fn test_single_double_char() {
 
 #[test]
 fn test_custom_gate_syntax() {
-    let input = "#[foreign(sha256)]#[foreign(blake2s)]#[builtin(sum)]";
+    let input = "#[foreign(sha256)]#[foreign(blake2s)]#[builtin(sum)]#[inline(never)]";
 
     let expected = vec![
         Token::Attribute(Attribute::Foreign("sha256".to_string())),
         Token::Attribute(Attribute::Foreign("blake2s".to_string())),
         Token::Attribute(Attribute::Builtin("sum".to_string())),
+        Token::Attribute(Attribute::Inline(crate::token::InlineType::Never)),
+    ];
+
+    let mut lexer = Lexer::new(input);
+    for token in expected.into_iter() {
+        let got = lexer.next_token().unwrap();
+        assert_eq!(got, token);
+    }
+}
+
+#[test]
+fn test_derive_attribute() {
+    let input = "#[derive(Eq)] #[derive(Eq, Default,Serialize)]";
+
+    let expected = vec![
+        Token::Attribute(Attribute::Derive(vec!["Eq".to_string()])),
+        Token::Attribute(Attribute::Derive(vec![
+            "Eq".to_string(),
+            "Default".to_string(),
+            "Serialize".to_string(),
+        ])),
     ];
 
     let mut lexer = Lexer::new(input);
@@ -414,6 +538,65 @@ fn test_custom_gate_syntax() {
         let got = lexer.next_token().unwrap();
         assert_eq!(got, token);
     }
+
+    for input in ["#[derive()]", "#[derive(Eq,)]", "#[derive(Eq Default)]"] {
+        assert!(Lexer::new(input).next_token().is_err());
+    }
+
+    // Only derive attributes may separate their arguments with commas and spaces
+    for input in ["#[foreign(sha256, blake2s)]", "#[oracle(get number)]", "#[test, derive(Eq)]"] {
+        assert!(Lexer::new(input).next_token().is_err());
+    }
+}
+
+#[test]
+fn test_deprecated_attribute() {
+    let input = r#"#[deprecated] #[deprecated("use bar() instead, [see docs]")]"#;
+
+    let expected = vec![
+        Token::Attribute(Attribute::Deprecated(None)),
+        Token::Attribute(Attribute::Deprecated(Some("use bar() instead, [see docs]".to_string()))),
+    ];
+
+    let mut lexer = Lexer::new(input);
+    for token in expected.into_iter() {
+        let got = lexer.next_token().unwrap();
+        assert_eq!(got, token);
+    }
+
+    for input in ["#[deprecated()]", "#[deprecated(note)]", "#[deprecatedd]"] {
+        assert!(Lexer::new(input).next_token().is_err());
+    }
+}
+
+#[test]
+fn test_cfg_attribute() {
+    let input = r#"#[cfg(feature = "poseidon")] #[cfg(feature="pedersen_v2")]"#;
+
+    let expected = vec![
+        Token::Attribute(Attribute::Cfg("poseidon".to_string())),
+        Token::Attribute(Attribute::Cfg("pedersen_v2".to_string())),
+    ];
+
+    let mut lexer = Lexer::new(input);
+    for token in expected.into_iter() {
+        let got = lexer.next_token().unwrap();
+        assert_eq!(got, token);
+    }
+
+    for input in [r#"#[cfg(feature)]"#, r#"#[cfg(feature = "")]"#, r#"#[cfg(test = "x")]"#] {
+        assert!(Lexer::new(input).next_token().is_err());
+    }
+}
+
+#[test]
+fn test_allow_attribute() {
+    let mut lexer = Lexer::new("#[allow(unused)]");
+    assert_eq!(lexer.next_token().unwrap(), Token::Attribute(Attribute::AllowUnused));
+
+    for input in ["#[allow]", "#[allow(dead_code)]", "#[allow()]"] {
+        assert!(Lexer::new(input).next_token().is_err());
+    }
 }
 
 #[test]
@@ -480,6 +663,28 @@ fn test_comment() {
     }
 }
 
+#[test]
+fn test_doc_comment() {
+    let input = "/// Adds one
+        ///
+        //// not a doc comment
+        ///    indented
+        fn";
+
+    let expected = vec![
+        Token::DocComment("Adds one".to_string()),
+        Token::DocComment("".to_string()),
+        Token::DocComment("   indented".to_string()),
+        Token::Keyword(Keyword::Fn),
+    ];
+
+    let mut lexer = Lexer::new(input);
+    for token in expected.into_iter() {
+        let got = lexer.next_token().unwrap();
+        assert_eq!(got, token);
+    }
+}
+
 #[test]
 fn test_eat_string_literal() {
     let input = "let _word = \"hello\"";
@@ -498,6 +703,27 @@ fn test_eat_string_literal() {
     }
 }
 
+#[test]
+fn test_eat_format_string_literal() {
+    let input = "let _word = f\"x = {x}\"; let f = 1";
+
+    let expected = vec![
+        Token::Keyword(Keyword::Let),
+        Token::Ident("_word".to_string()),
+        Token::Assign,
+        Token::FmtStr("x = {x}".to_string()),
+        Token::Semicolon,
+        Token::Keyword(Keyword::Let),
+        Token::Ident("f".to_string()),
+    ];
+    let mut lexer = Lexer::new(input);
+
+    for token in expected.into_iter() {
+        let got = lexer.next_token().unwrap();
+        assert_eq!(got, token);
+    }
+}
+
 #[test]
 fn test_eat_hex_int() {
     let input = "0x05";
@@ -511,6 +737,45 @@ fn test_eat_hex_int() {
     }
 }
 
+#[test]
+fn test_eat_integer_literals_with_radix_and_separators() {
+    let cases = [
+        ("1_000_000", 1_000_000_u128),
+        ("0b1010_1100", 0b1010_1100),
+        ("0o777", 0o777),
+        ("0x_ff_ff", 0xffff),
+        ("0", 0),
+        ("007", 7),
+    ];
+
+    for (input, expected) in cases {
+        let got = Lexer::new(input).next_token().unwrap();
+        assert_eq!(got, Token::Int(expected.into()), "failed to lex {input}");
+    }
+}
+
+#[test]
+fn test_invalid_integer_literals() {
+    for input in ["0b102", "0o8", "0x", "0b_"] {
+        let got = Lexer::new(input).next_token();
+        assert!(
+            matches!(got, Err(LexerErrorKind::InvalidIntegerLiteral { .. })),
+            "expected {input} to be an invalid integer literal"
+        );
+    }
+}
+
+#[test]
+fn test_integer_literal_larger_than_field_modulus() {
+    let modulus = "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+    let got = Lexer::new(modulus).next_token();
+    assert!(matches!(got, Err(LexerErrorKind::IntegerLiteralTooLarge { .. })));
+
+    let max_field = "21888242871839275222246405745257275088548364400416034343698204186575808495616";
+    let got = Lexer::new(max_field).next_token().unwrap();
+    assert_eq!(got, Token::Int(FieldElement::zero() - FieldElement::one()));
+}
+
 #[test]
 fn test_span() {
     let input = "let x = 5";
@@ -1,4 +1,5 @@
 use acvm::FieldElement;
+use iter_extended::vecmap;
 use noirc_errors::{Position, Span, Spanned};
 use std::{fmt, iter::Map, vec::IntoIter};
 
@@ -15,9 +16,12 @@ pub enum Token {
     Int(FieldElement),
     Bool(bool),
     Str(String),
+    FmtStr(String),
     Keyword(Keyword),
     IntType(IntType),
     Attribute(Attribute),
+    /// The text of a `///` doc comment, without the slashes
+    DocComment(String),
     /// <
     Less,
     /// <=
@@ -66,6 +70,8 @@ pub enum Token {
     RightBracket,
     /// ->
     Arrow,
+    /// =>
+    FatArrow,
     /// |
     Pipe,
     /// #
@@ -147,8 +153,10 @@ impl fmt::Display for Token {
             Token::Int(n) => write!(f, "{}", n.to_u128()),
             Token::Bool(b) => write!(f, "{b}"),
             Token::Str(ref b) => write!(f, "{b}"),
+            Token::FmtStr(ref b) => write!(f, "f{b}"),
             Token::Keyword(k) => write!(f, "{k}"),
             Token::Attribute(ref a) => write!(f, "{a}"),
+            Token::DocComment(ref comment) => write!(f, "/// {comment}"),
             Token::IntType(ref i) => write!(f, "{i}"),
             Token::Less => write!(f, "<"),
             Token::LessEqual => write!(f, "<="),
@@ -174,6 +182,7 @@ impl fmt::Display for Token {
             Token::LeftBracket => write!(f, "["),
             Token::RightBracket => write!(f, "]"),
             Token::Arrow => write!(f, "->"),
+            Token::FatArrow => write!(f, "=>"),
             Token::Pipe => write!(f, "|"),
             Token::Pound => write!(f, "#"),
             Token::Comma => write!(f, ","),
@@ -197,6 +206,7 @@ pub enum TokenKind {
     Literal,
     Keyword,
     Attribute,
+    DocComment,
 }
 
 impl fmt::Display for TokenKind {
@@ -207,6 +217,7 @@ impl fmt::Display for TokenKind {
             TokenKind::Literal => write!(f, "literal"),
             TokenKind::Keyword => write!(f, "keyword"),
             TokenKind::Attribute => write!(f, "attribute"),
+            TokenKind::DocComment => write!(f, "doc comment"),
         }
     }
 }
@@ -215,9 +226,10 @@ impl Token {
     pub fn kind(&self) -> TokenKind {
         match *self {
             Token::Ident(_) => TokenKind::Ident,
-            Token::Int(_) | Token::Bool(_) | Token::Str(_) => TokenKind::Literal,
+            Token::Int(_) | Token::Bool(_) | Token::Str(_) | Token::FmtStr(_) => TokenKind::Literal,
             Token::Keyword(_) => TokenKind::Keyword,
             Token::Attribute(_) => TokenKind::Attribute,
+            Token::DocComment(_) => TokenKind::DocComment,
             ref tok => TokenKind::Token(tok.clone()),
         }
     }
@@ -326,6 +338,36 @@ pub enum Attribute {
     Builtin(String),
     Oracle(String),
     Test,
+    Inline(InlineType),
+    /// The traits to generate an impl of for a struct, as in `#[derive(Eq, Default)]`
+    Derive(Vec<String>),
+    /// Marks a function or struct as deprecated, with an optional message shown at each use,
+    /// as in `#[deprecated("use bar instead")]`
+    Deprecated(Option<String>),
+    /// Only compiles an item if the given feature of its crate is enabled, as in
+    /// `#[cfg(feature = "poseidon")]`
+    Cfg(String),
+    /// Suppresses the warnings issued for an unused function or import, or for the unused
+    /// variables within a function, as in `#[allow(unused)]`
+    AllowUnused,
+}
+
+/// Whether calls to a function should be inlined, as requested by an `#[inline(..)]` attribute.
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone, PartialOrd, Ord)]
+pub enum InlineType {
+    /// Calls to the function are always inlined, unless it is recursive
+    Always,
+    /// Calls to the function are never inlined, unless required by the runtime
+    Never,
+}
+
+impl fmt::Display for InlineType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InlineType::Always => write!(f, "always"),
+            InlineType::Never => write!(f, "never"),
+        }
+    }
 }
 
 impl fmt::Display for Attribute {
@@ -335,16 +377,37 @@ impl fmt::Display for Attribute {
             Attribute::Builtin(ref k) => write!(f, "#[builtin({k})]"),
             Attribute::Oracle(ref k) => write!(f, "#[oracle({k})]"),
             Attribute::Test => write!(f, "#[test]"),
+            Attribute::Inline(inline_type) => write!(f, "#[inline({inline_type})]"),
+            Attribute::Derive(ref traits) => write!(f, "#[derive({})]", traits.join(", ")),
+            Attribute::Deprecated(None) => write!(f, "#[deprecated]"),
+            Attribute::Deprecated(Some(ref note)) => write!(f, "#[deprecated(\"{note}\")]"),
+            Attribute::Cfg(ref feature) => write!(f, "#[cfg(feature = \"{feature}\")]"),
+            Attribute::AllowUnused => write!(f, "#[allow(unused)]"),
         }
     }
 }
 
 impl Attribute {
+    /// The name each attribute is written with, before any arguments in parentheses
+    pub const NAMES: [&str; 9] =
+        ["allow", "builtin", "cfg", "deprecated", "derive", "foreign", "inline", "oracle", "test"];
+
     /// If the string is a fixed attribute return that, else
     /// return the custom attribute
     pub(crate) fn lookup_attribute(word: &str, span: Span) -> Result<Token, LexerErrorKind> {
+        // The message of a deprecation may contain parentheses, so it cannot be split below
+        if let Some(arguments) = word.strip_prefix("deprecated") {
+            return Attribute::lookup_deprecated(arguments.trim())
+                .map(Token::Attribute)
+                .ok_or_else(|| LexerErrorKind::MalformedFuncAttribute {
+                    span,
+                    found: word.to_owned(),
+                });
+        }
+
         let word_segments: Vec<&str> = word
             .split(|c| c == '(' || c == ')')
+            .map(str::trim)
             .filter(|string_segment| !string_segment.is_empty())
             .collect();
 
@@ -366,6 +429,44 @@ impl Attribute {
             "foreign" => Token::Attribute(Attribute::Foreign(attribute_name.to_string())),
             "builtin" => Token::Attribute(Attribute::Builtin(attribute_name.to_string())),
             "oracle" => Token::Attribute(Attribute::Oracle(attribute_name.to_string())),
+            "derive" => {
+                let traits = vecmap(attribute_name.split(','), |name| name.trim().to_string());
+                if traits.iter().any(|name| name.is_empty() || name.contains(' ')) {
+                    return Err(LexerErrorKind::MalformedFuncAttribute {
+                        span,
+                        found: word.to_owned(),
+                    });
+                }
+                Token::Attribute(Attribute::Derive(traits))
+            }
+            "cfg" => match Attribute::lookup_cfg_feature(attribute_name) {
+                Some(feature) => Token::Attribute(Attribute::Cfg(feature.to_owned())),
+                None => {
+                    return Err(LexerErrorKind::MalformedFuncAttribute {
+                        span,
+                        found: word.to_owned(),
+                    })
+                }
+            },
+            "allow" => match attribute_name {
+                "unused" => Token::Attribute(Attribute::AllowUnused),
+                _ => {
+                    return Err(LexerErrorKind::MalformedFuncAttribute {
+                        span,
+                        found: word.to_owned(),
+                    })
+                }
+            },
+            "inline" => match attribute_name {
+                "always" => Token::Attribute(Attribute::Inline(InlineType::Always)),
+                "never" => Token::Attribute(Attribute::Inline(InlineType::Never)),
+                _ => {
+                    return Err(LexerErrorKind::MalformedFuncAttribute {
+                        span,
+                        found: word.to_owned(),
+                    })
+                }
+            },
             _ => {
                 return Err(LexerErrorKind::MalformedFuncAttribute { span, found: word.to_owned() })
             }
@@ -373,6 +474,26 @@ impl Attribute {
         Ok(tok)
     }
 
+    /// Parses the arguments of a `#[deprecated]` attribute, which are either empty or a
+    /// parenthesized string literal.
+    fn lookup_deprecated(arguments: &str) -> Option<Attribute> {
+        if arguments.is_empty() {
+            return Some(Attribute::Deprecated(None));
+        }
+
+        let note = arguments.strip_prefix('(')?.strip_suffix(')')?.trim();
+        let note = note.strip_prefix('"')?.strip_suffix('"')?;
+        Some(Attribute::Deprecated(Some(note.to_owned())))
+    }
+
+    /// Parses the condition of a `#[cfg(..)]` attribute, which must be of the form
+    /// `feature = "name"`, returning the name of the feature.
+    fn lookup_cfg_feature(condition: &str) -> Option<&str> {
+        let name = condition.strip_prefix("feature")?.trim_start().strip_prefix('=')?.trim_start();
+        let name = name.strip_prefix('"')?.strip_suffix('"')?;
+        (!name.is_empty()).then_some(name)
+    }
+
     pub fn builtin(self) -> Option<String> {
         match self {
             Attribute::Builtin(name) => Some(name),
@@ -394,6 +515,13 @@ impl Attribute {
     pub fn is_low_level(&self) -> bool {
         matches!(self, Attribute::Foreign(_) | Attribute::Builtin(_))
     }
+
+    pub fn inline_type(&self) -> Option<InlineType> {
+        match self {
+            Attribute::Inline(inline_type) => Some(*inline_type),
+            _ => None,
+        }
+    }
 }
 
 impl AsRef<str> for Attribute {
@@ -402,7 +530,12 @@ impl AsRef<str> for Attribute {
             Attribute::Foreign(string) => string,
             Attribute::Builtin(string) => string,
             Attribute::Oracle(string) => string,
-            Attribute::Test => "",
+            Attribute::Test
+            | Attribute::Inline(_)
+            | Attribute::Derive(_)
+            | Attribute::Deprecated(_)
+            | Attribute::Cfg(_)
+            | Attribute::AllowUnused => "",
         }
     }
 }
@@ -424,6 +557,7 @@ pub enum Keyword {
     Dep,
     Distinct,
     Else,
+    Enum,
     Field,
     Fn,
     For,
@@ -432,6 +566,8 @@ pub enum Keyword {
     If,
     In,
     Let,
+    Loop,
+    Match,
     Mod,
     Mut,
     Open,
@@ -439,9 +575,12 @@ pub enum Keyword {
     String,
     Return,
     Struct,
+    Trait,
+    Type,
     Unconstrained,
     Use,
     Vec,
+    Where,
     While,
 }
 
@@ -459,6 +598,7 @@ impl fmt::Display for Keyword {
             Keyword::Dep => write!(f, "dep"),
             Keyword::Distinct => write!(f, "distinct"),
             Keyword::Else => write!(f, "else"),
+            Keyword::Enum => write!(f, "enum"),
             Keyword::Field => write!(f, "Field"),
             Keyword::Fn => write!(f, "fn"),
             Keyword::For => write!(f, "for"),
@@ -467,6 +607,8 @@ impl fmt::Display for Keyword {
             Keyword::If => write!(f, "if"),
             Keyword::In => write!(f, "in"),
             Keyword::Let => write!(f, "let"),
+            Keyword::Loop => write!(f, "loop"),
+            Keyword::Match => write!(f, "match"),
             Keyword::Mod => write!(f, "mod"),
             Keyword::Mut => write!(f, "mut"),
             Keyword::Open => write!(f, "open"),
@@ -474,9 +616,12 @@ impl fmt::Display for Keyword {
             Keyword::String => write!(f, "str"),
             Keyword::Return => write!(f, "return"),
             Keyword::Struct => write!(f, "struct"),
+            Keyword::Trait => write!(f, "trait"),
+            Keyword::Type => write!(f, "type"),
             Keyword::Unconstrained => write!(f, "unconstrained"),
             Keyword::Use => write!(f, "use"),
             Keyword::Vec => write!(f, "Vec"),
+            Keyword::Where => write!(f, "where"),
             Keyword::While => write!(f, "while"),
         }
     }
@@ -497,6 +642,7 @@ impl Keyword {
             "dep" => Keyword::Dep,
             "distinct" => Keyword::Distinct,
             "else" => Keyword::Else,
+            "enum" => Keyword::Enum,
             "Field" => Keyword::Field,
             "fn" => Keyword::Fn,
             "for" => Keyword::For,
@@ -505,6 +651,8 @@ impl Keyword {
             "if" => Keyword::If,
             "in" => Keyword::In,
             "let" => Keyword::Let,
+            "loop" => Keyword::Loop,
+            "match" => Keyword::Match,
             "mod" => Keyword::Mod,
             "mut" => Keyword::Mut,
             "open" => Keyword::Open,
@@ -512,9 +660,12 @@ impl Keyword {
             "str" => Keyword::String,
             "return" => Keyword::Return,
             "struct" => Keyword::Struct,
+            "trait" => Keyword::Trait,
+            "type" => Keyword::Type,
             "unconstrained" => Keyword::Unconstrained,
             "use" => Keyword::Use,
             "Vec" => Keyword::Vec,
+            "where" => Keyword::Where,
             "while" => Keyword::While,
 
             "true" => return Some(Token::Bool(true)),
@@ -3,7 +3,7 @@ use iter_extended::vecmap;
 use noirc_abi::FunctionSignature;
 use noirc_errors::Location;
 
-use crate::{BinaryOpKind, Signedness};
+use crate::{token::InlineType, BinaryOpKind, OverflowMode, Signedness};
 
 /// The monomorphized AST is expression-based, all statements are also
 /// folded into this expression enum. Compared to the HIR, the monomorphized
@@ -25,13 +25,16 @@ pub enum Expression {
     Index(Index),
     Cast(Cast),
     For(For),
+    While(While),
     If(If),
     Tuple(Vec<Expression>),
     ExtractTupleField(Box<Expression>, usize),
     Call(Call),
 
     Let(Let),
-    Constrain(Box<Expression>, Location),
+    /// A constraint along with a call reporting its message, which should only
+    /// be evaluated if the constraint fails.
+    Constrain(Box<Expression>, Location, Option<Box<Expression>>),
     Assign(Assign),
     Semi(Box<Expression>),
 }
@@ -75,6 +78,20 @@ pub struct For {
     pub start_range: Box<Expression>,
     pub end_range: Box<Expression>,
     pub block: Box<Expression>,
+
+    /// The location of the `start .. end` range of the loop
+    pub range_location: Location,
+}
+
+/// A loop which is only allowed within unconstrained functions since,
+/// unlike `For`, it cannot be unrolled.
+#[derive(Debug, Clone)]
+pub struct While {
+    pub condition: Box<Expression>,
+    pub block: Box<Expression>,
+
+    /// The location of the loop's condition
+    pub location: Location,
 }
 
 #[derive(Debug, Clone)]
@@ -100,6 +117,9 @@ pub struct Binary {
     pub operator: BinaryOp,
     pub rhs: Box<Expression>,
     pub location: Location,
+    /// How the operation behaves on overflow. This is only ever not `Wrapping` for the
+    /// addition, subtraction and multiplication of unsigned integers.
+    pub overflow: OverflowMode,
 }
 
 #[derive(Debug, Clone)]
@@ -192,6 +212,8 @@ pub struct Function {
 
     pub return_type: Type,
     pub unconstrained: bool,
+    /// Whether calls to the function should be inlined, if requested in the source
+    pub inline_type: Option<InlineType>,
 }
 
 /// Compared to hir_def::types::Type, this monomorphized Type has:
@@ -323,7 +345,7 @@ impl std::fmt::Display for Type {
                 let args = vecmap(args, ToString::to_string);
                 write!(f, "fn({}) -> {}", args.join(", "), ret)
             }
-            Type::Slice(element) => write!(f, "[{element}"),
+            Type::Slice(element) => write!(f, "[{element}]"),
             Type::MutableReference(element) => write!(f, "&mut {element}"),
         }
     }
@@ -0,0 +1,64 @@
+use noirc_errors::registry::{self, ErrorCode};
+use noirc_errors::{CustomDiagnostic as Diagnostic, FileDiagnostic, Location};
+use thiserror::Error;
+
+use crate::hir::comptime::ComptimeError;
+use crate::OverflowMode;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MonomorphizationError {
+    #[error("Static assertion failed: {message}")]
+    StaticAssertFailed { message: String, location: Location },
+    #[error("Condition of static_assert cannot be evaluated at compile-time")]
+    StaticAssertNotConstant { error: ComptimeError, location: Location },
+    #[error("{overflow} arithmetic is not supported on values of type {typ}")]
+    UnsupportedOverflowOperand { overflow: OverflowMode, typ: String, location: Location },
+}
+
+impl MonomorphizationError {
+    pub fn location(&self) -> Location {
+        match self {
+            MonomorphizationError::StaticAssertFailed { location, .. }
+            | MonomorphizationError::StaticAssertNotConstant { location, .. }
+            | MonomorphizationError::UnsupportedOverflowOperand { location, .. } => *location,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            MonomorphizationError::StaticAssertFailed { .. } => registry::E0090,
+            MonomorphizationError::StaticAssertNotConstant { .. } => registry::E0091,
+            MonomorphizationError::UnsupportedOverflowOperand { .. } => registry::E0094,
+        }
+    }
+}
+
+impl From<MonomorphizationError> for FileDiagnostic {
+    fn from(error: MonomorphizationError) -> FileDiagnostic {
+        let location = error.location();
+        let code = error.code();
+        let diagnostic = match error {
+            MonomorphizationError::StaticAssertFailed { .. } => {
+                Diagnostic::simple_error(error.to_string(), String::new(), location.span)
+            }
+            MonomorphizationError::StaticAssertNotConstant { error: cause, .. } => {
+                let mut diagnostic = Diagnostic::simple_error(
+                    error.to_string(),
+                    "The condition must only use literals, globals and numeric generics".into(),
+                    location.span,
+                );
+                let cause_location = cause.location();
+                if cause_location.file == location.file && cause_location.span != location.span {
+                    diagnostic.add_secondary(cause.to_string(), cause_location.span);
+                }
+                diagnostic
+            }
+            MonomorphizationError::UnsupportedOverflowOperand { .. } => Diagnostic::simple_error(
+                error.to_string(),
+                "Only integers are supported".into(),
+                location.span,
+            ),
+        };
+        diagnostic.with_code(code).in_file(location.file)
+    }
+}
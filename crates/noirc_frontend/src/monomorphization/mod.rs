@@ -15,19 +15,26 @@ use noirc_errors::Location;
 use std::collections::{BTreeMap, HashMap, VecDeque};
 
 use crate::{
+    hir::comptime::{Interpreter, Value},
     hir_def::{
         expr::*,
         function::{FuncMeta, Param, Parameters},
-        stmt::{HirAssignStatement, HirLValue, HirLetStatement, HirPattern, HirStatement},
+        stmt::{
+            HirAssignStatement, HirConstrainKind, HirConstrainStatement, HirLValue,
+            HirLetStatement, HirPattern, HirStatement,
+        },
+        traits::TraitMethodId,
     },
     node_interner::{self, DefinitionKind, NodeInterner, StmtId},
     token::Attribute,
-    CompTime, FunctionKind, Type, TypeBinding, TypeBindings,
+    BinaryOpKind, CompTime, FunctionKind, OverflowMode, Type, TypeBinding, TypeBindings,
 };
 
 use self::ast::{Definition, FuncId, Function, LocalId, Program};
+use self::errors::MonomorphizationError;
 
 pub mod ast;
+pub mod errors;
 pub mod printer;
 
 /// The context struct for the monomorphization pass.
@@ -58,10 +65,42 @@ struct Monomorphizer<'interner> {
     /// Used to reference existing definitions in the HIR
     interner: &'interner NodeInterner,
 
+    /// The functions called in place of the low level functions with the given opcode names
+    fallbacks: HashMap<String, node_interner::FuncId>,
+
+    /// Tracks the early returns of the function currently being monomorphized
+    returns: ReturnContext,
+
+    /// The first error found, such as a failing `static_assert`. Monomorphization continues
+    /// past it since it only affects the call it was found in.
+    error: Option<MonomorphizationError>,
+
     next_local_id: u32,
     next_function_id: u32,
 }
 
+/// Early `return` statements are desugared into assignments to two mutable variables of the
+/// enclosing function: a flag recording whether it has returned, and the value it returned.
+/// Any code which may run after a `return` is then guarded by a check of this flag, and the
+/// function finally evaluates to the returned value if the flag was set.
+struct ReturnContext {
+    return_type: ast::Type,
+
+    /// The ids of the flag and of the returned value, created once the first `return` of the
+    /// function is found. There is no returned value if the function returns the unit type.
+    variables: Option<(LocalId, Option<LocalId>)>,
+
+    /// The number of `return` statements found so far, used to tell whether a statement
+    /// contains any.
+    count: usize,
+}
+
+impl ReturnContext {
+    fn new(return_type: ast::Type) -> Self {
+        Self { return_type, variables: None, count: 0 }
+    }
+}
+
 type HirType = crate::Type;
 
 /// Starting from the given `main` function, monomorphize the entire program,
@@ -75,8 +114,26 @@ type HirType = crate::Type;
 /// Note that there is no requirement on the `main` function that can be passed into
 /// this function. Typically, this is the function named "main" in the source project,
 /// but it can also be, for example, an arbitrary test function for running `nargo test`.
-pub fn monomorphize(main: node_interner::FuncId, interner: &NodeInterner) -> Program {
-    let mut monomorphizer = Monomorphizer::new(interner);
+pub fn monomorphize(
+    main: node_interner::FuncId,
+    interner: &NodeInterner,
+) -> Result<Program, MonomorphizationError> {
+    monomorphize_with_fallbacks(main, interner, HashMap::new())
+}
+
+/// Monomorphize the program starting from the given `main` function, like `monomorphize`,
+/// calling the function of `fallbacks` keyed by the opcode name of each low level function
+/// in its place. Each such function must have the same signature as the low level function
+/// it replaces.
+///
+/// This lets a program use a constrained implementation of the black box functions which
+/// are not supported by the backend.
+pub fn monomorphize_with_fallbacks(
+    main: node_interner::FuncId,
+    interner: &NodeInterner,
+    fallbacks: HashMap<String, node_interner::FuncId>,
+) -> Result<Program, MonomorphizationError> {
+    let mut monomorphizer = Monomorphizer::new(interner, fallbacks);
     let function_sig = monomorphizer.compile_main(main);
 
     while !monomorphizer.queue.is_empty() {
@@ -88,21 +145,31 @@ pub fn monomorphize(main: node_interner::FuncId, interner: &NodeInterner) -> Pro
         undo_instantiation_bindings(bindings);
     }
 
+    if let Some(error) = monomorphizer.error {
+        return Err(error);
+    }
+
     let functions = vecmap(monomorphizer.finished_functions, |(_, f)| f);
     let FuncMeta { return_distinctness, .. } = interner.function_meta(&main);
-    Program::new(functions, function_sig, return_distinctness)
+    Ok(Program::new(functions, function_sig, return_distinctness))
 }
 
 impl<'interner> Monomorphizer<'interner> {
-    fn new(interner: &'interner NodeInterner) -> Self {
+    fn new(
+        interner: &'interner NodeInterner,
+        fallbacks: HashMap<String, node_interner::FuncId>,
+    ) -> Self {
         Monomorphizer {
             globals: HashMap::new(),
             locals: HashMap::new(),
             queue: VecDeque::new(),
             finished_functions: BTreeMap::new(),
+            returns: ReturnContext::new(ast::Type::Unit),
+            error: None,
             next_local_id: 0,
             next_function_id: 0,
             interner,
+            fallbacks,
         }
     }
 
@@ -140,7 +207,13 @@ impl<'interner> Monomorphizer<'interner> {
                         let opcode = attribute.foreign().expect(
                             "ice: function marked as foreign, but attribute kind does not match this",
                         );
-                        Definition::LowLevel(opcode)
+                        match self.fallbacks.get(&opcode) {
+                            Some(fallback) => {
+                                let id = self.queue_function_as(id, *fallback, typ);
+                                Definition::Function(id)
+                            }
+                            None => Definition::LowLevel(opcode),
+                        }
                     }
                     FunctionKind::Builtin => {
                         let attribute = meta.attributes.expect("all low level functions must contain an attribute which contains the opcode which it links to");
@@ -190,10 +263,12 @@ impl<'interner> Monomorphizer<'interner> {
 
         let return_type = Self::convert_type(meta.return_type());
         let parameters = self.parameters(meta.parameters);
-        let body = self.expr(*self.interner.function(&f).as_expr());
+        let body = self.function_body(*self.interner.function(&f).as_expr(), &return_type);
         let unconstrained = meta.is_unconstrained;
+        let inline_type = meta.attributes.and_then(|attribute| attribute.inline_type());
 
-        let function = ast::Function { id, name, parameters, body, return_type, unconstrained };
+        let function =
+            ast::Function { id, name, parameters, body, return_type, unconstrained, inline_type };
         self.push_function(id, function);
     }
 
@@ -260,6 +335,10 @@ impl<'interner> Monomorphizer<'interner> {
         match self.interner.expression(&expr) {
             HirExpression::Ident(ident) => self.ident(ident, expr),
             HirExpression::Literal(HirLiteral::Str(contents)) => Literal(Str(contents)),
+            HirExpression::Literal(HirLiteral::FmtStr(contents, fields)) => {
+                let fields = vecmap(fields, |field| self.expr(field));
+                ast::Expression::Tuple(vec![Literal(Str(contents)), ast::Expression::Tuple(fields)])
+            }
             HirExpression::Literal(HirLiteral::Bool(value)) => Literal(Bool(value)),
             HirExpression::Literal(HirLiteral::Integer(value)) => {
                 let typ = Self::convert_type(&self.interner.id_type(expr));
@@ -271,7 +350,10 @@ impl<'interner> Monomorphizer<'interner> {
                     self.repeated_array(repeated_element, length)
                 }
             },
-            HirExpression::Block(block) => self.block(block.0),
+            HirExpression::Block(block) => {
+                let typ = Self::convert_type(&self.interner.id_type(expr));
+                self.block(&block.0, &typ)
+            }
 
             HirExpression::Prefix(prefix) => ast::Expression::Unary(ast::Unary {
                 operator: prefix.operator,
@@ -284,7 +366,10 @@ impl<'interner> Monomorphizer<'interner> {
                 let rhs = Box::new(self.expr(infix.rhs));
                 let operator = infix.operator.kind;
                 let location = self.interner.expr_location(&expr);
-                ast::Expression::Binary(ast::Binary { lhs, rhs, operator, location })
+                let operand_type = self.interner.id_type(infix.lhs);
+                let overflow =
+                    Self::overflow_mode(infix.operator.overflow, operator, &operand_type);
+                ast::Expression::Binary(ast::Binary { lhs, rhs, operator, location, overflow })
             }
 
             HirExpression::Index(index) => self.index(expr, index),
@@ -308,7 +393,20 @@ impl<'interner> Monomorphizer<'interner> {
                 let index_variable = self.next_local_id();
                 self.define_local(for_expr.identifier.id, index_variable);
 
-                let block = Box::new(self.expr(for_expr.block));
+                let returns = self.returns.count;
+                let mut block = self.expr(for_expr.block);
+                if self.returns.count > returns {
+                    // Skip the remaining iterations once the body has returned
+                    block = self.if_not_returned(block, &ast::Type::Unit);
+                }
+                let block = Box::new(block);
+
+                let start_location = self.interner.expr_location(&for_expr.start_range);
+                let end_location = self.interner.expr_location(&for_expr.end_range);
+                let range_location = Location::new(
+                    start_location.span.merge(end_location.span),
+                    start_location.file,
+                );
 
                 ast::Expression::For(ast::For {
                     index_variable,
@@ -317,6 +415,25 @@ impl<'interner> Monomorphizer<'interner> {
                     start_range: Box::new(start),
                     end_range: Box::new(end),
                     block,
+                    range_location,
+                })
+            }
+
+            HirExpression::While(while_expr) => {
+                let mut condition = self.expr(while_expr.condition);
+                let location = self.interner.expr_location(&while_expr.condition);
+
+                let returns = self.returns.count;
+                let block = Box::new(self.expr(while_expr.block));
+                if self.returns.count > returns {
+                    // Stop looping once the body has returned
+                    condition = self.if_not_returned(condition, &ast::Type::Bool);
+                }
+
+                ast::Expression::While(ast::While {
+                    condition: Box::new(condition),
+                    block,
+                    location,
                 })
             }
 
@@ -337,8 +454,11 @@ impl<'interner> Monomorphizer<'interner> {
                 ast::Expression::Tuple(fields)
             }
             HirExpression::Constructor(constructor) => self.constructor(constructor, expr),
+            HirExpression::EnumConstructor(constructor) => self.enum_constructor(constructor, expr),
+            HirExpression::Match(match_expr) => self.match_expr(match_expr, expr),
 
             HirExpression::Lambda(lambda) => self.lambda(lambda),
+            HirExpression::TraitMethodReference(method) => self.trait_method(method, expr),
 
             HirExpression::MethodCall(_) => {
                 unreachable!("Encountered HirExpression::MethodCall during monomorphization")
@@ -456,21 +576,297 @@ impl<'interner> Monomorphizer<'interner> {
         }
     }
 
-    fn statement(&mut self, id: StmtId) -> ast::Expression {
+    /// Monomorphizes a statement of a block whose value has the given type
+    fn statement(&mut self, id: StmtId, block_type: &ast::Type) -> ast::Expression {
         match self.interner.statement(&id) {
             HirStatement::Let(let_statement) => self.let_statement(let_statement),
-            HirStatement::Constrain(constrain) => {
-                let expr = self.expr(constrain.0);
-                let location = self.interner.expr_location(&constrain.0);
-                ast::Expression::Constrain(Box::new(expr), location)
-            }
+            HirStatement::Constrain(constrain) => self.constrain(constrain),
             HirStatement::Assign(assign) => self.assign(assign),
             HirStatement::Expression(expr) => self.expr(expr),
             HirStatement::Semi(expr) => ast::Expression::Semi(Box::new(self.expr(expr))),
+            HirStatement::Return(expr) => self.return_statement(expr, block_type),
             HirStatement::Error => unreachable!(),
         }
     }
 
+    /// Desugars `return expr` into assignments to the return variables of the current
+    /// function. Since it ends its block, the block then evaluates to a zeroed value.
+    fn return_statement(
+        &mut self,
+        expr: node_interner::ExprId,
+        block_type: &ast::Type,
+    ) -> ast::Expression {
+        let value = Box::new(self.expr(expr));
+        let (returned, return_value) = self.return_variables();
+        self.returns.count += 1;
+
+        let mut statements = Vec::with_capacity(3);
+        match return_value {
+            Some(return_value) => {
+                let lvalue = ast::LValue::Ident(return_value);
+                statements.push(ast::Expression::Assign(ast::Assign { lvalue, expression: value }));
+            }
+            // Still evaluate the returned expression for its side effects
+            None => statements.push(ast::Expression::Semi(value)),
+        }
+
+        let lvalue = ast::LValue::Ident(returned);
+        let expression = Box::new(ast::Expression::Literal(ast::Literal::Bool(true)));
+        statements.push(ast::Expression::Assign(ast::Assign { lvalue, expression }));
+
+        if *block_type != ast::Type::Unit {
+            statements.push(self.zeroed_value_of_type(block_type));
+        }
+        ast::Expression::Block(statements)
+    }
+
+    /// Returns the identifiers of the flag and value returned by the current function,
+    /// creating them if this is its first `return`.
+    fn return_variables(&mut self) -> (ast::Ident, Option<ast::Ident>) {
+        let (returned, return_value) = match self.returns.variables {
+            Some(variables) => variables,
+            None => {
+                let returned = self.next_local_id();
+                let return_value =
+                    (self.returns.return_type != ast::Type::Unit).then(|| self.next_local_id());
+                self.returns.variables = Some((returned, return_value));
+                (returned, return_value)
+            }
+        };
+
+        let return_type = self.returns.return_type.clone();
+        (
+            Self::return_variable(returned, "returned", ast::Type::Bool),
+            return_value.map(|id| Self::return_variable(id, "return_value", return_type)),
+        )
+    }
+
+    fn return_variable(id: LocalId, name: &str, typ: ast::Type) -> ast::Ident {
+        let definition = Definition::Local(id);
+        ast::Ident { location: None, mutable: true, definition, name: name.to_owned(), typ }
+    }
+
+    /// Guards an expression of the given type by a check that the current function has
+    /// not returned yet, evaluating to a zeroed value otherwise.
+    fn if_not_returned(&mut self, expr: ast::Expression, typ: &ast::Type) -> ast::Expression {
+        let (returned, _) = self.return_variables();
+        let condition = ast::Expression::Unary(ast::Unary {
+            operator: crate::UnaryOp::Not,
+            rhs: Box::new(ast::Expression::Ident(returned)),
+            result_type: ast::Type::Bool,
+        });
+
+        let alternative =
+            (*typ != ast::Type::Unit).then(|| Box::new(self.zeroed_value_of_type(typ)));
+
+        ast::Expression::If(ast::If {
+            condition: Box::new(condition),
+            consequence: Box::new(expr),
+            alternative,
+            typ: typ.clone(),
+        })
+    }
+
+    /// Monomorphizes the body of a function or lambda with the given return type. If it
+    /// contains any `return` statements, the body is wrapped to define the variables they
+    /// assign to, and to evaluate to the returned value if one was returned.
+    fn function_body(
+        &mut self,
+        body: node_interner::ExprId,
+        return_type: &ast::Type,
+    ) -> ast::Expression {
+        let outer_returns =
+            std::mem::replace(&mut self.returns, ReturnContext::new(return_type.clone()));
+        let body = self.expr(body);
+        let returns = std::mem::replace(&mut self.returns, outer_returns);
+
+        let (returned, return_value) = match returns.variables {
+            Some(variables) => variables,
+            None => return body,
+        };
+
+        let define = |id, name: &str, value| {
+            let expression = Box::new(value);
+            ast::Expression::Let(ast::Let { id, mutable: true, name: name.to_owned(), expression })
+        };
+
+        let not_returned = ast::Expression::Literal(ast::Literal::Bool(false));
+        let mut statements = vec![define(returned, "returned", not_returned)];
+
+        match return_value {
+            Some(return_value) => {
+                let zeroed = self.zeroed_value_of_type(return_type);
+                statements.push(define(return_value, "return_value", zeroed));
+
+                let result = self.next_local_id();
+                statements.push(ast::Expression::Let(ast::Let {
+                    id: result,
+                    mutable: false,
+                    name: "result".to_owned(),
+                    expression: Box::new(body),
+                }));
+
+                let returned = Self::return_variable(returned, "returned", ast::Type::Bool);
+                let return_value =
+                    Self::return_variable(return_value, "return_value", return_type.clone());
+                let result = ast::Ident {
+                    location: None,
+                    mutable: false,
+                    definition: Definition::Local(result),
+                    name: "result".to_owned(),
+                    typ: return_type.clone(),
+                };
+
+                statements.push(ast::Expression::If(ast::If {
+                    condition: Box::new(ast::Expression::Ident(returned)),
+                    consequence: Box::new(ast::Expression::Ident(return_value)),
+                    alternative: Some(Box::new(ast::Expression::Ident(result))),
+                    typ: return_type.clone(),
+                }));
+            }
+            None => statements.push(body),
+        }
+
+        ast::Expression::Block(statements)
+    }
+
+    fn constrain(&mut self, constrain: HirConstrainStatement) -> ast::Expression {
+        let HirConstrainStatement(expr_id, _, message, kind) = constrain;
+        let location = self.interner.expr_location(&expr_id);
+
+        if let HirConstrainKind::AssertEq { lhs, rhs } = kind {
+            if let Some(assert_eq) = self.assert_eq(expr_id, lhs, rhs, message, location) {
+                return assert_eq;
+            }
+        }
+
+        let expr = self.expr(expr_id);
+        let message = message.map(|message| Box::new(self.constrain_message(message)));
+        ast::Expression::Constrain(Box::new(expr), location, message)
+    }
+
+    fn constrain_message(&mut self, message: node_interner::ExprId) -> ast::Expression {
+        let typ = self.interner.id_type(message);
+        let location = self.interner.expr_location(&message);
+        let message = self.expr(message);
+        Self::assert_message_call(message, &typ, location)
+    }
+
+    /// Each side of an `assert_eq` is evaluated once, before the constraint, so that both
+    /// values can be reported after any message if they differ. Returns None if the condition
+    /// no longer compares the two sides.
+    fn assert_eq(
+        &mut self,
+        condition: node_interner::ExprId,
+        lhs: node_interner::ExprId,
+        rhs: node_interner::ExprId,
+        message: Option<node_interner::ExprId>,
+        location: Location,
+    ) -> Option<ast::Expression> {
+        // Type checking rewrites `==` into a call to `eq` for types implementing `Eq`
+        let eq_call = match self.interner.expression(&condition) {
+            HirExpression::Infix(_) => None,
+            HirExpression::Call(call) if call.arguments == [lhs, rhs] => Some(call),
+            _ => return None,
+        };
+
+        let lhs_type = self.interner.id_type(lhs);
+        let rhs_type = self.interner.id_type(rhs);
+
+        let lhs_value = self.expr(lhs);
+        let (let_lhs, lhs) = self.bind_local("lhs", lhs_value, &lhs_type);
+        let rhs_value = self.expr(rhs);
+        let (let_rhs, rhs) = self.bind_local("rhs", rhs_value, &rhs_type);
+
+        let condition = match eq_call {
+            Some(call) => ast::Expression::Call(ast::Call {
+                func: Box::new(self.expr(call.func)),
+                arguments: vec![lhs.clone(), rhs.clone()],
+                return_type: ast::Type::Bool,
+                location: call.location,
+            }),
+            None => ast::Expression::Binary(ast::Binary {
+                lhs: Box::new(lhs.clone()),
+                rhs: Box::new(rhs.clone()),
+                operator: BinaryOpKind::Equal,
+                location,
+                overflow: OverflowMode::Wrapping,
+            }),
+        };
+
+        // The values are appended to the message, either as the fields of a format string
+        // literal or following a string
+        let (mut template, mut fields, mut field_types) = (String::new(), Vec::new(), Vec::new());
+        if let Some(message) = message {
+            let message_type = self.interner.id_type(message);
+            match (self.interner.expression(&message), message_type.follow_bindings()) {
+                (HirExpression::Literal(HirLiteral::FmtStr(contents, message_fields)), _) => {
+                    template = format!("{contents}: ");
+                    field_types = vecmap(&message_fields, |field| self.interner.id_type(*field));
+                    fields = vecmap(message_fields, |field| self.expr(field));
+                }
+                (_, HirType::String(_)) => {
+                    template = "{message}: ".to_owned();
+                    fields.push(self.expr(message));
+                    field_types.push(message_type);
+                }
+                // A format string which is not a literal cannot be extended with more fields
+                _ => {
+                    let message = self.constrain_message(message);
+                    let constrain = ast::Expression::Constrain(
+                        Box::new(condition),
+                        location,
+                        Some(Box::new(message)),
+                    );
+                    return Some(ast::Expression::Block(vec![let_lhs, let_rhs, constrain]));
+                }
+            }
+        }
+
+        template.push_str("{lhs} != {rhs}");
+        fields.extend([lhs, rhs]);
+        field_types.extend([lhs_type, rhs_type]);
+
+        let message_type = HirType::FmtString(
+            Box::new(HirType::Constant(template.len() as u64)),
+            Box::new(HirType::Tuple(field_types)),
+        );
+        let message = ast::Expression::Tuple(vec![
+            ast::Expression::Literal(ast::Literal::Str(template)),
+            ast::Expression::Tuple(fields),
+        ]);
+        let message = Self::assert_message_call(message, &message_type, location);
+
+        let constrain =
+            ast::Expression::Constrain(Box::new(condition), location, Some(Box::new(message)));
+        Some(ast::Expression::Block(vec![let_lhs, let_rhs, constrain]))
+    }
+
+    /// Binds the given value to a new local, returning the let statement along with
+    /// an identifier referring to it.
+    fn bind_local(
+        &mut self,
+        name: &str,
+        value: ast::Expression,
+        typ: &HirType,
+    ) -> (ast::Expression, ast::Expression) {
+        let id = self.next_local_id();
+        let let_statement = ast::Expression::Let(ast::Let {
+            id,
+            mutable: false,
+            name: name.to_owned(),
+            expression: Box::new(value),
+        });
+        let ident = ast::Expression::Ident(ast::Ident {
+            location: None,
+            mutable: false,
+            definition: Definition::Local(id),
+            name: name.to_owned(),
+            typ: Self::convert_type(typ),
+        });
+        (let_statement, ident)
+    }
+
     fn let_statement(&mut self, let_statement: HirLetStatement) -> ast::Expression {
         let expr = self.expr(let_statement.expression);
         let expected_type = self.interner.id_type(let_statement.expression);
@@ -526,8 +922,151 @@ impl<'interner> Monomorphizer<'interner> {
         ast::Expression::Block(new_exprs)
     }
 
-    fn block(&mut self, statement_ids: Vec<StmtId>) -> ast::Expression {
-        ast::Expression::Block(vecmap(statement_ids, |id| self.statement(id)))
+    /// Enums are represented as a tuple of a Field tag holding the index of the active variant,
+    /// followed by a tuple of the fields of each variant in order. The fields of every variant
+    /// other than the active one are zeroed.
+    fn enum_constructor(
+        &mut self,
+        constructor: HirEnumConstructorExpression,
+        id: node_interner::ExprId,
+    ) -> ast::Expression {
+        let variant_types = match Self::convert_type(&self.interner.id_type(id)) {
+            ast::Type::Tuple(fields) => fields,
+            other => unreachable!("Expected enum to be represented as a tuple, found {other}"),
+        };
+
+        let tag = FieldElement::from(constructor.variant_index as u128);
+        let mut fields =
+            vec![ast::Expression::Literal(ast::Literal::Integer(tag, ast::Type::Field))];
+        let mut arguments = Some(vecmap(constructor.arguments, |arg| self.expr(arg)));
+
+        for (index, variant_type) in variant_types.iter().skip(1).enumerate() {
+            if index == constructor.variant_index {
+                fields.push(ast::Expression::Tuple(arguments.take().unwrap()));
+            } else {
+                fields.push(self.zeroed_value_of_type(variant_type));
+            }
+        }
+
+        ast::Expression::Tuple(fields)
+    }
+
+    /// A match is lowered into a chain of if expressions comparing the tag of the matched
+    /// value against the index of each variant in turn. The final rule is never compared since
+    /// the type checker already ensures every match is exhaustive.
+    fn match_expr(
+        &mut self,
+        match_expr: HirMatchExpression,
+        id: node_interner::ExprId,
+    ) -> ast::Expression {
+        let typ = Self::convert_type(&self.interner.id_type(id));
+        let location = self.interner.expr_location(&id);
+
+        // Bind the matched value to a fresh variable so it is evaluated only once
+        let scrutinee_id = self.next_local_id();
+        let scrutinee = ast::Ident {
+            location: None,
+            mutable: false,
+            definition: Definition::Local(scrutinee_id),
+            name: "_".into(),
+            typ: Self::convert_type(&self.interner.id_type(match_expr.expression)),
+        };
+        let scrutinee_let = ast::Expression::Let(ast::Let {
+            id: scrutinee_id,
+            mutable: false,
+            name: "_".into(),
+            expression: Box::new(self.expr(match_expr.expression)),
+        });
+
+        // Any rules after the first wildcard are unreachable
+        let mut rules = match_expr.rules;
+        if let Some(index) =
+            rules.iter().position(|(pattern, _)| matches!(pattern, HirMatchPattern::Wildcard(_)))
+        {
+            rules.truncate(index + 1);
+        }
+
+        let mut arms =
+            vecmap(rules, |(pattern, rule)| self.match_rule(pattern, rule, &scrutinee, location));
+
+        let mut result = match arms.pop() {
+            Some((_, body)) => body,
+            None => ast::Expression::Block(Vec::new()),
+        };
+
+        while let Some((condition, body)) = arms.pop() {
+            let condition = condition.expect("Only the last rule of a match may be a wildcard");
+            result = ast::Expression::If(ast::If {
+                condition: Box::new(condition),
+                consequence: Box::new(body),
+                alternative: Some(Box::new(result)),
+                typ: typ.clone(),
+            });
+        }
+
+        ast::Expression::Block(vec![scrutinee_let, result])
+    }
+
+    /// Returns the condition under which the given rule of a match is taken, if any,
+    /// along with the body of the rule including the bindings of its pattern.
+    fn match_rule(
+        &mut self,
+        pattern: HirMatchPattern,
+        rule: node_interner::ExprId,
+        scrutinee: &ast::Ident,
+        location: Location,
+    ) -> (Option<ast::Expression>, ast::Expression) {
+        match pattern {
+            HirMatchPattern::Wildcard(_) => (None, self.expr(rule)),
+            HirMatchPattern::Variant(typ, variant_index, patterns, _) => {
+                let scrutinee = ast::Expression::Ident(scrutinee.clone());
+
+                let tag = ast::Expression::ExtractTupleField(Box::new(scrutinee.clone()), 0);
+                let index = FieldElement::from(variant_index as u128);
+                let index =
+                    ast::Expression::Literal(ast::Literal::Integer(index, ast::Type::Field));
+                let condition = ast::Expression::Binary(ast::Binary {
+                    lhs: Box::new(tag),
+                    rhs: Box::new(index),
+                    operator: BinaryOpKind::Equal,
+                    location,
+                    overflow: OverflowMode::Wrapping,
+                });
+
+                let fields = unwrap_enum_variant_type(&typ, variant_index);
+                let variant =
+                    ast::Expression::ExtractTupleField(Box::new(scrutinee), variant_index + 1);
+                let bindings = self.unpack_tuple_pattern(variant, patterns.into_iter().zip(fields));
+
+                let body = ast::Expression::Block(vec![bindings, self.expr(rule)]);
+                (Some(condition), body)
+            }
+        }
+    }
+
+    /// Monomorphizes a block with the given type. Any statements following one which may
+    /// return early are only evaluated if it did not, and any following a `return` itself
+    /// are unreachable and removed.
+    fn block(&mut self, statement_ids: &[StmtId], typ: &ast::Type) -> ast::Expression {
+        let mut statements = Vec::with_capacity(statement_ids.len());
+
+        for (i, id) in statement_ids.iter().enumerate() {
+            let returns = self.returns.count;
+            statements.push(self.statement(*id, typ));
+
+            if matches!(self.interner.statement(id), HirStatement::Return(_)) {
+                break;
+            }
+
+            let rest = &statement_ids[i + 1..];
+            if self.returns.count > returns && !rest.is_empty() {
+                let rest = self.block(rest, typ);
+                statements.push(self.if_not_returned(rest, typ));
+                break;
+            }
+        }
+
+        ast::Expression::Block(statements)
     }
 
     fn unpack_pattern(
@@ -651,6 +1190,41 @@ impl<'interner> Monomorphizer<'interner> {
         }
     }
 
+    /// Resolve a method of a trait to the method of the impl of the trait for the type `Self`
+    /// was bound to when type checking the call.
+    fn trait_method(
+        &mut self,
+        method: TraitMethodId,
+        expr_id: node_interner::ExprId,
+    ) -> ast::Expression {
+        let the_trait = self.interner.get_trait(method.trait_id);
+        let bindings = self.interner.get_instantiation_bindings(expr_id);
+        let self_type = match bindings.get(&the_trait.self_type_variable) {
+            Some((_, self_type)) => self_type.follow_bindings(),
+            None => unreachable!("ICE: Self is not bound for method of trait {}", the_trait.name),
+        };
+
+        let trait_impl = self.interner.find_trait_impl(method.trait_id, &self_type);
+        let func_id = match trait_impl {
+            Some(trait_impl) => trait_impl.methods[method.method_index],
+            None => {
+                unreachable!("ICE: trait {} is not implemented for {self_type}", the_trait.name)
+            }
+        };
+
+        let typ = self.interner.id_type(expr_id).follow_bindings();
+        let id = match self.globals.get(&func_id).and_then(|inner_map| inner_map.get(&typ)) {
+            Some(id) => *id,
+            None => self.queue_function_as(func_id, func_id, typ.clone()),
+        };
+
+        let location = Some(self.interner.expr_location(&expr_id));
+        let name = self.interner.function_name(&func_id).to_owned();
+        let definition = Definition::Function(id);
+        let typ = Self::convert_type(&typ);
+        ast::Expression::Ident(ast::Ident { location, mutable: false, definition, name, typ })
+    }
+
     /// Convert a non-tuple/struct type to a monomorphized type
     fn convert_type(typ: &HirType) -> ast::Type {
         match typ {
@@ -658,6 +1232,10 @@ impl<'interner> Monomorphizer<'interner> {
             HirType::Integer(_, sign, bits) => ast::Type::Integer(*sign, *bits),
             HirType::Bool(_) => ast::Type::Bool,
             HirType::String(size) => ast::Type::String(size.evaluate_to_u64().unwrap_or(0)),
+            HirType::FmtString(size, fields) => {
+                let size = size.evaluate_to_u64().unwrap_or(0);
+                ast::Type::Tuple(vec![ast::Type::String(size), Self::convert_type(fields)])
+            }
             HirType::Unit => ast::Type::Unit,
 
             HirType::Array(length, element) => {
@@ -696,15 +1274,31 @@ impl<'interner> Monomorphizer<'interner> {
                 ast::Type::Tuple(fields)
             }
 
+            HirType::Enum(def, args) => {
+                let def = def.borrow();
+                let mut fields = vec![ast::Type::Field];
+                fields.extend((0..def.num_variants()).map(|index| {
+                    let variant_fields = def.get_variant_fields(index, args);
+                    ast::Type::Tuple(vecmap(variant_fields, |field| Self::convert_type(&field)))
+                }));
+                ast::Type::Tuple(fields)
+            }
+
             HirType::Tuple(fields) => {
                 let fields = vecmap(fields, Self::convert_type);
                 ast::Type::Tuple(fields)
             }
 
-            HirType::Function(args, ret) => {
+            HirType::Function(args, ret, env) => {
                 let args = vecmap(args, Self::convert_type);
                 let ret = Box::new(Self::convert_type(ret));
-                ast::Type::Function(args, ret)
+                if Self::is_closure_env(env) {
+                    let env = Self::convert_type(env);
+                    let args = std::iter::once(env.clone()).chain(args).collect();
+                    ast::Type::Tuple(vec![env, ast::Type::Function(args, ret)])
+                } else {
+                    ast::Type::Function(args, ret)
+                }
             }
 
             HirType::MutableReference(element) => {
@@ -712,12 +1306,21 @@ impl<'interner> Monomorphizer<'interner> {
                 ast::Type::MutableReference(Box::new(element))
             }
 
-            HirType::Forall(_, _) | HirType::Constant(_) | HirType::Error => {
+            HirType::Forall(_, _)
+            | HirType::Constant(_)
+            | HirType::InfixExpr(..)
+            | HirType::Error => {
                 unreachable!("Unexpected type {} found", typ)
             }
         }
     }
 
+    /// Closures which capture variables are represented as a tuple of their environment
+    /// and a function taking that environment as its first parameter.
+    fn is_closure_env(env: &HirType) -> bool {
+        matches!(env.follow_bindings(), HirType::Tuple(captures) if !captures.is_empty())
+    }
+
     /// Converts arrays of structs (AOS) into structs of arrays (SOA).
     /// This is required since our SSA pass does not support arrays of structs.
     fn aos_to_soa_type(length: u64, element: ast::Type) -> ast::Type {
@@ -745,15 +1348,195 @@ impl<'interner> Monomorphizer<'interner> {
         id: node_interner::ExprId,
     ) -> ast::Expression {
         let func = Box::new(self.expr(call.func));
-        let arguments = vecmap(&call.arguments, |id| self.expr(*id));
+        let mut arguments = vecmap(&call.arguments, |id| self.expr(*id));
         let return_type = self.interner.id_type(id);
         let return_type = Self::convert_type(&return_type);
         let location = call.location;
 
+        let func_type = self.interner.id_type(call.func);
+        if let HirType::Function(_, _, env) = func_type.follow_bindings() {
+            if Self::is_closure_env(&env) {
+                let closure_type = Self::convert_type(&func_type);
+                return self.closure_call(func, closure_type, arguments, return_type, location);
+            }
+        }
+
+        if Self::is_builtin(&func, "static_assert") {
+            self.static_assert(call.arguments[0], &arguments[1], location);
+            return ast::Expression::Block(Vec::new());
+        }
+
+        if let Some((operator, overflow)) = Self::overflow_builtin(&func) {
+            let operand_type = self.interner.id_type(call.arguments[0]);
+            self.check_overflow_operand(overflow, &operand_type, location);
+            let overflow = Self::overflow_mode(overflow, operator, &operand_type);
+            let rhs = Box::new(arguments.pop().expect("overflow builtins take two arguments"));
+            let lhs = Box::new(arguments.pop().expect("overflow builtins take two arguments"));
+            return ast::Expression::Binary(ast::Binary { lhs, rhs, operator, location, overflow });
+        }
+
+        if Self::is_builtin(&func, "println") {
+            let typ = self.interner.id_type(call.arguments[0]);
+            let type_info = Self::printable_type_info(&typ);
+            arguments.push(ast::Expression::Literal(ast::Literal::Str(type_info)));
+        }
+
         self.try_evaluate_call(&func, &call.arguments, &return_type)
             .unwrap_or(ast::Expression::Call(ast::Call { func, arguments, return_type, location }))
     }
 
+    fn is_builtin(func: &ast::Expression, name: &str) -> bool {
+        match func {
+            ast::Expression::Ident(ident) => {
+                matches!(&ident.definition, Definition::Builtin(opcode) if opcode == name)
+            }
+            _ => false,
+        }
+    }
+
+    /// The `std::overflow` builtins, such as `wrapping_add`, are named after the overflow mode
+    /// and the arithmetic operation they perform.
+    fn overflow_builtin(func: &ast::Expression) -> Option<(BinaryOpKind, OverflowMode)> {
+        let name = match func {
+            ast::Expression::Ident(ast::Ident {
+                definition: Definition::Builtin(name), ..
+            }) => name,
+            _ => return None,
+        };
+        let (mode, operation) = name.split_once('_')?;
+        let operator = match operation {
+            "add" => BinaryOpKind::Add,
+            "sub" => BinaryOpKind::Subtract,
+            "mul" => BinaryOpKind::Multiply,
+            _ => return None,
+        };
+        Some((operator, mode.parse().ok()?))
+    }
+
+    /// The `std::overflow` builtins only take integers, as fields never overflow.
+    fn check_overflow_operand(
+        &mut self,
+        overflow: OverflowMode,
+        operand_type: &HirType,
+        location: Location,
+    ) {
+        if !matches!(operand_type.follow_bindings(), HirType::Integer(..)) {
+            let typ = operand_type.to_string();
+            let error =
+                MonomorphizationError::UnsupportedOverflowOperand { overflow, typ, location };
+            self.error.get_or_insert(error);
+        }
+    }
+
+    /// Only the addition, subtraction and multiplication of integers can overflow in a way which
+    /// the overflow mode applies to; every other operation wraps.
+    fn overflow_mode(
+        requested: OverflowMode,
+        operator: BinaryOpKind,
+        operand_type: &HirType,
+    ) -> OverflowMode {
+        let is_arithmetic =
+            matches!(operator, BinaryOpKind::Add | BinaryOpKind::Subtract | BinaryOpKind::Multiply);
+        match operand_type.follow_bindings() {
+            HirType::Integer(..) if is_arithmetic => requested,
+            _ => OverflowMode::Wrapping,
+        }
+    }
+
+    /// Implements `std::static_assert` by evaluating its condition at compile-time, within the
+    /// instantiation of the function being monomorphized so that numeric generics have values.
+    fn static_assert(
+        &mut self,
+        condition: node_interner::ExprId,
+        message: &ast::Expression,
+        location: Location,
+    ) {
+        let error = match Interpreter::new(self.interner).evaluate(condition) {
+            Ok(Value::Bool(true)) => return,
+            Ok(_) => {
+                let message = match message {
+                    ast::Expression::Literal(ast::Literal::Str(message)) => message.clone(),
+                    _ => "(message is not a string literal)".to_owned(),
+                };
+                MonomorphizationError::StaticAssertFailed { message, location }
+            }
+            Err(error) => MonomorphizationError::StaticAssertNotConstant { error, location },
+        };
+        self.error.get_or_insert(error);
+    }
+
+    /// Calls to `println` are passed a JSON string describing the type of the value being
+    /// printed as an extra final argument, so that the value can be decoded for display.
+    fn printable_type_info(typ: &HirType) -> String {
+        typ.as_printable_type().to_json()
+    }
+
+    /// Creates a call reporting the message of a failing constraint. Like `println`, it is
+    /// passed the type of the message so that it can be displayed.
+    fn assert_message_call(
+        message: ast::Expression,
+        typ: &HirType,
+        location: Location,
+    ) -> ast::Expression {
+        let type_info = Self::printable_type_info(typ);
+
+        let parameter_types =
+            vec![Self::convert_type(typ), ast::Type::String(type_info.len() as u64)];
+        let func = ast::Expression::Ident(ast::Ident {
+            location: None,
+            mutable: false,
+            definition: Definition::Builtin("assert_message".to_owned()),
+            name: "assert_message".to_owned(),
+            typ: ast::Type::Function(parameter_types, Box::new(ast::Type::Unit)),
+        });
+
+        let arguments = vec![message, ast::Expression::Literal(ast::Literal::Str(type_info))];
+        ast::Expression::Call(ast::Call {
+            func: Box::new(func),
+            arguments,
+            return_type: ast::Type::Unit,
+            location,
+        })
+    }
+
+    /// Calls the function of a closure with its environment prepended to the given arguments.
+    /// The closure is bound to a local first so that it is only evaluated once.
+    fn closure_call(
+        &mut self,
+        closure: Box<ast::Expression>,
+        closure_type: ast::Type,
+        mut arguments: Vec<ast::Expression>,
+        return_type: ast::Type,
+        location: Location,
+    ) -> ast::Expression {
+        let closure_id = self.next_local_id();
+        let name = "closure".to_owned();
+
+        let field = |index| {
+            let closure = ast::Expression::Ident(ast::Ident {
+                location: None,
+                mutable: false,
+                definition: Definition::Local(closure_id),
+                name: name.clone(),
+                typ: closure_type.clone(),
+            });
+            ast::Expression::ExtractTupleField(Box::new(closure), index)
+        };
+
+        arguments.insert(0, field(0));
+        let func = Box::new(field(1));
+
+        ast::Expression::Block(vec![
+            ast::Expression::Let(ast::Let {
+                id: closure_id,
+                mutable: false,
+                name: name.clone(),
+                expression: closure,
+            }),
+            ast::Expression::Call(ast::Call { func, arguments, return_type, location }),
+        ])
+    }
+
     /// Try to evaluate certain builtin functions (currently only 'array_len' and field modulus methods)
     /// at their call site.
     /// NOTE: Evaluating at the call site means we cannot track aliased functions.
@@ -837,6 +1620,27 @@ impl<'interner> Monomorphizer<'interner> {
         new_id
     }
 
+    /// Queue `function` to be monomorphized in place of the function `id` at the given type,
+    /// binding the generics of `function` to match it. This is used to call the fallback of a
+    /// low level function, or the method of an impl in place of the method of a trait.
+    fn queue_function_as(
+        &mut self,
+        id: node_interner::FuncId,
+        function: node_interner::FuncId,
+        function_type: HirType,
+    ) -> FuncId {
+        let bindings = self.interner.function_meta(&function).typ.instantiate_as(&function_type);
+        let bindings = bindings.unwrap_or_else(|| {
+            let name = self.interner.function_name(&function);
+            unreachable!("ICE: {name} cannot be called at type {function_type}")
+        });
+
+        let new_id = self.next_function_id();
+        self.define_global(id, function_type, new_id);
+        self.queue.push_back((function, new_id, bindings));
+        new_id
+    }
+
     /// Follow any type variable links within the given TypeBindings to produce
     /// a new TypeBindings that won't be changed when bindings are pushed or popped
     /// during {perform,undo}_monomorphization_bindings.
@@ -950,15 +1754,33 @@ impl<'interner> Monomorphizer<'interner> {
             Param(pattern, typ, noirc_abi::AbiVisibility::Private)
         }));
 
+        if !lambda.captures.is_empty() {
+            return self.closure(
+                lambda.captures,
+                parameters,
+                parameter_types,
+                lambda.body,
+                ret_type,
+            );
+        }
+
         let parameters = self.parameters(parameters);
-        let body = self.expr(lambda.body);
+        let body = self.function_body(lambda.body, &ret_type);
 
         let id = self.next_function_id();
         let return_type = ret_type.clone();
         let name = lambda_name.to_owned();
         let unconstrained = false;
 
-        let function = ast::Function { id, name, parameters, body, return_type, unconstrained };
+        let function = ast::Function {
+            id,
+            name,
+            parameters,
+            body,
+            return_type,
+            unconstrained,
+            inline_type: None,
+        };
         self.push_function(id, function);
 
         let typ = ast::Type::Function(parameter_types, Box::new(ret_type));
@@ -973,6 +1795,82 @@ impl<'interner> Monomorphizer<'interner> {
         })
     }
 
+    /// Converts a lambda which captures variables into a tuple of its environment and
+    /// a function taking that environment as its first parameter. The body of the
+    /// function starts by binding each captured variable to its field of the environment.
+    fn closure(
+        &mut self,
+        captures: Vec<HirIdent>,
+        parameters: Parameters,
+        parameter_types: Vec<ast::Type>,
+        body: node_interner::ExprId,
+        ret_type: ast::Type,
+    ) -> ast::Expression {
+        let lambda_name = "lambda";
+
+        let env_fields = vecmap(&captures, |capture| {
+            self.local_ident(capture).expect("Captured variables must be locals")
+        });
+        let env_type = ast::Type::Tuple(vecmap(&env_fields, |field| field.typ.clone()));
+        let env_fields = vecmap(env_fields, ast::Expression::Ident);
+
+        // The captured variables are redefined within the closure, so the locals
+        // of the enclosing function must be restored once it is monomorphized.
+        let outer_locals = self.locals.clone();
+
+        let env_id = self.next_local_id();
+        let env_name = "env".to_owned();
+        let mut parameters_with_env = vec![(env_id, false, env_name.clone(), env_type.clone())];
+        parameters_with_env.extend(self.parameters(parameters));
+
+        let mut statements = Vec::with_capacity(captures.len() + 1);
+        for (index, capture) in captures.iter().enumerate() {
+            let env = ast::Expression::Ident(ast::Ident {
+                location: None,
+                mutable: false,
+                definition: Definition::Local(env_id),
+                name: env_name.clone(),
+                typ: env_type.clone(),
+            });
+
+            let id = self.next_local_id();
+            let name = self.interner.definition(capture.id).name.clone();
+            let expression = Box::new(ast::Expression::ExtractTupleField(Box::new(env), index));
+            statements.push(ast::Expression::Let(ast::Let {
+                id,
+                mutable: false,
+                name,
+                expression,
+            }));
+            self.define_local(capture.id, id);
+        }
+        statements.push(self.function_body(body, &ret_type));
+        self.locals = outer_locals;
+
+        let id = self.next_function_id();
+        let function = ast::Function {
+            id,
+            name: lambda_name.to_owned(),
+            parameters: parameters_with_env,
+            body: ast::Expression::Block(statements),
+            return_type: ret_type.clone(),
+            unconstrained: false,
+            inline_type: None,
+        };
+        self.push_function(id, function);
+
+        let parameter_types = std::iter::once(env_type.clone()).chain(parameter_types).collect();
+        let function = ast::Expression::Ident(ast::Ident {
+            definition: Definition::Function(id),
+            mutable: false,
+            location: None,
+            name: lambda_name.to_owned(),
+            typ: ast::Type::Function(parameter_types, Box::new(ret_type)),
+        });
+
+        ast::Expression::Tuple(vec![ast::Expression::Tuple(env_fields), function])
+    }
+
     /// Implements std::unsafe::zeroed by returning an appropriate zeroed
     /// ast literal or collection node for the given type. Note that for functions
     /// there is no obvious zeroed value so this should be considered unsafe to use.
@@ -1040,7 +1938,15 @@ impl<'interner> Monomorphizer<'interner> {
         let name = lambda_name.to_owned();
 
         let unconstrained = false;
-        let function = ast::Function { id, name, parameters, body, return_type, unconstrained };
+        let function = ast::Function {
+            id,
+            name,
+            parameters,
+            body,
+            return_type,
+            unconstrained,
+            inline_type: None,
+        };
         self.push_function(id, function);
 
         ast::Expression::Ident(ast::Ident {
@@ -1075,6 +1981,17 @@ fn unwrap_struct_type(typ: &HirType) -> Vec<(String, HirType)> {
     }
 }
 
+fn unwrap_enum_variant_type(typ: &HirType, variant_index: usize) -> Vec<HirType> {
+    match typ {
+        HirType::Enum(def, args) => def.borrow().get_variant_fields(variant_index, args),
+        HirType::TypeVariable(binding) => match &*binding.borrow() {
+            TypeBinding::Bound(binding) => unwrap_enum_variant_type(binding, variant_index),
+            TypeBinding::Unbound(_) => unreachable!(),
+        },
+        other => unreachable!("unwrap_enum_variant_type: expected enum, found {:?}", other),
+    }
+}
+
 fn unwrap_array_element_type(typ: &HirType) -> HirType {
     match typ {
         HirType::Array(_, elem) => *elem.clone(),
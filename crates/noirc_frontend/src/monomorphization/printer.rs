@@ -47,6 +47,7 @@ impl AstPrinter {
                 write!(f, " as {})", cast.r#type)
             }
             Expression::For(for_expr) => self.print_for(for_expr, f),
+            Expression::While(while_expr) => self.print_while(while_expr, f),
             Expression::If(if_expr) => self.print_if(if_expr, f),
             Expression::Tuple(tuple) => self.print_tuple(tuple, f),
             Expression::ExtractTupleField(expr, index) => {
@@ -58,9 +59,14 @@ impl AstPrinter {
                 write!(f, "let {}${} = ", let_expr.name, let_expr.id.0)?;
                 self.print_expr(&let_expr.expression, f)
             }
-            Expression::Constrain(expr, ..) => {
+            Expression::Constrain(expr, _, message) => {
                 write!(f, "constrain ")?;
-                self.print_expr(expr, f)
+                self.print_expr(expr, f)?;
+                if let Some(message) = message {
+                    write!(f, ", ")?;
+                    self.print_expr(message, f)?;
+                }
+                Ok(())
             }
             Expression::Assign(assign) => {
                 self.print_lvalue(&assign.lvalue, f)?;
@@ -190,6 +196,22 @@ impl AstPrinter {
         write!(f, "}}")
     }
 
+    fn print_while(
+        &mut self,
+        while_expr: &super::ast::While,
+        f: &mut Formatter,
+    ) -> Result<(), std::fmt::Error> {
+        write!(f, "while ")?;
+        self.print_expr(&while_expr.condition, f)?;
+        write!(f, " {{")?;
+
+        self.indent_level += 1;
+        self.print_expr_expect_block(&while_expr.block, f)?;
+        self.indent_level -= 1;
+        self.next_line(f)?;
+        write!(f, "}}")
+    }
+
     fn print_if(
         &mut self,
         if_expr: &super::ast::If,
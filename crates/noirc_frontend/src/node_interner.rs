@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use arena::{Arena, Index};
 use fm::FileId;
@@ -7,15 +7,17 @@ use noirc_errors::{Location, Span, Spanned};
 
 use crate::ast::Ident;
 use crate::graph::CrateId;
-use crate::hir::def_collector::dc_crate::UnresolvedStruct;
-use crate::hir::def_map::{LocalModuleId, ModuleId};
+use crate::hir::def_collector::dc_crate::{UnresolvedEnum, UnresolvedStruct, UnresolvedTypeAlias};
+use crate::hir::def_map::{LocalModuleId, ModuleDefId, ModuleId};
+use crate::hir::resolution::resolver::Resolver;
 use crate::hir::StorageSlot;
 use crate::hir_def::stmt::HirLetStatement;
-use crate::hir_def::types::{StructType, Type};
+use crate::hir_def::traits::{OverloadableOperator, Trait, TraitImpl, TraitMethodId};
+use crate::hir_def::types::{EnumType, StructType, Type, TypeAliasType};
 use crate::hir_def::{
-    expr::HirExpression,
+    expr::{HirExpression, HirForExpression},
     function::{FuncMeta, HirFunction},
-    stmt::HirStatement,
+    stmt::{HirPattern, HirStatement},
 };
 use crate::{Shared, TypeBinding, TypeBindings, TypeVariable, TypeVariableId};
 
@@ -36,6 +38,10 @@ pub struct NodeInterner {
     // Maps each DefinitionId to a DefinitionInfo.
     definitions: Vec<DefinitionInfo>,
 
+    /// The location each local variable is declared at, used to point diagnostics back to
+    /// the declaration.
+    definition_locations: HashMap<DefinitionId, Location>,
+
     // Type checking map
     //
     // Notice that we use `Index` as the Key and not an ExprId or IdentId
@@ -52,6 +58,22 @@ pub struct NodeInterner {
     // methods from impls to the type.
     structs: HashMap<StructId, Shared<StructType>>,
 
+    /// Enum map. Like structs, each enum definition is shared across the type nodes
+    /// referring to it, and its variants are filled in after it is first created.
+    enums: HashMap<EnumId, Shared<EnumType>>,
+
+    /// Type alias map, indexed by TypeAliasId. Each alias is pushed when it is collected
+    /// and its aliased type is filled in once resolved.
+    type_aliases: Vec<TypeAliasType>,
+
+    /// Trait map, filled out during name resolution along with the impls of each trait.
+    traits: HashMap<TraitId, Trait>,
+    trait_impls: HashMap<TraitId, Vec<TraitImpl>>,
+
+    /// The trait method each overloadable operator is desugared to. Only filled out if the
+    /// standard library defining these traits is a dependency of the crate being compiled.
+    operator_methods: HashMap<OverloadableOperator, TraitMethodId>,
+
     /// Map from ExprId (referring to a Function/Method call) to its corresponding TypeBindings,
     /// filled out during type checking from instantiated variables. Used during monomorphization
     /// to map call site types back onto function parameter types, and undo this binding as needed.
@@ -65,17 +87,63 @@ pub struct NodeInterner {
 
     next_type_variable_id: usize,
 
+    /// Type variables standing in for the values of globals used as array lengths which must be
+    /// evaluated at compile-time. These are bound once every function has been resolved.
+    comptime_array_lengths: Vec<(StmtId, TypeVariable)>,
+
     /// A map from a struct type and method name to a function id for the method.
     struct_methods: HashMap<(StructId, String), FuncId>,
 
     /// Methods on primitive types defined in the stdlib.
     primitive_methods: HashMap<(TypeMethodKey, String), FuncId>,
 
+    /// The names looked up in the scope of each module while resolving paths, used to find
+    /// the imports which are never used.
+    used_names: HashSet<(ModuleId, String)>,
+
+    /// The functions referred to by a path anywhere, used to find the private functions which
+    /// are never called.
+    used_functions: HashSet<FuncId>,
+
+    /// The fields of each struct which are read somewhere, by their index, used to find the
+    /// fields which never are.
+    read_fields: HashSet<(StructId, usize)>,
+
+    /// The definition or item each name resolved in the program refers to, along with the
+    /// location of the name, used by the language server to find definitions and references.
+    references: Vec<(Location, ReferenceId)>,
+
+    /// The location of each name of a generic type, where it is declared or used within a type,
+    /// used by the language server to highlight them.
+    generic_locations: Vec<Location>,
+
     /// TODO(#1850): This is technical debt that should be removed once we fully move over
     /// to the new SSA pass which does have slices enabled
     pub enable_slices: bool,
 }
 
+/// The type of an expression found by the type checker, along with what the language server
+/// shows about the item the expression refers to when hovering over it.
+#[derive(Debug, Clone)]
+pub struct ExpressionInfo {
+    pub location: Location,
+    pub typ: Type,
+    /// The values the numeric generics of a generic function were instantiated with, if the
+    /// expression refers to one
+    pub numeric_generics: Vec<(String, Type)>,
+    /// The doc comments of the function, struct or global the expression refers to
+    pub doc_comments: Vec<String>,
+}
+
+/// What a name in the program refers to: either a variable or function in scope where it is
+/// used, an item found by resolving a path, or a field of a struct by its index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceId {
+    Definition(DefinitionId),
+    Item(ModuleDefId),
+    Field(StructId, usize),
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub struct DefinitionId(usize);
 
@@ -136,6 +204,40 @@ impl StructId {
     }
 }
 
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub struct EnumId(pub ModuleId);
+
+impl EnumId {
+    //dummy id for error reporting
+    // This can be anything, as the program will ultimately fail
+    // after resolution
+    pub fn dummy_id() -> EnumId {
+        EnumId(ModuleId { krate: CrateId::dummy_id(), local_id: LocalModuleId::dummy_id() })
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, PartialOrd, Ord)]
+pub struct TypeAliasId(pub usize);
+
+impl TypeAliasId {
+    //dummy id for error reporting
+    pub fn dummy_id() -> TypeAliasId {
+        TypeAliasId(std::usize::MAX)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub struct TraitId(pub ModuleId);
+
+impl TraitId {
+    //dummy id for error reporting
+    // This can be anything, as the program will ultimately fail
+    // after resolution
+    pub fn dummy_id() -> TraitId {
+        TraitId(ModuleId { krate: CrateId::dummy_id(), local_id: LocalModuleId::dummy_id() })
+    }
+}
+
 macro_rules! into_index {
     ($id_type:ty) => {
         impl From<$id_type> for Index {
@@ -235,6 +337,9 @@ pub struct GlobalInfo {
     /// Global definitions have an associated storage slot if they are defined within
     /// a contract. If they're defined elsewhere, this value is None.
     pub storage_slot: Option<StorageSlot>,
+
+    /// The lines of the `///` doc comments written above the global
+    pub doc_comments: Vec<String>,
 }
 
 impl Default for NodeInterner {
@@ -245,14 +350,26 @@ impl Default for NodeInterner {
             function_definition_ids: HashMap::new(),
             id_to_location: HashMap::new(),
             definitions: vec![],
+            definition_locations: HashMap::new(),
             id_to_type: HashMap::new(),
             structs: HashMap::new(),
+            enums: HashMap::new(),
+            type_aliases: Vec::new(),
+            traits: HashMap::new(),
+            trait_impls: HashMap::new(),
+            operator_methods: HashMap::new(),
             instantiation_bindings: HashMap::new(),
             field_indices: HashMap::new(),
             next_type_variable_id: 0,
+            comptime_array_lengths: Vec::new(),
             globals: HashMap::new(),
             struct_methods: HashMap::new(),
             primitive_methods: HashMap::new(),
+            used_names: HashSet::new(),
+            used_functions: HashSet::new(),
+            read_fields: HashSet::new(),
+            references: Vec::new(),
+            generic_locations: Vec::new(),
             enable_slices: false,
         };
 
@@ -291,18 +408,65 @@ impl NodeInterner {
     }
 
     pub fn push_empty_struct(&mut self, type_id: StructId, typ: &UnresolvedStruct) {
-        self.structs.insert(
+        let mut struct_type = StructType::new(
+            type_id,
+            typ.struct_def.name.clone(),
+            typ.struct_def.span,
+            typ.file_id,
+            Vec::new(),
+            vecmap(&typ.struct_def.generics, |_| {
+                // Temporary type variable ids before the struct is resolved to its actual ids.
+                // This lets us record how many arguments the type expects so that other types
+                // can refer to it with generic arguments before the generic parameters themselves
+                // are resolved.
+                let id = TypeVariableId(0);
+                (id, Shared::new(TypeBinding::Unbound(id)))
+            }),
+        );
+        struct_type.deprecated = typ.struct_def.deprecated.clone();
+        struct_type.doc_comments = typ.struct_def.doc_comments.clone();
+        self.structs.insert(type_id, Shared::new(struct_type));
+    }
+
+    pub fn update_struct(&mut self, type_id: StructId, f: impl FnOnce(&mut StructType)) {
+        let mut value = self.structs.get_mut(&type_id).unwrap().borrow_mut();
+        f(&mut value);
+    }
+
+    pub fn push_type_alias(&mut self, typ: &UnresolvedTypeAlias) -> TypeAliasId {
+        let type_id = TypeAliasId(self.type_aliases.len());
+
+        self.type_aliases.push(TypeAliasType::new(
+            type_id,
+            typ.type_alias_def.name.clone(),
+            typ.type_alias_def.span,
+            Type::Error,
+            vecmap(&typ.type_alias_def.generics, |_| {
+                // Temporary type variable ids before the alias is resolved, for the same
+                // reason as in push_empty_struct.
+                let id = TypeVariableId(0);
+                (id, Shared::new(TypeBinding::Unbound(id)))
+            }),
+        ));
+
+        type_id
+    }
+
+    pub fn update_type_alias(&mut self, type_id: TypeAliasId, f: impl FnOnce(&mut TypeAliasType)) {
+        f(&mut self.type_aliases[type_id.0]);
+    }
+
+    pub fn push_empty_enum(&mut self, type_id: EnumId, typ: &UnresolvedEnum) {
+        self.enums.insert(
             type_id,
-            Shared::new(StructType::new(
+            Shared::new(EnumType::new(
                 type_id,
-                typ.struct_def.name.clone(),
-                typ.struct_def.span,
+                typ.enum_def.name.clone(),
+                typ.enum_def.span,
                 Vec::new(),
-                vecmap(&typ.struct_def.generics, |_| {
-                    // Temporary type variable ids before the struct is resolved to its actual ids.
-                    // This lets us record how many arguments the type expects so that other types
-                    // can refer to it with generic arguments before the generic parameters themselves
-                    // are resolved.
+                vecmap(&typ.enum_def.generics, |_| {
+                    // Temporary type variable ids before the enum is resolved, for the same
+                    // reason as in push_empty_struct.
                     let id = TypeVariableId(0);
                     (id, Shared::new(TypeBinding::Unbound(id)))
                 }),
@@ -310,8 +474,8 @@ impl NodeInterner {
         );
     }
 
-    pub fn update_struct(&mut self, type_id: StructId, f: impl FnOnce(&mut StructType)) {
-        let mut value = self.structs.get_mut(&type_id).unwrap().borrow_mut();
+    pub fn update_enum(&mut self, type_id: EnumId, f: impl FnOnce(&mut EnumType)) {
+        let mut value = self.enums.get_mut(&type_id).unwrap().borrow_mut();
         f(&mut value);
     }
 
@@ -337,8 +501,10 @@ impl NodeInterner {
         ident: Ident,
         local_id: LocalModuleId,
         storage_slot: Option<StorageSlot>,
+        doc_comments: Vec<String>,
     ) {
-        self.globals.insert(stmt_id, GlobalInfo { ident, local_id, storage_slot });
+        let info = GlobalInfo { ident, local_id, storage_slot, doc_comments };
+        self.globals.insert(stmt_id, info);
     }
 
     /// Intern an empty global stmt. Used for collecting globals
@@ -402,6 +568,15 @@ impl NodeInterner {
         id
     }
 
+    pub fn push_definition_location(&mut self, id: DefinitionId, location: Location) {
+        self.definition_locations.insert(id, location);
+    }
+
+    /// Returns the location a local variable was declared at
+    pub fn definition_location(&self, id: DefinitionId) -> Option<Location> {
+        self.definition_locations.get(&id).copied()
+    }
+
     pub fn push_function_definition(&mut self, name: String, func: FuncId) -> DefinitionId {
         self.push_definition(name, false, DefinitionKind::Function(func))
     }
@@ -501,6 +676,75 @@ impl NodeInterner {
         self.structs[&id].clone()
     }
 
+    pub fn get_enum(&self, id: EnumId) -> Shared<EnumType> {
+        self.enums[&id].clone()
+    }
+
+    pub fn get_type_alias(&self, id: TypeAliasId) -> &TypeAliasType {
+        &self.type_aliases[id.0]
+    }
+
+    pub fn push_trait(&mut self, the_trait: Trait) {
+        self.traits.insert(the_trait.id, the_trait);
+    }
+
+    pub fn get_trait(&self, id: TraitId) -> &Trait {
+        &self.traits[&id]
+    }
+
+    /// Returns the trait with the given id, unless it has not been resolved yet
+    pub fn try_get_trait(&self, id: TraitId) -> Option<&Trait> {
+        self.traits.get(&id)
+    }
+
+    pub fn add_trait_impl(&mut self, trait_id: TraitId, trait_impl: TraitImpl) {
+        self.trait_impls.entry(trait_id).or_default().push(trait_impl);
+    }
+
+    /// Returns each impl of the given trait
+    pub fn trait_impls(&self, trait_id: TraitId) -> &[TraitImpl] {
+        self.trait_impls.get(&trait_id).map_or(&[], |impls| impls.as_slice())
+    }
+
+    /// Returns the impl of the given trait for the given type, if there is one
+    pub fn find_trait_impl(&self, trait_id: TraitId, typ: &Type) -> Option<&TraitImpl> {
+        self.trait_impls(trait_id)
+            .iter()
+            .find(|trait_impl| trait_impl.typ.instantiate_as(typ).is_some())
+    }
+
+    /// Returns an impl of the given trait which applies to some of the types an impl for `typ`
+    /// would apply to, if there is one. `typ` may be quantified over the generics of the impl.
+    pub fn find_overlapping_trait_impl(&self, trait_id: TraitId, typ: &Type) -> Option<&TraitImpl> {
+        self.trait_impls(trait_id).iter().find(|trait_impl| {
+            // The generics of the impl for `typ` may be any type, so they are replaced by type
+            // variables, bound anew for each impl it is compared with
+            let impl_head = match typ {
+                Type::Forall(generics, typ) => {
+                    let replacements = generics
+                        .iter()
+                        .map(|(id, var)| {
+                            let fresh = Type::TypeVariable(Shared::new(TypeBinding::Unbound(*id)));
+                            (*id, (var.clone(), fresh))
+                        })
+                        .collect();
+                    typ.substitute(&replacements)
+                }
+                typ => typ.clone(),
+            };
+            trait_impl.typ.instantiate_as(&impl_head).is_some()
+        })
+    }
+
+    pub fn set_operator_method(&mut self, operator: OverloadableOperator, method: TraitMethodId) {
+        self.operator_methods.insert(operator, method);
+    }
+
+    /// Returns the trait method overloading the given operator, if the trait defining it is known
+    pub fn operator_method(&self, operator: OverloadableOperator) -> Option<TraitMethodId> {
+        self.operator_methods.get(&operator).copied()
+    }
+
     pub fn get_global(&self, stmt_id: &StmtId) -> Option<GlobalInfo> {
         self.globals.get(stmt_id).cloned()
     }
@@ -531,6 +775,23 @@ impl NodeInterner {
         TypeVariableId(id)
     }
 
+    /// Returns the type variable standing in for the value of the given global when used as an
+    /// array length, creating it if this is the first such use.
+    pub fn comptime_array_length(&mut self, global: StmtId) -> Type {
+        if let Some((_, typevar)) = self.comptime_array_lengths.iter().find(|(id, _)| *id == global)
+        {
+            return Type::TypeVariable(typevar.clone());
+        }
+
+        let typevar = Shared::new(TypeBinding::Unbound(self.next_type_variable_id()));
+        self.comptime_array_lengths.push((global, typevar.clone()));
+        Type::TypeVariable(typevar)
+    }
+
+    pub fn take_comptime_array_lengths(&mut self) -> Vec<(StmtId, TypeVariable)> {
+        std::mem::take(&mut self.comptime_array_lengths)
+    }
+
     pub fn next_type_variable(&mut self) -> Type {
         Type::type_variable(self.next_type_variable_id())
     }
@@ -559,6 +820,473 @@ impl NodeInterner {
         self.function_definition_ids[&function]
     }
 
+    /// Records that the given name was looked up in the scope of the given module
+    pub fn mark_name_used(&mut self, module: ModuleId, name: &str) {
+        self.used_names.insert((module, name.to_owned()));
+    }
+
+    pub fn is_name_used(&self, module: ModuleId, name: &str) -> bool {
+        self.used_names.contains(&(module, name.to_owned()))
+    }
+
+    /// Records that the given function was referred to by a path
+    pub fn mark_function_used(&mut self, function: FuncId) {
+        self.used_functions.insert(function);
+    }
+
+    pub fn is_function_used(&self, function: FuncId) -> bool {
+        self.used_functions.contains(&function)
+    }
+
+    /// Records that the field at the given index of a struct is read somewhere
+    pub fn mark_field_read(&mut self, struct_id: StructId, index: usize) {
+        self.read_fields.insert((struct_id, index));
+    }
+
+    pub fn is_field_read(&self, struct_id: StructId, index: usize) -> bool {
+        self.read_fields.contains(&(struct_id, index))
+    }
+
+    /// Records that the name at the given location refers to the given definition or item
+    pub fn add_reference(&mut self, location: Location, reference: ReferenceId) {
+        self.references.push((location, reference));
+    }
+
+    /// Records that the name at the given location is the name of a generic type
+    pub fn add_generic_location(&mut self, location: Location) {
+        self.generic_locations.push(location);
+    }
+
+    /// Returns the location of the definition of the name at the given byte offset of a file.
+    /// A path such as `foo::bar` refers to its item from anywhere within it. Items without a
+    /// known location, such as modules, and names which failed to resolve have no definition.
+    pub fn find_definition(&self, file: FileId, offset: u32) -> Option<Location> {
+        let (_, reference) = self.find_reference(file, offset)?;
+        self.reference_location(reference)
+    }
+
+    /// Returns the type of the innermost expression at the given byte offset of a file, along
+    /// with the doc comments of the item it refers to and the values of the numeric generics
+    /// of the function it refers to, if any.
+    pub fn find_expression_info(&self, file: FileId, offset: u32) -> Option<ExpressionInfo> {
+        let (index, location) = self
+            .id_to_location
+            .iter()
+            .filter(|(index, location)| {
+                self.id_to_type.contains_key(*index) && location_contains(location, file, offset)
+            })
+            .min_by_key(|(_, location)| location.span.end() - location.span.start())?;
+
+        let doc_comments = match self.find_reference(file, offset) {
+            Some((_, reference)) => self.reference_doc_comments(reference),
+            None => Vec::new(),
+        };
+
+        Some(ExpressionInfo {
+            location: *location,
+            typ: self.id_type(*index),
+            numeric_generics: vecmap(
+                self.instantiated_numeric_generics(ExprId(*index)),
+                |(name, value)| (name, value.follow_bindings()),
+            ),
+            doc_comments,
+        })
+    }
+
+    /// Returns the location of each use of the definition or item named at the given byte offset
+    /// of a file, which may be either one of its uses or its declaration. The location of the
+    /// declaration itself comes first if `include_declaration` is set.
+    pub fn find_references(
+        &self,
+        file: FileId,
+        offset: u32,
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        let declaration = match self.find_declaration(file, offset) {
+            Some((_, declaration)) => declaration,
+            None => return Vec::new(),
+        };
+
+        let mut locations = if include_declaration { vec![declaration] } else { Vec::new() };
+        locations.extend(self.find_uses(declaration));
+        locations
+    }
+
+    /// Returns what the name at the given byte offset of a file refers to, along with the
+    /// location of its declaration. The offset may be within either a use of the name or the
+    /// declaration itself.
+    pub fn find_declaration(&self, file: FileId, offset: u32) -> Option<(ReferenceId, Location)> {
+        if let Some((_, reference)) = self.find_reference(file, offset) {
+            return Some((reference, self.reference_location(reference)?));
+        }
+
+        self.declarations().find(|(_, location)| location_contains(location, file, offset))
+    }
+
+    /// Returns the names resolved within the given file, each along with what it refers to.
+    pub fn references_in_file(&self, file: FileId) -> Vec<(Location, ReferenceId)> {
+        let in_file = self.references.iter().filter(|(location, _)| location.file == file);
+        in_file.copied().collect()
+    }
+
+    /// Returns the names of the variables, functions, structs and fields declared in the given
+    /// file, each along with what declaring it defines.
+    pub fn declarations_in_file(&self, file: FileId) -> Vec<(ReferenceId, Location)> {
+        self.declarations().filter(|(_, location)| location.file == file).collect()
+    }
+
+    /// Returns the location of each name of a generic type within the given file.
+    pub fn generics_in_file(&self, file: FileId) -> Vec<Location> {
+        let in_file = self.generic_locations.iter().filter(|location| location.file == file);
+        in_file.copied().collect()
+    }
+
+    /// Returns the patterns of the `let` statements without a type annotation within the given
+    /// file, whose types are only known once inferred by the type checker.
+    pub fn unannotated_let_patterns_in_file(&self, file: FileId) -> Vec<HirPattern> {
+        let mut patterns = Vec::new();
+        for (_, node) in self.nodes.iter() {
+            if let Node::Statement(HirStatement::Let(let_statement)) = node {
+                // A `let` statement without a type annotation is resolved with an error type
+                let in_file = self.id_to_location.get(&let_statement.expression.0);
+                if matches!(let_statement.r#type, Type::Error)
+                    && in_file.map_or(false, |location| location.file == file)
+                {
+                    patterns.push(let_statement.pattern.clone());
+                }
+            }
+        }
+        patterns
+    }
+
+    /// Returns the `for` loops within the given file, each along with its location.
+    pub fn for_loops_in_file(&self, file: FileId) -> Vec<(Location, HirForExpression)> {
+        let mut loops = Vec::new();
+        for (index, node) in self.nodes.iter() {
+            if let Node::Expression(HirExpression::For(for_loop)) = node {
+                match self.id_to_location.get(&index) {
+                    Some(location) if location.file == file => {
+                        loops.push((*location, for_loop.clone()));
+                    }
+                    _ => (),
+                }
+            }
+        }
+        loops
+    }
+
+    fn declarations(&self) -> impl Iterator<Item = (ReferenceId, Location)> + '_ {
+        let definitions = self
+            .definition_locations
+            .iter()
+            .map(|(id, location)| (ReferenceId::Definition(*id), *location));
+        let functions = self.func_meta.iter().map(|(id, meta)| {
+            (ReferenceId::Item(ModuleDefId::FunctionId(*id)), meta.name.location)
+        });
+        let structs = self.structs.values().flat_map(|struct_type| {
+            let struct_type = struct_type.borrow();
+            let (id, file) = (struct_type.id, struct_type.file);
+            let name = Location::new(struct_type.name.span(), file);
+            let fields =
+                vecmap(struct_type.field_idents().into_iter().enumerate(), |(index, field)| {
+                    (ReferenceId::Field(id, index), Location::new(field.span(), file))
+                });
+            std::iter::once((ReferenceId::Item(ModuleDefId::TypeId(id)), name)).chain(fields)
+        });
+        definitions.chain(functions).chain(structs)
+    }
+
+    /// Returns the location of each name referring to the item declared at the given location.
+    pub fn find_uses(&self, declaration: Location) -> Vec<Location> {
+        let mut uses = Vec::new();
+        for (location, reference) in &self.references {
+            // A path may be resolved more than once, such as when checking what kind of type it is
+            if self.reference_location(*reference) == Some(declaration) && !uses.contains(location)
+            {
+                uses.push(*location);
+            }
+        }
+        uses
+    }
+
+    /// Returns the declaration of each definition named at exactly the given location: what
+    /// the names recorded there refer to, along with the location itself if a variable is
+    /// declared there. A name usually refers to a single definition, but the shorthands
+    /// `Foo { x }` in expressions and patterns name both a field and a variable.
+    pub fn declarations_at(&self, location: Location) -> Vec<Location> {
+        let declared = self.definition_locations.values().filter(|declared| **declared == location);
+        self.references
+            .iter()
+            .filter(|(name, _)| *name == location)
+            .filter_map(|(_, reference)| self.reference_location(*reference))
+            .chain(declared.copied())
+            .collect()
+    }
+
+    /// Returns the location of the function containing the given location, spanning from the
+    /// name of the function to the end of its body so that its parameters are included.
+    pub fn enclosing_function(&self, location: Location) -> Option<Location> {
+        self.func_meta
+            .iter()
+            .filter_map(|(id, meta)| self.function_extent(id, meta))
+            .filter(|function| location_contains(function, location.file, location.span.start()))
+            .min_by_key(|function| function.span.end() - function.span.start())
+    }
+
+    /// Returns the functions and methods declared in the given file, each along with its
+    /// location spanning from its name to the end of its body.
+    pub fn functions_in_file(&self, file: FileId) -> Vec<(FuncId, Location)> {
+        self.func_meta
+            .iter()
+            .filter(|(_, meta)| meta.name.location.file == file)
+            .filter_map(|(id, meta)| Some((*id, self.function_extent(id, meta)?)))
+            .collect()
+    }
+
+    fn function_extent(&self, id: &FuncId, meta: &FuncMeta) -> Option<Location> {
+        let body = match self.nodes.get(id.0) {
+            Some(Node::Function(function)) => self.id_to_location.get(&function.as_expr().0)?,
+            _ => return None,
+        };
+        let start = meta.name.location.span.start();
+        let span = Span::exclusive(start, body.span.end().max(start));
+        Some(Location::new(span, meta.name.location.file))
+    }
+
+    /// Returns the location of a variable with the given name which is declared within the given
+    /// location, or of a use of a variable or function with that name within it.
+    pub fn find_name_within(&self, scope: Location, name: &str) -> Option<Location> {
+        let within = |location: &Location| {
+            location.file == scope.file
+                && scope.span.start() <= location.span.start()
+                && location.span.end() <= scope.span.end()
+        };
+
+        let declaration = self
+            .definition_locations
+            .iter()
+            .find(|(id, location)| within(location) && self.definition_name(**id) == name);
+        if let Some((_, location)) = declaration {
+            return Some(*location);
+        }
+
+        self.references.iter().find_map(|(location, reference)| match reference {
+            ReferenceId::Definition(id)
+                if within(location) && self.definition_name(*id) == name =>
+            {
+                Some(*location)
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns the type of the innermost expression ending at the given byte offset of a file,
+    /// such as the expression before a `.` written at that offset.
+    pub fn find_type_of_expression_ending_at(&self, file: FileId, offset: u32) -> Option<Type> {
+        let (index, _) = self
+            .id_to_location
+            .iter()
+            .filter(|(index, location)| {
+                self.id_to_type.contains_key(*index)
+                    && location.file == file
+                    && location.span.end() == offset
+            })
+            .min_by_key(|(_, location)| location.span.end() - location.span.start())?;
+        Some(self.id_type(*index))
+    }
+
+    /// Returns the innermost name containing the given byte offset of a file, with what it
+    /// refers to.
+    fn find_reference(&self, file: FileId, offset: u32) -> Option<(Location, ReferenceId)> {
+        self.references
+            .iter()
+            .filter(|(location, _)| location_contains(location, file, offset))
+            .min_by_key(|(location, _)| location.span.end() - location.span.start())
+            .copied()
+    }
+
+    /// Returns the location of the declaration of what a name refers to.
+    pub fn reference_location(&self, reference: ReferenceId) -> Option<Location> {
+        let function_location =
+            |func_id: &FuncId| self.func_meta.get(func_id).map(|meta| meta.name.location);
+
+        match reference {
+            ReferenceId::Definition(id) => match self.definition(id).kind {
+                DefinitionKind::Function(func_id) => function_location(&func_id),
+                _ => self.definition_location(id),
+            },
+            ReferenceId::Item(ModuleDefId::FunctionId(func_id)) => function_location(&func_id),
+            ReferenceId::Item(ModuleDefId::TypeId(struct_id)) => {
+                let struct_type = self.get_struct(struct_id);
+                let struct_type = struct_type.borrow();
+                Some(Location::new(struct_type.name.span(), struct_type.file))
+            }
+            ReferenceId::Item(ModuleDefId::TraitId(trait_id)) => {
+                let the_trait = self.try_get_trait(trait_id)?;
+                Some(Location::new(the_trait.name.span(), the_trait.file))
+            }
+            ReferenceId::Item(ModuleDefId::GlobalId(stmt_id)) => {
+                self.definition_location(self.let_statement(&stmt_id).ident().id)
+            }
+            ReferenceId::Item(_) => None,
+            ReferenceId::Field(struct_id, index) => {
+                let struct_type = self.get_struct(struct_id);
+                let struct_type = struct_type.borrow();
+                let field = struct_type.field_idents().into_iter().nth(index)?;
+                Some(Location::new(field.span(), struct_type.file))
+            }
+        }
+    }
+
+    fn reference_doc_comments(&self, reference: ReferenceId) -> Vec<String> {
+        let function_doc_comments = |func_id: &FuncId| {
+            self.func_meta.get(func_id).map(|meta| meta.doc_comments.clone()).unwrap_or_default()
+        };
+        let global_doc_comments = |stmt_id: &StmtId| {
+            self.globals.get(stmt_id).map(|global| global.doc_comments.clone()).unwrap_or_default()
+        };
+
+        match reference {
+            ReferenceId::Definition(id) => match self.definition(id).kind {
+                DefinitionKind::Function(func_id) => function_doc_comments(&func_id),
+                DefinitionKind::Global(_) => self
+                    .globals
+                    .keys()
+                    .find(|stmt_id| self.let_statement(stmt_id).ident().id == id)
+                    .map(global_doc_comments)
+                    .unwrap_or_default(),
+                _ => Vec::new(),
+            },
+            ReferenceId::Item(ModuleDefId::FunctionId(func_id)) => function_doc_comments(&func_id),
+            ReferenceId::Item(ModuleDefId::TypeId(struct_id)) => {
+                self.get_struct(struct_id).borrow().doc_comments.clone()
+            }
+            ReferenceId::Item(ModuleDefId::GlobalId(stmt_id)) => global_doc_comments(&stmt_id),
+            ReferenceId::Item(_) | ReferenceId::Field(..) => Vec::new(),
+        }
+    }
+
+    /// Returns the function an expression refers to, if it is the name of one.
+    pub fn referenced_function(&self, expr_id: ExprId) -> Option<FuncId> {
+        match self.nodes.get(expr_id.0) {
+            Some(Node::Expression(HirExpression::Ident(ident)))
+                if ident.id != DefinitionId::dummy_id() =>
+            {
+                match self.definition(ident.id).kind {
+                    DefinitionKind::Function(func_id) => Some(func_id),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the declared parameter and return types of a function with generics.
+    pub fn generic_function_type(&self, func_id: &FuncId) -> Option<(&[Type], &Type)> {
+        match &self.func_meta.get(func_id)?.typ {
+            Type::Forall(_, typ) => match typ.as_ref() {
+                Type::Function(parameters, return_type, _) => {
+                    Some((parameters.as_slice(), return_type.as_ref()))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the numeric generics of a generic function along with the types they were
+    /// instantiated with, if the given expression refers to one, sorted by the names of the
+    /// generics. The instantiated types are the type variables bound once they are inferred.
+    pub fn instantiated_numeric_generics(&self, expr_id: ExprId) -> Vec<(String, Type)> {
+        let func_id = match self.referenced_function(expr_id) {
+            Some(func_id) => func_id,
+            None => return Vec::new(),
+        };
+        let ((parameters, return_type), bindings) =
+            match (self.generic_function_type(&func_id), self.instantiation_bindings.get(&expr_id))
+            {
+                (Some(typ), Some(bindings)) => (typ, bindings),
+                _ => return Vec::new(),
+            };
+
+        let mut numeric_generics: Vec<_> = Resolver::find_numeric_generics(parameters, return_type)
+            .into_iter()
+            .filter_map(|(name, type_variable)| {
+                let id = match &*type_variable.borrow() {
+                    TypeBinding::Unbound(id) => *id,
+                    TypeBinding::Bound(_) => return None,
+                };
+                let (_, instantiated) = bindings.get(&id)?;
+                Some((name, instantiated.clone()))
+            })
+            .collect();
+        numeric_generics.sort_by(|(a, _), (b, _)| a.cmp(b));
+        numeric_generics
+    }
+
+    /// Follows the calls to a generic function up the call stack to find the value its numeric
+    /// generic `generic` is instantiated with. Returns each calling function along the way with
+    /// the generic of the function it calls and the value it is instantiated with there, which
+    /// is followed in turn while it is a generic of the calling function. Only the first call
+    /// to each function is followed.
+    pub fn numeric_generic_call_chain(
+        &self,
+        func_id: FuncId,
+        generic: &str,
+    ) -> Vec<(FuncId, String, Type)> {
+        let mut chain = Vec::new();
+        let mut visited = HashSet::new();
+        let (mut callee, mut generic) = (func_id, generic.to_owned());
+
+        while visited.insert((callee, generic.clone())) {
+            let call = self
+                .instantiation_bindings
+                .keys()
+                .filter(|expr_id| self.referenced_function(**expr_id) == Some(callee))
+                .filter_map(|expr_id| Some((*expr_id, *self.id_to_location.get(&expr_id.0)?)))
+                .min_by_key(|(_, location)| (location.file.as_usize(), location.span.start()));
+            let (call, location) = match call {
+                Some(call) => call,
+                None => break,
+            };
+            let caller = self
+                .func_meta
+                .iter()
+                .filter_map(|(id, meta)| Some((*id, self.function_extent(id, meta)?)))
+                .filter(|(_, extent)| {
+                    location_contains(extent, location.file, location.span.start())
+                })
+                .min_by_key(|(_, extent)| extent.span.end() - extent.span.start());
+            let (caller, _) = match caller {
+                Some(caller) => caller,
+                None => break,
+            };
+            let value = self
+                .instantiated_numeric_generics(call)
+                .into_iter()
+                .find(|(name, _)| name == &generic)
+                .map(|(_, value)| value.follow_bindings());
+            let value = match value {
+                Some(value) => value,
+                None => break,
+            };
+
+            let caller_generic = match &value {
+                Type::NamedGeneric(_, name) => Some(name.to_string()),
+                _ => None,
+            };
+            chain.push((caller, generic, value));
+            match caller_generic {
+                Some(caller_generic) => {
+                    callee = caller;
+                    generic = caller_generic;
+                }
+                None => break,
+            }
+        }
+        chain
+    }
+
     /// Add a method to a type.
     /// This will panic for non-struct types currently as we do not support methods
     /// for primitives. We could allow this in the future however.
@@ -589,11 +1317,44 @@ impl NodeInterner {
         self.struct_methods.get(&(id, method_name.to_owned())).copied()
     }
 
+    /// Returns the struct the given function is a method of, if it is one
+    pub fn method_struct(&self, method: FuncId) -> Option<StructId> {
+        self.struct_methods
+            .iter()
+            .find(|(_, id)| **id == method)
+            .map(|((struct_id, _), _)| *struct_id)
+    }
+
     /// Looks up a given method name on the given primitive type.
     pub fn lookup_primitive_method(&self, typ: &Type, method_name: &str) -> Option<FuncId> {
         get_type_method_key(typ)
             .and_then(|key| self.primitive_methods.get(&(key, method_name.to_owned())).copied())
     }
+
+    /// Search by name for a method of the traits with an impl for the given type, or for
+    /// another instance of the same generic type. Types with an impl for more than one of these
+    /// traits return one method for each trait.
+    pub fn lookup_trait_methods(&self, typ: &Type, method_name: &str) -> Vec<TraitMethodId> {
+        let mut methods: Vec<_> = self
+            .traits
+            .values()
+            .filter_map(|the_trait| {
+                let method_index = the_trait.find_method(method_name)?;
+                let has_impl = self.trait_impls(the_trait.id).iter().any(|trait_impl| {
+                    let impl_type = match &trait_impl.typ {
+                        Type::Forall(_, impl_type) => impl_type.as_ref(),
+                        impl_type => impl_type,
+                    };
+                    same_type_constructor(impl_type, typ)
+                });
+                has_impl.then_some(TraitMethodId { trait_id: the_trait.id, method_index })
+            })
+            .collect();
+
+        // Keep the order deterministic for error messages listing the candidates
+        methods.sort_by_key(|method| self.traits[&method.trait_id].name.0.contents.clone());
+        methods
+    }
 }
 
 /// These are the primitive type variants that we support adding methods to
@@ -611,6 +1372,15 @@ enum TypeMethodKey {
     Function,
 }
 
+/// True if both types are instances of the same struct, or share their primitive method key.
+fn same_type_constructor(a: &Type, b: &Type) -> bool {
+    match (a.follow_bindings(), b.follow_bindings()) {
+        (Type::Struct(a, _), Type::Struct(b, _)) => a.borrow().id == b.borrow().id,
+        (Type::Enum(a, _), Type::Enum(b, _)) => a.borrow().id == b.borrow().id,
+        (a, b) => get_type_method_key(&a).map_or(false, |key| get_type_method_key(&b) == Some(key)),
+    }
+}
+
 fn get_type_method_key(typ: &Type) -> Option<TypeMethodKey> {
     use TypeMethodKey::*;
     let typ = typ.follow_bindings();
@@ -624,15 +1394,22 @@ fn get_type_method_key(typ: &Type) -> Option<TypeMethodKey> {
         Type::String(_) => Some(String),
         Type::Unit => Some(Unit),
         Type::Tuple(_) => Some(Tuple),
-        Type::Function(_, _) => Some(Function),
+        Type::Function(..) => Some(Function),
         Type::MutableReference(element) => get_type_method_key(element),
 
         // We do not support adding methods to these types
         Type::TypeVariable(_)
         | Type::NamedGeneric(_, _)
         | Type::Forall(_, _)
+        | Type::FmtString(_, _)
         | Type::Constant(_)
+        | Type::InfixExpr(..)
         | Type::Error
-        | Type::Struct(_, _) => None,
+        | Type::Struct(_, _)
+        | Type::Enum(_, _) => None,
     }
 }
+
+fn location_contains(location: &Location, file: FileId, offset: u32) -> bool {
+    location.file == file && location.span.start() <= offset && offset <= location.span.end()
+}
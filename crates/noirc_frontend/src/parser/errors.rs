@@ -1,10 +1,12 @@
-use crate::lexer::token::Token;
+use crate::lexer::token::{Attribute, Token};
 use crate::Expression;
 use small_ord_set::SmallOrdSet;
 use thiserror::Error;
 
 use iter_extended::vecmap;
+use noirc_errors::registry::{self, ErrorCode};
 use noirc_errors::CustomDiagnostic as Diagnostic;
+use noirc_errors::Lint;
 use noirc_errors::Span;
 
 use super::labels::ParsingRuleLabel;
@@ -19,8 +21,10 @@ pub enum ParserErrorReason {
     ConstrainDeprecated,
     #[error("Expression is invalid in an array-length type: '{0}'. Only unsigned integer constants, globals, generics, +, -, *, /, and % may be used in this context.")]
     InvalidArrayLengthExpression(Expression),
-    #[error("Early 'return' is unsupported")]
-    EarlyReturn,
+    #[error("The attribute {0} cannot be used on a {1}")]
+    MisplacedAttribute(Attribute, &'static str),
+    #[error("#[cfg(..)] can only be used on top-level items, before any other attribute")]
+    MisplacedCfg,
 }
 
 /// Represents a parsing error, or a parsing error in the making.
@@ -66,6 +70,18 @@ impl ParserError {
         error.reason = Some(reason);
         error
     }
+
+    pub fn code(&self) -> ErrorCode {
+        match &self.reason {
+            None => registry::E0008,
+            Some(ParserErrorReason::ExpectedFieldName(_)) => registry::E0009,
+            Some(ParserErrorReason::MissingSeparatingSemi) => registry::E0010,
+            Some(ParserErrorReason::ConstrainDeprecated) => registry::E0011,
+            Some(ParserErrorReason::InvalidArrayLengthExpression(_)) => registry::E0012,
+            Some(ParserErrorReason::MisplacedAttribute(..)) => registry::E0013,
+            Some(ParserErrorReason::MisplacedCfg) => registry::E0014,
+        }
+    }
 }
 
 impl std::fmt::Display for ParserError {
@@ -95,14 +111,15 @@ impl std::fmt::Display for ParserError {
 
 impl From<ParserError> for Diagnostic {
     fn from(error: ParserError) -> Diagnostic {
-        match &error.reason {
+        let diagnostic = match &error.reason {
             Some(reason) => {
                 match reason {
                     ParserErrorReason::ConstrainDeprecated => Diagnostic::simple_warning(
                         "Use of deprecated keyword 'constrain'".into(),
                         "The 'constrain' keyword has been deprecated. Please use the 'assert' function instead.".into(),
                         error.span,
-                    ),
+                    )
+                    .with_lint(Lint::Deprecated),
                     other => {
 
                         Diagnostic::simple_error(format!("{other}"), String::new(), error.span)
@@ -113,7 +130,8 @@ impl From<ParserError> for Diagnostic {
                 let primary = error.to_string();
                 Diagnostic::simple_error(primary, String::new(), error.span)
             }
-        }
+        };
+        diagnostic.with_code(error.code())
     }
 }
 
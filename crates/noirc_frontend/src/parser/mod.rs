@@ -13,12 +13,12 @@ mod parser;
 
 use std::sync::atomic::{AtomicU32, Ordering};
 
-use crate::token::{Keyword, Token};
-use crate::{ast::ImportStatement, Expression, NoirStruct};
+use crate::token::{Attribute, Keyword, Token};
+use crate::{ast::ImportStatement, Expression, NoirEnum, NoirStruct, NoirTypeAlias};
 use crate::{
-    BlockExpression, ExpressionKind, ForExpression, Ident, IndexExpression, LetStatement,
-    MethodCallExpression, NoirFunction, NoirImpl, Path, PathKind, Pattern, Recoverable, Statement,
-    UnresolvedType, UseTree,
+    BlockExpression, Documented, ExpressionKind, ForExpression, Ident, IndexExpression,
+    ItemVisibility, LetStatement, MethodCallExpression, NoirFunction, NoirImpl, NoirTrait,
+    NoirTraitImpl, Path, PathKind, Pattern, Recoverable, Statement, UnresolvedType, UseTree,
 };
 
 use acvm::FieldElement;
@@ -37,15 +37,35 @@ static UNIQUE_NAME_COUNTER: AtomicU32 = AtomicU32::new(0);
 #[derive(Debug, Clone)]
 pub(crate) enum TopLevelStatement {
     Function(NoirFunction),
-    Module(Ident),
-    Import(UseTree),
+    Module(ItemVisibility, Documented<Ident>),
+    /// A `use` statement, along with whether it is marked `#[allow(unused)]`
+    Import(ItemVisibility, UseTree, bool),
     Struct(NoirStruct),
+    Enum(NoirEnum),
+    TypeAlias(NoirTypeAlias),
     Impl(NoirImpl),
+    Trait(NoirTrait),
+    TraitImpl(NoirTraitImpl),
     SubModule(SubModule),
-    Global(LetStatement),
+    Global(ItemVisibility, Documented<LetStatement>),
     Error,
 }
 
+impl TopLevelStatement {
+    /// Attaches the `///` doc comments written above this item to it. These are only retained
+    /// for functions, structs, globals and modules.
+    fn set_doc_comments(&mut self, doc_comments: Vec<String>) {
+        match self {
+            TopLevelStatement::Function(function) => function.def_mut().doc_comments = doc_comments,
+            TopLevelStatement::Struct(noir_struct) => noir_struct.doc_comments = doc_comments,
+            TopLevelStatement::Module(_, module) => module.doc_comments = doc_comments,
+            TopLevelStatement::SubModule(submodule) => submodule.doc_comments = doc_comments,
+            TopLevelStatement::Global(_, global) => global.doc_comments = doc_comments,
+            _ => (),
+        }
+    }
+}
+
 // Helper trait that gives us simpler type signatures for return types:
 // e.g. impl Parser<T> versus impl Parser<Token, T, Error = Simple<Token>>
 pub trait NoirParser<T>: Parser<Token, T, Error = ParserError> + Sized + Clone {}
@@ -220,14 +240,22 @@ pub struct ParsedModule {
     pub imports: Vec<ImportStatement>,
     pub functions: Vec<NoirFunction>,
     pub types: Vec<NoirStruct>,
+    pub enums: Vec<NoirEnum>,
+    pub type_aliases: Vec<NoirTypeAlias>,
     pub impls: Vec<NoirImpl>,
-    pub globals: Vec<LetStatement>,
+    pub traits: Vec<NoirTrait>,
+    pub trait_impls: Vec<NoirTraitImpl>,
+    pub globals: Vec<(ItemVisibility, Documented<LetStatement>)>,
 
     /// Module declarations like `mod foo;`
-    pub module_decls: Vec<Ident>,
+    pub module_decls: Vec<(ItemVisibility, Documented<Ident>)>,
 
     /// Full submodules as in `mod foo { ... definitions ... }`
     pub submodules: Vec<SubModule>,
+
+    /// Items marked with `#[cfg(feature = "..")]`, along with the features which must all be
+    /// enabled for them to be compiled. See [ParsedModule::enable_features].
+    pub(crate) conditional_items: Vec<(Vec<String>, TopLevelStatement)>,
 }
 
 /// A submodule defined via `mod name { contents }` in some larger file.
@@ -235,11 +263,45 @@ pub struct ParsedModule {
 #[derive(Clone, Debug)]
 pub struct SubModule {
     pub name: Ident,
+    pub visibility: ItemVisibility,
     pub contents: ParsedModule,
     pub is_contract: bool,
+    /// The lines of the `///` doc comments written above the submodule
+    pub doc_comments: Vec<String>,
 }
 
 impl ParsedModule {
+    /// Adds each conditional item of this module whose features are all enabled to the
+    /// module's items, discarding any other conditional items.
+    pub fn enable_features(&mut self, is_enabled: impl Fn(&str) -> bool) {
+        for (features, item) in std::mem::take(&mut self.conditional_items) {
+            if features.iter().all(|feature| is_enabled(feature)) {
+                self.push_statement(item);
+            }
+        }
+    }
+
+    fn push_statement(&mut self, statement: TopLevelStatement) {
+        match statement {
+            TopLevelStatement::Function(f) => self.push_function(f),
+            TopLevelStatement::Module(v, m) => self.push_module_decl(v, m),
+            TopLevelStatement::Import(v, i, allow_unused) => self.push_import(v, i, allow_unused),
+            TopLevelStatement::Struct(s) => self.push_type(s),
+            TopLevelStatement::Enum(e) => self.push_enum(e),
+            TopLevelStatement::TypeAlias(t) => self.push_type_alias(t),
+            TopLevelStatement::Impl(i) => self.push_impl(i),
+            TopLevelStatement::Trait(t) => self.push_trait(t),
+            TopLevelStatement::TraitImpl(i) => self.push_trait_impl(i),
+            TopLevelStatement::SubModule(s) => self.push_submodule(s),
+            TopLevelStatement::Global(v, c) => self.push_global(v, c),
+            TopLevelStatement::Error => (),
+        }
+    }
+
+    fn push_conditional_statement(&mut self, features: Vec<String>, statement: TopLevelStatement) {
+        self.conditional_items.push((features, statement));
+    }
+
     fn push_function(&mut self, func: NoirFunction) {
         self.functions.push(func);
     }
@@ -248,24 +310,49 @@ impl ParsedModule {
         self.types.push(typ);
     }
 
+    fn push_enum(&mut self, noir_enum: NoirEnum) {
+        self.enums.push(noir_enum);
+    }
+
+    fn push_type_alias(&mut self, type_alias: NoirTypeAlias) {
+        self.type_aliases.push(type_alias);
+    }
+
     fn push_impl(&mut self, r#impl: NoirImpl) {
         self.impls.push(r#impl);
     }
 
-    fn push_import(&mut self, import_stmt: UseTree) {
-        self.imports.extend(import_stmt.desugar(None));
+    fn push_trait(&mut self, noir_trait: NoirTrait) {
+        self.traits.push(noir_trait);
     }
 
-    fn push_module_decl(&mut self, mod_name: Ident) {
-        self.module_decls.push(mod_name);
+    fn push_trait_impl(&mut self, trait_impl: NoirTraitImpl) {
+        self.trait_impls.push(trait_impl);
+    }
+
+    fn push_import(
+        &mut self,
+        visibility: ItemVisibility,
+        import_stmt: UseTree,
+        allow_unused: bool,
+    ) {
+        self.imports.extend(import_stmt.desugar(None).into_iter().map(|mut import| {
+            import.allow_unused = allow_unused;
+            import.visibility = visibility;
+            import
+        }));
+    }
+
+    fn push_module_decl(&mut self, visibility: ItemVisibility, mod_name: Documented<Ident>) {
+        self.module_decls.push((visibility, mod_name));
     }
 
     fn push_submodule(&mut self, submodule: SubModule) {
         self.submodules.push(submodule);
     }
 
-    fn push_global(&mut self, global: LetStatement) {
-        self.globals.push(global);
+    fn push_global(&mut self, visibility: ItemVisibility, global: Documented<LetStatement>) {
+        self.globals.push((visibility, global));
     }
 }
 
@@ -362,9 +449,37 @@ impl ForRange {
     ///         ...
     ///     }
     /// }
-    fn into_for(self, identifier: Ident, block: Expression, for_loop_span: Span) -> ExpressionKind {
+    ///
+    /// A loop binding a pattern rather than a single identifier, e.g. `for (a, b) in array`,
+    /// binds it at the start of each iteration: `let (a, b) = fresh1[fresh2];`.
+    fn into_for(self, pattern: Pattern, block: Expression, for_loop_span: Span) -> ExpressionKind {
         match self {
             ForRange::Range(start_range, end_range) => {
+                let (identifier, block) = match pattern {
+                    Pattern::Identifier(identifier) => (identifier, block),
+                    pattern => {
+                        // for fresh in start .. end { let pattern = fresh; ... }
+                        let span = pattern.span();
+                        let next_unique_id = UNIQUE_NAME_COUNTER.fetch_add(1, Ordering::Relaxed);
+                        let index_name = format!("$i{next_unique_id}");
+                        let identifier = Ident::new(index_name.clone(), span);
+
+                        let segments = vec![Ident::new(index_name, span)];
+                        let index =
+                            ExpressionKind::Variable(Path { segments, kind: PathKind::Plain });
+                        let let_pattern = Statement::Let(LetStatement {
+                            pattern,
+                            r#type: UnresolvedType::Unspecified,
+                            expression: Expression::new(index, span),
+                        });
+
+                        let block_span = block.span;
+                        let block =
+                            BlockExpression(vec![let_pattern, Statement::Expression(block)]);
+                        (identifier, Expression::new(ExpressionKind::Block(block), block_span))
+                    }
+                };
+
                 ExpressionKind::For(Box::new(ForExpression {
                     identifier,
                     start_range,
@@ -417,7 +532,7 @@ impl ForRange {
 
                 // let elem = array[i];
                 let let_elem = Statement::Let(LetStatement {
-                    pattern: Pattern::Identifier(identifier),
+                    pattern,
                     r#type: UnresolvedType::Unspecified,
                     expression: Expression::new(loop_element, array_span),
                 });
@@ -445,12 +560,22 @@ impl std::fmt::Display for TopLevelStatement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             TopLevelStatement::Function(fun) => fun.fmt(f),
-            TopLevelStatement::Module(m) => write!(f, "mod {m}"),
-            TopLevelStatement::Import(tree) => write!(f, "use {tree}"),
+            TopLevelStatement::Module(visibility, m) => write!(f, "{visibility}mod {}", m.item),
+            TopLevelStatement::Import(visibility, tree, false) => {
+                write!(f, "{visibility}use {tree}")
+            }
+            TopLevelStatement::Import(visibility, tree, true) => {
+                writeln!(f, "{}", Attribute::AllowUnused)?;
+                write!(f, "{visibility}use {tree}")
+            }
             TopLevelStatement::Struct(s) => s.fmt(f),
+            TopLevelStatement::Enum(e) => e.fmt(f),
+            TopLevelStatement::TypeAlias(t) => t.fmt(f),
             TopLevelStatement::Impl(i) => i.fmt(f),
+            TopLevelStatement::Trait(t) => t.fmt(f),
+            TopLevelStatement::TraitImpl(i) => i.fmt(f),
             TopLevelStatement::SubModule(s) => s.fmt(f),
-            TopLevelStatement::Global(c) => c.fmt(f),
+            TopLevelStatement::Global(visibility, c) => write!(f, "{visibility}{}", c.item),
             TopLevelStatement::Error => write!(f, "error"),
         }
     }
@@ -458,22 +583,30 @@ impl std::fmt::Display for TopLevelStatement {
 
 impl std::fmt::Display for ParsedModule {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for decl in &self.module_decls {
-            writeln!(f, "mod {decl};")?;
+        for (visibility, decl) in &self.module_decls {
+            writeln!(f, "{visibility}mod {};", decl.item)?;
         }
 
         for import in &self.imports {
             write!(f, "{import}")?;
         }
 
-        for global_const in &self.globals {
-            write!(f, "{global_const}")?;
+        for (visibility, global_const) in &self.globals {
+            write!(f, "{visibility}{}", global_const.item)?;
         }
 
         for type_ in &self.types {
             write!(f, "{type_}")?;
         }
 
+        for enum_ in &self.enums {
+            write!(f, "{enum_}")?;
+        }
+
+        for type_alias in &self.type_aliases {
+            write!(f, "{type_alias}")?;
+        }
+
         for function in &self.functions {
             write!(f, "{function}")?;
         }
@@ -482,17 +615,32 @@ impl std::fmt::Display for ParsedModule {
             write!(f, "{impl_}")?;
         }
 
+        for trait_ in &self.traits {
+            write!(f, "{trait_}")?;
+        }
+
+        for trait_impl in &self.trait_impls {
+            write!(f, "{trait_impl}")?;
+        }
+
         for submodule in &self.submodules {
             write!(f, "{submodule}")?;
         }
 
+        for (features, item) in &self.conditional_items {
+            for feature in features {
+                writeln!(f, "{}", Attribute::Cfg(feature.clone()))?;
+            }
+            write!(f, "{item}")?;
+        }
+
         Ok(())
     }
 }
 
 impl std::fmt::Display for SubModule {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "mod {} {{", self.name)?;
+        write!(f, "{}mod {} {{", self.visibility, self.name)?;
 
         for line in self.contents.to_string().lines() {
             write!(f, "\n    {line}")?;
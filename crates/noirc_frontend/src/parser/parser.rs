@@ -32,11 +32,13 @@ use super::{
 use crate::ast::{Expression, ExpressionKind, LetStatement, Statement, UnresolvedType};
 use crate::lexer::Lexer;
 use crate::parser::{force, ignore_then_commit, statement_recovery};
-use crate::token::{Attribute, Keyword, Token, TokenKind};
+use crate::token::{Attribute, Keyword, SpannedToken, Token, TokenKind, Tokens};
 use crate::{
-    BinaryOp, BinaryOpKind, BlockExpression, CompTime, ConstrainStatement, FunctionDefinition,
-    Ident, IfExpression, InfixExpression, LValue, Lambda, NoirFunction, NoirImpl, NoirStruct, Path,
-    PathKind, Pattern, Recoverable, UnaryOp, UnresolvedTypeExpression, UseTree, UseTreeKind,
+    BinaryOp, BinaryOpKind, BlockExpression, CompTime, ConstrainKind, ConstrainStatement,
+    Documented, FunctionDefinition, Ident, IfExpression, InfixExpression, ItemVisibility, LValue,
+    Lambda, MatchExpression, MatchPattern, NoirEnum, NoirFunction, NoirImpl, NoirStruct, NoirTrait,
+    NoirTraitImpl, NoirTypeAlias, Path, PathKind, Pattern, Recoverable, TraitConstraint,
+    TraitFunction, UnaryOp, UnresolvedTypeExpression, UseTree, UseTreeKind, WhileExpression,
 };
 
 use chumsky::prelude::*;
@@ -54,12 +56,72 @@ pub fn parse_program(source_program: &str) -> (ParsedModule, Vec<CustomDiagnosti
     let (tokens, lexing_errors) = Lexer::lex(source_program);
     let mut errors = vecmap(lexing_errors, Into::into);
 
+    let tokens = remove_detached_doc_comments(tokens);
     let (module, parsing_errors) = program().parse_recovery_verbose(tokens);
     errors.extend(parsing_errors.into_iter().map(Into::into));
 
     (module.unwrap(), errors)
 }
 
+/// Doc comments are only retained above items, before any attributes of the item. Doc comments
+/// anywhere else are removed from the tokens, treating them like ordinary comments rather than
+/// requiring every rule of the grammar to allow them.
+fn remove_detached_doc_comments(tokens: Tokens) -> Tokens {
+    let tokens = tokens.0;
+    let mut retained = Vec::with_capacity(tokens.len());
+
+    let mut index = 0;
+    while index < tokens.len() {
+        if !matches!(tokens[index].token(), Token::DocComment(_)) {
+            retained.push(tokens[index].clone());
+            index += 1;
+            continue;
+        }
+
+        let end = tokens[index..]
+            .iter()
+            .position(|token| !matches!(token.token(), Token::DocComment(_)))
+            .map_or(tokens.len(), |length| index + length);
+
+        let follows_attribute =
+            index > 0 && matches!(tokens[index - 1].token(), Token::Attribute(_));
+        let item_start = tokens[end..]
+            .iter()
+            .map(SpannedToken::token)
+            .find(|token| !matches!(token, Token::Attribute(_)));
+
+        if !follows_attribute && item_start.map_or(false, starts_item) {
+            retained.extend_from_slice(&tokens[index..end]);
+        }
+        index = end;
+    }
+
+    Tokens(retained)
+}
+
+/// True if the token can begin a top-level item or a method, which doc comments may be
+/// written above.
+fn starts_item(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Keyword(
+            Keyword::Contract
+                | Keyword::Enum
+                | Keyword::Fn
+                | Keyword::Global
+                | Keyword::Impl
+                | Keyword::Mod
+                | Keyword::Open
+                | Keyword::Pub
+                | Keyword::Struct
+                | Keyword::Trait
+                | Keyword::Type
+                | Keyword::Unconstrained
+                | Keyword::Use
+        )
+    )
+}
+
 /// program: module EOF
 fn program() -> impl NoirParser<ParsedModule> {
     module().then_ignore(force(just(Token::EOF)))
@@ -71,17 +133,18 @@ fn module() -> impl NoirParser<ParsedModule> {
     recursive(|module_parser| {
         empty()
             .map(|_| ParsedModule::default())
-            .then(top_level_statement(module_parser).repeated())
-            .foldl(|mut program, statement| {
-                match statement {
-                    TopLevelStatement::Function(f) => program.push_function(f),
-                    TopLevelStatement::Module(m) => program.push_module_decl(m),
-                    TopLevelStatement::Import(i) => program.push_import(i),
-                    TopLevelStatement::Struct(s) => program.push_type(s),
-                    TopLevelStatement::Impl(i) => program.push_impl(i),
-                    TopLevelStatement::SubModule(s) => program.push_submodule(s),
-                    TopLevelStatement::Global(c) => program.push_global(c),
-                    TopLevelStatement::Error => (),
+            .then(
+                doc_comments()
+                    .then(cfg_attributes())
+                    .then(top_level_statement(module_parser))
+                    .repeated(),
+            )
+            .foldl(|mut program, ((doc_comments, features), mut statement)| {
+                statement.set_doc_comments(doc_comments);
+                if features.is_empty() {
+                    program.push_statement(statement);
+                } else {
+                    program.push_conditional_statement(features, statement);
                 }
                 program
             })
@@ -90,6 +153,10 @@ fn module() -> impl NoirParser<ParsedModule> {
 
 /// top_level_statement: function_definition
 ///                    | struct_definition
+///                    | enum_definition
+///                    | type_alias_definition
+///                    | trait_definition
+///                    | trait_implementation
 ///                    | implementation
 ///                    | submodule
 ///                    | module_declaration
@@ -101,6 +168,10 @@ fn top_level_statement(
     choice((
         function_definition(false).map(TopLevelStatement::Function),
         struct_definition(),
+        enum_definition(),
+        type_alias_definition().then_ignore(force(just(Token::Semicolon))),
+        trait_definition(),
+        trait_implementation(),
         implementation(),
         submodule(module_parser.clone()),
         contract(module_parser),
@@ -111,69 +182,165 @@ fn top_level_statement(
     .recover_via(top_level_statement_recovery())
 }
 
-/// global_declaration: 'global' ident global_type_annotation '=' literal
+/// doc_comments: ( '///' text )*
+///
+/// The lines of the doc comments written above an item.
+fn doc_comments() -> impl NoirParser<Vec<String>> {
+    filter_map(|span, token: Token| match token {
+        Token::DocComment(comment) => Ok(comment),
+        other => {
+            let label = ParsingRuleLabel::TokenKind(TokenKind::DocComment);
+            Err(ParserError::expected_label(label, other, span))
+        }
+    })
+    .repeated()
+}
+
+/// cfg_attributes: ( '#[cfg(feature = "' ident '")]' )*
+///
+/// The features which must all be enabled for the following item to be compiled.
+fn cfg_attributes() -> impl NoirParser<Vec<String>> {
+    filter_map(|span, token: Token| match token {
+        Token::Attribute(Attribute::Cfg(feature)) => Ok(feature),
+        other => {
+            let label = ParsingRuleLabel::TokenKind(TokenKind::Attribute);
+            Err(ParserError::expected_label(label, other, span))
+        }
+    })
+    .repeated()
+}
+
+/// allow_unused_attribute: ( '#[allow(unused)]' )*
+///
+/// Whether the following item is marked `#[allow(unused)]`.
+fn allow_unused_attribute() -> impl NoirParser<bool> {
+    just(Token::Attribute(Attribute::AllowUnused))
+        .repeated()
+        .map(|attributes| !attributes.is_empty())
+}
+
+/// global_declaration: item_visibility 'global' ident global_type_annotation '=' expression
 fn global_declaration() -> impl NoirParser<TopLevelStatement> {
-    let p = ignore_then_commit(
+    let p = item_visibility().then(ignore_then_commit(
         keyword(Keyword::Global).labelled(ParsingRuleLabel::Global),
         ident().map(Pattern::Identifier),
-    );
+    ));
     let p = then_commit(p, global_type_annotation());
     let p = then_commit_ignore(p, just(Token::Assign));
-    let p = then_commit(p, literal_or_collection(expression()).map_with_span(Expression::new));
-    p.map(LetStatement::new_let).map(TopLevelStatement::Global)
+    let p = then_commit(p, expression());
+    p.map(|(((visibility, pattern), typ), expression)| {
+        let global = LetStatement::new_let(((pattern, typ), expression));
+        TopLevelStatement::Global(visibility, Documented::new(global, Vec::new()))
+    })
 }
 
-/// submodule: 'mod' ident '{' module '}'
+/// submodule: item_visibility 'mod' ident '{' module '}'
 fn submodule(module_parser: impl NoirParser<ParsedModule>) -> impl NoirParser<TopLevelStatement> {
-    keyword(Keyword::Mod)
-        .ignore_then(ident())
+    item_visibility()
+        .then_ignore(keyword(Keyword::Mod))
+        .then(ident())
         .then_ignore(just(Token::LeftBrace))
         .then(module_parser)
         .then_ignore(just(Token::RightBrace))
-        .map(|(name, contents)| {
-            TopLevelStatement::SubModule(SubModule { name, contents, is_contract: false })
+        .map(|((visibility, name), contents)| {
+            TopLevelStatement::SubModule(SubModule {
+                name,
+                visibility,
+                contents,
+                is_contract: false,
+                doc_comments: Vec::new(),
+            })
         })
 }
 
-/// contract: 'contract' ident '{' module '}'
+/// contract: item_visibility 'contract' ident '{' module '}'
 fn contract(module_parser: impl NoirParser<ParsedModule>) -> impl NoirParser<TopLevelStatement> {
-    keyword(Keyword::Contract)
-        .ignore_then(ident())
+    item_visibility()
+        .then_ignore(keyword(Keyword::Contract))
+        .then(ident())
         .then_ignore(just(Token::LeftBrace))
         .then(module_parser)
         .then_ignore(just(Token::RightBrace))
-        .map(|(name, contents)| {
-            TopLevelStatement::SubModule(SubModule { name, contents, is_contract: true })
+        .map(|((visibility, name), contents)| {
+            TopLevelStatement::SubModule(SubModule {
+                name,
+                visibility,
+                contents,
+                is_contract: true,
+                doc_comments: Vec::new(),
+            })
         })
 }
 
-/// function_definition: attribute function_modifiers 'fn' ident generics '(' function_parameters ')' function_return_type block
-///                      function_modifiers 'fn' ident generics '(' function_parameters ')' function_return_type block
+/// function_definition: allow_unused_attribute attribute allow_unused_attribute item_visibility function_modifiers 'fn' ident function_generics '(' function_parameters ')' function_return_type where_clause block
+///                      allow_unused_attribute item_visibility function_modifiers 'fn' ident function_generics '(' function_parameters ')' function_return_type where_clause block
 fn function_definition(allow_self: bool) -> impl NoirParser<NoirFunction> {
-    attribute()
-        .or_not()
+    allow_unused_attribute()
+        .then(
+            attribute()
+                .validate(|attribute, span, emit| match attribute {
+                    Attribute::Derive(_) => {
+                        let reason = ParserErrorReason::MisplacedAttribute(attribute, "function");
+                        emit(ParserError::with_reason(reason, span));
+                        None
+                    }
+                    Attribute::Cfg(_) => {
+                        emit(ParserError::with_reason(ParserErrorReason::MisplacedCfg, span));
+                        None
+                    }
+                    attribute => Some(attribute),
+                })
+                .or_not()
+                .map(Option::flatten),
+        )
+        .then(allow_unused_attribute())
+        .map(|((allow_before, attribute), allow_after)| (attribute, allow_before || allow_after))
+        .then(item_visibility())
         .then(function_modifiers())
         .then_ignore(keyword(Keyword::Fn))
         .then(ident())
-        .then(generics())
+        .then(function_generics())
         .then(parenthesized(function_parameters(allow_self)))
         .then(function_return_type())
+        .then(where_clause())
         .then(block(expression()))
-        .map(
+        .map_with_span(
             |(
                 (
-                    ((((attribute, (is_unconstrained, is_open)), name), generics), parameters),
-                    ((return_distinctness, return_visibility), return_type),
+                    (
+                        (
+                            (
+                                (
+                                    (
+                                        ((attribute, allow_unused), visibility),
+                                        (is_unconstrained, is_open),
+                                    ),
+                                    name,
+                                ),
+                                (generics, mut trait_constraints),
+                            ),
+                            parameters,
+                        ),
+                        ((return_distinctness, return_visibility), return_type),
+                    ),
+                    where_constraints,
                 ),
                 body,
-            )| {
+            ),
+             full_span| {
+                trait_constraints.extend(where_constraints);
                 FunctionDefinition {
                     span: name.0.span(),
+                    full_span,
                     name,
+                    visibility,
+                    doc_comments: Vec::new(),
                     attribute, // XXX: Currently we only have one attribute defined. If more attributes are needed per function, we can make this a vector and make attribute definition more expressive
+                    allow_unused,
                     is_open,
                     is_unconstrained,
                     generics,
+                    trait_constraints,
                     parameters,
                     body,
                     return_type,
@@ -185,6 +352,20 @@ fn function_definition(allow_self: bool) -> impl NoirParser<NoirFunction> {
         )
 }
 
+/// item_visibility: 'pub' '(' 'crate' ')' | 'pub' | %empty
+fn item_visibility() -> impl NoirParser<ItemVisibility> {
+    let crate_restriction =
+        keyword(Keyword::Crate).delimited_by(just(Token::LeftParen), just(Token::RightParen));
+
+    keyword(Keyword::Pub).ignore_then(crate_restriction.or_not()).or_not().map(|visibility| {
+        match visibility {
+            None => ItemVisibility::Private,
+            Some(None) => ItemVisibility::Public,
+            Some(Some(_)) => ItemVisibility::PublicCrate,
+        }
+    })
+}
+
 /// function_modifiers: 'unconstrained' 'open' | 'unconstrained' | 'open' | %empty
 ///
 /// returns (is_unconstrained, is_open) for whether each keyword was present
@@ -210,6 +391,65 @@ fn generics() -> impl NoirParser<Vec<Ident>> {
         .map(|opt| opt.unwrap_or_default())
 }
 
+/// trait_bounds: path '+' trait_bounds
+///             | path
+///
+/// function_generic: ident ':' trait_bounds
+///                 | ident
+///
+/// function_generics: '<' function_generic (',' function_generic)* '>'
+///                  | %empty
+///
+/// Returns the generics along with the traits each of them is constrained to implement.
+fn function_generics() -> impl NoirParser<(Vec<Ident>, Vec<TraitConstraint>)> {
+    let generic = ident().then(just(Token::Colon).ignore_then(trait_bounds()).or_not());
+
+    generic
+        .separated_by(just(Token::Comma))
+        .allow_trailing()
+        .at_least(1)
+        .delimited_by(just(Token::Less), just(Token::Greater))
+        .or_not()
+        .map(|generics| {
+            let mut trait_constraints = Vec::new();
+            let generics = vecmap(generics.unwrap_or_default(), |(generic, trait_bounds)| {
+                for trait_name in trait_bounds.unwrap_or_default() {
+                    let constraint = TraitConstraint { generic: generic.clone(), trait_name };
+                    trait_constraints.push(constraint);
+                }
+                generic
+            });
+            (generics, trait_constraints)
+        })
+}
+
+fn trait_bounds() -> impl NoirParser<Vec<Path>> {
+    path().separated_by(just(Token::Plus)).at_least(1)
+}
+
+/// where_constraint: ident ':' trait_bounds
+///
+/// where_clause: 'where' where_constraint (',' where_constraint)*
+///             | %empty
+fn where_clause() -> impl NoirParser<Vec<TraitConstraint>> {
+    let constraint = ident().then_ignore(just(Token::Colon)).then(trait_bounds());
+
+    keyword(Keyword::Where)
+        .ignore_then(constraint.separated_by(just(Token::Comma)).allow_trailing().at_least(1))
+        .or_not()
+        .map(|constraints| {
+            let constraints = constraints.unwrap_or_default().into_iter();
+            constraints
+                .flat_map(|(generic, trait_bounds)| {
+                    trait_bounds.into_iter().map(move |trait_name| TraitConstraint {
+                        generic: generic.clone(),
+                        trait_name,
+                    })
+                })
+                .collect()
+        })
+}
+
 fn struct_definition() -> impl NoirParser<TopLevelStatement> {
     use self::Keyword::Struct;
     use Token::*;
@@ -223,13 +463,107 @@ fn struct_definition() -> impl NoirParser<TopLevelStatement> {
         ),
     );
 
-    keyword(Struct).ignore_then(ident()).then(generics()).then(fields).map_with_span(
-        |((name, generics), fields), span| {
-            TopLevelStatement::Struct(NoirStruct { name, generics, fields, span })
+    struct_attributes()
+        .then(item_visibility())
+        .then_ignore(keyword(Struct))
+        .then(ident())
+        .then(generics())
+        .then(fields)
+        .map_with_span(|(((((derives, deprecated), visibility), name), generics), fields), span| {
+            TopLevelStatement::Struct(NoirStruct {
+                name,
+                visibility,
+                derives,
+                deprecated,
+                doc_comments: Vec::new(),
+                generics,
+                fields,
+                span,
+            })
+        })
+}
+
+/// struct_attributes: struct_attribute*
+///
+/// struct_attribute: '#[derive(' ident (',' ident)* ')]'
+///                 | '#[deprecated' ( '("' message '")' )? ']'
+fn struct_attributes() -> impl NoirParser<(Vec<Ident>, Option<Option<String>>)> {
+    attribute().map_with_span(|attribute, span| (attribute, span)).repeated().validate(
+        |attributes, _, emit| {
+            let mut derives = Vec::new();
+            let mut deprecated = None;
+
+            for (attribute, span) in attributes {
+                match attribute {
+                    Attribute::Derive(traits) => {
+                        derives.extend(traits.into_iter().map(|name| Ident::new(name, span)));
+                    }
+                    Attribute::Deprecated(note) => deprecated = Some(note),
+                    Attribute::Cfg(_) => {
+                        emit(ParserError::with_reason(ParserErrorReason::MisplacedCfg, span));
+                    }
+                    attribute => {
+                        let reason = ParserErrorReason::MisplacedAttribute(attribute, "struct");
+                        emit(ParserError::with_reason(reason, span));
+                    }
+                }
+            }
+
+            (derives, deprecated)
         },
     )
 }
 
+/// enum_definition: 'enum' ident generics '{' enum_variants '}'
+///
+/// enum_variants: ident ( '(' type, ... ')' )? , ...
+fn enum_definition() -> impl NoirParser<TopLevelStatement> {
+    use self::Keyword::Enum;
+    use Token::*;
+
+    let variant_fields = parse_type()
+        .separated_by(just(Comma))
+        .allow_trailing()
+        .delimited_by(just(LeftParen), just(RightParen))
+        .or_not()
+        .map(Option::unwrap_or_default);
+
+    let variants = ident()
+        .then(variant_fields)
+        .separated_by(just(Comma))
+        .allow_trailing()
+        .delimited_by(just(LeftBrace), just(RightBrace))
+        .recover_with(nested_delimiters(
+            LeftBrace,
+            RightBrace,
+            [(LeftParen, RightParen), (LeftBracket, RightBracket)],
+            |_| vec![],
+        ));
+
+    item_visibility()
+        .then_ignore(keyword(Enum))
+        .then(ident())
+        .then(generics())
+        .then(variants)
+        .map_with_span(|(((visibility, name), generics), variants), span| {
+            TopLevelStatement::Enum(NoirEnum { name, visibility, generics, variants, span })
+        })
+}
+
+/// type_alias_definition: item_visibility 'type' ident generics '=' type
+fn type_alias_definition() -> impl NoirParser<TopLevelStatement> {
+    use self::Keyword::Type;
+
+    let p = item_visibility().then(ignore_then_commit(keyword(Type), ident()));
+    let p = then_commit(p, generics());
+    let p = then_commit_ignore(p, just(Token::Assign));
+    let p = then_commit(p, parse_type());
+
+    p.map_with_span(|(((visibility, name), generics), typ), span| {
+        TopLevelStatement::TypeAlias(NoirTypeAlias { name, visibility, generics, typ, span })
+    })
+}
+
 fn lambda_return_type() -> impl NoirParser<UnresolvedType> {
     just(Token::Arrow)
         .ignore_then(parse_type())
@@ -344,13 +678,69 @@ fn implementation() -> impl NoirParser<TopLevelStatement> {
         .ignore_then(generics())
         .then(parse_type().map_with_span(|typ, span| (typ, span)))
         .then_ignore(just(Token::LeftBrace))
-        .then(function_definition(true).repeated())
+        .then(method_definition().repeated())
         .then_ignore(just(Token::RightBrace))
         .map(|((generics, (object_type, type_span)), methods)| {
             TopLevelStatement::Impl(NoirImpl { generics, object_type, type_span, methods })
         })
 }
 
+/// method_definition: doc_comments function_definition
+fn method_definition() -> impl NoirParser<NoirFunction> {
+    doc_comments().then(function_definition(true)).map(|(doc_comments, mut method)| {
+        method.def_mut().doc_comments = doc_comments;
+        method
+    })
+}
+
+/// trait_definition: item_visibility 'trait' ident '{' trait_function* '}'
+fn trait_definition() -> impl NoirParser<TopLevelStatement> {
+    item_visibility()
+        .then_ignore(keyword(Keyword::Trait))
+        .then(ident())
+        .then_ignore(just(Token::LeftBrace))
+        .then(trait_function().repeated())
+        .then_ignore(just(Token::RightBrace))
+        .map_with_span(|((visibility, name), methods), span| {
+            TopLevelStatement::Trait(NoirTrait { name, visibility, methods, span })
+        })
+}
+
+/// trait_function: doc_comments 'fn' ident '(' function_parameters ')' function_return_type ';'
+fn trait_function() -> impl NoirParser<TraitFunction> {
+    doc_comments()
+        .ignore_then(keyword(Keyword::Fn))
+        .ignore_then(ident())
+        .then(parenthesized(function_parameters(true)))
+        .then(function_return_type().map(|(_, return_type)| return_type))
+        .then_ignore(just(Token::Semicolon))
+        .map(|((name, parameters), return_type)| {
+            let parameters = vecmap(parameters, |(pattern, typ, _)| (pattern, typ));
+            TraitFunction { name, parameters, return_type }
+        })
+}
+
+/// trait_implementation: 'impl' generics path 'for' type '{' method_definition* '}'
+fn trait_implementation() -> impl NoirParser<TopLevelStatement> {
+    keyword(Keyword::Impl)
+        .ignore_then(generics())
+        .then(path())
+        .then_ignore(keyword(Keyword::For))
+        .then(parse_type().map_with_span(|typ, span| (typ, span)))
+        .then_ignore(just(Token::LeftBrace))
+        .then(method_definition().repeated())
+        .then_ignore(just(Token::RightBrace))
+        .map(|(((generics, trait_name), (object_type, type_span)), methods)| {
+            TopLevelStatement::TraitImpl(NoirTraitImpl {
+                trait_name,
+                object_type,
+                type_span,
+                generics,
+                methods,
+            })
+        })
+}
+
 fn block_expr<'a, P>(expr_parser: P) -> impl NoirParser<Expression> + 'a
 where
     P: ExprParser + 'a,
@@ -412,11 +802,20 @@ fn optional_type_annotation<'a>() -> impl NoirParser<UnresolvedType> + 'a {
 }
 
 fn module_declaration() -> impl NoirParser<TopLevelStatement> {
-    keyword(Keyword::Mod).ignore_then(ident()).map(TopLevelStatement::Module)
+    item_visibility().then_ignore(keyword(Keyword::Mod)).then(ident()).map(|(visibility, name)| {
+        TopLevelStatement::Module(visibility, Documented::new(name, Vec::new()))
+    })
 }
 
+/// use_statement: allow_unused_attribute item_visibility 'use' use_tree
 fn use_statement() -> impl NoirParser<TopLevelStatement> {
-    keyword(Keyword::Use).ignore_then(use_tree()).map(TopLevelStatement::Import)
+    allow_unused_attribute()
+        .then(item_visibility())
+        .then_ignore(keyword(Keyword::Use))
+        .then(use_tree())
+        .map(|((allow_unused, visibility), tree)| {
+            TopLevelStatement::Import(visibility, tree, allow_unused)
+        })
 }
 
 fn keyword(keyword: Keyword) -> impl NoirParser<Token> {
@@ -495,6 +894,7 @@ where
     choice((
         constrain(expr_parser.clone()),
         assertion(expr_parser.clone()),
+        assertion_eq(expr_parser.clone()),
         declaration(expr_parser.clone()),
         assignment(expr_parser.clone()),
         return_statement(expr_parser.clone()),
@@ -510,7 +910,7 @@ where
         keyword(Keyword::Constrain).labelled(ParsingRuleLabel::Statement),
         expr_parser,
     )
-    .map(|expr| Statement::Constrain(ConstrainStatement(expr)))
+    .map(|expr| Statement::Constrain(ConstrainStatement(expr, None, ConstrainKind::Constrain)))
     .validate(|expr, span, emit| {
         emit(ParserError::with_reason(ParserErrorReason::ConstrainDeprecated, span));
         expr
@@ -521,9 +921,43 @@ fn assertion<'a, P>(expr_parser: P) -> impl NoirParser<Statement> + 'a
 where
     P: ExprParser + 'a,
 {
-    ignore_then_commit(keyword(Keyword::Assert), parenthesized(expr_parser))
+    let message = just(Token::Comma).ignore_then(expr_parser.clone()).or_not();
+    let arguments = expr_parser
+        .then(message)
+        .map(|(expr, message)| ConstrainStatement(expr, message, ConstrainKind::Assert));
+
+    ignore_then_commit(keyword(Keyword::Assert), parenthesized(arguments))
         .labelled(ParsingRuleLabel::Statement)
-        .map(|expr| Statement::Constrain(ConstrainStatement(expr)))
+        .map(Statement::Constrain)
+}
+
+/// `assert_eq(lhs, rhs)` is parsed as an assertion that `lhs == rhs`. `assert_eq` is not a
+/// keyword, so that it may still name functions and variables, and is only parsed as an assertion
+/// when a statement starts by calling it.
+fn assertion_eq<'a, P>(expr_parser: P) -> impl NoirParser<Statement> + 'a
+where
+    P: ExprParser + 'a,
+{
+    let message = just(Token::Comma).ignore_then(expr_parser.clone()).or_not();
+    let arguments = expr_parser
+        .clone()
+        .then_ignore(just(Token::Comma))
+        .then(expr_parser)
+        .map_with_span(|(lhs, rhs), span| {
+            let operator = Spanned::from(span, BinaryOpKind::Equal);
+            let infix = Box::new(InfixExpression { lhs, operator, rhs });
+            Expression::new(ExpressionKind::Infix(infix), span)
+        })
+        .then(message)
+        .map(|(expr, message)| ConstrainStatement(expr, message, ConstrainKind::AssertEq));
+
+    let assert_eq = just(Token::Ident("assert_eq".to_owned()))
+        .then_ignore(just(Token::LeftParen).rewind())
+        .labelled(ParsingRuleLabel::Statement);
+
+    ignore_then_commit(assert_eq, parenthesized(arguments))
+        .labelled(ParsingRuleLabel::Statement)
+        .map(Statement::Constrain)
 }
 
 fn declaration<'a, P>(expr_parser: P) -> impl NoirParser<Statement> + 'a
@@ -777,9 +1211,10 @@ where
     P: ExprParser + 'a,
 {
     ignore_then_commit(keyword(Keyword::Return), expr_parser.or_not())
-        .validate(|_, span, emit| {
-            emit(ParserError::with_reason(ParserErrorReason::EarlyReturn, span));
-            Statement::Error
+        .map_with_span(|expr, span| {
+            // `return;` returns the unit value
+            let expr = expr.unwrap_or_else(|| Expression::new(ExpressionKind::Tuple(vec![]), span));
+            Statement::Return(expr)
         })
         .labelled(ParsingRuleLabel::Statement)
 }
@@ -943,6 +1378,43 @@ where
     })
 }
 
+/// match_expr: 'match' expression '{' (match_pattern '=>' expression ','?)* '}'
+fn match_expr<'a, P>(expr_parser: P) -> impl NoirParser<ExpressionKind> + 'a
+where
+    P: ExprParser + 'a,
+{
+    let rule = match_pattern()
+        .then_ignore(just(Token::FatArrow))
+        .then(expr_parser.clone())
+        .then_ignore(just(Token::Comma).or_not());
+
+    keyword(Keyword::Match)
+        .ignore_then(expr_parser)
+        .then(rule.repeated().delimited_by(just(Token::LeftBrace), just(Token::RightBrace)))
+        .map(|(expression, rules)| {
+            ExpressionKind::Match(Box::new(MatchExpression { expression, rules }))
+        })
+}
+
+/// match_pattern: '_'
+///              | path ( '(' pattern, ... ')' )?
+fn match_pattern() -> impl NoirParser<MatchPattern> {
+    let wildcard = just(Token::Underscore).map_with_span(|_, span| MatchPattern::Wildcard(span));
+
+    let fields = pattern()
+        .separated_by(just(Token::Comma))
+        .allow_trailing()
+        .delimited_by(just(Token::LeftParen), just(Token::RightParen))
+        .or_not()
+        .map(Option::unwrap_or_default);
+
+    let variant = path()
+        .then(fields)
+        .map_with_span(|(path, fields), span| MatchPattern::Variant(path, fields, span));
+
+    wildcard.or(variant).labelled(ParsingRuleLabel::Pattern)
+}
+
 fn lambda<'a>(
     expr_parser: impl NoirParser<Expression> + 'a,
 ) -> impl NoirParser<ExpressionKind> + 'a {
@@ -960,11 +1432,34 @@ where
     P: ExprParser + 'a,
 {
     keyword(Keyword::For)
-        .ignore_then(ident())
+        .ignore_then(pattern())
         .then_ignore(keyword(Keyword::In))
         .then(for_range(expr_parser.clone()))
         .then(block_expr(expr_parser))
-        .map_with_span(|((identifier, range), block), span| range.into_for(identifier, block, span))
+        .map_with_span(|((pattern, range), block), span| range.into_for(pattern, block, span))
+}
+
+fn while_expr<'a, P>(expr_parser: P) -> impl NoirParser<ExpressionKind> + 'a
+where
+    P: ExprParser + 'a,
+{
+    keyword(Keyword::While)
+        .ignore_then(expr_parser.clone())
+        .then(block_expr(expr_parser))
+        .map(|(condition, block)| WhileExpression { condition, block })
+        .map(|while_loop| ExpressionKind::While(Box::new(while_loop)))
+}
+
+/// `loop { .. }` is sugar for `while true { .. }`
+fn loop_expr<'a, P>(expr_parser: P) -> impl NoirParser<ExpressionKind> + 'a
+where
+    P: ExprParser + 'a,
+{
+    keyword(Keyword::Loop)
+        .map_with_span(|_, span| Expression::new(ExpressionKind::boolean(true), span))
+        .then(block_expr(expr_parser))
+        .map(|(condition, block)| WhileExpression { condition, block })
+        .map(|while_loop| ExpressionKind::While(Box::new(while_loop)))
 }
 
 /// The 'range' of a for loop. Either an actual range `start .. end` or an array expression.
@@ -1057,7 +1552,10 @@ where
 {
     choice((
         if_expr(expr_parser.clone()),
+        match_expr(expr_parser.clone()),
         for_expr(expr_parser.clone()),
+        while_expr(expr_parser.clone()),
+        loop_expr(expr_parser.clone()),
         array_expr(expr_parser.clone()),
         constructor(expr_parser.clone()),
         lambda(expr_parser.clone()),
@@ -1130,14 +1628,11 @@ fn literal() -> impl NoirParser<ExpressionKind> {
         Token::Int(x) => ExpressionKind::integer(x),
         Token::Bool(b) => ExpressionKind::boolean(b),
         Token::Str(s) => ExpressionKind::string(s),
+        Token::FmtStr(s) => ExpressionKind::format_string(s),
         unexpected => unreachable!("Non-literal {} parsed as a literal", unexpected),
     })
 }
 
-fn literal_or_collection(expr_parser: impl ExprParser) -> impl NoirParser<ExpressionKind> {
-    choice((literal(), constructor(expr_parser.clone()), array_expr(expr_parser)))
-}
-
 #[cfg(test)]
 mod test {
     use noirc_errors::CustomDiagnostic;
@@ -1377,6 +1872,8 @@ mod test {
     #[test]
     fn parse_assert() {
         parse_with(assertion(expression()), "assert(x == y)").unwrap();
+        parse_with(assertion(expression()), "assert(x == y, \"x is not y\")").unwrap();
+        parse_with(assertion(expression()), "assert(x == y, f\"{x} is not {y}\")").unwrap();
 
         // Currently we disallow constrain statements where the outer infix operator
         // produces a value. This would require an implicit `==` which
@@ -1415,6 +1912,32 @@ mod test {
         );
     }
 
+    #[test]
+    fn parse_assert_eq() {
+        let statement = parse_with(assertion_eq(expression()), "assert_eq(x + 1, y)").unwrap();
+        match statement {
+            Statement::Constrain(ConstrainStatement(expr, None, ConstrainKind::AssertEq)) => {
+                match expr.kind {
+                    ExpressionKind::Infix(infix) => {
+                        assert_eq!(infix.operator.contents, BinaryOpKind::Equal);
+                    }
+                    _ => unreachable!("assert_eq should be parsed as an equality"),
+                }
+            }
+            _ => unreachable!("assert_eq should be parsed as a constrain statement"),
+        }
+
+        parse_with(assertion_eq(expression()), "assert_eq(x, y, \"x is not y\")").unwrap();
+        parse_with(assertion_eq(expression()), "assert_eq(x)").unwrap_err();
+
+        // `assert_eq` may name variables and functions other than in assertions
+        parse_all(
+            statement(expression()),
+            vec!["let assert_eq = 1", "assert_eq = x", "let y = assert_eq(x, y)"],
+        );
+        parse_with(function_definition(false), "fn assert_eq(x: Field, y: Field) {}").unwrap();
+    }
+
     #[test]
     fn parse_let() {
         // Why is it valid to specify a let declaration as having type u8?
@@ -1425,6 +1948,21 @@ mod test {
         parse_all(declaration(expression()), vec!["let x = y", "let x : u8 = y"]);
     }
 
+    #[test]
+    fn parse_global() {
+        let cases = vec![
+            "global N = 3",
+            "global N: u32 = 1_000",
+            "global M = N * 2 + 1",
+            "global P = dep::lib::square(N)",
+            "global C: Config = Config { size: N, enabled: true }",
+            "global B = if N > 2 { [1, 2] } else { [3, 4] }",
+        ];
+        parse_all(global_declaration(), cases);
+
+        parse_all_failing(global_declaration(), vec!["global N", "global N =", "global = 3"]);
+    }
+
     #[test]
     fn parse_invalid_pub() {
         // pub cannot be used to declare a statement
@@ -1435,7 +1973,13 @@ mod test {
     fn parse_for_loop() {
         parse_all(
             for_expr(expression()),
-            vec!["for i in x+y..z {}", "for i in 0..100 { foo; bar }"],
+            vec![
+                "for i in x+y..z {}",
+                "for i in 0..100 { foo; bar }",
+                "for (a, b) in pairs { foo; bar }",
+                "for Point { x, y: z } in points {}",
+                "for mut i in 0..100 {}",
+            ],
         );
 
         parse_all_failing(
@@ -1448,6 +1992,24 @@ mod test {
         );
     }
 
+    #[test]
+    fn parse_while_loop() {
+        parse_all(
+            while_expr(expression()),
+            vec!["while x < 10 {}", "while true { foo; bar }", "while (x == y) { x = x + 1; }"],
+        );
+        parse_all(loop_expr(expression()), vec!["loop {}", "loop { foo; bar }"]);
+
+        parse_all_failing(
+            while_expr(expression()),
+            vec![
+                "while {}",      // The condition is required
+                "while x < 10;", // The body must be a block
+            ],
+        );
+        parse_all_failing(loop_expr(expression()), vec!["loop x {}"]);
+    }
+
     #[test]
     fn parse_function() {
         parse_all(
@@ -1459,12 +2021,35 @@ mod test {
                 "fn func_name(f: Field, y : pub Field, z : pub [u8;5],) {}",
                 "fn func_name(x: [Field], y : [Field;2],y : pub [Field;2], z : pub [u8;5])  {}",
                 "fn main(x: pub u8, y: pub u8) -> distinct pub [u8; 2] { [x, y] }",
+                "fn f<T: Hasher>(x: T) -> Field { x.hash() }",
+                "fn f<T, U: Hasher + std::Eq, N>(x: T, y: [U; N]) {}",
+                "fn f<T>(x: T) -> Field where T: Hasher { x.hash() }",
+                "fn f<T, U>(x: T, y: U) where T: Eq + std::Hasher, U: Eq, {}",
+                "#[deprecated] fn f() {}",
+                "#[deprecated(\"use g instead\")] pub fn f() {}",
             ],
         );
 
+        let function = parse_with(function_definition(false), "fn f<T: A + B, U>() {}").unwrap();
+        assert_eq!(function.def.generics.len(), 2);
+        let constraints = vecmap(&function.def.trait_constraints, ToString::to_string);
+        assert_eq!(constraints, vec!["T: A", "T: B"]);
+
+        let source = "fn f<T: A, U>() where U: B + C, T: D {}";
+        let function = parse_with(function_definition(false), source).unwrap();
+        let constraints = vecmap(&function.def.trait_constraints, ToString::to_string);
+        assert_eq!(constraints, vec!["T: A", "U: B", "U: C", "T: D"]);
+
         parse_all_failing(
             function_definition(false),
-            vec!["fn x2( f: []Field,,) {}", "fn ( f: []Field) {}", "fn ( f: []Field) {}"],
+            vec![
+                "fn x2( f: []Field,,) {}",
+                "fn ( f: []Field) {}",
+                "fn ( f: []Field) {}",
+                "fn f<T>(x: T) where {}",
+                "fn f<T>(x: T) where T {}",
+                "#[derive(Eq)] fn f() {}",
+            ],
         );
     }
 
@@ -1519,6 +2104,15 @@ mod test {
         };
     }
 
+    #[test]
+    fn parse_format_string() {
+        let expr = parse_with(literal(), r#"f"x = {x}""#).unwrap();
+        match expr_to_lit(expr) {
+            Literal::FmtStr(s) => assert_eq!(s, "x = {x}"),
+            _ => unreachable!(),
+        };
+    }
+
     #[test]
     fn parse_bool() {
         let expr_true = parse_with(literal(), "true").unwrap();
@@ -1539,6 +2133,155 @@ mod test {
         parse_with(module_declaration(), "mod 1").unwrap_err();
     }
 
+    #[test]
+    fn parse_doc_comments() {
+        let src = r#"
+            /// A module
+            mod foo;
+
+            /// A global
+            /// over two lines
+            global X = 1;
+
+            /// A struct
+            #[derive(Eq)]
+            struct Foo {
+                /// Field comments are not retained
+                x: Field,
+            }
+
+            impl Foo {
+                /// A method
+                fn new() -> Self {
+                    /// Statement comments are not retained
+                    Foo { x: X }
+                }
+            }
+
+            trait Bar {
+                /// A trait method
+                fn bar(self) -> Field;
+            }
+
+            #[test]
+            /// A comment after an attribute is not retained
+            fn main() {}
+            ///
+        "#;
+
+        let (module, errors) = parse_program(src);
+        assert!(errors.is_empty(), "{errors:?}");
+
+        assert_eq!(module.module_decls[0].1.doc_comments, vec!["A module"]);
+        assert_eq!(module.globals[0].1.doc_comments, vec!["A global", "over two lines"]);
+        assert_eq!(module.types[0].doc_comments, vec!["A struct"]);
+        assert_eq!(module.impls[0].methods[0].def.doc_comments, vec!["A method"]);
+        assert!(module.functions[0].def.doc_comments.is_empty());
+    }
+
+    #[test]
+    fn parse_cfg_attributes() {
+        let src = r#"
+            #[cfg(feature = "poseidon")]
+            fn hash(x: Field) -> Field { x }
+
+            #[cfg(feature = "poseidon")]
+            #[cfg(feature = "fast")]
+            #[derive(Eq)]
+            pub struct Foo { }
+
+            fn main() {}
+        "#;
+
+        let (mut module, errors) = parse_program(src);
+        assert!(errors.is_empty());
+        assert_eq!(module.functions.len(), 1);
+        assert_eq!(module.conditional_items.len(), 2);
+
+        module.enable_features(|feature| feature == "poseidon");
+        assert_eq!(module.functions.len(), 2);
+        assert!(module.types.is_empty());
+        assert!(module.conditional_items.is_empty());
+
+        parse_all_failing(
+            implementation(),
+            vec![r#"impl Foo { #[cfg(feature = "fast")] fn f() {} }"#],
+        );
+        parse_all_failing(
+            struct_definition(),
+            vec![r#"#[derive(Eq)] #[cfg(feature = "fast")] struct Foo { }"#],
+        );
+    }
+
+    #[test]
+    fn parse_allow_unused_attributes() {
+        let src = "
+            #[allow(unused)]
+            use dep::std::hash;
+            use dep::std::println;
+
+            #[allow(unused)]
+            fn helper() {}
+
+            #[test]
+            #[allow(unused)]
+            fn test_helper() {}
+
+            fn main() {}
+        ";
+        let (module, errors) = parse_program(src);
+        assert!(errors.is_empty(), "{errors:?}");
+
+        let allowed = vecmap(&module.imports, |import| import.allow_unused);
+        assert_eq!(allowed, vec![true, false]);
+
+        let allowed = vecmap(&module.functions, |function| function.def.allow_unused);
+        assert_eq!(allowed, vec![true, true, false]);
+        assert_eq!(module.functions[1].def.attribute, Some(Attribute::Test));
+    }
+
+    #[test]
+    fn parse_item_visibility() {
+        let cases = vec![
+            ("fn f() {}", ItemVisibility::Private),
+            ("pub fn f() {}", ItemVisibility::Public),
+            ("pub(crate) fn f() {}", ItemVisibility::PublicCrate),
+            ("#[test] pub unconstrained fn f() {}", ItemVisibility::Public),
+        ];
+
+        for (src, expected) in cases {
+            let function = parse_with(function_definition(false), src).unwrap();
+            assert_eq!(function.def.visibility, expected);
+        }
+
+        parse_all(
+            top_level_statement(module()),
+            vec![
+                "pub mod foo;",
+                "pub(crate) mod foo { }",
+                "pub struct Foo { }",
+                "pub(crate) global N = 3;",
+                "pub type Hash = [u8; 32];",
+                "pub trait Empty { }",
+                "pub use foo::bar;",
+                "pub(crate) use foo::{bar, baz};",
+            ],
+        );
+
+        let (module, errors) = parse_program("use foo::bar; pub use foo::{baz, qux};");
+        assert!(errors.is_empty(), "{errors:?}");
+        let visibilities = vecmap(&module.imports, |import| import.visibility);
+        assert_eq!(
+            visibilities,
+            vec![ItemVisibility::Private, ItemVisibility::Public, ItemVisibility::Public]
+        );
+
+        parse_all_failing(
+            function_definition(false),
+            vec!["pub() fn f() {}", "pub(self) fn f() {}", "fn pub f() {}"],
+        );
+    }
+
     #[test]
     fn parse_path() {
         let cases = vec![
@@ -1621,13 +2364,96 @@ mod test {
             "struct Foo { }",
             "struct Bar { ident: Field, }",
             "struct Baz { ident: Field, other: Field }",
+            "#[derive(Eq)] struct Foo { }",
+            "#[derive(Eq, Default)] pub struct Bar { ident: Field }",
+            "#[derive(Eq)] #[derive(Default)] struct Foo { }",
+            "#[deprecated] struct Foo { }",
+            "#[derive(Eq)] #[deprecated(\"use Bar instead\")] struct Foo { }",
         ];
         parse_all(struct_definition(), cases);
 
-        let failing = vec!["struct {  }", "struct Foo { bar: pub Field }"];
+        let failing = vec![
+            "struct {  }",
+            "struct Foo { bar: pub Field }",
+            "#[test] struct Foo { }",
+            "#[derive(Eq)] #[oracle(foo)] struct Foo { }",
+        ];
         parse_all_failing(struct_definition(), failing);
     }
 
+    #[test]
+    fn parse_traits() {
+        let cases = vec![
+            "trait Empty { }",
+            "trait Hasher { fn hash(self) -> Field; }",
+            "trait Serialize { fn serialize(self) -> [Field; 2]; fn size() -> u32; }",
+            "trait Counter { fn increment(&mut self, amount: u32); }",
+        ];
+        parse_all(trait_definition(), cases);
+
+        let failing = vec![
+            "trait { }",
+            "trait Hasher { fn hash(self) -> Field }",
+            "trait Hasher { fn hash(self) -> Field { 0 } }",
+        ];
+        parse_all_failing(trait_definition(), failing);
+    }
+
+    #[test]
+    fn parse_trait_impls() {
+        let cases = vec![
+            "impl Empty for Foo { }",
+            "impl Hasher for Field { fn hash(self) -> Field { self } }",
+            "impl<T> std::Hasher for Foo<T> { fn hash(self) -> Field { 0 } }",
+        ];
+        parse_all(trait_implementation(), cases);
+
+        let failing = vec!["impl Foo { }", "impl Hasher for { }", "impl for Foo { }"];
+        parse_all_failing(trait_implementation(), failing);
+    }
+
+    #[test]
+    fn parse_enums() {
+        let cases = vec![
+            "enum Empty { }",
+            "enum State { Idle, Active(Field, u32), Done(bool,), }",
+            "enum Option<T> { None, Some(T) }",
+        ];
+        parse_all(enum_definition(), cases);
+
+        let failing =
+            vec!["enum { }", "enum State { Active(x: Field) }", "enum State { Idle = 0 }"];
+        parse_all_failing(enum_definition(), failing);
+    }
+
+    #[test]
+    fn parse_type_aliases() {
+        let cases = vec![
+            "type Hash = [u8; 32]",
+            "type Matrix<N> = [[Field; N]; N]",
+            "type Pair<A, B> = (A, B)",
+            "type Point3 = Point<Field, 3>",
+        ];
+        parse_all(type_alias_definition(), cases);
+
+        let failing = vec!["type = Field", "type Hash [u8; 32]", "type Hash ="];
+        parse_all_failing(type_alias_definition(), failing);
+    }
+
+    #[test]
+    fn parse_match_expr() {
+        let cases = vec![
+            "match x { }",
+            "match x { State::Idle => 0, State::Active(a, b) => a + b }",
+            "match f(x) { Option::Some((a, mut b)) => { b = a; b } _ => 2, }",
+        ];
+        parse_all(match_expr(expression()), cases);
+
+        let failing =
+            vec!["match x { State::Idle -> 0 }", "match { _ => 1 }", "match x { a + b => 1 }"];
+        parse_all_failing(match_expr(expression()), failing);
+    }
+
     #[test]
     fn parse_member_access() {
         let cases = vec!["a.b", "a + b.c", "foo.bar as i32"];
@@ -1710,15 +2536,15 @@ mod test {
     #[test]
     fn return_validation() {
         let cases = vec![
-            ("{ return 42; }", 1, "{\n    Error\n}"),
-            ("{ return 1; return 2; }", 2, "{\n    Error\n    Error\n}"),
+            ("{ return 42; }", 0, "{\n    return 42\n}"),
+            ("{ return 1; return 2; }", 0, "{\n    return 1\n    return 2\n}"),
             (
                 "{ return 123; let foo = 4 + 3; }",
-                1,
-                "{\n    Error\n    let foo: unspecified = (4 + 3)\n}",
+                0,
+                "{\n    return 123\n    let foo: unspecified = (4 + 3)\n}",
             ),
-            ("{ return 1 + 2 }", 2, "{\n    Error\n}"),
-            ("{ return; }", 1, "{\n    Error\n}"),
+            ("{ return 1 + 2 }", 0, "{\n    return (1 + 2)\n}"),
+            ("{ return; }", 0, "{\n    return ()\n}"),
         ];
 
         let show_errors = |v| vecmap(&v, ToString::to_string).join("\n");